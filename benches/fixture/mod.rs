@@ -0,0 +1,140 @@
+//! synthetic target and scaffolding shared by the benchmarks
+//!
+//! the target is a local http server, so that benchmarks that make requests measure feroxbuster
+//! rather than the network
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+use feroxbuster::{
+    config::{Configuration, OutputLevel},
+    event_handlers::{Command, FiltersHandle, Handles, StatsHandle, TermOutHandle},
+    filters::FeroxFilters,
+    response::FeroxResponse,
+    statistics::Stats,
+    utils::make_request,
+};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use reqwest::{Client, Method, Url};
+use tokio::{runtime::Runtime, sync::mpsc};
+
+/// paths beginning with this prefix are answered with a 404, everything else with a 200
+pub const MISSING_PREFIX: &str = "/missing";
+
+/// line of filler text used to build response bodies
+const FILLER: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod.";
+
+/// body of the synthetic target's response to the given path; its size grows with the length of
+/// the path, so that a range of response sizes can be produced on demand
+pub fn body_for(path: &str) -> String {
+    let mut body = format!("<html><head><title>{}</title></head><body>\n", path);
+
+    for _ in 0..path.len() * 4 {
+        body.push_str(FILLER);
+        body.push('\n');
+    }
+
+    body.push_str("</body></html>");
+    body
+}
+
+/// single request handler of the synthetic target
+async fn respond(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let path = request.uri().path();
+
+    let status = if path.starts_with(MISSING_PREFIX) {
+        404
+    } else {
+        200
+    };
+
+    Ok(Response::builder()
+        .status(status)
+        .header("Content-Type", "text/html")
+        .body(Body::from(body_for(path)))
+        .unwrap())
+}
+
+/// Local http server that's used as a scan target; it runs until its runtime is dropped
+pub struct Target {
+    /// runtime on which the server runs, also used to drive requests made against it
+    pub runtime: Runtime,
+
+    /// address on which the server is listening
+    pub addr: SocketAddr,
+}
+
+/// implementation of Target
+impl Target {
+    /// start the server on an unused port
+    pub fn start() -> Self {
+        let runtime = Runtime::new().unwrap();
+
+        let addr = runtime.block_on(async {
+            let make_service =
+                make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(respond)) });
+
+            let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+            let addr = server.local_addr();
+
+            tokio::spawn(server);
+            addr
+        });
+
+        Self { runtime, addr }
+    }
+
+    /// base url of the server, i.e. http://127.0.0.1:41337/
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    /// request each of the given paths, returning the responses with their bodies read
+    pub fn responses(&self, paths: &[String]) -> Vec<FeroxResponse> {
+        let client = Client::new();
+        let (tx, _) = mpsc::unbounded_channel::<Command>();
+
+        self.runtime.block_on(async {
+            let mut responses = Vec::with_capacity(paths.len());
+
+            for path in paths {
+                let url = Url::parse(&self.url()).unwrap().join(path).unwrap();
+                let response = make_request(
+                    &client,
+                    None,
+                    Method::GET,
+                    &url,
+                    OutputLevel::Default,
+                    tx.clone(),
+                )
+                .await
+                .unwrap();
+
+                responses.push(FeroxResponse::from(response, true, OutputLevel::Default).await);
+            }
+
+            responses
+        })
+    }
+}
+
+/// handles built from the given configuration, without any of the handlers running; commands sent
+/// to them are dropped
+pub fn handles(config: Configuration) -> Arc<Handles> {
+    let (tx, _) = mpsc::unbounded_channel::<Command>();
+
+    let stats = StatsHandle::new(
+        Arc::new(Stats::new(config.extensions.len(), config.json)),
+        tx.clone(),
+    );
+    let filters = FiltersHandle::new(Arc::new(FeroxFilters::default()), tx.clone());
+    let output = TermOutHandle::new(tx.clone(), tx);
+
+    Arc::new(Handles::new(stats, filters, output, Arc::new(config)))
+}
+
+/// `count` distinct words, i.e. word0, word1, ...
+pub fn words(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("word{}", i)).collect()
+}
@@ -0,0 +1,280 @@
+//! benchmarks of the code run for every word of every scan: url formatting, filter evaluation,
+//! response and scan bookkeeping, and requests themselves
+//!
+//! cargo bench --bench hot_path
+mod fixture;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use feroxbuster::{
+    config::{Configuration, OutputLevel},
+    event_handlers::Command,
+    filters::{FeroxFilters, LinesFilter, RegexFilter, SizeFilter, StatusCodeFilter, WordsFilter},
+    response::FeroxResponse,
+    scan_manager::{FeroxResponses, FeroxScans, ScanOrder},
+    url::FeroxUrl,
+    utils::make_request,
+};
+use regex::Regex;
+use reqwest::{Client, Method, Url};
+use std::thread;
+use tokio::sync::mpsc;
+
+use fixture::{handles, words, Target, MISSING_PREFIX};
+
+/// number of responses used by the bookkeeping benchmarks
+const NUM_RESPONSES: usize = 10_000;
+
+/// number of threads inserting responses concurrently, same as a scan with -t 200
+const NUM_THREADS: usize = 200;
+
+/// formatting of each word into its urls, with and without extensions
+fn url_formatting(c: &mut Criterion) {
+    let words = words(100);
+
+    let mut group = c.benchmark_group("url formatting");
+    group.throughput(Throughput::Elements(words.len() as u64));
+
+    let plain = FeroxUrl::from_string("http://localhost/", handles(Configuration::default()));
+
+    group.bench_function("no extensions", |b| {
+        b.iter(|| {
+            for word in &words {
+                black_box(plain.formatted_urls(word).unwrap());
+            }
+        })
+    });
+
+    let config = Configuration {
+        extensions: vec!["php".into(), "html".into(), "txt".into()],
+        queries: vec![("token".into(), "abc123".into())],
+        ..Default::default()
+    };
+    let extended = FeroxUrl::from_string("http://localhost/api", handles(config));
+
+    group.bench_function("3 extensions + query", |b| {
+        b.iter(|| {
+            for word in &words {
+                black_box(extended.formatted_urls(word).unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+/// evaluation of a typical set of filters against responses that none of them filter, so that
+/// every filter is evaluated
+fn filter_evaluation(c: &mut Criterion) {
+    let target = Target::start();
+    let responses = target.responses(&words(100));
+    let (tx, _) = mpsc::unbounded_channel::<Command>();
+
+    let filters = FeroxFilters::default();
+    filters
+        .push(Box::new(StatusCodeFilter { filter_code: 404 }))
+        .unwrap();
+    filters
+        .push(Box::new(SizeFilter { content_length: 1 }))
+        .unwrap();
+    filters
+        .push(Box::new(WordsFilter { word_count: 1 }))
+        .unwrap();
+    filters
+        .push(Box::new(LinesFilter { line_count: 1 }))
+        .unwrap();
+
+    let raw_string = String::from(r"(?i)access denied|not authorized");
+    filters
+        .push(Box::new(RegexFilter {
+            compiled: Regex::new(&raw_string).unwrap(),
+            raw_string,
+        }))
+        .unwrap();
+
+    let mut group = c.benchmark_group("filter evaluation");
+    group.throughput(Throughput::Elements(responses.len() as u64));
+
+    group.bench_function("status, size, words, lines, regex", |b| {
+        b.iter(|| {
+            for response in &responses {
+                black_box(filters.should_filter_response(response, tx.clone()));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+/// insertion of responses into, and lookups against, the responses seen so far
+fn response_bookkeeping(c: &mut Criterion) {
+    let target = Target::start();
+
+    // every response gets a distinct url; the body is the same for all of them
+    let template = target.responses(&[String::from("template")]).remove(0);
+    let responses: Vec<FeroxResponse> = (0..NUM_RESPONSES)
+        .map(|i| {
+            let json = serde_json::to_string(&template)
+                .unwrap()
+                .replace("/template", &format!("/word{}", i));
+            serde_json::from_str(&json).unwrap()
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("response bookkeeping");
+    group.throughput(Throughput::Elements(NUM_RESPONSES as u64));
+
+    group.bench_function("insert", |b| {
+        b.iter_batched(
+            || (FeroxResponses::default(), responses.clone()),
+            |(container, responses)| {
+                for response in responses {
+                    container.insert(response);
+                }
+                container
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    // responses per second stored by as many threads as a -t 200 scan would use
+    let chunk_size = NUM_RESPONSES / NUM_THREADS;
+
+    group.bench_function(format!("insert ({} threads)", NUM_THREADS), |b| {
+        b.iter_batched(
+            || {
+                let chunks: Vec<Vec<FeroxResponse>> = responses
+                    .chunks(chunk_size)
+                    .map(|chunk| chunk.to_vec())
+                    .collect();
+                (FeroxResponses::default(), chunks)
+            },
+            |(container, chunks)| {
+                thread::scope(|scope| {
+                    for chunk in chunks {
+                        let container = &container;
+                        scope.spawn(move || {
+                            for response in chunk {
+                                container.insert(response);
+                            }
+                        });
+                    }
+                });
+
+                container
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    let container = FeroxResponses::default();
+
+    for response in responses.iter().step_by(2) {
+        container.insert(response.clone());
+    }
+
+    group.bench_function("contains (half present)", |b| {
+        b.iter(|| {
+            for response in &responses {
+                black_box(container.contains(response));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+/// insertion of scans into, and lookups by url against, the scans known so far
+fn scan_bookkeeping(c: &mut Criterion) {
+    let urls: Vec<String> = (0..NUM_RESPONSES)
+        .map(|i| format!("http://localhost/word{}/", i))
+        .collect();
+
+    let mut group = c.benchmark_group("scan bookkeeping");
+    group.throughput(Throughput::Elements(NUM_RESPONSES as u64));
+
+    // file scans don't get a progress bar, so only the bookkeeping itself is measured
+    group.bench_function("insert", |b| {
+        b.iter_batched(
+            || FeroxScans::new(OutputLevel::Silent),
+            |scans| {
+                for url in &urls {
+                    scans.add_file_scan(url, ScanOrder::Latest);
+                }
+                scans
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    let scans = FeroxScans::new(OutputLevel::Silent);
+
+    for url in urls.iter().step_by(2) {
+        scans.add_file_scan(url, ScanOrder::Latest);
+    }
+
+    group.bench_function("contains (half present)", |b| {
+        b.iter(|| {
+            for url in &urls {
+                black_box(scans.contains(url));
+            }
+        })
+    });
+
+    group.bench_function("get_scan_by_url (half present)", |b| {
+        b.iter(|| {
+            for url in &urls {
+                black_box(scans.get_scan_by_url(url));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+/// a single request made against the synthetic target and turned into a `FeroxResponse`
+fn request(c: &mut Criterion) {
+    let target = Target::start();
+    let client = Client::new();
+    let (tx, _) = mpsc::unbounded_channel::<Command>();
+
+    let found = Url::parse(&target.url()).unwrap().join("admin").unwrap();
+    let missing = Url::parse(&target.url())
+        .unwrap()
+        .join(&MISSING_PREFIX[1..])
+        .unwrap();
+
+    let mut group = c.benchmark_group("request");
+
+    for (name, url) in &[("200", found), ("404", missing)] {
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                target.runtime.block_on(async {
+                    let response = make_request(
+                        &client,
+                        None,
+                        Method::GET,
+                        url,
+                        OutputLevel::Default,
+                        tx.clone(),
+                    )
+                    .await
+                    .unwrap();
+
+                    black_box(FeroxResponse::from(response, true, OutputLevel::Default).await)
+                })
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    url_formatting,
+    filter_evaluation,
+    response_bookkeeping,
+    scan_bookkeeping,
+    request
+);
+criterion_main!(benches);
@@ -7,6 +7,10 @@ include!("src/parser.rs");
 fn main() {
     println!("cargo:rerun-if-env-changed=src/parser.rs");
 
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/feroxbuster.proto")
+        .expect("Could not compile proto/feroxbuster.proto");
+
     if std::env::var("DOCS_RS").is_ok() {
         return; // only build when we're not generating docs
     }
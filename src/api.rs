@@ -0,0 +1,374 @@
+//! local http api used to control a running scan (--api-addr)
+//!
+//! the api has no authentication, so it only binds to loopback addresses
+//!
+//! - `GET /scans`: list all known scans
+//! - `POST /pause`: pause all scans
+//! - `POST /resume`: resume all scans
+//! - `DELETE /scans/{id}`: cancel the scan with the given id
+//! - `POST /scans/{id}/pause`: pause only the scan with the given id
+//! - `POST /scans/{id}/resume`: resume the scan with the given id
+//! - `PUT /rate-limit`: set the requests per second limit of all scans (body is the new limit,
+//!   0 removes the limit)
+//! - `GET /results`: stream reported responses as server-sent events
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Once},
+};
+
+use anyhow::{bail, Context, Result};
+use futures::stream;
+use hyper::{
+    body::to_bytes,
+    header::{HeaderValue, CONTENT_TYPE},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use lazy_static::lazy_static;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{
+    event_handlers::{Command::SubtractFromUsizeField, Handles},
+    events::{ScanEvent, Subscriber, EVENTS},
+    response::FeroxResponse,
+    scanner::set_rate_limit,
+    statistics::StatField::TotalExpected,
+    traits::FeroxSerialize,
+};
+
+/// number of reported responses buffered for each /results subscriber
+const RESULTS_BUFFER: usize = 1024;
+
+lazy_static! {
+    /// reported responses are published here for any /results (or --grpc) subscribers
+    static ref RESULTS: broadcast::Sender<FeroxResponse> = broadcast::channel(RESULTS_BUFFER).0;
+}
+
+/// guards the one-time subscription of ResultsPublisher to the event bus
+static PUBLISHER: Once = Once::new();
+
+/// copies reported responses from the event bus onto the /results (and --grpc) channel
+struct ResultsPublisher;
+
+/// implementation of Subscriber for ResultsPublisher
+impl Subscriber for ResultsPublisher {
+    /// publish reported responses to any /results (or --grpc) subscribers
+    fn on_event(&self, event: &ScanEvent) {
+        if let ScanEvent::ResponseReported(response) = event {
+            if RESULTS.receiver_count() == 0 {
+                // no one's listening, skip the clone
+                return;
+            }
+
+            let mut response = FeroxResponse::clone(response);
+            response.drop_text(); // subscribers never see the body, no sense in holding onto it
+
+            RESULTS.send(response).unwrap_or_default();
+        }
+    }
+}
+
+/// subscribe to all responses reported from here on out
+pub fn subscribe() -> broadcast::Receiver<FeroxResponse> {
+    // responses are only copied onto the channel once someone has asked for them
+    PUBLISHER.call_once(|| EVENTS.subscribe(Arc::new(ResultsPublisher)).keep());
+
+    RESULTS.subscribe()
+}
+
+/// Bind to the given address and serve the control api until the process exits
+pub fn initialize(addr: &str, handles: Arc<Handles>) -> Result<()> {
+    log::trace!("enter: initialize({}, {:?})", addr, handles);
+
+    let addr: SocketAddr = addr
+        .parse()
+        .with_context(|| format!("Could not parse {} as a socket address", addr))?;
+
+    if !addr.ip().is_loopback() {
+        // anyone who can reach the api can cancel scans and read results
+        bail!(
+            "The api has no authentication and can only bind to a loopback address, not {}",
+            addr
+        );
+    }
+
+    let builder =
+        Server::try_bind(&addr).with_context(|| format!("Could not bind api to {}", addr))?;
+
+    let make_service = make_service_fn(move |_| {
+        let handles = handles.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let handles = handles.clone();
+                async move { Ok::<_, Infallible>(route(request, handles).await) }
+            }))
+        }
+    });
+
+    tokio::spawn(async move {
+        if let Err(e) = builder.serve(make_service).await {
+            log::warn!("Control api encountered an error: {}", e);
+        }
+    });
+
+    log::info!("Serving control api from http://{}", addr);
+    log::trace!("exit: initialize");
+    Ok(())
+}
+
+/// build a response with the given status code and body
+fn respond(status: StatusCode, content_type: &'static str, body: Body) -> Response<Body> {
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    response
+}
+
+/// build a json response containing a single message
+fn message(status: StatusCode, msg: &str) -> Response<Body> {
+    let body = serde_json::json!({ "message": msg }).to_string();
+    respond(status, "application/json", Body::from(body))
+}
+
+/// dispatch a single request to its handler
+async fn route(request: Request<Body>, handles: Arc<Handles>) -> Response<Body> {
+    log::trace!("enter: route({:?})", request);
+
+    let path = request.uri().path().trim_end_matches('/').to_string();
+    let segments: Vec<&str> = path.split('/').skip(1).collect();
+
+    let response = match (request.method(), segments.as_slice()) {
+        (&Method::GET, ["scans"]) => list_scans(&handles),
+        (&Method::DELETE, ["scans", id]) => cancel_scan(id, &handles).await,
+        (&Method::POST, ["scans", id, "pause"]) => pause_scan(id, &handles, true),
+        (&Method::POST, ["scans", id, "resume"]) => pause_scan(id, &handles, false),
+        (&Method::POST, ["pause"]) => pause(&handles, true),
+        (&Method::POST, ["resume"]) => pause(&handles, false),
+        (&Method::PUT, ["rate-limit"]) => rate_limit(request).await,
+        (&Method::GET, ["results"]) => results(),
+        _ => message(StatusCode::NOT_FOUND, "not found"),
+    };
+
+    log::trace!("exit: route -> {:?}", response);
+    response
+}
+
+/// GET /scans
+fn list_scans(handles: &Handles) -> Response<Body> {
+    let scans = match handles.ferox_scans() {
+        Ok(scans) => scans,
+        Err(e) => return message(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+
+    match serde_json::to_string(&*scans) {
+        Ok(json) => respond(StatusCode::OK, "application/json", Body::from(json)),
+        Err(e) => message(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+/// DELETE /scans/{id}
+async fn cancel_scan(id: &str, handles: &Handles) -> Response<Body> {
+    let scans = match handles.ferox_scans() {
+        Ok(scans) => scans,
+        Err(e) => return message(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+
+    match scans.cancel_scan_by_id(id).await {
+        Some(num_cancelled) => {
+            handles
+                .stats
+                .send(SubtractFromUsizeField(TotalExpected, num_cancelled))
+                .unwrap_or_else(|e| log::warn!("Could not update overall scan bar: {}", e));
+
+            message(StatusCode::OK, &format!("cancelled {}", id))
+        }
+        None => message(StatusCode::NOT_FOUND, &format!("no scan with id {}", id)),
+    }
+}
+
+/// POST /scans/{id}/pause and POST /scans/{id}/resume
+fn pause_scan(id: &str, handles: &Handles, should_pause: bool) -> Response<Body> {
+    let scans = match handles.ferox_scans() {
+        Ok(scans) => scans,
+        Err(e) => return message(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+
+    if !scans.pause_scan_by_id(id, should_pause) {
+        return message(StatusCode::NOT_FOUND, &format!("no scan with id {}", id));
+    }
+
+    if should_pause {
+        message(StatusCode::OK, &format!("paused {}", id))
+    } else {
+        message(StatusCode::OK, &format!("resumed {}", id))
+    }
+}
+
+/// POST /pause and POST /resume
+fn pause(handles: &Handles, should_pause: bool) -> Response<Body> {
+    let scans = match handles.ferox_scans() {
+        Ok(scans) => scans,
+        Err(e) => return message(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+
+    if should_pause {
+        scans.pause_remotely();
+        message(StatusCode::OK, "paused")
+    } else {
+        scans.resume_remotely();
+        message(StatusCode::OK, "resumed")
+    }
+}
+
+/// PUT /rate-limit
+async fn rate_limit(request: Request<Body>) -> Response<Body> {
+    let body = match to_bytes(request.into_body()).await {
+        Ok(body) => body,
+        Err(e) => return message(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+
+    match String::from_utf8_lossy(&body).trim().parse::<usize>() {
+        Ok(limit) => {
+            set_rate_limit(limit);
+            message(StatusCode::OK, &format!("rate limit set to {}", limit))
+        }
+        Err(e) => message(StatusCode::BAD_REQUEST, &e.to_string()),
+    }
+}
+
+/// GET /results
+fn results() -> Response<Body> {
+    let events = stream::unfold(subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(response) => {
+                    let json = match response.as_json() {
+                        Ok(json) => json,
+                        Err(_) => continue,
+                    };
+                    let event = format!("data: {}\n\n", json.trim_end());
+                    return Some((Ok::<_, Infallible>(event), receiver));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("/results subscriber fell behind; skipped {}", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    respond(
+        StatusCode::OK,
+        "text/event-stream",
+        Body::wrap_stream(events),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan_manager::{FeroxScans, ScanOrder};
+
+    /// create Handles with a known scan for testing
+    fn setup() -> (Arc<Handles>, Arc<FeroxScans>) {
+        let scans = Arc::new(FeroxScans::default());
+        scans.add_directory_scan("http://localhost/", ScanOrder::Initial);
+        let (handles, _) = Handles::for_testing(Some(scans.clone()), None);
+        (Arc::new(handles), scans)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// the unauthenticated api should refuse to listen anywhere other than loopback
+    async fn api_only_binds_to_loopback() {
+        let (handles, _) = setup();
+
+        let result = initialize("0.0.0.0:0", handles.clone());
+        assert!(result.unwrap_err().to_string().contains("loopback"));
+
+        assert!(initialize("127.0.0.1:0", handles).is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// GET /scans should return the known scans as json
+    async fn api_lists_scans() {
+        let (handles, _) = setup();
+
+        let request = Request::get("/scans").body(Body::empty()).unwrap();
+        let response = route(request, handles).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("http://localhost/"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// unknown routes and scan ids should result in a 404
+    async fn api_returns_not_found() {
+        let (handles, _) = setup();
+
+        let request = Request::get("/nope").body(Body::empty()).unwrap();
+        assert_eq!(
+            route(request, handles.clone()).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let request = Request::delete("/scans/nope").body(Body::empty()).unwrap();
+        assert_eq!(
+            route(request, handles).await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// POST /scans/{id}/pause should pause only the given scan, resume should undo it
+    async fn api_pauses_single_scan() {
+        let (handles, scans) = setup();
+        let scan = scans.get_scan_by_url("http://localhost/").unwrap();
+        let path = format!("/scans/{}", scan.id());
+
+        let request = Request::post(format!("{}/pause", path))
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(
+            route(request, handles.clone()).await.status(),
+            StatusCode::OK
+        );
+        assert!(scan.is_paused());
+
+        let request = Request::post(format!("{}/resume", path))
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(
+            route(request, handles.clone()).await.status(),
+            StatusCode::OK
+        );
+        assert!(!scan.is_paused());
+
+        let request = Request::post("/scans/nope/pause")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(
+            route(request, handles).await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// PUT /rate-limit should reject anything that isn't a number
+    async fn api_rate_limit_requires_number() {
+        let (handles, _) = setup();
+
+        let request = Request::put("/rate-limit")
+            .body(Body::from("fast"))
+            .unwrap();
+        assert_eq!(
+            route(request, handles).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! --api; scanning tuned for REST backends
+//!
+//! requests ask for json, common version prefixes that exist are scanned along with each target,
+//! and json error bodies are told apart from real results; html-centric heuristics (word/line
+//! counts of a custom 404 page) don't help much when every response is a small json document
+use std::sync::Arc;
+
+use reqwest::{StatusCode, Url};
+use serde_json::Value;
+
+use crate::{
+    config::Configuration, event_handlers::Handles, response::FeroxResponse, scanner::RateGate,
+    utils::logged_request,
+};
+
+/// Accept header sent with every request, unless one was given with -H
+pub const ACCEPT: &str = "application/json";
+
+/// version prefixes scanned below each target, i.e. http://localhost/v1/
+pub const VERSION_PREFIXES: [&str; 2] = ["v1", "v2"];
+
+/// keys of a json object that describe an error
+const ERROR_KEYS: [&str; 11] = [
+    "error",
+    "errors",
+    "message",
+    "detail",
+    "title",
+    "msg",
+    "reason",
+    "status",
+    "statusCode",
+    "status_code",
+    "code",
+];
+
+/// phrases that mean the requested route doesn't exist
+const NOT_FOUND_PHRASES: [&str; 9] = [
+    "not found",
+    "not_found",
+    "notfound",
+    "no route",
+    "no such",
+    "does not exist",
+    "unknown endpoint",
+    "unknown route",
+    "no handler",
+];
+
+/// phrases that mean the requested route exists, but needs credentials
+const UNAUTHORIZED_PHRASES: [&str; 5] = [
+    "unauthorized",
+    "unauthenticated",
+    "forbidden",
+    "access denied",
+    "token",
+];
+
+/// phrases that mean the requested route exists, but not for this method
+const METHOD_PHRASES: [&str; 2] = ["method not allowed", "method_not_allowed"];
+
+/// What a json error body says about the route that was requested
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum JsonError {
+    /// the route doesn't exist; a soft 404
+    NotFound,
+
+    /// the route exists, but needs (other) credentials
+    Unauthorized,
+
+    /// the route exists, but not for the method used
+    MethodNotAllowed,
+
+    /// any other error
+    Other,
+}
+
+/// Update the given configuration for --api; asks for json unless an Accept header was given
+pub fn apply(config: &mut Configuration) {
+    if !config.api {
+        return;
+    }
+
+    if !config
+        .headers
+        .keys()
+        .any(|name| name.eq_ignore_ascii_case("accept"))
+    {
+        config
+            .headers
+            .insert(String::from("Accept"), String::from(ACCEPT));
+    }
+}
+
+/// The given targets, each followed by the same target below whichever of `VERSION_PREFIXES` exist
+/// there; prefixes are requested no faster than --rate-limit allows
+pub async fn with_version_prefixes(targets: Vec<String>, handles: Arc<Handles>) -> Vec<String> {
+    log::trace!("enter: with_version_prefixes({:?})", targets);
+
+    let gate = RateGate::with_limit(handles.config.rate_limit).unwrap_or_else(|e| {
+        log::warn!("Could not rate limit --api version prefixes: {}", e);
+        RateGate::default()
+    });
+
+    let mut expanded = Vec::new();
+
+    for target in targets {
+        let base = target.trim_end_matches('/').to_string();
+
+        expanded.push(target);
+
+        for prefix in VERSION_PREFIXES.iter() {
+            let versioned = format!("{}/{}/", base, prefix);
+
+            if !expanded.contains(&versioned)
+                && prefix_exists(&versioned, &gate, handles.clone()).await
+            {
+                expanded.push(versioned);
+            }
+        }
+    }
+
+    log::trace!("exit: with_version_prefixes -> {:?}", expanded);
+    expanded
+}
+
+/// Request the given version prefix, once the gate allows it, to see whether it exists
+async fn prefix_exists(url: &str, gate: &RateGate, handles: Arc<Handles>) -> bool {
+    let parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            log::warn!("Could not check --api version prefix {}: {}", url, e);
+            return false;
+        }
+    };
+
+    gate.wait().await;
+
+    let response = match logged_request(&parsed, handles.clone()).await {
+        Ok(response) => response,
+        Err(e) => {
+            log::debug!("Could not check --api version prefix {}: {}", url, e);
+            return false;
+        }
+    };
+
+    let response = FeroxResponse::from(response, true, handles.config.output_level).await;
+
+    exists(response.status(), response.text())
+}
+
+/// Whether a response with the given status code and body says the requested route exists; a 404,
+/// or a json error saying the route doesn't exist, means it doesn't
+fn exists(status: &StatusCode, body: &str) -> bool {
+    *status != StatusCode::NOT_FOUND && classify(body) != Some(JsonError::NotFound)
+}
+
+/// status codes (4xx/5xx) and lowercased text found in the given error value, and one level of
+/// the objects/arrays within it, i.e. {"error": {"code": 404, "message": "Not Found"}}
+fn collect(value: &Value, depth: usize, codes: &mut Vec<u64>, text: &mut String) {
+    match value {
+        Value::Number(number) => {
+            if let Some(code) = number.as_u64() {
+                if (400..600).contains(&code) {
+                    codes.push(code);
+                }
+            }
+        }
+        Value::String(string) => {
+            if let Ok(code) = string.parse::<u64>() {
+                if (400..600).contains(&code) {
+                    codes.push(code);
+                }
+            }
+
+            text.push_str(&string.to_lowercase());
+            text.push(' ');
+        }
+        Value::Array(values) if depth > 0 => {
+            for value in values {
+                collect(value, depth - 1, codes, text);
+            }
+        }
+        Value::Object(object) if depth > 0 => {
+            for key in ERROR_KEYS.iter() {
+                if let Some(value) = object.get(*key) {
+                    collect(value, depth - 1, codes, text);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Classify the given body, if it's a json error; None when it isn't json, or isn't an error
+pub fn classify(body: &str) -> Option<JsonError> {
+    let body = body.trim();
+
+    if !body.starts_with('{') {
+        return None;
+    }
+
+    let value: Value = serde_json::from_str(body).ok()?;
+    let object = value.as_object()?;
+
+    let mut codes = Vec::new();
+    let mut text = String::new();
+
+    for key in ERROR_KEYS.iter() {
+        if let Some(value) = object.get(*key) {
+            collect(value, 2, &mut codes, &mut text);
+        }
+    }
+
+    let failed = ["success", "ok"]
+        .iter()
+        .any(|key| object.get(*key) == Some(&Value::Bool(false)));
+
+    if !object.contains_key("error")
+        && !object.contains_key("errors")
+        && codes.is_empty()
+        && !failed
+    {
+        // some other json document, i.e. {"message": "hello"}
+        return None;
+    }
+
+    let mentions = |phrases: &[&str]| phrases.iter().any(|phrase| text.contains(phrase));
+
+    let class = if codes.iter().any(|code| *code == 404 || *code == 410) {
+        JsonError::NotFound
+    } else if codes.iter().any(|code| *code == 401 || *code == 403) {
+        JsonError::Unauthorized
+    } else if codes.contains(&405) {
+        JsonError::MethodNotAllowed
+    } else if mentions(&NOT_FOUND_PHRASES) {
+        JsonError::NotFound
+    } else if mentions(&UNAUTHORIZED_PHRASES) {
+        JsonError::Unauthorized
+    } else if mentions(&METHOD_PHRASES) {
+        JsonError::MethodNotAllowed
+    } else {
+        JsonError::Other
+    };
+
+    Some(class)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan_manager::FeroxScans;
+    use httpmock::{Method::GET, MockServer};
+
+    #[test]
+    /// json error bodies should be classified by their status codes first, then their text
+    fn classify_recognizes_json_errors() {
+        assert_eq!(
+            classify(r#"{"error": "Not Found"}"#),
+            Some(JsonError::NotFound)
+        );
+        assert_eq!(
+            classify(r#"{"statusCode": 404, "message": "Cannot GET /admin"}"#),
+            Some(JsonError::NotFound)
+        );
+        assert_eq!(
+            classify(r#"{"error": {"code": "401", "message": "token expired"}}"#),
+            Some(JsonError::Unauthorized)
+        );
+        assert_eq!(
+            classify(r#"{"errors": [{"message": "Method Not Allowed"}]}"#),
+            Some(JsonError::MethodNotAllowed)
+        );
+        assert_eq!(
+            classify(r#"{"success": false, "message": "no route matched"}"#),
+            Some(JsonError::NotFound)
+        );
+        assert_eq!(
+            classify(r#"{"error": "rate limited"}"#),
+            Some(JsonError::Other)
+        );
+    }
+
+    #[test]
+    /// anything that isn't a json error object shouldn't be classified
+    fn classify_ignores_other_bodies() {
+        assert_eq!(classify(r#"{"message": "hello", "users": []}"#), None);
+        assert_eq!(classify(r#"[{"error": "not found"}]"#), None);
+        assert_eq!(classify("<html>Not Found</html>"), None);
+        assert_eq!(classify("{not json"), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// each target should be followed by the versioned counterparts that exist
+    async fn with_version_prefixes_expands_targets() {
+        let srv = MockServer::start();
+
+        let v1 = srv.mock(|when, then| {
+            when.method(GET).path("/api/v1/");
+            then.status(401);
+        });
+
+        let v2 = srv.mock(|when, then| {
+            when.method(GET).path("/api/v2/");
+            then.status(404);
+        });
+
+        let scans = Arc::new(FeroxScans::default());
+        let (handles, _rx) = Handles::for_testing(Some(scans), None);
+        let target = srv.url("/api/");
+
+        assert_eq!(
+            with_version_prefixes(vec![target.clone()], Arc::new(handles)).await,
+            vec![target, srv.url("/api/v1/")]
+        );
+
+        v1.assert();
+        v2.assert();
+    }
+
+    #[test]
+    /// 404s and json errors saying the route doesn't exist mean a prefix doesn't exist
+    fn exists_recognizes_missing_routes() {
+        assert!(exists(&StatusCode::OK, "{}"));
+        assert!(exists(
+            &StatusCode::UNAUTHORIZED,
+            r#"{"error": "unauthorized"}"#
+        ));
+        assert!(!exists(&StatusCode::NOT_FOUND, ""));
+        assert!(!exists(&StatusCode::OK, r#"{"error": "not found"}"#));
+    }
+
+    #[test]
+    /// --api should ask for json without overriding the user's choices
+    fn apply_updates_headers_and_keeps_status_codes() {
+        let mut config = Configuration {
+            api: true,
+            status_codes: vec![200],
+            ..Default::default()
+        };
+        apply(&mut config);
+
+        assert_eq!(config.headers["Accept"], ACCEPT);
+        assert_eq!(config.status_codes, vec![200]);
+
+        let mut config = Configuration {
+            api: true,
+            ..Default::default()
+        };
+        config
+            .headers
+            .insert(String::from("accept"), String::from("application/xml"));
+        apply(&mut config);
+
+        assert_eq!(config.headers.len(), 1);
+        assert_eq!(config.headers["accept"], "application/xml");
+    }
+}
@@ -1,8 +1,13 @@
 use super::entry::BannerEntry;
 use crate::{
+    certificate::Certificate,
     config::Configuration,
+    cors,
     event_handlers::Handles,
+    scan_manager::redact_url,
     utils::{logged_request, status_colorizer},
+    wordlist::{Wordlist, STDIN_WORDLIST},
+    wordlists::{self, Resolved},
     VERSION,
 };
 use anyhow::{bail, Result};
@@ -20,21 +25,65 @@ pub(super) enum UpdateStatus {
     /// this version and latest release are the same
     UpToDate,
 
-    /// this version and latest release are not the same
-    OutOfDate,
+    /// this version and latest release (the contained version) are not the same
+    OutOfDate(String),
 
     /// some error occurred during version check
     Unknown,
 }
 
+/// Sorted status codes, where three or more consecutive codes of the same class are collapsed into
+/// a single range, i.e. 200-299
+pub(super) fn status_code_ranges(codes: &[u16]) -> Vec<String> {
+    let mut sorted = codes.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    while start < sorted.len() {
+        let mut end = start;
+
+        while end + 1 < sorted.len()
+            && sorted[end + 1] == sorted[end] + 1
+            && sorted[end + 1] / 100 == sorted[start] / 100
+        {
+            end += 1;
+        }
+
+        if end - start >= 2 {
+            ranges.push(format!("{}-{}", sorted[start], sorted[end]));
+        } else {
+            ranges.extend(sorted[start..=end].iter().map(|code| code.to_string()));
+        }
+
+        start = end + 1;
+    }
+
+    ranges
+}
+
 /// Banner object, contains multiple BannerEntry's and knows how to display itself
 pub struct Banner {
     /// all live targets
     targets: Vec<BannerEntry>,
 
+    /// certificates presented by https targets, and hostnames suggested by them
+    certificates: Vec<BannerEntry>,
+
+    /// represents Configuration.targets
+    targets_file: BannerEntry,
+
+    /// represents Configuration.target_ranges
+    target_ranges: BannerEntry,
+
     /// represents Configuration.status_codes
     status_codes: BannerEntry,
 
+    /// represents Configuration.extension_status
+    extension_status: Vec<BannerEntry>,
+
     /// represents Configuration.filter_status
     filter_status: BannerEntry,
 
@@ -47,12 +96,18 @@ pub struct Banner {
     /// represents Configuration.timeout
     timeout: BannerEntry,
 
+    /// represents Configuration.dns_cache_ttl
+    dns_cache_ttl: BannerEntry,
+
     /// represents Configuration.user_agent
     user_agent: BannerEntry,
 
     /// represents Configuration.config
     config: BannerEntry,
 
+    /// represents Configuration.profile
+    profile: BannerEntry,
+
     /// represents Configuration.proxy
     proxy: BannerEntry,
 
@@ -65,6 +120,12 @@ pub struct Banner {
     /// represents Configuration.headers
     headers: Vec<BannerEntry>,
 
+    /// represents Configuration.raw
+    raw: BannerEntry,
+
+    /// represents Configuration.raw_headers
+    raw_headers: Vec<BannerEntry>,
+
     /// represents Configuration.filter_size
     filter_size: Vec<BannerEntry>,
 
@@ -80,24 +141,162 @@ pub struct Banner {
     /// represents Configuration.filter_regex
     filter_regex: Vec<BannerEntry>,
 
+    /// represents Configuration.filter_header
+    filter_header: Vec<BannerEntry>,
+
+    /// represents Configuration.match_header
+    match_header: Vec<BannerEntry>,
+
+    /// represents Configuration.filter_command
+    filter_command: BannerEntry,
+
+    /// represents Configuration.plugins
+    plugins: Vec<BannerEntry>,
+
+    /// represents Configuration.notify_webhook
+    notify_webhook: BannerEntry,
+
+    /// represents Configuration.notify_on
+    notify_on: BannerEntry,
+
+    /// represents Configuration.on_found
+    on_found: BannerEntry,
+
+    /// represents Configuration.metrics_addr
+    metrics_addr: BannerEntry,
+
+    /// represents Configuration.api_addr
+    api_addr: BannerEntry,
+
+    /// represents Configuration.grpc
+    grpc: BannerEntry,
+
     /// represents Configuration.extract_links
     extract_links: BannerEntry,
 
+    /// represents Configuration.parse_listings
+    parse_listings: BannerEntry,
+
+    /// represents Configuration.smart_wordlist
+    smart_wordlist: BannerEntry,
+
+    /// represents Configuration.api
+    api: BannerEntry,
+
+    /// represents Configuration.dry_run
+    dry_run: BannerEntry,
+
+    /// represents Configuration.low_memory
+    low_memory: BannerEntry,
+
+    /// represents Configuration.state_redact
+    state_redact: BannerEntry,
+
+    /// represents Configuration.state_embed_wordlist
+    state_embed_wordlist: BannerEntry,
+
+    /// represents Configuration.state_password, the password itself is never shown
+    state_password: BannerEntry,
+
+    /// represents Configuration.fingerprint
+    fingerprint: BannerEntry,
+
+    /// represents Configuration.hashes
+    hashes: BannerEntry,
+
+    /// represents Configuration.fingerprint_db
+    fingerprint_db: BannerEntry,
+
+    /// represents Configuration.show_preview
+    show_preview: BannerEntry,
+
+    /// represents Configuration.group_similar
+    group_similar: BannerEntry,
+
+    /// represents Configuration.auto_extensions
+    auto_extensions: BannerEntry,
+
+    /// represents Configuration.auto_wordlist
+    auto_wordlist: BannerEntry,
+
+    /// represents Configuration.wordlist_rules
+    wordlist_rules: BannerEntry,
+
+    /// represents Configuration.interesting_rules
+    interesting_rules: BannerEntry,
+
+    /// represents Configuration.bypass_403
+    bypass_403: BannerEntry,
+
+    /// represents Configuration.verb_compare
+    verb_compare: BannerEntry,
+
+    /// represents Configuration.follow_disclosures
+    follow_disclosures: BannerEntry,
+
+    /// represents Configuration.cors
+    cors: BannerEntry,
+
+    /// represents Configuration.header_audit
+    header_audit: BannerEntry,
+
+    /// represents Configuration.favicon
+    favicon: BannerEntry,
+
+    /// represents Configuration.wsdl
+    wsdl: BannerEntry,
+
+    /// represents Configuration.cloud_storage
+    cloud_storage: BannerEntry,
+
     /// represents Configuration.json
     json: BannerEntry,
 
     /// represents Configuration.output
     output: BannerEntry,
 
+    /// represents Configuration.urls_file
+    urls_file: BannerEntry,
+
+    /// represents Configuration.har
+    har: BannerEntry,
+
+    /// represents Configuration.output_db
+    output_db: BannerEntry,
+
+    /// represents Configuration.output_dsn
+    output_dsn: BannerEntry,
+
+    /// represents Configuration.split_output
+    split_output: BannerEntry,
+
+    /// represents Configuration.output_dir
+    output_dir: BannerEntry,
+
+    /// represents Configuration.compare
+    compare: BannerEntry,
+
     /// represents Configuration.debug_log
     debug_log: BannerEntry,
 
+    /// represents Configuration.debug_log_max_size and Configuration.debug_log_backups
+    debug_log_rotation: BannerEntry,
+
+    /// represents Configuration.cache_dir
+    cache_dir: BannerEntry,
+
     /// represents Configuration.extensions
     extensions: BannerEntry,
 
     /// represents Configuration.insecure
     insecure: BannerEntry,
 
+    /// represents Configuration.ca_cert
+    ca_cert: BannerEntry,
+
+    /// represents Configuration.pin_sha256
+    pin_sha256: BannerEntry,
+
     /// represents Configuration.redirects
     redirects: BannerEntry,
 
@@ -107,24 +306,66 @@ pub struct Banner {
     /// represents Configuration.queries
     queries: Vec<BannerEntry>,
 
+    /// represents Configuration.query_file
+    query_file: BannerEntry,
+
     /// represents Configuration.verbosity
     verbosity: BannerEntry,
 
     /// represents Configuration.add_slash
     add_slash: BannerEntry,
 
+    /// represents Configuration.smart_slash
+    smart_slash: BannerEntry,
+
+    /// represents Configuration.url_encode_level
+    url_encode_level: BannerEntry,
+
+    /// represents Configuration.shuffle and Configuration.seed
+    shuffle: BannerEntry,
+
     /// represents Configuration.no_recursion
     no_recursion: BannerEntry,
 
+    /// represents Configuration.dont_recurse_regex
+    dont_recurse_regex: Vec<BannerEntry>,
+
     /// represents Configuration.scan_limit
     scan_limit: BannerEntry,
 
+    /// represents Configuration.max_scans
+    max_scans: BannerEntry,
+
+    /// represents Configuration.strategy and Configuration.priority_keywords
+    strategy: BannerEntry,
+
     /// represents Configuration.time_limit
     time_limit: BannerEntry,
 
+    /// represents Configuration.scan_time_limit
+    scan_time_limit: BannerEntry,
+
+    /// represents Configuration.tarpit_latency
+    tarpit_latency: BannerEntry,
+
+    /// represents Configuration.throttle
+    throttle: BannerEntry,
+
+    /// represents Configuration.request_limit
+    request_limit: BannerEntry,
+
+    /// represents Configuration.scan_window
+    scan_window: BannerEntry,
+
     /// represents Configuration.rate_limit
     rate_limit: BannerEntry,
 
+    /// represents Configuration.delay and Configuration.jitter
+    delay: BannerEntry,
+
+    /// represents Configuration.decoy_ratio
+    decoy_ratio: BannerEntry,
+
     /// represents Configuration.parallel
     parallel: BannerEntry,
 
@@ -134,6 +375,9 @@ pub struct Banner {
     /// represents Configuration.auto_bail
     auto_bail: BannerEntry,
 
+    /// represents Configuration.auto_pause
+    auto_pause: BannerEntry,
+
     /// current version of feroxbuster
     pub(super) version: String,
 
@@ -154,19 +398,49 @@ impl Banner {
         let mut filter_word_count = Vec::new();
         let mut filter_line_count = Vec::new();
         let mut filter_regex = Vec::new();
+        let mut filter_header = Vec::new();
+        let mut match_header = Vec::new();
+        let mut plugins = Vec::new();
         let mut queries = Vec::new();
 
         for target in tgts {
             targets.push(BannerEntry::new("🎯", "Target Url", target));
         }
 
-        let mut codes = vec![];
-        for code in &config.status_codes {
-            codes.push(status_colorizer(&code.to_string()))
-        }
+        let targets_file = BannerEntry::new("📋", "Targets File", &config.targets);
+        let target_ranges = BannerEntry::new(
+            "📡",
+            "Swept Ranges",
+            &format!("[{}]", config.target_ranges.join(", ")),
+        );
+
+        // classes (2xx) and all expand to a lot of codes, runs of them are shown as ranges
+        let codes: Vec<_> = status_code_ranges(&config.status_codes)
+            .iter()
+            .map(|range| status_colorizer(range))
+            .collect();
         let status_codes =
             BannerEntry::new("👌", "Status Codes", &format!("[{}]", codes.join(", ")));
 
+        let mut rules: Vec<_> = config.extension_status.iter().collect();
+        rules.sort();
+
+        let extension_status = rules
+            .into_iter()
+            .map(|(extension, codes)| {
+                let codes: Vec<_> = codes
+                    .iter()
+                    .map(|code| status_colorizer(&code.to_string()))
+                    .collect();
+
+                BannerEntry::new(
+                    "👌",
+                    "Extension Status Codes",
+                    &format!("{}: [{}]", extension, codes.join(", ")),
+                )
+            })
+            .collect();
+
         for code in &config.filter_status {
             code_filters.push(status_colorizer(&code.to_string()))
         }
@@ -176,6 +450,26 @@ impl Banner {
             &format!("[{}]", code_filters.join(", ")),
         );
 
+        let mut notify_codes = vec![];
+        for code in &config.notify_on {
+            notify_codes.push(status_colorizer(&code.to_string()))
+        }
+        let notify_on =
+            BannerEntry::new("📣", "Notify On", &format!("[{}]", notify_codes.join(", ")));
+        let notify_webhook = BannerEntry::new("📣", "Notify Webhook", &config.notify_webhook);
+        let on_found = BannerEntry::new(
+            "🎣",
+            "On Found",
+            &format!("{} (limit: {})", config.on_found, config.on_found_limit),
+        );
+        let api_addr = BannerEntry::new("🕹", "Control API", &format!("http://{}", config.api_addr));
+        let grpc = BannerEntry::new("🕹", "gRPC Service", &config.grpc);
+        let metrics_addr = BannerEntry::new(
+            "📊",
+            "Metrics",
+            &format!("http://{}/metrics", config.metrics_addr),
+        );
+
         for code in &config.replay_codes {
             replay_codes.push(status_colorizer(&code.to_string()))
         }
@@ -193,6 +487,14 @@ impl Banner {
             ));
         }
 
+        let raw = BannerEntry::new("🔩", "Raw Requests", &config.raw.to_string());
+
+        let raw_headers: Vec<BannerEntry> = config
+            .raw_headers
+            .iter()
+            .map(|line| BannerEntry::new("🔩", "Raw Header", &format!("{:?}", line)))
+            .collect();
+
         for filter in &config.filter_size {
             filter_size.push(BannerEntry::new("💢", "Size Filter", &filter.to_string()));
         }
@@ -221,6 +523,18 @@ impl Banner {
             filter_regex.push(BannerEntry::new("💢", "Regex Filter", filter));
         }
 
+        for filter in &config.filter_header {
+            filter_header.push(BannerEntry::new("💢", "Header Filter", filter));
+        }
+
+        for matcher in &config.match_header {
+            match_header.push(BannerEntry::new("🎯", "Header Matcher", matcher));
+        }
+
+        for plugin in &config.plugins {
+            plugins.push(BannerEntry::new("🧩", "Plugin", plugin));
+        }
+
         for query in &config.queries {
             queries.push(BannerEntry::new(
                 "🤔",
@@ -229,6 +543,16 @@ impl Banner {
             ));
         }
 
+        let query_file = BannerEntry::new(
+            "🎲",
+            "Query Values",
+            &format!(
+                "{} ({} values)",
+                config.query_file,
+                config.query_values.len()
+            ),
+        );
+
         let volume = ["🔈", "🔉", "🔊", "📢"];
         let verbosity = if let 1..=4 = config.verbosity {
             //speaker medium volume (increasing with verbosity to loudspeaker)
@@ -253,77 +577,295 @@ impl Banner {
             BannerEntry::new("🚫", "Do Not Recurse", &config.no_recursion.to_string())
         };
 
+        let dont_recurse_regex = config
+            .dont_recurse_regex
+            .iter()
+            .map(|regex| BannerEntry::new("🚫", "Do Not Recurse Into", regex))
+            .collect();
+
         let scan_limit = BannerEntry::new(
             "🦥",
             "Concurrent Scan Limit",
             &config.scan_limit.to_string(),
         );
 
+        let max_scans = BannerEntry::new("🧱", "Total Scan Limit", &config.max_scans.to_string());
+
+        let strategy = if config.priority_keywords.is_empty() {
+            BannerEntry::new("🪜", "Scan Strategy", &config.strategy)
+        } else {
+            BannerEntry::new(
+                "🪜",
+                "Scan Strategy",
+                &format!("{} ({})", config.strategy, config.priority_keywords),
+            )
+        };
+
         let replay_proxy = BannerEntry::new("🎥", "Replay Proxy", &config.replay_proxy);
         let auto_tune = BannerEntry::new("🎶", "Auto Tune", &config.auto_tune.to_string());
         let auto_bail = BannerEntry::new("🪣", "Auto Bail", &config.auto_bail.to_string());
+        let auto_pause = BannerEntry::new(
+            "⏸️",
+            "Auto Pause",
+            &format!(
+                "over {}% of the last {} responses blocked, throttled, or errored",
+                config.auto_pause, config.auto_pause_window
+            ),
+        );
         let cfg = BannerEntry::new("💉", "Config File", &config.config);
+        let profile = BannerEntry::new("💉", "Profile", &config.profile);
         let proxy = BannerEntry::new("💎", "Proxy", &config.proxy);
         let threads = BannerEntry::new("🚀", "Threads", &config.threads.to_string());
-        let wordlist = BannerEntry::new("📖", "Wordlist", &config.wordlist);
+        let wordlist = match wordlists::resolve(&config.wordlist, config.wordlist_given) {
+            _ if config.wordlist == STDIN_WORDLIST => {
+                BannerEntry::new("📖", "Wordlist", "stdin (streamed)")
+            }
+            Ok(Resolved::Embedded) => BannerEntry::new(
+                "📖",
+                "Wordlist",
+                &format!("built-in ({} words)", Wordlist::embedded().len()),
+            ),
+            _ => BannerEntry::new("📖", "Wordlist", &config.wordlist),
+        };
         let timeout = BannerEntry::new("💥", "Timeout (secs)", &config.timeout.to_string());
+        let dns_cache_ttl = BannerEntry::new(
+            "📇",
+            "DNS Cache TTL (secs)",
+            &config.dns_cache_ttl.to_string(),
+        );
         let user_agent = BannerEntry::new("🦡", "User-Agent", &config.user_agent);
         let extract_links =
             BannerEntry::new("🔎", "Extract Links", &config.extract_links.to_string());
+        let parse_listings =
+            BannerEntry::new("📂", "Parse Listings", &config.parse_listings.to_string());
+        let smart_wordlist =
+            BannerEntry::new("🕸", "Smart Wordlist", &config.smart_wordlist.to_string());
+        let api = BannerEntry::new("🧩", "API Mode", &config.api.to_string());
+        let dry_run = BannerEntry::new("🧪", "Dry Run", &config.dry_run.to_string());
+        let low_memory = BannerEntry::new("🪶", "Low Memory", &config.low_memory.to_string());
+        let state_redact =
+            BannerEntry::new("🧽", "Redact State File", &config.state_redact.to_string());
+        let state_embed_wordlist = BannerEntry::new(
+            "🧳",
+            "Embed Wordlist in State",
+            &config.state_embed_wordlist.to_string(),
+        );
+        let state_password = BannerEntry::new("🔐", "Encrypt State File", "true");
+        let fingerprint = BannerEntry::new("🔬", "Fingerprint", &config.fingerprint.to_string());
+        let hashes = BannerEntry::new(
+            "🧾",
+            "Body Hashes",
+            &format!("[{}]", config.hashes.join(", ")),
+        );
+        let fingerprint_db = BannerEntry::new("🧬", "Fingerprint Database", &config.fingerprint_db);
+        let show_preview = BannerEntry::new(
+            "👀",
+            "Body Preview (chars)",
+            &config.show_preview.to_string(),
+        );
+        let group_similar = BannerEntry::new(
+            "🧺",
+            "Group Similar Responses",
+            &config.group_similar.to_string(),
+        );
+        let auto_extensions =
+            BannerEntry::new("🧬", "Auto Extensions", &config.auto_extensions.to_string());
+        let auto_wordlist = BannerEntry::new("📚", "Auto Wordlists", &config.auto_wordlist);
+        let wordlist_rules = BannerEntry::new("📚", "Wordlist Rules", &config.wordlist_rules);
+        let interesting_rules =
+            BannerEntry::new("🚩", "Interesting Rules", &config.interesting_rules);
+
+        let techniques = if config.bypass_techniques.is_empty() {
+            String::from("all techniques")
+        } else {
+            format!("[{}]", config.bypass_techniques.join(", "))
+        };
+
+        let bypass_403 = BannerEntry::new(
+            "🚪",
+            "403 Bypass",
+            &format!("{} (budget: {} requests)", techniques, config.bypass_budget),
+        );
+        let verb_compare = BannerEntry::new("🔀", "Verb Compare", "HEAD, OPTIONS");
+        let follow_disclosures =
+            BannerEntry::new("🔦", "Follow Disclosures", ".git, .svn, .DS_Store");
+        let cors = BannerEntry::new("🌐", "CORS Check", &format!("Origin: {}", cors::ORIGIN));
+        let header_audit = BannerEntry::new("🛡", "Header Audit", "CSP, HSTS, X-Frame-Options");
+        let favicon = BannerEntry::new("🖼", "Favicon Hash", "/favicon.ico");
+        let wsdl = BannerEntry::new("🧼", "WSDL Probe", "?wsdl of SOAP endpoints");
+        let cloud_storage = BannerEntry::new("🪣", "Cloud Storage", "S3, GCS, Azure");
         let json = BannerEntry::new("🧔", "JSON Output", &config.json.to_string());
         let output = BannerEntry::new("💾", "Output File", &config.output);
+        let urls_file = BannerEntry::new("🔗", "URLs File", &config.urls_file);
+        let har = BannerEntry::new("📼", "HAR File", &config.har);
+        let output_db = BannerEntry::new("🗂", "Output Database", &config.output_db);
+        let output_dsn = BannerEntry::new("🐘", "Output DSN", &redact_url(&config.output_dsn));
+        let split_output = BannerEntry::new("🗃", "Split Output Dir", &config.split_output);
+        let output_dir = BannerEntry::new("📂", "Per-Target Output Dir", &config.output_dir);
+        let compare = BannerEntry::new("🆚", "Compare To", &config.compare);
         let debug_log = BannerEntry::new("🪲", "Debugging Log", &config.debug_log);
+        let debug_log_rotation = BannerEntry::new(
+            "🪲",
+            "Debug Log Rotation",
+            &format!(
+                "{} ({} backups)",
+                config.debug_log_max_size, config.debug_log_backups
+            ),
+        );
+        let cache_dir = BannerEntry::new("🗄", "Response Cache", &config.cache_dir);
         let extensions = BannerEntry::new(
             "💲",
             "Extensions",
             &format!("[{}]", config.extensions.join(", ")),
         );
         let insecure = BannerEntry::new("🔓", "Insecure", &config.insecure.to_string());
+        let ca_cert = BannerEntry::new("🔏", "CA Certificates", &config.ca_cert);
+        let pin_sha256 = BannerEntry::new(
+            "📌",
+            "Pinned Public Keys",
+            &format!("[{}]", config.pin_sha256.join(", ")),
+        );
         let redirects = BannerEntry::new("📍", "Follow Redirects", &config.redirects.to_string());
         let dont_filter =
             BannerEntry::new("🤪", "Filter Wildcards", &(!config.dont_filter).to_string());
         let add_slash = BannerEntry::new("🪓", "Add Slash", &config.add_slash.to_string());
+        let smart_slash = BannerEntry::new("🪄", "Smart Slash", &config.smart_slash.to_string());
+        let url_encode_level = BannerEntry::new("🥸", "URL Encode Level", &config.url_encode_level);
+        let shuffle = BannerEntry::new("🔀", "Shuffle Seed", &config.seed.to_string());
         let time_limit = BannerEntry::new("🕖", "Time Limit", &config.time_limit);
+        let scan_time_limit =
+            BannerEntry::new("🕖", "Time Limit (per directory)", &config.scan_time_limit);
+        let tarpit_latency = BannerEntry::new(
+            "🐢",
+            "Tarpit Latency (ms)",
+            &config.tarpit_latency.to_string(),
+        );
+        let throttle = BannerEntry::new("🐌", "Bandwidth Limit", &config.throttle);
+        let request_limit =
+            BannerEntry::new("🧮", "Request Limit", &config.request_limit.to_string());
+        let scan_window = BannerEntry::new("🌙", "Scan Window", &config.scan_window);
+        let filter_command = BannerEntry::new("💢", "Command Filter", &config.filter_command);
         let parallel = BannerEntry::new("🛤", "Parallel Scans", &config.parallel.to_string());
         let rate_limit =
             BannerEntry::new("🚧", "Requests per Second", &config.rate_limit.to_string());
 
+        let delay_value = match (config.delay.as_str(), config.jitter.as_str()) {
+            (delay, "") => delay.to_string(),
+            ("", jitter) => format!("0ms ± {}", jitter),
+            (delay, jitter) => format!("{} ± {}", delay, jitter),
+        };
+        let delay = BannerEntry::new("⏳", "Delay (per thread)", &delay_value);
+        let decoy_ratio = BannerEntry::new(
+            "🎭",
+            "Decoy Requests",
+            &format!("1 per {} requests", config.decoy_ratio),
+        );
+
         Self {
             targets,
+            certificates: Vec::new(),
+            targets_file,
+            target_ranges,
             status_codes,
+            extension_status,
             threads,
             wordlist,
             filter_status,
             timeout,
+            dns_cache_ttl,
             user_agent,
             auto_bail,
+            auto_pause,
             auto_tune,
             proxy,
             replay_codes,
             replay_proxy,
             headers,
+            raw,
+            raw_headers,
             filter_size,
             filter_similar,
             filter_word_count,
             filter_line_count,
             filter_regex,
+            filter_header,
+            match_header,
+            filter_command,
+            plugins,
+            notify_webhook,
+            notify_on,
+            on_found,
+            metrics_addr,
+            api_addr,
+            grpc,
             extract_links,
+            parse_listings,
+            smart_wordlist,
+            api,
+            dry_run,
+            low_memory,
+            state_redact,
+            state_embed_wordlist,
+            state_password,
+            fingerprint,
+            hashes,
+            fingerprint_db,
+            show_preview,
+            group_similar,
+            auto_extensions,
+            auto_wordlist,
+            wordlist_rules,
+            interesting_rules,
+            bypass_403,
+            verb_compare,
+            follow_disclosures,
+            cors,
+            header_audit,
+            favicon,
+            wsdl,
+            cloud_storage,
             parallel,
             json,
             queries,
+            query_file,
             output,
+            urls_file,
+            har,
+            output_db,
+            output_dsn,
+            split_output,
+            output_dir,
+            compare,
             debug_log,
+            debug_log_rotation,
+            cache_dir,
             extensions,
             insecure,
+            ca_cert,
+            pin_sha256,
             dont_filter,
             redirects,
             verbosity,
             add_slash,
+            smart_slash,
+            url_encode_level,
+            shuffle,
             no_recursion,
+            dont_recurse_regex,
             rate_limit,
+            delay,
+            decoy_ratio,
             scan_limit,
+            max_scans,
+            strategy,
             time_limit,
+            scan_time_limit,
+            tarpit_latency,
+            throttle,
+            request_limit,
+            scan_window,
             config: cfg,
+            profile,
             version: VERSION.to_string(),
             update_status: UpdateStatus::Unknown,
         }
@@ -394,13 +936,40 @@ by Ben "epi" Risher {}                 ver: {}"#,
             // where they have a version greater than the latest tagged release
             self.update_status = UpdateStatus::UpToDate;
         } else {
-            self.update_status = UpdateStatus::OutOfDate;
+            self.update_status = UpdateStatus::OutOfDate(latest_version.to_string());
         }
 
         log::trace!("exit: check_for_updates -> {:?}", self.update_status);
         Ok(())
     }
 
+    /// Add the certificates captured from https targets, each followed by its SANs and any
+    /// in-scope hostnames from them that aren't already targets
+    pub fn add_certificates(&mut self, certificates: &[Certificate], tgts: &[String]) {
+        for certificate in certificates {
+            self.certificates.push(BannerEntry::new(
+                "🔐",
+                "Certificate",
+                &certificate.to_string(),
+            ));
+
+            if !certificate.sans.is_empty() {
+                self.certificates.push(BannerEntry::new(
+                    "🔐",
+                    "Certificate SANs",
+                    &format!("[{}]", certificate.sans.join(", ")),
+                ));
+            }
+
+            for suggestion in certificate.suggestions() {
+                if !tgts.contains(&suggestion) {
+                    self.certificates
+                        .push(BannerEntry::new("💡", "Suggested Target", &suggestion));
+                }
+            }
+        }
+    }
+
     /// display the banner on Write writer
     pub fn print_to<W>(&self, mut writer: W, config: Arc<Configuration>) -> Result<()>
     where
@@ -413,10 +982,26 @@ by Ben "epi" Risher {}                 ver: {}"#,
             writeln!(&mut writer, "{}", target)?;
         }
 
+        for certificate in &self.certificates {
+            writeln!(&mut writer, "{}", certificate)?;
+        }
+
+        if !config.targets.is_empty() {
+            writeln!(&mut writer, "{}", self.targets_file)?;
+        }
+
+        if !config.target_ranges.is_empty() {
+            writeln!(&mut writer, "{}", self.target_ranges)?;
+        }
+
         writeln!(&mut writer, "{}", self.threads)?;
         writeln!(&mut writer, "{}", self.wordlist)?;
         writeln!(&mut writer, "{}", self.status_codes)?;
 
+        for rule in &self.extension_status {
+            writeln!(&mut writer, "{}", rule)?;
+        }
+
         if !config.filter_status.is_empty() {
             // exception here for an optional print in the middle of always printed values is due
             // to me wanting the allows and denys to be printed one after the other
@@ -424,6 +1009,7 @@ by Ben "epi" Risher {}                 ver: {}"#,
         }
 
         writeln!(&mut writer, "{}", self.timeout)?;
+        writeln!(&mut writer, "{}", self.dns_cache_ttl)?;
         writeln!(&mut writer, "{}", self.user_agent)?;
 
         // followed by the maybe printed or variably displayed values
@@ -431,6 +1017,10 @@ by Ben "epi" Risher {}                 ver: {}"#,
             writeln!(&mut writer, "{}", self.config)?;
         }
 
+        if !config.profile.is_empty() {
+            writeln!(&mut writer, "{}", self.profile)?;
+        }
+
         if !config.proxy.is_empty() {
             writeln!(&mut writer, "{}", self.proxy)?;
         }
@@ -446,6 +1036,14 @@ by Ben "epi" Risher {}                 ver: {}"#,
             writeln!(&mut writer, "{}", header)?;
         }
 
+        if config.raw {
+            writeln!(&mut writer, "{}", self.raw)?;
+        }
+
+        for line in &self.raw_headers {
+            writeln!(&mut writer, "{}", line)?;
+        }
+
         for filter in &self.filter_size {
             writeln!(&mut writer, "{}", filter)?;
         }
@@ -466,10 +1064,150 @@ by Ben "epi" Risher {}                 ver: {}"#,
             writeln!(&mut writer, "{}", filter)?;
         }
 
+        for filter in &self.filter_header {
+            writeln!(&mut writer, "{}", filter)?;
+        }
+
+        for matcher in &self.match_header {
+            writeln!(&mut writer, "{}", matcher)?;
+        }
+
+        if !config.filter_command.is_empty() {
+            writeln!(&mut writer, "{}", self.filter_command)?;
+        }
+
+        for plugin in &self.plugins {
+            writeln!(&mut writer, "{}", plugin)?;
+        }
+
+        if !config.notify_webhook.is_empty() {
+            writeln!(&mut writer, "{}", self.notify_webhook)?;
+
+            if !config.notify_on.is_empty() {
+                writeln!(&mut writer, "{}", self.notify_on)?;
+            }
+        }
+
+        if !config.on_found.is_empty() {
+            writeln!(&mut writer, "{}", self.on_found)?;
+        }
+
+        if !config.metrics_addr.is_empty() {
+            writeln!(&mut writer, "{}", self.metrics_addr)?;
+        }
+
+        if !config.api_addr.is_empty() {
+            writeln!(&mut writer, "{}", self.api_addr)?;
+        }
+
+        if !config.grpc.is_empty() {
+            writeln!(&mut writer, "{}", self.grpc)?;
+        }
+
         if config.extract_links {
             writeln!(&mut writer, "{}", self.extract_links)?;
         }
 
+        if config.parse_listings {
+            writeln!(&mut writer, "{}", self.parse_listings)?;
+        }
+
+        if config.smart_wordlist {
+            writeln!(&mut writer, "{}", self.smart_wordlist)?;
+        }
+
+        if config.api {
+            writeln!(&mut writer, "{}", self.api)?;
+        }
+
+        if config.dry_run {
+            writeln!(&mut writer, "{}", self.dry_run)?;
+        }
+
+        if config.low_memory {
+            writeln!(&mut writer, "{}", self.low_memory)?;
+        }
+
+        if config.state_redact {
+            writeln!(&mut writer, "{}", self.state_redact)?;
+        }
+
+        if config.state_embed_wordlist {
+            writeln!(&mut writer, "{}", self.state_embed_wordlist)?;
+        }
+
+        if !config.state_password.is_empty() {
+            writeln!(&mut writer, "{}", self.state_password)?;
+        }
+
+        if config.fingerprint {
+            writeln!(&mut writer, "{}", self.fingerprint)?;
+        }
+
+        if !config.hashes.is_empty() {
+            writeln!(&mut writer, "{}", self.hashes)?;
+        }
+
+        if !config.fingerprint_db.is_empty() {
+            writeln!(&mut writer, "{}", self.fingerprint_db)?;
+        }
+
+        if config.show_preview > 0 {
+            writeln!(&mut writer, "{}", self.show_preview)?;
+        }
+
+        if config.group_similar > 0 {
+            writeln!(&mut writer, "{}", self.group_similar)?;
+        }
+
+        if config.auto_extensions {
+            writeln!(&mut writer, "{}", self.auto_extensions)?;
+        }
+
+        if !config.auto_wordlist.is_empty() {
+            writeln!(&mut writer, "{}", self.auto_wordlist)?;
+        }
+
+        if !config.wordlist_rules.is_empty() {
+            writeln!(&mut writer, "{}", self.wordlist_rules)?;
+        }
+
+        if !config.interesting_rules.is_empty() {
+            writeln!(&mut writer, "{}", self.interesting_rules)?;
+        }
+
+        if config.bypass_403 {
+            writeln!(&mut writer, "{}", self.bypass_403)?;
+        }
+
+        if config.verb_compare {
+            writeln!(&mut writer, "{}", self.verb_compare)?;
+        }
+
+        if config.follow_disclosures {
+            writeln!(&mut writer, "{}", self.follow_disclosures)?;
+        }
+
+        if config.cors {
+            writeln!(&mut writer, "{}", self.cors)?;
+        }
+
+        if config.header_audit {
+            writeln!(&mut writer, "{}", self.header_audit)?;
+        }
+
+        if config.favicon {
+            writeln!(&mut writer, "{}", self.favicon)?;
+        }
+
+        if config.wsdl {
+            writeln!(&mut writer, "{}", self.wsdl)?;
+        }
+
+        if config.cloud_storage {
+            writeln!(&mut writer, "{}", self.cloud_storage)?;
+        }
+
         if config.json {
             writeln!(&mut writer, "{}", self.json)?;
         }
@@ -478,12 +1216,52 @@ by Ben "epi" Risher {}                 ver: {}"#,
             writeln!(&mut writer, "{}", query)?;
         }
 
+        if !config.query_file.is_empty() {
+            writeln!(&mut writer, "{}", self.query_file)?;
+        }
+
         if !config.output.is_empty() {
             writeln!(&mut writer, "{}", self.output)?;
         }
 
+        if !config.urls_file.is_empty() {
+            writeln!(&mut writer, "{}", self.urls_file)?;
+        }
+
+        if !config.har.is_empty() {
+            writeln!(&mut writer, "{}", self.har)?;
+        }
+
+        if !config.output_db.is_empty() {
+            writeln!(&mut writer, "{}", self.output_db)?;
+        }
+
+        if !config.output_dsn.is_empty() {
+            writeln!(&mut writer, "{}", self.output_dsn)?;
+        }
+
+        if !config.split_output.is_empty() {
+            writeln!(&mut writer, "{}", self.split_output)?;
+        }
+
+        if !config.output_dir.is_empty() {
+            writeln!(&mut writer, "{}", self.output_dir)?;
+        }
+
+        if !config.compare.is_empty() {
+            writeln!(&mut writer, "{}", self.compare)?;
+        }
+
         if !config.debug_log.is_empty() {
             writeln!(&mut writer, "{}", self.debug_log)?;
+
+            if !config.debug_log_max_size.is_empty() {
+                writeln!(&mut writer, "{}", self.debug_log_rotation)?;
+            }
+        }
+
+        if !config.cache_dir.is_empty() {
+            writeln!(&mut writer, "{}", self.cache_dir)?;
         }
 
         if !config.extensions.is_empty() {
@@ -494,9 +1272,20 @@ by Ben "epi" Risher {}                 ver: {}"#,
             writeln!(&mut writer, "{}", self.insecure)?;
         }
 
+        if !config.ca_cert.is_empty() {
+            writeln!(&mut writer, "{}", self.ca_cert)?;
+        }
+
+        if !config.pin_sha256.is_empty() {
+            writeln!(&mut writer, "{}", self.pin_sha256)?;
+        }
+
         if config.auto_bail {
             writeln!(&mut writer, "{}", self.auto_bail)?;
         }
+        if config.auto_pause > 0 {
+            writeln!(&mut writer, "{}", self.auto_pause)?;
+        }
         if config.auto_tune {
             writeln!(&mut writer, "{}", self.auto_tune)?;
         }
@@ -517,10 +1306,34 @@ by Ben "epi" Risher {}                 ver: {}"#,
             writeln!(&mut writer, "{}", self.add_slash)?;
         }
 
+        if config.smart_slash {
+            writeln!(&mut writer, "{}", self.smart_slash)?;
+        }
+
+        if config.url_encode_level != "none" {
+            writeln!(&mut writer, "{}", self.url_encode_level)?;
+        }
+
+        if config.shuffle {
+            writeln!(&mut writer, "{}", self.shuffle)?;
+        }
+
         writeln!(&mut writer, "{}", self.no_recursion)?;
 
+        if !config.no_recursion {
+            for regex in &self.dont_recurse_regex {
+                writeln!(&mut writer, "{}", regex)?;
+            }
+        }
+
         if config.scan_limit > 0 {
             writeln!(&mut writer, "{}", self.scan_limit)?;
+            // the order in which waiting scans are started only matters when scans have to wait
+            writeln!(&mut writer, "{}", self.strategy)?;
+        }
+
+        if config.max_scans > 0 {
+            writeln!(&mut writer, "{}", self.max_scans)?;
         }
 
         if config.parallel > 0 {
@@ -531,15 +1344,43 @@ by Ben "epi" Risher {}                 ver: {}"#,
             writeln!(&mut writer, "{}", self.rate_limit)?;
         }
 
+        if !config.delay.is_empty() || !config.jitter.is_empty() {
+            writeln!(&mut writer, "{}", self.delay)?;
+        }
+
+        if config.decoy_ratio > 0 {
+            writeln!(&mut writer, "{}", self.decoy_ratio)?;
+        }
+
         if !config.time_limit.is_empty() {
             writeln!(&mut writer, "{}", self.time_limit)?;
         }
 
-        if matches!(self.update_status, UpdateStatus::OutOfDate) {
+        if !config.scan_time_limit.is_empty() {
+            writeln!(&mut writer, "{}", self.scan_time_limit)?;
+        }
+
+        if config.tarpit_latency > 0 {
+            writeln!(&mut writer, "{}", self.tarpit_latency)?;
+        }
+
+        if !config.throttle.is_empty() {
+            writeln!(&mut writer, "{}", self.throttle)?;
+        }
+
+        if config.request_limit > 0 {
+            writeln!(&mut writer, "{}", self.request_limit)?;
+        }
+
+        if !config.scan_window.is_empty() {
+            writeln!(&mut writer, "{}", self.scan_window)?;
+        }
+
+        if let UpdateStatus::OutOfDate(latest) = &self.update_status {
             let update = BannerEntry::new(
                 "🎉",
                 "New Version Available",
-                "https://github.com/epi052/feroxbuster/releases/latest",
+                &format!("v{} (run: feroxbuster update)", latest),
             );
             writeln!(&mut writer, "{}", update)?;
         }
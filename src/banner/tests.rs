@@ -1,4 +1,4 @@
-use super::container::UpdateStatus;
+use super::container::{status_code_ranges, UpdateStatus};
 use super::*;
 use crate::{config::Configuration, event_handlers::Handles, scan_manager::FeroxScans};
 use httpmock::Method::GET;
@@ -106,7 +106,9 @@ async fn banner_needs_update_returns_out_of_date() {
     let _ = banner.check_for_updates(&srv.url("/latest"), handles).await;
 
     assert_eq!(mock.hits(), 1);
-    assert!(matches!(banner.update_status, UpdateStatus::OutOfDate));
+    assert!(
+        matches!(banner.update_status, UpdateStatus::OutOfDate(ref latest) if latest == "1.1.0")
+    );
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
@@ -172,3 +174,59 @@ async fn banner_needs_update_returns_unknown_on_json_without_correct_tag() {
     assert_eq!(mock.hits(), 1);
     assert!(matches!(banner.update_status, UpdateStatus::Unknown));
 }
+
+#[test]
+/// runs of three or more codes within a class should be collapsed into ranges
+fn banner_status_code_ranges_collapse_runs() {
+    assert_eq!(
+        status_code_ranges(&[200, 204, 301, 302, 307, 308, 401, 403, 405]),
+        vec!["200", "204", "301", "302", "307", "308", "401", "403", "405"]
+    );
+
+    let mut codes: Vec<u16> = (200..400).collect();
+    codes.push(401);
+    codes.push(200);
+    assert_eq!(
+        status_code_ranges(&codes),
+        vec!["200-299", "300-399", "401"]
+    );
+
+    assert_eq!(
+        status_code_ranges(&[500, 501, 502, 503, 404]),
+        vec!["404", "500-503"]
+    );
+}
+
+#[test]
+/// certificates should be shown after targets, without suggesting hosts that are already targets
+fn banner_shows_certificates_and_suggested_targets() {
+    let config = Configuration::new().unwrap();
+    let targets = [
+        String::from("https://www.example.com/"),
+        String::from("https://api.example.com/"),
+    ];
+
+    let certificate = crate::certificate::Certificate {
+        target: targets[0].clone(),
+        common_name: String::from("www.example.com"),
+        sans: vec![
+            String::from("www.example.com"),
+            String::from("api.example.com"),
+            String::from("dev.example.com"),
+        ],
+        not_after: String::from("Jan  1 00:00:00 2027 GMT"),
+        days_left: 77,
+    };
+
+    let mut banner = Banner::new(&targets, &config);
+    banner.add_certificates(&[certificate], &targets);
+
+    let mut output = Vec::new();
+    banner.print_to(&mut output, Arc::new(config)).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains("CN: www.example.com, expires: Jan  1 00:00:00 2027 GMT"));
+    assert!(output.contains("[www.example.com, api.example.com, dev.example.com]"));
+    assert!(output.contains("https://dev.example.com/"));
+    assert_eq!(output.matches("Suggested Target").count(), 1);
+}
@@ -0,0 +1,711 @@
+//! --bypass-403; reported 403s are requested again using well-known tricks that get past
+//! misconfigured access controls (path normalization differences, url rewriting headers, ip
+//! allow-lists, method specific rules), and each trick that gets a non-error response is reported
+//!
+//! spoofed extensions (i.e. /admin;.css) are also attempted against 401s, since authentication is
+//! often skipped for anything that looks like a static file
+//!
+//! `.` and `%2e` path segments (i.e. /%2e/admin or /admin/.) are collapsed while the url is
+//! parsed, before anything is sent, so the `;` path parameter forms are used in their place
+//!
+//! every attempt is counted against --bypass-budget, which is shared by all scans, and waits on the
+//! scan's rate limit; no resource is attempted more than once, and only the first few forbidden
+//! resources of each directory are attempted at all
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use lazy_static::lazy_static;
+use reqwest::{Method, StatusCode, Url};
+use uuid::Uuid;
+
+use crate::{
+    config::Configuration,
+    event_handlers::{
+        Command::{self, AddToUsizeField},
+        Handles,
+    },
+    response::FeroxResponse,
+    scanner::RateGate,
+    send_command,
+    statistics::StatField::TotalExpected,
+    utils::method_request,
+};
+
+/// names of all bypass techniques, as given to --bypass-techniques
+pub const TECHNIQUES: [&str; 8] = [
+    "path-param",
+    "double-slash",
+    "trailing-chars",
+    "case-switch",
+    "rewrite-headers",
+    "ip-headers",
+    "method",
+    "extension-spoof",
+];
+
+/// headers that some applications trust to hold the client's address
+const IP_HEADERS: [&str; 5] = [
+    "X-Forwarded-For",
+    "X-Real-IP",
+    "X-Originating-IP",
+    "X-Custom-IP-Authorization",
+    "Client-IP",
+];
+
+/// headers that some frameworks and proxies use to override the requested path
+const REWRITE_HEADERS: [&str; 2] = ["X-Original-URL", "X-Rewrite-URL"];
+
+/// appended to the forbidden path by the trailing-chars technique
+const TRAILING_CHARS: [&str; 3] = ["%20", "%09", "/*"];
+
+/// static file extensions, behind a delimiter that the access check doesn't stop at, appended to
+/// the protected path by the extension-spoof technique
+const SPOOFED_EXTENSIONS: [&str; 3] = [";.css", "%00.png", "?.js"];
+
+/// number of forbidden resources per directory that bypasses are attempted against; a directory
+/// full of 403s is most likely behind a single rule, which the first few attempts already test
+const PER_DIRECTORY: usize = 5;
+
+lazy_static! {
+    /// urls that bypasses were already attempted against
+    static ref ATTEMPTED: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+
+    /// number of forbidden resources attempted in each directory
+    static ref DIRECTORIES: Mutex<HashMap<String, usize>> = Mutex::new(HashMap::new());
+}
+
+/// number of bypass requests sent so far, across all scans
+static SPENT: AtomicUsize = AtomicUsize::new(0);
+
+/// whether or not the user was told that --bypass-budget ran out
+static EXHAUSTED: AtomicBool = AtomicBool::new(false);
+
+/// A way of getting past a 403 (or 401)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Technique {
+    /// ;-delimited path parameters, i.e. /.;/admin and /admin..;/
+    PathParam,
+
+    /// doubled slashes, i.e. //admin//
+    DoubleSlash,
+
+    /// characters that are stripped before the access check, i.e. /admin%20
+    TrailingChars,
+
+    /// the last path segment in a different case, i.e. /ADMIN
+    CaseSwitch,
+
+    /// the path requested through X-Original-URL and X-Rewrite-URL
+    RewriteHeaders,
+
+    /// headers claiming the request came from localhost, i.e. X-Forwarded-For: 127.0.0.1
+    IpHeaders,
+
+    /// POST instead of GET
+    Method,
+
+    /// a static file's extension, i.e. /admin;.css or /admin?.js
+    ExtensionSpoof,
+}
+
+/// implementation of Technique
+impl Technique {
+    /// technique with the given name, i.e. path-param
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "path-param" => Some(Technique::PathParam),
+            "double-slash" => Some(Technique::DoubleSlash),
+            "trailing-chars" => Some(Technique::TrailingChars),
+            "case-switch" => Some(Technique::CaseSwitch),
+            "rewrite-headers" => Some(Technique::RewriteHeaders),
+            "ip-headers" => Some(Technique::IpHeaders),
+            "method" => Some(Technique::Method),
+            "extension-spoof" => Some(Technique::ExtensionSpoof),
+            _ => None,
+        }
+    }
+
+    /// name of the technique, as given to --bypass-techniques
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::PathParam => "path-param",
+            Technique::DoubleSlash => "double-slash",
+            Technique::TrailingChars => "trailing-chars",
+            Technique::CaseSwitch => "case-switch",
+            Technique::RewriteHeaders => "rewrite-headers",
+            Technique::IpHeaders => "ip-headers",
+            Technique::Method => "method",
+            Technique::ExtensionSpoof => "extension-spoof",
+        }
+    }
+
+    /// whether or not this technique is attempted against responses with the given status
+    pub fn applies_to(&self, status: StatusCode) -> bool {
+        match self {
+            Technique::ExtensionSpoof => {
+                status == StatusCode::FORBIDDEN || status == StatusCode::UNAUTHORIZED
+            }
+            _ => status == StatusCode::FORBIDDEN,
+        }
+    }
+
+    /// techniques enabled by the given --bypass-techniques; all of them when none were given
+    pub fn enabled(names: &[String]) -> Vec<Self> {
+        if names.is_empty() {
+            return TECHNIQUES
+                .iter()
+                .filter_map(|n| Self::from_name(n))
+                .collect();
+        }
+
+        names.iter().filter_map(|n| Self::from_name(n)).collect()
+    }
+
+    /// requests that attempt this technique against the given forbidden url
+    fn attempts(self, url: &Url) -> Vec<Attempt> {
+        let path = url.path();
+        let trimmed = path.trim_end_matches('/');
+        let trailing = if path.ends_with('/') { "/" } else { "" };
+
+        // parent directory (with its trailing slash) and last segment of the path
+        let (dir, name) = match trimmed.rfind('/') {
+            Some(index) => (&trimmed[..=index], &trimmed[index + 1..]),
+            None => ("/", trimmed),
+        };
+
+        let with_path = |new_path: &str| {
+            let mut url = url.clone();
+            url.set_path(new_path);
+            Attempt::new(self, Method::GET, url)
+        };
+
+        match self {
+            Technique::RewriteHeaders => {
+                let mut root = url.clone();
+                root.set_path("/");
+                root.set_query(None);
+
+                REWRITE_HEADERS
+                    .iter()
+                    .map(|header| {
+                        Attempt::new(self, Method::GET, root.clone())
+                            .header(*header, path)
+                            .against_baseline(root.clone())
+                    })
+                    .collect()
+            }
+            Technique::IpHeaders => {
+                let attempt = IP_HEADERS.iter().fold(
+                    Attempt::new(self, Method::GET, url.clone()),
+                    |attempt, header| attempt.header(*header, "127.0.0.1"),
+                );
+                vec![attempt]
+            }
+            Technique::Method => {
+                vec![Attempt::new(self, Method::POST, url.clone()).header("Content-Length", "0")]
+            }
+            _ if name.is_empty() => {
+                // the target's root; there's no segment to play with
+                Vec::new()
+            }
+            Technique::PathParam => vec![
+                with_path(&format!("{}.;/{}{}", dir, name, trailing)),
+                with_path(&format!("{}{}..;/", dir, name)),
+            ],
+            Technique::DoubleSlash => vec![with_path(&format!("{}/{}//", dir, name))],
+            Technique::TrailingChars => {
+                let mut attempts: Vec<Attempt> = TRAILING_CHARS
+                    .iter()
+                    .map(|chars| with_path(&format!("{}{}", trimmed, chars)))
+                    .collect();
+
+                if url.query().is_none() {
+                    // an empty query string, i.e. /admin?
+                    let mut attempt = with_path(path);
+                    attempt.url.set_query(Some(""));
+                    attempts.push(attempt);
+                }
+
+                attempts
+            }
+            Technique::ExtensionSpoof => {
+                // a name that doesn't exist, with the same extension; a catch-all for static
+                // files answers it the same way it answers the spoofed path
+                let decoy = format!("{}{}", dir, Uuid::new_v4().to_simple());
+
+                let spoofed = |path: &str, extension: &str| {
+                    let mut base = url.clone();
+                    base.set_path(path);
+                    base.set_query(None);
+                    base.set_fragment(None);
+                    Url::parse(&format!("{}{}", base, extension)).ok()
+                };
+
+                SPOOFED_EXTENSIONS
+                    .iter()
+                    .filter_map(|extension| {
+                        let spoofed_url = spoofed(trimmed, extension)?;
+                        let decoy_url = spoofed(&decoy, extension)?;
+
+                        Some(
+                            Attempt::new(self, Method::GET, spoofed_url)
+                                .against_baseline(decoy_url),
+                        )
+                    })
+                    .collect()
+            }
+            Technique::CaseSwitch => {
+                let upper = name.to_uppercase();
+
+                let switched = if upper != name {
+                    upper
+                } else {
+                    name.to_lowercase()
+                };
+
+                if switched == name {
+                    // nothing that has a case, i.e. /123
+                    return Vec::new();
+                }
+
+                vec![with_path(&format!("{}{}{}", dir, switched, trailing))]
+            }
+        }
+    }
+}
+
+/// A single request made while attempting a bypass
+#[derive(Debug, Clone)]
+struct Attempt {
+    /// technique being attempted
+    technique: Technique,
+
+    /// request method
+    method: Method,
+
+    /// requested url
+    url: Url,
+
+    /// additional request headers
+    headers: Vec<(&'static str, String)>,
+
+    /// url whose plain response the response needs to differ from, when the technique could be
+    /// answered without getting past anything (i.e. the target's root for rewrite-headers)
+    baseline: Option<Url>,
+}
+
+/// implementation of Attempt
+impl Attempt {
+    /// GET (or other method) of the given url, without any additional headers
+    fn new(technique: Technique, method: Method, url: Url) -> Self {
+        Self {
+            technique,
+            method,
+            url,
+            headers: Vec::new(),
+            baseline: None,
+        }
+    }
+
+    /// add a header to the request
+    fn header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+
+    /// require the response to differ from a plain request for the given url
+    fn against_baseline(mut self, url: Url) -> Self {
+        self.baseline = Some(url);
+        self
+    }
+
+    /// method, url, and headers of the request, i.e. GET http://localhost/ (X-Original-URL: /admin)
+    fn describe(&self) -> String {
+        if self.headers.is_empty() {
+            return format!("{} {}", self.method, self.url);
+        }
+
+        let headers: Vec<String> = self
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect();
+
+        format!("{} {} ({})", self.method, self.url, headers.join(", "))
+    }
+}
+
+/// A request that got past a forbidden resource's access control
+#[derive(Debug, Clone)]
+pub struct Bypass {
+    /// the forbidden url
+    pub url: String,
+
+    /// name of the technique that worked
+    pub technique: String,
+
+    /// method, url, and headers of the request that worked
+    pub request: String,
+
+    /// status of the forbidden url
+    pub original_status: u16,
+
+    /// status of the request that worked
+    pub status: u16,
+
+    /// content length of the request that worked
+    pub content_length: u64,
+}
+
+/// Display implementation for Bypass
+impl fmt::Display for Bypass {
+    /// forbidden url, change in status, and the request that caused it, i.e.
+    /// http://localhost/admin 403 => 200 (case-switch: GET http://localhost/ADMIN)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} => {} ({}: {})",
+            self.url, self.original_status, self.status, self.technique, self.request
+        )
+    }
+}
+
+/// Determine whether bypasses should be attempted against the given (reported) response
+pub fn should_attempt(response: &FeroxResponse, config: &Configuration) -> bool {
+    config.bypass_403
+        && Technique::enabled(&config.bypass_techniques)
+            .iter()
+            .any(|technique| technique.applies_to(*response.status()))
+}
+
+/// directory that holds the given resource, i.e. http://localhost/app/ for http://localhost/app/admin
+/// and http://localhost/app/admin/
+fn directory_of(url: &Url) -> String {
+    let mut resource = url.clone();
+    resource.set_query(None);
+    resource.set_fragment(None);
+
+    let path = resource.path().trim_end_matches('/').to_string();
+    resource.set_path(&path);
+
+    resource
+        .join("./")
+        .map_or_else(|_| url.to_string(), |directory| directory.to_string())
+}
+
+/// whether bypasses should be attempted against the given url: it wasn't attempted before, and
+/// its directory hasn't used up its PER_DIRECTORY attempts
+fn claim(url: &Url) -> bool {
+    match ATTEMPTED.lock() {
+        Ok(mut guard) => {
+            if !guard.insert(url.to_string()) {
+                return false;
+            }
+        }
+        Err(_) => return false,
+    }
+
+    let directory = directory_of(url);
+
+    match DIRECTORIES.lock() {
+        Ok(mut guard) => {
+            let attempted = guard.entry(directory.clone()).or_insert(0);
+            *attempted += 1;
+
+            if *attempted == PER_DIRECTORY + 1 {
+                log::info!(
+                    "bypasses were attempted against {} resources in {}, skipping the rest",
+                    PER_DIRECTORY,
+                    directory
+                );
+            }
+
+            *attempted <= PER_DIRECTORY
+        }
+        Err(_) => false,
+    }
+}
+
+/// take one request from --bypass-budget, returning false once it's spent
+fn spend(budget: usize) -> bool {
+    if SPENT.fetch_add(1, Ordering::SeqCst) < budget {
+        return true;
+    }
+
+    if !EXHAUSTED.swap(true, Ordering::SeqCst) {
+        log::warn!(
+            "--bypass-budget of {} requests spent, no further bypasses will be attempted",
+            budget
+        );
+    }
+
+    false
+}
+
+/// send a single attempt's request, once the scan's rate limit allows it
+async fn send(attempt: &Attempt, gate: &RateGate, handles: &Arc<Handles>) -> Option<FeroxResponse> {
+    gate.wait().await;
+
+    let response = method_request(
+        attempt.method.clone(),
+        &attempt.url,
+        &attempt.headers,
+        handles,
+    )
+    .await
+    .ok()?;
+
+    Some(FeroxResponse::from(response, true, handles.config.output_level).await)
+}
+
+/// Attempt every enabled technique against the given forbidden url, which got the given status,
+/// reporting each one that gets a non-error response that isn't a wildcard, filtered, or the same
+/// as its baseline
+///
+/// meant to be spawned as one of the scan's follow-ups; every request waits on the given gate
+pub async fn attempt(forbidden: Url, status: StatusCode, gate: RateGate, handles: Arc<Handles>) {
+    log::trace!("enter: attempt({}, {})", forbidden, status);
+
+    if !claim(&forbidden) {
+        log::trace!("exit: attempt");
+        return;
+    }
+
+    let config = handles.config.clone();
+
+    let attempts: Vec<Attempt> = Technique::enabled(&config.bypass_techniques)
+        .into_iter()
+        .filter(|technique| technique.applies_to(status))
+        .flat_map(|technique| technique.attempts(&forbidden))
+        .collect();
+
+    // plain responses of the urls that attempts need to differ from, requested as needed
+    let mut baselines: Vec<(Url, Option<FeroxResponse>)> = Vec::new();
+
+    for attempt in attempts {
+        if let Some(baseline) = &attempt.baseline {
+            if !baselines.iter().any(|(url, _)| url == baseline) {
+                if !spend(config.bypass_budget) {
+                    break;
+                }
+
+                send_command!(handles.stats.tx, AddToUsizeField(TotalExpected, 1));
+
+                let plain = Attempt::new(attempt.technique, Method::GET, baseline.clone());
+                baselines.push((baseline.clone(), send(&plain, &gate, &handles).await));
+            }
+        }
+
+        if !spend(config.bypass_budget) {
+            break;
+        }
+
+        send_command!(handles.stats.tx, AddToUsizeField(TotalExpected, 1));
+
+        let response = match send(&attempt, &gate, &handles).await {
+            Some(response) => response,
+            None => continue,
+        };
+
+        if response.status().is_client_error() || response.status().is_server_error() {
+            continue;
+        }
+
+        if let Some(baseline) = &attempt.baseline {
+            let unchanged = baselines
+                .iter()
+                .find(|(url, _)| url == baseline)
+                .and_then(|(_, baseline)| baseline.as_ref())
+                .map_or(true, |baseline| {
+                    baseline.status() == response.status()
+                        && baseline.content_length() == response.content_length()
+                });
+
+            if unchanged {
+                // the trick was ignored, i.e. this is just the target's root
+                continue;
+            }
+        }
+
+        let is_wildcard = handles.ferox_scans().map_or(false, |scans| {
+            scans.is_wildcard(&response, handles.stats.tx.clone())
+        });
+
+        if is_wildcard
+            || handles
+                .filters
+                .data
+                .should_filter_response(&response, handles.stats.tx.clone())
+        {
+            continue;
+        }
+
+        let bypass = Bypass {
+            url: forbidden.to_string(),
+            technique: attempt.technique.name().to_string(),
+            request: attempt.describe(),
+            original_status: status.as_u16(),
+            status: response.status().as_u16(),
+            content_length: response.content_length(),
+        };
+
+        log::info!("bypassed {}", bypass);
+
+        if let Err(e) = handles.output.send(Command::ReportBypass(Box::new(bypass))) {
+            log::warn!("Could not send bypass to output handler: {}", e);
+        }
+    }
+
+    log::trace!("exit: attempt");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// urls requested by the given technique against the given url
+    fn urls(technique: Technique, url: &str) -> Vec<String> {
+        technique
+            .attempts(&Url::parse(url).unwrap())
+            .iter()
+            .map(|attempt| attempt.url.to_string())
+            .collect()
+    }
+
+    #[test]
+    /// every technique should be reachable by its name, and no names means all of them
+    fn technique_names_round_trip() {
+        for name in &TECHNIQUES {
+            assert_eq!(Technique::from_name(name).unwrap().name(), *name);
+        }
+
+        assert_eq!(Technique::enabled(&[]).len(), TECHNIQUES.len());
+        assert_eq!(
+            Technique::enabled(&[String::from("method"), String::from("bogus")]),
+            vec![Technique::Method]
+        );
+    }
+
+    #[test]
+    /// path techniques should only touch the last segment of the forbidden path
+    fn path_techniques_permute_last_segment() {
+        let url = "http://localhost/app/admin";
+
+        assert_eq!(
+            urls(Technique::PathParam, url),
+            vec![
+                "http://localhost/app/.;/admin",
+                "http://localhost/app/admin..;/"
+            ]
+        );
+        assert_eq!(
+            urls(Technique::DoubleSlash, url),
+            vec!["http://localhost/app//admin//"]
+        );
+        assert_eq!(
+            urls(Technique::TrailingChars, url),
+            vec![
+                "http://localhost/app/admin%20",
+                "http://localhost/app/admin%09",
+                "http://localhost/app/admin/*",
+                "http://localhost/app/admin?"
+            ]
+        );
+        assert_eq!(
+            urls(Technique::CaseSwitch, "http://localhost/app/admin/"),
+            vec!["http://localhost/app/ADMIN/"]
+        );
+        assert!(urls(Technique::CaseSwitch, "http://localhost/123").is_empty());
+        assert!(urls(Technique::PathParam, "http://localhost/").is_empty());
+    }
+
+    #[test]
+    /// header techniques should keep the path, and rewrites should be sent to the root
+    fn header_techniques_describe_their_headers() {
+        let url = Url::parse("http://localhost/app/admin").unwrap();
+
+        let rewrites = Technique::RewriteHeaders.attempts(&url);
+        assert_eq!(rewrites.len(), 2);
+        assert_eq!(
+            rewrites[0].baseline,
+            Some(Url::parse("http://localhost/").unwrap())
+        );
+        assert_eq!(
+            rewrites[0].describe(),
+            "GET http://localhost/ (X-Original-URL: /app/admin)"
+        );
+
+        let ip = Technique::IpHeaders.attempts(&url);
+        assert_eq!(ip[0].url, url);
+        assert_eq!(ip[0].headers.len(), IP_HEADERS.len());
+
+        let method = Technique::Method.attempts(&url);
+        assert_eq!(
+            method[0].describe(),
+            "POST http://localhost/app/admin (Content-Length: 0)"
+        );
+    }
+
+    #[test]
+    /// spoofed extensions should follow the protected path, each compared against a decoy path
+    /// with the same extension, and should be the only technique attempted against 401s
+    fn extension_spoof_appends_static_extensions() {
+        let url = Url::parse("http://localhost/app/admin/?id=1").unwrap();
+        let attempts = Technique::ExtensionSpoof.attempts(&url);
+
+        let spoofed: Vec<String> = attempts.iter().map(|a| a.url.to_string()).collect();
+        assert_eq!(
+            spoofed,
+            vec![
+                "http://localhost/app/admin;.css",
+                "http://localhost/app/admin%00.png",
+                "http://localhost/app/admin?.js"
+            ]
+        );
+
+        for (attempt, extension) in attempts.iter().zip(SPOOFED_EXTENSIONS.iter()) {
+            let decoy = attempt.baseline.as_ref().unwrap().to_string();
+            assert!(decoy.starts_with("http://localhost/app/"));
+            assert!(decoy.ends_with(extension));
+            assert!(!decoy.contains("admin"));
+        }
+
+        assert!(Technique::ExtensionSpoof.applies_to(StatusCode::UNAUTHORIZED));
+        assert!(Technique::ExtensionSpoof.applies_to(StatusCode::FORBIDDEN));
+        assert!(!Technique::PathParam.applies_to(StatusCode::UNAUTHORIZED));
+    }
+    #[test]
+    /// resources and directories share their parent directory, and each directory only gets
+    /// PER_DIRECTORY attempts
+    fn claims_are_capped_per_directory() {
+        let parent = |url: &str| directory_of(&Url::parse(url).unwrap());
+
+        assert_eq!(
+            parent("http://localhost/app/admin"),
+            "http://localhost/app/"
+        );
+        assert_eq!(
+            parent("http://localhost/app/admin/"),
+            "http://localhost/app/"
+        );
+        assert_eq!(
+            parent("http://localhost/app/admin?x=1"),
+            "http://localhost/app/"
+        );
+        assert_eq!(parent("http://localhost/admin"), "http://localhost/");
+
+        let claimed = (0..PER_DIRECTORY + 3)
+            .filter(|i| claim(&Url::parse(&format!("http://capped/dir/{}", i)).unwrap()))
+            .count();
+
+        assert_eq!(claimed, PER_DIRECTORY);
+        assert!(!claim(&Url::parse("http://capped/dir/0").unwrap()));
+        assert!(claim(&Url::parse("http://capped/other/0").unwrap()));
+    }
+}
@@ -0,0 +1,240 @@
+//! on-disk cache of responses (--cache-dir)
+//!
+//! each response is stored as its own json file, named after a hash of the request's method, url,
+//! user-agent, and configured headers (cookies included); re-running a scan against the same
+//! target reads responses from disk instead of sending the same traffic again
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use openssl::sha::sha256;
+use reqwest::{
+    header::{HeaderName, HeaderValue},
+    Response, ResponseBuilderExt, StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::config::Configuration;
+
+/// A response as it's stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedResponse {
+    /// final url of the response, after any redirects
+    url: String,
+
+    /// status code
+    status: u16,
+
+    /// (name, value) of each header, in the order they were received
+    headers: Vec<(String, String)>,
+
+    /// base64 encoded body, exactly as it was received (i.e. still compressed)
+    body: String,
+}
+
+/// Cache key of a request; a hex encoded sha256 of its method, url, user-agent, and the headers
+/// sent with it (-H and --raw-header, which is where cookies come from)
+pub fn key(method: &str, url: &Url, config: &Configuration) -> String {
+    let mut headers: Vec<_> = config
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name.to_lowercase(), value))
+        .collect();
+    headers.sort();
+
+    let mut material = format!("{}\n{}\nuser-agent: {}\n", method, url, config.user_agent);
+
+    for header in headers.iter().chain(config.raw_headers.iter()) {
+        material.push_str(header);
+        material.push('\n');
+    }
+
+    sha256(material.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Path of the file in which the response for the given key is stored
+fn path(cache_dir: &str, key: &str) -> PathBuf {
+    Path::new(cache_dir).join(format!("{}.json", key))
+}
+
+/// Rebuild a reqwest::Response from its parts
+fn build(
+    url: Url,
+    status: StatusCode,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+) -> Result<Response> {
+    let mut builder = hyper::Response::builder().status(status).url(url);
+
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())?;
+        let value = HeaderValue::from_str(&value)?;
+        builder = builder.header(name, value);
+    }
+
+    Ok(Response::from(builder.body(body)?))
+}
+
+/// Read the response stored for the given key, if there is one
+pub async fn load(cache_dir: &str, key: &str) -> Option<Response> {
+    let contents = fs::read_to_string(path(cache_dir, key)).await.ok()?;
+
+    let cached: CachedResponse = match serde_json::from_str(&contents) {
+        Ok(cached) => cached,
+        Err(e) => {
+            log::warn!("Could not parse cached response {}: {}", key, e);
+            return None;
+        }
+    };
+
+    let url = Url::parse(&cached.url).ok()?;
+    let status = StatusCode::from_u16(cached.status).ok()?;
+    let body = base64::decode(&cached.body).ok()?;
+
+    build(url, status, cached.headers, body)
+        .map_err(|e| log::warn!("Could not rebuild cached response {}: {}", key, e))
+        .ok()
+}
+
+/// Write the given response to disk under the given key
+async fn write(cache_dir: &str, key: &str, cached: &CachedResponse) -> Result<()> {
+    fs::create_dir_all(cache_dir)
+        .await
+        .with_context(|| format!("Could not create {}", cache_dir))?;
+
+    let file = path(cache_dir, key);
+    fs::write(&file, serde_json::to_string(cached)?)
+        .await
+        .with_context(|| format!("Could not write {}", file.display()))
+}
+
+/// Store the given response under the given key
+///
+/// the body has to be read in order to be stored, so the response is consumed; an equivalent
+/// response is returned in its place, whether or not it could be written to disk. an error is
+/// only returned when the body couldn't be read
+pub async fn store(cache_dir: &str, key: &str, response: Response) -> Result<Response> {
+    let url = response.url().clone();
+    let status = response.status();
+
+    let headers: Vec<(String, String)> = response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.as_str().to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            )
+        })
+        .collect();
+
+    let body = response.bytes().await?.to_vec();
+
+    let cached = CachedResponse {
+        url: url.to_string(),
+        status: status.as_u16(),
+        headers: headers.clone(),
+        body: base64::encode(&body),
+    };
+
+    if let Err(e) = write(cache_dir, key, &cached).await {
+        log::warn!("Could not cache response for {}: {:#}", url, e);
+    }
+
+    build(url, status, headers, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// keys should only depend on the method, url, user-agent, and headers; not the order of the
+    /// headers
+    fn key_is_stable() {
+        let url = Url::parse("http://localhost/admin").unwrap();
+
+        let mut config = Configuration::default();
+        config
+            .headers
+            .insert(String::from("X-One"), String::from("1"));
+        config
+            .headers
+            .insert(String::from("X-Two"), String::from("2"));
+
+        let first = key("GET", &url, &config);
+        assert_eq!(first.len(), 64);
+        assert_eq!(first, key("GET", &url, &config.clone()));
+
+        assert_ne!(first, key("HEAD", &url, &config));
+        assert_ne!(first, key("GET", &url, &Configuration::default()));
+        assert_ne!(
+            first,
+            key(
+                "GET",
+                &Url::parse("http://localhost/admin/").unwrap(),
+                &config
+            )
+        );
+
+        let agent = Configuration {
+            user_agent: String::from("curl/7.68.0"),
+            ..config.clone()
+        };
+        assert_ne!(first, key("GET", &url, &agent));
+
+        let cookie = Configuration {
+            raw_headers: vec![String::from("Cookie: session=1")],
+            ..config.clone()
+        };
+        assert_ne!(first, key("GET", &url, &cookie));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// a stored response should come back with the same url, status, headers, and body
+    async fn store_and_load_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache_dir = tmp.path().join("cache");
+        let cache_dir = cache_dir.to_str().unwrap();
+
+        let url = Url::parse("http://localhost/admin").unwrap();
+        let headers = vec![(String::from("server"), String::from("nginx"))];
+        let response = build(
+            url.clone(),
+            StatusCode::FORBIDDEN,
+            headers,
+            b"nope".to_vec(),
+        )
+        .unwrap();
+
+        let returned = store(cache_dir, "abc", response).await.unwrap();
+        assert_eq!(returned.text().await.unwrap(), "nope");
+
+        let loaded = load(cache_dir, "abc").await.unwrap();
+        assert_eq!(loaded.url(), &url);
+        assert_eq!(loaded.status(), StatusCode::FORBIDDEN);
+        assert_eq!(loaded.headers()["server"], "nginx");
+        assert_eq!(loaded.text().await.unwrap(), "nope");
+
+        assert!(load(cache_dir, "missing").await.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// a response that can't be written to disk should still be handed back
+    async fn store_keeps_response_when_write_fails() {
+        let tmp = tempfile::tempdir().unwrap();
+        let not_a_dir = tmp.path().join("file");
+        std::fs::write(&not_a_dir, "").unwrap();
+
+        let url = Url::parse("http://localhost/admin").unwrap();
+        let response = build(url, StatusCode::OK, Vec::new(), b"kept".to_vec()).unwrap();
+
+        let returned = store(not_a_dir.to_str().unwrap(), "abc", response)
+            .await
+            .unwrap();
+        assert_eq!(returned.text().await.unwrap(), "kept");
+    }
+}
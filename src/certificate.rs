@@ -0,0 +1,972 @@
+//! certificate details of https targets, captured once at the start of a scan and shown in the
+//! banner
+//!
+//! hostnames found in a certificate's subject alternative names that share the target's
+//! registrable domain (per the public suffix list) are suggested as additional targets; they're only shown, never scanned automatically
+//!
+//! also home to --pin-sha256, which checks each server's certificate against the given pins
+//! during the tls handshake, before any request is sent to it
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    fmt, fs,
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use futures::{stream, StreamExt};
+use lazy_static::lazy_static;
+use openssl::{
+    asn1::Asn1Time,
+    nid::Nid,
+    sha::sha256,
+    ssl::{HandshakeError, SslConnector, SslMethod, SslVerifyMode},
+    x509::{X509Ref, X509VerifyResult, X509},
+};
+use reqwest::{tls::TlsInfo, Client, Proxy, Response, Url};
+use tokio::{sync::Mutex, task, time::Duration};
+
+use crate::config::{Configuration, OutputLevel};
+
+/// optional prefix of a --pin-sha256 value, the same one curl's --pinnedpubkey uses
+const PIN_PREFIX: &str = "sha256//";
+
+/// ICANN section of the public suffix list (https://publicsuffix.org/list/)
+const PUBLIC_SUFFIX_LIST: &str = include_str!("public-suffix-list.dat");
+
+lazy_static! {
+    /// rules of the public suffix list, wildcard (`*.`) and exception (`!`) rules included
+    static ref PUBLIC_SUFFIXES: HashSet<&'static str> = PUBLIC_SUFFIX_LIST
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .collect();
+}
+
+/// Registrable domain of the given hostname: its public suffix plus one more label, i.e.
+/// example.co.uk for www.example.co.uk; None when the hostname is itself a public suffix
+pub fn registrable_domain(host: &str) -> Option<String> {
+    let host = host.trim_end_matches('.').to_lowercase();
+    let labels: Vec<&str> = host.split('.').collect();
+
+    if labels.iter().any(|label| label.is_empty()) {
+        return None;
+    }
+
+    // index of the public suffix's first label; checked longest first, so the first rule that
+    // matches wins. a host no rule matches falls back to the implicit `*` rule, its last label
+    let mut start = labels.len() - 1;
+
+    for i in 0..labels.len() {
+        let candidate = labels[i..].join(".");
+
+        if PUBLIC_SUFFIXES.contains(format!("!{}", candidate).as_str()) {
+            // exception rules carve a registrable domain out of a wildcard rule
+            start = i + 1;
+            break;
+        }
+
+        let wildcard = i + 1 < labels.len()
+            && PUBLIC_SUFFIXES.contains(format!("*.{}", labels[i + 1..].join(".")).as_str());
+
+        if wildcard || PUBLIC_SUFFIXES.contains(candidate.as_str()) {
+            start = i;
+            break;
+        }
+    }
+
+    if start == 0 {
+        return None;
+    }
+
+    Some(labels[start - 1..].join("."))
+}
+
+/// Pin of the given DER encoded certificate: the base64 encoded sha256 hash of its public key
+/// (SubjectPublicKeyInfo), which stays the same when a certificate is renewed with the same key
+pub fn public_key_pin(der: &[u8]) -> Result<String> {
+    let cert = X509::from_der(der)?;
+
+    pin_of(&cert)
+}
+
+/// Pin of the given certificate, see public_key_pin
+fn pin_of(cert: &X509Ref) -> Result<String> {
+    let public_key = cert.public_key()?.public_key_to_der()?;
+
+    Ok(base64::encode(sha256(&public_key)))
+}
+
+/// whether the given pin is one of the trusted pins, which may carry the sha256// prefix
+fn is_pinned(pin: &str, pins: &[String]) -> bool {
+    pins.iter()
+        .any(|trusted| trusted.trim_start_matches(PIN_PREFIX) == pin)
+}
+
+/// Ensure the certificate an https response arrived over matches one of the given pins; with no
+/// pins, every response passes
+///
+/// servers are checked by PinVerifier before any request is sent to them; this catches responses
+/// that arrive over a connection that wasn't, i.e. after a redirect to another server
+pub fn check_pins(response: &Response, pins: &[String]) -> Result<()> {
+    if pins.is_empty() || response.url().scheme() != "https" {
+        return Ok(());
+    }
+
+    let der = response
+        .extensions()
+        .get::<TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+        .ok_or_else(|| {
+            anyhow!(
+                "{} presented no certificate to check pins against",
+                response.url()
+            )
+        })?;
+
+    verify_pin(der, pins, response.url().as_str())
+}
+
+/// Ensure the given DER encoded certificate, presented by `source`, matches one of the given pins
+pub fn verify_pin(der: &[u8], pins: &[String], source: &str) -> Result<()> {
+    let pin = public_key_pin(der)?;
+
+    if is_pinned(&pin, pins) {
+        Ok(())
+    } else {
+        bail!(
+            "certificate of {} doesn't match any --pin-sha256 (its pin is {}{})",
+            source,
+            PIN_PREFIX,
+            pin
+        )
+    }
+}
+
+/// Build a tls connector for connections made outside of the http client (--raw, PinVerifier)
+///
+/// certificates are validated the same way the http client validates them, with --ca-cert trusted
+/// in addition to the system's CAs; when pins are given, the server's certificate must also match
+/// one of them. --insecure skips validation of the chain, never the pins
+pub fn tls_connector(insecure: bool, ca_cert: &str, pins: &[String]) -> Result<SslConnector> {
+    let mut builder = SslConnector::builder(SslMethod::tls())?;
+
+    if !ca_cert.is_empty() {
+        let pem = fs::read(ca_cert).with_context(|| format!("Could not read {}", ca_cert))?;
+
+        for cert in X509::stack_from_pem(&pem)? {
+            builder.cert_store_mut().add_cert(cert)?;
+        }
+    }
+
+    if pins.is_empty() {
+        if insecure {
+            builder.set_verify(SslVerifyMode::NONE);
+        }
+    } else {
+        let pins = pins.to_vec();
+
+        builder.set_verify_callback(SslVerifyMode::PEER, move |verified, ctx| {
+            let verified = verified || insecure;
+
+            if !verified || ctx.error_depth() != 0 {
+                // pins are checked against the server's own certificate, the last one verified
+                return verified;
+            }
+
+            let pinned = ctx
+                .current_cert()
+                .and_then(|cert| pin_of(cert).ok())
+                .map_or(false, |pin| is_pinned(&pin, &pins));
+
+            if !pinned {
+                // lets the handshake's error be told apart from other verification failures
+                ctx.set_error(X509VerifyResult::APPLICATION_VERIFICATION);
+            }
+
+            pinned
+        });
+    }
+
+    Ok(builder.build())
+}
+
+/// A server whose certificate was rejected by PinVerifier, as opposed to one that couldn't be
+/// reached
+#[derive(Debug, Clone)]
+pub struct Untrusted(String);
+
+/// Display implementation for Untrusted
+impl fmt::Display for Untrusted {
+    /// why the certificate was rejected
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Untrusted {}
+
+/// Checks the certificates of https servers against --pin-sha256 before the http client sends
+/// them anything
+///
+/// the http client can't be given a verifier of its own, so the first request to each server
+/// (host and port) waits on a handshake made with tls_connector; nothing is sent to a server
+/// until its handshake succeeds. the outcome is remembered for the rest of the scan, unless the
+/// handshake couldn't be made at all (i.e. a timeout), in which case the next request tries again
+pub struct PinVerifier {
+    /// trusted pins, see is_pinned
+    pins: Vec<String>,
+
+    /// whether hostnames are verified; not with --insecure
+    verify_hostname: bool,
+
+    /// connector that enforces the pins, built once and shared by every handshake
+    connector: SslConnector,
+
+    /// --proxy, which handshakes are tunneled through, as the http client's are
+    proxy: Option<Url>,
+
+    /// --timeout, applied to connecting and to each read or write
+    timeout: Duration,
+
+    /// servers already checked; None when their certificate was trusted, the reason it wasn't
+    /// otherwise
+    verdicts: Mutex<HashMap<String, Option<String>>>,
+}
+
+/// implementation of PinVerifier
+impl PinVerifier {
+    /// verifier for the pins, trust settings, and proxy of the given Configuration
+    pub fn new(config: &Configuration) -> Result<Self> {
+        let proxy = if config.proxy.is_empty() {
+            None
+        } else {
+            Some(Url::parse(&config.proxy)?)
+        };
+
+        Ok(Self {
+            pins: config.pin_sha256.clone(),
+            verify_hostname: !config.insecure,
+            connector: tls_connector(config.insecure, &config.ca_cert, &config.pin_sha256)?,
+            proxy,
+            timeout: Duration::from_secs(config.timeout),
+            verdicts: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Ensure the certificate of the given url's server is trusted and matches one of the pins;
+    /// urls that aren't https always pass. a rejected certificate's error is Untrusted
+    pub async fn verify(&self, url: &Url) -> Result<()> {
+        if url.scheme() != "https" {
+            return Ok(());
+        }
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("{} has no host", url))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+
+        let port = url.port_or_known_default().unwrap_or(443);
+        let server = format!("{}:{}", host, port);
+
+        // held for the whole handshake, so that concurrent requests to a new server wait on a
+        // single handshake instead of each making their own
+        let mut verdicts = self.verdicts.lock().await;
+
+        let verdict = match verdicts.get(&server) {
+            Some(verdict) => verdict.clone(),
+            None => {
+                let connector = self.connector.clone();
+                let proxy = self.proxy.clone();
+                let verify_hostname = self.verify_hostname;
+                let timeout = self.timeout;
+
+                let verdict = task::spawn_blocking(move || {
+                    handshake(
+                        &connector,
+                        verify_hostname,
+                        proxy.as_ref(),
+                        &host,
+                        port,
+                        timeout,
+                    )
+                })
+                .await??;
+
+                log::debug!("pin check of {}: {:?}", server, verdict);
+                verdicts.insert(server, verdict.clone());
+                verdict
+            }
+        };
+
+        match verdict {
+            None => Ok(()),
+            Some(reason) => Err(Untrusted(reason).into()),
+        }
+    }
+
+    /// Ensure the certificate a response arrived over matches one of the pins, see check_pins
+    pub fn check(&self, response: &Response) -> Result<()> {
+        check_pins(response, &self.pins)
+    }
+}
+
+/// PinVerifier holds an SslConnector, which isn't Debug
+impl fmt::Debug for PinVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PinVerifier")
+            .field("pins", &self.pins)
+            .field("proxy", &self.proxy)
+            .finish()
+    }
+}
+
+/// tls handshake with the given server; Ok(None) when its certificate is trusted, Ok(Some(reason))
+/// when it isn't, and an error when no handshake could be made
+fn handshake(
+    connector: &SslConnector,
+    verify_hostname: bool,
+    proxy: Option<&Url>,
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<Option<String>> {
+    let tcp = match proxy {
+        Some(proxy) => tunnel(proxy, host, port, timeout)?,
+        None => connect(host, port, timeout)?,
+    };
+
+    let result = connector
+        .configure()?
+        .verify_hostname(verify_hostname)
+        .connect(host, tcp);
+
+    let server = format!("{}:{}", host, port);
+
+    match result {
+        Ok(mut stream) => {
+            // nothing is sent over the connection, it only existed for the handshake
+            let _ = stream.shutdown();
+            Ok(None)
+        }
+        Err(HandshakeError::Failure(stream)) => {
+            let ssl = stream.ssl();
+
+            match ssl.verify_result() {
+                X509VerifyResult::OK => {
+                    bail!("tls handshake with {} failed: {}", server, stream.error())
+                }
+                X509VerifyResult::APPLICATION_VERIFICATION => {
+                    let pin = ssl
+                        .peer_certificate()
+                        .and_then(|cert| pin_of(&cert).ok())
+                        .unwrap_or_default();
+
+                    Ok(Some(format!(
+                        "certificate of {} doesn't match any --pin-sha256 (its pin is {}{})",
+                        server, PIN_PREFIX, pin
+                    )))
+                }
+                result => Ok(Some(format!(
+                    "certificate of {} failed verification: {}",
+                    server,
+                    result.error_string()
+                ))),
+            }
+        }
+        Err(e) => bail!("tls handshake with {} failed: {}", server, e),
+    }
+}
+
+/// tcp connection to the given host and port, with the given timeout applied to connecting and
+/// to every read and write
+fn connect(host: &str, port: u16, timeout: Duration) -> Result<TcpStream> {
+    let mut last_error = anyhow!("could not resolve {}", host);
+
+    for address in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(tcp) => {
+                tcp.set_read_timeout(Some(timeout))?;
+                tcp.set_write_timeout(Some(timeout))?;
+                return Ok(tcp);
+            }
+            Err(e) => last_error = e.into(),
+        }
+    }
+
+    Err(last_error)
+}
+
+/// tcp connection to the given host and port through the given proxy; http proxies are asked to
+/// CONNECT, socks5 proxies are asked to connect to the host by name
+fn tunnel(proxy: &Url, host: &str, port: u16, timeout: Duration) -> Result<TcpStream> {
+    let proxy_host = proxy
+        .host_str()
+        .ok_or_else(|| anyhow!("{} has no host", proxy))?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+
+    match proxy.scheme() {
+        "http" => {
+            let mut tcp = connect(proxy_host, proxy.port().unwrap_or(80), timeout)?;
+
+            let mut request = format!(
+                "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n",
+                host = host,
+                port = port
+            );
+
+            if !proxy.username().is_empty() {
+                let credentials = format!(
+                    "{}:{}",
+                    proxy.username(),
+                    proxy.password().unwrap_or_default()
+                );
+                request.push_str(&format!(
+                    "Proxy-Authorization: Basic {}\r\n",
+                    base64::encode(credentials)
+                ));
+            }
+
+            request.push_str("\r\n");
+            tcp.write_all(request.as_bytes())?;
+
+            // the proxy's response head, read a byte at a time so that nothing past it is consumed
+            let mut head = Vec::new();
+            let mut byte = [0_u8; 1];
+
+            while !head.ends_with(b"\r\n\r\n") {
+                if tcp.read(&mut byte)? == 0 {
+                    bail!("proxy {} closed the connection", proxy);
+                }
+                head.push(byte[0]);
+            }
+
+            let status = String::from_utf8_lossy(&head)
+                .split_whitespace()
+                .nth(1)
+                .unwrap_or_default()
+                .to_string();
+
+            if status != "200" {
+                bail!(
+                    "proxy {} refused to CONNECT to {}:{} ({})",
+                    proxy,
+                    host,
+                    port,
+                    status
+                );
+            }
+
+            Ok(tcp)
+        }
+        "socks5" | "socks5h" => {
+            let mut tcp = connect(proxy_host, proxy.port().unwrap_or(1080), timeout)?;
+            let authenticate = !proxy.username().is_empty();
+
+            // greeting: version 5, offering either no authentication or username/password
+            tcp.write_all(&[5, 1, if authenticate { 2 } else { 0 }])?;
+
+            let mut reply = [0_u8; 2];
+            tcp.read_exact(&mut reply)?;
+
+            match reply {
+                [5, 0] if !authenticate => {}
+                [5, 2] if authenticate => {
+                    let username = proxy.username().as_bytes();
+                    let password = proxy.password().unwrap_or_default().as_bytes();
+
+                    let mut request = vec![1, username.len() as u8];
+                    request.extend_from_slice(username);
+                    request.push(password.len() as u8);
+                    request.extend_from_slice(password);
+                    tcp.write_all(&request)?;
+
+                    tcp.read_exact(&mut reply)?;
+
+                    if reply[1] != 0 {
+                        bail!("proxy {} rejected its credentials", proxy);
+                    }
+                }
+                _ => bail!("proxy {} doesn't accept the offered authentication", proxy),
+            }
+
+            // connect, by name: version 5, command 1, reserved, address type 3
+            let mut request = vec![5, 1, 0, 3, host.len() as u8];
+            request.extend_from_slice(host.as_bytes());
+            request.extend_from_slice(&port.to_be_bytes());
+            tcp.write_all(&request)?;
+
+            let mut reply = [0_u8; 4];
+            tcp.read_exact(&mut reply)?;
+
+            if reply[1] != 0 {
+                bail!(
+                    "proxy {} couldn't connect to {}:{} ({})",
+                    proxy,
+                    host,
+                    port,
+                    reply[1]
+                );
+            }
+
+            // the bound address that follows is of no use, but has to be read past
+            let remaining = match reply[3] {
+                1 => 4 + 2,
+                4 => 16 + 2,
+                _ => {
+                    let mut length = [0_u8; 1];
+                    tcp.read_exact(&mut length)?;
+                    length[0] as usize + 2
+                }
+            };
+            tcp.read_exact(&mut vec![0; remaining])?;
+
+            Ok(tcp)
+        }
+        scheme => bail!(
+            "--pin-sha256 can't check certificates through {} proxies",
+            scheme
+        ),
+    }
+}
+
+/// Details of the certificate presented by a single https target
+#[derive(Debug, Default, Clone)]
+pub struct Certificate {
+    /// target that presented the certificate
+    pub target: String,
+
+    /// subject's common name, empty if the certificate has none
+    pub common_name: String,
+
+    /// dns names from the subject alternative names extension
+    pub sans: Vec<String>,
+
+    /// expiry, i.e. Jan  1 00:00:00 2027 GMT
+    pub not_after: String,
+
+    /// days until expiry; negative once the certificate has expired
+    pub days_left: i32,
+}
+
+/// implementation of Certificate
+impl Certificate {
+    /// Parse the DER encoded certificate presented by the given target
+    pub fn from_der(target: &str, der: &[u8]) -> Result<Self> {
+        let cert = X509::from_der(der)?;
+
+        let common_name = cert
+            .subject_name()
+            .entries_by_nid(Nid::COMMONNAME)
+            .next()
+            .map(|entry| String::from_utf8_lossy(entry.data().as_slice()).into_owned())
+            .unwrap_or_default();
+
+        let sans = cert
+            .subject_alt_names()
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|name| name.dnsname().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let now = Asn1Time::days_from_now(0)?;
+        let days_left = now.diff(cert.not_after())?.days;
+
+        Ok(Self {
+            target: target.to_string(),
+            common_name,
+            sans,
+            not_after: cert.not_after().to_string(),
+            days_left,
+        })
+    }
+
+    /// SAN hostnames sharing the target's registrable domain, excluding wildcards and the target
+    /// itself, as urls; when the target is an ip address, the common name's domain is used instead
+    pub fn suggestions(&self) -> Vec<String> {
+        let url = match Url::parse(&self.target) {
+            Ok(url) => url,
+            Err(_) => return Vec::new(),
+        };
+
+        let host = url.host_str().unwrap_or("").to_lowercase();
+
+        let scope_host = if url.domain().is_some() {
+            host.clone()
+        } else {
+            self.common_name.to_lowercase()
+        };
+
+        let domain = match registrable_domain(&scope_host) {
+            Some(domain) => domain,
+            // nothing to compare against, i.e. localhost or co.uk
+            None => return Vec::new(),
+        };
+
+        let suffix = format!(".{}", domain);
+
+        let mut suggestions = Vec::new();
+
+        for san in &self.sans {
+            let san = san.to_lowercase();
+
+            if san.contains('*') || san == host || !(san == domain || san.ends_with(&suffix)) {
+                continue;
+            }
+
+            let suggestion = match url.port() {
+                Some(port) => format!("https://{}:{}/", san, port),
+                None => format!("https://{}/", san),
+            };
+
+            if !suggestions.contains(&suggestion) {
+                suggestions.push(suggestion);
+            }
+        }
+
+        suggestions
+    }
+}
+
+/// Display implementation for Certificate
+impl fmt::Display for Certificate {
+    /// target, common name, and expiry, i.e.
+    /// https://example.com/ (CN: example.com, expires: Jan  1 00:00:00 2027 GMT)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expiry = if self.days_left < 0 {
+            "EXPIRED"
+        } else {
+            "expires"
+        };
+
+        write!(
+            f,
+            "{} (CN: {}, {}: {})",
+            self.target, self.common_name, expiry, self.not_after
+        )
+    }
+}
+
+/// Capture the certificate of each https target; targets that can't be reached are skipped, and
+/// nothing is captured with --quiet or --silent, since there's no banner to show them in
+///
+/// a separate client is used, one that records the tls handshake and accepts any certificate, so
+/// that self-signed and expired certificates are captured whether or not --insecure was given;
+/// with --pin-sha256, servers that fail the pin check aren't contacted
+pub async fn capture(targets: &[String], config: &Configuration) -> Vec<Certificate> {
+    log::trace!("enter: capture({:?})", targets);
+
+    if !matches!(config.output_level, OutputLevel::Default) {
+        log::trace!("exit: capture -> []");
+        return Vec::new();
+    }
+
+    let client = match build_client(config) {
+        Ok(client) => client,
+        Err(e) => {
+            log::warn!("Could not build client to capture certificates: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let https: Vec<&String> = targets
+        .iter()
+        .filter(|target| target.starts_with("https://"))
+        .collect();
+
+    let captured: Vec<Option<Certificate>> = stream::iter(https)
+        .map(|target| capture_one(target, &client, config.pin_verifier.as_deref()))
+        .buffered(config.threads.max(1))
+        .collect()
+        .await;
+
+    let certificates: Vec<Certificate> = captured.into_iter().flatten().collect();
+
+    log::trace!("exit: capture -> {:?}", certificates);
+    certificates
+}
+
+/// client that records the tls handshake of every connection it makes, sending the same headers
+/// as the scan's client
+fn build_client(config: &Configuration) -> Result<Client> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(config.timeout))
+        .user_agent(&config.user_agent)
+        .default_headers((&config.headers).try_into()?)
+        .danger_accept_invalid_certs(true)
+        .tls_info(true);
+
+    if !config.proxy.is_empty() {
+        builder = builder.proxy(Proxy::all(&config.proxy)?);
+    }
+
+    Ok(builder.build()?)
+}
+
+/// certificate presented by a single target, if any
+async fn capture_one(
+    target: &str,
+    client: &Client,
+    verifier: Option<&PinVerifier>,
+) -> Option<Certificate> {
+    let result = async {
+        if let Some(verifier) = verifier {
+            // a server whose certificate doesn't match the pins isn't sent anything, not even this
+            verifier.verify(&Url::parse(target)?).await?;
+        }
+
+        let response = client.get(target).send().await?;
+
+        let der = response
+            .extensions()
+            .get::<TlsInfo>()
+            .and_then(|info| info.peer_certificate())
+            .ok_or_else(|| anyhow!("no certificate was presented"))?;
+
+        Certificate::from_der(target, der)
+    }
+    .await;
+
+    match result {
+        Ok(certificate) => Some(certificate),
+        Err(e) => {
+            log::warn!("Could not capture certificate of {}: {}", target, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TlsServer;
+    use openssl::{
+        hash::MessageDigest,
+        pkey::PKey,
+        rsa::Rsa,
+        x509::{extension::SubjectAlternativeName, X509NameBuilder},
+    };
+
+    /// self-signed certificate for the given common name and dns names, DER encoded
+    fn self_signed(common_name: &str, sans: &[&str], days: u32) -> Vec<u8> {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, common_name)
+            .unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(days).unwrap())
+            .unwrap();
+
+        let mut alt_names = SubjectAlternativeName::new();
+        for san in sans {
+            alt_names.dns(san);
+        }
+        let alt_names = alt_names
+            .build(&builder.x509v3_context(None, None))
+            .unwrap();
+        builder.append_extension(alt_names).unwrap();
+
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        builder.build().to_der().unwrap()
+    }
+
+    #[test]
+    /// common name, SANs, and expiry should all be read from the certificate
+    fn certificate_from_der_reads_subject_and_expiry() {
+        let der = self_signed(
+            "www.example.com",
+            &["www.example.com", "api.example.com"],
+            30,
+        );
+        let cert = Certificate::from_der("https://www.example.com/", &der).unwrap();
+
+        assert_eq!(cert.common_name, "www.example.com");
+        assert_eq!(cert.sans, vec!["www.example.com", "api.example.com"]);
+        assert!(cert.days_left == 29 || cert.days_left == 30);
+        assert!(cert.to_string().contains("CN: www.example.com, expires: "));
+    }
+
+    #[test]
+    /// a certificate's pin should be the hash of its public key, not of the whole certificate
+    fn public_key_pin_hashes_the_public_key() {
+        let der = self_signed("www.example.com", &[], 30);
+        let cert = X509::from_der(&der).unwrap();
+        let spki = cert.public_key().unwrap().public_key_to_der().unwrap();
+
+        let pin = public_key_pin(&der).unwrap();
+
+        assert_eq!(pin, base64::encode(sha256(&spki)));
+        assert_ne!(pin, base64::encode(sha256(&der)));
+        assert!(crate::parser::PIN_REGEX.is_match(&pin));
+    }
+
+    #[test]
+    /// only non-wildcard SANs under the target's domain, other than the target, are suggested
+    fn certificate_suggestions_stay_in_scope() {
+        let cert = Certificate {
+            target: String::from("https://www.example.com:8443/app"),
+            common_name: String::from("www.example.com"),
+            sans: vec![
+                String::from("www.example.com"),
+                String::from("API.example.com"),
+                String::from("example.com"),
+                String::from("*.example.com"),
+                String::from("example.org"),
+                String::from("notexample.com"),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            cert.suggestions(),
+            vec!["https://api.example.com:8443/", "https://example.com:8443/"]
+        );
+
+        let by_ip = Certificate {
+            target: String::from("https://10.0.0.1/"),
+            ..cert.clone()
+        };
+
+        assert_eq!(
+            by_ip.suggestions(),
+            vec![
+                "https://www.example.com/",
+                "https://api.example.com/",
+                "https://example.com/"
+            ]
+        );
+
+        let multi_label_suffix = Certificate {
+            target: String::from("https://www.example.co.uk/"),
+            common_name: String::from("www.example.co.uk"),
+            sans: vec![
+                String::from("www.example.co.uk"),
+                String::from("api.example.co.uk"),
+                String::from("other.co.uk"),
+                String::from("co.uk"),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            multi_label_suffix.suggestions(),
+            vec!["https://api.example.co.uk/"]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// a server should only be sent requests once its certificate validates and matches a pin
+    async fn pin_verifier_checks_servers_before_requests() {
+        let srv = TlsServer::start();
+        let url = Url::parse(&srv.url("/")).unwrap();
+
+        let mut ca_cert = tempfile::NamedTempFile::new().unwrap();
+        ca_cert.write_all(srv.certificate()).unwrap();
+
+        let config = Configuration {
+            ca_cert: ca_cert.path().to_str().unwrap().to_string(),
+            pin_sha256: vec![format!("{}{}", PIN_PREFIX, srv.pin())],
+            ..Default::default()
+        };
+        assert!(PinVerifier::new(&config)
+            .unwrap()
+            .verify(&url)
+            .await
+            .is_ok());
+
+        let mismatched = Configuration {
+            pin_sha256: vec![base64::encode(sha256(b"not the server's key"))],
+            ..config.clone()
+        };
+        let error = PinVerifier::new(&mismatched)
+            .unwrap()
+            .verify(&url)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("doesn't match any --pin-sha256"));
+        assert!(error.is::<Untrusted>());
+
+        // the pin alone doesn't make a self-signed certificate trusted, --insecure does
+        let untrusted = Configuration {
+            ca_cert: String::new(),
+            ..config.clone()
+        };
+        let error = PinVerifier::new(&untrusted)
+            .unwrap()
+            .verify(&url)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("failed verification"));
+
+        let insecure = Configuration {
+            insecure: true,
+            ..untrusted
+        };
+        assert!(PinVerifier::new(&insecure)
+            .unwrap()
+            .verify(&url)
+            .await
+            .is_ok());
+
+        // http urls aren't checked
+        let plain = Url::parse("http://localhost/").unwrap();
+        assert!(PinVerifier::new(&mismatched)
+            .unwrap()
+            .verify(&plain)
+            .await
+            .is_ok());
+
+        assert_eq!(srv.hits(), 0);
+    }
+
+    #[test]
+    /// registrable domains should follow the public suffix list, wildcard and exception rules
+    /// included
+    fn registrable_domain_follows_public_suffix_list() {
+        assert_eq!(
+            registrable_domain("www.example.com").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            registrable_domain("a.b.example.co.uk").as_deref(),
+            Some("example.co.uk")
+        );
+        assert_eq!(
+            registrable_domain("Example.COM.").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(registrable_domain("co.uk"), None);
+        assert_eq!(registrable_domain("localhost"), None);
+        assert_eq!(registrable_domain(""), None);
+        // *.ck, with !www.ck as an exception
+        assert_eq!(
+            registrable_domain("a.foo.bar.ck").as_deref(),
+            Some("foo.bar.ck")
+        );
+        assert_eq!(registrable_domain("bar.ck"), None);
+        assert_eq!(registrable_domain("www.ck").as_deref(), Some("www.ck"));
+        // not on the list, falls back to the last label
+        assert_eq!(
+            registrable_domain("www.example.notatld").as_deref(),
+            Some("example.notatld")
+        );
+    }
+}
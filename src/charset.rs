@@ -0,0 +1,142 @@
+//! charset detection and decoding of response bodies
+//!
+//! the charset is taken from the Content-Type header, a byte order mark, or an html `<meta>` tag,
+//! in that order; bodies that declare nothing are treated as utf-8, falling back to windows-1252
+//! when they aren't valid utf-8
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use lazy_static::lazy_static;
+use regex::bytes::Regex;
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+
+/// number of bytes at the start of a body that are searched for a `<meta>` charset, same as
+/// the html spec's prescan
+const META_PRESCAN_LENGTH: usize = 1024;
+
+lazy_static! {
+    /// matches both `<meta charset="...">` and `<meta http-equiv=... content="...; charset=...">`
+    static ref META_CHARSET_REGEX: Regex =
+        Regex::new(r#"(?i)<meta[^>]+charset\s*=\s*["']?\s*([a-z0-9_:.\-]+)"#).unwrap();
+}
+
+/// charset declared by the Content-Type header, if any
+fn from_headers(headers: &HeaderMap) -> Option<&'static Encoding> {
+    let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+
+    content_type
+        .split(';')
+        .filter_map(|param| {
+            let (name, value) = param.split_once('=')?;
+
+            if name.trim().eq_ignore_ascii_case("charset") {
+                Some(value.trim().trim_matches(|c| c == '"' || c == '\''))
+            } else {
+                None
+            }
+        })
+        .find_map(|label| Encoding::for_label(label.as_bytes()))
+}
+
+/// charset declared by a `<meta>` tag near the start of the body, if any
+fn from_meta(body: &[u8]) -> Option<&'static Encoding> {
+    let prescan = &body[..body.len().min(META_PRESCAN_LENGTH)];
+    let label = META_CHARSET_REGEX.captures(prescan)?.get(1)?;
+
+    let encoding = Encoding::for_label(label.as_bytes())?;
+
+    if encoding.output_encoding() != encoding {
+        // utf-16 declared in a meta tag can't be right, the tag itself was readable as ascii
+        return Some(UTF_8);
+    }
+
+    Some(encoding)
+}
+
+/// Determine the charset of the given body
+pub fn detect(headers: &HeaderMap, body: &[u8]) -> &'static Encoding {
+    if let Some(encoding) = from_headers(headers) {
+        return encoding;
+    }
+
+    if let Some((encoding, _)) = Encoding::for_bom(body) {
+        return encoding;
+    }
+
+    if let Some(encoding) = from_meta(body) {
+        return encoding;
+    }
+
+    if std::str::from_utf8(body).is_ok() {
+        UTF_8
+    } else {
+        WINDOWS_1252
+    }
+}
+
+/// Decode the given body using its detected charset; returns the text along with the name of the
+/// charset that was used
+pub fn decode(headers: &HeaderMap, body: &[u8]) -> (String, &'static str) {
+    let encoding = detect(headers, body);
+
+    // a byte order mark takes precedence over whatever was detected, and is stripped
+    let (text, used, _) = encoding.decode(body);
+
+    (text.into_owned(), used.name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    /// helper to build headers with the given Content-Type
+    fn content_type(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    /// the Content-Type header's charset should take precedence over everything else
+    fn detect_prefers_content_type() {
+        let headers = content_type("text/html; Charset=\"Shift_JIS\"");
+        let body = b"<meta charset=\"gbk\">";
+
+        assert_eq!(detect(&headers, body).name(), "Shift_JIS");
+    }
+
+    #[test]
+    /// both forms of meta tag should be recognized
+    fn detect_reads_meta_tags() {
+        let headers = content_type("text/html");
+
+        let body = b"<html><head><META charset=gb2312></head>";
+        assert_eq!(detect(&headers, body).name(), "GBK");
+
+        let body = br#"<meta http-equiv="Content-Type" content="text/html; charset=iso-8859-1">"#;
+        assert_eq!(detect(&headers, body).name(), "windows-1252");
+    }
+
+    #[test]
+    /// undeclared bodies should be utf-8 when valid, windows-1252 otherwise
+    fn detect_falls_back_on_content() {
+        let headers = HeaderMap::new();
+
+        assert_eq!(detect(&headers, "héllo".as_bytes()).name(), "UTF-8");
+        assert_eq!(detect(&headers, b"h\xe9llo").name(), "windows-1252");
+    }
+
+    #[test]
+    /// shift_jis text should decode into the right characters (and therefore words)
+    fn decode_shift_jis() {
+        let headers = content_type("text/html; charset=shift_jis");
+
+        // "日本語 テキスト" in shift_jis
+        let body = b"\x93\xfa\x96\x7b\x8c\xea \x83\x65\x83\x4c\x83\x58\x83\x67";
+
+        let (text, charset) = decode(&headers, body);
+
+        assert_eq!(text, "日本語 テキスト");
+        assert_eq!(charset, "Shift_JIS");
+        assert_eq!(text.split_whitespace().count(), 2);
+    }
+}
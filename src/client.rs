@@ -1,18 +1,65 @@
-use anyhow::Result;
-use reqwest::header::HeaderMap;
-use reqwest::{redirect::Policy, Client, Proxy};
+use anyhow::{Context, Result};
+use openssl::x509::X509;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT_ENCODING};
+use reqwest::{redirect::Policy, Certificate, Client, Proxy};
 use std::collections::HashMap;
 use std::convert::TryInto;
+use std::fs::read;
+use std::sync::Arc;
 use std::time::Duration;
 
+use crate::{config::Configuration, decompress, dns::CachingResolver, utils::fmt_err};
+
+/// Which server certificates a client trusts
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Trust<'a> {
+    /// accept any certificate (--insecure)
+    pub insecure: bool,
+
+    /// PEM file of additional CA certificates to trust (--ca-cert)
+    pub ca_cert: &'a str,
+
+    /// public key hashes, one of which a certificate must match (--pin-sha256), in addition to
+    /// validating its chain; see certificate::PinVerifier
+    pub pins: &'a [String],
+}
+
+/// implementation of Trust
+impl<'a> Trust<'a> {
+    /// certificate settings of the given Configuration
+    pub fn from_config(config: &'a Configuration) -> Self {
+        Self {
+            insecure: config.insecure,
+            ca_cert: &config.ca_cert,
+            pins: &config.pin_sha256,
+        }
+    }
+}
+
+/// Read every certificate in the given PEM file
+fn read_ca_certs(path: &str) -> Result<Vec<Certificate>> {
+    let pem = read(path).with_context(|| fmt_err(&format!("Could not read {}", path)))?;
+
+    let mut certs = Vec::new();
+
+    for cert in X509::stack_from_pem(&pem)
+        .with_context(|| fmt_err(&format!("Could not parse certificates in {}", path)))?
+    {
+        certs.push(Certificate::from_der(&cert.to_der()?)?);
+    }
+
+    Ok(certs)
+}
+
 /// Create and return an instance of [reqwest::Client](https://docs.rs/reqwest/latest/reqwest/struct.Client.html)
 pub fn initialize(
     timeout: u64,
     user_agent: &str,
     redirects: bool,
-    insecure: bool,
+    trust: Trust,
     headers: &HashMap<String, String>,
     proxy: Option<&str>,
+    dns_cache_ttl: u64,
 ) -> Result<Client> {
     let policy = if redirects {
         Policy::limited(10)
@@ -20,15 +67,36 @@ pub fn initialize(
         Policy::none()
     };
 
-    let header_map: HeaderMap = headers.try_into()?;
+    let mut header_map: HeaderMap = headers.try_into()?;
+
+    if !header_map.contains_key(ACCEPT_ENCODING) {
+        // bodies are decoded in FeroxResponse::from, see decompress.rs
+        header_map.insert(
+            ACCEPT_ENCODING,
+            HeaderValue::from_static(decompress::ACCEPT_ENCODING),
+        );
+    }
 
-    let client = Client::builder()
+    let mut client = Client::builder()
         .timeout(Duration::new(timeout, 0))
         .user_agent(user_agent)
-        .danger_accept_invalid_certs(insecure)
+        .danger_accept_invalid_certs(trust.insecure)
+        // responses are checked against the pins too, which requires the handshake's details
+        .tls_info(!trust.pins.is_empty())
         .default_headers(header_map)
         .redirect(policy);
 
+    if !trust.ca_cert.is_empty() {
+        for cert in read_ca_certs(trust.ca_cert)? {
+            client = client.add_root_certificate(cert);
+        }
+    }
+
+    if dns_cache_ttl > 0 {
+        // a ttl of 0 disables the cache, leaving lookups to the default resolver
+        client = client.dns_resolver(Arc::new(CachingResolver::new(dns_cache_ttl)));
+    }
+
     if let Some(some_proxy) = proxy {
         if !some_proxy.is_empty() {
             // it's not an empty string; set the proxy
@@ -49,7 +117,16 @@ mod tests {
     /// create client with a bad proxy, expect panic
     fn client_with_bad_proxy() {
         let headers = HashMap::new();
-        initialize(0, "stuff", true, false, &headers, Some("not a valid proxy")).unwrap();
+        initialize(
+            0,
+            "stuff",
+            true,
+            Trust::default(),
+            &headers,
+            Some("not a valid proxy"),
+            0,
+        )
+        .unwrap();
     }
 
     #[test]
@@ -57,6 +134,53 @@ mod tests {
     fn client_with_good_proxy() {
         let headers = HashMap::new();
         let proxy = "http://127.0.0.1:8080";
-        initialize(0, "stuff", true, true, &headers, Some(proxy)).unwrap();
+        let trust = Trust {
+            insecure: true,
+            ..Default::default()
+        };
+        initialize(0, "stuff", true, trust, &headers, Some(proxy), 0).unwrap();
+    }
+
+    #[test]
+    /// create client with the dns cache enabled, expect no error
+    fn client_with_dns_cache() {
+        let headers = HashMap::new();
+        initialize(0, "stuff", true, Trust::default(), &headers, None, 60).unwrap();
+    }
+
+    #[test]
+    /// every certificate in a bundle should be read, and a bad bundle should be an error
+    fn client_with_ca_cert_bundle() {
+        use openssl::{asn1::Asn1Time, hash::MessageDigest, pkey::PKey, rsa::Rsa};
+        use std::io::Write;
+
+        let mut bundle = tempfile::NamedTempFile::new().unwrap();
+
+        for _ in 0..2 {
+            let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+            let mut builder = X509::builder().unwrap();
+            builder.set_pubkey(&key).unwrap();
+            builder
+                .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+                .unwrap();
+            builder
+                .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+                .unwrap();
+            builder.sign(&key, MessageDigest::sha256()).unwrap();
+            bundle
+                .write_all(&builder.build().to_pem().unwrap())
+                .unwrap();
+        }
+
+        let path = bundle.path().to_str().unwrap();
+        assert_eq!(read_ca_certs(path).unwrap().len(), 2);
+
+        let trust = Trust {
+            ca_cert: path,
+            ..Default::default()
+        };
+        initialize(0, "stuff", true, trust, &HashMap::new(), None, 0).unwrap();
+
+        assert!(read_ca_certs("/nonexistent/ca.pem").is_err());
     }
 }
@@ -0,0 +1,383 @@
+//! cloud storage (S3, GCS, Azure) detection in extracted content
+//!
+//! with --cloud-storage, bucket urls found in reported response bodies are checked for public
+//! listings; the checks go to the providers rather than the target, so they're sent by a client of
+//! their own (the target's headers and cookies stay with the target) at a fixed rate, shared by all
+//! scans
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::{Client, StatusCode, Url};
+use tokio::time::{sleep_until, Instant};
+
+use crate::{
+    client::{self, Trust},
+    config::Configuration,
+    event_handlers::{Command, Handles},
+    response::FeroxResponse,
+};
+
+/// requests per second sent to cloud storage providers, across all scans
+pub const RATE_LIMIT: u64 = 5;
+
+/// elements that start a bucket listing; S3 and GCS share an api, Azure has its own
+const LISTING_MARKERS: [&str; 2] = ["<ListBucketResult", "<EnumerationResults"];
+
+lazy_static! {
+    /// S3, virtual-hosted style, i.e. https://assets.s3.us-east-1.amazonaws.com
+    static ref S3_HOST: Regex = Regex::new(
+        r"(?i)([a-z0-9][a-z0-9.-]{1,61}[a-z0-9])\.s3(?:[.-][a-z0-9-]+)?\.amazonaws\.com"
+    )
+    .unwrap();
+
+    /// S3, path style, i.e. https://s3.amazonaws.com/assets
+    static ref S3_PATH: Regex = Regex::new(
+        r"(?i)//s3(?:[.-][a-z0-9-]+)?\.amazonaws\.com/([a-z0-9][a-z0-9.-]{1,61}[a-z0-9])"
+    )
+    .unwrap();
+
+    /// GCS, virtual-hosted style, i.e. https://assets.storage.googleapis.com
+    static ref GCS_HOST: Regex =
+        Regex::new(r"(?i)([a-z0-9][a-z0-9._-]{1,61}[a-z0-9])\.storage\.googleapis\.com").unwrap();
+
+    /// GCS, path style, i.e. https://storage.googleapis.com/assets
+    static ref GCS_PATH: Regex =
+        Regex::new(r"(?i)//storage\.googleapis\.com/([a-z0-9][a-z0-9._-]{1,61}[a-z0-9])").unwrap();
+
+    /// Azure blob container, i.e. https://account.blob.core.windows.net/assets
+    static ref AZURE: Regex = Regex::new(
+        r"(?i)([a-z0-9]{3,24})\.blob\.core\.windows\.net/([a-z0-9](?:[a-z0-9-]{1,61}[a-z0-9])?)"
+    )
+    .unwrap();
+
+    /// buckets that were already checked
+    static ref CHECKED: Mutex<HashSet<Bucket>> = Mutex::new(HashSet::new());
+
+    /// client used for every check, built on first use
+    static ref CLIENT: Mutex<Option<Client>> = Mutex::new(None);
+
+    /// earliest time the next check may be sent (`RATE_LIMIT`)
+    static ref NEXT_CHECK: tokio::sync::Mutex<Instant> = tokio::sync::Mutex::new(Instant::now());
+}
+
+/// Cloud storage provider
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Provider {
+    /// Amazon S3
+    S3,
+
+    /// Google Cloud Storage
+    Gcs,
+
+    /// Azure Blob Storage
+    Azure,
+}
+
+/// implementation of Provider
+impl Provider {
+    /// name of the provider, as it appears in output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Provider::S3 => "s3",
+            Provider::Gcs => "gcs",
+            Provider::Azure => "azure",
+        }
+    }
+}
+
+/// A bucket (or Azure container) referenced by a response body
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bucket {
+    /// who hosts the bucket
+    pub provider: Provider,
+
+    /// name of the bucket; account/container for Azure
+    pub name: String,
+}
+
+/// implementation of Bucket
+impl Bucket {
+    /// url that returns the bucket's contents, if it's publicly listable; S3 uses path-style urls,
+    /// since bucket names with dots don't match the certificate of a virtual-hosted one
+    pub fn listing_url(&self) -> String {
+        match self.provider {
+            Provider::S3 => format!("https://s3.amazonaws.com/{}/", self.name),
+            Provider::Gcs => format!("https://storage.googleapis.com/{}/", self.name),
+            Provider::Azure => match self.name.split_once('/') {
+                Some((account, container)) => format!(
+                    "https://{}.blob.core.windows.net/{}?restype=container&comp=list",
+                    account, container
+                ),
+                None => format!("https://{}.blob.core.windows.net/", self.name),
+            },
+        }
+    }
+}
+
+/// Result of checking a bucket for a public listing
+#[derive(Debug, Clone)]
+pub struct CloudStorage {
+    /// the bucket that was checked
+    pub bucket: Bucket,
+
+    /// url whose body referenced the bucket
+    pub found_on: String,
+
+    /// status of the listing request
+    pub status: u16,
+
+    /// whether the listing request returned the bucket's contents
+    pub listable: bool,
+}
+
+/// Display implementation for CloudStorage
+impl fmt::Display for CloudStorage {
+    /// provider, bucket, whether it's listable, and where it was found, i.e.
+    /// s3 assets (listable: 200 https://s3.amazonaws.com/assets/) found on http://localhost/
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = if self.listable {
+            "listable"
+        } else {
+            "not listable"
+        };
+
+        write!(
+            f,
+            "{} {} ({}: {} {}) found on {}",
+            self.bucket.provider.as_str(),
+            self.bucket.name,
+            state,
+            self.status,
+            self.bucket.listing_url(),
+            self.found_on
+        )
+    }
+}
+
+/// Every bucket referenced by the given text, in the order they appear, without repeats
+pub fn find_buckets(text: &str) -> Vec<Bucket> {
+    let mut buckets = Vec::new();
+
+    let mut add = |provider: Provider, name: String| {
+        let bucket = Bucket {
+            provider,
+            name: name.to_lowercase(),
+        };
+
+        if !buckets.contains(&bucket) {
+            buckets.push(bucket);
+        }
+    };
+
+    for (regex, provider) in &[
+        (&*S3_HOST, Provider::S3),
+        (&*S3_PATH, Provider::S3),
+        (&*GCS_HOST, Provider::Gcs),
+        (&*GCS_PATH, Provider::Gcs),
+    ] {
+        for captures in regex.captures_iter(text) {
+            add(*provider, captures[1].to_string());
+        }
+    }
+
+    for captures in AZURE.captures_iter(text) {
+        add(
+            Provider::Azure,
+            format!("{}/{}", &captures[1], &captures[2]),
+        );
+    }
+
+    buckets
+}
+
+/// Determine whether the given response to a listing request holds the bucket's contents
+pub fn is_listable(status: StatusCode, text: &str) -> bool {
+    status.is_success() && LISTING_MARKERS.iter().any(|marker| text.contains(marker))
+}
+
+/// The client used for checks; the target's headers and certificate settings aren't applied,
+/// only its proxy, timeout, and user agent
+fn client(config: &Configuration) -> Option<Client> {
+    let mut guard = CLIENT.lock().ok()?;
+
+    if guard.is_none() {
+        let proxy = if config.proxy.is_empty() {
+            None
+        } else {
+            Some(config.proxy.as_str())
+        };
+
+        let initialized = client::initialize(
+            config.timeout,
+            &config.user_agent,
+            false,
+            Trust::default(),
+            &HashMap::new(),
+            proxy,
+            config.dns_cache_ttl,
+        );
+
+        match initialized {
+            Ok(client) => *guard = Some(client),
+            Err(e) => log::warn!(
+                "Could not build a client to check cloud storage with: {}",
+                e
+            ),
+        }
+    }
+
+    guard.clone()
+}
+
+/// Wait until another check may be sent, per `RATE_LIMIT`
+async fn wait_for_turn() {
+    let mut next = NEXT_CHECK.lock().await;
+
+    let now = Instant::now();
+
+    if *next > now {
+        sleep_until(*next).await;
+    }
+
+    *next = (*next).max(now) + Duration::from_millis(1000 / RATE_LIMIT);
+}
+
+/// Every bucket referenced by the given text that wasn't seen already; each one is only returned
+/// once, so that it's only checked once
+pub fn unchecked_buckets(text: &str) -> Vec<Bucket> {
+    match CHECKED.lock() {
+        Ok(mut checked) => find_buckets(text)
+            .into_iter()
+            .filter(|bucket| checked.insert(bucket.clone()))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Check the given buckets (see `unchecked_buckets`), referenced by the response at `found_on`,
+/// reporting each one
+pub async fn check(found_on: Url, buckets: Vec<Bucket>, handles: Arc<Handles>) {
+    log::trace!("enter: check({}, {:?})", found_on, buckets);
+
+    let client = match client(&handles.config) {
+        Some(client) => client,
+        None => return,
+    };
+
+    for bucket in buckets {
+        wait_for_turn().await;
+
+        let response = match client.get(&bucket.listing_url()).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                log::warn!("Could not check {}: {}", bucket.listing_url(), e);
+                continue;
+            }
+        };
+
+        // bodies may come back compressed, FeroxResponse::from decodes them
+        let listing = FeroxResponse::from(response, true, handles.config.output_level).await;
+
+        let storage = CloudStorage {
+            listable: is_listable(*listing.status(), listing.text()),
+            status: listing.status().as_u16(),
+            bucket,
+            found_on: found_on.to_string(),
+        };
+
+        log::info!("checked {}", storage);
+
+        if let Err(e) = handles
+            .output
+            .send(Command::ReportCloudStorage(Box::new(storage)))
+        {
+            log::warn!("Could not send cloud storage to output handler: {}", e);
+        }
+    }
+
+    log::trace!("exit: check");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// buckets should be found in both url styles of each provider, once each
+    fn find_buckets_recognizes_each_provider() {
+        let text = r#"
+            <img src="https://Assets.s3.us-west-2.amazonaws.com/logo.png">
+            <script src="https://s3.amazonaws.com/backups-2021/app.js"></script>
+            <a href="https://assets.s3.amazonaws.com/other.png">again</a>
+            <link href="https://storage.googleapis.com/media.example.com/site.css">
+            <img src="https://uploads.storage.googleapis.com/a.png">
+            fetch("https://acmecorp.blob.core.windows.net/public/data.json")
+            <a href="https://example.com/s3/not-a-bucket">"#;
+
+        let buckets: Vec<(Provider, String)> = find_buckets(text)
+            .into_iter()
+            .map(|bucket| (bucket.provider, bucket.name))
+            .collect();
+
+        assert_eq!(
+            buckets,
+            vec![
+                (Provider::S3, String::from("assets")),
+                (Provider::S3, String::from("backups-2021")),
+                (Provider::Gcs, String::from("uploads")),
+                (Provider::Gcs, String::from("media.example.com")),
+                (Provider::Azure, String::from("acmecorp/public")),
+            ]
+        );
+    }
+
+    #[test]
+    /// listing urls should use each provider's listing api
+    fn listing_url_per_provider() {
+        let bucket = |provider, name: &str| Bucket {
+            provider,
+            name: name.to_string(),
+        };
+
+        assert_eq!(
+            bucket(Provider::S3, "assets").listing_url(),
+            "https://s3.amazonaws.com/assets/"
+        );
+        assert_eq!(
+            bucket(Provider::S3, "media.example.com").listing_url(),
+            "https://s3.amazonaws.com/media.example.com/"
+        );
+        assert_eq!(
+            bucket(Provider::Gcs, "assets").listing_url(),
+            "https://storage.googleapis.com/assets/"
+        );
+        assert_eq!(
+            bucket(Provider::Azure, "acme/public").listing_url(),
+            "https://acme.blob.core.windows.net/public?restype=container&comp=list"
+        );
+    }
+
+    #[test]
+    /// only successful responses holding a listing mean the bucket is listable
+    fn is_listable_requires_listing() {
+        assert!(is_listable(
+            StatusCode::OK,
+            r#"<?xml version="1.0"?><ListBucketResult><Name>assets</Name></ListBucketResult>"#
+        ));
+        assert!(is_listable(
+            StatusCode::OK,
+            "<EnumerationResults ContainerName=\"public\"><Blobs/></EnumerationResults>"
+        ));
+        assert!(!is_listable(
+            StatusCode::FORBIDDEN,
+            "<Error><Code>AccessDenied</Code></Error>"
+        ));
+        assert!(!is_listable(StatusCode::OK, "<html>welcome</html>"));
+    }
+}
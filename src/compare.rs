@@ -0,0 +1,342 @@
+//! compare the results of a scan against those of a previous scan (--compare)
+use std::{collections::BTreeMap, fmt, fs::read_to_string};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use serde_json::Value;
+
+use crate::{
+    progress::PROGRESS_PRINTER, response::FeroxResponse, scan_manager::FeroxResponses,
+    utils::status_colorizer,
+};
+
+/// What's known about a single reported url, used as the basis for comparison
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Endpoint {
+    /// status code of the response
+    pub status: u16,
+
+    /// content length of the response
+    pub content_length: u64,
+}
+
+/// A single difference between a previous scan and the current one
+#[derive(Debug, PartialEq)]
+pub enum Difference {
+    /// url reported now, but not previously
+    New(String, Endpoint),
+
+    /// url reported previously, but not now
+    Removed(String, Endpoint),
+
+    /// url reported both times, but with a different status code or size
+    Changed(String, Endpoint, Endpoint),
+}
+
+/// Display implementation for Difference
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difference::New(url, now) => write!(
+                f,
+                "{} {} {:>9}c {}",
+                style("NEW    ").green(),
+                status_colorizer(&now.status.to_string()),
+                now.content_length,
+                url
+            ),
+            Difference::Removed(url, before) => write!(
+                f,
+                "{} {} {:>9}c {}",
+                style("REMOVED").red(),
+                status_colorizer(&before.status.to_string()),
+                before.content_length,
+                url
+            ),
+            Difference::Changed(url, before, now) => {
+                let delta = now.content_length as i64 - before.content_length as i64;
+                write!(
+                    f,
+                    "{} {} -> {} {:>+9}c {}",
+                    style("CHANGED").yellow(),
+                    status_colorizer(&before.status.to_string()),
+                    status_colorizer(&now.status.to_string()),
+                    delta,
+                    url
+                )
+            }
+        }
+    }
+}
+
+/// pull the url and endpoint out of a single serialized response, skipping wildcards
+fn endpoint_from_json(entry: &Value) -> Option<(String, Endpoint)> {
+    if entry["type"] != "response" || entry["wildcard"] == true {
+        return None;
+    }
+
+    let url = entry["url"].as_str()?.to_string();
+    let status = entry["status"].as_u64()? as u16;
+    let content_length = entry["content_length"].as_u64().unwrap_or_default();
+
+    Some((
+        url,
+        Endpoint {
+            status,
+            content_length,
+        },
+    ))
+}
+
+/// Read the responses reported by a previous scan; accepts either --json output or a state file
+pub fn read_baseline(path: &str) -> Result<BTreeMap<String, Endpoint>> {
+    log::trace!("enter: read_baseline({})", path);
+
+    let contents = read_to_string(path).with_context(|| format!("Could not open {}", path))?;
+
+    let baseline: BTreeMap<String, Endpoint> = match serde_json::from_str::<Value>(&contents) {
+        // state file, a single object with all responses under the "responses" key
+        Ok(state) if state["responses"].is_array() => state["responses"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(endpoint_from_json)
+            .collect(),
+        // --json output, one object per line
+        _ => contents
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter_map(|entry| endpoint_from_json(&entry))
+            .collect(),
+    };
+
+    if baseline.is_empty() {
+        bail!(
+            "Could not find any responses in {}; expected --json output or a state file",
+            path
+        );
+    }
+
+    log::trace!("exit: read_baseline -> {} responses", baseline.len());
+    Ok(baseline)
+}
+
+/// Determine the differences between the baseline and the responses reported during this scan,
+/// ordered by url
+///
+/// `fingerprint` looks up urls of the baseline that aren't among `current`, in case only a
+/// fingerprint of their response was kept (--low-memory); fingerprints don't retain the url, so
+/// they can't be reported as new
+pub fn compare<F>(
+    baseline: &BTreeMap<String, Endpoint>,
+    current: &[FeroxResponse],
+    fingerprint: F,
+) -> Vec<Difference>
+where
+    F: Fn(&str) -> Option<Endpoint>,
+{
+    let current: BTreeMap<String, Endpoint> = current
+        .iter()
+        .filter(|response| !response.wildcard())
+        .map(|response| {
+            (
+                response.url().to_string(),
+                Endpoint {
+                    status: response.status().as_u16(),
+                    content_length: response.content_length(),
+                },
+            )
+        })
+        .collect();
+
+    let mut differences = Vec::new();
+
+    for (url, now) in &current {
+        match baseline.get(url) {
+            None => differences.push(Difference::New(url.clone(), *now)),
+            Some(before) if before != now => {
+                differences.push(Difference::Changed(url.clone(), *before, *now))
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (url, before) in baseline {
+        if current.contains_key(url) {
+            continue;
+        }
+
+        match fingerprint(url) {
+            None => differences.push(Difference::Removed(url.clone(), *before)),
+            Some(now) if now != *before => {
+                differences.push(Difference::Changed(url.clone(), *before, now))
+            }
+            Some(_) => {}
+        }
+    }
+
+    differences.sort_by(|a, b| url_of(a).cmp(url_of(b)));
+    differences
+}
+
+/// url associated with the given difference
+fn url_of(difference: &Difference) -> &str {
+    match difference {
+        Difference::New(url, _) | Difference::Removed(url, _) | Difference::Changed(url, _, _) => {
+            url
+        }
+    }
+}
+
+/// Print the differences between the baseline found at `path` and this scan's responses
+pub fn report(path: &str, responses: &FeroxResponses) -> Result<()> {
+    let baseline = read_baseline(path)?;
+
+    let current = responses.to_vec();
+
+    let differences = compare(&baseline, &current, |url| {
+        responses.fingerprint(url).map(|fingerprint| Endpoint {
+            status: fingerprint.status,
+            content_length: fingerprint.content_length,
+        })
+    });
+
+    PROGRESS_PRINTER.println(format!(
+        "{} {} ({} differences)",
+        style("Compared to").bright().yellow(),
+        path,
+        differences.len()
+    ));
+
+    for difference in &differences {
+        PROGRESS_PRINTER.println(format!("  {}", difference));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scan_manager::ResponseFingerprint, test_utils::response};
+
+    #[test]
+    /// compare should find new, removed, and changed urls, ignoring those that are the same
+    fn compare_finds_new_removed_and_changed() {
+        let mut baseline = BTreeMap::new();
+        let same = Endpoint {
+            status: 200,
+            content_length: 4,
+        };
+        baseline.insert(String::from("http://localhost/same"), same);
+        baseline.insert(
+            String::from("http://localhost/changed"),
+            Endpoint {
+                status: 200,
+                content_length: 10,
+            },
+        );
+        baseline.insert(String::from("http://localhost/removed"), same);
+
+        let current = vec![
+            response("http://localhost/same", 200, "same"),
+            response("http://localhost/changed", 403, "nope"),
+            response("http://localhost/new", 200, "new!"),
+        ];
+
+        let differences = compare(&baseline, &current, |_| None);
+
+        assert_eq!(differences.len(), 3);
+        assert!(
+            matches!(&differences[0], Difference::Changed(url, _, now) if url.ends_with("/changed") && now.status == 403)
+        );
+        assert!(matches!(&differences[1], Difference::New(url, _) if url.ends_with("/new")));
+        assert!(
+            matches!(&differences[2], Difference::Removed(url, _) if url.ends_with("/removed"))
+        );
+    }
+
+    #[test]
+    /// urls of which only a fingerprint was kept (--low-memory) shouldn't be reported as removed
+    fn compare_uses_fingerprints() {
+        let before = Endpoint {
+            status: 200,
+            content_length: 4,
+        };
+
+        let mut baseline = BTreeMap::new();
+        baseline.insert(String::from("http://localhost/same"), before);
+        baseline.insert(String::from("http://localhost/changed"), before);
+
+        let responses = FeroxResponses::default();
+        responses.insert_fingerprint(ResponseFingerprint::new(&response(
+            "http://localhost/same",
+            200,
+            "same",
+        )));
+        responses.insert_fingerprint(ResponseFingerprint::new(&response(
+            "http://localhost/changed",
+            500,
+            "same",
+        )));
+
+        let differences = compare(&baseline, &responses.to_vec(), |url| {
+            responses.fingerprint(url).map(|fingerprint| Endpoint {
+                status: fingerprint.status,
+                content_length: fingerprint.content_length,
+            })
+        });
+
+        assert_eq!(differences.len(), 1);
+        assert!(
+            matches!(&differences[0], Difference::Changed(url, _, now) if url.ends_with("/changed") && now.status == 500)
+        );
+    }
+
+    #[test]
+    /// read_baseline should understand --json output and skip wildcards and statistics
+    fn read_baseline_parses_json_output() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            concat!(
+                r#"{"type":"response","url":"http://localhost/a","status":200,"content_length":5,"wildcard":false}"#,
+                "\n",
+                r#"{"type":"response","url":"http://localhost/b","status":301,"content_length":0,"wildcard":true}"#,
+                "\n",
+                r#"{"type":"statistics","requests":42}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let baseline = read_baseline(tmp.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(baseline.len(), 1);
+        assert_eq!(baseline["http://localhost/a"].content_length, 5);
+    }
+
+    #[test]
+    /// read_baseline should understand state files
+    fn read_baseline_parses_state_file() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            r#"{"scans":[],"responses":[{"type":"response","url":"http://localhost/a","status":200,"content_length":5,"wildcard":false}]}"#,
+        )
+        .unwrap();
+
+        let baseline = read_baseline(tmp.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(baseline["http://localhost/a"].status, 200);
+    }
+
+    #[test]
+    /// a file without any responses can't be used as a baseline
+    fn read_baseline_errors_without_responses() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), "not json").unwrap();
+
+        assert!(read_baseline(tmp.path().to_str().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! completions and manpage subcommands; both are generated from the same clap definitions that
+//! parse the command line, so they always match the binary they came from
+use std::io::Write;
+
+use anyhow::{anyhow, Result};
+use clap::Shell;
+
+use crate::{config::Configuration, parser, VERSION};
+
+/// sections of --help whose lines are options, arguments, or subcommands, each followed by a
+/// description
+const ENTRY_SECTIONS: [&str; 4] = ["FLAGS", "OPTIONS", "ARGS", "SUBCOMMANDS"];
+
+/// entries of --help start at most this far in; anything indented further continues the
+/// description of the entry above it
+const MAX_ENTRY_INDENT: usize = 8;
+
+/// whether the given --help section is free-form text (NOTE, EXAMPLES), rather than entries
+fn is_freeform(section: &str) -> bool {
+    !section.is_empty() && section != "USAGE" && !ENTRY_SECTIONS.contains(&section)
+}
+
+/// Completion script for the given shell (bash, zsh, fish, or powershell)
+pub fn completions(shell: &str) -> Result<Vec<u8>> {
+    let shell = shell.parse::<Shell>().map_err(|e| anyhow!(e))?;
+
+    let mut script = Vec::new();
+    parser::initialize().gen_completions_to("feroxbuster", shell, &mut script);
+
+    Ok(script)
+}
+
+/// text escaped for use in a roff document
+fn roff(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+
+    // lines beginning with . or ' are read as requests
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// Man page (in roff) made from the --help output
+pub fn manpage() -> Result<String> {
+    let mut help = Vec::new();
+
+    // a width of 0 turns off wrapping, so that each entry and its description share a line
+    parser::initialize()
+        .set_term_width(0)
+        .write_help(&mut help)?;
+
+    let help = String::from_utf8(help)?;
+
+    let mut page = format!(
+        ".TH FEROXBUSTER 1 \"\" \"feroxbuster {}\" \"User Commands\"\n",
+        VERSION
+    );
+
+    let mut section = String::new();
+    let mut about = String::new();
+
+    for line in help.lines() {
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+
+        if indent == 0 && trimmed.ends_with(':') && trimmed == trimmed.to_uppercase() {
+            // new section, i.e. USAGE:; the name/version/author/about lines come before any
+            if section.is_empty() {
+                page.push_str(&format!(".SH NAME\nferoxbuster \\- {}\n", roff(&about)));
+            } else if is_freeform(&section) {
+                page.push_str(".fi\n");
+            }
+
+            section = trimmed.trim_end_matches(':').to_string();
+
+            match section.as_str() {
+                "USAGE" => page.push_str(".SH SYNOPSIS\n"),
+                name if ENTRY_SECTIONS.contains(&name) => {
+                    page.push_str(&format!(".SH {}\n", name));
+                }
+                name => page.push_str(&format!(".SH {}\n.nf\n", name)),
+            }
+
+            continue;
+        }
+
+        if section.is_empty() {
+            // last line before the first section is the about string
+            if !trimmed.is_empty() {
+                about = trimmed.to_string();
+            }
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            if is_freeform(&section) {
+                page.push('\n');
+            }
+            continue;
+        }
+
+        match section.as_str() {
+            "USAGE" => page.push_str(&format!("{}\n", roff(trimmed))),
+            name if ENTRY_SECTIONS.contains(&name) => {
+                if indent <= MAX_ENTRY_INDENT {
+                    let (entry, description) = match trimmed.find("  ") {
+                        Some(split) => (&trimmed[..split], trimmed[split..].trim()),
+                        None => (trimmed, ""),
+                    };
+
+                    page.push_str(&format!(".TP\n\\fB{}\\fR\n", roff(entry)));
+
+                    if !description.is_empty() {
+                        page.push_str(&format!("{}\n", roff(description)));
+                    }
+                } else {
+                    page.push_str(&format!("{}\n", roff(trimmed)));
+                }
+            }
+            _ => {
+                // free-form sections (NOTE, EXAMPLES) keep their layout, less the section's indent
+                let dedented = line.strip_prefix("    ").unwrap_or(line);
+                page.push_str(&format!("{}\n", roff(dedented)));
+            }
+        }
+    }
+
+    if is_freeform(&section) {
+        page.push_str(".fi\n");
+    }
+
+    Ok(page)
+}
+
+/// Print the completion script or man page asked for on the command line
+pub fn run(config: &Configuration) -> Result<()> {
+    let output = if config.manpage {
+        manpage()?.into_bytes()
+    } else {
+        completions(&config.completions)?
+    };
+
+    std::io::stdout().write_all(&output)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// each supported shell should get a script that knows about feroxbuster's options
+    fn completions_include_options() {
+        for shell in &["bash", "zsh", "fish", "powershell"] {
+            let script = String::from_utf8(completions(shell).unwrap()).unwrap();
+            assert!(script.contains("feroxbuster"));
+            assert!(script.contains("wordlist"));
+        }
+
+        assert!(completions("tcsh").is_err());
+    }
+
+    #[test]
+    /// dashes and backslashes should be escaped, and lines mustn't be read as requests
+    fn roff_escapes_text() {
+        assert_eq!(roff("--add-slash"), "\\-\\-add\\-slash");
+        assert_eq!(roff(r"\d{4}"), r"\ed{4}");
+        assert_eq!(roff("./feroxbuster"), "\\&./feroxbuster");
+    }
+
+    #[test]
+    /// the man page should have a section per --help section, with each option as an entry
+    fn manpage_lists_options_and_subcommands() {
+        let page = manpage().unwrap();
+
+        assert!(page.starts_with(".TH FEROXBUSTER 1"));
+        assert!(page.contains(".SH NAME\nferoxbuster \\- A fast, simple, recursive content"));
+        assert!(page.contains(".SH SYNOPSIS\n"));
+        assert!(page.contains(".SH FLAGS\n"));
+        assert!(page.contains(".SH OPTIONS\n"));
+        assert!(page.contains(".TP\n\\fB\\-w, \\-\\-wordlist <FILE>\\fR\n"));
+        assert!(page.contains(".SH SUBCOMMANDS\n"));
+        assert!(page.contains("\\fBrefilter\\fR"));
+        assert!(page.contains(".SH EXAMPLES\n.nf\n"));
+        assert!(page.trim_end().ends_with(".fi"));
+    }
+}
@@ -1,22 +1,41 @@
 use super::utils::{
-    depth, report_and_exit, save_state, serialized_type, status_codes, threads, timeout,
+    auto_pause_window, bypass_budget, check_update, debug_log_backups, depth, dns_cache_ttl,
+    extension_status, normalize_extension, on_found_limit, parse_status_codes, parse_values,
+    save_state, serialized_type, status_codes, strategy, threads, timeout, url_encode_level,
     user_agent, wordlist, OutputLevel, RequesterPolicy,
 };
 use crate::config::determine_output_level;
 use crate::config::utils::determine_requester_policy;
 use crate::{
-    client, parser, scan_manager::resume_scan, traits::FeroxSerialize, utils::fmt_err,
-    DEFAULT_CONFIG_NAME,
+    api_mode, bypass,
+    certificate::PinVerifier,
+    client::{self, Trust},
+    error::{FeroxError, FeroxResult},
+    findings,
+    parser::{
+        self, DELAY_REGEX, PIN_REGEX, SCAN_WINDOW_REGEX, SIZE_REGEX, THROTTLE_REGEX, TIMESPEC_REGEX,
+    },
+    plugins::FeroxPlugins,
+    scan_manager::{redacted_settings, resume_scan},
+    schedule::parse_schedule,
+    theme::{self, COLOR_CLASSES},
+    traits::FeroxSerialize,
+    url::QUERY_PLACEHOLDER,
+    utils::fmt_err,
+    wordlist::{Wordlist, STDIN_WORDLIST},
+    wordlist_rules, DEFAULT_CONFIG_NAME, ENV_PREFIX,
 };
 use anyhow::{anyhow, Context, Result};
 use clap::{value_t, ArgMatches};
-use reqwest::{Client, StatusCode};
+use regex::Regex;
+use reqwest::{Client, StatusCode, Url};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     env::{current_dir, current_exe},
     fs::read_to_string,
     path::PathBuf,
+    sync::Arc,
 };
 
 /// macro helper to abstract away repetitive configuration updates
@@ -65,10 +84,23 @@ pub struct Configuration {
     #[serde(default = "wordlist")]
     pub wordlist: String,
 
+    /// Whether the wordlist was chosen (-w, a config file, or FEROX_WORDLIST) rather than left at
+    /// its default; only a default wordlist falls back to the built-in one when it's missing
+    #[serde(skip)]
+    pub wordlist_given: bool,
+
     /// Path to the config file used
     #[serde(default)]
     pub config: String,
 
+    /// Name of the config file profile applied, i.e. `stealth` for `[profiles.stealth]`
+    #[serde(default)]
+    pub profile: String,
+
+    /// All `[profiles.NAME]` tables found in the config file(s), keyed by NAME
+    #[serde(skip)]
+    pub profiles: HashMap<String, toml::Value>,
+
     /// Proxy to use for requests (ex: http(s)://host:port, socks5(h)://host:port)
     #[serde(default)]
     pub proxy: String,
@@ -81,10 +113,23 @@ pub struct Configuration {
     #[serde(default)]
     pub target_url: String,
 
+    /// File of targets, one per line, each optionally followed by options that only apply to it
+    #[serde(default)]
+    pub targets: String,
+
+    /// Address ranges and ports probed for web servers, each of which becomes a target
+    #[serde(default)]
+    pub target_ranges: Vec<String>,
+
     /// Status Codes to include (allow list) (default: 200 204 301 302 307 308 401 403 405)
     #[serde(default = "status_codes")]
     pub status_codes: Vec<u16>,
 
+    /// Per-extension Status Codes to include; used instead of `status_codes` for responses whose
+    /// path ends with one of the extensions (ex: php -> [200, 403])
+    #[serde(default, deserialize_with = "extension_status")]
+    pub extension_status: HashMap<String, Vec<u16>>,
+
     /// Status Codes to replay to the Replay Proxy (default: whatever is passed to --status-code)
     #[serde(default = "status_codes")]
     pub replay_codes: Vec<u16>,
@@ -101,6 +146,15 @@ pub struct Configuration {
     #[serde(skip)]
     pub replay_client: Option<Client>,
 
+    /// Checks servers' certificates against `pin_sha256` before requests are sent to them; None
+    /// when no pins are given
+    #[serde(skip)]
+    pub pin_verifier: Option<Arc<PinVerifier>>,
+
+    /// WebAssembly plugins loaded from the paths in `plugins`
+    #[serde(skip)]
+    pub loaded_plugins: Arc<FeroxPlugins>,
+
     /// Number of concurrent threads (default: 50)
     #[serde(default = "threads")]
     pub threads: usize,
@@ -109,6 +163,10 @@ pub struct Configuration {
     #[serde(default = "timeout")]
     pub timeout: u64,
 
+    /// Number of seconds for which dns lookups are cached; a value of 0 disables the cache
+    #[serde(default = "dns_cache_ttl")]
+    pub dns_cache_ttl: u64,
+
     /// Level of verbosity, equates to log level
     #[serde(default)]
     pub verbosity: u8,
@@ -121,6 +179,14 @@ pub struct Configuration {
     #[serde(default)]
     pub quiet: bool,
 
+    /// Print everything without colors (also enabled by the NO_COLOR environment variable)
+    #[serde(default)]
+    pub no_color: bool,
+
+    /// Colors used for each class of status code (1xx - 5xx, wildcard, error); config file only
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+
     /// more easily differentiate between the three states of output levels
     #[serde(skip)]
     pub output_level: OutputLevel,
@@ -133,6 +199,15 @@ pub struct Configuration {
     #[serde(default)]
     pub auto_tune: bool,
 
+    /// pause all scans once more than this percentage of recent responses were blocked or
+    /// errored; 0 means scans are never paused automatically
+    #[serde(default)]
+    pub auto_pause: usize,
+
+    /// number of recent responses, across all scans, looked at by auto_pause (default: 100)
+    #[serde(default = "auto_pause_window")]
+    pub auto_pause_window: usize,
+
     /// more easily differentiate between the three requester policies
     #[serde(skip)]
     pub requester_policy: RequesterPolicy,
@@ -145,11 +220,57 @@ pub struct Configuration {
     #[serde(default)]
     pub output: String,
 
+    /// File to which only the urls of reported responses are written, one per line
+    #[serde(default)]
+    pub urls_file: String,
+
+    /// Path to the HTTP Archive to which all requests and responses are written
+    #[serde(default)]
+    pub har: String,
+
+    /// Path to the SQLite database to which responses, scans, and statistics are written
+    #[serde(default)]
+    pub output_db: String,
+
+    /// Connection string of the PostgreSQL database to which responses, scans, and statistics
+    /// are written
+    #[serde(default)]
+    pub output_dsn: String,
+
+    /// Directory in which results are split into one file per class of status code
+    #[serde(default)]
+    pub split_output: String,
+
+    /// Directory in which each target's results, bodies, and state file are written to a
+    /// subdirectory of their own
+    #[serde(default)]
+    pub output_dir: String,
+
+    /// Path to the --json output (or state file) of a previous scan, compared against at scan end
+    #[serde(default)]
+    pub compare: String,
+
     /// File in which to store debug output, used in conjunction with verbosity to dictate which
     /// logs are written
     #[serde(default)]
     pub debug_log: String,
 
+    /// Write --debug-log entries as JSON, regardless of --json
+    #[serde(default)]
+    pub debug_log_json: bool,
+
+    /// Size at which --debug-log is rotated (ex: 100mb); no rotation when empty
+    #[serde(default)]
+    pub debug_log_max_size: String,
+
+    /// Number of rotated --debug-log files to keep (debug.log.1, debug.log.2, ...)
+    #[serde(default = "debug_log_backups")]
+    pub debug_log_backups: usize,
+
+    /// Directory in which responses are cached; subsequent runs read identical requests from it
+    #[serde(default)]
+    pub cache_dir: String,
+
     /// Sets the User-Agent (default: feroxbuster/VERSION)
     #[serde(default = "user_agent")]
     pub user_agent: String,
@@ -162,6 +283,14 @@ pub struct Configuration {
     #[serde(default)]
     pub insecure: bool,
 
+    /// PEM file of additional CA certificates to trust
+    #[serde(default)]
+    pub ca_cert: String,
+
+    /// base64 encoded sha256 hashes of trusted certificates' public keys
+    #[serde(default)]
+    pub pin_sha256: Vec<String>,
+
     /// File extension(s) to search for
     #[serde(default)]
     pub extensions: Vec<String>,
@@ -170,22 +299,152 @@ pub struct Configuration {
     #[serde(default)]
     pub headers: HashMap<String, String>,
 
+    /// Write requests to the socket byte-for-byte, instead of using the http client
+    #[serde(default)]
+    pub raw: bool,
+
+    /// Header lines written exactly as given, in raw mode
+    #[serde(default)]
+    pub raw_headers: Vec<String>,
+
     /// URL query parameters
     #[serde(default)]
     pub queries: Vec<(String, String)>,
 
+    /// File of values substituted for {QUERY} in `queries`, one value per request
+    #[serde(default)]
+    pub query_file: String,
+
+    /// values read from `query_file`
+    #[serde(skip)]
+    pub query_values: Vec<String>,
+
     /// Do not scan recursively
     #[serde(default)]
     pub no_recursion: bool,
 
+    /// Regular expressions matched against the path of discovered directories; matches aren't
+    /// scanned recursively
+    #[serde(default)]
+    pub dont_recurse_regex: Vec<String>,
+
     /// Extract links from html/javscript
     #[serde(default)]
     pub extract_links: bool,
 
+    /// Parse the entries of directory listings instead of brute forcing those directories
+    #[serde(default)]
+    pub parse_listings: bool,
+
+    /// Spider the target for words to add to the wordlist (cewl-style)
+    #[serde(default)]
+    pub smart_wordlist: bool,
+
+    /// Scan tuned for REST backends; ask for json, scan the version prefixes that exist, and
+    /// filter json errors saying a route doesn't exist
+    #[serde(default)]
+    pub api: bool,
+
+    /// Tag results with technologies detected in response headers/bodies
+    #[serde(default)]
+    pub fingerprint: bool,
+
+    /// Hashes of each response's body to show alongside its url: md5 and/or sha256
+    #[serde(default)]
+    pub hashes: Vec<String>,
+
+    /// Path to a file that maps body hashes to known files and their versions
+    #[serde(default)]
+    pub fingerprint_db: String,
+
+    /// Number of printable characters of each response's body to show alongside its url; 0 means
+    /// no preview is shown
+    #[serde(default)]
+    pub show_preview: usize,
+
+    /// Number of responses with the same status, template, and roughly the same length that are
+    /// shown before the rest are collapsed into a summary; 0 means responses are never collapsed
+    #[serde(default)]
+    pub group_similar: usize,
+
+    /// Add extensions associated with detected technologies to subsequent directory scans
+    #[serde(default)]
+    pub auto_extensions: bool,
+
+    /// Path to a file that maps detected technologies to technology-specific wordlists
+    #[serde(default)]
+    pub auto_wordlist: String,
+
+    /// Path to a file of rules mapping directory paths to the wordlists used to scan them
+    #[serde(default)]
+    pub wordlist_rules: String,
+
+    /// Path to a file of interesting file rules, added to the built-in rules
+    #[serde(default)]
+    pub interesting_rules: String,
+
+    /// Request forbidden resources again using tricks that get past misconfigured access controls
+    #[serde(default)]
+    pub bypass_403: bool,
+
+    /// Names of the bypass techniques to attempt; all of them when empty
+    #[serde(default)]
+    pub bypass_techniques: Vec<String>,
+
+    /// Number of requests that bypass attempts may send, across all scans
+    #[serde(default = "bypass_budget")]
+    pub bypass_budget: usize,
+
+    /// Request discovered resources with HEAD and OPTIONS, recording how they differ from GET
+    #[serde(default)]
+    pub verb_compare: bool,
+
+    /// Request well-known paths beneath discovered .git/.svn directories and .DS_Store files
+    #[serde(default)]
+    pub follow_disclosures: bool,
+
+    /// Request reported resources from another origin, recording their CORS policy
+    #[serde(default)]
+    pub cors: bool,
+
+    /// Rate the security headers of reported responses
+    #[serde(default)]
+    pub header_audit: bool,
+
+    /// Request /favicon.ico from each target, reporting its hash
+    #[serde(default)]
+    pub favicon: bool,
+
+    /// Request the ?wsdl of SOAP endpoints that are found, and report their operations
+    #[serde(default)]
+    pub wsdl: bool,
+
+    /// Check cloud storage buckets referenced by response bodies for public listings
+    #[serde(default)]
+    pub cloud_storage: bool,
+
     /// Append / to each request
     #[serde(default)]
     pub add_slash: bool,
 
+    /// Follow redirects that only add or remove a trailing slash, reporting the canonical url
+    #[serde(default)]
+    pub smart_slash: bool,
+
+    /// How words are percent-encoded before being requested: none, standard, double, or
+    /// mixed-case
+    #[serde(default = "url_encode_level")]
+    pub url_encode_level: String,
+
+    /// Request each directory's words in a pseudo-random order, rather than wordlist order
+    #[serde(default)]
+    pub shuffle: bool,
+
+    /// Seed from which each directory's word order is derived when shuffling; saved in the state
+    /// file so that resumed scans request words in the same order
+    #[serde(default)]
+    pub seed: u64,
+
     /// Read url(s) from STDIN
     #[serde(default)]
     pub stdin: bool,
@@ -198,6 +457,18 @@ pub struct Configuration {
     #[serde(default)]
     pub scan_limit: usize,
 
+    /// Total number of directory scans permitted; a limit of 0 means no limit is imposed
+    #[serde(default)]
+    pub max_scans: usize,
+
+    /// Order in which scans waiting on scan_limit are started: bfs, dfs, or priority
+    #[serde(default = "strategy")]
+    pub strategy: String,
+
+    /// TOML file mapping keywords to weights, used to score directories with --strategy priority
+    #[serde(default)]
+    pub priority_keywords: String,
+
     /// Number of parallel scans permitted; a limit of 0 means no limit is imposed
     #[serde(default)]
     pub parallel: usize,
@@ -206,6 +477,19 @@ pub struct Configuration {
     #[serde(default)]
     pub rate_limit: usize,
 
+    /// Time to wait before each request (per thread), i.e. 100ms
+    #[serde(default)]
+    pub delay: String,
+
+    /// Most by which each delay may randomly vary, in either direction, i.e. 50ms
+    #[serde(default)]
+    pub jitter: String,
+
+    /// Number of brute force requests per decoy request, where a decoy is a page or asset that's
+    /// already been found; a ratio of 0 means no decoys are sent
+    #[serde(default)]
+    pub decoy_ratio: usize,
+
     /// Filter out messages of a particular size
     #[serde(default)]
     pub filter_size: Vec<u64>,
@@ -230,6 +514,48 @@ pub struct Configuration {
     #[serde(default)]
     pub resumed: bool,
 
+    /// Print the effective configuration and exit, without scanning
+    #[serde(skip)]
+    pub print_config: bool,
+
+    /// Cron expression on which to repeat the scan; command line only, as the scheduled scans
+    /// themselves read the same config file
+    #[serde(skip)]
+    pub schedule: String,
+
+    /// Results of a previous scan to re-apply filters to (refilter subcommand); command line only
+    #[serde(skip)]
+    pub refilter: String,
+
+    /// Action to send to a running instance's --api-addr (scans, pause, resume, or cancel
+    /// subcommand); command line only
+    #[serde(skip)]
+    pub control: String,
+
+    /// Id of the scan targeted by the pause, resume, or cancel subcommand; command line only
+    #[serde(skip)]
+    pub control_id: String,
+
+    /// Replace the running binary with the latest release (update subcommand); command line only
+    #[serde(skip)]
+    pub update: bool,
+
+    /// Shell to print a completion script for (completions subcommand); command line only
+    #[serde(skip)]
+    pub completions: String,
+
+    /// Print a man page (manpage subcommand); command line only
+    #[serde(skip)]
+    pub manpage: bool,
+
+    /// Action of the wordlists subcommand (list or fetch); command line only
+    #[serde(skip)]
+    pub wordlists: String,
+
+    /// Names of the wordlists to download (wordlists fetch subcommand); command line only
+    #[serde(skip)]
+    pub wordlist_names: Vec<String>,
+
     /// Resume scan from this file
     #[serde(default)]
     pub resume_from: String,
@@ -240,14 +566,108 @@ pub struct Configuration {
     #[serde(default = "save_state")]
     pub save_state: bool,
 
+    /// Whether or not credentials (Authorization/Cookie headers, proxy passwords) are removed
+    /// from state files
+    #[serde(default)]
+    pub state_redact: bool,
+
+    /// Whether or not the words of the wordlist are written into state files, so that the scan
+    /// can be resumed where the wordlist doesn't exist
+    #[serde(default)]
+    pub state_embed_wordlist: bool,
+
+    /// Password used to encrypt state files; never written to the state file itself
+    #[serde(default, skip_serializing)]
+    pub state_password: String,
+
     /// The maximum runtime for a scan, expressed as N[smdh] where N can be parsed into a
     /// non-negative integer and the next character is either s, m, h, or d (case insensitive)
     #[serde(default)]
     pub time_limit: String,
 
+    /// The maximum runtime of a single directory scan, expressed the same way as time_limit
+    #[serde(default)]
+    pub scan_time_limit: String,
+
+    /// Median response time, in milliseconds, above which a directory scan is abandoned as a
+    /// tarpit (directories where most requests time out are abandoned as well); 0 means
+    /// directories are never abandoned
+    #[serde(default)]
+    pub tarpit_latency: u64,
+
+    /// Maximum download bandwidth across all scans, i.e. 500kb/s
+    #[serde(default)]
+    pub throttle: String,
+
+    /// Maximum number of requests sent across all scans; a limit of 0 means no limit is imposed
+    #[serde(default)]
+    pub request_limit: usize,
+
+    /// Local times between which scanning is allowed, expressed as HH:MM-HH:MM
+    #[serde(default)]
+    pub scan_window: String,
+
     /// Filter out response bodies that meet a certain threshold of similarity
     #[serde(default)]
     pub filter_similar: Vec<String>,
+
+    /// Filter out responses that contain the given header (`Name: value`)
+    #[serde(default)]
+    pub filter_header: Vec<String>,
+
+    /// Only report responses that contain the given header (`Name: value`)
+    #[serde(default)]
+    pub match_header: Vec<String>,
+
+    /// External command that receives each response as JSON on stdin; responses are filtered
+    /// out when the command exits with a non-zero status
+    #[serde(default)]
+    pub filter_command: String,
+
+    /// Paths to WebAssembly plugins to load
+    #[serde(default)]
+    pub plugins: Vec<String>,
+
+    /// Webhook to which discoveries are POSTed as they're found
+    #[serde(default)]
+    pub notify_webhook: String,
+
+    /// Status codes that trigger a webhook notification; all reported responses when empty
+    #[serde(default)]
+    pub notify_on: Vec<u16>,
+
+    /// Command run for each reported result, with {url} and {status} replaced
+    #[serde(default)]
+    pub on_found: String,
+
+    /// Number of --on-found commands allowed to run at once
+    #[serde(default = "on_found_limit")]
+    pub on_found_limit: usize,
+
+    /// Address on which to serve prometheus metrics, i.e. 127.0.0.1:9095
+    #[serde(default)]
+    pub metrics_addr: String,
+
+    /// Address on which to serve the api used to control a running scan, i.e. 127.0.0.1:9096
+    #[serde(default)]
+    pub api_addr: String,
+
+    /// Address on which to serve the grpc service used to stream results from, and control, a
+    /// running scan, i.e. 127.0.0.1:50051
+    #[serde(default)]
+    pub grpc: String,
+
+    /// Report the urls that would be requested, without sending any requests
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// Only retain a compact fingerprint of each reported response, instead of the entire response
+    #[serde(default)]
+    pub low_memory: bool,
+
+    /// Whether or not to check for a newer release at startup, shown in the banner
+    #[serde(default = "check_update")]
+    pub check_update: bool,
 }
 
 impl Default for Configuration {
@@ -255,8 +675,17 @@ impl Default for Configuration {
     fn default() -> Self {
         let timeout = timeout();
         let user_agent = user_agent();
-        let client = client::initialize(timeout, &user_agent, false, false, &HashMap::new(), None)
-            .expect("Could not build client");
+        let dns_cache_ttl = dns_cache_ttl();
+        let client = client::initialize(
+            timeout,
+            &user_agent,
+            false,
+            Trust::default(),
+            &HashMap::new(),
+            None,
+            dns_cache_ttl,
+        )
+        .expect("Could not build client");
         let replay_client = None;
         let status_codes = status_codes();
         let replay_codes = status_codes.clone();
@@ -268,39 +697,117 @@ impl Default for Configuration {
             kind,
             client,
             timeout,
+            dns_cache_ttl,
             user_agent,
             replay_codes,
             status_codes,
+            extension_status: HashMap::new(),
             replay_client,
+            pin_verifier: None,
+            loaded_plugins: Arc::new(FeroxPlugins::default()),
+            profiles: HashMap::new(),
             requester_policy,
             dont_filter: false,
             auto_bail: false,
             auto_tune: false,
+            auto_pause: 0,
+            auto_pause_window: auto_pause_window(),
             silent: false,
             quiet: false,
+            no_color: false,
+            colors: HashMap::new(),
             output_level,
             resumed: false,
+            print_config: false,
+            schedule: String::new(),
+            refilter: String::new(),
+            control: String::new(),
+            control_id: String::new(),
+            update: false,
+            completions: String::new(),
+            manpage: false,
+            wordlists: String::new(),
+            wordlist_names: Vec::new(),
             stdin: false,
             json: false,
             verbosity: 0,
             scan_limit: 0,
+            max_scans: 0,
+            strategy: strategy(),
+            priority_keywords: String::new(),
             parallel: 0,
             rate_limit: 0,
+            delay: String::new(),
+            jitter: String::new(),
+            decoy_ratio: 0,
             add_slash: false,
+            smart_slash: false,
+            url_encode_level: url_encode_level(),
+            shuffle: false,
+            seed: 0,
             insecure: false,
+            ca_cert: String::new(),
+            pin_sha256: Vec::new(),
             redirects: false,
             no_recursion: false,
+            dont_recurse_regex: Vec::new(),
             extract_links: false,
+            parse_listings: false,
+            smart_wordlist: false,
+            api: false,
+            fingerprint: false,
+            hashes: Vec::new(),
+            fingerprint_db: String::new(),
+            show_preview: 0,
+            group_similar: 0,
+            auto_extensions: false,
+            auto_wordlist: String::new(),
+            wordlist_rules: String::new(),
+            interesting_rules: String::new(),
+            bypass_403: false,
+            bypass_techniques: Vec::new(),
+            bypass_budget: bypass_budget(),
+            verb_compare: false,
+            follow_disclosures: false,
+            cors: false,
+            header_audit: false,
+            favicon: false,
+            wsdl: false,
+            cloud_storage: false,
             save_state: true,
+            state_redact: false,
+            state_embed_wordlist: false,
+            state_password: String::new(),
             proxy: String::new(),
             config: String::new(),
+            profile: String::new(),
             output: String::new(),
+            urls_file: String::new(),
+            har: String::new(),
+            output_db: String::new(),
+            output_dsn: String::new(),
+            split_output: String::new(),
+            output_dir: String::new(),
+            compare: String::new(),
             debug_log: String::new(),
+            debug_log_json: false,
+            debug_log_max_size: String::new(),
+            debug_log_backups: debug_log_backups(),
+            cache_dir: String::new(),
             target_url: String::new(),
+            targets: String::new(),
+            target_ranges: Vec::new(),
             time_limit: String::new(),
+            scan_time_limit: String::new(),
+            tarpit_latency: 0,
+            throttle: String::new(),
+            request_limit: 0,
+            scan_window: String::new(),
             resume_from: String::new(),
             replay_proxy: String::new(),
             queries: Vec::new(),
+            query_file: String::new(),
+            query_values: Vec::new(),
             extensions: Vec::new(),
             filter_size: Vec::new(),
             filter_regex: Vec::new(),
@@ -308,10 +815,27 @@ impl Default for Configuration {
             filter_word_count: Vec::new(),
             filter_status: Vec::new(),
             filter_similar: Vec::new(),
+            filter_header: Vec::new(),
+            match_header: Vec::new(),
+            filter_command: String::new(),
+            plugins: Vec::new(),
+            notify_webhook: String::new(),
+            notify_on: Vec::new(),
+            on_found: String::new(),
+            on_found_limit: on_found_limit(),
+            metrics_addr: String::new(),
+            api_addr: String::new(),
+            grpc: String::new(),
+            dry_run: false,
+            low_memory: false,
+            check_update: check_update(),
             headers: HashMap::new(),
+            raw: false,
+            raw_headers: Vec::new(),
             depth: depth(),
             threads: threads(),
             wordlist: wordlist(),
+            wordlist_given: false,
         }
     }
 }
@@ -323,41 +847,130 @@ impl Configuration {
     /// - **timeout**: `5` seconds
     /// - **redirects**: `false`
     /// - **extract-links**: `false`
+    /// - **parse_listings**: `false`
+    /// - **smart_wordlist**: `false`
+    /// - **api**: `false`
     /// - **wordlist**: [`DEFAULT_WORDLIST`](constant.DEFAULT_WORDLIST.html)
     /// - **config**: `None`
     /// - **threads**: `50`
     /// - **timeout**: `7` seconds
+    /// - **dns_cache_ttl**: `60` seconds
     /// - **verbosity**: `0` (no logging enabled)
     /// - **proxy**: `None`
     /// - **status_codes**: [`DEFAULT_RESPONSE_CODES`](constant.DEFAULT_RESPONSE_CODES.html)
+    /// - **extension_status**: `None` (`status_codes` applies to every extension)
     /// - **filter_status**: `None`
     /// - **output**: `None` (print to stdout)
+    /// - **urls_file**: `None`
+    /// - **har**: `None`
+    /// - **output_db**: `None`
+    /// - **output_dsn**: `None`
+    /// - **split_output**: `None`
+    /// - **output_dir**: `None`
+    /// - **compare**: `None`
+    /// - **schedule**: `None`
+    /// - **refilter**: `None`
+    /// - **control**: `None`
+    /// - **control_id**: `None`
+    /// - **update**: `false`
+    /// - **completions**: `None`
+    /// - **manpage**: `false`
+    /// - **wordlists**: `None`
+    /// - **wordlist_names**: `None`
     /// - **debug_log**: `None`
+    /// - **debug_log_json**: `false` (JSON entries only when --json is used)
+    /// - **debug_log_max_size**: `None` (--debug-log is never rotated)
+    /// - **debug_log_backups**: `5`
+    /// - **cache_dir**: `None` (responses aren't cached)
     /// - **quiet**: `false`
+    /// - **no_color**: `false`
+    /// - **colors**: `None` (built-in colors)
     /// - **silent**: `false`
     /// - **auto_tune**: `false`
     /// - **auto_bail**: `false`
+    /// - **auto_pause**: `0` (scans are never paused automatically)
+    /// - **auto_pause_window**: `100`
     /// - **save_state**: `true`
+    /// - **state_redact**: `false`
+    /// - **state_embed_wordlist**: `false`
+    /// - **state_password**: `None` (state files aren't encrypted)
     /// - **user_agent**: `feroxbuster/VERSION`
     /// - **insecure**: `false` (don't be insecure, i.e. don't allow invalid certs)
+    /// - **ca_cert**: `None` (only the system's CA certificates are trusted)
+    /// - **pin_sha256**: `None` (certificates aren't pinned)
     /// - **extensions**: `None`
     /// - **filter_size**: `None`
     /// - **filter_similar**: `None`
     /// - **filter_regex**: `None`
     /// - **filter_word_count**: `None`
     /// - **filter_line_count**: `None`
+    /// - **filter_header**: `None`
+    /// - **match_header**: `None`
+    /// - **filter_command**: `None`
+    /// - **plugins**: `None`
+    /// - **notify_webhook**: `None`
+    /// - **notify_on**: `None` (notify on all reported responses)
+    /// - **on_found**: `None`
+    /// - **on_found_limit**: `4`
+    /// - **metrics_addr**: `None`
+    /// - **api_addr**: `None`
+    /// - **grpc**: `None`
+    /// - **profile**: `None`
+    /// - **dry_run**: `false`
+    /// - **low_memory**: `false`
+    /// - **check_update**: `true`
     /// - **headers**: `None`
+    /// - **raw**: `false`
+    /// - **raw_headers**: `None`
     /// - **queries**: `None`
+    /// - **query_file**: `None`
     /// - **no_recursion**: `false` (recursively scan enumerated sub-directories)
+    /// - **dont_recurse_regex**: `None` (recurse into every enumerated sub-directory)
     /// - **add_slash**: `false`
+    /// - **smart_slash**: `false`
+    /// - **url_encode_level**: `none` (words are requested as-is)
+    /// - **shuffle**: `false` (words are requested in wordlist order)
+    /// - **seed**: `0` (a random seed is chosen when shuffling)
+    /// - **fingerprint**: `false`
+    /// - **hashes**: `None` (body hashes are only included in JSON output)
+    /// - **fingerprint_db**: `None` (known files aren't identified)
+    /// - **show_preview**: `0` (no preview of response bodies is shown)
+    /// - **group_similar**: `0` (similar responses aren't collapsed)
+    /// - **auto_extensions**: `false`
+    /// - **auto_wordlist**: `None`
+    /// - **wordlist_rules**: `None` (every directory is scanned with the same wordlist)
+    /// - **interesting_rules**: `None` (only the built-in rules are used)
+    /// - **bypass_403**: `false`
+    /// - **bypass_techniques**: `None` (every technique is attempted)
+    /// - **bypass_budget**: `250`
+    /// - **verb_compare**: `false`
+    /// - **follow_disclosures**: `false`
+    /// - **cors**: `false`
+    /// - **header_audit**: `false`
+    /// - **favicon**: `false`
+    /// - **wsdl**: `false`
+    /// - **cloud_storage**: `false`
     /// - **stdin**: `false`
+    /// - **targets**: `None`
+    /// - **target_ranges**: `None`
     /// - **json**: `false`
     /// - **dont_filter**: `false` (auto filter wildcard responses)
     /// - **depth**: `4` (maximum recursion depth)
     /// - **scan_limit**: `0` (no limit on concurrent scans imposed)
+    /// - **max_scans**: `0` (no limit on total scans imposed)
+    /// - **strategy**: `bfs` (shallowest waiting scans are started first)
+    /// - **priority_keywords**: `None` (built-in keyword weights are used)
     /// - **parallel**: `0` (no limit on parallel scans imposed)
     /// - **rate_limit**: `0` (no limit on requests per second imposed)
+    /// - **delay**: `None` (requests are sent as soon as possible)
+    /// - **jitter**: `None` (delays don't vary)
+    /// - **decoy_ratio**: `0` (no decoy requests are sent)
     /// - **time_limit**: `None` (no limit on length of scan imposed)
+    /// - **scan_time_limit**: `None` (no limit on length of each directory scan imposed)
+    /// - **tarpit_latency**: `0` (directory scans are never abandoned)
+    /// - **throttle**: `None` (no limit on download bandwidth imposed)
+    /// - **request_limit**: `0` (no limit on number of requests imposed)
+    /// - **scan_window**: `None` (scanning allowed at any time)
     /// - **replay_proxy**: `None` (no limit on concurrent scans imposed)
     /// - **replay_codes**: [`DEFAULT_RESPONSE_CODES`](constant.DEFAULT_RESPONSE_CODES.html)
     ///
@@ -373,8 +986,16 @@ impl Configuration {
     ///
     /// If more than one valid configuration file is found, each one overwrites the values found previously.
     ///
-    /// Finally, any options/arguments given on the commandline will override both built-in and
-    /// config-file specified values.
+    /// Config files may also define named profiles, i.e. `[profiles.stealth]`. When a profile is
+    /// selected, either via `--profile` or a top-level `profile = "NAME"` in a config file, its
+    /// values are merged over those found above.
+    ///
+    /// Next, any `FEROX_*` environment variables override the config file values. The variable
+    /// name is the config file key in uppercase, i.e. `FEROX_THREADS=20` or
+    /// `FEROX_STATUS_CODES=200,301`.
+    ///
+    /// Finally, any options/arguments given on the commandline will override built-in,
+    /// config-file, and environment specified values.
     ///
     /// The resulting [Configuration](struct.Configuration.html) is a singleton with a `static`
     /// lifetime.
@@ -397,22 +1018,79 @@ impl Configuration {
         // read in all config files
         Self::parse_config_files(&mut config)?;
 
+        // read in any FEROX_* environment variables
+        let env_config = Self::parse_env_vars(std::env::vars())?;
+
         // read in the user provided options, this produces a separate instance of Configuration
         // in order to allow for potentially merging into a --resume-from Configuration
-        let cli_config = Self::parse_cli_args(&args);
+        let cli_config = Self::parse_cli_args(&args)?;
+
+        // --profile takes precedence over FEROX_PROFILE, which takes precedence over any profile
+        // selected from within a config file
+        if !env_config.profile.is_empty() {
+            config.profile = env_config.profile.clone();
+        }
+
+        if !cli_config.profile.is_empty() {
+            config.profile = cli_config.profile.clone();
+        }
+
+        if !config.profile.is_empty() {
+            let name = config.profile.clone();
+            Self::apply_profile(&mut config, &name)?;
+        }
+
+        // environment variables override config file (and profile) values
+        Self::merge_config(&mut config, env_config);
 
         // --resume-from used, need to first read the Configuration from disk, and then
         // merge the cli_config into the resumed config
         if let Some(filename) = args.value_of("resume_from") {
+            // the password isn't saved in the state file, it has to come from the command line,
+            // the environment, or a config file
+            let password = if cli_config.state_password.is_empty() {
+                config.state_password.clone()
+            } else {
+                cli_config.state_password.clone()
+            };
+
             // when resuming a scan, instead of normal configuration loading, we just
             // load the config from disk by calling resume_scan
-            let mut previous_config = resume_scan(filename);
+            // a wordlist (or rules) given on the command line replaces the saved one, so there's
+            // no point looking for (or checking) the saved one
+            let given: Vec<&str> = ["wordlist", "wordlist_rules", "auto_wordlist"]
+                .iter()
+                .copied()
+                .filter(|name| args.is_present(name))
+                .collect();
+
+            let mut previous_config = resume_scan(filename, &password, &given)?;
 
             // if any other arguments were passed on the command line, the theory is that the
             // user meant to modify the previously cancelled/saved scan in some way that we
             // should take into account
             Self::merge_config(&mut previous_config, cli_config);
 
+            // credentials removed by --state-redact would otherwise be sent as-is
+            let redacted = redacted_settings(&previous_config);
+
+            if !redacted.is_empty() {
+                return Err(anyhow!(fmt_err(&format!(
+                    "{} was saved with --state-redact; give {} again to resume it",
+                    filename,
+                    redacted.join(", ")
+                ))));
+            }
+
+            // --check-update overrides check_update = false from a config file; merge_config
+            // can't tell the flag apart from the default, which is also true
+            if args.is_present("check_update") {
+                previous_config.check_update = true;
+            }
+
+            // keep encrypting with the same password if the resumed scan is saved again
+            previous_config.state_password = password;
+
             // the resumed flag isn't printed in the banner and really has no business being
             // serialized or included in much of the usual config logic; simply setting it to true
             // here and being done with it
@@ -423,8 +1101,16 @@ impl Configuration {
             // not flipped to false, the program hangs waiting for input from stdin again)
             previous_config.stdin = false;
 
+            // --api adds headers, which have to be in place before the clients are built
+            api_mode::apply(&mut previous_config);
+
             // clients aren't serialized, have to remake them from the previous config
-            Self::try_rebuild_clients(&mut previous_config);
+            Self::try_rebuild_clients(&mut previous_config)?;
+
+            // same goes for plugins and --query-file values
+            Self::try_load_plugins(&mut previous_config)?;
+            Self::try_load_query_values(&mut previous_config)?;
+            Self::try_seed_shuffle(&mut previous_config);
 
             return Ok(previous_config);
         }
@@ -433,12 +1119,30 @@ impl Configuration {
         // merge the cli options into the config file options and return the result
         Self::merge_config(&mut config, cli_config);
 
-        // rebuild clients is the last step in either code branch
-        Self::try_rebuild_clients(&mut config);
+        // same as above, --check-update overrides check_update = false from a config file
+        if args.is_present("check_update") {
+            config.check_update = true;
+        }
+
+        // rebuild clients and load plugins/query values are the last steps in either code branch
+        api_mode::apply(&mut config);
+        Self::try_rebuild_clients(&mut config)?;
+        Self::try_load_plugins(&mut config)?;
+        Self::try_load_query_values(&mut config)?;
+        Self::try_seed_shuffle(&mut config);
 
         Ok(config)
     }
 
+    /// Pick a random seed when shuffling without one, so that the order can be reproduced from
+    /// the banner or the state file
+    fn try_seed_shuffle(config: &mut Self) {
+        if config.shuffle && config.seed == 0 {
+            // 0 is reserved for 'not yet chosen'
+            config.seed = rand::random::<u64>().max(1);
+        }
+    }
+
     /// Parse all possible versions of the ferox-config.toml file, adhering to the order of
     /// precedence outlined above
     fn parse_config_files(mut config: &mut Self) -> Result<()> {
@@ -486,91 +1190,180 @@ impl Configuration {
 
     /// Given a set of ArgMatches read from the CLI, update and return the default Configuration
     /// settings
-    fn parse_cli_args(args: &ArgMatches) -> Self {
+    fn parse_cli_args(args: &ArgMatches) -> FeroxResult<Self> {
         let mut config = Configuration::default();
 
         update_config_if_present!(&mut config.threads, args, "threads", usize);
         update_config_if_present!(&mut config.depth, args, "depth", usize);
         update_config_if_present!(&mut config.scan_limit, args, "scan_limit", usize);
+        update_config_if_present!(&mut config.max_scans, args, "max_scans", usize);
+        update_config_if_present!(&mut config.strategy, args, "strategy", String);
+        update_config_if_present!(&mut config.query_file, args, "query_file", String);
+        update_config_if_present!(
+            &mut config.url_encode_level,
+            args,
+            "url_encode_level",
+            String
+        );
+        update_config_if_present!(&mut config.seed, args, "seed", u64);
+        update_config_if_present!(
+            &mut config.priority_keywords,
+            args,
+            "priority_keywords",
+            String
+        );
         update_config_if_present!(&mut config.parallel, args, "parallel", usize);
         update_config_if_present!(&mut config.rate_limit, args, "rate_limit", usize);
+        update_config_if_present!(&mut config.delay, args, "delay", String);
+        update_config_if_present!(&mut config.jitter, args, "jitter", String);
+        update_config_if_present!(&mut config.decoy_ratio, args, "decoy_ratio", usize);
         update_config_if_present!(&mut config.wordlist, args, "wordlist", String);
+        config.wordlist_given = args.is_present("wordlist");
         update_config_if_present!(&mut config.output, args, "output", String);
+        update_config_if_present!(&mut config.urls_file, args, "urls_file", String);
+        update_config_if_present!(&mut config.har, args, "har", String);
+        update_config_if_present!(&mut config.output_db, args, "output_db", String);
+        update_config_if_present!(&mut config.output_dsn, args, "output_dsn", String);
+        update_config_if_present!(&mut config.split_output, args, "split_output", String);
+        update_config_if_present!(&mut config.output_dir, args, "output_dir", String);
+        update_config_if_present!(&mut config.compare, args, "compare", String);
+        update_config_if_present!(&mut config.schedule, args, "schedule", String);
         update_config_if_present!(&mut config.debug_log, args, "debug_log", String);
+        update_config_if_present!(
+            &mut config.debug_log_max_size,
+            args,
+            "debug_log_max_size",
+            String
+        );
+        update_config_if_present!(
+            &mut config.debug_log_backups,
+            args,
+            "debug_log_backups",
+            usize
+        );
+
+        if args.is_present("debug_log_json") {
+            config.debug_log_json = true;
+        }
+
+        update_config_if_present!(&mut config.cache_dir, args, "cache_dir", String);
         update_config_if_present!(&mut config.time_limit, args, "time_limit", String);
+        update_config_if_present!(&mut config.scan_time_limit, args, "scan_time_limit", String);
+        update_config_if_present!(&mut config.tarpit_latency, args, "tarpit_latency", u64);
+        update_config_if_present!(&mut config.throttle, args, "throttle", String);
+        update_config_if_present!(&mut config.request_limit, args, "request_limit", usize);
+        update_config_if_present!(&mut config.scan_window, args, "scan_window", String);
         update_config_if_present!(&mut config.resume_from, args, "resume_from", String);
+        update_config_if_present!(&mut config.state_password, args, "state_password", String);
+        update_config_if_present!(&mut config.filter_command, args, "filter_command", String);
+        update_config_if_present!(&mut config.auto_wordlist, args, "auto_wordlist", String);
+        update_config_if_present!(&mut config.wordlist_rules, args, "wordlist_rules", String);
+        update_config_if_present!(
+            &mut config.interesting_rules,
+            args,
+            "interesting_rules",
+            String
+        );
+        update_config_if_present!(&mut config.notify_webhook, args, "notify_webhook", String);
+        update_config_if_present!(&mut config.on_found, args, "on_found", String);
+        update_config_if_present!(&mut config.on_found_limit, args, "on_found_limit", usize);
+        update_config_if_present!(&mut config.fingerprint_db, args, "fingerprint_db", String);
+        update_config_if_present!(&mut config.bypass_budget, args, "bypass_budget", usize);
+        update_config_if_present!(&mut config.show_preview, args, "show_preview", usize);
+        update_config_if_present!(&mut config.group_similar, args, "group_similar", usize);
+        update_config_if_present!(&mut config.metrics_addr, args, "metrics_addr", String);
+        update_config_if_present!(&mut config.api_addr, args, "api_addr", String);
+        update_config_if_present!(&mut config.grpc, args, "grpc", String);
+        update_config_if_present!(&mut config.profile, args, "profile", String);
 
         if let Some(arg) = args.values_of("status_codes") {
-            config.status_codes = arg
-                .map(|code| {
-                    StatusCode::from_bytes(code.as_bytes())
-                        .unwrap_or_else(|e| report_and_exit(&e.to_string()))
-                        .as_u16()
-                })
-                .collect();
+            // classes (2xx) and all are expanded to the codes they cover
+            config.status_codes = parser::expand_status_codes(arg).map_err(FeroxError::argument)?;
+        }
+
+        if let Some(rules) = args.values_of("extension_status") {
+            for rule in rules {
+                // php:200,403 -> (php, [200, 403])
+                let mut split_val = rule.splitn(2, ':');
+
+                let extension = split_val.next().unwrap().trim();
+
+                let codes = split_val.next().ok_or_else(|| {
+                    FeroxError::argument(format!(
+                        "Invalid --extension-status {}, expected EXTENSION:STATUS_CODE[,STATUS_CODE...]",
+                        rule
+                    ))
+                })?;
+                let codes = parse_status_codes(codes.split(',').map(str::trim))?;
+
+                config
+                    .extension_status
+                    .insert(normalize_extension(extension), codes);
+            }
         }
 
         if let Some(arg) = args.values_of("replay_codes") {
             // replay codes passed in by the user
-            config.replay_codes = arg
-                .map(|code| {
-                    StatusCode::from_bytes(code.as_bytes())
-                        .unwrap_or_else(|e| report_and_exit(&e.to_string()))
-                        .as_u16()
-                })
-                .collect();
+            config.replay_codes = parse_status_codes(arg)?;
         } else {
             // not passed in by the user, use whatever value is held in status_codes
             config.replay_codes = config.status_codes.clone();
         }
 
         if let Some(arg) = args.values_of("filter_status") {
-            config.filter_status = arg
-                .map(|code| {
-                    StatusCode::from_bytes(code.as_bytes())
-                        .unwrap_or_else(|e| report_and_exit(&e.to_string()))
-                        .as_u16()
-                })
-                .collect();
+            config.filter_status = parse_status_codes(arg)?;
+        }
+
+        if let Some(arg) = args.values_of("notify_on") {
+            config.notify_on = parse_status_codes(arg)?;
         }
 
         if let Some(arg) = args.values_of("extensions") {
             config.extensions = arg.map(|val| val.to_string()).collect();
         }
 
+        if let Some(arg) = args.values_of("hashes") {
+            config.hashes = arg.map(|val| val.to_string()).collect();
+        }
+
+        if let Some(arg) = args.values_of("bypass_techniques") {
+            config.bypass_techniques = arg.map(|val| val.to_string()).collect();
+        }
+
         if let Some(arg) = args.values_of("filter_regex") {
             config.filter_regex = arg.map(|val| val.to_string()).collect();
         }
 
+        if let Some(arg) = args.values_of("dont_recurse_regex") {
+            config.dont_recurse_regex = arg.map(|val| val.to_string()).collect();
+        }
+
         if let Some(arg) = args.values_of("filter_similar") {
             config.filter_similar = arg.map(|val| val.to_string()).collect();
         }
 
+        if let Some(arg) = args.values_of("filter_header") {
+            config.filter_header = arg.map(|val| val.to_string()).collect();
+        }
+
+        if let Some(arg) = args.values_of("match_header") {
+            config.match_header = arg.map(|val| val.to_string()).collect();
+        }
+
+        if let Some(arg) = args.values_of("plugins") {
+            config.plugins = arg.map(|val| val.to_string()).collect();
+        }
+
         if let Some(arg) = args.values_of("filter_size") {
-            config.filter_size = arg
-                .map(|size| {
-                    size.parse::<u64>()
-                        .unwrap_or_else(|e| report_and_exit(&e.to_string()))
-                })
-                .collect();
+            config.filter_size = parse_values(arg)?;
         }
 
         if let Some(arg) = args.values_of("filter_words") {
-            config.filter_word_count = arg
-                .map(|size| {
-                    size.parse::<usize>()
-                        .unwrap_or_else(|e| report_and_exit(&e.to_string()))
-                })
-                .collect();
+            config.filter_word_count = parse_values(arg)?;
         }
 
         if let Some(arg) = args.values_of("filter_lines") {
-            config.filter_line_count = arg
-                .map(|size| {
-                    size.parse::<usize>()
-                        .unwrap_or_else(|e| report_and_exit(&e.to_string()))
-                })
-                .collect();
+            config.filter_line_count = parse_values(arg)?;
         }
 
         if args.is_present("silent") {
@@ -587,6 +1380,10 @@ impl Configuration {
             config.output_level = OutputLevel::Quiet;
         }
 
+        if args.is_present("no_color") {
+            config.no_color = true;
+        }
+
         if args.is_present("auto_tune") {
             config.auto_tune = true;
             config.requester_policy = RequesterPolicy::AutoTune;
@@ -597,6 +1394,14 @@ impl Configuration {
             config.requester_policy = RequesterPolicy::AutoBail;
         }
 
+        update_config_if_present!(&mut config.auto_pause, args, "auto_pause", usize);
+        update_config_if_present!(
+            &mut config.auto_pause_window,
+            args,
+            "auto_pause_window",
+            usize
+        );
+
         if args.is_present("dont_filter") {
             config.dont_filter = true;
         }
@@ -615,20 +1420,152 @@ impl Configuration {
             config.add_slash = true;
         }
 
-        if args.is_present("extract_links") {
-            config.extract_links = true;
+        if args.is_present("smart_slash") {
+            config.smart_slash = true;
         }
 
-        if args.is_present("json") {
+        if args.is_present("shuffle") {
+            config.shuffle = true;
+        }
+
+        if args.is_present("extract_links") {
+            config.extract_links = true;
+        }
+
+        if args.is_present("parse_listings") {
+            config.parse_listings = true;
+        }
+
+        if args.is_present("smart_wordlist") {
+            config.smart_wordlist = true;
+        }
+
+        if args.is_present("api") {
+            config.api = true;
+        }
+
+        if args.is_present("fingerprint") {
+            config.fingerprint = true;
+        }
+
+        if args.is_present("bypass_403") {
+            config.bypass_403 = true;
+        }
+
+        if args.is_present("verb_compare") {
+            config.verb_compare = true;
+        }
+
+        if args.is_present("follow_disclosures") {
+            config.follow_disclosures = true;
+        }
+
+        if args.is_present("cors") {
+            config.cors = true;
+        }
+
+        if args.is_present("header_audit") {
+            config.header_audit = true;
+        }
+
+        if args.is_present("favicon") {
+            config.favicon = true;
+        }
+
+        if args.is_present("wsdl") {
+            config.wsdl = true;
+        }
+
+        if args.is_present("cloud_storage") {
+            config.cloud_storage = true;
+        }
+
+        if args.is_present("auto_extensions") {
+            config.auto_extensions = true;
+        }
+
+        if args.is_present("json") {
             config.json = true;
         }
 
+        if args.is_present("dry_run") {
+            config.dry_run = true;
+        }
+
+        if args.is_present("low_memory") {
+            config.low_memory = true;
+        }
+
+        if args.is_present("check_update") {
+            config.check_update = true;
+        }
+
+        if args.is_present("state_redact") {
+            config.state_redact = true;
+        }
+
+        if args.is_present("state_embed_wordlist") {
+            config.state_embed_wordlist = true;
+        }
+
+        if args.is_present("print_config") {
+            config.print_config = true;
+        }
+
+        if let Some(refilter) = args.subcommand_matches("refilter") {
+            // refilter subcommand; its own -o|--output and --json take the place of the scan's
+            update_config_if_present!(&mut config.refilter, refilter, "input", String);
+            update_config_if_present!(&mut config.output, refilter, "output", String);
+
+            if refilter.is_present("json") {
+                config.json = true;
+            }
+        }
+
+        match args.subcommand() {
+            (action, Some(control)) if ["scans", "pause", "resume", "cancel"].contains(&action) => {
+                // subcommands that control a running instance through its --api-addr
+                config.control = String::from(action);
+                update_config_if_present!(&mut config.control_id, control, "scan_id", String);
+            }
+            _ => {}
+        }
+
+        if args.subcommand_matches("update").is_some() {
+            config.update = true;
+        }
+
+        if let Some(completions) = args.subcommand_matches("completions") {
+            update_config_if_present!(&mut config.completions, completions, "shell", String);
+        }
+
+        if args.subcommand_matches("manpage").is_some() {
+            config.manpage = true;
+        }
+
+        if let Some(wordlists) = args.subcommand_matches("wordlists") {
+            // wordlists list|fetch NAME...
+            if let (action, Some(matches)) = wordlists.subcommand() {
+                config.wordlists = String::from(action);
+
+                if let Some(names) = matches.values_of("names") {
+                    config.wordlist_names = names.map(String::from).collect();
+                }
+            }
+        }
+
         if args.is_present("stdin") {
             config.stdin = true;
         } else if let Some(url) = args.value_of("url") {
             config.target_url = String::from(url);
         }
 
+        update_config_if_present!(&mut config.targets, args, "targets", String);
+
+        if let Some(arg) = args.values_of("target_ranges") {
+            config.target_ranges = arg.map(|val| val.to_string()).collect();
+        }
+
         ////
         // organizational breakpoint; all options below alter the Client configuration
         ////
@@ -636,6 +1573,7 @@ impl Configuration {
         update_config_if_present!(&mut config.replay_proxy, args, "replay_proxy", String);
         update_config_if_present!(&mut config.user_agent, args, "user_agent", String);
         update_config_if_present!(&mut config.timeout, args, "timeout", u64);
+        update_config_if_present!(&mut config.dns_cache_ttl, args, "dns_cache_ttl", u64);
 
         if args.is_present("redirects") {
             config.redirects = true;
@@ -645,6 +1583,20 @@ impl Configuration {
             config.insecure = true;
         }
 
+        update_config_if_present!(&mut config.ca_cert, args, "ca_cert", String);
+
+        if let Some(arg) = args.values_of("pin_sha256") {
+            config.pin_sha256 = arg.map(|val| val.to_string()).collect();
+        }
+
+        if args.is_present("raw") {
+            config.raw = true;
+        }
+
+        if let Some(arg) = args.values_of("raw_headers") {
+            config.raw_headers = arg.map(|val| val.to_string()).collect();
+        }
+
         if let Some(headers) = args.values_of("headers") {
             for val in headers {
                 let mut split_val = val.split(':');
@@ -672,18 +1624,21 @@ impl Configuration {
             }
         }
 
-        config
+        Ok(config)
     }
 
     /// this function determines if we've gotten a Client configuration change from
     /// either the config file or command line arguments; if we have, we need to rebuild
     /// the client and store it in the config struct
-    fn try_rebuild_clients(configuration: &mut Configuration) {
+    fn try_rebuild_clients(configuration: &mut Configuration) -> FeroxResult<()> {
         if !configuration.proxy.is_empty()
             || configuration.timeout != timeout()
+            || configuration.dns_cache_ttl != dns_cache_ttl()
             || configuration.user_agent != user_agent()
             || configuration.redirects
             || configuration.insecure
+            || !configuration.ca_cert.is_empty()
+            || !configuration.pin_sha256.is_empty()
             || !configuration.headers.is_empty()
             || configuration.resumed
         {
@@ -692,24 +1647,32 @@ impl Configuration {
                     configuration.timeout,
                     &configuration.user_agent,
                     configuration.redirects,
-                    configuration.insecure,
+                    Trust::from_config(configuration),
                     &configuration.headers,
                     None,
+                    configuration.dns_cache_ttl,
                 )
-                .expect("Could not rebuild client")
+                .map_err(|e| FeroxError::client("scan", e))?
             } else {
                 configuration.client = client::initialize(
                     configuration.timeout,
                     &configuration.user_agent,
                     configuration.redirects,
-                    configuration.insecure,
+                    Trust::from_config(configuration),
                     &configuration.headers,
                     Some(&configuration.proxy),
+                    configuration.dns_cache_ttl,
                 )
-                .expect("Could not rebuild client")
+                .map_err(|e| FeroxError::client("scan", e))?
             }
         }
 
+        if !configuration.pin_sha256.is_empty() {
+            configuration.pin_verifier = Some(Arc::new(
+                PinVerifier::new(configuration).map_err(|e| FeroxError::client("scan", e))?,
+            ));
+        }
+
         if !configuration.replay_proxy.is_empty() {
             // only set replay_client when replay_proxy is set
             configuration.replay_client = Some(
@@ -717,13 +1680,120 @@ impl Configuration {
                     configuration.timeout,
                     &configuration.user_agent,
                     configuration.redirects,
-                    configuration.insecure,
+                    Trust::from_config(configuration),
                     &configuration.headers,
                     Some(&configuration.replay_proxy),
+                    configuration.dns_cache_ttl,
                 )
-                .expect("Could not rebuild client"),
+                .map_err(|e| FeroxError::client("replay", e))?,
             );
         }
+
+        Ok(())
+    }
+
+    /// load any plugins specified via --plugin or the config file and store them in the
+    /// config struct
+    fn try_load_plugins(configuration: &mut Configuration) -> Result<()> {
+        if !configuration.plugins.is_empty() {
+            configuration.loaded_plugins =
+                Arc::new(FeroxPlugins::initialize(&configuration.plugins)?);
+        }
+
+        Ok(())
+    }
+
+    /// read the values specified via --query-file or the config file and store them in the
+    /// config struct
+    fn try_load_query_values(configuration: &mut Configuration) -> Result<()> {
+        if !configuration.query_file.is_empty() {
+            // same rules as a wordlist; empty lines and comments are skipped
+            let values = Wordlist::from_file(&configuration.query_file)?;
+            configuration.query_values = values.iter().map(String::from).collect();
+        }
+
+        Ok(())
+    }
+
+    /// Read the --targets file, returning one Configuration per target; each is a copy of this
+    /// Configuration, with `target_url` set to the target and any options given alongside it
+    /// layered on top
+    ///
+    /// empty lines and comments (#) are skipped
+    pub fn read_targets(&self) -> Result<Vec<Configuration>> {
+        let contents = read_to_string(&self.targets)
+            .with_context(|| fmt_err(&format!("Could not open {}", self.targets)))?;
+
+        let mut overlays = Vec::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let words = parser::split_target_line(line)
+                .map_err(|e| anyhow!("{}:{}: {}", self.targets, number + 1, e))?;
+
+            let args = parser::target_overrides()
+                .get_matches_from_safe(words)
+                .map_err(|e| anyhow!("{}:{}: {}", self.targets, number + 1, e.message))?;
+
+            let mut overlay = self.clone();
+            Self::apply_target_overrides(&mut overlay, &args)?;
+            overlays.push(overlay);
+        }
+
+        Ok(overlays)
+    }
+
+    /// Layer the options given on a single line of a --targets file on top of `config`
+    fn apply_target_overrides(config: &mut Configuration, args: &ArgMatches) -> FeroxResult<()> {
+        if let Some(url) = args.value_of("url") {
+            config.target_url = String::from(url);
+        }
+
+        update_config_if_present!(&mut config.depth, args, "depth", usize);
+
+        if let Some(arg) = args.values_of("extensions") {
+            config.extensions = arg.map(|val| val.to_string()).collect();
+        }
+
+        if let Some(queries) = args.values_of("queries") {
+            for val in queries {
+                let mut split_val = val.splitn(2, '=');
+                let name = split_val.next().unwrap().trim();
+                let value = split_val.next().unwrap_or_default();
+                config.queries.push((name.to_string(), value.to_string()));
+            }
+        }
+
+        // headers and user agent are baked into the client, which has to be remade if either
+        // one changed
+        let mut rebuild = false;
+
+        if let Some(user_agent) = args.value_of("user_agent") {
+            config.user_agent = String::from(user_agent);
+            rebuild = true;
+        }
+
+        if let Some(headers) = args.values_of("headers") {
+            for val in headers {
+                // headers given for the target are added to (or replace) the global ones
+                let mut split_val = val.splitn(2, ':');
+                let name = split_val.next().unwrap().trim();
+                let value = split_val.next().unwrap_or_default();
+                config.headers.insert(name.to_string(), value.to_string());
+            }
+            rebuild = true;
+        }
+
+        if rebuild {
+            Self::try_rebuild_clients(config)?;
+        }
+
+        Ok(())
     }
 
     /// Given a configuration file's location and an instance of `Configuration`, read in
@@ -749,28 +1819,114 @@ impl Configuration {
         //  - kind
         //  - client
         //  - replay_client
+        //  - loaded_plugins
+        //  - query_values
         //  - resumed
         //  - config
         update_if_not_default!(&mut conf.target_url, new.target_url, "");
+        update_if_not_default!(&mut conf.targets, new.targets, "");
+        update_if_not_default!(
+            &mut conf.target_ranges,
+            new.target_ranges,
+            Vec::<String>::new()
+        );
         update_if_not_default!(&mut conf.time_limit, new.time_limit, "");
+        update_if_not_default!(&mut conf.scan_time_limit, new.scan_time_limit, "");
+        update_if_not_default!(&mut conf.tarpit_latency, new.tarpit_latency, 0);
+        update_if_not_default!(&mut conf.throttle, new.throttle, "");
+        update_if_not_default!(&mut conf.request_limit, new.request_limit, 0);
+        update_if_not_default!(&mut conf.scan_window, new.scan_window, "");
+        update_if_not_default!(&mut conf.filter_command, new.filter_command, "");
         update_if_not_default!(&mut conf.proxy, new.proxy, "");
         update_if_not_default!(&mut conf.verbosity, new.verbosity, 0);
         update_if_not_default!(&mut conf.silent, new.silent, false);
         update_if_not_default!(&mut conf.quiet, new.quiet, false);
+        update_if_not_default!(&mut conf.no_color, new.no_color, false);
+        update_if_not_default!(&mut conf.colors, new.colors, HashMap::new());
         update_if_not_default!(&mut conf.auto_bail, new.auto_bail, false);
         update_if_not_default!(&mut conf.auto_tune, new.auto_tune, false);
+        update_if_not_default!(&mut conf.auto_pause, new.auto_pause, 0);
+        update_if_not_default!(
+            &mut conf.auto_pause_window,
+            new.auto_pause_window,
+            auto_pause_window()
+        );
         // use updated quiet/silent values to determine output level; same for requester policy
         conf.output_level = determine_output_level(conf.quiet, conf.silent);
         conf.requester_policy = determine_requester_policy(conf.auto_tune, conf.auto_bail);
         update_if_not_default!(&mut conf.output, new.output, "");
+        update_if_not_default!(&mut conf.urls_file, new.urls_file, "");
+        update_if_not_default!(&mut conf.har, new.har, "");
+        update_if_not_default!(&mut conf.output_db, new.output_db, "");
+        update_if_not_default!(&mut conf.output_dsn, new.output_dsn, "");
+        update_if_not_default!(&mut conf.split_output, new.split_output, "");
+        update_if_not_default!(&mut conf.output_dir, new.output_dir, "");
+        update_if_not_default!(&mut conf.compare, new.compare, "");
+        update_if_not_default!(&mut conf.schedule, new.schedule, "");
+        update_if_not_default!(&mut conf.refilter, new.refilter, "");
+        update_if_not_default!(&mut conf.control, new.control, "");
+        update_if_not_default!(&mut conf.control_id, new.control_id, "");
+        update_if_not_default!(&mut conf.update, new.update, false);
+        update_if_not_default!(&mut conf.completions, new.completions, "");
+        update_if_not_default!(&mut conf.manpage, new.manpage, false);
+        update_if_not_default!(&mut conf.wordlists, new.wordlists, "");
+        update_if_not_default!(
+            &mut conf.wordlist_names,
+            new.wordlist_names,
+            Vec::<String>::new()
+        );
         update_if_not_default!(&mut conf.redirects, new.redirects, false);
         update_if_not_default!(&mut conf.insecure, new.insecure, false);
+        update_if_not_default!(&mut conf.ca_cert, new.ca_cert, "");
+        update_if_not_default!(&mut conf.pin_sha256, new.pin_sha256, Vec::<String>::new());
         update_if_not_default!(&mut conf.extract_links, new.extract_links, false);
+        update_if_not_default!(&mut conf.parse_listings, new.parse_listings, false);
+        update_if_not_default!(&mut conf.smart_wordlist, new.smart_wordlist, false);
+        update_if_not_default!(&mut conf.api, new.api, false);
+        update_if_not_default!(&mut conf.fingerprint, new.fingerprint, false);
+        update_if_not_default!(&mut conf.hashes, new.hashes, Vec::<String>::new());
+        update_if_not_default!(&mut conf.fingerprint_db, new.fingerprint_db, "");
+        update_if_not_default!(&mut conf.show_preview, new.show_preview, 0);
+        update_if_not_default!(&mut conf.group_similar, new.group_similar, 0);
+        update_if_not_default!(&mut conf.auto_extensions, new.auto_extensions, false);
+        update_if_not_default!(&mut conf.auto_wordlist, new.auto_wordlist, "");
+        update_if_not_default!(&mut conf.wordlist_rules, new.wordlist_rules, "");
+        update_if_not_default!(&mut conf.interesting_rules, new.interesting_rules, "");
+        update_if_not_default!(&mut conf.bypass_403, new.bypass_403, false);
+        update_if_not_default!(
+            &mut conf.bypass_techniques,
+            new.bypass_techniques,
+            Vec::<String>::new()
+        );
+        update_if_not_default!(&mut conf.bypass_budget, new.bypass_budget, bypass_budget());
+        update_if_not_default!(&mut conf.verb_compare, new.verb_compare, false);
+        update_if_not_default!(&mut conf.follow_disclosures, new.follow_disclosures, false);
+        update_if_not_default!(&mut conf.cors, new.cors, false);
+        update_if_not_default!(&mut conf.header_audit, new.header_audit, false);
+        update_if_not_default!(&mut conf.favicon, new.favicon, false);
+        update_if_not_default!(&mut conf.wsdl, new.wsdl, false);
+        update_if_not_default!(&mut conf.cloud_storage, new.cloud_storage, false);
         update_if_not_default!(&mut conf.extensions, new.extensions, Vec::<String>::new());
         update_if_not_default!(&mut conf.headers, new.headers, HashMap::new());
+        update_if_not_default!(&mut conf.raw, new.raw, false);
+        update_if_not_default!(&mut conf.raw_headers, new.raw_headers, Vec::<String>::new());
         update_if_not_default!(&mut conf.queries, new.queries, Vec::new());
+        update_if_not_default!(&mut conf.query_file, new.query_file, "");
         update_if_not_default!(&mut conf.no_recursion, new.no_recursion, false);
+        update_if_not_default!(
+            &mut conf.dont_recurse_regex,
+            new.dont_recurse_regex,
+            Vec::<String>::new()
+        );
         update_if_not_default!(&mut conf.add_slash, new.add_slash, false);
+        update_if_not_default!(&mut conf.smart_slash, new.smart_slash, false);
+        update_if_not_default!(
+            &mut conf.url_encode_level,
+            new.url_encode_level,
+            url_encode_level()
+        );
+        update_if_not_default!(&mut conf.shuffle, new.shuffle, false);
+        update_if_not_default!(&mut conf.seed, new.seed, 0);
         update_if_not_default!(&mut conf.stdin, new.stdin, false);
         update_if_not_default!(&mut conf.filter_size, new.filter_size, Vec::<u64>::new());
         update_if_not_default!(
@@ -783,6 +1939,36 @@ impl Configuration {
             new.filter_similar,
             Vec::<String>::new()
         );
+        update_if_not_default!(
+            &mut conf.filter_header,
+            new.filter_header,
+            Vec::<String>::new()
+        );
+        update_if_not_default!(
+            &mut conf.match_header,
+            new.match_header,
+            Vec::<String>::new()
+        );
+        update_if_not_default!(&mut conf.plugins, new.plugins, Vec::<String>::new());
+        update_if_not_default!(&mut conf.notify_webhook, new.notify_webhook, "");
+        update_if_not_default!(&mut conf.notify_on, new.notify_on, Vec::<u16>::new());
+        update_if_not_default!(&mut conf.on_found, new.on_found, "");
+        update_if_not_default!(
+            &mut conf.on_found_limit,
+            new.on_found_limit,
+            on_found_limit()
+        );
+        update_if_not_default!(&mut conf.metrics_addr, new.metrics_addr, "");
+        update_if_not_default!(&mut conf.api_addr, new.api_addr, "");
+        update_if_not_default!(&mut conf.grpc, new.grpc, "");
+        update_if_not_default!(&mut conf.profile, new.profile, "");
+        update_if_not_default!(&mut conf.print_config, new.print_config, false);
+        update_if_not_default!(&mut conf.dry_run, new.dry_run, false);
+        update_if_not_default!(&mut conf.low_memory, new.low_memory, false);
+        update_if_not_default!(&mut conf.check_update, new.check_update, check_update());
+
+        // profiles from later config files override any of the same name found earlier
+        conf.profiles.extend(new.profiles);
         update_if_not_default!(
             &mut conf.filter_word_count,
             new.filter_word_count,
@@ -800,22 +1986,50 @@ impl Configuration {
         );
         update_if_not_default!(&mut conf.dont_filter, new.dont_filter, false);
         update_if_not_default!(&mut conf.scan_limit, new.scan_limit, 0);
+        update_if_not_default!(&mut conf.max_scans, new.max_scans, 0);
+        update_if_not_default!(&mut conf.strategy, new.strategy, strategy());
+        update_if_not_default!(&mut conf.priority_keywords, new.priority_keywords, "");
         update_if_not_default!(&mut conf.parallel, new.parallel, 0);
         update_if_not_default!(&mut conf.rate_limit, new.rate_limit, 0);
+        update_if_not_default!(&mut conf.delay, new.delay, "");
+        update_if_not_default!(&mut conf.jitter, new.jitter, "");
+        update_if_not_default!(&mut conf.decoy_ratio, new.decoy_ratio, 0);
         update_if_not_default!(&mut conf.replay_proxy, new.replay_proxy, "");
         update_if_not_default!(&mut conf.debug_log, new.debug_log, "");
+        update_if_not_default!(&mut conf.debug_log_json, new.debug_log_json, false);
+        update_if_not_default!(&mut conf.debug_log_max_size, new.debug_log_max_size, "");
+        update_if_not_default!(
+            &mut conf.debug_log_backups,
+            new.debug_log_backups,
+            debug_log_backups()
+        );
+        update_if_not_default!(&mut conf.cache_dir, new.cache_dir, "");
         update_if_not_default!(&mut conf.resume_from, new.resume_from, "");
         update_if_not_default!(&mut conf.json, new.json, false);
 
         update_if_not_default!(&mut conf.timeout, new.timeout, timeout());
+        update_if_not_default!(&mut conf.dns_cache_ttl, new.dns_cache_ttl, dns_cache_ttl());
         update_if_not_default!(&mut conf.user_agent, new.user_agent, user_agent());
         update_if_not_default!(&mut conf.threads, new.threads, threads());
         update_if_not_default!(&mut conf.depth, new.depth, depth());
         update_if_not_default!(&mut conf.wordlist, new.wordlist, wordlist());
+        update_if_not_default!(&mut conf.wordlist_given, new.wordlist_given, false);
         update_if_not_default!(&mut conf.status_codes, new.status_codes, status_codes());
+        update_if_not_default!(
+            &mut conf.extension_status,
+            new.extension_status,
+            HashMap::new()
+        );
         // status_codes() is the default for replay_codes, if they're not provided
         update_if_not_default!(&mut conf.replay_codes, new.replay_codes, status_codes());
         update_if_not_default!(&mut conf.save_state, new.save_state, save_state());
+        update_if_not_default!(&mut conf.state_redact, new.state_redact, false);
+        update_if_not_default!(
+            &mut conf.state_embed_wordlist,
+            new.state_embed_wordlist,
+            false
+        );
+        update_if_not_default!(&mut conf.state_password, new.state_password, "");
     }
 
     /// If present, read in `DEFAULT_CONFIG_NAME` and deserialize the specified values
@@ -823,9 +2037,433 @@ impl Configuration {
     /// uses serde to deserialize the toml into a `Configuration` struct
     pub(super) fn parse_config(config_file: PathBuf) -> Result<Self> {
         let content = read_to_string(config_file)?;
-        let config: Self = toml::from_str(content.as_str())?;
+        let mut table: toml::value::Table = toml::from_str(content.as_str())?;
+
+        // [profiles.NAME] tables are set aside until a profile is selected
+        let profiles = match table.remove("profiles") {
+            Some(toml::Value::Table(profiles)) => profiles.into_iter().collect(),
+            Some(_) => {
+                return Err(anyhow!(
+                    "profiles should be a table of [profiles.NAME] sections"
+                ))
+            }
+            None => HashMap::new(),
+        };
+
+        let wordlist_given = table.contains_key("wordlist");
+
+        let mut config: Self = toml::Value::Table(table).try_into()?;
+        config.profiles = profiles;
+        config.wordlist_given = wordlist_given;
+
         Ok(config)
     }
+
+    /// Status Codes that should be reported for a response with the given extension; the
+    /// matching `extension_status` rule when there is one, `status_codes` otherwise
+    ///
+    /// extensions are compared case-insensitively; rule keys are normalized when they're read, so
+    /// a rule may be written with or without a leading dot (php or .php)
+    pub fn status_codes_for(&self, extension: Option<&str>) -> &[u16] {
+        extension
+            .and_then(|extension| self.extension_status.get(&normalize_extension(extension)))
+            .unwrap_or(&self.status_codes)
+    }
+
+    /// Whether or not response bodies need to be hashed; the hashes are only used when they're
+    /// shown (--hashes), looked up (--fingerprint-db), or written out as json
+    pub fn needs_body_hashes(&self) -> bool {
+        self.json
+            || !self.hashes.is_empty()
+            || !self.fingerprint_db.is_empty()
+            || !self.output_dir.is_empty()
+            || !self.output_db.is_empty()
+            || !self.output_dsn.is_empty()
+            || !self.filter_command.is_empty()
+            || !self.notify_webhook.is_empty()
+            || !self.api_addr.is_empty()
+            || !self.grpc.is_empty()
+    }
+
+    /// Check the values of the Configuration for problems that would otherwise only surface once
+    /// a scan is underway; returns a description of each problem found
+    ///
+    /// these are errors, a scan doesn't start when any are found; see `warnings` for settings
+    /// that are merely ignored
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let code_lists = [
+            ("status_codes", &self.status_codes),
+            ("replay_codes", &self.replay_codes),
+            ("filter_status", &self.filter_status),
+            ("notify_on", &self.notify_on),
+        ];
+
+        for (name, codes) in code_lists.iter() {
+            for code in codes.iter() {
+                if StatusCode::from_u16(*code).is_err() {
+                    problems.push(format!("{}: {} is not a valid status code", name, code));
+                }
+            }
+        }
+
+        for (extension, codes) in &self.extension_status {
+            for code in codes {
+                if StatusCode::from_u16(*code).is_err() {
+                    problems.push(format!(
+                        "extension_status: {} ({}) is not a valid status code",
+                        code, extension
+                    ));
+                }
+            }
+        }
+
+        for regex in &self.filter_regex {
+            if let Err(e) = Regex::new(regex) {
+                problems.push(format!("filter_regex: {}", e));
+            }
+        }
+
+        for regex in &self.dont_recurse_regex {
+            if let Err(e) = Regex::new(regex) {
+                problems.push(format!("dont_recurse_regex: {}", e));
+            }
+        }
+
+        if self.wordlist == STDIN_WORDLIST {
+            if self.stdin {
+                problems.push(String::from(
+                    "wordlist: -w - and --stdin can't both read from stdin",
+                ));
+            }
+
+            if self.shuffle {
+                problems.push(String::from(
+                    "wordlist: --shuffle can't be used with a streamed wordlist (-w -)",
+                ));
+            }
+
+            if self.smart_wordlist {
+                problems.push(String::from(
+                    "smart_wordlist: can't be used with a streamed wordlist (-w -)",
+                ));
+            }
+
+            if self.resumed {
+                problems.push(String::from(
+                    "wordlist: a scan with a streamed wordlist (-w -) can't be resumed",
+                ));
+            }
+        }
+
+        let urls = [
+            ("target_url", &self.target_url),
+            ("proxy", &self.proxy),
+            ("replay_proxy", &self.replay_proxy),
+        ];
+
+        for (name, url) in urls.iter() {
+            if !url.is_empty() {
+                if let Err(e) = Url::parse(url) {
+                    problems.push(format!("{}: {} is not a valid url: {}", name, url, e));
+                }
+            }
+        }
+
+        for pin in &self.pin_sha256 {
+            if !PIN_REGEX.is_match(pin) {
+                problems.push(format!(
+                    "pin_sha256: {} is not a base64 encoded sha256 hash",
+                    pin
+                ));
+            }
+        }
+
+        if !self.schedule.is_empty() {
+            if let Err(e) = parse_schedule(&self.schedule) {
+                problems.push(format!("schedule: {}", e));
+            }
+        }
+
+        if !["bfs", "dfs", "priority"].contains(&self.strategy.as_str()) {
+            problems.push(format!(
+                "strategy: {} is not one of bfs, dfs, or priority",
+                self.strategy
+            ));
+        }
+
+        if !["none", "standard", "double", "mixed-case"].contains(&self.url_encode_level.as_str()) {
+            problems.push(format!(
+                "url_encode_level: {} is not one of none, standard, double, or mixed-case",
+                self.url_encode_level
+            ));
+        }
+
+        if self.auto_pause > 100 {
+            problems.push(format!(
+                "auto_pause: {} is not a percentage between 1 and 100",
+                self.auto_pause
+            ));
+        }
+
+        if self.auto_pause_window == 0 {
+            problems.push(String::from(
+                "auto_pause_window: at least one response has to be looked at",
+            ));
+        }
+
+        if self.on_found_limit == 0 {
+            problems.push(String::from(
+                "on_found_limit: at least one command has to be allowed to run",
+            ));
+        }
+
+        if self.add_slash && self.smart_slash {
+            problems.push(String::from(
+                "smart_slash: can't be used with add_slash, which always appends the slash",
+            ));
+        }
+
+        if self.low_memory && !self.compare.is_empty() {
+            problems.push(String::from(
+                "low_memory: can't be used with compare, which needs the full responses",
+            ));
+        }
+
+        if self.low_memory && !self.schedule.is_empty() {
+            problems.push(String::from(
+                "low_memory: can't be used with schedule, each scheduled scan is compared to the one before it",
+            ));
+        }
+
+        if !self.debug_log_max_size.is_empty() && !SIZE_REGEX.is_match(&self.debug_log_max_size) {
+            problems.push(format!(
+                "debug_log_max_size: {} is not a valid size (ex: 100mb)",
+                self.debug_log_max_size
+            ));
+        }
+
+        if !self.throttle.is_empty() && !THROTTLE_REGEX.is_match(&self.throttle) {
+            problems.push(format!(
+                "throttle: {} is not a valid bandwidth (ex: 500kb/s)",
+                self.throttle
+            ));
+        }
+
+        for hash in &self.hashes {
+            if !["md5", "sha256"].contains(&hash.as_str()) {
+                problems.push(format!("hashes: {} is not one of md5 or sha256", hash));
+            }
+        }
+
+        for technique in &self.bypass_techniques {
+            if !bypass::TECHNIQUES.contains(&technique.as_str()) {
+                problems.push(format!(
+                    "bypass_techniques: {} is not one of {}",
+                    technique,
+                    bypass::TECHNIQUES.join(", ")
+                ));
+            }
+        }
+
+        if !self.delay.is_empty() && !DELAY_REGEX.is_match(&self.delay) {
+            problems.push(format!(
+                "delay: {} is not a valid delay (ex: 100ms)",
+                self.delay
+            ));
+        }
+
+        if !self.jitter.is_empty() && !DELAY_REGEX.is_match(&self.jitter) {
+            problems.push(format!(
+                "jitter: {} is not a valid delay (ex: 50ms)",
+                self.jitter
+            ));
+        }
+
+        if !self.scan_window.is_empty() && !SCAN_WINDOW_REGEX.is_match(&self.scan_window) {
+            problems.push(format!(
+                "scan_window: {} is not a valid window (ex: 22:00-06:00)",
+                self.scan_window
+            ));
+        }
+
+        if !self.time_limit.is_empty() && !TIMESPEC_REGEX.is_match(&self.time_limit) {
+            problems.push(format!(
+                "time_limit: {} is not a valid time spec (ex: 10m)",
+                self.time_limit
+            ));
+        }
+
+        if !self.scan_time_limit.is_empty() && !TIMESPEC_REGEX.is_match(&self.scan_time_limit) {
+            problems.push(format!(
+                "scan_time_limit: {} is not a valid time spec (ex: 10m)",
+                self.scan_time_limit
+            ));
+        }
+
+        if !self.interesting_rules.is_empty() {
+            if let Err(e) = findings::read_rules(&self.interesting_rules) {
+                problems.push(format!("interesting_rules: {:#}", e));
+            }
+        }
+
+        if !self.wordlist_rules.is_empty() {
+            if let Err(e) = wordlist_rules::read_wordlist_rules(&self.wordlist_rules) {
+                problems.push(format!("wordlist_rules: {:#}", e));
+            }
+        }
+
+        problems
+    }
+
+    /// Check the values of the Configuration for settings that don't stop a scan, but are ignored
+    /// or have no effect; returns a description of each one found
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if self.seed != 0 && !self.shuffle {
+            warnings.push(String::from(
+                "seed: --seed only applies when --shuffle is used",
+            ));
+        }
+
+        for (class, style) in &self.colors {
+            let unknown = theme::unknown_style_parts(style);
+
+            if !unknown.is_empty() {
+                warnings.push(format!(
+                    "colors: {} ({}) has unknown style(s) {}",
+                    style,
+                    class,
+                    unknown.join(", ")
+                ));
+            }
+
+            if !COLOR_CLASSES
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(class))
+            {
+                warnings.push(format!(
+                    "colors: {} is not one of {}",
+                    class,
+                    COLOR_CLASSES.join(", ")
+                ));
+            }
+        }
+
+        if !self.query_file.is_empty()
+            && !self.queries.iter().any(|(name, value)| {
+                name.contains(QUERY_PLACEHOLDER) || value.contains(QUERY_PLACEHOLDER)
+            })
+        {
+            warnings.push(format!(
+                "query_file: none of the queries contain {} to substitute values into",
+                QUERY_PLACEHOLDER
+            ));
+        }
+
+        if self.raw && !self.proxy.is_empty() {
+            warnings.push(String::from(
+                "raw: proxy is ignored, raw requests are always sent directly",
+            ));
+        }
+
+        warnings
+    }
+
+    /// Serialize the Configuration as toml, suitable for use as a ferox-config.toml
+    pub fn as_toml(&self) -> Result<String> {
+        // going through toml::Value ensures tables (i.e. headers) are emitted after plain values
+        let value = toml::Value::try_from(self)?;
+        Ok(toml::to_string(&value)?)
+    }
+
+    /// Build a Configuration from all `FEROX_*` environment variables found in `vars`
+    ///
+    /// each value is interpreted as a toml value (`20`, `true`, `[200, 301]`), falling back to
+    /// a plain string, and finally to a comma-separated list (`200,301`), whichever the
+    /// associated field accepts first
+    pub(super) fn parse_env_vars<I>(vars: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let known = serde_json::to_value(Configuration::default())?;
+
+        let mut table = toml::value::Table::new();
+
+        for (name, raw) in vars {
+            if !name.starts_with(ENV_PREFIX) {
+                continue;
+            }
+
+            let key = name[ENV_PREFIX.len()..].to_lowercase();
+
+            if key == "type" || known.get(&key).is_none() {
+                log::warn!("Ignoring unknown environment variable {}", name);
+                continue;
+            }
+
+            let mut candidates = Vec::new();
+
+            if let Ok(mut parsed) = toml::from_str::<toml::value::Table>(&format!("v = {}", raw)) {
+                candidates.extend(parsed.remove("v"));
+            }
+
+            candidates.push(toml::Value::String(raw.clone()));
+
+            let elements = raw
+                .split(',')
+                .map(|element| {
+                    let element = element.trim();
+                    toml::from_str::<toml::value::Table>(&format!("v = {}", element))
+                        .ok()
+                        .and_then(|mut parsed| parsed.remove("v"))
+                        .unwrap_or_else(|| toml::Value::String(element.to_string()))
+                })
+                .collect();
+            candidates.push(toml::Value::Array(elements));
+
+            let value = candidates
+                .into_iter()
+                .find(|candidate| {
+                    let mut single = toml::value::Table::new();
+                    single.insert(key.clone(), candidate.clone());
+                    toml::Value::Table(single).try_into::<Self>().is_ok()
+                })
+                .ok_or_else(|| anyhow!("Could not parse {}={}", name, raw))?;
+
+            table.insert(key, value);
+        }
+
+        let wordlist_given = table.contains_key("wordlist");
+
+        let mut config: Self = toml::Value::Table(table).try_into()?;
+        config.wordlist_given = wordlist_given;
+
+        Ok(config)
+    }
+
+    /// Merge the values of the named profile over the given Configuration
+    pub(super) fn apply_profile(config: &mut Self, name: &str) -> Result<()> {
+        let profile = config.profiles.get(name).cloned().ok_or_else(|| {
+            let mut known: Vec<&String> = config.profiles.keys().collect();
+            known.sort();
+            anyhow!("Unknown profile {}; known profiles: {:?}", name, known)
+        })?;
+
+        let wordlist_given = profile.get("wordlist").is_some();
+
+        let mut settings: Self = profile
+            .try_into()
+            .with_context(|| format!("Could not parse profile {}", name))?;
+        settings.wordlist_given = wordlist_given;
+
+        Self::merge_config(config, settings);
+        config.profile = name.to_string();
+
+        Ok(())
+    }
 }
 
 /// Implementation of FeroxMessage
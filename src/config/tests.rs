@@ -1,7 +1,7 @@
 use super::utils::*;
 use super::*;
 use crate::{traits::FeroxSerialize, DEFAULT_CONFIG_NAME};
-use std::{collections::HashMap, fs::write};
+use std::{collections::HashMap, fs::write, io::Write};
 use tempfile::TempDir;
 
 /// creates a dummy configuration file for testing
@@ -9,42 +9,128 @@ fn setup_config_test() -> Configuration {
     let data = r#"
             wordlist = "/some/path"
             status_codes = [201, 301, 401]
+            extension_status = {php = [200, 403], ".js" = [200]}
             replay_codes = [201, 301]
             threads = 40
             timeout = 5
+            dns_cache_ttl = 300
             proxy = "http://127.0.0.1:8080"
             replay_proxy = "http://127.0.0.1:8081"
+            targets = "/some/targets.txt"
+            target_ranges = ["10.10.10.0/24:80,443"]
             quiet = true
+            no_color = true
+            colors = {"2xx" = "blue", error = "magenta.bold"}
             silent = true
             auto_tune = true
             auto_bail = true
+            auto_pause = 50
+            auto_pause_window = 200
             verbosity = 1
             scan_limit = 6
+            max_scans = 500
+            strategy = "priority"
+            priority_keywords = "/some/keywords.toml"
             parallel = 14
             rate_limit = 250
+            delay = "100ms"
+            jitter = "50ms"
+            decoy_ratio = 10
             time_limit = "10m"
+            scan_time_limit = "5m"
+            tarpit_latency = 5000
+            throttle = "500kb/s"
+            request_limit = 100000
+            scan_window = "22:00-06:00"
             output = "/some/otherpath"
+            urls_file = "/some/urlspath"
+            har = "/some/harpath"
+            output_db = "/some/results.sqlite"
+            output_dsn = "postgres://ferox@localhost/results"
+            split_output = "/some/splitdir"
+            output_dir = "/some/outputdir"
+            compare = "/some/previous.json"
             debug_log = "/yet/anotherpath"
+            debug_log_json = true
+            debug_log_max_size = "100mb"
+            debug_log_backups = 2
+            cache_dir = "/some/cache"
             resume_from = "/some/state/file"
             redirects = true
             insecure = true
+            ca_cert = "/some/ca.pem"
+            pin_sha256 = ["sha256//47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="]
             extensions = ["html", "php", "js"]
             headers = {stuff = "things", mostuff = "mothings"}
+            raw = true
+            raw_headers = ["Transfer-Encoding : chunked"]
             queries = [["name","value"], ["rick", "astley"]]
+            query_file = "/some/values.txt"
             no_recursion = true
+            dont_recurse_regex = ['\d{4}/\d{2}', '^/(en|de|fr)/$']
             add_slash = true
+            smart_slash = true
+            url_encode_level = "double"
+            shuffle = true
+            seed = 31337
             stdin = true
             dont_filter = true
             extract_links = true
+            parse_listings = true
+            smart_wordlist = true
+            api = true
+            fingerprint = true
+            hashes = ["sha256"]
+            fingerprint_db = "/some/known-files.toml"
+            show_preview = 120
+            group_similar = 5
+            auto_extensions = true
+            auto_wordlist = "/some/auto-wordlists.toml"
+            wordlist_rules = "/some/wordlist-rules.toml"
+            interesting_rules = "/some/rules.toml"
+            bypass_403 = true
+            bypass_techniques = ["method", "ip-headers"]
+            bypass_budget = 50
+            verb_compare = true
+            follow_disclosures = true
+            cors = true
+            header_audit = true
+            favicon = true
+            wsdl = true
+            cloud_storage = true
             json = true
             save_state = false
+            state_redact = true
+            state_embed_wordlist = true
+            state_password = "hunter2"
             depth = 1
             filter_size = [4120]
             filter_regex = ["^ignore me$"]
             filter_similar = ["https://somesite.com/soft404"]
+            filter_header = ["Server: cloudflare"]
+            filter_command = "grep -q admin"
+            plugins = ["fingerprint.wasm"]
+            notify_webhook = "https://hooks.slack.com/services/T0/B0/X"
+            notify_on = [200, 401]
+            on_found = "notify.sh {url} {status}"
+            on_found_limit = 2
+            metrics_addr = "127.0.0.1:9095"
+            api_addr = "127.0.0.1:9096"
+            dry_run = true
+            low_memory = true
+            check_update = false
+            grpc = "127.0.0.1:50051"
+            match_header = ["X-Powered-By: PHP"]
             filter_word_count = [994, 992]
             filter_line_count = [34]
             filter_status = [201]
+
+            [profiles.stealth]
+            threads = 2
+            rate_limit = 10
+
+            [profiles.fast]
+            threads = 200
         "#;
     let tmp_dir = TempDir::new().unwrap();
     let file = tmp_dir.path().join(DEFAULT_CONFIG_NAME);
@@ -59,35 +145,103 @@ fn default_configuration() {
     assert_eq!(config.wordlist, wordlist());
     assert_eq!(config.proxy, String::new());
     assert_eq!(config.target_url, String::new());
+    assert_eq!(config.targets, String::new());
+    assert!(config.target_ranges.is_empty());
     assert_eq!(config.time_limit, String::new());
+    assert_eq!(config.scan_time_limit, String::new());
+    assert_eq!(config.delay, String::new());
+    assert_eq!(config.jitter, String::new());
+    assert_eq!(config.decoy_ratio, 0);
+    assert_eq!(config.tarpit_latency, 0);
+    assert_eq!(config.throttle, String::new());
+    assert_eq!(config.request_limit, 0);
+    assert_eq!(config.scan_window, String::new());
     assert_eq!(config.resume_from, String::new());
     assert_eq!(config.debug_log, String::new());
+    assert_eq!(config.debug_log_json, false);
+    assert_eq!(config.debug_log_max_size, String::new());
+    assert_eq!(config.debug_log_backups, 5);
+    assert_eq!(config.cache_dir, String::new());
+    assert_eq!(config.urls_file, String::new());
+    assert_eq!(config.har, String::new());
+    assert_eq!(config.output_db, String::new());
+    assert_eq!(config.output_dsn, String::new());
+    assert_eq!(config.split_output, String::new());
+    assert_eq!(config.output_dir, String::new());
+    assert_eq!(config.compare, String::new());
+    assert_eq!(config.schedule, String::new());
+    assert_eq!(config.refilter, String::new());
     assert_eq!(config.config, String::new());
     assert_eq!(config.replay_proxy, String::new());
     assert_eq!(config.status_codes, status_codes());
     assert_eq!(config.replay_codes, config.status_codes);
+    assert!(config.extension_status.is_empty());
     assert!(config.replay_client.is_none());
     assert_eq!(config.threads, threads());
     assert_eq!(config.depth, depth());
     assert_eq!(config.timeout, timeout());
+    assert_eq!(config.dns_cache_ttl, dns_cache_ttl());
     assert_eq!(config.verbosity, 0);
     assert_eq!(config.scan_limit, 0);
+    assert_eq!(config.max_scans, 0);
+    assert_eq!(config.strategy, strategy());
+    assert_eq!(config.priority_keywords, String::new());
     assert_eq!(config.silent, false);
     assert_eq!(config.quiet, false);
+    assert_eq!(config.no_color, false);
+    assert!(config.colors.is_empty());
     assert_eq!(config.output_level, OutputLevel::Default);
     assert_eq!(config.dont_filter, false);
     assert_eq!(config.auto_tune, false);
+    assert_eq!(config.auto_pause, 0);
+    assert_eq!(config.auto_pause_window, 100);
     assert_eq!(config.auto_bail, false);
     assert_eq!(config.requester_policy, RequesterPolicy::Default);
     assert_eq!(config.no_recursion, false);
+    assert!(config.dont_recurse_regex.is_empty());
     assert_eq!(config.json, false);
     assert_eq!(config.save_state, true);
+    assert_eq!(config.state_redact, false);
+    assert_eq!(config.state_embed_wordlist, false);
+    assert_eq!(config.state_password, String::new());
     assert_eq!(config.stdin, false);
     assert_eq!(config.add_slash, false);
+    assert_eq!(config.smart_slash, false);
+    assert_eq!(config.url_encode_level, url_encode_level());
+    assert_eq!(config.shuffle, false);
+    assert_eq!(config.seed, 0);
     assert_eq!(config.redirects, false);
     assert_eq!(config.extract_links, false);
+    assert_eq!(config.parse_listings, false);
+    assert_eq!(config.smart_wordlist, false);
+    assert_eq!(config.api, false);
+    assert_eq!(config.fingerprint, false);
+    assert_eq!(config.hashes, Vec::<String>::new());
+    assert_eq!(config.fingerprint_db, String::new());
+    assert_eq!(config.show_preview, 0);
+    assert_eq!(config.group_similar, 0);
+    assert_eq!(config.auto_extensions, false);
+    assert_eq!(config.auto_wordlist, String::new());
+    assert_eq!(config.wordlist_rules, String::new());
+    assert_eq!(config.interesting_rules, String::new());
+    assert_eq!(config.bypass_403, false);
+    assert_eq!(config.bypass_techniques, Vec::<String>::new());
+    assert_eq!(config.bypass_budget, 250);
+    assert_eq!(config.verb_compare, false);
+    assert_eq!(config.follow_disclosures, false);
+    assert_eq!(config.cors, false);
+    assert_eq!(config.header_audit, false);
+    assert_eq!(config.favicon, false);
+    assert_eq!(config.wsdl, false);
+    assert_eq!(config.cloud_storage, false);
     assert_eq!(config.insecure, false);
+    assert_eq!(config.ca_cert, String::new());
+    assert!(config.pin_sha256.is_empty());
+    assert_eq!(config.raw, false);
+    assert!(config.raw_headers.is_empty());
     assert_eq!(config.queries, Vec::new());
+    assert_eq!(config.query_file, String::new());
+    assert!(config.query_values.is_empty());
     assert_eq!(config.extensions, Vec::<String>::new());
     assert_eq!(config.filter_size, Vec::<u64>::new());
     assert_eq!(config.filter_regex, Vec::<String>::new());
@@ -95,6 +249,28 @@ fn default_configuration() {
     assert_eq!(config.filter_word_count, Vec::<usize>::new());
     assert_eq!(config.filter_line_count, Vec::<usize>::new());
     assert_eq!(config.filter_status, Vec::<u16>::new());
+    assert_eq!(config.filter_header, Vec::<String>::new());
+    assert_eq!(config.filter_command, String::new());
+    assert_eq!(config.plugins, Vec::<String>::new());
+    assert!(config.loaded_plugins.is_empty());
+    assert_eq!(config.notify_webhook, String::new());
+    assert_eq!(config.notify_on, Vec::<u16>::new());
+    assert_eq!(config.on_found, String::new());
+    assert_eq!(config.on_found_limit, 4);
+    assert_eq!(config.metrics_addr, String::new());
+    assert_eq!(config.api_addr, String::new());
+    assert_eq!(config.grpc, String::new());
+    assert_eq!(config.dry_run, false);
+    assert_eq!(config.low_memory, false);
+    assert_eq!(config.check_update, true);
+    assert_eq!(config.update, false);
+    assert_eq!(config.completions, String::new());
+    assert_eq!(config.manpage, false);
+    assert_eq!(config.wordlists, String::new());
+    assert!(config.wordlist_names.is_empty());
+    assert_eq!(config.profile, String::new());
+    assert!(config.profiles.is_empty());
+    assert_eq!(config.match_header, Vec::<String>::new());
     assert_eq!(config.headers, HashMap::new());
 }
 
@@ -103,6 +279,21 @@ fn default_configuration() {
 fn config_reads_wordlist() {
     let config = setup_config_test();
     assert_eq!(config.wordlist, "/some/path");
+    assert!(config.wordlist_given);
+}
+
+#[test]
+/// a wordlist is only given when one was set, even when it's set to the default
+fn config_tracks_whether_wordlist_was_given() {
+    assert!(!Configuration::default().wordlist_given);
+
+    let config = Configuration::parse_env_vars(env_vars(&[("FEROX_THREADS", "20")])).unwrap();
+    assert!(!config.wordlist_given);
+
+    let config =
+        Configuration::parse_env_vars(env_vars(&[("FEROX_WORDLIST", &wordlist())])).unwrap();
+    assert_eq!(config.wordlist, wordlist());
+    assert!(config.wordlist_given);
 }
 
 #[test]
@@ -112,6 +303,34 @@ fn config_reads_debug_log() {
     assert_eq!(config.debug_log, "/yet/anotherpath");
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_debug_log_json() {
+    let config = setup_config_test();
+    assert_eq!(config.debug_log_json, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_debug_log_max_size() {
+    let config = setup_config_test();
+    assert_eq!(config.debug_log_max_size, "100mb");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_debug_log_backups() {
+    let config = setup_config_test();
+    assert_eq!(config.debug_log_backups, 2);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_cache_dir() {
+    let config = setup_config_test();
+    assert_eq!(config.cache_dir, "/some/cache");
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_status_codes() {
@@ -119,6 +338,27 @@ fn config_reads_status_codes() {
     assert_eq!(config.status_codes, vec![201, 301, 401]);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_extension_status() {
+    let config = setup_config_test();
+    assert_eq!(config.extension_status["php"], vec![200, 403]);
+    // keys are normalized, so .js is stored as js
+    assert_eq!(config.extension_status["js"], vec![200]);
+    assert!(!config.extension_status.contains_key(".js"));
+}
+
+#[test]
+/// extension_status rules should take precedence over status_codes for their extension only
+fn config_status_codes_for_extension() {
+    let config = setup_config_test();
+    assert_eq!(config.status_codes_for(Some("php")), &[200, 403]);
+    assert_eq!(config.status_codes_for(Some("PHP")), &[200, 403]);
+    assert_eq!(config.status_codes_for(Some("js")), &[200]);
+    assert_eq!(config.status_codes_for(Some("html")), &[201, 301, 401]);
+    assert_eq!(config.status_codes_for(None), &[201, 301, 401]);
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_replay_codes() {
@@ -147,6 +387,27 @@ fn config_reads_scan_limit() {
     assert_eq!(config.scan_limit, 6);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_max_scans() {
+    let config = setup_config_test();
+    assert_eq!(config.max_scans, 500);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_strategy() {
+    let config = setup_config_test();
+    assert_eq!(config.strategy, "priority");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_priority_keywords() {
+    let config = setup_config_test();
+    assert_eq!(config.priority_keywords, "/some/keywords.toml");
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_parallel() {
@@ -161,6 +422,27 @@ fn config_reads_rate_limit() {
     assert_eq!(config.rate_limit, 250);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_delay() {
+    let config = setup_config_test();
+    assert_eq!(config.delay, "100ms");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_jitter() {
+    let config = setup_config_test();
+    assert_eq!(config.jitter, "50ms");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_decoy_ratio() {
+    let config = setup_config_test();
+    assert_eq!(config.decoy_ratio, 10);
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_timeout() {
@@ -168,6 +450,13 @@ fn config_reads_timeout() {
     assert_eq!(config.timeout, 5);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_dns_cache_ttl() {
+    let config = setup_config_test();
+    assert_eq!(config.dns_cache_ttl, 300);
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_proxy() {
@@ -182,6 +471,67 @@ fn config_reads_replay_proxy() {
     assert_eq!(config.replay_proxy, "http://127.0.0.1:8081");
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_targets() {
+    let config = setup_config_test();
+    assert_eq!(config.targets, "/some/targets.txt");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_target_ranges() {
+    let config = setup_config_test();
+    assert_eq!(config.target_ranges, vec!["10.10.10.0/24:80,443"]);
+}
+
+#[test]
+/// each --targets line should become a copy of the configuration with its own options on top
+fn config_read_targets_layers_options() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "# staging\n\nhttps://a.example -H 'Host: internal' --depth 2\nhttps://b.example -x php,bak -Q debug=1"
+    )
+    .unwrap();
+
+    let mut headers = HashMap::new();
+    headers.insert(String::from("X-Base"), String::from("1"));
+
+    let config = Configuration {
+        targets: file.path().to_str().unwrap().to_string(),
+        headers,
+        extensions: vec![String::from("txt")],
+        ..Default::default()
+    };
+
+    let overlays = config.read_targets().unwrap();
+    assert_eq!(overlays.len(), 2);
+
+    assert_eq!(overlays[0].target_url, "https://a.example");
+    assert_eq!(overlays[0].depth, 2);
+    assert_eq!(overlays[0].headers["Host"], " internal");
+    assert_eq!(overlays[0].headers["X-Base"], "1");
+    assert_eq!(overlays[0].extensions, vec!["txt"]);
+
+    assert_eq!(overlays[1].target_url, "https://b.example");
+    assert_eq!(overlays[1].depth, depth());
+    assert_eq!(overlays[1].extensions, vec!["php", "bak"]);
+    assert_eq!(
+        overlays[1].queries,
+        vec![(String::from("debug"), String::from("1"))]
+    );
+
+    let mut bad = tempfile::NamedTempFile::new().unwrap();
+    writeln!(bad, "https://a.example --threads 2").unwrap();
+
+    let config = Configuration {
+        targets: bad.path().to_str().unwrap().to_string(),
+        ..Default::default()
+    };
+    assert!(config.read_targets().is_err());
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_silent() {
@@ -196,6 +546,21 @@ fn config_reads_quiet() {
     assert_eq!(config.quiet, true);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_no_color() {
+    let config = setup_config_test();
+    assert_eq!(config.no_color, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_colors() {
+    let config = setup_config_test();
+    assert_eq!(config.colors["2xx"], "blue");
+    assert_eq!(config.colors["error"], "magenta.bold");
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_json() {
@@ -210,6 +575,14 @@ fn config_reads_auto_bail() {
     assert_eq!(config.auto_bail, true);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_auto_pause() {
+    let config = setup_config_test();
+    assert_eq!(config.auto_pause, 50);
+    assert_eq!(config.auto_pause_window, 200);
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_auto_tune() {
@@ -231,6 +604,55 @@ fn config_reads_output() {
     assert_eq!(config.output, "/some/otherpath");
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_urls_file() {
+    let config = setup_config_test();
+    assert_eq!(config.urls_file, "/some/urlspath");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_har() {
+    let config = setup_config_test();
+    assert_eq!(config.har, "/some/harpath");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_output_db() {
+    let config = setup_config_test();
+    assert_eq!(config.output_db, "/some/results.sqlite");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_output_dsn() {
+    let config = setup_config_test();
+    assert_eq!(config.output_dsn, "postgres://ferox@localhost/results");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_split_output() {
+    let config = setup_config_test();
+    assert_eq!(config.split_output, "/some/splitdir");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_output_dir() {
+    let config = setup_config_test();
+    assert_eq!(config.output_dir, "/some/outputdir");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_compare() {
+    let config = setup_config_test();
+    assert_eq!(config.compare, "/some/previous.json");
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_redirects() {
@@ -245,6 +667,31 @@ fn config_reads_insecure() {
     assert_eq!(config.insecure, true);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_ca_cert() {
+    let config = setup_config_test();
+    assert_eq!(config.ca_cert, "/some/ca.pem");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_pin_sha256() {
+    let config = setup_config_test();
+    assert_eq!(
+        config.pin_sha256,
+        vec!["sha256//47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="]
+    );
+}
+
+#[test]
+/// parse the test config and see that the values parsed are correct
+fn config_reads_raw() {
+    let config = setup_config_test();
+    assert_eq!(config.raw, true);
+    assert_eq!(config.raw_headers, vec!["Transfer-Encoding : chunked"]);
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_no_recursion() {
@@ -252,6 +699,16 @@ fn config_reads_no_recursion() {
     assert_eq!(config.no_recursion, true);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_dont_recurse_regex() {
+    let config = setup_config_test();
+    assert_eq!(
+        config.dont_recurse_regex,
+        vec![r"\d{4}/\d{2}", "^/(en|de|fr)/$"]
+    );
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_stdin() {
@@ -273,6 +730,34 @@ fn config_reads_add_slash() {
     assert_eq!(config.add_slash, true);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_smart_slash() {
+    let config = setup_config_test();
+    assert_eq!(config.smart_slash, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_url_encode_level() {
+    let config = setup_config_test();
+    assert_eq!(config.url_encode_level, "double");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_shuffle() {
+    let config = setup_config_test();
+    assert_eq!(config.shuffle, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_seed() {
+    let config = setup_config_test();
+    assert_eq!(config.seed, 31337);
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_extract_links() {
@@ -280,6 +765,148 @@ fn config_reads_extract_links() {
     assert_eq!(config.extract_links, true);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_parse_listings() {
+    let config = setup_config_test();
+    assert_eq!(config.parse_listings, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_smart_wordlist() {
+    let config = setup_config_test();
+    assert_eq!(config.smart_wordlist, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_api() {
+    let config = setup_config_test();
+    assert_eq!(config.api, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_fingerprint() {
+    let config = setup_config_test();
+    assert_eq!(config.fingerprint, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_hashes() {
+    let config = setup_config_test();
+    assert_eq!(config.hashes, vec!["sha256"]);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_fingerprint_db() {
+    let config = setup_config_test();
+    assert_eq!(config.fingerprint_db, "/some/known-files.toml");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_show_preview() {
+    let config = setup_config_test();
+    assert_eq!(config.show_preview, 120);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_group_similar() {
+    let config = setup_config_test();
+    assert_eq!(config.group_similar, 5);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_auto_extensions() {
+    let config = setup_config_test();
+    assert_eq!(config.auto_extensions, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_auto_wordlist() {
+    let config = setup_config_test();
+    assert_eq!(config.auto_wordlist, "/some/auto-wordlists.toml");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_wordlist_rules() {
+    let config = setup_config_test();
+    assert_eq!(config.wordlist_rules, "/some/wordlist-rules.toml");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_interesting_rules() {
+    let config = setup_config_test();
+    assert_eq!(config.interesting_rules, "/some/rules.toml");
+}
+
+#[test]
+/// parse the test config and see that the values parsed are correct
+fn config_reads_bypass_403() {
+    let config = setup_config_test();
+    assert_eq!(config.bypass_403, true);
+    assert_eq!(config.bypass_techniques, vec!["method", "ip-headers"]);
+    assert_eq!(config.bypass_budget, 50);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_verb_compare() {
+    let config = setup_config_test();
+    assert_eq!(config.verb_compare, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_follow_disclosures() {
+    let config = setup_config_test();
+    assert_eq!(config.follow_disclosures, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_cors() {
+    let config = setup_config_test();
+    assert_eq!(config.cors, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_header_audit() {
+    let config = setup_config_test();
+    assert_eq!(config.header_audit, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_favicon() {
+    let config = setup_config_test();
+    assert_eq!(config.favicon, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_wsdl() {
+    let config = setup_config_test();
+    assert_eq!(config.wsdl, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_cloud_storage() {
+    let config = setup_config_test();
+    assert_eq!(config.cloud_storage, true);
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_extensions() {
@@ -301,6 +928,375 @@ fn config_reads_filter_similar() {
     assert_eq!(config.filter_similar, vec!["https://somesite.com/soft404"]);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_filter_header() {
+    let config = setup_config_test();
+    assert_eq!(config.filter_header, vec!["Server: cloudflare"]);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_filter_command() {
+    let config = setup_config_test();
+    assert_eq!(config.filter_command, "grep -q admin");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_plugins() {
+    let config = setup_config_test();
+    assert_eq!(config.plugins, vec!["fingerprint.wasm"]);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_notify_webhook() {
+    let config = setup_config_test();
+    assert_eq!(
+        config.notify_webhook,
+        "https://hooks.slack.com/services/T0/B0/X"
+    );
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_notify_on() {
+    let config = setup_config_test();
+    assert_eq!(config.notify_on, vec![200, 401]);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_on_found() {
+    let config = setup_config_test();
+    assert_eq!(config.on_found, "notify.sh {url} {status}");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_on_found_limit() {
+    let config = setup_config_test();
+    assert_eq!(config.on_found_limit, 2);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_metrics_addr() {
+    let config = setup_config_test();
+    assert_eq!(config.metrics_addr, "127.0.0.1:9095");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_api_addr() {
+    let config = setup_config_test();
+    assert_eq!(config.api_addr, "127.0.0.1:9096");
+}
+
+#[test]
+/// parse the test config and see that profiles are set aside instead of being applied
+fn config_reads_profiles() {
+    let config = setup_config_test();
+    assert_eq!(config.profiles.len(), 2);
+    assert!(config.profiles.contains_key("stealth"));
+    assert!(config.profiles.contains_key("fast"));
+    assert_eq!(config.profile, "");
+    assert_eq!(config.threads, 40);
+}
+
+#[test]
+/// applying a profile should only override the values the profile defines
+fn config_applies_profile() {
+    let mut config = setup_config_test();
+    Configuration::apply_profile(&mut config, "stealth").unwrap();
+    assert_eq!(config.profile, "stealth");
+    assert_eq!(config.threads, 2);
+    assert_eq!(config.rate_limit, 10);
+    assert_eq!(config.scan_limit, 6);
+    assert_eq!(config.status_codes, vec![201, 301, 401]);
+}
+
+#[test]
+/// applying a profile that doesn't exist should result in an error
+fn config_errors_on_unknown_profile() {
+    let mut config = setup_config_test();
+    assert!(Configuration::apply_profile(&mut config, "nope").is_err());
+}
+
+#[test]
+/// a top-level profile key should select a profile rather than define one
+fn config_reads_selected_profile() {
+    let data = r#"
+            profile = "fast"
+
+            [profiles.fast]
+            threads = 200
+        "#;
+    let tmp_dir = TempDir::new().unwrap();
+    let file = tmp_dir.path().join(DEFAULT_CONFIG_NAME);
+    write(&file, data).unwrap();
+    let config = Configuration::parse_config(file).unwrap();
+    assert_eq!(config.profile, "fast");
+    assert!(config.profiles.contains_key("fast"));
+}
+
+/// helper to build (name, value) pairs as returned by std::env::vars
+fn env_vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+/// FEROX_* environment variables should be parsed into their associated fields
+fn config_reads_env_vars() {
+    let vars = env_vars(&[
+        ("FEROX_THREADS", "20"),
+        ("FEROX_PROXY", "http://127.0.0.1:8080"),
+        ("FEROX_INSECURE", "true"),
+        ("FEROX_STATUS_CODES", "200,301"),
+        ("FEROX_EXTENSIONS", "[\"php\", \"html\"]"),
+        ("FEROX_USER_AGENT", "Mozilla/5.0 (KHTML, like Gecko)"),
+        ("PATH", "/usr/bin"),
+    ]);
+
+    let config = Configuration::parse_env_vars(vars).unwrap();
+
+    assert_eq!(config.threads, 20);
+    assert_eq!(config.proxy, "http://127.0.0.1:8080");
+    assert!(config.insecure);
+    assert_eq!(config.status_codes, vec![200, 301]);
+    assert_eq!(config.extensions, vec!["php", "html"]);
+    assert_eq!(config.user_agent, "Mozilla/5.0 (KHTML, like Gecko)");
+}
+
+#[test]
+/// unknown FEROX_* variables are ignored, while bad values for known ones are an error
+fn config_env_vars_unknown_and_invalid() {
+    let config = Configuration::parse_env_vars(env_vars(&[("FEROX_NOPE", "1")])).unwrap();
+    assert_eq!(config.threads, threads());
+
+    assert!(Configuration::parse_env_vars(env_vars(&[("FEROX_THREADS", "lots")])).is_err());
+}
+
+#[test]
+/// validate should report nothing for a sane configuration
+fn config_validate_accepts_good_values() {
+    let config = Configuration {
+        target_url: String::from("http://localhost"),
+        filter_regex: vec![String::from("^ignore me$")],
+        time_limit: String::from("10m"),
+        ..Default::default()
+    };
+
+    assert!(config.validate().is_empty());
+    assert!(config.warnings().is_empty());
+}
+
+#[test]
+/// bodies should only be hashed when the hashes are shown, looked up, or written as json
+fn config_needs_body_hashes() {
+    assert!(!Configuration::default().needs_body_hashes());
+
+    let config = Configuration {
+        json: true,
+        ..Default::default()
+    };
+    assert!(config.needs_body_hashes());
+
+    let config = Configuration {
+        hashes: vec![String::from("md5")],
+        ..Default::default()
+    };
+    assert!(config.needs_body_hashes());
+}
+
+#[test]
+/// --seed without --shuffle would silently do nothing, which is worth a warning but shouldn't stop
+/// the scan
+fn config_warnings_reports_seed_without_shuffle() {
+    let config = Configuration {
+        seed: 31337,
+        ..Default::default()
+    };
+
+    assert!(config.validate().is_empty());
+    assert_eq!(
+        config.warnings(),
+        vec!["seed: --seed only applies when --shuffle is used"]
+    );
+
+    let config = Configuration {
+        seed: 31337,
+        shuffle: true,
+        ..Default::default()
+    };
+
+    assert!(config.warnings().is_empty());
+}
+
+#[test]
+/// a streamed wordlist (-w -) can't share stdin, be shuffled, be added to, or be resumed
+fn config_validate_reports_streamed_wordlist_conflicts() {
+    let config = Configuration {
+        wordlist: String::from("-"),
+        stdin: true,
+        resumed: true,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        config.validate(),
+        vec![
+            "wordlist: -w - and --stdin can't both read from stdin",
+            "wordlist: a scan with a streamed wordlist (-w -) can't be resumed"
+        ]
+    );
+
+    let config = Configuration {
+        wordlist: String::from("-"),
+        ..Default::default()
+    };
+
+    assert!(config.validate().is_empty());
+}
+
+#[test]
+/// validate should report each bad value
+fn config_validate_reports_bad_values() {
+    let mut colors = HashMap::new();
+    colors.insert(String::from("6xx"), String::from("red"));
+    colors.insert(String::from("2xx"), String::from("bleu.bold"));
+
+    let config = Configuration {
+        status_codes: vec![200, 1000],
+        filter_regex: vec![String::from("[unclosed")],
+        dont_recurse_regex: vec![String::from("(unclosed")],
+        colors,
+        proxy: String::from("not a url"),
+        time_limit: String::from("soon"),
+        scan_time_limit: String::from("later"),
+        delay: String::from("slowly"),
+        ..Default::default()
+    };
+
+    let problems = config.validate();
+
+    assert_eq!(problems.len(), 7);
+    assert!(problems[0].starts_with("status_codes: 1000"));
+
+    // unknown colors are only ignored
+    let warnings = config.warnings();
+
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings
+        .iter()
+        .any(|warning| warning == "colors: bleu.bold (2xx) has unknown style(s) bleu"));
+}
+
+#[test]
+/// validate should report an interesting rules file that can't be read or has bad rules
+fn config_validate_reports_bad_interesting_rules() {
+    let tmp_dir = TempDir::new().unwrap();
+    let file = tmp_dir.path().join("rules.toml");
+    write(
+        &file,
+        "[[rules]]\nname = \"bad\"\nseverity = \"high\"\npath = '(unclosed'\n",
+    )
+    .unwrap();
+
+    let missing = Configuration {
+        interesting_rules: tmp_dir.path().join("missing.toml").to_string_lossy().into(),
+        ..Default::default()
+    };
+
+    let bad = Configuration {
+        interesting_rules: file.to_string_lossy().into(),
+        ..Default::default()
+    };
+
+    assert!(missing.validate()[0].starts_with("interesting_rules: Could not open"));
+    assert_eq!(bad.validate().len(), 1);
+    assert!(bad.validate()[0].starts_with("interesting_rules: "));
+}
+
+#[test]
+/// validate should report a wordlist rules file that can't be read or has bad rules
+fn config_validate_reports_bad_wordlist_rules() {
+    let tmp_dir = TempDir::new().unwrap();
+    let file = tmp_dir.path().join("wordlist-rules.toml");
+    write(&file, "[[rules]]\npattern = \"/api/*\"\n").unwrap();
+
+    let missing = Configuration {
+        wordlist_rules: tmp_dir.path().join("missing.toml").to_string_lossy().into(),
+        ..Default::default()
+    };
+
+    let bad = Configuration {
+        wordlist_rules: file.to_string_lossy().into(),
+        ..Default::default()
+    };
+
+    assert!(missing.validate()[0].starts_with("wordlist_rules: Could not open"));
+    assert_eq!(bad.validate().len(), 1);
+    assert!(bad.validate()[0].starts_with("wordlist_rules: Could not parse"));
+}
+
+#[test]
+/// as_toml should produce toml that can be read back in as a config file
+fn config_as_toml_round_trips() {
+    let mut config = setup_config_test();
+    config
+        .headers
+        .insert(String::from("stuff"), String::from("things"));
+
+    let tmp_dir = TempDir::new().unwrap();
+    let file = tmp_dir.path().join(DEFAULT_CONFIG_NAME);
+    write(&file, config.as_toml().unwrap()).unwrap();
+
+    let parsed = Configuration::parse_config(file).unwrap();
+
+    assert_eq!(parsed.threads, config.threads);
+    assert_eq!(parsed.headers, config.headers);
+    assert_eq!(parsed.status_codes, config.status_codes);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_dry_run() {
+    let config = setup_config_test();
+    assert_eq!(config.dry_run, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_low_memory() {
+    let config = setup_config_test();
+    assert_eq!(config.low_memory, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_check_update() {
+    let config = setup_config_test();
+    assert_eq!(config.check_update, false);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_grpc() {
+    let config = setup_config_test();
+    assert_eq!(config.grpc, "127.0.0.1:50051");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_match_header() {
+    let config = setup_config_test();
+    assert_eq!(config.match_header, vec!["X-Powered-By: PHP"]);
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_filter_size() {
@@ -336,6 +1332,35 @@ fn config_reads_save_state() {
     assert_eq!(config.save_state, false);
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_state_redact() {
+    let config = setup_config_test();
+    assert_eq!(config.state_redact, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_state_embed_wordlist() {
+    let config = setup_config_test();
+    assert_eq!(config.state_embed_wordlist, true);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_state_password() {
+    let config = setup_config_test();
+    assert_eq!(config.state_password, "hunter2");
+}
+
+#[test]
+/// the state password should never end up in a state file or --print-config
+fn config_never_serializes_state_password() {
+    let config = setup_config_test();
+    assert!(!serde_json::to_string(&config).unwrap().contains("hunter2"));
+    assert!(!config.as_toml().unwrap().contains("hunter2"));
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_time_limit() {
@@ -343,6 +1368,41 @@ fn config_reads_time_limit() {
     assert_eq!(config.time_limit, "10m");
 }
 
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_scan_time_limit() {
+    let config = setup_config_test();
+    assert_eq!(config.scan_time_limit, "5m");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_tarpit_latency() {
+    let config = setup_config_test();
+    assert_eq!(config.tarpit_latency, 5000);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_throttle() {
+    let config = setup_config_test();
+    assert_eq!(config.throttle, "500kb/s");
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_request_limit() {
+    let config = setup_config_test();
+    assert_eq!(config.request_limit, 100000);
+}
+
+#[test]
+/// parse the test config and see that the value parsed is correct
+fn config_reads_scan_window() {
+    let config = setup_config_test();
+    assert_eq!(config.scan_window, "22:00-06:00");
+}
+
 #[test]
 /// parse the test config and see that the value parsed is correct
 fn config_reads_resume_from() {
@@ -372,10 +1432,10 @@ fn config_reads_queries() {
 }
 
 #[test]
-#[should_panic]
-/// test that an error message is printed and panic is called when report_and_exit is called
-fn config_report_and_exit_works() {
-    report_and_exit("some message");
+/// parse the test config and see that the value parsed is correct
+fn config_reads_query_file() {
+    let config = setup_config_test();
+    assert_eq!(config.query_file, "/some/values.txt");
 }
 
 #[test]
@@ -1,23 +1,33 @@
 use crate::{
-    utils::{module_colorizer, status_colorizer},
+    error::{FeroxError, FeroxResult},
     DEFAULT_STATUS_CODES, DEFAULT_WORDLIST, VERSION,
 };
-#[cfg(not(test))]
-use std::process::exit;
+use reqwest::StatusCode;
+use serde::{Deserialize, Deserializer};
+use std::{collections::HashMap, fmt, str::FromStr};
 
-/// simple helper to clean up some code reuse below; panics under test / exits in prod
-pub(super) fn report_and_exit(err: &str) -> ! {
-    eprintln!(
-        "{} {}: {}",
-        status_colorizer("ERROR"),
-        module_colorizer("Configuration::new"),
-        err
-    );
+/// parse each of the given command line values, i.e. -S 1234 5678
+pub(super) fn parse_values<'a, T>(values: impl Iterator<Item = &'a str>) -> FeroxResult<Vec<T>>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    values
+        .map(|value| value.parse::<T>().map_err(FeroxError::argument))
+        .collect()
+}
 
-    #[cfg(test)]
-    panic!();
-    #[cfg(not(test))]
-    exit(1);
+/// parse each of the given command line status codes, i.e. -C 404 500
+pub(super) fn parse_status_codes<'a>(
+    values: impl Iterator<Item = &'a str>,
+) -> FeroxResult<Vec<u16>> {
+    values
+        .map(|code| {
+            StatusCode::from_bytes(code.as_bytes())
+                .map(|code| code.as_u16())
+                .map_err(FeroxError::argument)
+        })
+        .collect()
 }
 
 // functions timeout, threads, status_codes, user_agent, wordlist, save_state, and depth are used to provide
@@ -34,11 +44,46 @@ pub(super) fn timeout() -> u64 {
     7
 }
 
+/// default dns_cache_ttl value
+pub(super) fn dns_cache_ttl() -> u64 {
+    60
+}
+
+/// default strategy value
+pub(super) fn strategy() -> String {
+    String::from("bfs")
+}
+
+/// default url_encode_level value
+pub(super) fn url_encode_level() -> String {
+    String::from("none")
+}
+
 /// default save_state value
 pub(super) fn save_state() -> bool {
     true
 }
 
+/// default check_update value
+pub(super) fn check_update() -> bool {
+    true
+}
+
+/// default number of recent responses looked at by --auto-pause
+pub(super) fn auto_pause_window() -> usize {
+    100
+}
+
+/// default number of requests that --bypass-403 may send, across all scans
+pub(super) fn bypass_budget() -> usize {
+    250
+}
+
+/// default number of --on-found commands allowed to run at once
+pub(super) fn on_found_limit() -> usize {
+    4
+}
+
 /// default threads value
 pub(super) fn threads() -> usize {
     50
@@ -67,6 +112,32 @@ pub(super) fn depth() -> usize {
     4
 }
 
+/// default number of rotated --debug-log files to keep
+pub(super) fn debug_log_backups() -> usize {
+    5
+}
+
+/// key used for an --extension-status rule; php, .php, and PHP all refer to the same extension
+pub(super) fn normalize_extension(extension: &str) -> String {
+    extension.trim().trim_start_matches('.').to_lowercase()
+}
+
+/// deserialize extension_status rules with their keys normalized, so that rules read from a
+/// config file, profile, or state file match the same way as those passed on the command line
+pub(super) fn extension_status<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<String, Vec<u16>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let rules = HashMap::<String, Vec<u16>>::deserialize(deserializer)?;
+
+    Ok(rules
+        .into_iter()
+        .map(|(extension, codes)| (normalize_extension(&extension), codes))
+        .collect())
+}
+
 /// enum representing the three possible states for informational output (not logging verbosity)
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum OutputLevel {
@@ -174,9 +245,16 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    /// report_and_exit should panic/exit when called
-    fn report_and_exit_panics_under_test() {
-        report_and_exit("test");
+    /// values that don't parse should be an error rather than an exit
+    fn parse_values_reports_bad_values() {
+        let sizes: Vec<u64> = parse_values(["1234", "5678"].iter().copied()).unwrap();
+        assert_eq!(sizes, vec![1234, 5678]);
+
+        let error = parse_values::<u64>(["1234", "12ab"].iter().copied()).unwrap_err();
+        assert!(matches!(error, FeroxError::Argument { .. }));
+
+        let codes = parse_status_codes(["200", "404"].iter().copied()).unwrap();
+        assert_eq!(codes, vec![200, 404]);
+        assert!(parse_status_codes(["2000"].iter().copied()).is_err());
     }
 }
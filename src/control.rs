@@ -0,0 +1,138 @@
+//! control a running instance through its --api-addr (scans, pause, resume, and cancel subcommands)
+//!
+//! scans are identified by the same ids used in FeroxScan, which can be listed with the scans
+//! subcommand or seen in the interactive scan management menu
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use reqwest::{Client, Method};
+use serde_json::Value;
+
+use crate::{config::Configuration, progress::PROGRESS_PRINTER};
+
+/// Method and path of the api endpoint that performs the given action
+fn endpoint(action: &str, id: &str) -> Result<(Method, String)> {
+    let endpoint = match action {
+        "scans" => (Method::GET, String::from("/scans")),
+        "pause" => (Method::POST, format!("/scans/{}/pause", id)),
+        "resume" => (Method::POST, format!("/scans/{}/resume", id)),
+        "cancel" => (Method::DELETE, format!("/scans/{}", id)),
+        _ => bail!("Unknown control action: {}", action),
+    };
+
+    Ok(endpoint)
+}
+
+/// Format the scans returned from GET /scans as one line per scan: id, status, type, and url
+fn format_scans(scans: &[Value]) -> Vec<String> {
+    scans
+        .iter()
+        .map(|scan| {
+            let status = if scan["paused"].as_bool().unwrap_or_default() {
+                "paused"
+            } else {
+                scan["status"].as_str().unwrap_or("unknown")
+            };
+
+            format!(
+                "{:32} {:12} {:10} {}",
+                scan["id"].as_str().unwrap_or_default(),
+                status.to_lowercase(),
+                scan["scan_type"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_lowercase(),
+                scan["url"].as_str().unwrap_or_default()
+            )
+        })
+        .collect()
+}
+
+/// Send the action given on the command line to the instance listening on --api-addr
+pub async fn run(config: Arc<Configuration>) -> Result<()> {
+    log::trace!("enter: run({}, {})", config.control, config.control_id);
+
+    if config.api_addr.is_empty() {
+        bail!(
+            "The {} subcommand requires the --api-addr of a running instance (ex: --api-addr 127.0.0.1:9096)",
+            config.control
+        );
+    }
+
+    let (method, path) = endpoint(&config.control, &config.control_id)?;
+    let url = format!("http://{}{}", config.api_addr, path);
+
+    let response = Client::new()
+        .request(method, &url)
+        .send()
+        .await
+        .with_context(|| format!("Could not reach the api at {}", config.api_addr))?;
+
+    let status = response.status();
+    let text = response.text().await?;
+    let body: Value = serde_json::from_str(&text)
+        .with_context(|| format!("Could not parse the response from {}", url))?;
+
+    if !status.is_success() {
+        bail!(
+            "{}: {}",
+            status,
+            body["message"].as_str().unwrap_or_default()
+        );
+    }
+
+    match body.as_array() {
+        Some(scans) => {
+            for line in format_scans(scans) {
+                PROGRESS_PRINTER.println(line);
+            }
+        }
+        None => PROGRESS_PRINTER.println(body["message"].as_str().unwrap_or_default()),
+    }
+
+    log::trace!("exit: run");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// each action should map to its api endpoint, unknown actions should be rejected
+    fn endpoint_maps_actions_to_api() {
+        assert_eq!(
+            endpoint("scans", "").unwrap(),
+            (Method::GET, String::from("/scans"))
+        );
+        assert_eq!(
+            endpoint("pause", "abc").unwrap(),
+            (Method::POST, String::from("/scans/abc/pause"))
+        );
+        assert_eq!(
+            endpoint("resume", "abc").unwrap(),
+            (Method::POST, String::from("/scans/abc/resume"))
+        );
+        assert_eq!(
+            endpoint("cancel", "abc").unwrap(),
+            (Method::DELETE, String::from("/scans/abc"))
+        );
+        assert!(endpoint("explode", "abc").is_err());
+    }
+
+    #[test]
+    /// listed scans should show their id, and paused scans should be reported as paused
+    fn format_scans_shows_ids() {
+        let scans: Vec<Value> = serde_json::from_str(
+            r#"[{"id":"abc","url":"http://localhost/","scan_type":"Directory","status":"Running","num_requests":42,"paused":true}]"#,
+        )
+        .unwrap();
+
+        let lines = format_scans(&scans);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("abc"));
+        assert!(lines[0].contains("paused"));
+        assert!(lines[0].contains("directory"));
+        assert!(lines[0].ends_with("http://localhost/"));
+    }
+}
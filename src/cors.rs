@@ -0,0 +1,161 @@
+//! --cors; reported resources are requested again with an `Origin` header naming a site that has
+//! no business reading them, and the resource's CORS policy is recorded alongside it
+//!
+//! a policy that echoes back any origin while allowing credentials lets every site on the internet
+//! read the resource on behalf of a logged-in user
+use reqwest::{
+    header::{
+        HeaderMap, ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_ORIGIN,
+        ORIGIN as ORIGIN_HEADER,
+    },
+    Url,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    config::Configuration,
+    event_handlers::{Command, Handles},
+    response::FeroxResponse,
+    scanner::RateGate,
+    utils::send_request,
+};
+
+/// origin sent with each check; nothing legitimate should ever allow it
+pub const ORIGIN: &str = "https://evil.example";
+
+/// CORS policy of a resource, as seen by a request from `ORIGIN`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cors {
+    /// value of the Access-Control-Allow-Origin header, empty when it wasn't sent
+    #[serde(default)]
+    pub allow_origin: String,
+
+    /// whether `ORIGIN` was echoed back as the allowed origin
+    #[serde(default)]
+    pub reflected: bool,
+
+    /// whether Access-Control-Allow-Credentials: true was sent
+    #[serde(default)]
+    pub credentials: bool,
+}
+
+/// implementation of Cors
+impl Cors {
+    /// Read the CORS policy from the headers of a response to a request from `ORIGIN`
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let allow_origin = headers
+            .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        let credentials = headers
+            .get(ACCESS_CONTROL_ALLOW_CREDENTIALS)
+            .and_then(|value| value.to_str().ok())
+            .map_or(false, |value| value.trim().eq_ignore_ascii_case("true"));
+
+        Self {
+            reflected: allow_origin == ORIGIN,
+            allow_origin,
+            credentials,
+        }
+    }
+
+    /// Whether any origin may read the resource on behalf of a logged-in user
+    pub fn misconfigured(&self) -> bool {
+        self.reflected && self.credentials
+    }
+
+    /// Short description shown alongside the url, None when the origin wasn't reflected
+    pub fn summary(&self) -> Option<&'static str> {
+        match (self.reflected, self.credentials) {
+            (true, true) => Some("origin reflected with credentials"),
+            (true, false) => Some("origin reflected"),
+            _ => None,
+        }
+    }
+}
+
+/// Request the given url from `ORIGIN`, using the scan's client, and return the CORS policy of
+/// the response; None when the request fails
+pub async fn check(
+    url: &Url,
+    config: &Configuration,
+    tx_stats: UnboundedSender<Command>,
+) -> Option<Cors> {
+    log::trace!("enter: check({})", url);
+
+    let request = config
+        .client
+        .get(url.to_owned())
+        .header(ORIGIN_HEADER, ORIGIN);
+
+    let cors = send_request(
+        request,
+        config.pin_verifier.as_deref(),
+        config.output_level,
+        tx_stats,
+    )
+    .await
+    .ok()
+    .map(|response| Cors::from_headers(response.headers()));
+
+    log::trace!("exit: check -> {:?}", cors);
+    cors
+}
+
+/// Check the CORS policy of the given response's url, once the gate allows it, and record it on
+/// the response; responses whose status code isn't reported (-s) aren't checked
+pub async fn check_response(response: &mut FeroxResponse, gate: &RateGate, handles: &Handles) {
+    let reported = handles
+        .config
+        .status_codes_for(response.extension())
+        .contains(&response.status().as_u16());
+
+    if !reported {
+        return;
+    }
+
+    gate.wait().await;
+
+    if let Some(policy) = check(response.url(), &handles.config, handles.stats.tx.clone()).await {
+        response.set_cors(policy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::header_map;
+
+    #[test]
+    /// only an echoed origin counts as reflected, and only along with credentials is it a
+    /// misconfiguration
+    fn from_headers_detects_reflection() {
+        let cors = Cors::from_headers(&header_map(&[
+            ("access-control-allow-origin", "https://evil.example"),
+            ("access-control-allow-credentials", "True"),
+        ]));
+        assert!(cors.reflected && cors.credentials && cors.misconfigured());
+        assert_eq!(cors.summary(), Some("origin reflected with credentials"));
+
+        let cors = Cors::from_headers(&header_map(&[(
+            "access-control-allow-origin",
+            "https://evil.example",
+        )]));
+        assert!(cors.reflected && !cors.misconfigured());
+        assert_eq!(cors.summary(), Some("origin reflected"));
+
+        let cors = Cors::from_headers(&header_map(&[
+            ("access-control-allow-origin", "*"),
+            ("access-control-allow-credentials", "true"),
+        ]));
+        assert_eq!(cors.allow_origin, "*");
+        assert!(!cors.reflected && !cors.misconfigured());
+        assert_eq!(cors.summary(), None);
+
+        assert_eq!(Cors::from_headers(&HeaderMap::new()), Cors::default());
+    }
+}
@@ -0,0 +1,408 @@
+//! result databases (--output-db, --output-dsn); reported responses, the scans that found them, and the run's
+//! statistics are written into a relational schema, so that huge result sets can be triaged with
+//! sql instead of grep
+//!
+//! each run is recorded as a session, so one database can hold the results of many runs (or many
+//! scanners, in the case of a shared postgres database); sqlite and postgres support are only
+//! compiled in when feroxbuster is built with the `sqlite` and `postgres` features, respectively
+use std::sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc, Mutex,
+};
+
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+use lazy_static::lazy_static;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    config::Configuration,
+    event_handlers::Handles,
+    events::{ScanEvent, Subscriber, Subscription, EVENTS},
+    response::FeroxResponse,
+    scan_manager::{FeroxScan, FeroxScans},
+    statistics::Stats,
+    VERSION,
+};
+
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+lazy_static! {
+    /// task writing to the databases opened via `initialize`, if any
+    static ref WRITER: Mutex<Option<Writer>> = Mutex::new(None);
+}
+
+/// Handle on the blocking task that owns the open databases; responses are written there, off the
+/// async runtime, in the order they're reported
+struct Writer {
+    /// responses waiting to be written
+    records: Sender<ResponseRecord>,
+
+    /// the databases, handed back once every response sent to the task is written
+    finished: Receiver<Vec<Box<dyn ResultSink>>>,
+
+    /// keeps reported responses coming in until `finalize`
+    subscription: Subscription<'static>,
+}
+
+/// A database to which results are written as they're reported
+pub trait ResultSink: Send {
+    /// Record a single reported response
+    fn write_response(&mut self, response: &ResponseRecord) -> Result<()>;
+
+    /// Record the final state of the run's scans
+    fn write_scans(&mut self, scans: &[ScanRecord]) -> Result<()>;
+
+    /// Record the run's statistics, as (field, value) pairs
+    fn write_statistics(&mut self, statistics: &[(String, String)]) -> Result<()>;
+
+    /// Mark the session finished and flush anything outstanding; nothing is written afterwards
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// A single run of feroxbuster, to which all of the run's other records belong
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// UUID that uniquely ID's the run
+    pub id: String,
+
+    /// time at which the run started, rfc3339
+    pub started: String,
+
+    /// target given by the user, empty when targets were read from --stdin
+    pub target: String,
+
+    /// version of feroxbuster that made the run
+    pub version: String,
+}
+
+/// implementation of Session
+impl Session {
+    /// Start a new session for a run with the given configuration
+    pub fn new(config: &Configuration) -> Self {
+        Self {
+            id: Uuid::new_v4().to_simple().to_string(),
+            started: now(),
+            target: config.target_url.clone(),
+            version: VERSION.to_string(),
+        }
+    }
+}
+
+/// Row of the responses table
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseRecord {
+    /// FeroxScan.id of the scan that found the response, empty when it's unknown
+    pub scan_id: String,
+
+    /// url of the response
+    pub url: String,
+
+    /// path portion of the url
+    pub path: String,
+
+    /// status code of the response
+    pub status: u16,
+
+    /// length of the response's body
+    pub content_length: u64,
+
+    /// number of lines in the response's body
+    pub line_count: usize,
+
+    /// number of words in the response's body
+    pub word_count: usize,
+
+    /// whether the response was a wildcard
+    pub wildcard: bool,
+
+    /// the response's --json record, for anything that doesn't get a column of its own
+    pub json: String,
+}
+
+/// implementation of ResponseRecord
+impl ResponseRecord {
+    /// Build the row for the given response
+    pub fn new(response: &FeroxResponse) -> Result<Self> {
+        Ok(Self {
+            scan_id: response
+                .scan()
+                .map(|scan| scan.id.clone())
+                .unwrap_or_default(),
+            url: response.url().to_string(),
+            path: response.url().path().to_string(),
+            status: response.status().as_u16(),
+            content_length: response.content_length(),
+            line_count: response.line_count(),
+            word_count: response.word_count(),
+            wildcard: response.wildcard(),
+            json: serde_json::to_string(response)?,
+        })
+    }
+}
+
+/// Row of the scans table
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanRecord {
+    /// FeroxScan.id of the scan
+    pub id: String,
+
+    /// url being scanned
+    pub url: String,
+
+    /// Directory or File
+    pub scan_type: String,
+
+    /// status of the scan when the run ended (Complete, Cancelled, etc...)
+    pub status: String,
+
+    /// number of requests the scan made
+    pub requests: u64,
+}
+
+/// implementation of ScanRecord
+impl ScanRecord {
+    /// Build the row for the given scan
+    pub fn new(scan: &FeroxScan) -> Result<Self> {
+        let value = serde_json::to_value(scan)?;
+        let field = |name: &str| value[name].as_str().unwrap_or_default().to_string();
+
+        Ok(Self {
+            id: scan.id().to_string(),
+            url: scan.url().to_string(),
+            scan_type: field("scan_type"),
+            status: field("status"),
+            requests: scan.requests(),
+        })
+    }
+}
+
+/// Open the databases asked for by the given configuration; responses are recorded until
+/// `finalize` is called
+pub fn initialize(config: &Configuration) -> Result<()> {
+    log::trace!("enter: initialize({})", config.output_db);
+
+    let session = Session::new(config);
+    let mut sinks = Vec::new();
+
+    if !config.output_db.is_empty() {
+        sinks.push(open_sqlite(&config.output_db, &session)?);
+    }
+
+    if !config.output_dsn.is_empty() {
+        sinks.push(open_postgres(
+            &config.output_dsn,
+            &session,
+            config.insecure,
+        )?);
+    }
+
+    if !sinks.is_empty() {
+        let (records, pending) = mpsc::channel();
+        let (done, finished) = mpsc::channel();
+
+        // sqlite/postgres calls block, so they're kept off the runtime's worker threads
+        tokio::task::spawn_blocking(move || write_responses(sinks, pending, done));
+
+        let subscription = EVENTS.subscribe(Arc::new(Recorder));
+
+        if let Ok(mut guard) = WRITER.lock() {
+            *guard = Some(Writer {
+                records,
+                finished,
+                subscription,
+            });
+        }
+    }
+
+    log::trace!("exit: initialize");
+    Ok(())
+}
+
+/// records reported responses from the event bus in each open database
+struct Recorder;
+
+/// implementation of Subscriber for Recorder
+impl Subscriber for Recorder {
+    /// record reported responses, everything else is written by `finalize`
+    fn on_event(&self, event: &ScanEvent) {
+        if let ScanEvent::ResponseReported(response) = event {
+            record(response);
+        }
+    }
+}
+
+/// Send a reported response to the task writing to the open databases
+fn record(response: &FeroxResponse) {
+    if let Ok(guard) = WRITER.lock() {
+        let writer = match guard.as_ref() {
+            Some(writer) => writer,
+            None => return,
+        };
+
+        let record = match ResponseRecord::new(response) {
+            Ok(record) => record,
+            Err(e) => {
+                log::warn!(
+                    "Could not build database record for {}: {}",
+                    response.url(),
+                    e
+                );
+                return;
+            }
+        };
+
+        if writer.records.send(record).is_err() {
+            log::warn!("Could not write {} to database", response.url());
+        }
+    }
+}
+
+/// Write each response received to the given databases, until every sender is gone; the
+/// databases are handed back through `done` afterwards
+fn write_responses(
+    mut sinks: Vec<Box<dyn ResultSink>>,
+    pending: Receiver<ResponseRecord>,
+    done: Sender<Vec<Box<dyn ResultSink>>>,
+) {
+    for record in pending {
+        for sink in sinks.iter_mut() {
+            if let Err(e) = sink.write_response(&record) {
+                log::warn!("Could not write {} to database: {}", record.url, e);
+            }
+        }
+    }
+
+    done.send(sinks).unwrap_or_default();
+}
+
+/// Write the run's scans and statistics to each open database and close them out, once every
+/// response reported so far is written; nothing is recorded afterwards
+pub fn finalize(handles: &Handles) -> Result<()> {
+    let writer = match WRITER.lock() {
+        Ok(mut guard) => guard.take(),
+        Err(_) => return Ok(()),
+    };
+
+    let Writer {
+        records,
+        finished,
+        subscription,
+    } = match writer {
+        Some(writer) => writer,
+        None => return Ok(()),
+    };
+
+    // no more responses are coming, which lets the writer finish up and hand the databases back
+    drop(subscription);
+    drop(records);
+
+    let sinks = match finished.recv() {
+        Ok(sinks) => sinks,
+        Err(_) => return Ok(()),
+    };
+
+    let scans = handles.ferox_scans()?;
+    let scans = scan_records(&scans)?;
+    let statistics = statistic_records(&handles.stats.data)?;
+
+    for mut sink in sinks {
+        sink.write_scans(&scans)?;
+        sink.write_statistics(&statistics)?;
+        sink.finish()?;
+    }
+
+    Ok(())
+}
+
+/// rows of the scans table, one per scan known to the run
+fn scan_records(scans: &FeroxScans) -> Result<Vec<ScanRecord>> {
+    let mut records = Vec::new();
+
+    for scan in scans.get_scans() {
+        records.push(ScanRecord::new(&scan)?);
+    }
+
+    Ok(records)
+}
+
+/// (field, value) pairs of the given statistics, values as json
+fn statistic_records(stats: &Arc<Stats>) -> Result<Vec<(String, String)>> {
+    let records = match serde_json::to_value(stats.as_ref())? {
+        Value::Object(fields) => fields
+            .into_iter()
+            .filter(|(name, _)| name != "type")
+            .map(|(name, value)| (name, value.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(records)
+}
+
+/// the current time, rfc3339
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// open (creating if necessary) the sqlite database at the given path
+#[cfg(feature = "sqlite")]
+fn open_sqlite(path: &str, session: &Session) -> Result<Box<dyn ResultSink>> {
+    Ok(Box::new(sqlite::SqliteSink::open(path, session)?))
+}
+
+/// sqlite databases are only available when built with the `sqlite` feature
+#[cfg(not(feature = "sqlite"))]
+fn open_sqlite(_path: &str, _session: &Session) -> Result<Box<dyn ResultSink>> {
+    anyhow::bail!("--output-db requires feroxbuster to be built with --features sqlite");
+}
+
+/// connect to the postgres database at the given dsn
+#[cfg(feature = "postgres")]
+fn open_postgres(dsn: &str, session: &Session, insecure: bool) -> Result<Box<dyn ResultSink>> {
+    Ok(Box::new(postgres::PostgresSink::open(
+        dsn, session, insecure,
+    )?))
+}
+
+/// postgres databases are only available when built with the `postgres` feature
+#[cfg(not(feature = "postgres"))]
+fn open_postgres(_dsn: &str, _session: &Session, _insecure: bool) -> Result<Box<dyn ResultSink>> {
+    anyhow::bail!("--output-dsn requires feroxbuster to be built with --features postgres");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    /// response records should carry the columns pulled out of the response, plus its json
+    fn response_record_has_response_columns() {
+        let mut response = FeroxResponse::default();
+        response.set_url("http://localhost/api/users");
+        response.set_status(StatusCode::FORBIDDEN);
+
+        let record = ResponseRecord::new(&response).unwrap();
+
+        assert_eq!(record.scan_id, "");
+        assert_eq!(record.url, "http://localhost/api/users");
+        assert_eq!(record.path, "/api/users");
+        assert_eq!(record.status, 403);
+        assert!(!record.wildcard);
+        assert!(record.json.starts_with(r#"{"type":"response""#));
+    }
+
+    #[test]
+    /// the statistics' own type field isn't worth a row
+    fn statistic_records_skip_type() {
+        let records = statistic_records(&Arc::new(Stats::new(0, false))).unwrap();
+
+        assert!(!records.is_empty());
+        assert!(records.iter().all(|(name, _)| name != "type"));
+    }
+}
@@ -0,0 +1,276 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+
+use super::{now, ResponseRecord, ResultSink, ScanRecord, Session};
+use crate::utils::fmt_err;
+
+/// number of responses inserted per transaction; committing each insert on its own would make
+/// sqlite sync to disk once per response, and holding one transaction open for the whole run
+/// would lock out any other writers
+const BATCH_SIZE: usize = 500;
+
+/// tables and indices written to by SqliteSink; created if they don't already exist
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    started TEXT NOT NULL,
+    finished TEXT,
+    target TEXT NOT NULL,
+    version TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS scans (
+    session_id TEXT NOT NULL REFERENCES sessions(id),
+    id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    scan_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    requests INTEGER NOT NULL,
+    PRIMARY KEY (session_id, id)
+);
+
+CREATE TABLE IF NOT EXISTS responses (
+    id INTEGER PRIMARY KEY,
+    session_id TEXT NOT NULL REFERENCES sessions(id),
+    scan_id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    path TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    content_length INTEGER NOT NULL,
+    line_count INTEGER NOT NULL,
+    word_count INTEGER NOT NULL,
+    wildcard INTEGER NOT NULL,
+    json TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS responses_url ON responses(url);
+CREATE INDEX IF NOT EXISTS responses_status ON responses(status);
+CREATE INDEX IF NOT EXISTS responses_content_length ON responses(content_length);
+CREATE INDEX IF NOT EXISTS responses_scan ON responses(session_id, scan_id);
+
+CREATE TABLE IF NOT EXISTS statistics (
+    session_id TEXT NOT NULL REFERENCES sessions(id),
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (session_id, name)
+);
+"#;
+
+/// SQLite database written to via --output-db
+pub(super) struct SqliteSink {
+    /// open connection to the database
+    connection: Connection,
+
+    /// session to which everything written belongs
+    session: Session,
+
+    /// responses waiting to be inserted
+    pending: Vec<ResponseRecord>,
+}
+
+/// implementation of SqliteSink
+impl SqliteSink {
+    /// Open (creating if necessary) the database at the given path and record the new session
+    pub(super) fn open(path: &str, session: &Session) -> Result<Self> {
+        log::trace!("enter: open({}, {:?})", path, session);
+
+        let connection = Connection::open(path)
+            .with_context(|| fmt_err(&format!("Could not open database {}", path)))?;
+
+        // write-ahead logging lets --parallel instances share a database without waiting on
+        // each other's reads
+        connection.execute_batch("PRAGMA journal_mode = WAL")?;
+
+        connection
+            .execute_batch(SCHEMA)
+            .with_context(|| fmt_err(&format!("Could not create tables in {}", path)))?;
+
+        connection.execute(
+            "INSERT INTO sessions (id, started, target, version) VALUES (?1, ?2, ?3, ?4)",
+            params![session.id, session.started, session.target, session.version],
+        )?;
+
+        log::trace!("exit: open");
+
+        Ok(Self {
+            connection,
+            session: session.clone(),
+            pending: Vec::with_capacity(BATCH_SIZE),
+        })
+    }
+
+    /// Insert all pending responses in a single transaction
+    fn flush(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let transaction = self.connection.transaction()?;
+
+        {
+            let mut statement = transaction.prepare_cached(
+                "INSERT INTO responses (session_id, scan_id, url, path, status, content_length, \
+                 line_count, word_count, wildcard, json) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+
+            for response in &self.pending {
+                statement.execute(params![
+                    self.session.id,
+                    response.scan_id,
+                    response.url,
+                    response.path,
+                    response.status,
+                    response.content_length as i64,
+                    response.line_count as i64,
+                    response.word_count as i64,
+                    response.wildcard,
+                    response.json,
+                ])?;
+            }
+        }
+
+        transaction.commit()?;
+        self.pending.clear();
+
+        Ok(())
+    }
+}
+
+/// implementation of ResultSink for SqliteSink
+impl ResultSink for SqliteSink {
+    /// Queue the response, inserting the queue once BATCH_SIZE responses are waiting
+    fn write_response(&mut self, response: &ResponseRecord) -> Result<()> {
+        self.pending.push(response.clone());
+
+        if self.pending.len() >= BATCH_SIZE {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Insert (or update, when a scan was resumed) each scan
+    fn write_scans(&mut self, scans: &[ScanRecord]) -> Result<()> {
+        let mut statement = self.connection.prepare_cached(
+            "INSERT OR REPLACE INTO scans (session_id, id, url, scan_type, status, requests) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+
+        for scan in scans {
+            statement.execute(params![
+                self.session.id,
+                scan.id,
+                scan.url,
+                scan.scan_type,
+                scan.status,
+                scan.requests as i64,
+            ])?;
+        }
+
+        Ok(())
+    }
+
+    /// Insert one row per statistic
+    fn write_statistics(&mut self, statistics: &[(String, String)]) -> Result<()> {
+        let mut statement = self.connection.prepare_cached(
+            "INSERT OR REPLACE INTO statistics (session_id, name, value) VALUES (?1, ?2, ?3)",
+        )?;
+
+        for (name, value) in statistics {
+            statement.execute(params![self.session.id, name, value])?;
+        }
+
+        Ok(())
+    }
+
+    /// Insert any pending responses and mark the session finished
+    fn finish(&mut self) -> Result<()> {
+        self.flush()?;
+
+        self.connection.execute(
+            "UPDATE sessions SET finished = ?1 WHERE id = ?2",
+            params![now(), self.session.id],
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// session used by each test
+    fn session() -> Session {
+        Session {
+            id: "5ba3b2d3a1ec4b1e9f3cbbd0b5dfc0d4".to_string(),
+            started: now(),
+            target: "http://localhost".to_string(),
+            version: "0.0.0".to_string(),
+        }
+    }
+
+    #[test]
+    /// everything written should be queryable once the sink is finished
+    fn sqlite_sink_writes_session_scans_responses_and_statistics() {
+        let tmp_dir = tempfile::TempDir::new().unwrap();
+        let path = tmp_dir.path().join("results.sqlite");
+        let path = path.to_str().unwrap();
+
+        let mut sink = SqliteSink::open(path, &session()).unwrap();
+
+        let response = ResponseRecord {
+            scan_id: "abc123".to_string(),
+            url: "http://localhost/admin".to_string(),
+            path: "/admin".to_string(),
+            status: 403,
+            content_length: 10,
+            line_count: 1,
+            word_count: 2,
+            wildcard: false,
+            json: "{}".to_string(),
+        };
+
+        let scan = ScanRecord {
+            id: "abc123".to_string(),
+            url: "http://localhost/".to_string(),
+            scan_type: "Directory".to_string(),
+            status: "Complete".to_string(),
+            requests: 42,
+        };
+
+        sink.write_response(&response).unwrap();
+        sink.write_scans(&[scan]).unwrap();
+        sink.write_statistics(&[("requests".to_string(), "42".to_string())])
+            .unwrap();
+        sink.finish().unwrap();
+
+        let connection = Connection::open(path).unwrap();
+
+        let finished: Option<String> = connection
+            .query_row("SELECT finished FROM sessions", [], |row| row.get(0))
+            .unwrap();
+        assert!(finished.is_some());
+
+        let (url, status): (String, u16) = connection
+            .query_row(
+                "SELECT r.url, r.status FROM responses r JOIN scans s \
+                 ON r.session_id = s.session_id AND r.scan_id = s.id WHERE s.requests = 42",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(url, "http://localhost/admin");
+        assert_eq!(status, 403);
+
+        let requests: String = connection
+            .query_row(
+                "SELECT value FROM statistics WHERE name = 'requests'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(requests, "42");
+    }
+}
@@ -0,0 +1,142 @@
+//! content-encoding negotiation and decoding
+//!
+//! bodies are decoded here, instead of by the client, so that both the compressed (transfer) and
+//! decompressed sizes of a response are known
+use std::io::Read;
+
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+
+/// value of the Accept-Encoding header sent with every request, unless overridden with -H
+pub const ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+/// maximum number of bytes a body is decoded to; anything past that is dropped, so that a small
+/// compressed body can't expand to fill all available memory
+pub const MAX_DECODED_LENGTH: u64 = 16 * 1024 * 1024;
+
+/// Decode the given body according to the response's Content-Encoding header
+///
+/// encodings are undone in the reverse order in which they were applied; if an encoding is
+/// unknown or can't be decoded, the body is returned as-is. Decoded bodies are truncated to
+/// `MAX_DECODED_LENGTH`
+pub fn decode(content_encoding: Option<&str>, body: Vec<u8>) -> Vec<u8> {
+    let content_encoding = match content_encoding {
+        // HEAD requests, 204s, 304s, etc... have the header, but nothing to decode
+        Some(encoding) if !body.is_empty() => encoding,
+        _ => return body,
+    };
+
+    let mut decoded = body;
+
+    for encoding in content_encoding.rsplit(',') {
+        let encoding = encoding.trim().to_lowercase();
+
+        let result = match encoding.as_str() {
+            "" | "identity" => continue,
+            "gzip" | "x-gzip" => read_all(GzDecoder::new(&decoded[..])),
+            "deflate" => {
+                // deflate is supposed to be zlib wrapped, but plenty of servers send raw deflate
+                read_all(ZlibDecoder::new(&decoded[..]))
+                    .or_else(|_| read_all(DeflateDecoder::new(&decoded[..])))
+            }
+            "br" => read_all(brotli::Decompressor::new(&decoded[..], 4096)),
+            _ => {
+                log::warn!("Unknown Content-Encoding {}, body left as-is", encoding);
+                return decoded;
+            }
+        };
+
+        match result {
+            Ok(bytes) => decoded = bytes,
+            Err(e) => {
+                log::warn!("Could not decode {} body: {}", encoding, e);
+                return decoded;
+            }
+        }
+    }
+
+    decoded
+}
+
+/// read everything from the given decoder, up to `MAX_DECODED_LENGTH` bytes
+fn read_all<R: Read>(reader: R) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(MAX_DECODED_LENGTH).read_to_end(&mut bytes)?;
+
+    if bytes.len() as u64 == MAX_DECODED_LENGTH {
+        log::warn!(
+            "Decoded body reached {} bytes, the rest was dropped",
+            MAX_DECODED_LENGTH
+        );
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{
+        write::{GzEncoder, ZlibEncoder},
+        Compression,
+    };
+    use std::io::Write;
+
+    /// helper to gzip the given bytes
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    /// gzip, deflate, and brotli bodies should all be decoded
+    fn decode_handles_known_encodings() {
+        let body = b"hello hello hello hello".to_vec();
+
+        assert_eq!(decode(Some("gzip"), gzip(&body)), body);
+
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(&body).unwrap();
+        assert_eq!(decode(Some("deflate"), zlib.finish().unwrap()), body);
+
+        let mut br = Vec::new();
+        brotli::CompressorReader::new(&body[..], 4096, 5, 22)
+            .read_to_end(&mut br)
+            .unwrap();
+        assert_eq!(decode(Some("br"), br), body);
+    }
+
+    #[test]
+    /// a body that decodes to more than MAX_DECODED_LENGTH should be truncated
+    fn decode_caps_decoded_length() {
+        let bomb = gzip(&vec![0; MAX_DECODED_LENGTH as usize + 1024]);
+        assert!(bomb.len() < 1024 * 1024);
+
+        assert_eq!(decode(Some("gzip"), bomb).len() as u64, MAX_DECODED_LENGTH);
+    }
+
+    #[test]
+    /// empty bodies (i.e. HEAD requests) should be returned as-is
+    fn decode_skips_empty_bodies() {
+        assert!(decode(Some("gzip"), Vec::new()).is_empty());
+    }
+
+    #[test]
+    /// stacked encodings should be undone in reverse order
+    fn decode_handles_multiple_encodings() {
+        let body = b"stacked".to_vec();
+        let twice = gzip(&gzip(&body));
+
+        assert_eq!(decode(Some("gzip, identity, GZIP"), twice), body);
+    }
+
+    #[test]
+    /// missing, unknown, and undecodable encodings should leave the body alone
+    fn decode_leaves_body_alone_when_it_cant_decode() {
+        let body = b"plain".to_vec();
+
+        assert_eq!(decode(None, body.clone()), body);
+        assert_eq!(decode(Some("zstd"), body.clone()), body);
+        assert_eq!(decode(Some("gzip"), body.clone()), body);
+    }
+}
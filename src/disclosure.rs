@@ -0,0 +1,345 @@
+//! follow-up enumeration of exposed source control metadata and .DS_Store files
+//!
+//! once a .git or .svn directory (or a .DS_Store file) is discovered, well-known paths beneath it
+//! are requested, and every one that's retrievable is reported; confirming the disclosure without
+//! needing a second tool
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    sync::{Arc, Mutex},
+};
+
+use lazy_static::lazy_static;
+use reqwest::{header::CONTENT_ENCODING, Url};
+
+use crate::{
+    decompress,
+    event_handlers::{Command::AddToUsizeField, Handles},
+    response::FeroxResponse,
+    scanner::RateGate,
+    statistics::StatField::TotalExpected,
+    throttle,
+    utils::logged_request,
+};
+
+/// paths below a .git directory that are requested once it's discovered
+pub const GIT_PATHS: [&str; 12] = [
+    "HEAD",
+    "config",
+    "index",
+    "description",
+    "packed-refs",
+    "COMMIT_EDITMSG",
+    "ORIG_HEAD",
+    "FETCH_HEAD",
+    "info/exclude",
+    "logs/HEAD",
+    "refs/heads/master",
+    "refs/heads/main",
+];
+
+/// paths below a .svn directory that are requested once it's discovered
+pub const SVN_PATHS: [&str; 3] = ["entries", "wc.db", "format"];
+
+/// record types found in .DS_Store files, used to tell records apart from other data
+const DS_STORE_TYPES: [&[u8]; 8] = [
+    b"long", b"shor", b"bool", b"blob", b"type", b"ustr", b"comp", b"dutc",
+];
+
+lazy_static! {
+    /// .git/.svn directories and .DS_Store files that were already followed up on
+    static ref FOLLOWED_UP: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// Something that discloses the contents of the server's filesystem
+#[derive(Debug, Clone, PartialEq)]
+pub enum Disclosure {
+    /// a .git directory; the url always ends with .git/
+    Git(Url),
+
+    /// a .svn directory; the url always ends with .svn/
+    Svn(Url),
+
+    /// a .DS_Store file
+    DsStore(Url),
+}
+
+/// implementation of Disclosure
+impl Disclosure {
+    /// Determine whether the given url is (or is within) a .git or .svn directory, or is a
+    /// .DS_Store file
+    pub fn from_url(url: &Url) -> Option<Self> {
+        if url.path().ends_with("/.DS_Store") {
+            let mut url = url.clone();
+            url.set_query(None);
+            url.set_fragment(None);
+            return Some(Disclosure::DsStore(url));
+        }
+
+        if let Some(root) = metadata_root(url, ".git") {
+            return Some(Disclosure::Git(root));
+        }
+
+        metadata_root(url, ".svn").map(Disclosure::Svn)
+    }
+
+    /// url of the directory or file
+    pub fn url(&self) -> &Url {
+        match self {
+            Disclosure::Git(url) | Disclosure::Svn(url) | Disclosure::DsStore(url) => url,
+        }
+    }
+}
+
+/// url of the `dir` directory that the given url is (or is within), i.e. /app/.git/logs/HEAD ->
+/// /app/.git/
+fn metadata_root(url: &Url, dir: &str) -> Option<Url> {
+    let path = url.path();
+    let marker = format!("/{}/", dir);
+
+    let root_path = match path.find(&marker) {
+        Some(index) => path[..index + marker.len()].to_string(),
+        None if path.ends_with(&marker[..marker.len() - 1]) => format!("{}/", path),
+        None => return None,
+    };
+
+    let mut root = url.clone();
+    root.set_path(&root_path);
+    root.set_query(None);
+    root.set_fragment(None);
+
+    Some(root)
+}
+
+/// Names of the files and directories recorded in the given .DS_Store file
+///
+/// .DS_Store files are made up of records that start with a utf-16 filename, followed by a
+/// four character structure id and a four character data type; rather than walking the file's
+/// b-tree, every offset that looks like the start of such a record is decoded
+pub fn ds_store_names(bytes: &[u8]) -> Vec<String> {
+    let mut names = Vec::new();
+
+    if bytes.len() < 8 || &bytes[4..8] != b"Bud1" {
+        return names;
+    }
+
+    let mut offset = 8;
+
+    while offset + 4 <= bytes.len() {
+        let length = u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        let end = offset + 4 + length * 2;
+
+        let looks_like_record = length > 0
+            && length < 1024
+            && end + 8 <= bytes.len()
+            && bytes[end..end + 4].iter().all(u8::is_ascii_alphanumeric)
+            && DS_STORE_TYPES.contains(&&bytes[end + 4..end + 8]);
+
+        if looks_like_record {
+            let units: Vec<u16> = bytes[offset + 4..end]
+                .chunks(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+
+            if let Ok(name) = String::from_utf16(&units) {
+                if name != "." && !name.contains('/') && !names.contains(&name) {
+                    names.push(name);
+                }
+
+                offset = end + 8;
+                continue;
+            }
+        }
+
+        offset += 1;
+    }
+
+    names
+}
+
+/// Request the given url, returning the response only when it was retrievable
+async fn retrieve(url: &Url, handles: Arc<Handles>) -> Option<FeroxResponse> {
+    let response = logged_request(url, handles.clone()).await.ok()?;
+
+    let ferox_response = FeroxResponse::from(response, true, handles.config.output_level).await;
+
+    // a catch-all 200 would otherwise make every path look retrievable
+    let is_wildcard = handles.ferox_scans().map_or(false, |scans| {
+        scans.is_wildcard(&ferox_response, handles.stats.tx.clone())
+    });
+
+    if !ferox_response.status().is_success()
+        || is_wildcard
+        || handles
+            .filters
+            .data
+            .should_filter_response(&ferox_response, handles.stats.tx.clone())
+    {
+        return None;
+    }
+
+    Some(ferox_response)
+}
+
+/// Request the raw bytes of a .DS_Store file; its body is binary, so it can't go through
+/// FeroxResponse's text decoding
+async fn retrieve_ds_store(url: &Url, gate: &RateGate, handles: Arc<Handles>) -> Option<Vec<u8>> {
+    gate.wait().await;
+
+    let response = logged_request(url, handles).await.ok()?;
+
+    if !response.status().is_success() {
+        return None;
+    }
+
+    let encoding = response
+        .headers()
+        .get(CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    let raw = throttle::read_body(response).await.ok()?;
+
+    Some(decompress::decode(encoding.as_deref(), raw))
+}
+
+/// Enumerate the well-known paths of the given disclosure, reporting every one that's
+/// retrievable
+///
+/// directories listed in a .DS_Store file are checked for their own .DS_Store in turn; nothing is
+/// followed up on more than once. every request waits on the scan's rate limit
+pub async fn follow_up(disclosure: Disclosure, gate: RateGate, handles: Arc<Handles>) {
+    log::trace!("enter: follow_up({:?})", disclosure);
+
+    let mut queue = vec![disclosure];
+
+    while let Some(disclosure) = queue.pop() {
+        match FOLLOWED_UP.lock() {
+            Ok(mut guard) => {
+                if !guard.insert(disclosure.url().to_string()) {
+                    continue;
+                }
+            }
+            Err(_) => continue,
+        }
+
+        let urls: Vec<Url> = match &disclosure {
+            Disclosure::Git(root) => GIT_PATHS.iter().filter_map(|p| root.join(p).ok()).collect(),
+            Disclosure::Svn(root) => SVN_PATHS.iter().filter_map(|p| root.join(p).ok()).collect(),
+            Disclosure::DsStore(url) => {
+                match retrieve_ds_store(url, &gate, handles.clone()).await {
+                    Some(bytes) => ds_store_names(&bytes)
+                        .iter()
+                        .filter_map(|name| url.join(name).ok())
+                        .collect(),
+                    None => continue,
+                }
+            }
+        };
+
+        handles
+            .stats
+            .send(AddToUsizeField(TotalExpected, urls.len()))
+            .unwrap_or_else(|e| log::warn!("Could not update overall scan bar: {}", e));
+
+        let mut retrieved = 0;
+
+        for url in urls {
+            let is_directory = url
+                .path_segments()
+                .and_then(|segments| segments.last())
+                .map_or(false, |last| !last.contains('.'));
+
+            if matches!(disclosure, Disclosure::DsStore(_)) && is_directory {
+                // most likely a directory, which may have a .DS_Store of its own
+                if let Ok(nested) = Url::parse(&format!("{}/.DS_Store", url)) {
+                    queue.push(Disclosure::DsStore(nested));
+                }
+            }
+
+            gate.wait().await;
+
+            if let Some(response) = retrieve(&url, handles.clone()).await {
+                retrieved += 1;
+
+                if let Err(e) = response.send_report(handles.output.tx.clone()) {
+                    log::warn!("Could not send FeroxResponse to output handler: {}", e);
+                }
+            }
+        }
+
+        log::info!(
+            "{} follow-up paths retrievable at {}",
+            retrieved,
+            disclosure.url()
+        );
+    }
+
+    log::trace!("exit: follow_up");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// helper to build a single .DS_Store record
+    fn record(name: &str, structure: &[u8; 4], kind: &[u8; 4]) -> Vec<u8> {
+        let units: Vec<u16> = name.encode_utf16().collect();
+
+        let mut bytes = (units.len() as u32).to_be_bytes().to_vec();
+
+        for unit in units {
+            bytes.extend_from_slice(&unit.to_be_bytes());
+        }
+
+        bytes.extend_from_slice(structure);
+        bytes.extend_from_slice(kind);
+        bytes.extend_from_slice(&[0, 0, 0, 1]);
+        bytes
+    }
+
+    #[test]
+    /// urls within .git/.svn should resolve to the metadata directory itself
+    fn from_url_finds_metadata_roots() {
+        let url = Url::parse("http://localhost/app/.git/logs/HEAD").unwrap();
+        assert_eq!(
+            Disclosure::from_url(&url),
+            Some(Disclosure::Git(
+                Url::parse("http://localhost/app/.git/").unwrap()
+            ))
+        );
+
+        let url = Url::parse("http://localhost/.svn").unwrap();
+        assert_eq!(
+            Disclosure::from_url(&url),
+            Some(Disclosure::Svn(
+                Url::parse("http://localhost/.svn/").unwrap()
+            ))
+        );
+
+        let url = Url::parse("http://localhost/images/.DS_Store").unwrap();
+        assert_eq!(
+            Disclosure::from_url(&url),
+            Some(Disclosure::DsStore(url.clone()))
+        );
+
+        let url = Url::parse("http://localhost/.github/workflows").unwrap();
+        assert_eq!(Disclosure::from_url(&url), None);
+    }
+
+    #[test]
+    /// filenames should be pulled out of each record, skipping anything else
+    fn ds_store_names_parses_records() {
+        let mut bytes = vec![0, 0, 0, 1];
+        bytes.extend_from_slice(b"Bud1");
+        bytes.extend_from_slice(&[0; 24]);
+        bytes.extend(record("backup.zip", b"Iloc", b"blob"));
+        bytes.extend(record("backup.zip", b"lsvp", b"blob"));
+        bytes.extend(record(".", b"icvp", b"blob"));
+        bytes.extend(record("admin", b"lg1S", b"comp"));
+        bytes.extend_from_slice(&[0; 16]);
+
+        assert_eq!(ds_store_names(&bytes), vec!["backup.zip", "admin"]);
+        assert!(ds_store_names(b"not a ds_store file").is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+//! in-process dns cache (--dns-cache-ttl)
+//!
+//! every request made to the same host would otherwise result in a separate resolver lookup; the
+//! cache is shared by all clients
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use hyper::client::connect::dns::Name;
+use lazy_static::lazy_static;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use tokio::net::lookup_host;
+
+lazy_static! {
+    /// hostname -> (time of lookup, resolved addresses)
+    static ref DNS_CACHE: RwLock<HashMap<String, (Instant, Vec<SocketAddr>)>> =
+        RwLock::new(HashMap::new());
+}
+
+/// Resolver that caches successful lookups for a fixed amount of time
+#[derive(Debug, Clone, Copy)]
+pub struct CachingResolver {
+    /// amount of time for which a lookup is reused
+    ttl: Duration,
+}
+
+/// implementation of CachingResolver
+impl CachingResolver {
+    /// create a new resolver that reuses lookups for `ttl` seconds
+    pub fn new(ttl: u64) -> Self {
+        Self {
+            ttl: Duration::from_secs(ttl),
+        }
+    }
+
+    /// addresses previously resolved for `host`, as long as they haven't expired
+    fn cached(&self, host: &str) -> Option<Vec<SocketAddr>> {
+        let guard = DNS_CACHE.read().ok()?;
+        let (resolved_at, addrs) = guard.get(host)?;
+
+        if resolved_at.elapsed() < self.ttl {
+            Some(addrs.clone())
+        } else {
+            None
+        }
+    }
+}
+
+/// record the addresses resolved for `host`
+fn store(host: &str, addrs: Vec<SocketAddr>) {
+    if let Ok(mut guard) = DNS_CACHE.write() {
+        guard.insert(host.to_string(), (Instant::now(), addrs));
+    }
+}
+
+/// Resolve implementation for CachingResolver
+impl Resolve for CachingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = *self;
+
+        Box::pin(async move {
+            let host = name.as_str();
+
+            if let Some(addrs) = resolver.cached(host) {
+                log::trace!("dns cache hit for {}", host);
+                return Ok(Box::new(addrs.into_iter()) as Addrs);
+            }
+
+            // the port is filled in by the client
+            let addrs: Vec<SocketAddr> = lookup_host((host, 0)).await?.collect();
+
+            if !addrs.is_empty() {
+                store(host, addrs.clone());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// lookups should be reused until the ttl expires
+    fn caching_resolver_honors_ttl() {
+        let addrs = vec![SocketAddr::from(([10, 0, 0, 1], 0))];
+        store("cached.ferox.test", addrs.clone());
+
+        assert_eq!(
+            CachingResolver::new(60).cached("cached.ferox.test"),
+            Some(addrs)
+        );
+        assert_eq!(CachingResolver::new(0).cached("cached.ferox.test"), None);
+        assert_eq!(CachingResolver::new(60).cached("missing.ferox.test"), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// cached addresses should be returned without consulting the system resolver
+    async fn caching_resolver_returns_cached_addresses() {
+        let addrs = vec![SocketAddr::from(([10, 0, 0, 2], 0))];
+        store("resolved.ferox.test", addrs.clone());
+
+        let name: Name = "resolved.ferox.test".parse().unwrap();
+        let resolved: Vec<SocketAddr> = CachingResolver::new(60)
+            .resolve(name)
+            .await
+            .unwrap()
+            .collect();
+
+        assert_eq!(resolved, addrs);
+    }
+}
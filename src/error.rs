@@ -0,0 +1,161 @@
+//! errors that end feroxbuster early
+//!
+//! library code returns these instead of exiting the process, so that what happens next (and
+//! what the user is shown) is decided by the binary, or by whatever embeds the library
+use std::fmt;
+
+/// Alias for results whose error is a `FeroxError`
+pub type FeroxResult<T> = std::result::Result<T, FeroxError>;
+
+/// An error that feroxbuster can't continue past
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeroxError {
+    /// the --resume-from state file couldn't be read (or decrypted)
+    StateFileUnreadable {
+        /// path of the state file
+        filename: String,
+
+        /// why it couldn't be read
+        reason: String,
+    },
+
+    /// the --resume-from state file was read, but its configuration couldn't be used
+    StateFileConfig {
+        /// path of the state file
+        filename: String,
+
+        /// what's wrong with the configuration
+        reason: String,
+    },
+
+    /// a value given on the command line couldn't be parsed
+    Argument {
+        /// why it couldn't be parsed
+        reason: String,
+    },
+
+    /// an http client couldn't be built from the configuration
+    Client {
+        /// what the client is used for, i.e. scanning or replaying responses to a proxy
+        purpose: &'static str,
+
+        /// why it couldn't be built
+        reason: String,
+    },
+
+    /// the scan's state couldn't be saved when it was stopped early
+    SaveState {
+        /// why it couldn't be saved
+        reason: String,
+    },
+
+    /// the scan was stopped early (ctrl+c, --time-limit, --request-limit)
+    Interrupted {
+        /// file to which the scan's state was saved, if it was saved
+        state_file: Option<String>,
+    },
+}
+
+/// implementation of FeroxError
+impl FeroxError {
+    /// error for a command line value that couldn't be parsed
+    pub fn argument(reason: impl fmt::Display) -> Self {
+        FeroxError::Argument {
+            reason: reason.to_string(),
+        }
+    }
+
+    /// error for a client, used for `purpose`, that couldn't be built
+    pub fn client(purpose: &'static str, error: anyhow::Error) -> Self {
+        FeroxError::Client {
+            purpose,
+            reason: format!("{:#}", error),
+        }
+    }
+}
+
+/// Display implementation for FeroxError
+impl fmt::Display for FeroxError {
+    /// human readable description, including whatever caused the error
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeroxError::StateFileUnreadable { filename, reason } => {
+                write!(f, "Could not read state file {}: {}", filename, reason)
+            }
+            FeroxError::StateFileConfig { filename, reason } => write!(
+                f,
+                "Could not load configuration from state file {}: {}",
+                filename, reason
+            ),
+            FeroxError::Argument { reason } => {
+                write!(f, "Invalid command line argument: {}", reason)
+            }
+            FeroxError::Client { purpose, reason } => {
+                write!(f, "Could not build {} client: {}", purpose, reason)
+            }
+            FeroxError::SaveState { reason } => {
+                write!(f, "Could not save scan state: {}", reason)
+            }
+            FeroxError::Interrupted {
+                state_file: Some(state_file),
+            } => write!(f, "Scan stopped early; state saved to {}", state_file),
+            FeroxError::Interrupted { state_file: None } => write!(f, "Scan stopped early"),
+        }
+    }
+}
+
+/// FeroxError is a std Error, which also lets it be used with anyhow
+impl std::error::Error for FeroxError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// the underlying reason should be part of each message, so that the user isn't left
+    /// guessing
+    fn ferox_error_display_includes_context() {
+        let error = FeroxError::StateFileUnreadable {
+            filename: String::from("ferox-1.state"),
+            reason: String::from("No such file or directory"),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Could not read state file ferox-1.state: No such file or directory"
+        );
+
+        let error = FeroxError::Client {
+            purpose: "replay",
+            reason: String::from("builder error: invalid proxy"),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Could not build replay client: builder error: invalid proxy"
+        );
+
+        let error = FeroxError::argument("invalid digit found in string");
+        assert_eq!(
+            error.to_string(),
+            "Invalid command line argument: invalid digit found in string"
+        );
+
+        let error = FeroxError::Interrupted {
+            state_file: Some(String::from("ferox-1.state")),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Scan stopped early; state saved to ferox-1.state"
+        );
+    }
+
+    #[test]
+    /// a FeroxError should convert into an anyhow::Error without losing its variant
+    fn ferox_error_converts_to_anyhow() {
+        let error: anyhow::Error = FeroxError::Interrupted { state_file: None }.into();
+
+        assert_eq!(
+            error.downcast_ref::<FeroxError>(),
+            Some(&FeroxError::Interrupted { state_file: None })
+        );
+    }
+}
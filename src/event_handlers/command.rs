@@ -5,8 +5,14 @@ use tokio::sync::oneshot::Sender;
 
 use crate::response::FeroxResponse;
 use crate::{
+    bypass::Bypass,
+    cloud_storage::CloudStorage,
+    graphql::Introspection,
+    grouping::ResponseGroup,
     statistics::{StatError, StatField},
     traits::FeroxFilter,
+    wordlist::Wordlist,
+    wsdl::Wsdl,
 };
 
 /// Protocol definition for updating an event handler via mpsc
@@ -33,11 +39,14 @@ pub enum Command {
     /// Update a `Stats` field that corresponds to the given `StatField` by the given `f64` value
     AddToF64Field(StatField, f64),
 
+    /// Add one to the number of responses suppressed by the `FeroxFilter` with the given name
+    AddFilterHit(&'static str),
+
     /// Save a `Stats` object to disk using `reporter::get_cached_file_handle`
     Save,
 
-    /// Load a `Stats` object from disk
-    LoadStats(String),
+    /// Load a `Stats` object from the given state file, decrypting it with the given password
+    LoadStats(String, String),
 
     /// Add a `FeroxFilter` implementor to `FilterHandler`'s instance of `FeroxFilters`
     AddFilter(Box<dyn FeroxFilter>),
@@ -45,14 +54,56 @@ pub enum Command {
     /// Send a `FeroxResponse` to the output handler for reporting
     Report(Box<FeroxResponse>),
 
+    /// Send a url that would have been requested to the output handler (--dry-run)
+    ReportUrl(String),
+
+    /// Send a directory that wasn't scanned because --max-scans was reached to the output handler
+    ReportSkipped(String),
+
+    /// Send a directory whose scan was abandoned because it looked like a tarpit
+    /// (--tarpit-latency), along with the reason why, to the output handler
+    ReportAbandoned(String, String),
+
+    /// Send a redirect loop found during recursion, as the urls that make it up (the first url
+    /// repeated at the end), to the output handler
+    ReportRedirectLoop(Vec<String>),
+
+    /// Send a url whose response looked like a WAF/CDN block page, along with the name of the WAF
+    /// and the number of responses its scan has seen blocked, to the output handler
+    ReportBlocked(String, String, usize),
+
+    /// Send a request that got past a forbidden resource's access control (--bypass-403) to the
+    /// output handler
+    ReportBypass(Box<Bypass>),
+
+    /// Send a GraphQL endpoint that answered an introspection query with its schema to the output
+    /// handler
+    ReportIntrospection(Box<Introspection>),
+
+    /// Send a cloud storage bucket referenced by a response body, along with whether its contents
+    /// are publicly listable, to the output handler
+    ReportCloudStorage(Box<CloudStorage>),
+
+    /// Send a WSDL document found for a SOAP endpoint, along with its operations, to the output
+    /// handler
+    ReportWsdl(Box<Wsdl>),
+
+    /// Send a group of similar responses, some of which weren't shown on their own
+    /// (--group-similar), to the output handler
+    ReportGroup(Box<ResponseGroup>),
+
     /// Send a group of urls to be scanned (only used for the urls passed in explicitly by the user)
     ScanInitialUrls(Vec<String>),
 
     /// Determine whether or not recursion is appropriate, given a FeroxResponse, if so start a scan
     TryRecursion(Box<FeroxResponse>),
 
+    /// Given a directory and technologies detected beneath it, scan the directory with each
+    /// technology's wordlist (--auto-wordlist)
+    TryAutoWordlist(String, Vec<String>),
+
     /// Send a pointer to the wordlist to the recursion handler
-    UpdateWordlist(Arc<Vec<String>>),
+    UpdateWordlist(Arc<Wordlist>),
 
     /// Instruct the ScanHandler to join on all known scans, use sender to notify main when done
     JoinTasks(Sender<bool>),
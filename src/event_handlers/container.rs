@@ -76,6 +76,24 @@ impl Handles {
         }
     }
 
+    /// Copy of these handles that uses the given Configuration; every handler is shared with
+    /// the original (--targets)
+    pub fn with_config(&self, config: Arc<Configuration>) -> Self {
+        let scans = self
+            .scans
+            .read()
+            .map(|guard| guard.clone())
+            .unwrap_or_default();
+
+        Self {
+            stats: self.stats.clone(),
+            filters: self.filters.clone(),
+            output: self.output.clone(),
+            config,
+            scans: RwLock::new(scans),
+        }
+    }
+
     /// create a Handles object suitable for unit testing (non-functional)
     #[cfg(test)]
     pub fn for_testing(
@@ -84,7 +102,7 @@ impl Handles {
     ) -> (Self, UnboundedReceiver<Command>) {
         let configuration = config.unwrap_or_else(|| Arc::new(Configuration::new().unwrap()));
         let (tx, rx) = mpsc::unbounded_channel::<Command>();
-        let terminal_handle = TermOutHandle::new(tx.clone(), tx.clone());
+        let terminal_handle = TermOutHandle::new(tx.clone(), tx.clone(), Default::default());
         let stats_handle = StatsHandle::new(
             Arc::new(Stats::new(
                 configuration.extensions.len(),
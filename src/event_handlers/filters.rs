@@ -7,7 +7,7 @@ use tokio::sync::{
     oneshot,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// Container for filters transmitter and FeroxFilters object
 pub struct FiltersHandle {
     /// FeroxFilters object used across modules to track active filters
@@ -1,51 +1,227 @@
 use super::*;
 use crate::{
+    database,
+    error::FeroxError,
+    har, output_dir,
     progress::PROGRESS_PRINTER,
-    scan_manager::{FeroxState, PAUSE_SCAN},
-    scanner::RESPONSES,
-    statistics::StatError,
-    utils::{open_file, write_to},
+    scan_manager::{FeroxState, Lifecycle, PAUSE_SCAN},
+    scanner::{current_rate_limit, set_rate_limit, RESPONSES},
+    statistics::{StatError, StatField::TotalExpected},
+    utils::{fmt_err, write_to},
     SLEEP_DURATION,
 };
-use anyhow::Result;
+use anyhow::{Context, Result};
 use console::style;
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+#[cfg(not(target_os = "windows"))]
+use lazy_static::lazy_static;
 use std::{
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+    fs::File,
+    io::BufWriter,
+    sync::{atomic::Ordering, Arc},
     thread::sleep,
     time::Duration,
     time::{SystemTime, UNIX_EPOCH},
 };
 
-/// Atomic boolean flag, used to determine whether or not the terminal input handler should exit
-pub static SCAN_COMPLETE: AtomicBool = AtomicBool::new(false);
+/// What a key pressed during a scan asks for
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum KeyAction {
+    /// ENTER, pause the scan and show the interactive menu
+    Menu,
+
+    /// p, pause (or resume) all scans without showing the menu
+    TogglePause,
+
+    /// c, cancel the most recently discovered directory scan that's still running
+    CancelNewest,
+
+    /// s, save a snapshot of the scan's state without exiting
+    SaveState,
+
+    /// +, raise the requests per second limit
+    RaiseRateLimit,
+
+    /// -, lower the requests per second limit
+    LowerRateLimit,
+
+    /// ctrl+c, only seen as a key press while the terminal is in raw mode
+    Interrupt,
+}
+
+/// Determine what (if anything) the given terminal event asks for
+fn key_action(event: &Event) -> Option<KeyAction> {
+    let (code, modifiers) = match event {
+        Event::Key(KeyEvent { code, modifiers }) => (code, modifiers),
+        _ => return None,
+    };
+
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        return match code {
+            KeyCode::Char('c') => Some(KeyAction::Interrupt),
+            _ => None,
+        };
+    }
+
+    match code {
+        KeyCode::Enter => Some(KeyAction::Menu),
+        KeyCode::Char('p') | KeyCode::Char('P') => Some(KeyAction::TogglePause),
+        KeyCode::Char('c') | KeyCode::Char('C') => Some(KeyAction::CancelNewest),
+        KeyCode::Char('s') | KeyCode::Char('S') => Some(KeyAction::SaveState),
+        KeyCode::Char('+') => Some(KeyAction::RaiseRateLimit),
+        KeyCode::Char('-') => Some(KeyAction::LowerRateLimit),
+        _ => None,
+    }
+}
+
+/// New requests per second limit after pressing + or -; changes by 10% of the current limit (at
+/// least 1), a limit is never lowered below 1. There's nothing to adjust without a limit, None is
+/// returned instead
+fn adjust_rate_limit(current: usize, raise: bool) -> Option<usize> {
+    if current == 0 {
+        return None;
+    }
+
+    let step = (current / 10).max(1);
+
+    if raise {
+        Some(current.saturating_add(step))
+    } else {
+        Some(current.saturating_sub(step).max(1))
+    }
+}
+
+/// Put the terminal into (or take it out of) raw mode; returns whether or not the terminal is now
+/// in raw mode
+///
+/// windows consoles hand key presses to the poller as they happen in raw mode, and deliver
+/// ctrl+c as a key press instead of a console control event; the menu needs the console back in
+/// its normal mode to read a line of input
+#[cfg(target_os = "windows")]
+fn set_raw_mode(enabled: bool) -> bool {
+    let result = if enabled {
+        crossterm::terminal::enable_raw_mode()
+    } else {
+        crossterm::terminal::disable_raw_mode()
+    };
+
+    match result {
+        Ok(_) => enabled,
+        Err(e) => {
+            log::warn!("Could not set raw mode to {}: {}", enabled, e);
+            false
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+lazy_static! {
+    /// terminal settings from before raw mode was entered; restored once raw mode is left
+    static ref ORIGINAL_TERMIOS: std::sync::Mutex<Option<libc::termios>> =
+        std::sync::Mutex::new(None);
+}
+
+/// Put the terminal into (or take it out of) raw mode; returns whether or not the terminal is now
+/// in raw mode
+///
+/// keys are handed to the poller as they're pressed, without being echoed, and ctrl+c arrives as
+/// a key press instead of a signal. unlike crossterm's raw mode, output processing is left alone,
+/// so lines printed while scanning still start at the beginning of the line
+#[cfg(not(target_os = "windows"))]
+fn set_raw_mode(enabled: bool) -> bool {
+    let mut original = match ORIGINAL_TERMIOS.lock() {
+        Ok(guard) => guard,
+        Err(_) => return false,
+    };
+
+    if !enabled {
+        if let Some(termios) = original.take() {
+            // safety: termios came from a successful tcgetattr on the same descriptor
+            if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) } != 0 {
+                log::warn!(
+                    "Could not restore terminal settings: {}",
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+
+        return false;
+    }
+
+    if original.is_some() {
+        // already in raw mode
+        return true;
+    }
+
+    // safety: termios is plain old data, and is only used once tcgetattr has filled it in
+    let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+
+    if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1
+        || unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0
+    {
+        // stdin isn't a terminal (piped targets, etc...); keys only arrive once ENTER is pressed
+        return false;
+    }
+
+    let mut raw = termios;
+    raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+    raw.c_cc[libc::VMIN] = 1;
+    raw.c_cc[libc::VTIME] = 0;
+
+    // safety: raw is a copy of the terminal's own settings
+    if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+        log::warn!(
+            "Could not set raw mode to {}: {}",
+            enabled,
+            std::io::Error::last_os_error()
+        );
+        return false;
+    }
+
+    *original = Some(termios);
+    true
+}
+
+/// Take the terminal out of raw mode, if keyboard shortcuts put it there; safe to call more than
+/// once, and expected to be called before the program exits
+pub fn restore_terminal() {
+    set_raw_mode(false);
+}
+
+/// Called with the error that ends the program when the user interrupts the scan; expected to
+/// exit the process
+pub type InterruptHandler = fn(FeroxError) -> !;
 
 /// Container for filters transmitter and FeroxFilters object
 pub struct TermInputHandler {
     /// handles to other handlers
     handles: Arc<Handles>,
+
+    /// what to do once the user interrupts the scan (ctrl+c), after its state is saved
+    on_interrupt: InterruptHandler,
 }
 
 /// implementation of event handler for terminal input
 ///
 /// kicks off the following handlers related to terminal input:
 ///     ctrl+c handler that saves scan state to disk
-///     enter handler that listens for enter during scans to drop into interactive scan cancel menu
+///     enter handler that listens for enter during scans to drop into interactive scan cancel menu,
+///     along with the other keyboard shortcuts (p, c, s, +, -)
 impl TermInputHandler {
     /// Create new event handler
-    pub fn new(handles: Arc<Handles>) -> Self {
-        Self { handles }
+    pub fn new(handles: Arc<Handles>, on_interrupt: InterruptHandler) -> Self {
+        Self {
+            handles,
+            on_interrupt,
+        }
     }
 
     /// Initialize the sigint and enter handlers that are responsible for handling initial user
     /// interaction during scans
-    pub fn initialize(handles: Arc<Handles>) {
+    pub fn initialize(handles: Arc<Handles>, on_interrupt: InterruptHandler) {
         log::trace!("enter: initialize({:?})", handles);
 
-        let handler = Self::new(handles);
+        let handler = Self::new(handles, on_interrupt);
         handler.start();
 
         log::trace!("exit: initialize");
@@ -53,14 +229,16 @@ impl TermInputHandler {
 
     /// wrapper around sigint_handler and enter_handler
     fn start(&self) {
-        tokio::task::spawn_blocking(Self::enter_handler);
+        let handles = self.handles.clone();
+        let on_interrupt = self.on_interrupt;
+        tokio::task::spawn_blocking(move || Self::enter_handler(handles, on_interrupt));
 
         if self.handles.config.save_state {
             // start the ctrl+c handler
             let cloned = self.handles.clone();
 
             let result = ctrlc::set_handler(move || {
-                let _ = Self::sigint_handler(cloned.clone());
+                on_interrupt(Self::sigint_handler(cloned.clone()));
             });
 
             if result.is_err() {
@@ -73,10 +251,114 @@ impl TermInputHandler {
         }
     }
 
-    /// Writes the current state of the program to disk (if save_state is true) and then exits
-    pub fn sigint_handler(handles: Arc<Handles>) -> Result<()> {
+    /// Writes the current state of the program to disk, returning the error that should end the
+    /// program; exiting is left to the caller
+    pub fn sigint_handler(handles: Arc<Handles>) -> FeroxError {
         log::trace!("enter: sigint_handler({:?})", handles);
 
+        // leave the terminal the way we found it
+        set_raw_mode(false);
+
+        // --group-similar; the summaries would otherwise be lost along with the handler
+        handles.output.print_groups();
+
+        let error = match Self::save_on_interrupt(&handles) {
+            Ok(filename) => FeroxError::Interrupted {
+                state_file: Some(filename),
+            },
+            Err(e) => FeroxError::SaveState {
+                reason: format!("{:#}", e),
+            },
+        };
+
+        log::trace!("exit: sigint_handler -> {}", error);
+        error
+    }
+
+    /// Save the state of an interrupted scan, returning the name(s) of the state file(s)
+    fn save_on_interrupt(handles: &Handles) -> Result<String> {
+        if !Lifecycle::current().can_save() {
+            // saving now would overwrite a meaningful state file with the little that's been
+            // loaded so far; wait for the rest
+            PROGRESS_PRINTER.println(format!(
+                "🚨 Caught {} 🚨 waiting for {} to finish loading ...",
+                style("ctrl+c").yellow(),
+                handles.config.resume_from
+            ));
+
+            Lifecycle::wait_until_saveable();
+        }
+
+        let files = Self::state_files(handles)?;
+        let filenames = Self::filenames(&files);
+        let warning = format!(
+            "🚨 Caught {} 🚨 saving scan state to {} ...",
+            style("ctrl+c").yellow(),
+            filenames
+        );
+
+        PROGRESS_PRINTER.println(warning);
+
+        for (filename, target) in &files {
+            Self::save_state(handles, filename, target.as_deref())?;
+        }
+
+        // leave any --har archive as valid json
+        har::finalize()?;
+
+        // keep the results found so far (--output-db|--output-dsn)
+        database::finalize(handles)?;
+
+        Ok(filenames)
+    }
+
+    /// State file(s) to write, each paired with the --output-dir subdirectory of the target
+    /// whose scans it holds (None when it holds every scan)
+    fn state_files(handles: &Handles) -> Result<Vec<(String, Option<String>)>> {
+        if handles.config.output_dir.is_empty() {
+            return Ok(vec![(Self::state_filename(handles)?, None)]);
+        }
+
+        // --output-dir; each target's state file lives alongside its other results, and only
+        // holds that target's scans, no matter how many targets this run was given (stdin,
+        // --targets, etc...)
+        let mut files: Vec<(String, Option<String>)> = Vec::new();
+
+        for scan in handles.ferox_scans()?.get_scans() {
+            let slug = output_dir::target_slug(scan.url());
+
+            if !files.iter().any(|(_, known)| known.as_ref() == Some(&slug)) {
+                let filename = output_dir::state_file(&handles.config.output_dir, scan.url())?;
+                files.push((filename, Some(slug)));
+            }
+        }
+
+        if files.is_empty() {
+            // nothing's been scanned yet
+            let target = if handles.config.target_url.is_empty() {
+                "stdin"
+            } else {
+                &handles.config.target_url
+            };
+
+            let filename = output_dir::state_file(&handles.config.output_dir, target)?;
+            files.push((filename, None));
+        }
+
+        Ok(files)
+    }
+
+    /// Names of the given state files, for display
+    fn filenames(files: &[(String, Option<String>)]) -> String {
+        files
+            .iter()
+            .map(|(filename, _)| filename.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Name of the state file to write, based on the target and the current time
+    fn state_filename(handles: &Handles) -> Result<String> {
         let ts = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
 
         let slug = if !handles.config.target_url.is_empty() {
@@ -92,38 +374,148 @@ impl TermInputHandler {
             "stdin".to_string()
         };
 
-        let filename = format!("ferox-{}-{}.state", slug, ts);
-        let warning = format!(
-            "🚨 Caught {} 🚨 saving scan state to {} ...",
-            style("ctrl+c").yellow(),
-            filename
-        );
-
-        PROGRESS_PRINTER.println(warning);
+        Ok(format!("ferox-{}-{}.state", slug, ts))
+    }
 
-        let state = FeroxState::new(
+    /// Write the current state of the program to the given file; only the given target's scans
+    /// are written when there is one (--output-dir)
+    fn save_state(handles: &Handles, filename: &str, target: Option<&str>) -> Result<()> {
+        let mut state = FeroxState::new(
             handles.ferox_scans()?,
             handles.config.clone(),
             &RESPONSES,
             handles.stats.data.clone(),
         );
 
-        let state_file = open_file(&filename);
+        if let Some(slug) = target {
+            state = state.for_target(slug);
+        }
+
+        // truncated rather than appended to, --output-dir's state file is saved under the same
+        // name every time
+        let state_file = File::create(filename)
+            .with_context(|| fmt_err(&format!("Could not open {}", filename)))?;
 
-        let mut buffered_file = state_file?;
+        let mut buffered_file = BufWriter::new(state_file);
         write_to(&state, &mut buffered_file, true)?;
 
-        log::trace!("exit: sigint_handler (end of program)");
-        std::process::exit(1);
+        Ok(())
+    }
+
+    /// Carry out the given action (other than showing the menu, or ctrl+c)
+    fn handle_key(handles: &Arc<Handles>, action: KeyAction) -> Result<()> {
+        log::trace!("enter: handle_key({:?})", action);
+
+        let scans = handles.ferox_scans()?;
+
+        match action {
+            KeyAction::TogglePause if scans.paused_remotely() => {
+                scans.resume_remotely();
+                PROGRESS_PRINTER.println(format!("▶️  {} all scans", style("resumed").green()));
+            }
+            KeyAction::TogglePause => {
+                scans.pause_remotely();
+                PROGRESS_PRINTER.println(format!(
+                    "⏸️  {} all scans, press {} to resume",
+                    style("paused").magenta(),
+                    style("p").yellow()
+                ));
+            }
+            KeyAction::CancelNewest => match scans.newest_active_scan() {
+                Some(scan) => {
+                    let num_cancelled = tokio::runtime::Handle::current()
+                        .block_on(scans.cancel_scan_by_id(scan.id()))
+                        .unwrap_or_default();
+
+                    handles
+                        .stats
+                        .send(Command::SubtractFromUsizeField(
+                            TotalExpected,
+                            num_cancelled,
+                        ))
+                        .unwrap_or_default();
+
+                    PROGRESS_PRINTER.println(format!(
+                        "💀 {} {}",
+                        style("cancelled").red(),
+                        scan.url()
+                    ));
+                }
+                None => PROGRESS_PRINTER.println("💀 no running scans that can be cancelled"),
+            },
+            KeyAction::SaveState if !Lifecycle::current().can_save() => {
+                PROGRESS_PRINTER
+                    .println("💾 the previous scan's state is still loading; try again shortly");
+            }
+            KeyAction::SaveState => {
+                let files = Self::state_files(handles)?;
+
+                for (filename, target) in &files {
+                    Self::save_state(handles, filename, target.as_deref())?;
+                }
+
+                PROGRESS_PRINTER.println(format!(
+                    "💾 saved scan state to {}",
+                    Self::filenames(&files)
+                ));
+            }
+            KeyAction::RaiseRateLimit | KeyAction::LowerRateLimit => {
+                let current = current_rate_limit(handles.config.rate_limit);
+                let raise = action == KeyAction::RaiseRateLimit;
+
+                match adjust_rate_limit(current, raise) {
+                    Some(limit) => {
+                        set_rate_limit(limit);
+                        PROGRESS_PRINTER.println(format!(
+                            "🚧 rate limit set to {} requests per second",
+                            style(limit).yellow()
+                        ));
+                    }
+                    None => PROGRESS_PRINTER.println(
+                        "🚧 no rate limit to adjust; start with --rate-limit to use + and -",
+                    ),
+                }
+            }
+            KeyAction::Menu | KeyAction::Interrupt => {}
+        }
+
+        log::trace!("exit: handle_key");
+        Ok(())
     }
 
     /// Handles specific key events triggered by the user over stdin
-    fn enter_handler() {
+    fn enter_handler(handles: Arc<Handles>, on_interrupt: InterruptHandler) {
         // todo eventually move away from atomics, the blocking recv is the problem
         log::trace!("enter: start_enter_handler");
 
+        // whether raw mode was asked for, and whether the terminal actually ended up in it
+        let mut wants_raw_mode = false;
+        let mut raw_mode = false;
+
+        // without raw mode (stdin isn't a terminal), keys only show up once ENTER is pressed; that ENTER shouldn't open the
+        // menu when it was used to submit one of the other keys
+        let mut key_pending = false;
+
         loop {
-            if PAUSE_SCAN.load(Ordering::Relaxed) {
+            if Lifecycle::current() == Lifecycle::Finished {
+                // scan has been marked complete by main, time to exit the loop; checked while the
+                // menu is open too, since a pause that lands after the last request has no menu
+                // left to resume it
+                break;
+            }
+
+            let menu_open = PAUSE_SCAN.load(Ordering::Relaxed)
+                && !handles
+                    .ferox_scans()
+                    .map_or(false, |scans| scans.paused_remotely());
+
+            if wants_raw_mode == menu_open {
+                // raw mode while polling for keys, normal mode while the menu reads from stdin
+                wants_raw_mode = !menu_open;
+                raw_mode = set_raw_mode(wants_raw_mode);
+            }
+
+            if menu_open {
                 // if the scan is already paused, we don't want this event poller fighting the user
                 // over stdin
                 sleep(Duration::from_millis(SLEEP_DURATION));
@@ -131,22 +523,100 @@ impl TermInputHandler {
                 // It's guaranteed that the `read()` won't block when the `poll()`
                 // function returns `true`
 
-                if let Ok(key_pressed) = event::read() {
-                    // ignore any other keys
-                    if key_pressed == Event::Key(KeyCode::Enter.into()) {
+                // ignore any other keys
+                match event::read().ok().as_ref().and_then(key_action) {
+                    Some(KeyAction::Menu) if key_pending && !raw_mode => key_pending = false,
+                    Some(KeyAction::Menu) => {
                         // if the user presses Enter, set PAUSE_SCAN to true. The interactive menu
                         // will be triggered and will handle setting PAUSE_SCAN to false
                         PAUSE_SCAN.store(true, Ordering::Release);
                     }
-                }
-            } else {
-                // Timeout expired and no `Event` is available; use the timeout to check SCAN_COMPLETE
-                if SCAN_COMPLETE.load(Ordering::Relaxed) {
-                    // scan has been marked complete by main, time to exit the loop
-                    break;
+                    Some(KeyAction::Interrupt) => {
+                        // raw mode swallows the signal the ctrl+c handler would otherwise get
+                        let error = if handles.config.save_state {
+                            Self::sigint_handler(handles.clone())
+                        } else {
+                            set_raw_mode(false);
+                            FeroxError::Interrupted { state_file: None }
+                        };
+
+                        on_interrupt(error);
+                    }
+                    Some(action) => {
+                        key_pending = true;
+
+                        if let Err(e) = Self::handle_key(&handles, action) {
+                            log::warn!("Could not handle {:?}: {}", action, e);
+                        }
+                    }
+                    None => {}
                 }
             }
         }
+
+        if raw_mode {
+            set_raw_mode(false);
+        }
+
         log::trace!("exit: start_enter_handler");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// ENTER should open the menu, ctrl+c should interrupt, anything else should be ignored
+    fn key_action_recognizes_enter_and_ctrl_c() {
+        assert_eq!(
+            key_action(&Event::Key(KeyCode::Enter.into())),
+            Some(KeyAction::Menu)
+        );
+        assert_eq!(
+            key_action(&Event::Key(KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL
+            ))),
+            Some(KeyAction::Interrupt)
+        );
+        assert_eq!(
+            key_action(&Event::Key(KeyEvent::new(
+                KeyCode::Char('p'),
+                KeyModifiers::CONTROL
+            ))),
+            None
+        );
+        assert_eq!(key_action(&Event::Key(KeyCode::Char('x').into())), None);
+        assert_eq!(key_action(&Event::Resize(80, 24)), None);
+    }
+
+    #[test]
+    /// p, c, s, +, and - should map to their shortcuts, regardless of shift
+    fn key_action_recognizes_shortcuts() {
+        let key = |c| key_action(&Event::Key(KeyCode::Char(c).into()));
+
+        assert_eq!(key('p'), Some(KeyAction::TogglePause));
+        assert_eq!(key('C'), Some(KeyAction::CancelNewest));
+        assert_eq!(key('s'), Some(KeyAction::SaveState));
+        assert_eq!(
+            key_action(&Event::Key(KeyEvent::new(
+                KeyCode::Char('+'),
+                KeyModifiers::SHIFT
+            ))),
+            Some(KeyAction::RaiseRateLimit)
+        );
+        assert_eq!(key('-'), Some(KeyAction::LowerRateLimit));
+    }
+
+    #[test]
+    /// + and - should change the limit by 10% (at least 1), never going below 1
+    fn adjust_rate_limit_steps_by_ten_percent() {
+        assert_eq!(adjust_rate_limit(0, true), None);
+        assert_eq!(adjust_rate_limit(0, false), None);
+        assert_eq!(adjust_rate_limit(100, true), Some(110));
+        assert_eq!(adjust_rate_limit(100, false), Some(90));
+        assert_eq!(adjust_rate_limit(5, true), Some(6));
+        assert_eq!(adjust_rate_limit(1, false), Some(1));
+    }
+}
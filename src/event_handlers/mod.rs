@@ -10,7 +10,7 @@ mod inputs;
 pub use self::command::Command;
 pub use self::container::{Handles, Tasks};
 pub use self::filters::{FiltersHandle, FiltersHandler};
-pub use self::inputs::{TermInputHandler, SCAN_COMPLETE};
+pub use self::inputs::{restore_terminal, InterruptHandler, TermInputHandler};
 pub use self::outputs::{TermOutHandle, TermOutHandler};
 pub use self::scans::{ScanHandle, ScanHandler};
 pub use self::statistics::{StatsHandle, StatsHandler};
@@ -1,22 +1,108 @@
-use super::Command::AddToUsizeField;
 use super::*;
 
 use anyhow::{Context, Result};
+use console::style;
+use reqwest::{Method, StatusCode};
 use tokio::sync::{mpsc, oneshot};
 
 use crate::{
     config::Configuration,
+    events::{ScanEvent, EVENTS},
+    findings::{builtin_rules, read_rules},
+    fingerprint::add_auto_extensions,
+    grouping::{ResponseGroup, ResponseGroups},
+    known_files::{read_db, KnownFiles},
+    notify::Notifier,
+    on_found::OnFound,
+    openapi,
+    output_dir::OutputDir,
     progress::PROGRESS_PRINTER,
     scanner::RESPONSES,
     send_command, skip_fail,
-    statistics::StatField::ResourcesDiscovered,
     traits::FeroxSerialize,
     utils::{ferox_print, fmt_err, make_request, open_file, write_to},
     CommandReceiver, CommandSender, Joiner,
 };
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{create_dir_all, read_to_string, File},
+    io::{BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
-#[derive(Debug)]
+/// shown in front of directories that weren't scanned because --max-scans was reached
+const SKIPPED_MESSAGE: &str = "not scanned (limit reached)";
+
+/// shown in front of directories whose scans were abandoned because of --tarpit-latency
+const ABANDONED_MESSAGE: &str = "abandoned (tarpit)";
+
+/// shown in front of urls that are part of a redirect loop, and weren't scanned recursively
+const REDIRECT_LOOP_MESSAGE: &str = "not scanned (redirect loop)";
+
+/// shown in front of urls whose responses looked like a WAF/CDN block page
+const BLOCKED_MESSAGE: &str = "blocked (waf)";
+
+/// shown in front of forbidden urls whose access control was bypassed (--bypass-403)
+const BYPASS_MESSAGE: &str = "bypassed";
+
+/// shown in front of GraphQL endpoints that allow introspection
+const INTROSPECTION_MESSAGE: &str = "introspection (graphql)";
+
+/// shown in front of WSDL documents found for SOAP endpoints
+const WSDL_MESSAGE: &str = "wsdl (soap)";
+
+/// shown in front of cloud storage buckets referenced by response bodies
+const CLOUD_STORAGE_MESSAGE: &str = "bucket (cloud storage)";
+
+/// Name of the file (within --split-output's directory) to which a response with the given status
+/// code is written
+fn split_output_name(status: &StatusCode) -> &'static str {
+    match status.as_u16() {
+        200..=299 => "200s.txt",
+        300..=399 => "30x.txt",
+        401..=403 => "401-403.txt",
+        400..=499 => "40x.txt",
+        500..=599 => "50x.txt",
+        _ => "other.txt",
+    }
+}
+
+/// Read the urls already reported in the given output file, i.e. from a prior run against the same
+/// target; both plain and --json output are understood
+///
+/// a missing or unreadable file simply means nothing was reported previously
+fn read_reported_urls(path: &str) -> HashSet<String> {
+    let contents = match read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return HashSet::new(),
+    };
+
+    contents
+        .lines()
+        .filter_map(|line| {
+            if line.starts_with('{') {
+                // --json; only response entries are of interest
+                let entry: serde_json::Value = serde_json::from_str(line).ok()?;
+
+                if entry["type"] != "response" || entry["wildcard"] == true {
+                    return None;
+                }
+
+                entry["url"].as_str().map(String::from)
+            } else if line.starts_with("WLD") {
+                // wildcard messages are never deduplicated
+                None
+            } else {
+                line.split_whitespace()
+                    .find(|token| token.starts_with("http://") || token.starts_with("https://"))
+                    .map(String::from)
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
 /// Container for terminal output transmitter
 pub struct TermOutHandle {
     /// Transmitter that sends to the TermOutHandler handler
@@ -24,13 +110,34 @@ pub struct TermOutHandle {
 
     /// Transmitter that sends to the FileOutHandler handler
     pub tx_file: CommandSender,
+
+    /// responses grouped by --group-similar, shared with the TermOutHandler handler
+    groups: Arc<Mutex<ResponseGroups>>,
 }
 
 /// implementation of OutputHandle
 impl TermOutHandle {
     /// Given a CommandSender, create a new OutputHandle
-    pub fn new(tx: CommandSender, tx_file: CommandSender) -> Self {
-        Self { tx, tx_file }
+    pub fn new(
+        tx: CommandSender,
+        tx_file: CommandSender,
+        groups: Arc<Mutex<ResponseGroups>>,
+    ) -> Self {
+        Self {
+            tx,
+            tx_file,
+            groups,
+        }
+    }
+
+    /// Print the summary of each group with collapsed responses (--group-similar); used when the
+    /// scan is interrupted, since the handler only prints them when it exits
+    pub fn print_groups(&self) {
+        if let Ok(groups) = self.groups.lock() {
+            for group in groups.collapsed() {
+                ferox_print(&group.as_str(), &PROGRESS_PRINTER);
+            }
+        }
     }
 
     /// Send the given Command over `tx`
@@ -81,14 +188,156 @@ impl FileOutHandler {
     async fn start(&mut self, tx_stats: CommandSender) -> Result<()> {
         log::trace!("enter: start_file_handler({:?})", tx_stats);
 
-        let mut file = open_file(&self.config.output)?;
+        // urls reported to the same file by a previous run aren't written again
+        let mut reported = read_reported_urls(&self.config.output);
 
-        log::info!("Writing scan results to {}", self.config.output);
+        if !reported.is_empty() {
+            log::info!(
+                "Found {} previously reported urls in {}; only new results will be appended",
+                reported.len(),
+                self.config.output
+            );
+        }
+
+        // -o; a single file with every target's results
+        let mut file = if self.config.output.is_empty() {
+            None
+        } else {
+            log::info!("Writing scan results to {}", self.config.output);
+            Some(open_file(&self.config.output)?)
+        };
+
+        // --output-dir; results, bodies, etc... split up by target
+        let mut output_dir = if self.config.output_dir.is_empty() {
+            None
+        } else {
+            log::info!("Writing scan results under {}", self.config.output_dir);
+            Some(OutputDir::new(&self.config.output_dir))
+        };
 
         while let Some(command) = self.receiver.recv().await {
             match command {
                 Command::Report(response) => {
-                    skip_fail!(write_to(&*response, &mut file, self.config.json));
+                    if !response.wildcard() && !reported.insert(response.url().to_string()) {
+                        log::debug!("{} was previously reported, skipping", response.url());
+                        continue;
+                    }
+
+                    if let Some(file) = file.as_mut() {
+                        skip_fail!(write_to(&*response, file, self.config.json));
+                    }
+
+                    if let Some(output_dir) = output_dir.as_mut() {
+                        skip_fail!(output_dir.report(&response));
+                    }
+                }
+                Command::ReportUrl(url) => {
+                    let json = serde_json::json!({ "type": "dry_run", "url": url }).to_string();
+                    self.write_line(&mut file, &mut output_dir, &url, &url, &json);
+                }
+                Command::ReportSkipped(url) => {
+                    let json =
+                        serde_json::json!({ "type": "skipped", "url": url, "reason": "limit reached" })
+                            .to_string();
+                    let text = format!("{} {}", SKIPPED_MESSAGE, url);
+                    self.write_line(&mut file, &mut output_dir, &url, &text, &json);
+                }
+                Command::ReportAbandoned(url, reason) => {
+                    let json =
+                        serde_json::json!({ "type": "abandoned", "url": url, "reason": reason })
+                            .to_string();
+                    let text = format!("{} {} ({})", ABANDONED_MESSAGE, url, reason);
+                    self.write_line(&mut file, &mut output_dir, &url, &text, &json);
+                }
+                Command::ReportRedirectLoop(cycle) => {
+                    let url = cycle.first().cloned().unwrap_or_default();
+                    let json =
+                        serde_json::json!({ "type": "redirect_loop", "url": url, "cycle": cycle })
+                            .to_string();
+                    let text =
+                        format!("{} {} ({})", REDIRECT_LOOP_MESSAGE, url, cycle.join(" -> "));
+                    self.write_line(&mut file, &mut output_dir, &url, &text, &json);
+                }
+                Command::ReportBlocked(url, waf, blocked) => {
+                    let json = serde_json::json!({
+                        "type": "blocked",
+                        "url": url,
+                        "waf": waf,
+                        "blocked": blocked
+                    })
+                    .to_string();
+                    let text = format!(
+                        "{} {} ({}; {} blocked in this scan)",
+                        BLOCKED_MESSAGE, url, waf, blocked
+                    );
+                    self.write_line(&mut file, &mut output_dir, &url, &text, &json);
+                }
+                Command::ReportBypass(bypass) => {
+                    let json = serde_json::json!({
+                        "type": "bypass",
+                        "url": bypass.url,
+                        "technique": bypass.technique,
+                        "request": bypass.request,
+                        "original_status": bypass.original_status,
+                        "status": bypass.status,
+                        "content_length": bypass.content_length
+                    })
+                    .to_string();
+                    let text = format!("{} {}", BYPASS_MESSAGE, bypass);
+                    self.write_line(&mut file, &mut output_dir, &bypass.url, &text, &json);
+                }
+                Command::ReportIntrospection(introspection) => {
+                    let finding = introspection.finding();
+                    let json = serde_json::json!({
+                        "type": "graphql",
+                        "url": introspection.url,
+                        "severity": finding.severity.as_str(),
+                        "finding": finding.name,
+                        "types": introspection.types,
+                        "schema_file": introspection.schema_file
+                    })
+                    .to_string();
+                    let text = format!(
+                        "{} {} [!{}: {}]",
+                        INTROSPECTION_MESSAGE, introspection, finding.severity, finding.name
+                    );
+                    self.write_line(&mut file, &mut output_dir, &introspection.url, &text, &json);
+                }
+                Command::ReportWsdl(wsdl) => {
+                    let json = serde_json::json!({
+                        "type": "wsdl",
+                        "url": wsdl.url,
+                        "service": wsdl.service,
+                        "operations": wsdl.operations
+                    })
+                    .to_string();
+                    let text = format!("{} {}", WSDL_MESSAGE, wsdl);
+                    self.write_line(&mut file, &mut output_dir, &wsdl.url, &text, &json);
+                }
+                Command::ReportCloudStorage(storage) => {
+                    let json = serde_json::json!({
+                        "type": "cloud_storage",
+                        "url": storage.bucket.listing_url(),
+                        "provider": storage.bucket.provider.as_str(),
+                        "bucket": storage.bucket.name,
+                        "found_on": storage.found_on,
+                        "status": storage.status,
+                        "listable": storage.listable
+                    })
+                    .to_string();
+                    let text = format!("{} {}", CLOUD_STORAGE_MESSAGE, storage);
+                    // grouped with the target whose body referenced the bucket
+                    let url = storage.found_on.clone();
+                    self.write_line(&mut file, &mut output_dir, &url, &text, &json);
+                }
+                Command::ReportGroup(group) => {
+                    let text = console::strip_ansi_codes(&group.as_str())
+                        .trim_end()
+                        .to_string();
+                    let json = skip_fail!(group.as_json());
+                    // grouped with the target of the group's first response
+                    let url = group.urls.first().cloned().unwrap_or_default();
+                    self.write_line(&mut file, &mut output_dir, &url, &text, json.trim_end());
                 }
                 Command::Exit => {
                     break;
@@ -100,6 +349,12 @@ impl FileOutHandler {
             }
         }
 
+        if file.is_none() {
+            // --output-dir without -o; statistics are only ever saved alongside -o's results
+            log::trace!("exit: start_file_handler");
+            return Ok(());
+        }
+
         // close the file before we tell statistics to save current data to the same file
         drop(file);
 
@@ -108,6 +363,31 @@ impl FileOutHandler {
         log::trace!("exit: start_file_handler");
         Ok(())
     }
+
+    /// Write a message about the given url to -o's file (as text or json, depending on --json)
+    /// and/or its target's --output-dir results (as both)
+    fn write_line(
+        &self,
+        file: &mut Option<BufWriter<File>>,
+        output_dir: &mut Option<OutputDir>,
+        url: &str,
+        text: &str,
+        json: &str,
+    ) {
+        if let Some(file) = file.as_mut() {
+            let line = if self.config.json { json } else { text };
+
+            if let Err(e) = writeln!(file, "{}", line) {
+                log::warn!("{}", fmt_err(&format!("{}; skipping...", e)));
+            }
+        }
+
+        if let Some(output_dir) = output_dir.as_mut() {
+            if let Err(e) = output_dir.write_line(url, text, json) {
+                log::warn!("{}", fmt_err(&format!("{}; skipping...", e)));
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -124,6 +404,9 @@ pub struct TermOutHandler {
 
     /// pointer to "global" configuration struct
     config: Arc<Configuration>,
+
+    /// responses grouped by --group-similar
+    groups: Arc<Mutex<ResponseGroups>>,
 }
 
 /// implementation of TermOutHandler
@@ -135,12 +418,14 @@ impl TermOutHandler {
         tx_file: CommandSender,
         file_task: Option<Joiner>,
         config: Arc<Configuration>,
+        groups: Arc<Mutex<ResponseGroups>>,
     ) -> Self {
         Self {
             receiver,
             tx_file,
             file_task,
             config,
+            groups,
         }
     }
 
@@ -158,8 +443,8 @@ impl TermOutHandler {
 
         let tx_stats_clone = tx_stats.clone();
 
-        let file_task = if !config.output.is_empty() {
-            // -o used, need to spawn the thread for writing to disk
+        let file_task = if !config.output.is_empty() || !config.output_dir.is_empty() {
+            // -o or --output-dir used, need to spawn the thread for writing to disk
             Some(tokio::spawn(async move {
                 file_handler.start(tx_stats_clone).await
             }))
@@ -167,10 +452,18 @@ impl TermOutHandler {
             None
         };
 
-        let mut term_handler = Self::new(rx_term, tx_file.clone(), file_task, config);
+        // --group-similar; --silent output is meant to be piped elsewhere, nothing's collapsed
+        let groups = Arc::new(Mutex::new(if config.silent {
+            ResponseGroups::default()
+        } else {
+            ResponseGroups::new(config.group_similar, config.output_level)
+        }));
+
+        let mut term_handler =
+            Self::new(rx_term, tx_file.clone(), file_task, config, groups.clone());
         let term_task = tokio::spawn(async move { term_handler.start(tx_stats).await });
 
-        let event_handle = TermOutHandle::new(tx_term, tx_file);
+        let event_handle = TermOutHandle::new(tx_term, tx_file, groups);
 
         log::trace!("exit: initialize -> ({:?}, {:?})", term_task, event_handle);
 
@@ -183,22 +476,182 @@ impl TermOutHandler {
     async fn start(&mut self, tx_stats: CommandSender) -> Result<()> {
         log::trace!("enter: start({:?})", tx_stats);
 
+        // --urls-file; only urls are written, each url at most once
+        let mut urls_file = if self.config.urls_file.is_empty() {
+            None
+        } else {
+            Some(open_file(&self.config.urls_file)?)
+        };
+        let mut urls_written = HashSet::new();
+
+        // --split-output; files are opened as the first response of each status class is seen
+        if !self.config.split_output.is_empty() {
+            create_dir_all(&self.config.split_output).with_context(|| {
+                fmt_err(&format!("Could not create {}", self.config.split_output))
+            })?;
+        }
+        let mut split_files = HashMap::new();
+
+        // interesting files are flagged using the built-in rules, plus any --interesting-rules
+        let rules = if self.config.interesting_rules.is_empty() {
+            builtin_rules()
+        } else {
+            read_rules(&self.config.interesting_rules).unwrap_or_else(|e| {
+                log::warn!("Could not read --interesting-rules: {}", e);
+                builtin_rules()
+            })
+        };
+
+        // --fingerprint-db; an unreadable database is only a warning, like --interesting-rules
+        let known_files = if self.config.fingerprint_db.is_empty() {
+            KnownFiles::default()
+        } else {
+            read_db(&self.config.fingerprint_db).unwrap_or_else(|e| {
+                log::warn!("Could not read --fingerprint-db: {}", e);
+                KnownFiles::default()
+            })
+        };
+
+        // subscribers that only hear about responses while this handler is running
+        let mut subscriptions = Vec::new();
+
+        // --on-found; commands run in the background, at most --on-found-limit at a time
+        let on_found = if self.config.on_found.is_empty() {
+            None
+        } else {
+            let on_found = Arc::new(OnFound::new(
+                &self.config.on_found,
+                self.config.on_found_limit,
+            ));
+
+            subscriptions.push(EVENTS.subscribe(on_found.clone()));
+            Some(on_found)
+        };
+
+        // --notify-webhook; discoveries are sent to the webhook in the background as they're
+        // reported
+        let notifier = if self.config.notify_webhook.is_empty() {
+            None
+        } else {
+            let notifier = Arc::new(Notifier::new(self.config.clone()));
+
+            subscriptions.push(EVENTS.subscribe(notifier.clone()));
+            Some(notifier)
+        };
+
         while let Some(command) = self.receiver.recv().await {
             match command {
                 Command::Report(mut resp) => {
-                    let contains_sentry =
-                        self.config.status_codes.contains(&resp.status().as_u16());
+                    // --extension-status rules take precedence over --status-codes
+                    let contains_sentry = self
+                        .config
+                        .status_codes_for(resp.extension())
+                        .contains(&resp.status().as_u16());
                     let unknown_sentry = !RESPONSES.contains(&resp); // !contains == unknown
                     let should_process_response = contains_sentry && unknown_sentry;
 
                     if should_process_response {
-                        // print to stdout
-                        ferox_print(&resp.as_str(), &PROGRESS_PRINTER);
+                        // flagged before being printed, so the finding is highlighted
+                        resp.flag_findings(&rules);
+
+                        if self.config.api {
+                            // --api; resources an OpenAPI/Swagger spec should, but doesn't, list
+                            resp.set_undocumented(openapi::is_undocumented(resp.url()));
+                        }
 
-                        send_command!(tx_stats, AddToUsizeField(ResourcesDiscovered, 1));
+                        if self.config.header_audit {
+                            // --header-audit; rated from the headers already at hand
+                            resp.audit_headers();
+                        }
+
+                        // --hashes; shown alongside the url, json output always has them
+                        resp.show_hashes(&self.config.hashes);
+
+                        // --show-preview; taken here, as the body is dropped once reported
+                        resp.set_preview(self.config.show_preview);
+
+                        if !known_files.is_empty() {
+                            // matched by hash, so the exact version of a known file is shown
+                            resp.identify(&known_files);
+                        }
+
+                        if self.config.fingerprint {
+                            // tag the response with any detected technologies before it's printed
+                            resp.fingerprint();
+
+                            if self.config.auto_extensions {
+                                let added = add_auto_extensions(
+                                    resp.technologies(),
+                                    &self.config.extensions,
+                                );
+
+                                if !added.is_empty() {
+                                    log::info!(
+                                        "Detected {:?}; adding extension(s) {:?} to new scans",
+                                        resp.technologies(),
+                                        added
+                                    );
+                                }
+                            }
+                        }
+
+                        // --group-similar; collapsed responses are only shown in their group's
+                        // summary, at the end of the scan
+                        let shown = self
+                            .groups
+                            .lock()
+                            .map_or(true, |mut groups| groups.add(&resp));
+
+                        if shown {
+                            // print to stdout
+                            ferox_print(&resp.as_str(), &PROGRESS_PRINTER);
+                        }
+
+                        if let Some(file) = urls_file.as_mut() {
+                            // wildcards are noise as far as other tools are concerned
+                            if !resp.wildcard() && urls_written.insert(resp.url().to_string()) {
+                                // flushed after each url so the file can be consumed while the
+                                // scan is still running
+                                if let Err(e) =
+                                    writeln!(file, "{}", resp.url()).and_then(|_| file.flush())
+                                {
+                                    log::warn!(
+                                        "Could not write to {}: {}",
+                                        self.config.urls_file,
+                                        e
+                                    );
+                                }
+                            }
+                        }
+
+                        if !self.config.split_output.is_empty() {
+                            let name = split_output_name(resp.status());
+
+                            if !split_files.contains_key(name) {
+                                let path = Path::new(&self.config.split_output).join(name);
+                                match open_file(&path.to_string_lossy()) {
+                                    Ok(file) => {
+                                        split_files.insert(name, file);
+                                    }
+                                    Err(e) => log::warn!("{}", e),
+                                }
+                            }
+
+                            if let Some(file) = split_files.get_mut(name) {
+                                if let Err(e) = write_to(&*resp, file, self.config.json) {
+                                    log::warn!("Could not write {} to {}: {}", resp, name, e);
+                                }
+                            }
+                        }
+
+                        // let everything subscribed to the run's events know about the
+                        // discovery (--api-addr, --grpc, --output-db, --notify-webhook,
+                        // --on-found, --plugin, etc...)
+                        EVENTS.publish(ScanEvent::ResponseReported(&resp));
 
                         if self.file_task.is_some() {
-                            // -o used, need to send the report to be written out to disk
+                            // -o used, need to send the report to be written out to disk; collapsed
+                            // responses too, so that they aren't lost if the scan is interrupted
                             self.tx_file
                                 .send(Command::Report(resp.clone()))
                                 .with_context(|| {
@@ -213,6 +666,9 @@ impl TermOutHandler {
                         // should be replayed; not using logged_request due to replay proxy client
                         make_request(
                             self.config.replay_client.as_ref().unwrap(),
+                            // the replay proxy presents certificates of its own
+                            None,
+                            Method::GET,
                             &resp.url(),
                             self.config.output_level,
                             tx_stats.clone(),
@@ -233,10 +689,193 @@ impl TermOutHandler {
                         RESPONSES.insert(*resp);
                     }
                 }
+                Command::ReportUrl(url) => {
+                    // --dry-run; url would have been requested
+                    ferox_print(&url, &PROGRESS_PRINTER);
+
+                    if self.file_task.is_some() {
+                        // -o used, need to send the url to be written out to disk
+                        self.tx_file
+                            .send(Command::ReportUrl(url.clone()))
+                            .with_context(|| {
+                                fmt_err(&format!("Could not send {} to file handler", url))
+                            })?;
+                    }
+                }
+                Command::ReportSkipped(url) => {
+                    // --max-scans reached; url was reported, but won't be scanned
+                    let message = format!("{} {}", style(SKIPPED_MESSAGE).dim(), url);
+                    ferox_print(&message, &PROGRESS_PRINTER);
+
+                    if self.file_task.is_some() {
+                        // -o used, need to send the url to be written out to disk
+                        self.tx_file
+                            .send(Command::ReportSkipped(url.clone()))
+                            .with_context(|| {
+                                fmt_err(&format!("Could not send {} to file handler", url))
+                            })?;
+                    }
+                }
+                Command::ReportAbandoned(url, reason) => {
+                    // --tarpit-latency; directory was too slow, the rest of its scan was dropped
+                    let message =
+                        format!("{} {} ({})", style(ABANDONED_MESSAGE).red(), url, reason);
+                    ferox_print(&message, &PROGRESS_PRINTER);
+
+                    if self.file_task.is_some() {
+                        // -o used, need to send the url to be written out to disk
+                        self.tx_file
+                            .send(Command::ReportAbandoned(url.clone(), reason))
+                            .with_context(|| {
+                                fmt_err(&format!("Could not send {} to file handler", url))
+                            })?;
+                    }
+                }
+                Command::ReportRedirectLoop(cycle) => {
+                    // recursion found urls that only ever redirect to each other; none of them
+                    // are scanned
+                    let url = cycle.first().cloned().unwrap_or_default();
+                    let message = format!(
+                        "{} {} ({})",
+                        style(REDIRECT_LOOP_MESSAGE).dim(),
+                        url,
+                        cycle.join(" -> ")
+                    );
+                    ferox_print(&message, &PROGRESS_PRINTER);
+
+                    if self.file_task.is_some() {
+                        // -o used, need to send the loop to be written out to disk
+                        self.tx_file
+                            .send(Command::ReportRedirectLoop(cycle))
+                            .with_context(|| {
+                                fmt_err(&format!("Could not send {} to file handler", url))
+                            })?;
+                    }
+                }
+                Command::ReportBlocked(url, waf, blocked) => {
+                    // response looked like a WAF/CDN block page; results for the scan are suspect
+                    let message = format!(
+                        "{} {} ({}; {} blocked in this scan)",
+                        style(BLOCKED_MESSAGE).yellow(),
+                        url,
+                        waf,
+                        blocked
+                    );
+                    ferox_print(&message, &PROGRESS_PRINTER);
+
+                    if self.file_task.is_some() {
+                        // -o used, need to send the url to be written out to disk
+                        self.tx_file
+                            .send(Command::ReportBlocked(url.clone(), waf, blocked))
+                            .with_context(|| {
+                                fmt_err(&format!("Could not send {} to file handler", url))
+                            })?;
+                    }
+                }
+                Command::ReportBypass(bypass) => {
+                    // access control of a forbidden url was bypassed (--bypass-403)
+                    let message = format!("{} {}", style(BYPASS_MESSAGE).green(), bypass);
+                    ferox_print(&message, &PROGRESS_PRINTER);
+
+                    if self.file_task.is_some() {
+                        // -o used, need to send the bypass to be written out to disk
+                        let url = bypass.url.clone();
+                        self.tx_file
+                            .send(Command::ReportBypass(bypass))
+                            .with_context(|| {
+                                fmt_err(&format!("Could not send {} to file handler", url))
+                            })?;
+                    }
+                }
+                Command::ReportIntrospection(introspection) => {
+                    // GraphQL endpoint gave away its schema
+                    let message = format!(
+                        "{} {} {}",
+                        style(INTROSPECTION_MESSAGE).red(),
+                        introspection,
+                        introspection.finding().tag()
+                    );
+                    ferox_print(&message, &PROGRESS_PRINTER);
+
+                    if self.file_task.is_some() {
+                        // -o used, need to send the endpoint to be written out to disk
+                        let url = introspection.url.clone();
+                        self.tx_file
+                            .send(Command::ReportIntrospection(introspection))
+                            .with_context(|| {
+                                fmt_err(&format!("Could not send {} to file handler", url))
+                            })?;
+                    }
+                }
+                Command::ReportWsdl(wsdl) => {
+                    // SOAP endpoint described its operations
+                    let message = format!("{} {}", style(WSDL_MESSAGE).cyan(), wsdl);
+                    ferox_print(&message, &PROGRESS_PRINTER);
+
+                    if self.file_task.is_some() {
+                        // -o used, need to send the wsdl to be written out to disk
+                        let url = wsdl.url.clone();
+                        self.tx_file
+                            .send(Command::ReportWsdl(wsdl))
+                            .with_context(|| {
+                                fmt_err(&format!("Could not send {} to file handler", url))
+                            })?;
+                    }
+                }
+                Command::ReportCloudStorage(storage) => {
+                    // bucket referenced by a body; publicly listable ones stand out
+                    let message = if storage.listable {
+                        format!("{} {}", style(CLOUD_STORAGE_MESSAGE).red(), storage)
+                    } else {
+                        format!("{} {}", style(CLOUD_STORAGE_MESSAGE).yellow(), storage)
+                    };
+                    ferox_print(&message, &PROGRESS_PRINTER);
+
+                    if self.file_task.is_some() {
+                        // -o used, need to send the bucket to be written out to disk
+                        let url = storage.bucket.listing_url();
+                        self.tx_file
+                            .send(Command::ReportCloudStorage(storage))
+                            .with_context(|| {
+                                fmt_err(&format!("Could not send {} to file handler", url))
+                            })?;
+                    }
+                }
                 Command::Sync(sender) => {
                     sender.send(true).unwrap_or_default();
                 }
                 Command::Exit => {
+                    let collapsed: Vec<ResponseGroup> = match self.groups.lock() {
+                        Ok(groups) => groups.collapsed().cloned().collect(),
+                        Err(_) => Vec::new(),
+                    };
+
+                    for group in collapsed {
+                        ferox_print(&group.as_str(), &PROGRESS_PRINTER);
+
+                        if self.file_task.is_some() {
+                            // -o used, need to send the group to be written out to disk
+                            self.tx_file
+                                .send(Command::ReportGroup(Box::new(group)))
+                                .with_context(|| {
+                                    fmt_err("Could not send response group to file handler")
+                                })?;
+                        }
+                    }
+
+                    // nothing else is reported once the handler exits
+                    subscriptions.clear();
+
+                    if let Some(on_found) = &on_found {
+                        // commands still running would otherwise be cut off as the program exits
+                        on_found.wait().await;
+                    }
+
+                    if let Some(notifier) = &notifier {
+                        // same goes for notifications that haven't been sent yet
+                        notifier.wait().await;
+                    }
+
                     if self.file_task.is_some() && self.tx_file.send(Command::Exit).is_ok() {
                         self.file_task.as_mut().unwrap().await??; // wait for death
                     }
@@ -254,6 +893,52 @@ impl TermOutHandler {
 mod tests {
     use super::*;
 
+    #[test]
+    /// each status code should map to the expected --split-output file
+    fn split_output_name_groups_status_codes() {
+        let name = |code| split_output_name(&StatusCode::from_u16(code).unwrap());
+
+        assert_eq!(name(200), "200s.txt");
+        assert_eq!(name(204), "200s.txt");
+        assert_eq!(name(301), "30x.txt");
+        assert_eq!(name(400), "40x.txt");
+        assert_eq!(name(401), "401-403.txt");
+        assert_eq!(name(403), "401-403.txt");
+        assert_eq!(name(405), "40x.txt");
+        assert_eq!(name(500), "50x.txt");
+        assert_eq!(name(101), "other.txt");
+    }
+
+    #[test]
+    /// urls should be read from both plain and json output, ignoring wildcards and statistics
+    fn read_reported_urls_parses_prior_output() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            concat!(
+                "200      GET       10l       16w      173c http://localhost/css\n",
+                "301      GET        7l       11w      169c http://localhost/js => http://localhost/js/\n",
+                "WLD       10l       16w      173c Got 200 for http://localhost/abc (url length: 3)\n",
+                r#"{"type":"response","url":"http://localhost/img","wildcard":false}"#,
+                "\n",
+                r#"{"type":"response","url":"http://localhost/wild","wildcard":true}"#,
+                "\n",
+                r#"{"type":"statistics","requests":42}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let urls = read_reported_urls(tmp.path().to_str().unwrap());
+
+        assert_eq!(urls.len(), 3);
+        assert!(urls.contains("http://localhost/css"));
+        assert!(urls.contains("http://localhost/js"));
+        assert!(urls.contains("http://localhost/img"));
+
+        assert!(read_reported_urls("/nonexistent/output").is_empty());
+    }
+
     #[test]
     /// try to hit struct field coverage of FileOutHandler
     fn struct_fields_of_file_out_handler() {
@@ -278,6 +963,7 @@ mod tests {
             file_task: None,
             receiver: rx,
             tx_file,
+            groups: Default::default(),
         };
 
         println!("{:?}", toh);
@@ -1,14 +1,26 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use anyhow::{bail, Result};
-use tokio::sync::{mpsc, Semaphore};
+use regex::Regex;
+use tokio::sync::mpsc;
 
 use crate::response::FeroxResponse;
 use crate::url::FeroxUrl;
 use crate::{
-    scan_manager::{FeroxScan, FeroxScans, ScanOrder},
-    scanner::FeroxScanner,
-    statistics::StatField::TotalScans,
+    events::{ScanEvent, EVENTS},
+    fingerprint::read_auto_wordlists,
+    progress::ScanBars,
+    scan_manager::{FeroxScan, FeroxScans, ScanContext, ScanOrder},
+    scanner::{
+        read_priority_keywords, FeroxScanner, ScanLimiter, ScanStrategy, DEFAULT_KEYWORD_WEIGHTS,
+    },
+    statistics::StatField::{TotalExpected, TotalScans},
+    utils::get_unique_words_from_wordlist,
+    wordlist::Wordlist,
+    wordlist_rules::{self, read_wordlist_rules, WordlistRule},
     CommandReceiver, CommandSender, FeroxChannel, Joiner, SLEEP_DURATION,
 };
 
@@ -16,7 +28,7 @@ use super::command::Command::AddToUsizeField;
 use super::*;
 use tokio::time::Duration;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// Container for recursion transmitter and FeroxScans object
 pub struct ScanHandle {
     /// FeroxScans object used across modules to track scans
@@ -53,7 +65,7 @@ pub struct ScanHandler {
     receiver: CommandReceiver,
 
     /// wordlist (re)used for each scan
-    wordlist: std::sync::Mutex<Option<Arc<Vec<String>>>>,
+    wordlist: std::sync::Mutex<Option<Arc<Wordlist>>>,
 
     /// group of scans that need to be joined
     tasks: Vec<Arc<FeroxScan>>,
@@ -64,8 +76,30 @@ pub struct ScanHandler {
     /// depths associated with the initial targets provided by the user
     depths: Vec<(String, usize)>,
 
-    /// Bounded semaphore used as a barrier to limit concurrent scans
-    limiter: Arc<Semaphore>,
+    /// barrier used to limit concurrent scans, and to order those waiting to start (--strategy)
+    limiter: Arc<ScanLimiter>,
+
+    /// technology -> wordlist path, read from the file passed via --auto-wordlist
+    auto_wordlists: HashMap<String, String>,
+
+    /// technology-specific wordlists that have already been read from disk
+    tech_wordlists: HashMap<String, Arc<Wordlist>>,
+
+    /// (directory, technology) pairs that have already been scanned with a technology's wordlist
+    auto_scanned: HashSet<(String, String)>,
+
+    /// rules read from the file passed via --wordlist-rules, checked in order
+    wordlist_rules: Vec<WordlistRule>,
+
+    /// wordlists of --wordlist-rules that have already been read from disk, keyed by path; None
+    /// when one couldn't be read (or was empty), so that it's only reported once
+    rule_wordlists: HashMap<String, Option<Arc<Wordlist>>>,
+
+    /// compiled --dont-recurse-regex expressions; matching directories aren't scanned recursively
+    dont_recurse: Vec<Regex>,
+
+    /// handles that use a --targets entry's Configuration, keyed by the entry's url
+    target_handles: HashMap<String, Arc<Handles>>,
 }
 
 /// implementation of event handler for filters
@@ -77,20 +111,49 @@ impl ScanHandler {
         max_depth: usize,
         receiver: CommandReceiver,
     ) -> Self {
-        let limit = handles.config.scan_limit;
-        let limiter = Semaphore::new(limit);
-
-        if limit == 0 {
-            // scan_limit == 0 means no limit should be imposed... however, scoping the Semaphore
-            // permit is tricky, so as a workaround, we'll add a ridiculous number of permits to
-            // the semaphore (1,152,921,504,606,846,975 to be exact) and call that 'unlimited'
-
-            // note to self: the docs say max is usize::MAX >> 3, however, threads will panic if
-            // that value is used (says adding (1) will overflow the semaphore, even though none
-            // are being added...)
-            limiter.add_permits(usize::MAX >> 4);
+        let strategy = ScanStrategy::from_name(&handles.config.strategy);
+
+        let mut weights: HashMap<String, i64> = DEFAULT_KEYWORD_WEIGHTS
+            .iter()
+            .map(|(keyword, weight)| (keyword.to_string(), *weight))
+            .collect();
+
+        if !handles.config.priority_keywords.is_empty() {
+            match read_priority_keywords(&handles.config.priority_keywords) {
+                Ok(user_weights) => weights.extend(user_weights),
+                Err(e) => log::warn!("Could not read --priority-keywords mapping: {}", e),
+            }
         }
 
+        // scan_limit == 0 means no limit should be imposed
+        let limiter = ScanLimiter::new(handles.config.scan_limit, strategy, weights);
+
+        let auto_wordlists = if handles.config.auto_wordlist.is_empty() {
+            HashMap::new()
+        } else {
+            read_auto_wordlists(&handles.config.auto_wordlist).unwrap_or_else(|e| {
+                log::warn!("Could not read --auto-wordlist mapping: {}", e);
+                HashMap::new()
+            })
+        };
+
+        let wordlist_rules = if handles.config.wordlist_rules.is_empty() {
+            Vec::new()
+        } else {
+            read_wordlist_rules(&handles.config.wordlist_rules).unwrap_or_else(|e| {
+                log::warn!("Could not read --wordlist-rules: {}", e);
+                Vec::new()
+            })
+        };
+
+        // invalid expressions are caught by Configuration::validate
+        let dont_recurse = handles
+            .config
+            .dont_recurse_regex
+            .iter()
+            .filter_map(|regex| Regex::new(regex).ok())
+            .collect();
+
         Self {
             data,
             handles,
@@ -100,13 +163,21 @@ impl ScanHandler {
             depths: Vec::new(),
             limiter: Arc::new(limiter),
             wordlist: std::sync::Mutex::new(None),
+            auto_wordlists,
+            tech_wordlists: HashMap::new(),
+            auto_scanned: HashSet::new(),
+            wordlist_rules,
+            rule_wordlists: HashMap::new(),
+            dont_recurse,
+            target_handles: HashMap::new(),
         }
     }
 
     /// Set the wordlist
-    fn wordlist(&self, wordlist: Arc<Vec<String>>) {
+    fn wordlist(&self, wordlist: Arc<Wordlist>) {
         if let Ok(mut guard) = self.wordlist.lock() {
             if guard.is_none() {
+                self.data.set_wordlist(wordlist.clone());
                 let _ = std::mem::replace(&mut *guard, Some(wordlist));
             }
         }
@@ -124,7 +195,13 @@ impl ScanHandler {
 
         let mut handler = Self::new(data.clone(), handles, max_depth, rx);
 
-        let task = tokio::spawn(async move { handler.start().await });
+        let subscription = EVENTS.subscribe(Arc::new(ScanBars));
+
+        let task = tokio::spawn(async move {
+            // scans' bars are stopped for as long as the handler is running
+            let _subscription = subscription;
+            handler.start().await
+        });
 
         let event_handle = ScanHandle::new(data, tx);
 
@@ -162,6 +239,9 @@ impl ScanHandler {
                 Command::TryRecursion(response) => {
                     self.try_recursion(response).await?;
                 }
+                Command::TryAutoWordlist(target, technologies) => {
+                    self.try_auto_wordlist(target, technologies).await?;
+                }
                 Command::Sync(sender) => {
                     sender.send(true).unwrap_or_default();
                 }
@@ -174,7 +254,7 @@ impl ScanHandler {
     }
 
     /// Helper to easily get the (locked) underlying wordlist
-    pub fn get_wordlist(&self) -> Result<Arc<Vec<String>>> {
+    pub fn get_wordlist(&self) -> Result<Arc<Wordlist>> {
         if let Ok(guard) = self.wordlist.lock().as_ref() {
             if let Some(list) = guard.as_ref() {
                 return Ok(list.clone());
@@ -184,10 +264,31 @@ impl ScanHandler {
         bail!("Could not get underlying wordlist")
     }
 
+    /// handles with which `url` is scanned; urls under a --targets entry get that entry's
+    /// Configuration, everything else the global one
+    fn handles_for(&mut self, url: &str) -> Arc<Handles> {
+        let config = match self.data.get_target_config(url) {
+            Some(config) => config,
+            None => return self.handles.clone(),
+        };
+
+        let handles = &self.handles;
+
+        self.target_handles
+            .entry(config.target_url.clone())
+            .or_insert_with(|| Arc::new(handles.with_config(config)))
+            .clone()
+    }
+
     /// wrapper around scanning a url to stay DRY
-    async fn ordered_scan_url(&mut self, targets: Vec<String>, order: ScanOrder) -> Result<()> {
+    ///
+    /// returns the number of targets that were queued to be scanned; targets that are already
+    /// known, or that are skipped because of --max-scans, aren't counted
+    async fn ordered_scan_url(&mut self, targets: Vec<String>, order: ScanOrder) -> Result<usize> {
         log::trace!("enter: ordered_scan_url({:?}, {:?})", targets, order);
 
+        let mut queued = 0;
+
         for target in targets {
             if self.data.contains(&target) && matches!(order, ScanOrder::Latest) {
                 // FeroxScans knows about this url and scan isn't an Initial scan
@@ -197,13 +298,59 @@ impl ScanHandler {
                 continue;
             }
 
-            let scan = if let Some(ferox_scan) = self.data.get_scan_by_url(&target) {
+            let skipped = self
+                .data
+                .get_scan_by_url(&target)
+                .map_or(false, |scan| scan.is_skipped());
+
+            if (matches!(order, ScanOrder::Latest) || skipped) && self.max_scans_reached() {
+                // user-supplied targets are always scanned, everything else counts against
+                // --max-scans; that includes directories skipped during a previous run
+                self.skip_scan(&target)?;
+                continue;
+            }
+
+            // --wordlist-rules only apply to directories found along the way; a resumed scan
+            // restarts those as initial scans, so they're told apart from the user's targets
+            let found = match order {
+                ScanOrder::Latest => true,
+                ScanOrder::Initial => self.handles.config.resumed && !self.is_user_target(&target),
+            };
+
+            let rule_list = if found {
+                self.rule_wordlist(&target)
+            } else {
+                None
+            };
+
+            let known_scan = self.data.get_scan_by_url(&target);
+            let counted = known_scan.is_some() || rule_list.is_none();
+
+            // scans resumed from a state file were counted against --max-scans by the run that
+            // started them
+            let resumed = known_scan.is_some() && !skipped;
+
+            let scan = if let Some(ferox_scan) = known_scan {
                 ferox_scan // scan already known
+            } else if let Some(list) = &rule_list {
+                // the overall bar's length is based on the default wordlist when counting scans,
+                // so the expected requests are added directly instead
+                let extensions = self.handles_for(&target).config.extensions.len();
+                let num_requests = list.fixed_len() * (extensions + 1);
+
+                self.handles
+                    .stats
+                    .send(AddToUsizeField(TotalExpected, num_requests))?;
+
+                self.data.add_wordlist_scan(&target, num_requests as u64)
             } else {
                 self.data.add_directory_scan(&target, order).1 // add the new target; return FeroxScan
             };
 
-            let list = self.get_wordlist()?;
+            let list = match rule_list {
+                Some(list) => list,
+                None => self.get_wordlist()?,
+            };
 
             log::info!("scan handler received {} - beginning scan", target);
 
@@ -220,38 +367,223 @@ impl ScanHandler {
                 order,
                 list,
                 self.limiter.clone(),
-                self.handles.clone(),
+                self.handles_for(&target),
             );
 
-            let task = tokio::spawn(async move {
+            let context = self.scan_context(&scan);
+
+            let task = tokio::spawn(context.scope(async move {
                 if let Err(e) = scanner.scan_url().await {
                     log::warn!("{}", e);
                 }
-            });
+            }));
+
+            if counted {
+                self.handles.stats.send(AddToUsizeField(TotalScans, 1))?;
+            }
 
-            self.handles.stats.send(AddToUsizeField(TotalScans, 1))?;
+            if !resumed {
+                self.data.increment_num_scans();
+            }
 
             scan.set_task(task).await?;
 
             self.tasks.push(scan.clone());
+            queued += 1;
         }
 
-        log::trace!("exit: ordered_scan_url");
-        Ok(())
+        log::trace!("exit: ordered_scan_url -> {}", queued);
+        Ok(queued)
     }
 
-    async fn try_recursion(&mut self, response: Box<FeroxResponse>) -> Result<()> {
-        log::trace!("enter: try_recursion({:?})", response,);
+    /// whether `url` is one of the user's targets (--url, or an entry in --targets)
+    fn is_user_target(&self, url: &str) -> bool {
+        let url = url.trim_end_matches('/');
+
+        if self.handles.config.target_url.trim_end_matches('/') == url {
+            return true;
+        }
+
+        self.data.get_target_config(url).map_or(false, |config| {
+            config.target_url.trim_end_matches('/') == url
+        })
+    }
+
+    /// wordlist of the first --wordlist-rules rule that applies to `url`, if any
+    fn rule_wordlist(&mut self, url: &str) -> Option<Arc<Wordlist>> {
+        let rule = wordlist_rules::find(&self.wordlist_rules, url)?;
+        let (pattern, path) = (rule.pattern.clone(), rule.wordlist.clone());
+
+        let list = self
+            .rule_wordlists
+            .entry(path.clone())
+            .or_insert_with(|| match get_unique_words_from_wordlist(&path, true) {
+                Ok(list) if !list.is_empty() => Some(list),
+                Ok(_) => {
+                    log::warn!("Did not find any words in {}, used by {}", path, pattern);
+                    None
+                }
+                Err(e) => {
+                    log::warn!("Could not read {} wordlist: {}", pattern, e);
+                    None
+                }
+            })
+            .clone()?;
 
+        log::info!("{} matches {} - scanning with {}", url, pattern, path);
+
+        Some(list)
+    }
+
+    /// depth of the initial target under which `url` was found; 1 when there isn't one
+    fn base_depth(&self, url: &str) -> usize {
         let mut base_depth = 1_usize;
 
         for (base_url, base_url_depth) in &self.depths {
-            if response.url().as_str().starts_with(base_url) {
+            if url.starts_with(base_url) {
                 base_depth = *base_url_depth;
             }
         }
 
-        if response.reached_max_depth(base_depth, self.max_depth, self.handles.clone()) {
+        base_depth
+    }
+
+    /// context in which everything done on behalf of `scan` runs; its recursion depth is relative
+    /// to the initial target under which it was found
+    fn scan_context(&self, scan: &FeroxScan) -> ScanContext {
+        let depth = FeroxUrl::from_string(scan.url(), self.handles.clone())
+            .depth()
+            .unwrap_or(0);
+
+        ScanContext::new(scan.id(), depth.saturating_sub(self.base_depth(scan.url())))
+    }
+
+    /// whether or not --max-scans directory scans have been started already
+    fn max_scans_reached(&self) -> bool {
+        self.handles.config.max_scans > 0 && self.data.num_scans() >= self.handles.config.max_scans
+    }
+
+    /// record `target` as a directory that won't be scanned because --max-scans was reached; it's
+    /// reported as such, and kept in FeroxScans so that it's saved to the state file
+    fn skip_scan(&self, target: &str) -> Result<()> {
+        log::info!("--max-scans reached, not scanning {}", target);
+
+        self.data.add_skipped_scan(target);
+        self.handles
+            .output
+            .send(Command::ReportSkipped(target.to_string()))?;
+
+        Ok(())
+    }
+
+    /// scan `target` with the wordlist associated with each of the given technologies, skipping
+    /// any technology without a wordlist and any that `target` was already scanned for
+    async fn try_auto_wordlist(&mut self, target: String, technologies: Vec<String>) -> Result<()> {
+        log::trace!("enter: try_auto_wordlist({}, {:?})", target, technologies);
+
+        for technology in technologies {
+            let path = match self.auto_wordlists.get(&technology) {
+                Some(path) => path.clone(),
+                None => continue, // no wordlist for this technology
+            };
+
+            if !self
+                .auto_scanned
+                .insert((target.clone(), technology.clone()))
+            {
+                // already scanned (or scanning) this directory with the technology's wordlist
+                continue;
+            }
+
+            let list = match self.tech_wordlists.get(&technology) {
+                Some(list) => list.clone(),
+                None => match get_unique_words_from_wordlist(&path, true) {
+                    Ok(list) => {
+                        self.tech_wordlists.insert(technology.clone(), list.clone());
+                        list
+                    }
+                    Err(e) => {
+                        log::warn!("Could not read {} wordlist: {}", technology, e);
+                        continue;
+                    }
+                },
+            };
+
+            if list.is_empty() {
+                continue;
+            }
+
+            let handles = self.handles_for(&target);
+
+            let num_requests = list.len() * (handles.config.extensions.len() + 1);
+
+            let scan = self.data.add_wordlist_scan(&target, num_requests as u64);
+
+            log::info!(
+                "Detected {} at {} - scanning with {}",
+                technology,
+                target,
+                path
+            );
+
+            let scanner = FeroxScanner::new(
+                &target,
+                ScanOrder::Latest,
+                list,
+                self.limiter.clone(),
+                handles,
+            )
+            .with_scan(scan.clone());
+
+            let context = self.scan_context(&scan);
+
+            let task = tokio::spawn(context.scope(async move {
+                if let Err(e) = scanner.scan_url().await {
+                    log::warn!("{}", e);
+                }
+            }));
+
+            // the overall bar's length is based on the default wordlist when counting scans, so
+            // the expected requests are added directly instead
+            self.handles
+                .stats
+                .send(AddToUsizeField(TotalExpected, num_requests))?;
+
+            scan.set_task(task).await?;
+
+            self.tasks.push(scan);
+        }
+
+        log::trace!("exit: try_auto_wordlist");
+        Ok(())
+    }
+
+    async fn try_recursion(&mut self, response: Box<FeroxResponse>) -> Result<()> {
+        log::trace!("enter: try_recursion({:?})", response,);
+
+        if let Some(location) = response.redirect_location() {
+            // every redirect is recorded, whether or not it's a directory, so that loops made up
+            // of any kind of redirect are found
+            if let Some(cycle) = self
+                .data
+                .add_redirect(response.url().as_str(), location.as_str())
+            {
+                log::info!("found redirect loop: {}", cycle.join(" -> "));
+                self.handles
+                    .output
+                    .send(Command::ReportRedirectLoop(cycle))?;
+            }
+        }
+
+        let base_depth = self.base_depth(response.url().as_str());
+
+        // --targets entries can each have their own --depth
+        let max_depth = self
+            .data
+            .get_target_config(response.url().as_str())
+            .map_or(self.max_depth, |config| config.depth);
+
+        if response.reached_max_depth(base_depth, max_depth, self.handles.clone()) {
             // at or past recursion depth
             return Ok(());
         }
@@ -261,8 +593,36 @@ impl ScanHandler {
             return Ok(());
         }
 
+        let path = response.url().path();
+
+        if let Some(regex) = self.dont_recurse.iter().find(|regex| regex.is_match(path)) {
+            // reported already, just not scanned
+            log::info!(
+                "{} matches --dont-recurse-regex {}, not scanning it recursively",
+                response.url(),
+                regex
+            );
+            return Ok(());
+        }
+
+        if self.data.in_redirect_loop(response.url().as_str()) {
+            // reported already, scanning it would only ever redirect
+            log::info!(
+                "{} is part of a redirect loop, not scanning it recursively",
+                response.url()
+            );
+            return Ok(());
+        }
+
         let targets = vec![response.url().to_string()];
-        self.ordered_scan_url(targets, ScanOrder::Latest).await?;
+
+        if self.ordered_scan_url(targets, ScanOrder::Latest).await? == 0 {
+            // already known, or skipped because of --max-scans
+            log::trace!("exit: try_recursion");
+            return Ok(());
+        }
+
+        EVENTS.publish(ScanEvent::DirectoryDiscovered(response.url().as_str()));
 
         log::info!("Added new directory to recursive scan: {}", response.url());
 
@@ -270,3 +630,69 @@ impl ScanHandler {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Configuration;
+    use crate::events::Subscriber;
+    use reqwest::StatusCode;
+
+    #[derive(Default)]
+    /// remembers every directory discovered under http://discovered.localhost
+    struct Discovered(std::sync::Mutex<Vec<String>>);
+
+    impl Subscriber for Discovered {
+        fn on_event(&self, event: &ScanEvent) {
+            if let ScanEvent::DirectoryDiscovered(url) = event {
+                if url.starts_with("http://discovered.localhost/") {
+                    self.0.lock().unwrap().push(url.to_string());
+                }
+            }
+        }
+    }
+
+    /// 200 response for the given directory
+    fn directory(url: &str) -> Box<FeroxResponse> {
+        let mut response = FeroxResponse::default();
+        response.set_url(url);
+        response.set_status(StatusCode::OK);
+        Box::new(response)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// directories that aren't queued, because they're already known or --max-scans was reached,
+    /// shouldn't be published as discovered
+    async fn try_recursion_only_publishes_queued_directories() {
+        let config = Configuration {
+            max_scans: 1,
+            ..Default::default()
+        };
+
+        let data = Arc::new(FeroxScans::default());
+        data.add_directory_scan("http://discovered.localhost/known/", ScanOrder::Latest);
+        data.increment_num_scans();
+
+        let (handles, _rx) = Handles::for_testing(Some(data.clone()), Some(Arc::new(config)));
+        let (_tx, rx) = mpsc::unbounded_channel();
+        let mut handler = ScanHandler::new(data.clone(), Arc::new(handles), 0, rx);
+
+        let discovered = Arc::new(Discovered::default());
+        let _subscription = EVENTS.subscribe(discovered.clone());
+
+        handler
+            .try_recursion(directory("http://discovered.localhost/known/"))
+            .await
+            .unwrap();
+        handler
+            .try_recursion(directory("http://discovered.localhost/skipped/"))
+            .await
+            .unwrap();
+
+        assert!(data
+            .get_scan_by_url("http://discovered.localhost/skipped/")
+            .unwrap()
+            .is_skipped());
+        assert!(discovered.0.lock().unwrap().is_empty());
+    }
+}
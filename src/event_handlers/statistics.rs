@@ -1,6 +1,7 @@
 use super::*;
 use crate::{
     config::Configuration,
+    events::{ScanEvent, Subscriber, EVENTS},
     progress::{add_bar, BarType},
     statistics::{StatField, Stats},
     CommandSender, FeroxChannel, Joiner,
@@ -8,13 +9,17 @@ use crate::{
 use anyhow::Result;
 use console::style;
 use indicatif::ProgressBar;
-use std::{sync::Arc, time::Instant};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 use tokio::sync::{
     mpsc::{self, UnboundedReceiver},
     oneshot,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// Container for statistics transmitter and Stats object
 pub struct StatsHandle {
     /// Stats object used across modules to track statistics
@@ -103,7 +108,7 @@ impl StatsHandler {
                 Command::AddToUsizeField(field, value) => {
                     self.stats.update_usize_field(field, value);
 
-                    if matches!(field, StatField::TotalScans) {
+                    if matches!(field, StatField::TotalScans | StatField::TotalExpected) {
                         self.bar.set_length(self.stats.total_expected() as u64);
                     }
                 }
@@ -115,11 +120,12 @@ impl StatsHandler {
                     }
                 }
                 Command::AddToF64Field(field, value) => self.stats.update_f64_field(field, value),
+                Command::AddFilterHit(name) => self.stats.add_filter_hit(name),
                 Command::CreateBar => {
                     self.bar = add_bar("", self.stats.total_expected() as u64, BarType::Total);
                 }
-                Command::LoadStats(filename) => {
-                    self.stats.merge_from(&filename)?;
+                Command::LoadStats(filename, password) => {
+                    self.stats.merge_from(&filename, &password)?;
                 }
                 Command::Sync(sender) => {
                     sender.send(true).unwrap_or_default();
@@ -138,7 +144,7 @@ impl StatsHandler {
 
     /// Wrapper around incrementing the overall scan's progress bar
     fn increment_bar(&self) {
-        let msg = format!(
+        let mut msg = format!(
             "{}:{:<7} {}:{:<7}",
             style("found").green(),
             self.stats.resources_discovered(),
@@ -146,6 +152,15 @@ impl StatsHandler {
             self.stats.errors(),
         );
 
+        if self.stats.blocked() > 0 {
+            // only shown once a WAF/CDN has been seen blocking requests
+            msg.push_str(&format!(
+                " {}:{:<7}",
+                style("blocked").yellow(),
+                self.stats.blocked()
+            ));
+        }
+
         self.bar.set_message(&msg);
         self.bar.inc(1);
     }
@@ -160,7 +175,13 @@ impl StatsHandler {
 
         let mut handler = StatsHandler::new(data.clone(), rx);
 
-        let task = tokio::spawn(async move { handler.start(&config.output).await });
+        let subscription = EVENTS.subscribe(Arc::new(StatsRecorder::new(tx.clone())));
+
+        let task = tokio::spawn(async move {
+            // the run's events are counted for as long as the handler is running
+            let _subscription = subscription;
+            handler.start(&config.output).await
+        });
 
         let event_handle = StatsHandle::new(data, tx);
 
@@ -169,3 +190,66 @@ impl StatsHandler {
         (task, event_handle)
     }
 }
+
+/// keeps statistics up to date with the run's events
+#[derive(Debug)]
+struct StatsRecorder {
+    /// transmitter used to update statistics
+    tx: CommandSender,
+
+    /// when each running scan started, by scan id
+    started: Mutex<HashMap<String, Instant>>,
+}
+
+/// implementation of StatsRecorder
+impl StatsRecorder {
+    /// create a recorder that sends its updates over the given transmitter
+    fn new(tx: CommandSender) -> Self {
+        Self {
+            tx,
+            started: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// implementation of Subscriber for StatsRecorder
+impl Subscriber for StatsRecorder {
+    /// count reported responses and time each scan from start to completion
+    /// (`directory_scan_times`)
+    fn on_event(&self, event: &ScanEvent) {
+        match event {
+            ScanEvent::ResponseReported(_) => {
+                self.tx
+                    .send(Command::AddToUsizeField(StatField::ResourcesDiscovered, 1))
+                    .unwrap_or_default();
+            }
+            ScanEvent::ScanStarted(scan) => {
+                if let Ok(mut guard) = self.started.lock() {
+                    guard.insert(scan.id().to_string(), Instant::now());
+                }
+            }
+            ScanEvent::ScanComplete(scan) => {
+                let started = self
+                    .started
+                    .lock()
+                    .ok()
+                    .and_then(|mut guard| guard.remove(scan.id()));
+
+                if let Some(started) = started {
+                    self.tx
+                        .send(Command::AddToF64Field(
+                            StatField::DirScanTimes,
+                            started.elapsed().as_secs_f64(),
+                        ))
+                        .unwrap_or_default();
+                }
+            }
+            ScanEvent::ScanAborted(scan) => {
+                if let Ok(mut guard) = self.started.lock() {
+                    guard.remove(scan.id());
+                }
+            }
+            ScanEvent::DirectoryDiscovered(_) => {}
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! internal event bus; the run's lifecycle (scans starting and stopping, directories and
+//! responses being found) is published here as it happens, and anything that needs to react to it
+//! subscribes, rather than being called directly from wherever the event happened
+//!
+//! subscribers are called synchronously, in the order they subscribed, on whichever task published
+//! the event; anything slow (network, disk, child processes) should hand the event off instead of
+//! handling it inline
+//!
+//! a subscriber is called for as long as the `Subscription` returned when it subscribed is held
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use lazy_static::lazy_static;
+
+use crate::{response::FeroxResponse, scan_manager::FeroxScan};
+
+lazy_static! {
+    /// bus to which the run's events are published
+    pub static ref EVENTS: EventBus = EventBus::default();
+}
+
+/// Something that happened during the run
+#[derive(Debug, Copy, Clone)]
+pub enum ScanEvent<'a> {
+    /// a scan has started sending requests
+    ScanStarted(&'a FeroxScan),
+
+    /// a directory was found and queued to be scanned recursively
+    DirectoryDiscovered(&'a str),
+
+    /// a response made it through all of the filters and was reported
+    ResponseReported(&'a FeroxResponse),
+
+    /// a scan made all of its requests, or ran out of time (--scan-time-limit)
+    ScanComplete(&'a FeroxScan),
+
+    /// a scan was stopped before making all of its requests (cancelled, abandoned)
+    ScanAborted(&'a FeroxScan),
+}
+
+/// Anything that reacts to the run's events
+pub trait Subscriber: Send + Sync {
+    /// Handle a single event
+    fn on_event(&self, event: &ScanEvent);
+}
+
+/// Publishes events to every subscriber
+#[derive(Default)]
+pub struct EventBus {
+    /// everything currently subscribed, in the order they subscribed, along with the id of their
+    /// subscription
+    subscribers: RwLock<Vec<(usize, Arc<dyn Subscriber>)>>,
+
+    /// id given to the next subscription
+    next_id: AtomicUsize,
+}
+
+/// implementation of EventBus
+impl EventBus {
+    /// Have the given subscriber called for every event published from here on out, until the
+    /// returned `Subscription` is dropped
+    pub fn subscribe(&self, subscriber: Arc<dyn Subscriber>) -> Subscription<'_> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        if let Ok(mut guard) = self.subscribers.write() {
+            guard.push((id, subscriber));
+        }
+
+        Subscription { bus: self, id }
+    }
+
+    /// Stop calling the subscriber with the given subscription id
+    fn unsubscribe(&self, id: usize) {
+        if let Ok(mut guard) = self.subscribers.write() {
+            guard.retain(|(subscribed, _)| *subscribed != id);
+        }
+    }
+
+    /// Call every subscriber with the given event
+    pub fn publish(&self, event: ScanEvent) {
+        if let Ok(guard) = self.subscribers.read() {
+            for (_, subscriber) in guard.iter() {
+                subscriber.on_event(&event);
+            }
+        }
+    }
+
+    /// Number of subscribers
+    pub fn len(&self) -> usize {
+        self.subscribers.read().map_or(0, |guard| guard.len())
+    }
+
+    /// Whether nothing has subscribed yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A subscriber's place on an `EventBus`; the subscriber is removed from the bus when this is
+/// dropped
+#[must_use = "the subscriber is unsubscribed as soon as its Subscription is dropped"]
+pub struct Subscription<'a> {
+    /// bus the subscriber is subscribed to
+    bus: &'a EventBus,
+
+    /// id of the subscription
+    id: usize,
+}
+
+/// implementation of Subscription
+impl Subscription<'_> {
+    /// Keep the subscriber subscribed until the process exits
+    pub fn keep(self) {
+        std::mem::forget(self);
+    }
+}
+
+/// Drop implementation for Subscription
+impl Drop for Subscription<'_> {
+    /// remove the subscriber from the bus
+    fn drop(&mut self) {
+        self.bus.unsubscribe(self.id);
+    }
+}
+
+/// Debug implementation for Subscription; only its id is shown
+impl fmt::Debug for Subscription<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+/// Debug implementation for EventBus; subscribers are trait objects, only their number is shown
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// subscriber that remembers a description of each event it sees
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Subscriber for Recorder {
+        fn on_event(&self, event: &ScanEvent) {
+            let seen = match event {
+                ScanEvent::ScanStarted(scan) => format!("started {}", scan.url()),
+                ScanEvent::DirectoryDiscovered(url) => format!("discovered {}", url),
+                ScanEvent::ResponseReported(response) => format!("reported {}", response.url()),
+                ScanEvent::ScanComplete(scan) => format!("complete {}", scan.url()),
+                ScanEvent::ScanAborted(scan) => format!("aborted {}", scan.url()),
+            };
+
+            self.0.lock().unwrap().push(seen);
+        }
+    }
+
+    #[test]
+    /// every subscriber should see every event published after it subscribed, in order
+    fn publish_reaches_every_subscriber() {
+        let bus = EventBus::default();
+        let first = Arc::new(Recorder::default());
+        let second = Arc::new(Recorder::default());
+
+        let _first = bus.subscribe(first.clone());
+        bus.publish(ScanEvent::DirectoryDiscovered("http://localhost/css/"));
+        let _second = bus.subscribe(second.clone());
+
+        let mut response = FeroxResponse::default();
+        response.set_url("http://localhost/js");
+        bus.publish(ScanEvent::ResponseReported(&response));
+
+        assert_eq!(bus.len(), 2);
+        assert_eq!(
+            *first.0.lock().unwrap(),
+            vec![
+                "discovered http://localhost/css/",
+                "reported http://localhost/js"
+            ]
+        );
+        assert_eq!(
+            *second.0.lock().unwrap(),
+            vec!["reported http://localhost/js"]
+        );
+    }
+
+    #[test]
+    /// a subscriber shouldn't see anything published after its subscription was dropped, unless
+    /// the subscription was kept
+    fn dropped_subscriptions_unsubscribe() {
+        let bus = EventBus::default();
+        let dropped = Arc::new(Recorder::default());
+        let kept = Arc::new(Recorder::default());
+
+        let subscription = bus.subscribe(dropped.clone());
+        bus.subscribe(kept.clone()).keep();
+        bus.publish(ScanEvent::DirectoryDiscovered("http://localhost/css/"));
+
+        drop(subscription);
+        bus.publish(ScanEvent::DirectoryDiscovered("http://localhost/js/"));
+
+        assert_eq!(bus.len(), 1);
+        assert_eq!(
+            *dropped.0.lock().unwrap(),
+            vec!["discovered http://localhost/css/"]
+        );
+        assert_eq!(
+            *kept.0.lock().unwrap(),
+            vec![
+                "discovered http://localhost/css/",
+                "discovered http://localhost/js/"
+            ]
+        );
+    }
+}
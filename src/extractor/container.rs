@@ -1,11 +1,12 @@
 use super::*;
 use crate::{
-    client,
+    client::{self, Trust},
     event_handlers::{
         Command,
         Command::{AddError, AddToUsizeField},
         Handles,
     },
+    listing,
     scan_manager::ScanOrder,
     statistics::{
         StatError::Other,
@@ -15,7 +16,7 @@ use crate::{
     utils::{logged_request, make_request},
 };
 use anyhow::{bail, Context, Result};
-use reqwest::{StatusCode, Url};
+use reqwest::{Method, StatusCode, Url};
 use std::collections::HashSet;
 use tokio::sync::oneshot;
 
@@ -58,6 +59,7 @@ impl<'a> Extractor<'a> {
         let links = match self.target {
             ExtractionTarget::ResponseBody => self.extract_from_body().await?,
             ExtractionTarget::RobotsTxt => self.extract_from_robots().await?,
+            ExtractionTarget::DirectoryListing => self.extract_from_listing()?,
         };
 
         let recursive = if self.handles.config.no_recursion {
@@ -74,12 +76,13 @@ impl<'a> Extractor<'a> {
                 Err(_) => continue,
             };
 
-            // filter if necessary
+            // filter if necessary, including the custom 404 of the directory the link is in
             if self
                 .handles
                 .filters
                 .data
                 .should_filter_response(&resp, self.handles.stats.tx.clone())
+                || scanned_urls.is_wildcard(&resp, self.handles.stats.tx.clone())
             {
                 continue;
             }
@@ -97,6 +100,13 @@ impl<'a> Extractor<'a> {
                 continue;
             }
 
+            if matches!(self.target, ExtractionTarget::DirectoryListing) {
+                // listed directories aren't reported when they're scanned, so they're reported here
+                if let Err(e) = resp.clone().send_report(self.handles.output.tx.clone()) {
+                    log::warn!("Could not send FeroxResponse to output handler: {}", e);
+                }
+            }
+
             if matches!(recursive, RecursionStatus::Recursive) {
                 log::debug!("Extracted Directory: {}", resp);
 
@@ -257,7 +267,9 @@ impl<'a> Extractor<'a> {
         log::trace!("enter: add_link_to_set_of_links({}, {:?})", link, links);
 
         let old_url = match self.target {
-            ExtractionTarget::ResponseBody => self.response.unwrap().url().clone(),
+            ExtractionTarget::ResponseBody | ExtractionTarget::DirectoryListing => {
+                self.response.unwrap().url().clone()
+            }
             ExtractionTarget::RobotsTxt => match Url::parse(&self.url) {
                 Ok(u) => u,
                 Err(e) => {
@@ -344,6 +356,29 @@ impl<'a> Extractor<'a> {
         Ok(links)
     }
 
+    /// Entry point to perform extraction of the entries of a directory listing (--parse-listings)
+    ///
+    /// unlike response bodies, only the listed entries themselves are returned; sub-directories
+    /// are listings of their own and will be parsed in turn
+    pub(super) fn extract_from_listing(&self) -> Result<HashSet<String>> {
+        log::trace!("enter: extract_from_listing");
+
+        let response = match self.response {
+            Some(response) => response,
+            None => bail!("Directory listing extraction requires a FeroxResponse"),
+        };
+
+        let links: HashSet<String> = listing::entries(response.url(), response.text())
+            .iter()
+            .map(|entry| entry.to_string())
+            .collect();
+
+        self.update_stats(links.len())?;
+
+        log::trace!("exit: extract_from_listing -> {:?}", links);
+        Ok(links)
+    }
+
     /// helper function that simply requests /robots.txt on the given url's base url
     ///
     /// example:
@@ -370,9 +405,10 @@ impl<'a> Extractor<'a> {
             self.handles.config.timeout,
             &self.handles.config.user_agent,
             follow_redirects,
-            self.handles.config.insecure,
+            Trust::from_config(&self.handles.config),
             &self.handles.config.headers,
             proxy,
+            self.handles.config.dns_cache_ttl,
         )?;
 
         let mut url = Url::parse(&self.url)?;
@@ -381,6 +417,8 @@ impl<'a> Extractor<'a> {
         // purposefully not using logged_request here due to using the special client
         let response = make_request(
             &client,
+            self.handles.config.pin_verifier.as_deref(),
+            Method::GET,
             &url,
             self.handles.config.output_level,
             self.handles.stats.tx.clone(),
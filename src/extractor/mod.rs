@@ -1,4 +1,4 @@
-//! extract links from html source and robots.txt
+//! extract links from html source, robots.txt, and directory listings
 mod builder;
 mod container;
 #[cfg(test)]
@@ -6,6 +6,7 @@ mod tests;
 
 pub use self::builder::ExtractionTarget;
 pub use self::builder::ExtractorBuilder;
+pub(crate) use self::builder::LINKFINDER_REGEX;
 pub use self::container::Extractor;
 
 use crate::response::FeroxResponse;
@@ -8,7 +8,7 @@ use crate::{
 use anyhow::Result;
 use httpmock::{Method::GET, MockServer};
 use lazy_static::lazy_static;
-use reqwest::{Client, StatusCode, Url};
+use reqwest::{Client, Method, StatusCode, Url};
 use std::collections::HashSet;
 use tokio::sync::mpsc;
 
@@ -41,6 +41,9 @@ fn setup_extractor(target: ExtractionTarget, scanned_urls: Arc<FeroxScans>) -> E
         ExtractionTarget::RobotsTxt => builder
             .url("http://localhost")
             .target(ExtractionTarget::RobotsTxt),
+        ExtractionTarget::DirectoryListing => builder
+            .target(ExtractionTarget::DirectoryListing)
+            .response(&RESPONSE),
     };
 
     let config = Arc::new(Configuration::new().unwrap());
@@ -218,9 +221,16 @@ async fn extractor_get_links_with_absolute_url_that_differs_from_target_domain()
     let client = Client::new();
     let url = Url::parse(&srv.url("/some-path")).unwrap();
 
-    let response = make_request(&client, &url, OutputLevel::Default, tx_stats.clone())
-        .await
-        .unwrap();
+    let response = make_request(
+        &client,
+        None,
+        Method::GET,
+        &url,
+        OutputLevel::Default,
+        tx_stats.clone(),
+    )
+    .await
+    .unwrap();
     let (handles, _rx) = Handles::for_testing(None, None);
 
     let handles = Arc::new(handles);
@@ -352,3 +362,52 @@ async fn request_link_bails_on_seen_url() -> Result<()> {
     assert_eq!(mock.hits(), 0); // function exits before requests can happen
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+/// test that only the entries of a directory listing are extracted from it
+async fn extractor_extract_from_listing_returns_entries() -> Result<()> {
+    let srv = MockServer::start();
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/files/");
+        then.status(200).body(
+            r#"<title>Index of /files</title><a href="/">Parent Directory</a>
+<a href="?C=N;O=D">Name</a><a href="notes.txt">notes.txt</a><a href="old/">old/</a>"#,
+        );
+    });
+
+    let client = Client::new();
+    let url = Url::parse(&srv.url("/files/")).unwrap();
+    let (tx_stats, _): FeroxChannel<Command> = mpsc::unbounded_channel();
+
+    let response = make_request(
+        &client,
+        None,
+        Method::GET,
+        &url,
+        OutputLevel::Default,
+        tx_stats,
+    )
+    .await
+    .unwrap();
+    let ferox_response = FeroxResponse::from(response, true, OutputLevel::Default).await;
+    assert!(ferox_response.listing());
+
+    let (handles, _rx) = Handles::for_testing(None, None);
+
+    let extractor = ExtractorBuilder::default()
+        .response(&ferox_response)
+        .target(ExtractionTarget::DirectoryListing)
+        .handles(Arc::new(handles))
+        .build()?;
+
+    let links = extractor.extract_from_listing()?;
+
+    let expected: HashSet<String> = vec![srv.url("/files/notes.txt"), srv.url("/files/old/")]
+        .into_iter()
+        .collect();
+
+    assert_eq!(links, expected);
+    assert_eq!(mock.hits(), 1);
+    Ok(())
+}
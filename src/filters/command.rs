@@ -0,0 +1,121 @@
+use super::*;
+use anyhow::Result;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tokio::{
+    runtime::{Handle, RuntimeFlavor},
+    task::block_in_place,
+};
+
+/// Simple implementor of FeroxFilter; used to filter out responses based on the exit status of
+/// an external command; specified using --filter-command
+#[derive(Default, Debug, PartialEq)]
+pub struct CommandFilter {
+    /// Command to be executed by the system shell for each response
+    pub command: String,
+}
+
+/// implementation of CommandFilter
+impl CommandFilter {
+    /// create a JSON representation of the given response, including its body, to be passed to
+    /// the user's command over stdin
+    fn response_to_json(response: &FeroxResponse) -> Result<String> {
+        let mut blob = serde_json::to_value(response)?;
+
+        if let Some(map) = blob.as_object_mut() {
+            map.insert("body".to_string(), response.text().to_owned().into());
+        }
+
+        Ok(serde_json::to_string(&blob)?)
+    }
+
+    /// build the shell invocation for the user's command
+    fn shell(&self) -> Command {
+        if cfg!(target_os = "windows") {
+            let mut cmd = Command::new("cmd");
+            cmd.arg("/C").arg(&self.command);
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(&self.command);
+            cmd
+        }
+    }
+
+    /// run the user's command with the given JSON on stdin; returns whether or not the command
+    /// exited successfully
+    ///
+    /// filters are evaluated from within the runtime, so the wait for the command is handed off
+    /// via `block_in_place`; other requests keep being processed in the meantime
+    fn run(&self, json: &str) -> Result<bool> {
+        match Handle::try_current() {
+            Ok(handle) if matches!(handle.runtime_flavor(), RuntimeFlavor::MultiThread) => {
+                block_in_place(|| self.run_to_completion(json))
+            }
+            _ => self.run_to_completion(json),
+        }
+    }
+
+    /// spawn the user's command, write the given JSON to its stdin, and wait for it to exit
+    fn run_to_completion(&self, json: &str) -> Result<bool> {
+        let mut child = self
+            .shell()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // a command that exits without reading all of stdin results in a broken pipe, which
+            // isn't an error from our perspective; only the exit status matters
+            stdin.write_all(json.as_bytes()).unwrap_or_default();
+        } // stdin dropped here, signaling EOF to the child
+
+        Ok(child.wait()?.success())
+    }
+}
+
+/// implementation of FeroxFilter for CommandFilter
+impl FeroxFilter for CommandFilter {
+    /// Pipe the response to the command passed in via --filter-command, if the command exits
+    /// with a non-zero status, the response should be filtered out
+    ///
+    /// any failure to execute the command results in the response being reported
+    fn should_filter_response(&self, response: &FeroxResponse) -> bool {
+        log::trace!("enter: should_filter_response({:?} {})", self, response);
+
+        let result = match Self::response_to_json(response).and_then(|json| self.run(&json)) {
+            Ok(success) => !success,
+            Err(e) => {
+                log::warn!("Could not run --filter-command {}: {}", self.command, e);
+                false
+            }
+        };
+
+        if result {
+            log::debug!(
+                "filtered out {} based on --filter-command of {}",
+                response.url(),
+                self.command
+            );
+        }
+
+        log::trace!("exit: should_filter_response -> {}", result);
+        result
+    }
+
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "Command"
+    }
+
+    /// Compare one CommandFilter to another
+    fn box_eq(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<Self>().map_or(false, |a| self == a)
+    }
+
+    /// Return self as Any for dynamic dispatch purposes
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
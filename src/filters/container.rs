@@ -1,32 +1,46 @@
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 
 use crate::response::FeroxResponse;
 use crate::{
-    event_handlers::Command::AddToUsizeField, statistics::StatField::WildcardsFiltered,
+    event_handlers::Command::{AddFilterHit, AddToUsizeField},
+    statistics::StatField::{ResponsesFiltered, WildcardsFiltered},
     CommandSender,
 };
 
-use super::{FeroxFilter, WildcardFilter};
+use super::{filter_rank, FeroxFilter, WildcardFilter};
 
 /// Container around a collection of `FeroxFilters`s
 #[derive(Debug, Default)]
 pub struct FeroxFilters {
-    /// collection of `FeroxFilters`
-    pub filters: Mutex<Vec<Box<dyn FeroxFilter>>>,
+    /// collection of `FeroxFilters`, kept sorted by their position in `FILTER_REGISTRY`
+    pub filters: Mutex<Vec<Arc<dyn FeroxFilter>>>,
 }
 
 /// implementation of FeroxFilter collection
 impl FeroxFilters {
     /// add a single FeroxFilter to the collection
+    ///
+    /// the filter is inserted after all other filters of equal or lesser rank, which keeps the
+    /// evaluation pipeline ordered while preserving insertion order within a filter type
     pub fn push(&self, filter: Box<dyn FeroxFilter>) -> Result<()> {
         if let Ok(mut guard) = self.filters.lock() {
-            if guard.contains(&filter) {
+            if guard
+                .iter()
+                .any(|existing| existing.box_eq(filter.as_any()))
+            {
                 return Ok(());
             }
 
-            guard.push(filter)
+            let rank = filter_rank(filter.name());
+
+            let index = guard
+                .iter()
+                .position(|existing| filter_rank(existing.name()) > rank)
+                .unwrap_or_else(|| guard.len());
+
+            guard.insert(index, Arc::from(filter))
         }
         Ok(())
     }
@@ -38,19 +52,32 @@ impl FeroxFilters {
         response: &FeroxResponse,
         tx_stats: CommandSender,
     ) -> bool {
-        if let Ok(filters) = self.filters.lock() {
-            for filter in filters.iter() {
-                // wildcard.should_filter goes here
-                if filter.should_filter_response(&response) {
-                    if filter.as_any().downcast_ref::<WildcardFilter>().is_some() {
-                        tx_stats
-                            .send(AddToUsizeField(WildcardsFiltered, 1))
-                            .unwrap_or_default();
-                    }
-                    return true;
-                }
+        // the lock is only held long enough to copy the pipeline; some filters (i.e. the one
+        // behind --filter-command) take a while, and shouldn't hold up anyone else's responses
+        let filters = match self.filters.lock() {
+            Ok(guard) => guard.clone(),
+            Err(_) => return false,
+        };
+
+        for filter in filters.iter() {
+            // filters are evaluated in FILTER_REGISTRY order; first match wins
+            if filter.should_filter_response(&response) {
+                // wildcards_filtered also increments responses_filtered
+                let field = if filter.as_any().downcast_ref::<WildcardFilter>().is_some() {
+                    WildcardsFiltered
+                } else {
+                    ResponsesFiltered
+                };
+
+                tx_stats.send(AddToUsizeField(field, 1)).unwrap_or_default();
+                tx_stats
+                    .send(AddFilterHit(filter.name()))
+                    .unwrap_or_default();
+
+                return true;
             }
         }
+
         false
     }
 }
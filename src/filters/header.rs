@@ -0,0 +1,102 @@
+use super::*;
+
+/// Simple implementor of FeroxFilter; used to filter out responses based on the presence of a
+/// given response header; specified using --filter-header and --match-header
+#[derive(Default, Debug, PartialEq)]
+pub struct HeaderFilter {
+    /// Name of the header to be examined (case-insensitive)
+    pub name: String,
+
+    /// Value the header is expected to contain (case-insensitive); an empty value only checks
+    /// for the header's presence
+    pub value: String,
+
+    /// When true, responses that do NOT contain the header are filtered (--match-header); when
+    /// false, responses that DO contain the header are filtered (--filter-header)
+    pub invert: bool,
+}
+
+/// implementation of HeaderFilter
+impl HeaderFilter {
+    /// given a raw `Name: value` string, as passed in via --filter-header or --match-header,
+    /// create a new HeaderFilter
+    pub fn from_raw(raw: &str, invert: bool) -> Self {
+        let mut split_val = raw.split(':');
+
+        // explicitly take first split value as header's name
+        let name = split_val.next().unwrap_or_default().trim().to_string();
+
+        // all other items in the iterator returned by split, when combined with the
+        // original split deliminator (:), make up the header's final value
+        let value = split_val
+            .collect::<Vec<&str>>()
+            .join(":")
+            .trim()
+            .to_string();
+
+        Self {
+            name,
+            value,
+            invert,
+        }
+    }
+
+    /// determine whether the given response contains a header that matches this filter
+    fn response_has_header(&self, response: &FeroxResponse) -> bool {
+        let expected = self.value.to_lowercase();
+
+        // HeaderMap::get_all performs a case-insensitive lookup on the header's name
+        response
+            .headers()
+            .get_all(self.name.as_str())
+            .iter()
+            .any(|value| {
+                let actual = String::from_utf8_lossy(value.as_bytes()).to_lowercase();
+                expected.is_empty() || actual.contains(&expected)
+            })
+    }
+}
+
+/// implementation of FeroxFilter for HeaderFilter
+impl FeroxFilter for HeaderFilter {
+    /// Check the response's headers against what was passed in via --filter-header or
+    /// --match-header
+    fn should_filter_response(&self, response: &FeroxResponse) -> bool {
+        log::trace!("enter: should_filter_response({:?} {})", self, response);
+
+        let result = self.response_has_header(response) != self.invert;
+
+        if result {
+            log::debug!(
+                "filtered out {} based on {} of {}: {}",
+                response.url(),
+                if self.invert {
+                    "--match-header"
+                } else {
+                    "--filter-header"
+                },
+                self.name,
+                self.value
+            );
+        }
+
+        log::trace!("exit: should_filter_response -> {}", result);
+
+        result
+    }
+
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "Header"
+    }
+
+    /// Compare one HeaderFilter to another
+    fn box_eq(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<Self>().map_or(false, |a| self == a)
+    }
+
+    /// Return self as Any for dynamic dispatch purposes
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -1,7 +1,9 @@
 use super::{
-    LinesFilter, RegexFilter, SimilarityFilter, SizeFilter, StatusCodeFilter, WordsFilter,
+    CommandFilter, FeroxFilter, HeaderFilter, JsonErrorFilter, LinesFilter, PluginFilter,
+    RegexFilter, SimilarityFilter, SizeFilter, StatusCodeFilter, WordsFilter,
 };
 use crate::{
+    config::Configuration,
     event_handlers::Handles,
     response::FeroxResponse,
     skip_fail,
@@ -15,46 +17,45 @@ use regex::Regex;
 use reqwest::Url;
 use std::sync::Arc;
 
-/// add all user-supplied filters to the (already started) filters handler
-pub async fn initialize(handles: Arc<Handles>) -> Result<()> {
-    // add any status code filters to filters handler's FeroxFilters  (-C|--filter-status)
-    for code_filter in &handles.config.filter_status {
+/// Build every user-supplied filter that can be created from the configuration alone; that's
+/// all of them except --filter-similar-to, which has to request its url first
+pub fn from_config(config: &Configuration) -> Vec<Box<dyn FeroxFilter>> {
+    let mut filters: Vec<Box<dyn FeroxFilter>> = Vec::new();
+
+    // add any status code filters (-C|--filter-status)
+    for code_filter in &config.filter_status {
         let filter = StatusCodeFilter {
             filter_code: *code_filter,
         };
-        let boxed_filter = Box::new(filter);
-        skip_fail!(handles.filters.send(AddFilter(boxed_filter)));
+        filters.push(Box::new(filter));
     }
 
-    // add any line count filters to filters handler's FeroxFilters  (-N|--filter-lines)
-    for lines_filter in &handles.config.filter_line_count {
+    // add any line count filters (-N|--filter-lines)
+    for lines_filter in &config.filter_line_count {
         let filter = LinesFilter {
             line_count: *lines_filter,
         };
-        let boxed_filter = Box::new(filter);
-        skip_fail!(handles.filters.send(AddFilter(boxed_filter)));
+        filters.push(Box::new(filter));
     }
 
-    // add any line count filters to filters handler's FeroxFilters  (-W|--filter-words)
-    for words_filter in &handles.config.filter_word_count {
+    // add any line count filters (-W|--filter-words)
+    for words_filter in &config.filter_word_count {
         let filter = WordsFilter {
             word_count: *words_filter,
         };
-        let boxed_filter = Box::new(filter);
-        skip_fail!(handles.filters.send(AddFilter(boxed_filter)));
+        filters.push(Box::new(filter));
     }
 
-    // add any line count filters to filters handler's FeroxFilters  (-S|--filter-size)
-    for size_filter in &handles.config.filter_size {
+    // add any line count filters (-S|--filter-size)
+    for size_filter in &config.filter_size {
         let filter = SizeFilter {
             content_length: *size_filter,
         };
-        let boxed_filter = Box::new(filter);
-        skip_fail!(handles.filters.send(AddFilter(boxed_filter)));
+        filters.push(Box::new(filter));
     }
 
-    // add any regex filters to filters handler's FeroxFilters  (-X|--filter-regex)
-    for regex_filter in &handles.config.filter_regex {
+    // add any regex filters (-X|--filter-regex)
+    for regex_filter in &config.filter_regex {
         let raw = regex_filter;
         let compiled = skip_fail!(Regex::new(&raw));
 
@@ -62,12 +63,58 @@ pub async fn initialize(handles: Arc<Handles>) -> Result<()> {
             raw_string: raw.to_owned(),
             compiled,
         };
-        let boxed_filter = Box::new(filter);
-        skip_fail!(handles.filters.send(AddFilter(boxed_filter)));
+        filters.push(Box::new(filter));
+    }
+
+    // add any header filters (--filter-header)
+    for header_filter in &config.filter_header {
+        let filter = HeaderFilter::from_raw(header_filter, false);
+        filters.push(Box::new(filter));
+    }
+
+    // add any header matchers (--match-header)
+    for header_matcher in &config.match_header {
+        let filter = HeaderFilter::from_raw(header_matcher, true);
+        filters.push(Box::new(filter));
+    }
+
+    // add the json error filter (--api)
+    if config.api {
+        filters.push(Box::new(JsonErrorFilter));
+    }
+
+    // add the plugin filter (--plugin)
+    if !config.loaded_plugins.is_empty() {
+        let filter = PluginFilter {
+            plugins: config.loaded_plugins.clone(),
+        };
+        filters.push(Box::new(filter));
+    }
+
+    // add the external command filter (--filter-command)
+    if !config.filter_command.is_empty() {
+        let filter = CommandFilter {
+            command: config.filter_command.clone(),
+        };
+        filters.push(Box::new(filter));
+    }
+
+    filters
+}
+
+/// add all user-supplied filters to the (already started) filters handler
+pub async fn initialize(handles: Arc<Handles>) -> Result<()> {
+    for filter in from_config(&handles.config) {
+        skip_fail!(handles.filters.send(AddFilter(filter)));
     }
 
     // add any similarity filters to filters handler's FeroxFilters  (--filter-similar-to)
     for similarity_filter in &handles.config.filter_similar {
+        if handles.config.dry_run {
+            // --dry-run; no requests are sent, this one included
+            break;
+        }
+
         // url as-is based on input, ignores user-specified url manipulation options (add-slash etc)
         let url = skip_fail!(Url::parse(&similarity_filter));
 
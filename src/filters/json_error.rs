@@ -0,0 +1,48 @@
+use super::*;
+use crate::api_mode::{classify, JsonError};
+
+/// Simple implementor of FeroxFilter; used to filter out json error bodies that say the requested
+/// route doesn't exist (soft 404s), when using --api
+#[derive(Default, Debug, PartialEq)]
+pub struct JsonErrorFilter;
+
+/// implementation of FeroxFilter for JsonErrorFilter
+impl FeroxFilter for JsonErrorFilter {
+    /// Check whether the response's body is a json error saying the route wasn't found
+    fn should_filter_response(&self, response: &FeroxResponse) -> bool {
+        log::trace!("enter: should_filter_response({:?} {})", self, response);
+
+        let class = classify(response.text());
+
+        if let Some(class) = class {
+            log::debug!("{} is a json error: {:?}", response.url(), class);
+        }
+
+        let result = class == Some(JsonError::NotFound);
+
+        if result {
+            log::debug!(
+                "filtered out {} based on its json error body (--api)",
+                response.url()
+            );
+        }
+
+        log::trace!("exit: should_filter_response -> {}", result);
+        result
+    }
+
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "JSON Error"
+    }
+
+    /// Compare one JsonErrorFilter to another
+    fn box_eq(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<Self>().map_or(false, |a| self == a)
+    }
+
+    /// Return self as Any for dynamic dispatch purposes
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
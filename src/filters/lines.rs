@@ -21,6 +21,11 @@ impl FeroxFilter for LinesFilter {
         result
     }
 
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "Line Count"
+    }
+
     /// Compare one LinesFilter to another
     fn box_eq(&self, other: &dyn Any) -> bool {
         other.downcast_ref::<Self>().map_or(false, |a| self == a)
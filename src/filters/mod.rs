@@ -5,9 +5,13 @@ use std::fmt::Debug;
 use crate::response::FeroxResponse;
 use crate::traits::{FeroxFilter, FeroxSerialize};
 
+pub use self::command::CommandFilter;
 pub use self::container::FeroxFilters;
-pub use self::init::initialize;
+pub use self::header::HeaderFilter;
+pub use self::init::{from_config, initialize};
+pub use self::json_error::JsonErrorFilter;
 pub use self::lines::LinesFilter;
+pub use self::plugin::PluginFilter;
 pub use self::regex::RegexFilter;
 pub use self::similarity::SimilarityFilter;
 pub use self::size::SizeFilter;
@@ -15,6 +19,33 @@ pub use self::status_code::StatusCodeFilter;
 pub use self::wildcard::WildcardFilter;
 pub use self::words::WordsFilter;
 
+/// Compile-time registry of every `FeroxFilter` implementor, by name; a filter's position in
+/// this list determines the order in which it's evaluated against a response. Cheap checks come
+/// first so that the more expensive ones (regex, fuzzy hashing, plugins, external commands) only
+/// run when needed
+pub const FILTER_REGISTRY: [&str; 11] = [
+    "Status Code",
+    "Size",
+    "Word Count",
+    "Line Count",
+    "Header",
+    "JSON Error",
+    "Wildcard",
+    "Regex",
+    "Similarity",
+    "Plugin",
+    "Command",
+];
+
+/// Given a filter's name, return its position in the evaluation pipeline; unregistered names
+/// are evaluated last
+pub fn filter_rank(name: &str) -> usize {
+    FILTER_REGISTRY
+        .iter()
+        .position(|registered| *registered == name)
+        .unwrap_or(FILTER_REGISTRY.len())
+}
+
 mod wildcard;
 mod status_code;
 mod words;
@@ -22,6 +53,10 @@ mod lines;
 mod size;
 mod regex;
 mod similarity;
+mod header;
+mod json_error;
+mod command;
+mod plugin;
 mod container;
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,48 @@
+use super::*;
+use crate::plugins::FeroxPlugins;
+use std::sync::Arc;
+
+/// Simple implementor of FeroxFilter; used to filter out responses based on the `on_response`
+/// hook of any WebAssembly plugins loaded via --plugin
+#[derive(Debug)]
+pub struct PluginFilter {
+    /// plugins loaded during configuration
+    pub plugins: Arc<FeroxPlugins>,
+}
+
+/// implementation of FeroxFilter for PluginFilter
+impl FeroxFilter for PluginFilter {
+    /// Pass the response to each plugin's `on_response` hook, if any plugin returns non-zero,
+    /// the response should be filtered out
+    fn should_filter_response(&self, response: &FeroxResponse) -> bool {
+        log::trace!("enter: should_filter_response({:?} {})", self, response);
+
+        let result = self.plugins.on_response(response);
+
+        log::trace!("exit: should_filter_response -> {}", result);
+        result
+    }
+
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "Plugin"
+    }
+
+    /// Compare one PluginFilter to another
+    fn box_eq(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<Self>().map_or(false, |a| self == a)
+    }
+
+    /// Return self as Any for dynamic dispatch purposes
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// PartialEq implementation for PluginFilter
+impl PartialEq for PluginFilter {
+    /// two PluginFilters are equal when they wrap the same set of loaded plugins
+    fn eq(&self, other: &PluginFilter) -> bool {
+        Arc::ptr_eq(&self.plugins, &other.plugins)
+    }
+}
@@ -26,6 +26,11 @@ impl FeroxFilter for RegexFilter {
         result
     }
 
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "Regex"
+    }
+
     /// Compare one SizeFilter to another
     fn box_eq(&self, other: &dyn Any) -> bool {
         other.downcast_ref::<Self>().map_or(false, |a| self == a)
@@ -28,6 +28,11 @@ impl FeroxFilter for SimilarityFilter {
         false
     }
 
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "Similarity"
+    }
+
     /// Compare one SimilarityFilter to another
     fn box_eq(&self, other: &dyn Any) -> bool {
         other.downcast_ref::<Self>().map_or(false, |a| self == a)
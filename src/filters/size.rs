@@ -11,16 +11,25 @@ pub struct SizeFilter {
 /// implementation of FeroxFilter for SizeFilter
 impl FeroxFilter for SizeFilter {
     /// Check `content_length` against what was passed in via -S|--filter-size
+    ///
+    /// compressed responses match on either their decoded size or their size on the wire, so
+    /// sizes taken from a proxy (or from before bodies were decoded) still filter
     fn should_filter_response(&self, response: &FeroxResponse) -> bool {
         log::trace!("enter: should_filter_response({:?} {})", self, response);
 
-        let result = response.content_length() == self.content_length;
+        let result = response.content_length() == self.content_length
+            || response.transfer_length() == self.content_length;
 
         log::trace!("exit: should_filter_response -> {}", result);
 
         result
     }
 
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "Size"
+    }
+
     /// Compare one SizeFilter to another
     fn box_eq(&self, other: &dyn Any) -> bool {
         other.downcast_ref::<Self>().map_or(false, |a| self == a)
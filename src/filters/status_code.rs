@@ -28,6 +28,11 @@ impl FeroxFilter for StatusCodeFilter {
         false
     }
 
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "Status Code"
+    }
+
     /// Compare one StatusCodeFilter to another
     fn box_eq(&self, other: &dyn Any) -> bool {
         other.downcast_ref::<Self>().map_or(false, |a| self == a)
@@ -1,6 +1,7 @@
 use super::*;
 use ::fuzzyhash::FuzzyHash;
 use ::regex::Regex;
+use reqwest::header::{HeaderMap, HeaderValue};
 
 #[test]
 /// simply test the default values for wildcardfilter, expect 0, 0
@@ -69,6 +70,26 @@ fn size_filter_as_any() {
     );
 }
 
+#[test]
+/// a compressed response should be filtered by either its decoded or its on-wire size
+fn size_filter_matches_transfer_length() {
+    let compressed = r#"{"type":"response","url":"http://localhost/","status":200,"content_length":1000,"transfer_length":120}"#;
+    let response: FeroxResponse = serde_json::from_str(compressed).unwrap();
+
+    assert!(SizeFilter {
+        content_length: 1000
+    }
+    .should_filter_response(&response));
+    assert!(SizeFilter {
+        content_length: 120
+    }
+    .should_filter_response(&response));
+    assert!(!SizeFilter {
+        content_length: 121
+    }
+    .should_filter_response(&response));
+}
+
 #[test]
 /// just a simple test to increase code coverage by hitting as_any and the inner value
 fn status_code_filter_as_any() {
@@ -121,6 +142,8 @@ fn wildcard_should_filter_when_static_wildcard_found() {
     let filter = WildcardFilter {
         size: 83,
         dynamic: 0,
+        size_range: None,
+        word_range: None,
         dont_filter: false,
     };
 
@@ -138,6 +161,8 @@ fn wildcard_should_filter_when_dynamic_wildcard_found() {
     let filter = WildcardFilter {
         size: 0,
         dynamic: 59, // content-length - 5 (len('stuff'))
+        size_range: None,
+        word_range: None,
         dont_filter: false,
     };
 
@@ -146,6 +171,33 @@ fn wildcard_should_filter_when_dynamic_wildcard_found() {
     assert!(filter.should_filter_response(&resp));
 }
 
+#[test]
+/// test should_filter on WilcardFilter where the response has to fall within both bands
+fn wildcard_should_filter_when_within_tolerance_band() {
+    let mut resp = FeroxResponse::default();
+    resp.set_wildcard(true);
+    resp.set_url("http://localhost/stuff");
+    resp.set_text("pellentesque diam volutpat commodo sed egestas egestas fringilla");
+
+    let mut filter = WildcardFilter {
+        size: u64::MAX,
+        dynamic: u64::MAX,
+        size_range: Some((60, 70)),
+        word_range: Some((7, 9)),
+        dont_filter: false,
+    };
+
+    // 64 bytes, 8 words
+    assert!(filter.should_filter_response(&resp));
+
+    filter.word_range = Some((9, 12));
+    assert!(!filter.should_filter_response(&resp));
+
+    filter.word_range = Some((7, 9));
+    filter.size_range = Some((65, 70));
+    assert!(!filter.should_filter_response(&resp));
+}
+
 #[test]
 /// test should_filter on RegexFilter where regex matches body
 fn regexfilter_should_filter_when_regex_matches_on_response_body() {
@@ -213,3 +265,227 @@ fn similarity_filter_as_any() {
         filter
     );
 }
+
+#[test]
+/// just a simple test to increase code coverage by hitting as_any and the inner value
+fn header_filter_as_any() {
+    let filter = HeaderFilter::from_raw("Server: cloudflare", false);
+    let filter2 = HeaderFilter::from_raw("Server: cloudflare", false);
+
+    assert!(filter.box_eq(filter2.as_any()));
+
+    assert_eq!(filter.name, "Server");
+    assert_eq!(filter.value, "cloudflare");
+    assert_eq!(
+        *filter.as_any().downcast_ref::<HeaderFilter>().unwrap(),
+        filter
+    );
+}
+
+#[test]
+/// test should_filter on HeaderFilter for both --filter-header and --match-header behavior
+fn header_filter_should_filter_based_on_header_presence() {
+    let mut headers = HeaderMap::new();
+    headers.insert("server", HeaderValue::from_static("cloudflare-nginx"));
+
+    let mut resp = FeroxResponse::default();
+    resp.set_url("http://localhost/stuff");
+    resp.set_headers(headers);
+
+    let filter_header = HeaderFilter::from_raw("Server: CloudFlare", false);
+    let match_header = HeaderFilter::from_raw("Server: CloudFlare", true);
+    let name_only = HeaderFilter::from_raw("server", false);
+    let missing = HeaderFilter::from_raw("X-Powered-By: PHP", false);
+    let match_missing = HeaderFilter::from_raw("X-Powered-By: PHP", true);
+
+    assert!(filter_header.should_filter_response(&resp));
+    assert!(!match_header.should_filter_response(&resp));
+    assert!(name_only.should_filter_response(&resp));
+    assert!(!missing.should_filter_response(&resp));
+    assert!(match_missing.should_filter_response(&resp));
+}
+
+#[test]
+/// JsonErrorFilter should only filter json errors saying the route doesn't exist
+fn json_error_filter_should_filter_soft_404s() {
+    let filter = JsonErrorFilter;
+
+    let mut resp = FeroxResponse::default();
+    resp.set_url("http://localhost/api/stuff");
+
+    resp.set_text(r#"{"status": 404, "error": "Not Found", "path": "/api/stuff"}"#);
+    assert!(filter.should_filter_response(&resp));
+
+    resp.set_text(r#"{"error": "Unauthorized"}"#);
+    assert!(!filter.should_filter_response(&resp));
+
+    resp.set_text(r#"{"users": []}"#);
+    assert!(!filter.should_filter_response(&resp));
+}
+
+#[test]
+/// just a simple test to increase code coverage by hitting as_any and the inner value
+fn command_filter_as_any() {
+    let filter = CommandFilter {
+        command: String::from("exit 1"),
+    };
+    let filter2 = CommandFilter {
+        command: String::from("exit 1"),
+    };
+
+    assert!(filter.box_eq(filter2.as_any()));
+
+    assert_eq!(filter.command, "exit 1");
+    assert_eq!(
+        *filter.as_any().downcast_ref::<CommandFilter>().unwrap(),
+        filter
+    );
+}
+
+#[test]
+/// CommandFilter should filter responses when the command exits non-zero and report them when
+/// the command exits successfully or can't be run
+fn command_filter_should_filter_based_on_exit_status() {
+    let mut resp = FeroxResponse::default();
+    resp.set_url("http://localhost/admin");
+    resp.set_text("super secret admin panel");
+
+    let failure = CommandFilter {
+        command: String::from("exit 1"),
+    };
+    let success = CommandFilter {
+        command: String::from("exit 0"),
+    };
+
+    assert!(failure.should_filter_response(&resp));
+    assert!(!success.should_filter_response(&resp));
+}
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+/// CommandFilter should run its command from within the runtime without stalling other tasks
+async fn command_filter_runs_within_the_runtime() {
+    let mut resp = FeroxResponse::default();
+    resp.set_url("http://localhost/admin");
+
+    let filter = CommandFilter {
+        command: String::from("sleep 1; exit 1"),
+    };
+
+    let ticker = tokio::spawn(async {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    });
+
+    assert!(filter.should_filter_response(&resp));
+    assert!(ticker.is_finished());
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+/// CommandFilter should pipe the response, including its body, to the command's stdin
+fn command_filter_pipes_response_json_to_stdin() {
+    let mut resp = FeroxResponse::default();
+    resp.set_url("http://localhost/admin");
+    resp.set_text("super secret admin panel");
+
+    let matches_body = CommandFilter {
+        command: String::from("grep -q 'super secret'"),
+    };
+    let matches_nothing = CommandFilter {
+        command: String::from("grep -q 'not in there'"),
+    };
+
+    assert!(!matches_body.should_filter_response(&resp));
+    assert!(matches_nothing.should_filter_response(&resp));
+}
+
+#[test]
+/// FeroxFilters::push should keep filters ordered by their position in FILTER_REGISTRY,
+/// regardless of the order in which they were added
+fn ferox_filters_push_orders_by_registry() {
+    let filters = FeroxFilters::default();
+
+    filters.push(Box::new(WildcardFilter::default())).unwrap();
+    filters
+        .push(Box::new(SizeFilter { content_length: 1 }))
+        .unwrap();
+    filters
+        .push(Box::new(StatusCodeFilter { filter_code: 404 }))
+        .unwrap();
+    filters
+        .push(Box::new(SizeFilter { content_length: 2 }))
+        .unwrap();
+
+    let names = filters
+        .filters
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|filter| filter.name())
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, vec!["Status Code", "Size", "Size", "Wildcard"]);
+}
+
+#[test]
+/// every FeroxFilter implementor's name should be present in FILTER_REGISTRY, and every entry in
+/// FILTER_REGISTRY should belong to an implementor
+fn filter_registry_contains_all_filters() {
+    let filters: Vec<Box<dyn FeroxFilter>> = vec![
+        Box::new(StatusCodeFilter::default()),
+        Box::new(SizeFilter::default()),
+        Box::new(WordsFilter::default()),
+        Box::new(LinesFilter::default()),
+        Box::new(HeaderFilter::default()),
+        Box::new(JsonErrorFilter),
+        Box::new(WildcardFilter::default()),
+        Box::new(RegexFilter {
+            compiled: Regex::new("^ignore me$").unwrap(),
+            raw_string: String::from("^ignore me$"),
+        }),
+        Box::new(SimilarityFilter::default()),
+        Box::new(CommandFilter::default()),
+        Box::new(PluginFilter {
+            plugins: std::sync::Arc::new(crate::plugins::FeroxPlugins::default()),
+        }),
+    ];
+
+    let mut names: Vec<_> = filters.iter().map(|filter| filter.name()).collect();
+
+    for name in &names {
+        let rank = filter_rank(name);
+        assert!(rank < FILTER_REGISTRY.len(), "{} isn't registered", name);
+        assert_eq!(FILTER_REGISTRY[rank], *name);
+    }
+
+    let mut registered = FILTER_REGISTRY.to_vec();
+    names.sort_unstable();
+    registered.sort_unstable();
+
+    assert_eq!(names, registered);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+/// FeroxFilters::should_filter_response should report which filter suppressed the response
+async fn ferox_filters_should_filter_response_reports_filter_hit() {
+    let filters = FeroxFilters::default();
+    filters
+        .push(Box::new(StatusCodeFilter { filter_code: 200 }))
+        .unwrap();
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let mut resp = FeroxResponse::default();
+    resp.set_url("http://localhost");
+
+    assert!(filters.should_filter_response(&resp, tx));
+
+    assert!(matches!(
+        rx.recv().await.unwrap(),
+        crate::Command::AddToUsizeField(crate::statistics::StatField::ResponsesFiltered, 1)
+    ));
+    assert!(matches!(
+        rx.recv().await.unwrap(),
+        crate::Command::AddFilterHit("Status Code")
+    ));
+}
@@ -9,6 +9,9 @@ use crate::url::FeroxUrl;
 ///
 /// `size` is size of the response that should be included with filters passed via runtime
 /// configuration and any static wildcard lengths.
+///
+/// `size_range` and `word_range` are tolerance bands, used when the wildcard responses varied
+/// slightly between probes (i.e. timestamps or csrf tokens); a response has to fall within both
 #[derive(Debug, Clone, PartialEq)]
 pub struct WildcardFilter {
     /// size of the response that will later be combined with the length of the path of the url
@@ -18,6 +21,12 @@ pub struct WildcardFilter {
     /// size of the response that should be included with filters passed via runtime configuration
     pub size: u64,
 
+    /// inclusive range of content lengths considered to be wildcard responses
+    pub size_range: Option<(u64, u64)>,
+
+    /// inclusive range of word counts considered to be wildcard responses
+    pub word_range: Option<(usize, usize)>,
+
     /// whether or not the user passed -D on the command line
     pub(super) dont_filter: bool,
 }
@@ -41,6 +50,8 @@ impl Default for WildcardFilter {
             dont_filter: false,
             size: u64::MAX,
             dynamic: u64::MAX,
+            size_range: None,
+            word_range: None,
         }
     }
 }
@@ -68,6 +79,20 @@ impl FeroxFilter for WildcardFilter {
             return true;
         }
 
+        if let (Some((min_size, max_size)), Some((min_words, max_words))) =
+            (self.size_range, self.word_range)
+        {
+            // tolerance band found during testing
+            let size = response.content_length();
+            let words = response.word_count();
+
+            if (min_size..=max_size).contains(&size) && (min_words..=max_words).contains(&words) {
+                log::debug!("wildcard band: filtered out {}", response.url());
+                log::trace!("exit: should_filter_response -> true");
+                return true;
+            }
+        }
+
         if self.dynamic != u64::MAX {
             // dynamic wildcard offset found during testing
 
@@ -88,6 +113,11 @@ impl FeroxFilter for WildcardFilter {
         false
     }
 
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "Wildcard"
+    }
+
     /// Compare one WildcardFilter to another
     fn box_eq(&self, other: &dyn Any) -> bool {
         other.downcast_ref::<Self>().map_or(false, |a| self == a)
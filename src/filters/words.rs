@@ -21,6 +21,11 @@ impl FeroxFilter for WordsFilter {
         result
     }
 
+    /// Name of this filter, as it appears in `FILTER_REGISTRY`
+    fn name(&self) -> &'static str {
+        "Word Count"
+    }
+
     /// Compare one WordsFilter to another
     fn box_eq(&self, other: &dyn Any) -> bool {
         other.downcast_ref::<Self>().map_or(false, |a| self == a)
@@ -0,0 +1,350 @@
+//! rules that flag interesting files, i.e. an exposed .git directory or private key
+//!
+//! a set of built-in rules is always applied; additional rules can be read from a toml file
+//! passed via --interesting-rules, where a rule with the same name as a built-in replaces it
+use std::{fmt, fs::read_to_string};
+
+use anyhow::{Context, Result};
+use console::style;
+use regex::Regex;
+use serde::Deserialize;
+
+/// How bad it is that an interesting file is exposed
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// informational, i.e. a .DS_Store file
+    Low,
+
+    /// potentially sensitive, i.e. a backup archive
+    Medium,
+
+    /// very likely sensitive, i.e. source control metadata
+    High,
+
+    /// credentials or keys
+    Critical,
+}
+
+/// implementation of Severity
+impl Severity {
+    /// name of the severity, as it appears in rules files and JSON records
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// convert a name back into a Severity; None if it's unknown
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "low" => Some(Severity::Low),
+            "medium" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+}
+
+/// Display implementation for Severity
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The rule a response matched, stored with the response
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// name of the rule that matched
+    pub name: String,
+
+    /// severity of the rule that matched
+    pub severity: Severity,
+}
+
+/// implementation of Finding
+impl Finding {
+    /// highlighted tag shown after the url in normal output, i.e. `[!high: Git repository]`
+    pub fn tag(&self) -> String {
+        let tag = format!("[!{}: {}]", self.severity, self.name);
+
+        let styled = match self.severity {
+            Severity::Low => style(tag).cyan(),
+            Severity::Medium => style(tag).yellow(),
+            Severity::High | Severity::Critical => style(tag).red(),
+        };
+
+        styled.bold().to_string()
+    }
+}
+
+/// A single interesting file rule
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// name of the rule, shown in output and JSON records
+    pub name: String,
+
+    /// severity given to responses that match
+    pub severity: Severity,
+
+    /// matched against the path of the response's url
+    pub path: Regex,
+
+    /// when given, also has to match the response body; keeps custom 404 pages from matching
+    pub body: Option<Regex>,
+}
+
+/// implementation of Rule
+impl Rule {
+    /// create a new rule, compiling the given regular expressions
+    pub fn new(name: &str, severity: Severity, path: &str, body: Option<&str>) -> Result<Self> {
+        let path = Regex::new(path).with_context(|| format!("Invalid path regex in {}", name))?;
+
+        let body = match body {
+            Some(body) => {
+                Some(Regex::new(body).with_context(|| format!("Invalid body regex in {}", name))?)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            severity,
+            path,
+            body,
+        })
+    }
+
+    /// determine whether or not the given response matches this rule
+    pub fn matches(&self, path: &str, text: &str) -> bool {
+        self.path.is_match(path) && self.body.as_ref().map_or(true, |body| body.is_match(text))
+    }
+}
+
+/// (name, severity, path regex, body regex) of every built-in rule
+const BUILTIN_RULES: [(&str, Severity, &str, Option<&str>); 10] = [
+    (
+        "Git repository",
+        Severity::High,
+        r"/\.git/(HEAD|config)$",
+        Some(r"^ref: |\[core\]"),
+    ),
+    (
+        "Subversion repository",
+        Severity::High,
+        r"/\.svn/(entries|wc\.db)$",
+        None,
+    ),
+    (
+        "Environment file",
+        Severity::High,
+        r"/\.env(\.[\w-]+)?$",
+        Some(r"(?m)^\s*[A-Za-z_][A-Za-z0-9_]*\s*="),
+    ),
+    (
+        "Private key",
+        Severity::Critical,
+        r"/(id_rsa|id_dsa|id_ecdsa|id_ed25519|[^/]+\.pem|[^/]+\.key)$",
+        Some(r"-----BEGIN [A-Z ]*PRIVATE KEY-----"),
+    ),
+    (
+        "Password file",
+        Severity::Critical,
+        r"/\.htpasswd$",
+        Some(r"(?m)^[^:\s]+:"),
+    ),
+    (
+        "IIS configuration",
+        Severity::Medium,
+        r"(?i)/web\.config$",
+        Some(r"<configuration"),
+    ),
+    (
+        "PHP info",
+        Severity::Medium,
+        r"/[^/]*\.php$",
+        Some(r"<title>phpinfo\(\)</title>"),
+    ),
+    (
+        "Database dump",
+        Severity::High,
+        r"(?i)\.(sql|sqlite|db|mdb)(\.gz)?$",
+        None,
+    ),
+    (
+        "Backup archive",
+        Severity::Medium,
+        r"(?i)\.(zip|tar|tar\.gz|tgz|rar|7z|bak|old|swp|orig)$",
+        None,
+    ),
+    ("macOS metadata", Severity::Low, r"/\.DS_Store$", None),
+];
+
+/// One rule in a --interesting-rules file
+#[derive(Debug, Deserialize)]
+struct UserRule {
+    /// name of the rule
+    name: String,
+
+    /// low, medium, high, or critical
+    severity: Severity,
+
+    /// regex matched against the url's path
+    path: String,
+
+    /// optional regex matched against the body
+    body: Option<String>,
+}
+
+/// Layout of a --interesting-rules file
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    /// every rule in the file
+    #[serde(default)]
+    rules: Vec<UserRule>,
+}
+
+/// All built-in rules
+pub fn builtin_rules() -> Vec<Rule> {
+    BUILTIN_RULES
+        .iter()
+        .filter_map(|(name, severity, path, body)| Rule::new(name, *severity, path, *body).ok())
+        .collect()
+}
+
+/// Read the rules file passed in via --interesting-rules, combining it with the built-in rules
+///
+/// the file is expected to be toml, where each rule is an entry in the `rules` array, i.e.
+///
+/// ```toml
+/// [[rules]]
+/// name = "Terraform state"
+/// severity = "critical"
+/// path = '\.tfstate$'
+/// body = '"terraform_version"'
+/// ```
+pub fn read_rules(path: &str) -> Result<Vec<Rule>> {
+    log::trace!("enter: read_rules({})", path);
+
+    let contents = read_to_string(path).with_context(|| format!("Could not open {}", path))?;
+    let file: RulesFile =
+        toml::from_str(&contents).with_context(|| format!("Could not parse {}", path))?;
+
+    let mut rules = builtin_rules();
+
+    for user_rule in file.rules {
+        let rule = Rule::new(
+            &user_rule.name,
+            user_rule.severity,
+            &user_rule.path,
+            user_rule.body.as_deref(),
+        )?;
+
+        match rules.iter_mut().find(|known| known.name == rule.name) {
+            Some(known) => *known = rule,
+            None => rules.push(rule),
+        }
+    }
+
+    log::trace!("exit: read_rules -> {} rules", rules.len());
+    Ok(rules)
+}
+
+/// Find the most severe rule matched by a successful response with the given path and body
+pub fn check(rules: &[Rule], path: &str, text: &str) -> Option<Finding> {
+    rules
+        .iter()
+        .filter(|rule| rule.matches(path, text))
+        .max_by_key(|rule| rule.severity)
+        .map(|rule| Finding {
+            name: rule.name.clone(),
+            severity: rule.severity,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// every built-in rule should compile
+    fn builtin_rules_compile() {
+        assert_eq!(builtin_rules().len(), BUILTIN_RULES.len());
+    }
+
+    #[test]
+    /// built-in rules should require body matches where given, and pick the most severe rule
+    fn check_flags_interesting_files() {
+        let rules = builtin_rules();
+
+        let finding = check(&rules, "/.git/HEAD", "ref: refs/heads/main\n").unwrap();
+        assert_eq!(finding.name, "Git repository");
+        assert_eq!(finding.severity, Severity::High);
+
+        // custom 404 served for .git/HEAD
+        assert!(check(&rules, "/.git/HEAD", "<html>not found</html>").is_none());
+
+        let finding = check(&rules, "/.env", "DB_PASSWORD=hunter2\n").unwrap();
+        assert_eq!(finding.severity, Severity::High);
+
+        let finding = check(&rules, "/backup/site.tar.gz", "").unwrap();
+        assert_eq!(finding.name, "Backup archive");
+
+        assert!(check(&rules, "/index.html", "<html></html>").is_none());
+    }
+
+    #[test]
+    /// user rules should be added to the built-ins, replacing those with the same name
+    fn read_rules_combines_with_builtins() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            r#"
+            [[rules]]
+            name = "Terraform state"
+            severity = "critical"
+            path = '\.tfstate$'
+
+            [[rules]]
+            name = "macOS metadata"
+            severity = "medium"
+            path = '/\.DS_Store$'
+            "#,
+        )
+        .unwrap();
+
+        let rules = read_rules(tmp.path().to_str().unwrap()).unwrap();
+        assert_eq!(rules.len(), BUILTIN_RULES.len() + 1);
+
+        let finding = check(&rules, "/terraform.tfstate", "").unwrap();
+        assert_eq!(finding.severity, Severity::Critical);
+
+        let finding = check(&rules, "/.DS_Store", "").unwrap();
+        assert_eq!(finding.severity, Severity::Medium);
+    }
+
+    #[test]
+    /// read_rules should error on invalid regular expressions and unknown severities
+    fn read_rules_errors_on_bad_rules() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+
+        std::fs::write(
+            tmp.path(),
+            "[[rules]]\nname = \"bad\"\nseverity = \"high\"\npath = '('\n",
+        )
+        .unwrap();
+        assert!(read_rules(tmp.path().to_str().unwrap()).is_err());
+
+        std::fs::write(
+            tmp.path(),
+            "[[rules]]\nname = \"bad\"\nseverity = \"urgent\"\npath = 'x'\n",
+        )
+        .unwrap();
+        assert!(read_rules(tmp.path().to_str().unwrap()).is_err());
+    }
+}
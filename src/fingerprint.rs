@@ -0,0 +1,315 @@
+//! lightweight technology fingerprinting of responses (--fingerprint)
+use std::{collections::HashMap, fs::read_to_string, sync::RwLock};
+
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+
+use crate::response::FeroxResponse;
+
+lazy_static! {
+    /// extensions added to the scan based on detected technologies (--auto-extensions)
+    static ref AUTO_EXTENSIONS: AutoExtensions = AutoExtensions::default();
+}
+
+/// A single technology signature; a response matches if any one of its indicators is present
+pub struct Signature {
+    /// name of the technology, shown in output and JSON records
+    pub name: &'static str,
+
+    /// (header name, substring of header value) pairs, case-insensitive
+    pub headers: &'static [(&'static str, &'static str)],
+
+    /// substrings of the response body, case-sensitive
+    pub body: &'static [&'static str],
+
+    /// extensions commonly served by the technology, used by --auto-extensions
+    pub extensions: &'static [&'static str],
+}
+
+/// All known technology signatures
+pub const SIGNATURES: [Signature; 14] = [
+    Signature {
+        name: "PHP",
+        headers: &[("x-powered-by", "php"), ("set-cookie", "phpsessid")],
+        body: &[],
+        extensions: &["php"],
+    },
+    Signature {
+        name: "ASP.NET",
+        headers: &[
+            ("x-powered-by", "asp.net"),
+            ("x-aspnet-version", ""),
+            ("x-aspnetmvc-version", ""),
+            ("set-cookie", "asp.net_sessionid"),
+        ],
+        body: &["__VIEWSTATE"],
+        extensions: &["aspx", "asp", "ashx"],
+    },
+    Signature {
+        name: "IIS",
+        headers: &[("server", "microsoft-iis")],
+        body: &[],
+        extensions: &["asp", "aspx"],
+    },
+    Signature {
+        name: "Apache",
+        headers: &[("server", "apache")],
+        body: &[],
+        extensions: &[],
+    },
+    Signature {
+        name: "Nginx",
+        headers: &[("server", "nginx")],
+        body: &[],
+        extensions: &[],
+    },
+    Signature {
+        name: "Tomcat",
+        headers: &[("server", "tomcat")],
+        body: &["Apache Tomcat"],
+        extensions: &["jsp", "do"],
+    },
+    Signature {
+        name: "Java",
+        headers: &[("set-cookie", "jsessionid"), ("x-powered-by", "servlet")],
+        body: &[],
+        extensions: &["jsp", "do", "action"],
+    },
+    Signature {
+        name: "ColdFusion",
+        headers: &[("set-cookie", "cfid"), ("set-cookie", "cftoken")],
+        body: &[],
+        extensions: &["cfm", "cfc"],
+    },
+    Signature {
+        name: "WordPress",
+        headers: &[("link", "wp-json")],
+        body: &["/wp-content/", "/wp-includes/"],
+        extensions: &["php"],
+    },
+    Signature {
+        name: "Drupal",
+        headers: &[("x-generator", "drupal"), ("x-drupal-cache", "")],
+        body: &["Drupal.settings", "/sites/default/files/"],
+        extensions: &["php"],
+    },
+    Signature {
+        name: "Joomla",
+        headers: &[],
+        body: &["/media/jui/", "content=\"Joomla!"],
+        extensions: &["php"],
+    },
+    Signature {
+        name: "Express",
+        headers: &[("x-powered-by", "express")],
+        body: &[],
+        extensions: &[],
+    },
+    Signature {
+        name: "Django",
+        headers: &[("set-cookie", "csrftoken")],
+        body: &["csrfmiddlewaretoken"],
+        extensions: &[],
+    },
+    Signature {
+        name: "Ruby on Rails",
+        headers: &[("x-runtime", ""), ("set-cookie", "_session_id")],
+        body: &["csrf-param"],
+        extensions: &[],
+    },
+];
+
+/// implementation of Signature
+impl Signature {
+    /// determine whether or not the given response matches this signature
+    pub fn matches(&self, response: &FeroxResponse) -> bool {
+        let header_match = self.headers.iter().any(|(name, expected)| {
+            response.headers().get_all(*name).iter().any(|value| {
+                String::from_utf8_lossy(value.as_bytes())
+                    .to_lowercase()
+                    .contains(expected)
+            })
+        });
+
+        header_match
+            || self
+                .body
+                .iter()
+                .any(|needle| response.text().contains(needle))
+    }
+}
+
+/// Examine the given response's headers and body, returning the names of all technologies
+/// detected
+pub fn fingerprint(response: &FeroxResponse) -> Vec<String> {
+    log::trace!("enter: fingerprint({})", response);
+
+    let detected: Vec<String> = SIGNATURES
+        .iter()
+        .filter(|signature| signature.matches(response))
+        .map(|signature| signature.name.to_string())
+        .collect();
+
+    log::trace!("exit: fingerprint -> {:?}", detected);
+    detected
+}
+
+/// Extensions added based on detected technologies (--auto-extensions)
+#[derive(Debug, Default)]
+pub struct AutoExtensions {
+    /// every extension added so far, in the order they were added
+    extensions: RwLock<Vec<String>>,
+}
+
+/// implementation of AutoExtensions
+impl AutoExtensions {
+    /// Given detected technologies, add their associated extensions; returns only the
+    /// extensions that were newly added
+    ///
+    /// extensions already passed in via -x|--extensions are ignored
+    pub fn add(&self, technologies: &[String], user_extensions: &[String]) -> Vec<String> {
+        let mut added = Vec::new();
+
+        if let Ok(mut guard) = self.extensions.write() {
+            for signature in SIGNATURES
+                .iter()
+                .filter(|sig| technologies.iter().any(|tech| tech == sig.name))
+            {
+                for ext in signature.extensions {
+                    let ext = ext.to_string();
+
+                    if user_extensions.contains(&ext) || guard.contains(&ext) {
+                        continue;
+                    }
+
+                    guard.push(ext.clone());
+                    added.push(ext);
+                }
+            }
+        }
+
+        added
+    }
+
+    /// Current set of added extensions, excluding any already passed in via -x|--extensions
+    pub fn current(&self, user_extensions: &[String]) -> Vec<String> {
+        match self.extensions.read() {
+            Ok(guard) => guard
+                .iter()
+                .filter(|ext| !user_extensions.contains(ext))
+                .cloned()
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// Given detected technologies, add their associated extensions to those that will be used for
+/// directory scans started from here on out; returns only the extensions that were newly added
+///
+/// extensions already passed in via -x|--extensions are ignored
+pub fn add_auto_extensions(technologies: &[String], user_extensions: &[String]) -> Vec<String> {
+    AUTO_EXTENSIONS.add(technologies, user_extensions)
+}
+
+/// Current set of automatically added extensions, excluding any already passed in via
+/// -x|--extensions
+pub fn auto_extensions(user_extensions: &[String]) -> Vec<String> {
+    AUTO_EXTENSIONS.current(user_extensions)
+}
+
+/// Read the technology -> wordlist mapping passed in via --auto-wordlist
+///
+/// the file is expected to be toml, where each key is a technology name as it appears in
+/// `SIGNATURES` and each value is the path to a wordlist, i.e. `Tomcat = "/wordlists/tomcat.txt"`
+pub fn read_auto_wordlists(path: &str) -> Result<HashMap<String, String>> {
+    log::trace!("enter: read_auto_wordlists({})", path);
+
+    let contents = read_to_string(path).with_context(|| format!("Could not open {}", path))?;
+    let mapping: HashMap<String, String> =
+        toml::from_str(&contents).with_context(|| format!("Could not parse {}", path))?;
+
+    for technology in mapping.keys() {
+        if !SIGNATURES.iter().any(|sig| sig.name == technology) {
+            log::warn!("{} maps unknown technology {}", path, technology);
+        }
+    }
+
+    log::trace!("exit: read_auto_wordlists -> {:?}", mapping);
+    Ok(mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    #[test]
+    /// headers should be matched case-insensitively against signatures
+    fn fingerprint_detects_technologies_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Server", HeaderValue::from_static("Microsoft-IIS/10.0"));
+        headers.insert("X-Powered-By", HeaderValue::from_static("ASP.NET"));
+
+        let mut resp = FeroxResponse::default();
+        resp.set_headers(headers);
+
+        assert_eq!(fingerprint(&resp), vec!["ASP.NET", "IIS"]);
+    }
+
+    #[test]
+    /// body indicators should be matched against signatures
+    fn fingerprint_detects_technologies_from_body() {
+        let mut resp = FeroxResponse::default();
+        resp.set_text(r#"<link rel="stylesheet" href="/wp-content/themes/style.css">"#);
+
+        assert_eq!(fingerprint(&resp), vec!["WordPress"]);
+    }
+
+    #[test]
+    /// a response without any indicators shouldn't be tagged
+    fn fingerprint_returns_empty_when_nothing_detected() {
+        let resp = FeroxResponse::default();
+        assert!(fingerprint(&resp).is_empty());
+    }
+
+    #[test]
+    /// AutoExtensions::add should only report new extensions that the user didn't already pass in
+    fn add_auto_extensions_ignores_known_extensions() {
+        let auto = AutoExtensions::default();
+        let user_extensions = vec![String::from("cfm")];
+        let technologies = vec![String::from("ColdFusion")];
+
+        let added = auto.add(&technologies, &user_extensions);
+        assert_eq!(added, vec!["cfc"]);
+
+        // second call with the same technologies shouldn't add anything
+        assert!(auto.add(&technologies, &user_extensions).is_empty());
+
+        assert_eq!(auto.current(&user_extensions), vec!["cfc"]);
+        assert!(auto.current(&[String::from("cfc")]).is_empty());
+    }
+
+    #[test]
+    /// read_auto_wordlists should parse a technology -> wordlist mapping from toml
+    fn read_auto_wordlists_parses_mapping() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            "Tomcat = \"/wordlists/tomcat.txt\"\n\"ASP.NET\" = \"/wordlists/iis.txt\"\n",
+        )
+        .unwrap();
+
+        let mapping = read_auto_wordlists(tmp.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(mapping.len(), 2);
+        assert_eq!(mapping["Tomcat"], "/wordlists/tomcat.txt");
+        assert_eq!(mapping["ASP.NET"], "/wordlists/iis.txt");
+    }
+
+    #[test]
+    /// read_auto_wordlists should error on a missing file
+    fn read_auto_wordlists_errors_on_missing_file() {
+        assert!(read_auto_wordlists("/nonexistent/auto-wordlists.toml").is_err());
+    }
+}
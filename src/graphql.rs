@@ -0,0 +1,252 @@
+//! GraphQL endpoint detection and introspection
+//!
+//! once a path like /graphql or /api/graphql responds, an introspection query is sent to it; an
+//! endpoint that answers with its schema hands out every query, mutation, and type it supports,
+//! so it's reported as a high severity finding, and the schema is saved to --output-dir (if given)
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    sync::{Arc, Mutex},
+};
+
+use lazy_static::lazy_static;
+use reqwest::{Method, StatusCode, Url};
+use serde_json::Value;
+
+use crate::{
+    config::Configuration,
+    event_handlers::{
+        Command::{self, AddToUsizeField},
+        Handles,
+    },
+    findings::{Finding, Severity},
+    output_dir::graphql_schema_file,
+    response::FeroxResponse,
+    scanner::RateGate,
+    send_command,
+    statistics::StatField::TotalExpected,
+    utils::body_request,
+};
+
+/// last path segments that identify a GraphQL endpoint, i.e. /graphql or /api/graphql
+const ENDPOINT_NAMES: [&str; 3] = ["graphql", "graphiql", "gql"];
+
+/// name of the finding reported for endpoints that allow introspection
+pub const FINDING_NAME: &str = "GraphQL introspection";
+
+/// standard introspection query; asks for every type, along with its fields and arguments
+const INTROSPECTION_QUERY: &str = "query IntrospectionQuery { __schema { queryType { name } \
+    mutationType { name } subscriptionType { name } types { ...FullType } directives { name \
+    description locations args { ...InputValue } } } } fragment FullType on __Type { kind name \
+    description fields(includeDeprecated: true) { name description args { ...InputValue } type { \
+    ...TypeRef } isDeprecated deprecationReason } inputFields { ...InputValue } interfaces { \
+    ...TypeRef } enumValues(includeDeprecated: true) { name description isDeprecated \
+    deprecationReason } possibleTypes { ...TypeRef } } fragment InputValue on __InputValue { name \
+    description type { ...TypeRef } defaultValue } fragment TypeRef on __Type { kind name ofType { \
+    kind name ofType { kind name ofType { kind name ofType { kind name } } } } }";
+
+lazy_static! {
+    /// endpoints that were already sent an introspection query
+    static ref PROBED: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// A GraphQL endpoint that answered an introspection query with its schema
+#[derive(Debug, Clone)]
+pub struct Introspection {
+    /// url of the endpoint
+    pub url: String,
+
+    /// number of types in the schema
+    pub types: usize,
+
+    /// file the schema was saved to, empty when --output-dir wasn't used
+    pub schema_file: String,
+}
+
+/// implementation of Introspection
+impl Introspection {
+    /// finding reported along with the endpoint
+    pub fn finding(&self) -> Finding {
+        Finding {
+            name: FINDING_NAME.to_string(),
+            severity: Severity::High,
+        }
+    }
+}
+
+/// Display implementation for Introspection
+impl fmt::Display for Introspection {
+    /// endpoint, size of its schema, and where it was saved, i.e.
+    /// http://localhost/graphql (42 types; schema saved to out/http_localhost/graphql/graphql.json)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.schema_file.is_empty() {
+            write!(f, "{} ({} types)", self.url, self.types)
+        } else {
+            write!(
+                f,
+                "{} ({} types; schema saved to {})",
+                self.url, self.types, self.schema_file
+            )
+        }
+    }
+}
+
+/// Determine whether the given url looks like a GraphQL endpoint, going by its last path segment
+pub fn is_endpoint(url: &Url) -> bool {
+    url.path()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .map_or(false, |last| {
+            ENDPOINT_NAMES.contains(&last.to_lowercase().as_str())
+        })
+}
+
+/// Determine whether an introspection query should be sent to the endpoint that gave the given
+/// response; anything that answered with a 404 isn't an endpoint, and neither is anything whose
+/// status code isn't reported (-s)
+pub fn should_probe(response: &FeroxResponse, config: &Configuration) -> bool {
+    let status = response.status();
+
+    status != &StatusCode::NOT_FOUND
+        && config
+            .status_codes_for(response.extension())
+            .contains(&status.as_u16())
+        && is_endpoint(response.url())
+}
+
+/// Schema within the given response to an introspection query, along with its number of types;
+/// None when the body isn't json, or introspection is disabled (the usual response is an error)
+pub fn schema(body: &str) -> Option<(Value, usize)> {
+    let value: Value = serde_json::from_str(body).ok()?;
+
+    let schema = value.get("data")?.get("__schema")?;
+    let types = schema.get("types")?.as_array()?.len();
+
+    Some((schema.clone(), types))
+}
+
+/// Send an introspection query to the given endpoint, once the gate allows it, reporting the
+/// endpoint if its schema comes back; nothing is probed more than once
+pub async fn probe(mut url: Url, gate: RateGate, handles: Arc<Handles>) {
+    log::trace!("enter: probe({})", url);
+
+    url.set_query(None);
+    url.set_fragment(None);
+
+    match PROBED.lock() {
+        Ok(mut guard) => {
+            if !guard.insert(url.to_string()) {
+                return;
+            }
+        }
+        Err(_) => return,
+    }
+
+    send_command!(handles.stats.tx, AddToUsizeField(TotalExpected, 1));
+
+    let body = serde_json::json!({ "query": INTROSPECTION_QUERY }).to_string();
+    let headers = [("Content-Type", String::from("application/json"))];
+
+    gate.wait().await;
+
+    let answer = match body_request(Method::POST, &url, &headers, body, &handles).await {
+        Ok(answer) => FeroxResponse::from(answer, true, handles.config.output_level).await,
+        Err(_) => return,
+    };
+
+    let (schema, types) = match schema(answer.text()) {
+        Some(found) => found,
+        None => {
+            log::info!("{} doesn't allow introspection", url);
+            log::trace!("exit: probe");
+            return;
+        }
+    };
+
+    let mut schema_file = String::new();
+
+    if !handles.config.output_dir.is_empty() {
+        let saved = graphql_schema_file(&handles.config.output_dir, &url).and_then(|path| {
+            let pretty = serde_json::to_string_pretty(&schema)?;
+            fs::write(&path, pretty)?;
+            Ok(path)
+        });
+
+        match saved {
+            Ok(path) => schema_file = path,
+            Err(e) => log::warn!("Could not save the schema of {}: {}", url, e),
+        }
+    }
+
+    let introspection = Introspection {
+        url: url.to_string(),
+        types,
+        schema_file,
+    };
+
+    log::info!("introspection allowed at {}", introspection);
+
+    if let Err(e) = handles
+        .output
+        .send(Command::ReportIntrospection(Box::new(introspection)))
+    {
+        log::warn!("Could not send introspection to output handler: {}", e);
+    }
+
+    log::trace!("exit: probe");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// only the last path segment should decide whether a url is a GraphQL endpoint
+    fn is_endpoint_checks_last_segment() {
+        let is = |url: &str| is_endpoint(&Url::parse(url).unwrap());
+
+        assert!(is("http://localhost/graphql"));
+        assert!(is("http://localhost/api/graphql/"));
+        assert!(is("http://localhost/v1/GraphiQL?x=1"));
+        assert!(!is("http://localhost/graphql/schema.json"));
+        assert!(!is("http://localhost/graphqlx"));
+        assert!(!is("http://localhost/"));
+    }
+
+    #[test]
+    /// only endpoints whose status code is reported should be probed
+    fn should_probe_respects_status_codes() {
+        let mut response = FeroxResponse::default();
+        response.set_url("http://localhost/graphql");
+        response.set_status(StatusCode::OK);
+
+        let mut config = Configuration::default();
+        assert!(should_probe(&response, &config));
+
+        config.status_codes = vec![401];
+        assert!(!should_probe(&response, &config));
+
+        response.set_status(StatusCode::NOT_FOUND);
+        config.status_codes = vec![404];
+        assert!(!should_probe(&response, &config));
+    }
+
+    #[test]
+    /// a schema should only be found in a successful introspection response
+    fn schema_requires_introspection_data() {
+        let (schema, types) = schema(
+            r#"{"data": {"__schema": {"queryType": {"name": "Query"},
+                "types": [{"kind": "OBJECT", "name": "Query"}, {"kind": "SCALAR", "name": "ID"}]}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(types, 2);
+        assert_eq!(schema["queryType"]["name"], "Query");
+
+        assert!(
+            super::schema(r#"{"errors": [{"message": "introspection is disabled"}]}"#).is_none()
+        );
+        assert!(super::schema("<html>GraphQL Playground</html>").is_none());
+    }
+}
@@ -0,0 +1,317 @@
+//! grouping of near-identical responses in output (--group-similar)
+//!
+//! templated applications answer many different paths with the same page, give or take whatever
+//! part of the path they reflect back; once enough responses with the same status, template, and
+//! roughly the same length have been shown, the rest of the group is collapsed into a single
+//! summary shown at the end of the scan (or when it's interrupted)
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::{
+    config::OutputLevel,
+    response::FeroxResponse,
+    traits::FeroxSerialize,
+    utils::{create_report_string, fmt_err},
+};
+
+/// most a response's content length can differ, in bytes, from that of the first response of a
+/// group and still be part of it
+pub const LENGTH_TOLERANCE: u64 = 100;
+
+/// most urls kept for each group; the rest are only counted
+pub const MAX_GROUP_URLS: usize = 100;
+
+/// what similar responses have in common, besides their length; a response's template is
+/// approximated by its line count and title, neither of which change when all that differs is a
+/// reflected path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct GroupKey {
+    /// status code
+    status: u16,
+
+    /// number of lines in the body
+    line_count: usize,
+
+    /// contents of the body's `<title>` tag, if any
+    title: String,
+}
+
+/// implementation of GroupKey
+impl GroupKey {
+    /// the key of the given response
+    fn new(response: &FeroxResponse) -> Self {
+        Self {
+            status: response.status().as_u16(),
+            line_count: response.line_count(),
+            title: response.title().to_string(),
+        }
+    }
+}
+
+/// Reported responses that share a status and template, and whose lengths are within
+/// LENGTH_TOLERANCE of the first one's
+#[derive(Debug, Clone, Serialize)]
+pub struct ResponseGroup {
+    /// always "group"
+    #[serde(rename = "type")]
+    kind: String,
+
+    /// status code of every response in the group
+    pub status: u16,
+
+    /// smallest content length within the group
+    pub min_length: u64,
+
+    /// largest content length within the group
+    pub max_length: u64,
+
+    /// number of lines in the body of every response in the group
+    pub line_count: usize,
+
+    /// title of every response in the group, empty if they don't have one
+    pub title: String,
+
+    /// number of responses in the group
+    pub count: usize,
+
+    /// number of responses in the group that weren't shown on their own
+    pub collapsed: usize,
+
+    /// url of the first MAX_GROUP_URLS responses in the group, in the order they were reported
+    pub urls: Vec<String>,
+
+    /// content length of the group's first response, which the others are compared against
+    #[serde(skip)]
+    anchor: u64,
+
+    /// how the summary is shown, based on -q|--quiet
+    #[serde(skip)]
+    output_level: OutputLevel,
+}
+
+/// implementation of ResponseGroup
+impl ResponseGroup {
+    /// an empty group for responses with the given key, whose first response has the given length
+    fn new(key: &GroupKey, length: u64, output_level: OutputLevel) -> Self {
+        Self {
+            kind: "group".to_string(),
+            status: key.status,
+            min_length: length,
+            max_length: length,
+            line_count: key.line_count,
+            title: key.title.clone(),
+            count: 0,
+            collapsed: 0,
+            urls: Vec::new(),
+            anchor: length,
+            output_level,
+        }
+    }
+
+    /// whether a response with the given length belongs in this group
+    fn fits(&self, length: u64) -> bool {
+        length.max(self.anchor) - length.min(self.anchor) <= LENGTH_TOLERANCE
+    }
+}
+
+/// FeroxSerialize implementation for ResponseGroup
+impl FeroxSerialize for ResponseGroup {
+    /// the group's status, lines, and length range, followed by its first url and counts, i.e.
+    /// 200       31l        -w 1204-1262c http://localhost/a "Welcome" (found 48 similar
+    /// responses, 45 collapsed)
+    fn as_str(&self) -> String {
+        let mut summary = self.urls.first().cloned().unwrap_or_default();
+
+        if !self.title.is_empty() {
+            summary.push_str(&format!(" \"{}\"", self.title));
+        }
+
+        summary.push_str(&format!(
+            " (found {} similar responses, {} collapsed)",
+            self.count, self.collapsed
+        ));
+
+        create_report_string(
+            &self.status.to_string(),
+            &self.line_count.to_string(),
+            "-",
+            &format!("{}-{}", self.min_length, self.max_length),
+            &summary,
+            self.output_level,
+        )
+    }
+
+    /// NDJSON representation of the group, including the urls kept for it
+    fn as_json(&self) -> Result<String> {
+        let mut json = serde_json::to_string(&self)
+            .with_context(|| fmt_err("Could not convert response group to JSON"))?;
+        json.push('\n');
+        Ok(json)
+    }
+}
+
+/// Every group of responses reported so far
+#[derive(Debug, Default)]
+pub struct ResponseGroups {
+    /// number of responses shown from each group before the rest are collapsed; 0 means
+    /// responses are never collapsed
+    threshold: usize,
+
+    /// how group summaries are shown
+    output_level: OutputLevel,
+
+    /// position within `groups` of each group with a given key
+    index: HashMap<GroupKey, Vec<usize>>,
+
+    /// groups in the order their first response was reported
+    groups: Vec<ResponseGroup>,
+}
+
+/// implementation of ResponseGroups
+impl ResponseGroups {
+    /// Group responses, collapsing all but the first `threshold` of each group
+    pub fn new(threshold: usize, output_level: OutputLevel) -> Self {
+        Self {
+            threshold,
+            output_level,
+            ..Default::default()
+        }
+    }
+
+    /// Add the given response to its group, returning whether it should still be shown on its own
+    ///
+    /// wildcards and responses that matched an interesting file rule are always shown, and
+    /// aren't added to any group
+    pub fn add(&mut self, response: &FeroxResponse) -> bool {
+        if self.threshold == 0 || response.wildcard() || response.finding().is_some() {
+            return true;
+        }
+
+        let key = GroupKey::new(response);
+        let length = response.content_length();
+
+        let groups = &mut self.groups;
+        let positions = self.index.entry(key.clone()).or_default();
+
+        let found = positions
+            .iter()
+            .copied()
+            .find(|position| groups[*position].fits(length));
+
+        let position = match found {
+            Some(position) => position,
+            None => {
+                groups.push(ResponseGroup::new(&key, length, self.output_level));
+                positions.push(groups.len() - 1);
+                groups.len() - 1
+            }
+        };
+
+        let group = &mut groups[position];
+        group.count += 1;
+        group.min_length = group.min_length.min(length);
+        group.max_length = group.max_length.max(length);
+
+        if group.urls.len() < MAX_GROUP_URLS {
+            group.urls.push(response.url().to_string());
+        }
+
+        if group.count > self.threshold {
+            group.collapsed += 1;
+            return false;
+        }
+
+        true
+    }
+
+    /// Groups from which at least one response was collapsed, in the order they were first seen
+    pub fn collapsed(&self) -> impl Iterator<Item = &ResponseGroup> {
+        self.groups.iter().filter(|group| group.collapsed > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::response;
+
+    #[test]
+    /// responses past the threshold of their group should be collapsed, other groups unaffected
+    fn response_groups_collapse_past_threshold() {
+        let mut groups = ResponseGroups::new(2, OutputLevel::Default);
+        let page = |path: &str| format!("<h1>Not Here</h1>\nyou asked for {}", path);
+
+        for path in &["a", "bb", "ccc", "dddd"] {
+            let url = format!("http://localhost/{}", path);
+            let shown = groups.add(&response(&url, 200, &page(path)));
+            assert_eq!(shown, path.len() <= 2);
+        }
+
+        // different status, different group
+        assert!(groups.add(&response("http://localhost/x", 403, &page("x"))));
+
+        let collapsed: Vec<_> = groups.collapsed().collect();
+        assert_eq!(collapsed.len(), 1);
+        assert_eq!(collapsed[0].count, 4);
+        assert_eq!(collapsed[0].collapsed, 2);
+        assert_eq!(collapsed[0].line_count, 2);
+        assert_eq!(collapsed[0].urls[3], "http://localhost/dddd");
+
+        let summary = console::strip_ansi_codes(&collapsed[0].as_str()).to_string();
+        assert!(
+            summary.contains("33-36c http://localhost/a (found 4 similar responses, 2 collapsed)")
+        );
+
+        let json = collapsed[0].as_json().unwrap();
+        assert!(json.starts_with(r#"{"type":"group","status":200,"min_length":33"#));
+    }
+
+    #[test]
+    /// lengths are compared with the group's first response, rather than split into fixed bands,
+    /// and only the first MAX_GROUP_URLS urls are kept
+    fn response_groups_compare_lengths_and_cap_urls() {
+        let mut groups = ResponseGroups::new(1, OutputLevel::Default);
+
+        // either side of what used to be a band boundary
+        assert!(groups.add(&response("http://localhost/a", 200, &"a".repeat(95))));
+        assert!(!groups.add(&response("http://localhost/b", 200, &"b".repeat(105))));
+
+        // too far from the first response's length
+        assert!(groups.add(&response("http://localhost/c", 200, &"c".repeat(300))));
+
+        for index in 0..MAX_GROUP_URLS {
+            let url = format!("http://localhost/{}", index);
+            groups.add(&response(&url, 200, &"d".repeat(100)));
+        }
+
+        let collapsed: Vec<_> = groups.collapsed().collect();
+        assert_eq!(collapsed.len(), 1);
+        assert_eq!(collapsed[0].count, MAX_GROUP_URLS + 2);
+        assert_eq!(
+            (collapsed[0].min_length, collapsed[0].max_length),
+            (95, 105)
+        );
+        assert_eq!(collapsed[0].urls.len(), MAX_GROUP_URLS);
+        assert_eq!(collapsed[0].urls[1], "http://localhost/b");
+    }
+
+    #[test]
+    /// nothing should be collapsed with a threshold of 0, nor wildcards with any threshold
+    fn response_groups_skip_wildcards_and_disabled() {
+        let mut disabled = ResponseGroups::new(0, OutputLevel::Default);
+        let mut groups = ResponseGroups::new(1, OutputLevel::Default);
+
+        for _ in 0..3 {
+            let mut wildcard = response("http://localhost/abc", 200, "same");
+            wildcard.set_wildcard(true);
+
+            assert!(disabled.add(&response("http://localhost/abc", 200, "same")));
+            assert!(groups.add(&wildcard));
+        }
+
+        assert_eq!(disabled.collapsed().count(), 0);
+        assert_eq!(groups.collapsed().count(), 0);
+    }
+}
@@ -0,0 +1,24 @@
+//! grpc service used to stream results from, and control, a running scan (--grpc)
+//!
+//! the service definition lives in `proto/feroxbuster.proto`; it's only compiled in when
+//! feroxbuster is built with the `grpc` feature
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::event_handlers::Handles;
+
+#[cfg(feature = "grpc")]
+mod service;
+
+/// Bind to the given address and serve the grpc service until the process exits
+#[cfg(feature = "grpc")]
+pub fn initialize(addr: &str, handles: Arc<Handles>) -> Result<()> {
+    service::initialize(addr, handles)
+}
+
+/// the grpc service is only available when built with the `grpc` feature
+#[cfg(not(feature = "grpc"))]
+pub fn initialize(_addr: &str, _handles: Arc<Handles>) -> Result<()> {
+    anyhow::bail!("--grpc requires feroxbuster to be built with --features grpc");
+}
@@ -0,0 +1,228 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::{Context, Result};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use tokio::{net::TcpListener, sync::broadcast::error::RecvError};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::{
+    api::subscribe,
+    event_handlers::{Command::SubtractFromUsizeField, Handles},
+    response::FeroxResponse,
+    scan_manager::FeroxScans,
+    scanner::set_rate_limit,
+    statistics::StatField::TotalExpected,
+};
+
+/// code generated from proto/feroxbuster.proto
+mod proto {
+    tonic::include_proto!("feroxbuster");
+}
+
+use proto::{
+    feroxbuster_server::{Feroxbuster, FeroxbusterServer},
+    CancelScanRequest, ControlReply, ListScansReply, ListScansRequest, PauseScansRequest,
+    ResumeScansRequest, Scan, SetRateLimitRequest, StreamResponsesRequest,
+};
+
+/// Bind to the given address and serve the grpc service until the process exits
+pub fn initialize(addr: &str, handles: Arc<Handles>) -> Result<()> {
+    log::trace!("enter: initialize({}, {:?})", addr, handles);
+
+    let addr: SocketAddr = addr
+        .parse()
+        .with_context(|| format!("Could not parse {} as a socket address", addr))?;
+
+    // bind up front, so that a bad address is reported before the scan starts
+    let listener = std::net::TcpListener::bind(addr)
+        .with_context(|| format!("Could not bind grpc service to {}", addr))?;
+    listener.set_nonblocking(true)?;
+    let listener = TcpListener::from_std(listener)?;
+
+    let service = FeroxbusterServer::new(FeroxService { handles });
+
+    tokio::spawn(async move {
+        if let Err(e) = Server::builder()
+            .add_service(service)
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+        {
+            log::warn!("grpc service encountered an error: {}", e);
+        }
+    });
+
+    log::info!("Serving grpc service from {}", addr);
+    log::trace!("exit: initialize");
+    Ok(())
+}
+
+/// convert a reported response into its protobuf representation
+fn to_proto(response: &FeroxResponse) -> proto::Response {
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            )
+        })
+        .collect();
+
+    proto::Response {
+        url: response.url().to_string(),
+        path: response.url().path().to_string(),
+        status: response.status().as_u16() as u32,
+        content_length: response.content_length(),
+        line_count: response.line_count() as u64,
+        word_count: response.word_count() as u64,
+        wildcard: response.wildcard(),
+        headers,
+        technologies: response.technologies().to_vec(),
+        title: response.title().to_string(),
+        favicon_hash: response.favicon_hash().unwrap_or_default(),
+        has_favicon_hash: response.favicon_hash().is_some(),
+    }
+}
+
+/// Implementation of the Feroxbuster grpc service
+struct FeroxService {
+    /// handles used to reach the scan manager and statistics
+    handles: Arc<Handles>,
+}
+
+/// implementation of FeroxService
+impl FeroxService {
+    /// grab the scan manager, converting any failure into a grpc status
+    fn scans(&self) -> Result<Arc<FeroxScans>, Status> {
+        self.handles
+            .ferox_scans()
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+/// implementation of the generated Feroxbuster trait
+#[tonic::async_trait]
+impl Feroxbuster for FeroxService {
+    type StreamResponsesStream = UnboundedReceiver<Result<proto::Response, Status>>;
+
+    /// stream each reported response as it's found
+    async fn stream_responses(
+        &self,
+        _request: Request<StreamResponsesRequest>,
+    ) -> Result<Response<Self::StreamResponsesStream>, Status> {
+        let mut receiver = subscribe();
+        let (tx, rx) = unbounded();
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(response) => {
+                        if tx.unbounded_send(Ok(to_proto(&response))).is_err() {
+                            // client went away
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("grpc subscriber fell behind; skipped {}", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        Ok(Response::new(rx))
+    }
+
+    /// list all known scans
+    async fn list_scans(
+        &self,
+        _request: Request<ListScansRequest>,
+    ) -> Result<Response<ListScansReply>, Status> {
+        let json =
+            serde_json::to_value(&*self.scans()?).map_err(|e| Status::internal(e.to_string()))?;
+
+        let field = |scan: &serde_json::Value, name: &str| {
+            scan[name].as_str().unwrap_or_default().to_string()
+        };
+
+        let scans = json
+            .as_array()
+            .map(|scans| {
+                scans
+                    .iter()
+                    .map(|scan| Scan {
+                        id: field(scan, "id"),
+                        url: field(scan, "url"),
+                        scan_type: field(scan, "scan_type"),
+                        status: field(scan, "status"),
+                        num_requests: scan["num_requests"].as_u64().unwrap_or_default(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Response::new(ListScansReply { scans }))
+    }
+
+    /// pause all scans
+    async fn pause_scans(
+        &self,
+        _request: Request<PauseScansRequest>,
+    ) -> Result<Response<ControlReply>, Status> {
+        self.scans()?.pause_remotely();
+
+        Ok(Response::new(ControlReply {
+            message: "paused".to_string(),
+        }))
+    }
+
+    /// resume all scans
+    async fn resume_scans(
+        &self,
+        _request: Request<ResumeScansRequest>,
+    ) -> Result<Response<ControlReply>, Status> {
+        self.scans()?.resume_remotely();
+
+        Ok(Response::new(ControlReply {
+            message: "resumed".to_string(),
+        }))
+    }
+
+    /// cancel a single scan by id
+    async fn cancel_scan(
+        &self,
+        request: Request<CancelScanRequest>,
+    ) -> Result<Response<ControlReply>, Status> {
+        let id = request.into_inner().id;
+
+        match self.scans()?.cancel_scan_by_id(&id).await {
+            Some(num_cancelled) => {
+                self.handles
+                    .stats
+                    .send(SubtractFromUsizeField(TotalExpected, num_cancelled))
+                    .unwrap_or_else(|e| log::warn!("Could not update overall scan bar: {}", e));
+
+                Ok(Response::new(ControlReply {
+                    message: format!("cancelled {}", id),
+                }))
+            }
+            None => Err(Status::not_found(format!("no scan with id {}", id))),
+        }
+    }
+
+    /// change the requests per second limit of all scans
+    async fn set_rate_limit(
+        &self,
+        request: Request<SetRateLimitRequest>,
+    ) -> Result<Response<ControlReply>, Status> {
+        let limit = request.into_inner().limit as usize;
+
+        set_rate_limit(limit);
+
+        Ok(Response::new(ControlReply {
+            message: format!("rate limit set to {}", limit),
+        }))
+    }
+}
@@ -0,0 +1,236 @@
+//! request/response logging in HTTP Archive format (--har)
+//!
+//! entries are written to disk as they're recorded, the archive is only valid json once
+//! `finalize` has been called
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use lazy_static::lazy_static;
+use reqwest::{header::CONTENT_TYPE, Method, Url, Version};
+use serde_json::{json, Value};
+
+use crate::{config::Configuration, response::FeroxResponse, utils::fmt_err, VERSION};
+
+/// maximum number of bytes of each response body written to the archive
+pub const HAR_BODY_LIMIT: usize = 64 * 1024;
+
+lazy_static! {
+    /// archive opened via `initialize`, if any
+    static ref HAR: Mutex<Option<HarWriter>> = Mutex::new(None);
+}
+
+/// Incrementally written HTTP Archive
+struct HarWriter {
+    /// file to which entries are written
+    file: BufWriter<File>,
+
+    /// number of entries written so far
+    entries: usize,
+}
+
+/// Create the archive at the given path; entries are recorded until `finalize` is called
+pub fn initialize(path: &str) -> Result<()> {
+    log::trace!("enter: initialize({})", path);
+
+    // unlike other output files, the archive is truncated; appending would result in invalid json
+    let mut file = BufWriter::new(
+        File::create(path).with_context(|| fmt_err(&format!("Could not open {}", path)))?,
+    );
+
+    let creator = json!({ "name": "feroxbuster", "version": VERSION });
+
+    write!(
+        file,
+        r#"{{"log":{{"version":"1.2","creator":{},"entries":["#,
+        creator
+    )
+    .with_context(|| format!("Could not write to {}", path))?;
+
+    if let Ok(mut guard) = HAR.lock() {
+        *guard = Some(HarWriter { file, entries: 0 });
+    }
+
+    log::trace!("exit: initialize");
+    Ok(())
+}
+
+/// Add a request/response pair to the archive, if one was opened
+pub fn record(entry: &Value) {
+    if let Ok(mut guard) = HAR.lock() {
+        if let Some(writer) = guard.as_mut() {
+            let separator = if writer.entries > 0 { "," } else { "" };
+
+            match write!(writer.file, "{}{}", separator, entry) {
+                Ok(_) => writer.entries += 1,
+                Err(e) => log::warn!("Could not write HAR entry: {}", e),
+            }
+        }
+    }
+}
+
+/// Close out the archive, if one was opened; nothing is recorded afterwards
+pub fn finalize() -> Result<()> {
+    if let Ok(mut guard) = HAR.lock() {
+        if let Some(mut writer) = guard.take() {
+            write!(writer.file, "]}}}}")?;
+            writer.file.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// convert headers into HAR's list of name/value objects
+fn headers_to_har<'a, I>(headers: I) -> Vec<Value>
+where
+    I: Iterator<Item = (&'a str, String)>,
+{
+    headers
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+/// Build a single archive entry from the requested url and the response it produced
+///
+/// reqwest doesn't expose the headers it actually sent, so request headers are reconstructed from
+/// the configuration (user agent and any -H|--header values); `version` is the http version
+/// negotiated for the exchange, as reported by the response
+pub fn entry(
+    method: &Method,
+    version: Version,
+    url: &Url,
+    config: &Configuration,
+    response: &FeroxResponse,
+    started: DateTime<Utc>,
+    elapsed_ms: f64,
+) -> Value {
+    let request_headers = std::iter::once(("User-Agent", config.user_agent.clone())).chain(
+        config
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.clone())),
+    );
+
+    let query_string: Vec<Value> = url
+        .query_pairs()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect();
+
+    let response_headers = response.headers().iter().map(|(name, value)| {
+        (
+            name.as_str(),
+            String::from_utf8_lossy(value.as_bytes()).to_string(),
+        )
+    });
+
+    let mime_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string())
+        .unwrap_or_default();
+
+    let text = response.text();
+    let mut end = text.len().min(HAR_BODY_LIMIT);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let mut content = json!({
+        "size": response.content_length(),
+        "mimeType": mime_type,
+        "text": &text[..end],
+    });
+
+    if end < text.len() {
+        content["comment"] = json!(format!("truncated to {} bytes", end));
+    }
+
+    let location = response
+        .headers()
+        .get("location")
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string())
+        .unwrap_or_default();
+
+    let http_version = format!("{:?}", version);
+
+    json!({
+        "startedDateTime": started.to_rfc3339_opts(SecondsFormat::Millis, true),
+        "time": elapsed_ms,
+        "request": {
+            "method": method.as_str(),
+            "url": url.as_str(),
+            "httpVersion": http_version,
+            "cookies": [],
+            "headers": headers_to_har(request_headers),
+            "queryString": query_string,
+            "headersSize": -1,
+            "bodySize": 0,
+        },
+        "response": {
+            "status": response.status().as_u16(),
+            "statusText": response.status().canonical_reason().unwrap_or_default(),
+            "httpVersion": http_version,
+            "cookies": [],
+            "headers": headers_to_har(response_headers),
+            "content": content,
+            "redirectURL": location,
+            "headersSize": -1,
+            "bodySize": response.content_length(),
+        },
+        "cache": {},
+        "timings": { "send": 0, "wait": elapsed_ms, "receive": 0 },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    #[test]
+    /// entry should capture both sides of the exchange and truncate large bodies
+    fn har_entry_captures_request_and_response() {
+        let config = Configuration {
+            user_agent: String::from("ferox-test"),
+            ..Default::default()
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", HeaderValue::from_static("text/html"));
+
+        let mut response = FeroxResponse::default();
+        response.set_headers(headers);
+        response.set_text(&"a".repeat(HAR_BODY_LIMIT + 10));
+
+        let url = Url::parse("http://localhost/admin?debug=1").unwrap();
+
+        let entry = entry(
+            &Method::HEAD,
+            Version::HTTP_2,
+            &url,
+            &config,
+            &response,
+            Utc::now(),
+            12.5,
+        );
+
+        assert_eq!(entry["request"]["method"], "HEAD");
+        assert_eq!(entry["request"]["httpVersion"], "HTTP/2.0");
+        assert_eq!(entry["response"]["httpVersion"], "HTTP/2.0");
+        assert_eq!(entry["request"]["url"], "http://localhost/admin?debug=1");
+        assert_eq!(entry["request"]["headers"][0]["value"], "ferox-test");
+        assert_eq!(entry["request"]["queryString"][0]["name"], "debug");
+        assert_eq!(entry["response"]["content"]["mimeType"], "text/html");
+        assert_eq!(
+            entry["response"]["content"]["text"].as_str().unwrap().len(),
+            HAR_BODY_LIMIT
+        );
+        assert!(entry["response"]["content"]["comment"].is_string());
+        assert_eq!(entry["time"], 12.5);
+    }
+}
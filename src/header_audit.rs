@@ -0,0 +1,200 @@
+//! --header-audit; the security headers of each reported response are rated, so that a scan
+//! doubles as a quick header audit; nothing is requested, only the scan's own responses are used
+use reqwest::{
+    header::{
+        HeaderMap, CONTENT_SECURITY_POLICY, CONTENT_SECURITY_POLICY_REPORT_ONLY,
+        STRICT_TRANSPORT_SECURITY, X_FRAME_OPTIONS,
+    },
+    Url,
+};
+use serde::{Deserialize, Serialize};
+
+/// shortest HSTS max-age (one year) that isn't considered weak
+const HSTS_MIN_AGE: u64 = 31_536_000;
+
+/// CSP sources that let injected scripts run anyway
+const UNSAFE_SOURCES: [&str; 3] = ["'unsafe-inline'", "'unsafe-eval'", "*"];
+
+/// Rating of a single security header
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rating {
+    /// the header wasn't sent
+    Missing,
+
+    /// the header was sent, but with a value that undoes most of its protection
+    Weak,
+
+    /// the header was sent with a sound value
+    Present,
+
+    /// the header doesn't apply to the response (i.e. HSTS over plain http)
+    #[serde(rename = "n/a")]
+    NotApplicable,
+}
+
+/// Ratings of a response's security headers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeaderAudit {
+    /// Content-Security-Policy; weak when it allows unsafe-inline/unsafe-eval/* sources, or is
+    /// only sent as Content-Security-Policy-Report-Only
+    pub csp: Rating,
+
+    /// Strict-Transport-Security; weak when its max-age is under a year
+    pub hsts: Rating,
+
+    /// X-Frame-Options; a CSP frame-ancestors directive counts in its place
+    pub x_frame_options: Rating,
+}
+
+/// implementation of HeaderAudit
+impl HeaderAudit {
+    /// Rate the security headers of a response to the given url
+    pub fn new(url: &Url, headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim().to_lowercase())
+        };
+
+        let policy = header(CONTENT_SECURITY_POLICY);
+
+        let csp = match (&policy, header(CONTENT_SECURITY_POLICY_REPORT_ONLY)) {
+            (Some(policy), _) if is_unsafe_policy(policy) => Rating::Weak,
+            (Some(_), _) => Rating::Present,
+            (None, Some(_)) => Rating::Weak,
+            (None, None) => Rating::Missing,
+        };
+
+        let hsts = if url.scheme() != "https" {
+            Rating::NotApplicable
+        } else {
+            match header(STRICT_TRANSPORT_SECURITY) {
+                Some(value) if max_age(&value).map_or(false, |age| age >= HSTS_MIN_AGE) => {
+                    Rating::Present
+                }
+                Some(_) => Rating::Weak,
+                None => Rating::Missing,
+            }
+        };
+
+        let frame_ancestors = policy.map_or(false, |policy| {
+            directives(&policy).any(|(name, _)| name == "frame-ancestors")
+        });
+
+        let x_frame_options = match header(X_FRAME_OPTIONS) {
+            Some(value) if value == "deny" || value == "sameorigin" => Rating::Present,
+            _ if frame_ancestors => Rating::Present,
+            Some(_) => Rating::Weak,
+            None => Rating::Missing,
+        };
+
+        Self {
+            csp,
+            hsts,
+            x_frame_options,
+        }
+    }
+}
+
+/// (name, sources) of each directive in the given (lowercased) policy
+fn directives<'a>(policy: &'a str) -> impl Iterator<Item = (&'a str, Vec<&'a str>)> + 'a {
+    policy.split(';').filter_map(|directive| {
+        let mut words = directive.split_whitespace();
+        words.next().map(|name| (name, words.collect()))
+    })
+}
+
+/// Determine whether the given (lowercased) policy lets scripts through via unsafe sources
+fn is_unsafe_policy(policy: &str) -> bool {
+    directives(policy)
+        .filter(|(name, _)| *name == "script-src" || *name == "default-src")
+        .any(|(_, sources)| sources.iter().any(|source| UNSAFE_SOURCES.contains(source)))
+}
+
+/// max-age of the given (lowercased) Strict-Transport-Security value, if it has a valid one
+fn max_age(value: &str) -> Option<u64> {
+    value.split(';').find_map(|directive| {
+        let (name, age) = directive.split_once('=')?;
+
+        if name.trim() == "max-age" {
+            age.trim().trim_matches('"').parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::header_map;
+
+    #[test]
+    /// sound values should be rated present, and missing headers missing
+    fn new_rates_present_and_missing_headers() {
+        let url = Url::parse("https://localhost/").unwrap();
+
+        let audit = HeaderAudit::new(
+            &url,
+            &header_map(&[
+                (
+                    "content-security-policy",
+                    "default-src 'self'; frame-ancestors 'none'",
+                ),
+                (
+                    "strict-transport-security",
+                    "max-age=63072000; includeSubDomains",
+                ),
+            ]),
+        );
+        assert_eq!(audit.csp, Rating::Present);
+        assert_eq!(audit.hsts, Rating::Present);
+        assert_eq!(audit.x_frame_options, Rating::Present);
+
+        let audit = HeaderAudit::new(&url, &HeaderMap::new());
+        assert_eq!(audit.csp, Rating::Missing);
+        assert_eq!(audit.hsts, Rating::Missing);
+        assert_eq!(audit.x_frame_options, Rating::Missing);
+    }
+
+    #[test]
+    /// unsafe sources, short max-ages, and ALLOW-FROM should be rated weak
+    fn new_rates_weak_headers() {
+        let url = Url::parse("https://localhost/").unwrap();
+
+        let audit = HeaderAudit::new(
+            &url,
+            &header_map(&[
+                (
+                    "content-security-policy",
+                    "script-src 'self' 'unsafe-inline'",
+                ),
+                ("strict-transport-security", "max-age=3600"),
+                ("x-frame-options", "ALLOW-FROM https://example.com"),
+            ]),
+        );
+        assert_eq!(audit.csp, Rating::Weak);
+        assert_eq!(audit.hsts, Rating::Weak);
+        assert_eq!(audit.x_frame_options, Rating::Weak);
+
+        let audit = HeaderAudit::new(
+            &url,
+            &header_map(&[("content-security-policy-report-only", "default-src 'self'")]),
+        );
+        assert_eq!(audit.csp, Rating::Weak);
+    }
+
+    #[test]
+    /// hsts doesn't apply to plain http, and the summary should stay compact in json
+    fn hsts_not_applicable_over_http() {
+        let url = Url::parse("http://localhost/").unwrap();
+        let audit = HeaderAudit::new(&url, &header_map(&[("x-frame-options", "SAMEORIGIN")]));
+
+        assert_eq!(
+            serde_json::to_string(&audit).unwrap(),
+            r#"{"csp":"missing","hsts":"n/a","x_frame_options":"present"}"#
+        );
+    }
+}
@@ -10,13 +10,82 @@ use crate::{
     filters::WildcardFilter,
     progress::PROGRESS_PRINTER,
     response::FeroxResponse,
+    scan_manager::FeroxScan,
     skip_fail,
     url::FeroxUrl,
     utils::{ferox_print, fmt_err, logged_request, status_colorizer},
 };
 
-/// length of a standard UUID, used when determining wildcard responses
-const UUID_LENGTH: u64 = 32;
+/// extension used by the extension probe when no extensions were configured
+const PROBE_EXTENSION: &str = "html";
+
+/// minimum number of wildcard responses required before a tolerance band is built
+const MIN_BAND_PROBES: usize = 3;
+
+/// maximum difference between the smallest and largest wildcard responses, as a percentage of the
+/// largest, for which a tolerance band is built
+const BAND_TOLERANCE: u64 = 10;
+
+/// smallest and largest of the given values
+fn min_max<T: Copy + Ord + Default>(values: &[T]) -> (T, T) {
+    let min = values.iter().min().copied().unwrap_or_default();
+    let max = values.iter().max().copied().unwrap_or_default();
+    (min, max)
+}
+
+/// Build the wildcard filter for the given wildcard responses
+///
+/// The responses either share a static length, share a length that grows with the url
+/// (dynamic), or vary. Responses that vary slightly (i.e. a timestamp) are filtered by a tolerance
+/// band of lengths and word counts, padded by the observed spread; responses that vary more than
+/// that are filtered by the observed lengths and word counts alone
+fn calibrate(dont_filter: bool, responses: &[FeroxResponse]) -> WildcardFilter {
+    let mut wildcard = WildcardFilter::new(dont_filter);
+
+    let lengths: Vec<u64> = responses.iter().map(|r| r.content_length()).collect();
+    let words: Vec<usize> = responses.iter().map(|r| r.word_count()).collect();
+
+    // what's left of each response once the length of the requested path is removed; all the
+    // same when the url is reflected in the response along with some static content
+    let offsets: Vec<Option<u64>> = responses
+        .iter()
+        .map(|r| {
+            r.content_length()
+                .checked_sub(FeroxUrl::path_length_of_url(r.url()))
+        })
+        .collect();
+
+    let (min_length, max_length) = min_max(&lengths);
+    let (min_words, max_words) = min_max(&words);
+
+    if min_length == max_length {
+        wildcard.size = min_length;
+    } else if offsets[0].is_some() && offsets.iter().all(|offset| *offset == offsets[0]) {
+        // custom 404 that reflects the requested url
+        wildcard.dynamic = offsets[0].unwrap_or_default();
+    } else if responses.len() >= MIN_BAND_PROBES
+        && (max_length - min_length) * 100 <= max_length * BAND_TOLERANCE
+    {
+        // slightly different each time, pad the observed range by its own width
+        let length_spread = max_length - min_length;
+        let word_spread = max_words - min_words;
+
+        wildcard.size_range = Some((
+            min_length.saturating_sub(length_spread),
+            max_length + length_spread,
+        ));
+        wildcard.word_range = Some((
+            min_words.saturating_sub(word_spread),
+            max_words + word_spread,
+        ));
+    } else {
+        // too far apart to extrapolate from, only filter what was actually seen
+        wildcard.size_range = Some((min_length, max_length));
+        wildcard.word_range = Some((min_words, max_words));
+    }
+
+    wildcard
+}
 
 /// wrapper around ugly string formatting
 macro_rules! format_template {
@@ -66,21 +135,21 @@ impl HeuristicTests {
         unique_id
     }
 
-    /// wrapper for sending a filter to the filters event handler
-    fn send_filter(&self, filter: WildcardFilter) -> Result<()> {
-        self.handles
-            .filters
-            .send(Command::AddFilter(Box::new(filter)))
-    }
-
-    /// Tests the given url to see if it issues a wildcard response
+    /// Tests the given scan's url to see if it issues a wildcard response
     ///
     /// In the event that url returns a wildcard response, a
-    /// [WildcardFilter](struct.WildcardFilter.html) is created and sent to the filters event
-    /// handler.
+    /// [WildcardFilter](struct.WildcardFilter.html) is created and stored with the scan; every
+    /// directory is calibrated separately, since virtual-hosted apps often have different 404
+    /// behavior per subdirectory
+    ///
+    /// Once a wildcard is found, further probes of varying lengths (and one with an extension) are
+    /// made and used to [calibrate](fn.calibrate.html) the filter; only the first two probes are
+    /// reported
     ///
     /// Returns the number of times to increment the caller's progress bar
-    pub async fn wildcard(&self, target_url: &str) -> Result<u64> {
+    pub async fn wildcard(&self, ferox_scan: &FeroxScan) -> Result<u64> {
+        let target_url = ferox_scan.url();
+
         log::trace!("enter: wildcard_test({:?})", target_url);
 
         if self.handles.config.dont_filter {
@@ -91,55 +160,97 @@ impl HeuristicTests {
 
         let ferox_url = FeroxUrl::from_string(target_url, self.handles.clone());
 
-        let ferox_response = self.make_wildcard_request(&ferox_url, 1).await?;
+        // a directory without a custom 404 is calibrated as such, otherwise its responses would be
+        // compared against its parent directory's calibration
+        ferox_scan.set_wildcard_filter(WildcardFilter::new(self.handles.config.dont_filter));
+
+        let ferox_response = self.make_wildcard_request(&ferox_url, 1, None).await?;
 
         // found a wildcard response
-        let mut wildcard = WildcardFilter::new(self.handles.config.dont_filter);
+        self.report_wildcard(&ferox_response)?;
 
-        let wc_length = ferox_response.content_length();
+        let wildcard = WildcardFilter::new(self.handles.config.dont_filter);
 
-        if wc_length == 0 {
+        if ferox_response.content_length() == 0 {
             log::trace!("exit: wildcard_test -> 1");
-            self.send_filter(wildcard)?;
+            ferox_scan.set_wildcard_filter(wildcard);
             return Ok(1);
         }
 
-        // content length of wildcard is non-zero, perform additional tests:
-        //   make a second request, with a known-sized (64) longer request
-        let resp_two = self.make_wildcard_request(&ferox_url, 3).await?;
+        // content length of wildcard is non-zero, perform additional probes; any of them may not
+        // be a wildcard (i.e. .php handled differently than everything else), which is fine
+        let extension = self
+            .handles
+            .config
+            .extensions
+            .first()
+            .map(String::as_str)
+            .unwrap_or(PROBE_EXTENSION);
+
+        // only the first two probes are shown, the rest are only used to calibrate the filter
+        let probes = [
+            (3, None, true),
+            (2, None, false),
+            (1, Some(extension), false),
+        ];
+        let mut responses = vec![ferox_response];
+
+        for (length, extension, report) in probes.iter() {
+            match self
+                .make_wildcard_request(&ferox_url, *length, *extension)
+                .await
+            {
+                Ok(response) => {
+                    if *report {
+                        self.report_wildcard(&response)?;
+                    }
+
+                    responses.push(response);
+                }
+                Err(e) => log::debug!("wildcard probe wasn't a wildcard: {}", e),
+            }
+        }
 
-        let wc2_length = resp_two.content_length();
+        let num_requests = probes.len() as u64 + 1;
 
-        if wc2_length == wc_length + (UUID_LENGTH * 2) {
-            // second length is what we'd expect to see if the requested url is
-            // reflected in the response along with some static content; aka custom 404
-            let url_len = ferox_url.path_length()?;
+        let wildcard = calibrate(self.handles.config.dont_filter, &responses);
 
-            wildcard.dynamic = wc_length - url_len;
+        if self.should_print() {
+            let msg = if wildcard.size != u64::MAX {
+                format_template!("{} {:>9} {:>9} {:>9} Wildcard response is static; {} {} responses; toggle this behavior by using {}\n", wildcard.size)
+            } else if wildcard.dynamic != u64::MAX {
+                format_template!("{} {:>9} {:>9} {:>9} Wildcard response is dynamic; {} ({} + url length) responses; toggle this behavior by using {}\n", wildcard.dynamic)
+            } else {
+                let (low, high) = wildcard.size_range.unwrap_or_default();
+                let range = format!("{}-{}", low, high);
+                format_template!("{} {:>9} {:>9} {:>9} Wildcard response varies; {} {} byte responses; toggle this behavior by using {}\n", range)
+            };
 
-            if matches!(
-                self.handles.config.output_level,
-                OutputLevel::Default | OutputLevel::Quiet
-            ) {
-                let msg = format_template!("{} {:>9} {:>9} {:>9} Wildcard response is dynamic; {} ({} + url length) responses; toggle this behavior by using {}\n", wildcard.dynamic);
-                ferox_print(&msg, &PROGRESS_PRINTER);
-            }
-        } else if wc_length == wc2_length {
-            wildcard.size = wc_length;
-
-            if matches!(
-                self.handles.config.output_level,
-                OutputLevel::Default | OutputLevel::Quiet
-            ) {
-                let msg = format_template!("{} {:>9} {:>9} {:>9} Wildcard response is static; {} {} responses; toggle this behavior by using {}\n", wildcard.size);
-                ferox_print(&msg, &PROGRESS_PRINTER);
-            }
+            ferox_print(&msg, &PROGRESS_PRINTER);
         }
 
-        self.send_filter(wildcard)?;
+        ferox_scan.set_wildcard_filter(wildcard);
 
-        log::trace!("exit: wildcard_test");
-        Ok(2)
+        log::trace!("exit: wildcard_test -> {}", num_requests);
+        Ok(num_requests)
+    }
+
+    /// send the given wildcard response to the output handler, if wildcard messages should be shown
+    fn report_wildcard(&self, response: &FeroxResponse) -> Result<()> {
+        if self.should_print() {
+            let boxed = Box::new(response.clone());
+            self.handles.output.send(Command::Report(boxed))?;
+        }
+
+        Ok(())
+    }
+
+    /// whether or not wildcard messages should be shown, based on the output level
+    fn should_print(&self) -> bool {
+        matches!(
+            self.handles.config.output_level,
+            OutputLevel::Default | OutputLevel::Quiet
+        )
     }
 
     /// Generates a uuid and appends it to the given target url. The reasoning is that the randomly
@@ -152,11 +263,17 @@ impl HeuristicTests {
         &self,
         target: &FeroxUrl,
         length: usize,
+        extension: Option<&str>,
     ) -> Result<FeroxResponse> {
-        log::trace!("enter: make_wildcard_request({}, {})", target, length);
+        log::trace!(
+            "enter: make_wildcard_request({}, {}, {:?})",
+            target,
+            length,
+            extension
+        );
 
         let unique_str = self.unique_string(length);
-        let nonexistent_url = target.format(&unique_str, None)?;
+        let nonexistent_url = target.format(&unique_str, extension)?;
 
         let response = logged_request(&nonexistent_url.to_owned(), self.handles.clone()).await?;
 
@@ -180,14 +297,6 @@ impl HeuristicTests {
                 bail!("filtered response")
             }
 
-            if matches!(
-                self.handles.config.output_level,
-                OutputLevel::Default | OutputLevel::Quiet
-            ) {
-                let boxed = Box::new(ferox_response.clone());
-                self.handles.output.send(Command::Report(boxed))?;
-            }
-
             log::trace!("exit: make_wildcard_request -> {}", ferox_response);
             return Ok(ferox_response);
         }
@@ -260,4 +369,83 @@ mod tests {
             assert_eq!(tester.unique_string(i).len(), i * 32);
         }
     }
+
+    #[test]
+    /// min_max should return the smallest and largest values, or defaults when empty
+    fn heuristics_min_max_returns_bounds() {
+        assert_eq!(min_max(&[46_u64, 44, 51, 47]), (44, 51));
+        assert_eq!(min_max::<usize>(&[]), (0, 0));
+    }
+
+    /// wildcard responses with the given bodies, requested at the given paths
+    fn wildcard_responses(responses: &[(&str, &str)]) -> Vec<FeroxResponse> {
+        responses
+            .iter()
+            .map(|(path, text)| {
+                let mut response = FeroxResponse::default();
+                response.set_url(&format!("http://localhost/{}", path));
+                response.set_text(text);
+                response
+            })
+            .collect()
+    }
+
+    #[test]
+    /// responses of the same length should be filtered by that length
+    fn heuristics_calibrate_static() {
+        let responses = wildcard_responses(&[("aaaa", "not found"), ("bb", "not found")]);
+        let wildcard = calibrate(false, &responses);
+
+        assert_eq!(wildcard.size, 9);
+        assert_eq!(wildcard.dynamic, u64::MAX);
+        assert!(wildcard.size_range.is_none());
+    }
+
+    #[test]
+    /// responses that grow with the requested path should be filtered as dynamic
+    fn heuristics_calibrate_dynamic() {
+        let responses = wildcard_responses(&[
+            ("aaaa", "/aaaa not found"),
+            ("bb", "/bb not found"),
+            ("c", "/c not found"),
+        ]);
+        let wildcard = calibrate(false, &responses);
+
+        assert_eq!(wildcard.size, u64::MAX);
+        assert_eq!(wildcard.dynamic, 11);
+        assert!(wildcard.size_range.is_none());
+    }
+
+    #[test]
+    /// responses that vary slightly should be filtered by a padded band
+    fn heuristics_calibrate_band() {
+        let responses = wildcard_responses(&[
+            ("a", "not found at 1234567890"),
+            ("a", "not found at 123456789"),
+            ("a", "not found at 12345678"),
+        ]);
+        let wildcard = calibrate(false, &responses);
+
+        assert_eq!(wildcard.size, u64::MAX);
+        assert_eq!(wildcard.dynamic, u64::MAX);
+        assert_eq!(wildcard.size_range, Some((19, 25)));
+        assert_eq!(wildcard.word_range, Some((4, 4)));
+    }
+
+    #[test]
+    /// responses that vary by more than the band's tolerance should still be filtered by what
+    /// was observed
+    fn heuristics_calibrate_wide_spread() {
+        let responses = wildcard_responses(&[
+            ("a", "not found"),
+            ("a", "not found, try again later"),
+            ("a", "not found, try again"),
+        ]);
+        let wildcard = calibrate(false, &responses);
+
+        assert_eq!(wildcard.size, u64::MAX);
+        assert_eq!(wildcard.dynamic, u64::MAX);
+        assert_eq!(wildcard.size_range, Some((9, 26)));
+        assert_eq!(wildcard.word_range, Some((2, 5)));
+    }
 }
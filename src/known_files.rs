@@ -0,0 +1,176 @@
+//! identification of well-known files (i.e. a specific version of jquery) by the hash of their
+//! contents
+//!
+//! the database of known files is a toml file passed via --fingerprint-db, where each entry gives
+//! the md5 and/or sha256 of a file along with its name and version
+use std::{collections::HashMap, fmt, fs::read_to_string};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// A file whose contents are known, identified by name and version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnownFile {
+    /// name of the file or library, i.e. jquery
+    pub name: String,
+
+    /// version of the file or library, i.e. 3.5.1; empty if the version isn't known
+    #[serde(default)]
+    pub version: String,
+}
+
+/// Display implementation for KnownFile
+impl fmt::Display for KnownFile {
+    /// name followed by version, i.e. jquery 3.5.1
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.version.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} {}", self.name, self.version)
+        }
+    }
+}
+
+/// A single entry of a --fingerprint-db file
+#[derive(Debug, Deserialize)]
+struct DbEntry {
+    /// hex encoded md5 of the file
+    #[serde(default)]
+    md5: String,
+
+    /// hex encoded sha256 of the file
+    #[serde(default)]
+    sha256: String,
+
+    /// what the file is
+    #[serde(flatten)]
+    file: KnownFile,
+}
+
+/// Layout of a --fingerprint-db file
+#[derive(Debug, Deserialize)]
+struct DbFile {
+    /// every known file
+    #[serde(default)]
+    files: Vec<DbEntry>,
+}
+
+/// Known files, keyed by the (lowercase) hashes of their contents
+#[derive(Debug, Default)]
+pub struct KnownFiles {
+    /// md5 and sha256 hashes mapped to the file they identify
+    by_hash: HashMap<String, KnownFile>,
+}
+
+/// implementation of KnownFiles
+impl KnownFiles {
+    /// Number of hashes in the database
+    pub fn len(&self) -> usize {
+        self.by_hash.len()
+    }
+
+    /// Whether or not the database is empty
+    pub fn is_empty(&self) -> bool {
+        self.by_hash.is_empty()
+    }
+
+    /// The known file with either of the given hashes, if any
+    pub fn lookup(&self, md5: &str, sha256: &str) -> Option<&KnownFile> {
+        [sha256, md5]
+            .iter()
+            .filter(|hash| !hash.is_empty())
+            .find_map(|hash| self.by_hash.get(*hash))
+    }
+}
+
+/// Read a database of known files from the given toml file
+///
+/// ex:
+/// ```toml
+/// [[files]]
+/// name = "jquery"
+/// version = "3.5.1"
+/// sha256 = "<sha256 of jquery-3.5.1.min.js>"
+/// ```
+pub fn read_db(path: &str) -> Result<KnownFiles> {
+    log::trace!("enter: read_db({})", path);
+
+    let contents = read_to_string(path).with_context(|| format!("Could not open {}", path))?;
+    let file: DbFile =
+        toml::from_str(&contents).with_context(|| format!("Could not parse {}", path))?;
+
+    let mut known_files = KnownFiles::default();
+
+    for entry in file.files {
+        if entry.md5.is_empty() && entry.sha256.is_empty() {
+            bail!("{} in {} has neither an md5 nor a sha256", entry.file, path);
+        }
+
+        for hash in [entry.md5, entry.sha256].iter() {
+            if !hash.is_empty() {
+                known_files
+                    .by_hash
+                    .insert(hash.to_lowercase(), entry.file.clone());
+            }
+        }
+    }
+
+    log::trace!("exit: read_db -> {} hashes", known_files.len());
+    Ok(known_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// helper to write the given toml to a temporary file
+    fn db_file(toml: &str) -> tempfile::NamedTempFile {
+        let mut tmp = tempfile::NamedTempFile::new().unwrap();
+        tmp.write_all(toml.as_bytes()).unwrap();
+        tmp
+    }
+
+    #[test]
+    /// files should be found by either hash, regardless of the hash's case in the database
+    fn read_db_and_lookup() {
+        let tmp = db_file(
+            r#"
+            [[files]]
+            name = "jquery"
+            version = "3.5.1"
+            sha256 = "ABCDEF"
+
+            [[files]]
+            name = "robots"
+            md5 = "123456"
+            "#,
+        );
+
+        let db = read_db(tmp.path().to_str().unwrap()).unwrap();
+        assert_eq!(db.len(), 2);
+
+        let jquery = db.lookup("", "abcdef").unwrap();
+        assert_eq!(jquery.to_string(), "jquery 3.5.1");
+
+        let robots = db.lookup("123456", "ffffff").unwrap();
+        assert_eq!(robots.to_string(), "robots");
+
+        assert!(db.lookup("", "").is_none());
+        assert!(db.lookup("000000", "ffffff").is_none());
+    }
+
+    #[test]
+    /// entries without a hash can never match anything, and should be rejected
+    fn read_db_rejects_entries_without_hashes() {
+        let tmp = db_file(
+            r#"
+            [[files]]
+            name = "jquery"
+            "#,
+        );
+
+        assert!(read_db(tmp.path().to_str().unwrap()).is_err());
+        assert!(read_db("/nonexistent/db.toml").is_err());
+    }
+}
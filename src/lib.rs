@@ -7,24 +7,77 @@ use tokio::{
 
 use crate::event_handlers::Command;
 
+// lets test_utils refer to the crate by name, the same way the integration tests that include it
+// directly do
+#[cfg(any(test, feature = "test-utils"))]
+extern crate self as feroxbuster;
+
+pub mod api;
+pub mod api_mode;
 pub mod banner;
+pub mod bypass;
+mod cache;
+pub mod certificate;
+mod charset;
+pub mod cloud_storage;
+pub mod compare;
+pub mod completions;
 pub mod config;
+pub mod cors;
+pub mod database;
 mod client;
+pub mod control;
+mod decompress;
+pub mod disclosure;
+mod dns;
+pub mod error;
 pub mod event_handlers;
+pub mod events;
 pub mod filters;
+pub mod findings;
+pub mod fingerprint;
+pub mod graphql;
+pub mod grouping;
+pub mod grpc;
+pub mod har;
+pub mod header_audit;
 pub mod heuristics;
+pub mod known_files;
+mod listing;
 pub mod logger;
+pub mod metrics;
+pub mod notify;
+pub mod on_found;
+pub mod openapi;
+mod output_dir;
 mod parser;
+pub mod plugins;
 pub mod progress;
+mod raw;
+pub mod refilter;
 pub mod scan_manager;
 pub mod scanner;
+pub mod schedule;
+pub mod smart_wordlist;
 pub mod statistics;
+pub mod sweep;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod theme;
+pub mod throttle;
 mod traits;
+pub mod update;
 pub mod utils;
+pub mod verbs;
+pub mod waf;
+pub mod wordlist;
+pub mod wordlist_rules;
+pub mod wordlists;
+pub mod wsdl;
 mod extractor;
 mod macros;
-mod url;
-mod response;
+pub mod url;
+pub mod response;
 mod message;
 
 /// Alias for tokio::sync::mpsc::UnboundedSender<Command>
@@ -53,6 +106,9 @@ pub const SIMILARITY_THRESHOLD: u32 = 95;
 ///
 /// defaults to kali's default install location:
 /// - `/usr/share/seclists/Discovery/Web-Content/raft-medium-directories.txt`
+///
+/// when it doesn't exist, a downloaded copy (`feroxbuster wordlists fetch seclists/raft-medium`)
+/// or the wordlist built into the binary is used instead
 pub const DEFAULT_WORDLIST: &str =
     "/usr/share/seclists/Discovery/Web-Content/raft-medium-directories.txt";
 
@@ -90,6 +146,9 @@ pub const DEFAULT_STATUS_CODES: [StatusCode; 9] = [
 /// Expected location is in the same directory as the feroxbuster binary.
 pub const DEFAULT_CONFIG_NAME: &str = "ferox-config.toml";
 
+/// Prefix of environment variables that set configuration values, i.e. `FEROX_THREADS`
+pub const ENV_PREFIX: &str = "FEROX_";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,104 @@
+//! detection and parsing of directory listings (apache/nginx autoindex and friends)
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::Url;
+
+lazy_static! {
+    /// markup shared by the listings of common servers; apache, nginx, lighttpd, tomcat, iis, and
+    /// python's http.server
+    static ref LISTING_REGEX: Regex = Regex::new(
+        r"(?i)<title>\s*(index of|directory listing for)\s|<h1>\s*index of\s|\[to parent directory\]"
+    )
+    .unwrap();
+
+    /// hrefs of anchor tags; sort links (?C=N;O=D) and fragments aren't captured
+    static ref HREF_REGEX: Regex =
+        Regex::new(r#"(?i)<a\s[^>]*href\s*=\s*["']([^"'?#]+)["']"#).unwrap();
+}
+
+/// Whether or not the given body looks like a directory listing
+pub fn is_listing(text: &str) -> bool {
+    LISTING_REGEX.is_match(text)
+}
+
+/// Entries of the directory listing found at `base`
+///
+/// only urls below `base` are returned, which leaves out parent directory links, column sort
+/// links, and anything pointing somewhere else entirely
+pub fn entries(base: &Url, text: &str) -> Vec<Url> {
+    let mut entries: Vec<Url> = Vec::new();
+
+    for capture in HREF_REGEX.captures_iter(text) {
+        let entry = match base.join(&capture[1]) {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::debug!("Could not join {} with {}: {}", base, &capture[1], e);
+                continue;
+            }
+        };
+
+        let is_child = entry.origin() == base.origin()
+            && entry.path().len() > base.path().len()
+            && entry.path().starts_with(base.path());
+
+        if is_child && !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// trimmed down apache autoindex page
+    const APACHE_LISTING: &str = r#"<html><head><title>Index of /files</title></head><body>
+<h1>Index of /files</h1>
+<table>
+<tr><th><a href="?C=N;O=D">Name</a></th><th><a href="?C=M;O=A">Last modified</a></th></tr>
+<tr><td><a href="/">Parent Directory</a></td></tr>
+<tr><td><a href="backup.zip">backup.zip</a></td></tr>
+<tr><td><a href="old/">old/</a></td></tr>
+<tr><td><a href="https://example.com/elsewhere">elsewhere</a></td></tr>
+</table></body></html>"#;
+
+    #[test]
+    /// listings from common servers should be detected, regular pages should not
+    fn is_listing_detects_common_servers() {
+        assert!(is_listing(APACHE_LISTING));
+        assert!(is_listing(
+            "<html>\r\n<head><title>Index of /img/</title></head>"
+        ));
+        assert!(is_listing(
+            "<title>Directory listing for /</title><h1>Directory listing for /</h1>"
+        ));
+        assert!(is_listing(
+            r#"<pre><A HREF="/">[To Parent Directory]</A><br><br>"#
+        ));
+        assert!(!is_listing(
+            "<title>Indexing strategies for /r/databases</title>"
+        ));
+        assert!(!is_listing("<html><body>hello</body></html>"));
+    }
+
+    #[test]
+    /// only entries below the listed directory should be returned
+    fn entries_returns_children() {
+        let base = Url::parse("http://localhost/files/").unwrap();
+
+        let found: Vec<String> = entries(&base, APACHE_LISTING)
+            .iter()
+            .map(|url| url.to_string())
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                "http://localhost/files/backup.zip",
+                "http://localhost/files/old/"
+            ]
+        );
+    }
+}
@@ -1,20 +1,143 @@
 use std::env;
-use std::fs::OpenOptions;
-use std::io::BufWriter;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
 use anyhow::{Context, Result};
+use chrono::{Local, SecondsFormat};
 use env_logger::Builder;
 
 use crate::{
     config::Configuration,
     message::FeroxMessage,
+    parser::SIZE_REGEX,
     progress::PROGRESS_PRINTER,
+    scan_manager::ScanContext,
     traits::FeroxSerialize,
     utils::{fmt_err, write_to},
 };
 
+/// Parse a --debug-log-max-size value into bytes; units are powers of 1024
+///
+/// Examples of expected values: 100mb, 1gb, 4096b
+pub fn parse_size(size: &str) -> Option<u64> {
+    let captures = SIZE_REGEX.captures(size)?;
+
+    let amount = captures.name("n")?.as_str().parse::<u64>().ok()?;
+
+    let multiplier = match captures.name("u")?.as_str().to_ascii_lowercase().as_str() {
+        "kb" => 1024,
+        "mb" => 1024 * 1024,
+        "gb" => 1024 * 1024 * 1024,
+        _ => 1,
+    };
+
+    amount.checked_mul(multiplier).filter(|size| *size > 0)
+}
+
+/// Writer for --debug-log that rotates the file once it grows past `max_size` bytes
+///
+/// rotation renames debug.log to debug.log.1, debug.log.1 to debug.log.2, and so on; only
+/// `backups` rotated files are kept
+#[derive(Debug)]
+struct RotatingWriter {
+    /// path of the active log file
+    path: PathBuf,
+
+    /// size at which the active file is rotated, 0 means it's never rotated
+    max_size: u64,
+
+    /// number of rotated files to keep
+    backups: usize,
+
+    /// number of bytes in the active file
+    written: u64,
+
+    /// the active file
+    writer: BufWriter<File>,
+}
+
+/// implementation of RotatingWriter
+impl RotatingWriter {
+    /// open (or create) the log file at `path`, appending to anything that's already there
+    fn new(path: &str, max_size: u64, backups: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let written = file.metadata()?.len();
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            max_size,
+            backups,
+            written,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// path of the nth rotated file, i.e. debug.log.2
+    fn backup(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    /// shift each rotated file down by one, move the active file to .1, and start a new one
+    fn rotate(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+
+        if self.backups == 0 {
+            // nothing to keep, start over
+            let file = File::create(&self.path)?;
+            self.writer = BufWriter::new(file);
+            self.written = 0;
+            return Ok(());
+        }
+
+        // the oldest file (if any) is overwritten by the rename below
+        for n in (1..self.backups).rev() {
+            let from = self.backup(n);
+
+            if from.exists() {
+                fs::rename(&from, self.backup(n + 1))?;
+            }
+        }
+
+        fs::rename(&self.path, self.backup(1))?;
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        self.writer = BufWriter::new(file);
+        self.written = 0;
+
+        Ok(())
+    }
+}
+
+/// implementation of Write for RotatingWriter
+impl Write for RotatingWriter {
+    /// rotate first if `buf` would push the active file past `max_size`; entries are never split
+    /// across files
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.max_size > 0 && self.written > 0 && self.written + buf.len() as u64 > self.max_size
+        {
+            self.rotate()?;
+        }
+
+        let written = self.writer.write(buf)?;
+        self.written += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
 /// Create a customized instance of
 /// [env_logger::Logger](https://docs.rs/env_logger/latest/env_logger/struct.Logger.html)
 /// with timer offset/color and set the log level based on `verbosity`
@@ -38,17 +161,18 @@ pub fn initialize(config: Arc<Configuration>) -> Result<()> {
     let start = Instant::now();
     let mut builder = Builder::from_default_env();
 
+    // --debug-log-json writes JSON entries to the debug log without affecting --output
+    let json = config.json || config.debug_log_json;
+
     let file = if !config.debug_log.is_empty() {
-        let f = OpenOptions::new() // std fs
-            .create(true)
-            .append(true)
-            .open(&config.debug_log)
-            .with_context(|| fmt_err(&format!("Could not open {}", &config.debug_log)))?;
+        // already validated, an unparseable size simply means no rotation
+        let max_size = parse_size(&config.debug_log_max_size).unwrap_or(0);
 
-        let mut writer = BufWriter::new(f);
+        let mut writer = RotatingWriter::new(&config.debug_log, max_size, config.debug_log_backups)
+            .with_context(|| fmt_err(&format!("Could not open {}", &config.debug_log)))?;
 
         // write out the configuration to the debug file if it exists
-        write_to(&*config, &mut writer, config.json)?;
+        write_to(&*config, &mut writer, json)?;
 
         Some(Arc::new(RwLock::new(writer)))
     } else {
@@ -57,19 +181,25 @@ pub fn initialize(config: Arc<Configuration>) -> Result<()> {
 
     builder
         .format(move |_, record| {
+            // set when logging from a task that's running on behalf of a scan
+            let scan = ScanContext::current();
+
             let log_entry = FeroxMessage {
                 message: record.args().to_string(),
                 level: record.level().to_string(),
                 time_offset: start.elapsed().as_secs_f32(),
                 module: record.target().to_string(),
                 kind: "log".to_string(),
+                timestamp: Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+                scan_id: scan.as_ref().map(|scan| scan.id.clone()),
+                depth: scan.map(|scan| scan.depth),
             };
 
             PROGRESS_PRINTER.println(&log_entry.as_str());
 
             if let Some(buffered_file) = file.clone() {
                 if let Ok(mut unlocked) = buffered_file.write() {
-                    let _ = write_to(&log_entry, &mut unlocked, config.json);
+                    let _ = write_to(&log_entry, &mut *unlocked, json);
                 }
             }
 
@@ -79,3 +209,39 @@ pub fn initialize(config: Arc<Configuration>) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// sizes should be understood with or without units
+    fn parse_size_understands_units() {
+        assert_eq!(parse_size("4096b"), Some(4096));
+        assert_eq!(parse_size("500kb"), Some(500 * 1024));
+        assert_eq!(parse_size("100MB"), Some(100 * 1024 * 1024));
+        assert_eq!(parse_size("0mb"), None);
+        assert_eq!(parse_size("huge"), None);
+    }
+
+    #[test]
+    /// the log should be rotated once it would grow past the max size, keeping only `backups`
+    /// rotated files
+    fn rotating_writer_rotates_and_prunes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("debug.log");
+        let path_str = path.to_str().unwrap();
+
+        let mut writer = RotatingWriter::new(path_str, 10, 2).unwrap();
+
+        for entry in &["first\n", "second\n", "third\n", "fourth\n"] {
+            writer.write_all(entry.as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(fs::read_to_string(writer.backup(1)).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(writer.backup(2)).unwrap(), "second\n");
+        assert!(!writer.backup(3).exists());
+    }
+}
@@ -1,13 +1,7 @@
-use std::{
-    env::args,
-    fs::File,
-    io::{stderr, BufRead, BufReader},
-    ops::Index,
-    process::Command,
-    sync::{atomic::Ordering, Arc},
-};
+use std::{env::args, fs::File, io::stderr, ops::Index, process::Command, sync::Arc};
 
 use anyhow::{bail, Context, Result};
+use console::style;
 use futures::StreamExt;
 use tokio::{
     io,
@@ -16,18 +10,28 @@ use tokio::{
 use tokio_util::codec::{FramedRead, LinesCodec};
 
 use feroxbuster::{
+    api, api_mode,
     banner::{Banner, UPDATE_URL},
+    certificate, compare, completions,
     config::{Configuration, OutputLevel},
+    control, database,
+    error::FeroxError,
     event_handlers::{
+        restore_terminal,
         Command::{CreateBar, Exit, JoinTasks, LoadStats, ScanInitialUrls, UpdateWordlist},
         FiltersHandler, Handles, ScanHandler, StatsHandler, Tasks, TermInputHandler,
-        TermOutHandler, SCAN_COMPLETE,
+        TermOutHandler,
     },
-    filters, heuristics, logger,
+    events::EVENTS,
+    filters, grpc, har, heuristics, logger, metrics,
     progress::{PROGRESS_BAR, PROGRESS_PRINTER},
-    scan_manager::{self},
-    scanner,
-    utils::fmt_err,
+    refilter, response,
+    scan_manager::{self, Lifecycle},
+    scanner::{self, RESPONSES},
+    schedule, smart_wordlist, sweep, theme, throttle, update,
+    utils::{fmt_err, fmt_warn, get_unique_words_from_wordlist},
+    wordlist::{Wordlist, STDIN_WORDLIST},
+    wordlists,
 };
 #[cfg(not(target_os = "windows"))]
 use feroxbuster::{utils::set_open_file_limit, DEFAULT_OPEN_FILE_LIMIT};
@@ -39,37 +43,6 @@ lazy_static! {
     static ref PARALLEL_LIMITER: Semaphore = Semaphore::new(0);
 }
 
-/// Create a HashSet of Strings from the given wordlist then stores it inside an Arc
-fn get_unique_words_from_wordlist(path: &str) -> Result<Arc<Vec<String>>> {
-    log::trace!("enter: get_unique_words_from_wordlist({})", path);
-
-    let file = File::open(&path).with_context(|| format!("Could not open {}", path))?;
-
-    let reader = BufReader::new(file);
-
-    let mut words = Vec::new();
-
-    for line in reader.lines() {
-        let result = match line {
-            Ok(read_line) => read_line,
-            Err(_) => continue,
-        };
-
-        if result.starts_with('#') || result.is_empty() {
-            continue;
-        }
-
-        words.push(result);
-    }
-
-    log::trace!(
-        "exit: get_unique_words_from_wordlist -> Arc<wordlist[{} words...]>",
-        words.len()
-    );
-
-    Ok(Arc::new(words))
-}
-
 /// Determine whether it's a single url scan or urls are coming from stdin, then scan as needed
 async fn scan(targets: Vec<String>, handles: Arc<Handles>) -> Result<()> {
     log::trace!("enter: scan({:?}, {:?})", targets, handles);
@@ -77,9 +50,23 @@ async fn scan(targets: Vec<String>, handles: Arc<Handles>) -> Result<()> {
     // so that will allow for cheap/safe sharing of a single wordlist across multi-target scans
     // as well as additional directories found as part of recursion
 
-    let words = get_unique_words_from_wordlist(&handles.config.wordlist)?;
+    let words = if handles.config.wordlist == STDIN_WORDLIST {
+        // combinations that can't work with a streamed wordlist were rejected by validate
+        Wordlist::from_reader(io::stdin())
+    } else if handles.config.smart_wordlist && !handles.config.resumed && !handles.config.dry_run {
+        // words from the spidered pages are streamed in after those of the wordlist
+        let words =
+            wordlists::resolve(&handles.config.wordlist, handles.config.wordlist_given)?.load()?;
+        Arc::new(words.extendable())
+    } else {
+        if handles.config.smart_wordlist && handles.config.resumed {
+            log::warn!("--smart-wordlist doesn't spider the target again for a resumed scan");
+        }
 
-    if words.len() == 0 {
+        get_unique_words_from_wordlist(&handles.config.wordlist, handles.config.wordlist_given)?
+    };
+
+    if words.is_empty() && !words.is_streaming() {
         bail!("Did not find any words in {}", handles.config.wordlist);
     }
 
@@ -87,7 +74,9 @@ async fn scan(targets: Vec<String>, handles: Arc<Handles>) -> Result<()> {
 
     handles.send_scan_command(UpdateWordlist(words.clone()))?;
 
-    scanner::initialize(words.len(), handles.clone()).await?;
+    // streamed words are added to each scan's expected requests as they're taken, rather than
+    // counted up front
+    scanner::initialize(words.fixed_len(), handles.clone()).await?;
 
     // at this point, the stat thread's progress bar can be created; things that needed to happen
     // first:
@@ -109,6 +98,22 @@ async fn scan(targets: Vec<String>, handles: Arc<Handles>) -> Result<()> {
         scanned_urls.print_completed_bars(words.len())?;
     }
 
+    // --api; version prefixes are scanned as targets of their own. a resumed scan already knows
+    // about them
+    let targets = if handles.config.api && !handles.config.resumed {
+        api_mode::with_version_prefixes(targets, handles.clone()).await
+    } else {
+        targets
+    };
+
+    if handles.config.smart_wordlist && words.is_streaming() {
+        tokio::spawn(smart_wordlist::run(
+            targets.clone(),
+            handles.clone(),
+            words.clone(),
+        ));
+    }
+
     log::debug!("sending {:?} to be scanned as initial targets", targets);
     handles.send_scan_command(ScanInitialUrls(targets))?;
 
@@ -137,27 +142,61 @@ async fn get_targets(handles: Arc<Handles>) -> Result<Vec<String>> {
         // scan, making it mutually exclusive from either of the other two options
         let ferox_scans = handles.ferox_scans()?;
 
-        if let Ok(scans) = ferox_scans.scans.read() {
-            for scan in scans.iter() {
-                // ferox_scans gets deserialized scans added to it at program start if --resume-from
-                // is used, so scans that aren't marked complete still need to be scanned
-                if scan.is_complete() {
-                    // this one's already done, ignore it
-                    continue;
-                }
+        if !handles.config.targets.is_empty() {
+            // per-target options aren't saved with the scan's state, they're read again
+            add_target_configs(&handles)?;
+        }
+        let mut pending = vec![];
+
+        for scan in ferox_scans.get_scans() {
+            // ferox_scans gets deserialized scans added to it at program start if --resume-from
+            // is used, so scans that aren't marked complete still need to be scanned
+            if scan.is_complete() {
+                // this one's already done, ignore it
+                continue;
+            }
 
+            if scan.is_pending() || scan.is_skipped() {
+                // was waiting on --scan-limit, or wasn't scanned because of --max-scans; gets
+                // back in line behind the started scans
+                pending.push(scan.url().to_owned());
+            } else {
                 targets.push(scan.url().to_owned());
             }
-        };
-    } else {
+        }
+
+        targets.extend(pending);
+    } else if !handles.config.targets.is_empty() {
+        // got targets from --targets, each of which may have options of its own
+        targets = add_target_configs(&handles)?;
+    } else if !handles.config.target_url.is_empty() {
         targets.push(handles.config.target_url.clone());
     }
 
+    if !handles.config.target_ranges.is_empty() && !handles.config.resumed {
+        // --target; every web server found in the given ranges is a target as well
+        targets.extend(sweep::discover(&handles.config.target_ranges, &handles.config).await?);
+    }
+
     log::trace!("exit: get_targets -> {:?}", targets);
 
     Ok(targets)
 }
 
+/// Read the --targets file and hand each target's Configuration to the scan manager, so that
+/// everything found beneath a target is scanned using that target's options; returns the targets
+fn add_target_configs(handles: &Handles) -> Result<Vec<String>> {
+    let ferox_scans = handles.ferox_scans()?;
+    let mut targets = vec![];
+
+    for config in handles.config.read_targets()? {
+        targets.push(config.target_url.clone());
+        ferox_scans.add_target_config(Arc::new(config));
+    }
+
+    Ok(targets)
+}
+
 /// async main called from real main, broken out in this way to allow for some synchronous code
 /// to be executed before bringing the tokio runtime online
 async fn wrapped_main(config: Arc<Configuration>) -> Result<()> {
@@ -174,6 +213,34 @@ async fn wrapped_main(config: Arc<Configuration>) -> Result<()> {
         PROGRESS_BAR.join().unwrap();
     });
 
+    if !config.refilter.is_empty() {
+        // refilter subcommand, re-apply filters to a previous scan's results without scanning
+        let result = refilter::run(config.clone()).await;
+        PROGRESS_PRINTER.finish();
+        return result;
+    }
+
+    if config.update {
+        // update subcommand, replace this binary with the latest release
+        let result = update::run(config.clone()).await;
+        PROGRESS_PRINTER.finish();
+        return result;
+    }
+
+    if !config.wordlists.is_empty() {
+        // wordlists subcommand, list or download wordlists that -w can use by name
+        let result = wordlists::run(config.clone()).await;
+        PROGRESS_PRINTER.finish();
+        return result;
+    }
+
+    if !config.control.is_empty() {
+        // scans, pause, resume, or cancel subcommand; talk to a running instance's --api-addr
+        let result = control::run(config.clone()).await;
+        PROGRESS_PRINTER.finish();
+        return result;
+    }
+
     // spawn all event handlers, expect back a JoinHandle and a *Handle to the specific event
     let (stats_task, stats_handle) = StatsHandler::initialize(config.clone());
     let (filters_task, filters_handle) = FiltersHandler::initialize();
@@ -201,34 +268,117 @@ async fn wrapped_main(config: Arc<Configuration>) -> Result<()> {
         // --time-limit value not an empty string, need to kick off the thread that enforces
         // the limit
         let time_handles = handles.clone();
-        tokio::spawn(async move { scan_manager::start_max_time_thread(time_handles).await });
+        tokio::spawn(async move {
+            if let Err(e) = scan_manager::start_max_time_thread(time_handles).await {
+                exit_with(e);
+            }
+        });
+    }
+
+    if config.request_limit > 0 {
+        // --request-limit used, need to kick off the thread that enforces the limit
+        let request_handles = handles.clone();
+        tokio::spawn(async move {
+            if let Err(e) = scan_manager::start_request_limit_thread(request_handles).await {
+                exit_with(e);
+            }
+        });
+    }
+
+    if !config.scan_window.is_empty() {
+        // --scan-window used, need to kick off the thread that pauses/resumes scans according to
+        // the time of day
+        let window_handles = handles.clone();
+        tokio::spawn(async move { scan_manager::start_scan_window_thread(window_handles).await });
+    }
+
+    if !config.compare.is_empty() {
+        // --compare used, make sure the previous scan can be read before any time is spent
+        // scanning
+        compare::read_baseline(&config.compare)?;
+    }
+
+    if !config.har.is_empty() {
+        // --har used, open the archive before any requests are made
+        har::initialize(&config.har)?;
+    }
+
+    if !config.output_db.is_empty() || !config.output_dsn.is_empty() {
+        // --output-db|--output-dsn used, open the databases before anything can be reported
+        database::initialize(&config)?;
+    }
+
+    // --plugin used, let the plugins know about discoveries as they're reported
+    let _plugins = if config.loaded_plugins.is_empty() {
+        None
+    } else {
+        Some(EVENTS.subscribe(config.loaded_plugins.clone()))
+    };
+
+    if config.low_memory {
+        // --low-memory used, only fingerprints of responses found from here on out are kept
+        RESPONSES.set_compact(true);
+    }
+
+    if !config.throttle.is_empty() {
+        // --throttle used, limit bandwidth before any bodies are read
+        throttle::initialize(&config.throttle)?;
+    }
+
+    if !config.metrics_addr.is_empty() {
+        // --metrics-addr used, serve prometheus metrics for the remainder of the scan
+        metrics::initialize(&config.metrics_addr, handles.clone())?;
+    }
+
+    if !config.api_addr.is_empty() {
+        // --api-addr used, serve the control api for the remainder of the scan
+        api::initialize(&config.api_addr, handles.clone())?;
+    }
+
+    if !config.grpc.is_empty() {
+        // --grpc used, serve the grpc service for the remainder of the scan
+        grpc::initialize(&config.grpc, handles.clone())?;
     }
 
     // can't trace main until after logger is initialized and the above task is started
     log::trace!("enter: main");
 
+    if config.resumed {
+        // the ctrl+c handler started below mustn't save state until the previous scan's state is
+        // fully loaded
+        Lifecycle::Loading.enter();
+    }
+
     // spawn a thread that listens for keyboard input on stdin, when a user presses enter
     // the input handler will toggle PAUSE_SCAN, which in turn is used to pause and resume
     // scans that are already running
     // also starts ctrl+c handler
-    TermInputHandler::initialize(handles.clone());
+    TermInputHandler::initialize(handles.clone(), exit_with);
 
     if config.resumed {
         let scanned_urls = handles.ferox_scans()?;
         let from_here = config.resume_from.clone();
 
         // populate FeroxScans object with previously seen scans
-        scanned_urls.add_serialized_scans(&from_here)?;
+        scanned_urls.add_serialized_scans(&from_here, &config.state_password)?;
 
         // populate Stats object with previously known statistics
-        handles.stats.send(LoadStats(from_here))?;
+        handles
+            .stats
+            .send(LoadStats(from_here, config.state_password.clone()))?;
+
+        // wait for the stats handler to finish loading them
+        handles.stats.sync().await?;
     }
 
+    Lifecycle::Scanning.enter();
+
     // get targets from command line or stdin
     let targets = match get_targets(handles.clone()).await {
         Ok(t) => t,
         Err(e) => {
-            // should only happen in the event that there was an error reading from stdin
+            // should only happen in the event that there was an error reading from stdin, or
+            // reading/probing --targets/--target
             clean_up(handles, tasks).await?;
             bail!("Could not get determine initial targets: {}", e);
         }
@@ -306,8 +456,15 @@ async fn wrapped_main(config: Arc<Configuration>) -> Result<()> {
 
         let mut banner = Banner::new(&targets, &config);
 
-        // only interested in the side-effect that sets banner.update_status
-        let _ = banner.check_for_updates(UPDATE_URL, handles.clone()).await;
+        if !config.dry_run {
+            let certificates = certificate::capture(&targets, &config).await;
+            banner.add_certificates(&certificates, &targets);
+        }
+
+        if config.check_update {
+            // only interested in the side-effect that sets banner.update_status
+            let _ = banner.check_for_updates(UPDATE_URL, handles.clone()).await;
+        }
 
         if banner.print_to(std_stderr, config.clone()).is_err() {
             clean_up(handles, tasks).await?;
@@ -316,7 +473,7 @@ async fn wrapped_main(config: Arc<Configuration>) -> Result<()> {
     }
 
     {
-        let send_to_file = !config.output.is_empty();
+        let send_to_file = !config.output.is_empty() || !config.output_dir.is_empty();
 
         // The TermOutHandler spawns a FileOutHandler, so errors in the FileOutHandler never bubble
         // up due to the TermOutHandler never awaiting the result of FileOutHandler::start (that's
@@ -324,13 +481,22 @@ async fn wrapped_main(config: Arc<Configuration>) -> Result<()> {
         if send_to_file && handles.output.sync(send_to_file).await.is_err() {
             // output file specified and file handler could not initialize
             clean_up(handles, tasks).await?;
-            let msg = format!("Couldn't start {} file handler", config.output);
+            let msg = format!(
+                "Couldn't start {} file handler",
+                if config.output.is_empty() {
+                    &config.output_dir
+                } else {
+                    &config.output
+                }
+            );
             bail!(fmt_err(&msg));
         }
     }
 
-    // discard non-responsive targets
-    let live_targets = {
+    // discard non-responsive targets; --dry-run assumes every target is live
+    let live_targets = if config.dry_run {
+        targets
+    } else {
         let test = heuristics::HeuristicTests::new(handles.clone());
         let result = test.connectivity(&targets).await;
         if result.is_err() {
@@ -360,6 +526,36 @@ async fn wrapped_main(config: Arc<Configuration>) -> Result<()> {
     Ok(())
 }
 
+/// Display the number of responses suppressed by each filter, in evaluation order; helpful when
+/// tuning noisy filters
+fn print_filter_summary(hits: &[(String, usize)]) {
+    if hits.is_empty() {
+        return;
+    }
+
+    PROGRESS_PRINTER.println(format!("{}", style("Filter Summary").bright().yellow()));
+
+    for (name, count) in hits {
+        PROGRESS_PRINTER.println(format!("  {:<12} │ {} filtered", name, count));
+    }
+}
+
+/// End the program early because of the given error; library code hands errors it can't
+/// continue past back to main, rather than exiting on its own
+fn exit_with(error: FeroxError) -> ! {
+    log::trace!("exit: exit_with({})", error);
+
+    // process::exit skips the input handler's own clean up
+    restore_terminal();
+
+    if !matches!(error, FeroxError::Interrupted { .. }) {
+        // interruptions were already announced when the scan's state was saved
+        eprintln!("{}", fmt_err(&error.to_string()));
+    }
+
+    std::process::exit(1);
+}
+
 /// Single cleanup function that handles all the necessary drops/finishes etc required to gracefully
 /// shutdown the program
 async fn clean_up(handles: Arc<Handles>, tasks: Tasks) -> Result<()> {
@@ -384,8 +580,24 @@ async fn clean_up(handles: Arc<Handles>, tasks: Tasks) -> Result<()> {
     tasks.stats.await??;
     log::trace!("stats handler closed");
 
+    // all requests are done, the archive can be closed out (--har)
+    har::finalize()?;
+
+    // statistics are final now that the stats handler has exited (--output-db|--output-dsn)
+    database::finalize(&handles)?;
+
+    if matches!(handles.config.output_level, OutputLevel::Default) {
+        // only show the filter summary if output level is default (no summary on --quiet|--silent)
+        print_filter_summary(&handles.stats.data.filter_hits());
+    }
+
+    if !handles.config.compare.is_empty() {
+        // --compare used, show what's changed since the previous scan
+        compare::report(&handles.config.compare, &RESPONSES)?;
+    }
+
     // mark all scans complete so the terminal input handler will exit cleanly
-    SCAN_COMPLETE.store(true, Ordering::Relaxed);
+    Lifecycle::Finished.enter();
 
     // clean-up function for the MultiProgress bar; must be called last in order to still see
     // the final trace messages above
@@ -395,9 +607,78 @@ async fn clean_up(handles: Arc<Handles>, tasks: Tasks) -> Result<()> {
     Ok(())
 }
 
+/// print the fully resolved configuration (--print-config), along with any problems found while
+/// validating it
+fn print_config(config: &Configuration) -> Result<()> {
+    if config.json {
+        println!("{}", serde_json::to_string_pretty(config)?);
+    } else {
+        println!("{}", config.as_toml()?);
+    }
+
+    let mut problems = config.validate();
+
+    if config.wordlist != STDIN_WORDLIST {
+        // only checked here; a scan reports a missing wordlist once it's started
+        match wordlists::resolve(&config.wordlist, config.wordlist_given) {
+            Ok(wordlists::Resolved::File(path)) => {
+                if let Err(e) = File::open(&path) {
+                    problems.push(format!("wordlist: could not open {}: {}", path, e));
+                }
+            }
+            Ok(wordlists::Resolved::Embedded) => {}
+            Err(e) => problems.push(format!("wordlist: {}", e)),
+        }
+    }
+
+    report_problems(&problems, &config.warnings())
+}
+
+/// print each of the given configuration problems and warnings, failing if there were any
+/// problems; warnings are only printed
+fn report_problems(problems: &[String], warnings: &[String]) -> Result<()> {
+    for warning in warnings {
+        eprintln!("{}", fmt_warn(warning));
+    }
+
+    for problem in problems {
+        eprintln!("{}", fmt_err(problem));
+    }
+
+    if !problems.is_empty() {
+        bail!("Found {} problem(s) with the configuration", problems.len());
+    }
+
+    Ok(())
+}
+
 fn main() -> Result<()> {
     let config = Arc::new(Configuration::new().with_context(|| "Could not create Configuration")?);
 
+    // --no-color / NO_COLOR and any colors from the config file, before anything is printed
+    theme::initialize(&config);
+
+    // md5/sha256 of every body read is wasted work unless something uses them
+    response::set_body_hashing(config.needs_body_hashes());
+
+    if config.print_config {
+        // --print-config used, show the effective configuration and exit without scanning
+        return print_config(&config);
+    }
+
+    if config.manpage || !config.completions.is_empty() {
+        // completions or manpage subcommand, print what was asked for and exit without scanning
+        return completions::run(&config);
+    }
+
+    // catch bad values (or combinations of them) before any time is spent scanning
+    report_problems(&config.validate(), &config.warnings())?;
+
+    if !config.schedule.is_empty() {
+        // --schedule used, this process only launches scans; each one runs as a child process
+        return schedule::run(&config);
+    }
+
     // setup logging based on the number of -v's used
     if matches!(
         config.output_level,
@@ -421,6 +702,9 @@ fn main() -> Result<()> {
         };
     }
 
+    // leave the terminal the way we found it, even when the scan ended early
+    restore_terminal();
+
     log::trace!("exit: main");
 
     Ok(())
@@ -23,6 +23,18 @@ pub struct FeroxMessage {
 
     /// The module from which log::* was called
     pub(crate) module: String,
+
+    /// Wall-clock time at which the entry was logged (RFC 3339), for ingestion by log tooling
+    #[serde(default)]
+    pub(crate) timestamp: String,
+
+    /// FeroxScan.id of the scan on whose behalf the entry was logged, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) scan_id: Option<String>,
+
+    /// recursion depth of the scan on whose behalf the entry was logged, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) depth: Option<usize>,
 }
 
 /// Implementation of FeroxMessage
@@ -41,11 +53,17 @@ impl FeroxSerialize for FeroxMessage {
             _ => ("UNK", Color::White),
         };
 
+        // entries logged on behalf of a scan are tagged with the scan's id, i.e. [3f2a...]
+        let module = match &self.scan_id {
+            Some(id) => format!("{} [{}]", self.module, id),
+            None => self.module.clone(),
+        };
+
         format!(
             "{} {:10.03} {} {}\n",
             style(level_name).bg(level_color).black(),
             style(self.time_offset).dim(),
-            self.module,
+            module,
             style(&self.message).dim(),
         )
     }
@@ -59,7 +77,10 @@ impl FeroxSerialize for FeroxMessage {
     ///   "message": "Sent https://localhost/api to file handler",
     ///   "level": "DEBUG",
     ///   "time_offset": 0.86333454,
-    ///   "module": "feroxbuster::reporter"
+    ///   "module": "feroxbuster::reporter",
+    ///   "timestamp": "2021-05-01T13:37:00.123-05:00",
+    ///   "scan_id": "d8b6eb4fd7b04ba4a59a4a1d7eaa5e4a",
+    ///   "depth": 1
     /// }\n
     fn as_json(&self) -> anyhow::Result<String> {
         let mut json = serde_json::to_string(&self).with_context(|| {
@@ -86,6 +107,9 @@ mod tests {
             time_offset: 1.0,
             level: "INFO".to_string(),
             kind: "log".to_string(),
+            timestamp: "2021-05-01T13:37:00.123-05:00".to_string(),
+            scan_id: None,
+            depth: None,
         };
         let message_str = message.as_str();
 
@@ -105,6 +129,9 @@ mod tests {
             time_offset: 1.0,
             level: "INFO".to_string(),
             kind: "log".to_string(),
+            timestamp: "2021-05-01T13:37:00.123-05:00".to_string(),
+            scan_id: None,
+            depth: None,
         };
 
         let message_str = message.as_json().unwrap();
@@ -117,6 +144,7 @@ mod tests {
         assert!((json.time_offset - message.time_offset).abs() < error_margin);
         assert_eq!(json.level, message.level);
         assert_eq!(json.kind, message.kind);
+        assert_eq!(json.timestamp, message.timestamp);
     }
 
     #[test]
@@ -139,10 +167,33 @@ mod tests {
             time_offset: 1.0,
             level: "WILDCARD".to_string(),
             kind: "log".to_string(),
+            ..Default::default()
         };
         assert!(console::strip_ansi_codes(&msg.as_str()).starts_with("WLD"));
 
         msg.level = "UNKNOWN".to_string();
         assert!(console::strip_ansi_codes(&msg.as_str()).starts_with("UNK"));
     }
+
+    #[test]
+    /// entries logged on behalf of a scan should carry the scan's id and depth
+    fn message_includes_scan_context() {
+        let mut msg = FeroxMessage {
+            message: "message".to_string(),
+            module: "utils".to_string(),
+            level: "INFO".to_string(),
+            kind: "log".to_string(),
+            ..Default::default()
+        };
+
+        assert!(!msg.as_json().unwrap().contains("scan_id"));
+        assert!(!msg.as_str().contains('['));
+
+        msg.scan_id = Some("d8b6eb4f".to_string());
+        msg.depth = Some(2);
+
+        let json = msg.as_json().unwrap();
+        assert!(json.contains(r#""scan_id":"d8b6eb4f","depth":2"#));
+        assert!(msg.as_str().contains("utils [d8b6eb4f]"));
+    }
 }
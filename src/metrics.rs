@@ -0,0 +1,168 @@
+//! prometheus metrics endpoint for long-running scans (--metrics-addr)
+use std::{convert::Infallible, fmt::Write, net::SocketAddr, sync::Arc};
+
+use anyhow::{Context, Result};
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use tokio::time::Instant;
+
+use crate::{event_handlers::Handles, scanner::RESPONSES};
+
+/// Content-Type of the prometheus text exposition format
+const CONTENT_TYPE_PROMETHEUS: &str = "text/plain; version=0.0.4";
+
+/// Bind to the given address and serve metrics from `/metrics` until the process exits
+pub fn initialize(addr: &str, handles: Arc<Handles>) -> Result<()> {
+    log::trace!("enter: initialize({}, {:?})", addr, handles);
+
+    let addr: SocketAddr = addr
+        .parse()
+        .with_context(|| format!("Could not parse {} as a socket address", addr))?;
+
+    let builder =
+        Server::try_bind(&addr).with_context(|| format!("Could not bind metrics to {}", addr))?;
+
+    let start = Instant::now();
+
+    let make_service = make_service_fn(move |_| {
+        let handles = handles.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let handles = handles.clone();
+                async move { Ok::<_, Infallible>(respond(request, &handles, start)) }
+            }))
+        }
+    });
+
+    tokio::spawn(async move {
+        if let Err(e) = builder.serve(make_service).await {
+            log::warn!("Metrics server encountered an error: {}", e);
+        }
+    });
+
+    log::info!("Serving metrics from http://{}/metrics", addr);
+    log::trace!("exit: initialize");
+    Ok(())
+}
+
+/// route a single request; only `GET /metrics` is served
+fn respond(request: Request<Body>, handles: &Handles, start: Instant) -> Response<Body> {
+    if request.method() != Method::GET || request.uri().path() != "/metrics" {
+        let mut not_found = Response::new(Body::empty());
+        *not_found.status_mut() = StatusCode::NOT_FOUND;
+        return not_found;
+    }
+
+    let seconds = start.elapsed().as_secs_f64();
+
+    let mut response = Response::new(Body::from(render(handles, seconds)));
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static(CONTENT_TYPE_PROMETHEUS),
+    );
+    response
+}
+
+/// append a single metric, along with its HELP and TYPE lines, to `output`
+fn write_metric(output: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    writeln!(output, "# HELP {} {}", name, help).unwrap_or_default();
+    writeln!(output, "# TYPE {} {}", name, kind).unwrap_or_default();
+    writeln!(output, "{} {}", name, value).unwrap_or_default();
+}
+
+/// render the current state of the scan in the prometheus text exposition format, `seconds` is
+/// the amount of time the scan has been running
+pub fn render(handles: &Handles, seconds: f64) -> String {
+    let stats = &handles.stats.data;
+    let mut output = String::new();
+
+    let requests = stats.requests() as f64;
+    let requests_per_second = if seconds > 0.0 {
+        requests / seconds
+    } else {
+        0.0
+    };
+
+    let active_scans = handles
+        .ferox_scans()
+        .map(|scans| scans.get_active_scans().len())
+        .unwrap_or_default();
+
+    write_metric(
+        &mut output,
+        "feroxbuster_requests_total",
+        "counter",
+        "Total number of requests sent",
+        requests,
+    );
+    write_metric(
+        &mut output,
+        "feroxbuster_errors_total",
+        "counter",
+        "Total number of errors encountered",
+        stats.errors() as f64,
+    );
+    write_metric(
+        &mut output,
+        "feroxbuster_active_scans",
+        "gauge",
+        "Number of directory scans currently running or queued",
+        active_scans as f64,
+    );
+    write_metric(
+        &mut output,
+        "feroxbuster_requests_per_second",
+        "gauge",
+        "Average number of requests sent per second",
+        requests_per_second,
+    );
+
+    // sorted so the output is stable between scrapes
+    let found = RESPONSES.status_counts();
+
+    writeln!(
+        output,
+        "# HELP feroxbuster_found_total Number of reported responses by status code"
+    )
+    .unwrap_or_default();
+    writeln!(output, "# TYPE feroxbuster_found_total counter").unwrap_or_default();
+
+    for (status, count) in found {
+        writeln!(
+            output,
+            "feroxbuster_found_total{{status=\"{}\"}} {}",
+            status, count
+        )
+        .unwrap_or_default();
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// render should include each metric with its type
+    fn render_includes_all_metrics() {
+        let scans = Arc::new(crate::scan_manager::FeroxScans::default());
+        let (handles, _) = Handles::for_testing(Some(scans), None);
+
+        handles.stats.data.add_request();
+        handles.stats.data.add_request();
+
+        let output = render(&handles, 2.0);
+
+        assert!(output.contains("# TYPE feroxbuster_requests_total counter"));
+        assert!(output.contains("feroxbuster_requests_total 2\n"));
+        assert!(output.contains("feroxbuster_errors_total 0\n"));
+        assert!(output.contains("feroxbuster_active_scans 0\n"));
+        assert!(output.contains("feroxbuster_requests_per_second 1\n"));
+        assert!(output.contains("# TYPE feroxbuster_found_total counter"));
+    }
+}
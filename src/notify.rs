@@ -0,0 +1,216 @@
+//! webhook notifications for discoveries (--notify-webhook)
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, Result};
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde_json::json;
+use tokio::{sync::Semaphore, task::JoinHandle};
+
+use crate::{
+    config::Configuration,
+    events::{ScanEvent, Subscriber},
+    response::FeroxResponse,
+};
+
+/// most notifications sent to the webhook at once
+const MAX_IN_FLIGHT: usize = 4;
+
+/// Sends reported responses from the event bus to the --notify-webhook
+#[derive(Debug)]
+pub struct Notifier {
+    /// a plain client, so that the target's proxy, headers, etc... aren't applied to webhook
+    /// requests
+    client: Client,
+
+    /// configuration holding the webhook and --notify-on
+    config: Arc<Configuration>,
+
+    /// one permit per notification allowed to be sent at once
+    permits: Arc<Semaphore>,
+
+    /// notifications spawned so far, whether they're waiting for a permit, in flight, or sent
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// implementation of Notifier
+impl Notifier {
+    /// Send responses to the webhook given in the configuration
+    pub fn new(config: Arc<Configuration>) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            permits: Arc::new(Semaphore::new(MAX_IN_FLIGHT)),
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Wait for every notification spawned so far to be sent
+    pub async fn wait(&self) {
+        let tasks = match self.tasks.lock() {
+            Ok(mut tasks) => mem::take(&mut *tasks),
+            Err(_) => return,
+        };
+
+        for task in tasks {
+            task.await.unwrap_or_default();
+        }
+    }
+}
+
+/// implementation of Subscriber for Notifier
+impl Subscriber for Notifier {
+    /// notify the webhook of reported responses that match --notify-on
+    fn on_event(&self, event: &ScanEvent) {
+        if let ScanEvent::ResponseReported(response) = event {
+            if !should_notify(&self.config, response) {
+                return;
+            }
+
+            let client = self.client.clone();
+            let webhook = self.config.notify_webhook.clone();
+            let response = FeroxResponse::clone(response);
+            let permits = self.permits.clone();
+
+            // notifications are sent in the background so that a slow webhook doesn't hold up
+            // output, at most MAX_IN_FLIGHT at a time
+            let task = tokio::spawn(async move {
+                let _permit = match permits.acquire_owned().await {
+                    Ok(permit) => permit,
+                    Err(_) => return,
+                };
+
+                if let Err(e) = notify(&client, &webhook, &response).await {
+                    log::warn!("{}", e);
+                }
+            });
+
+            if let Ok(mut tasks) = self.tasks.lock() {
+                // sent notifications don't need to be waited on
+                tasks.retain(|task| !task.is_finished());
+                tasks.push(task);
+            }
+        }
+    }
+}
+
+/// Determine whether or not the given (reported) response should be sent to the webhook
+///
+/// when --notify-on isn't used, every reported response is sent
+pub fn should_notify(config: &Configuration, response: &FeroxResponse) -> bool {
+    if config.notify_webhook.is_empty() || response.wildcard() {
+        return false;
+    }
+
+    config.notify_on.is_empty() || config.notify_on.contains(&response.status().as_u16())
+}
+
+/// Build the JSON body sent to the webhook
+///
+/// `text` and `content` are the fields Slack and Discord display, respectively; the full
+/// response is included under `response` for generic webhooks
+pub fn payload(response: &FeroxResponse) -> Result<String> {
+    let message = format!(
+        "feroxbuster found {} {}",
+        response.status().as_u16(),
+        response.url()
+    );
+
+    let body = json!({
+        "text": message,
+        "content": message,
+        "response": response,
+    });
+
+    Ok(serde_json::to_string(&body)?)
+}
+
+/// POST the given response to the webhook passed in via --notify-webhook
+pub async fn notify(client: &Client, webhook: &str, response: &FeroxResponse) -> Result<()> {
+    log::trace!("enter: notify({}, {})", webhook, response);
+
+    let body = payload(response)?;
+
+    client
+        .post(webhook)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .with_context(|| format!("Could not send notification to {}", webhook))?
+        .error_for_status()?;
+
+    log::trace!("exit: notify");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::{Method::POST, MockServer};
+    use reqwest::StatusCode;
+    use serde_json::Value;
+
+    #[test]
+    /// responses should only be sent when a webhook is configured and the status code matches
+    fn should_notify_respects_notify_on() {
+        let mut response = FeroxResponse::default();
+        response.set_status(StatusCode::UNAUTHORIZED);
+
+        let mut config = Configuration::default();
+        assert!(!should_notify(&config, &response));
+
+        config.notify_webhook = String::from("http://localhost/hook");
+        assert!(should_notify(&config, &response));
+
+        config.notify_on = vec![200];
+        assert!(!should_notify(&config, &response));
+
+        config.notify_on.push(401);
+        assert!(should_notify(&config, &response));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// every notification should have been sent once wait returns
+    async fn wait_waits_for_spawned_notifications() {
+        let srv = MockServer::start();
+
+        let mock = srv.mock(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(200);
+        });
+
+        let config = Configuration {
+            notify_webhook: srv.url("/hook"),
+            ..Default::default()
+        };
+        let notifier = Notifier::new(Arc::new(config));
+
+        let mut response = FeroxResponse::default();
+        response.set_status(StatusCode::OK);
+
+        for _ in 0..MAX_IN_FLIGHT * 2 {
+            notifier.on_event(&ScanEvent::ResponseReported(&response));
+        }
+
+        notifier.wait().await;
+
+        assert_eq!(mock.hits(), MAX_IN_FLIGHT * 2);
+    }
+
+    #[test]
+    /// the payload should contain a message for slack/discord and the full response
+    fn payload_contains_message_and_response() {
+        let mut response = FeroxResponse::default();
+        response.set_status(StatusCode::OK);
+        response.set_url("http://localhost/admin");
+
+        let body: Value = serde_json::from_str(&payload(&response).unwrap()).unwrap();
+
+        assert_eq!(body["text"], "feroxbuster found 200 http://localhost/admin");
+        assert_eq!(body["content"], body["text"]);
+        assert_eq!(body["response"]["url"], "http://localhost/admin");
+    }
+}
@@ -0,0 +1,225 @@
+//! --on-found; a user command is run for each reported result, as it's reported, so follow-up
+//! actions (curl with special auth, notification scripts, etc...) don't have to wait for the scan
+//! to finish
+//!
+//! `{url}` and `{status}` in the command stand for the result's url and status code; they're
+//! passed to the command in environment variables, rather than pasted into it, as the target
+//! decides what's in the url
+use std::{
+    mem,
+    process::Stdio,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{process::Command, sync::Semaphore, task::JoinHandle};
+
+use crate::{
+    events::{ScanEvent, Subscriber},
+    response::FeroxResponse,
+};
+
+/// placeholder replaced with the result's url
+pub const URL_PLACEHOLDER: &str = "{url}";
+
+/// placeholder replaced with the result's status code
+pub const STATUS_PLACEHOLDER: &str = "{status}";
+
+/// environment variable holding the result's url
+pub const URL_VARIABLE: &str = "FEROX_URL";
+
+/// environment variable holding the result's status code
+pub const STATUS_VARIABLE: &str = "FEROX_STATUS";
+
+/// Runs the --on-found command for reported results, with at most `limit` running at once
+#[derive(Debug)]
+pub struct OnFound {
+    /// command given to --on-found, placeholders and all
+    template: String,
+
+    /// one permit per command allowed to run at once (--on-found-limit)
+    permits: Arc<Semaphore>,
+
+    /// commands spawned so far, whether they're waiting for a permit, running, or finished
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// implementation of OnFound
+impl OnFound {
+    /// Run the given command for reported results, at most `limit` at a time
+    pub fn new(template: &str, limit: usize) -> Self {
+        Self {
+            template: template.to_string(),
+            permits: Arc::new(Semaphore::new(limit.max(1))),
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The command to run, placeholders replaced with references to `URL_VARIABLE` and
+    /// `STATUS_VARIABLE`; the shell expands those only after the command is parsed, so nothing in
+    /// a result's url is ever run
+    pub fn command_line(&self) -> String {
+        self.template
+            .replace(URL_PLACEHOLDER, &variable(URL_VARIABLE))
+            .replace(STATUS_PLACEHOLDER, &variable(STATUS_VARIABLE))
+    }
+
+    /// Run the command for the given result in the background, once fewer than `limit` are
+    /// running; its output is discarded, only failures are logged
+    pub fn spawn(&self, response: &FeroxResponse) {
+        let command_line = self.command_line();
+        let url = response.url().to_string();
+        let status = response.status().as_str().to_string();
+        let permits = self.permits.clone();
+
+        let task = tokio::spawn(async move {
+            let _permit = match permits.acquire_owned().await {
+                Ok(permit) => permit,
+                Err(_) => return,
+            };
+
+            log::debug!("running --on-found command for {}: {}", url, command_line);
+
+            let status = shell(&command_line)
+                .env(URL_VARIABLE, &url)
+                .env(STATUS_VARIABLE, &status)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .await;
+
+            match status {
+                Ok(status) if status.success() => {}
+                Ok(status) => log::warn!("--on-found command {} exited {}", command_line, status),
+                Err(e) => log::warn!("Could not run --on-found command {}: {}", command_line, e),
+            }
+        });
+
+        if let Ok(mut tasks) = self.tasks.lock() {
+            // finished commands don't need to be waited on
+            tasks.retain(|task| !task.is_finished());
+            tasks.push(task);
+        }
+    }
+
+    /// Wait for every command spawned so far to finish
+    pub async fn wait(&self) {
+        let tasks = match self.tasks.lock() {
+            Ok(mut tasks) => mem::take(&mut *tasks),
+            Err(_) => return,
+        };
+
+        for task in tasks {
+            task.await.unwrap_or_default();
+        }
+    }
+}
+
+/// implementation of Subscriber for OnFound
+impl Subscriber for OnFound {
+    /// run the command for reported responses
+    fn on_event(&self, event: &ScanEvent) {
+        if let ScanEvent::ResponseReported(response) = event {
+            // wildcards are noise as far as other tools are concerned
+            if !response.wildcard() {
+                self.spawn(response);
+            }
+        }
+    }
+}
+
+/// build the shell invocation for the given command; on Windows, delayed expansion is turned on,
+/// so that variables written as `!NAME!` are expanded after the command is parsed
+fn shell(command_line: &str) -> Command {
+    if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/V:ON").arg("/C").arg(command_line);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command_line);
+        cmd
+    }
+}
+
+/// Reference to the given environment variable in the shell the command runs in; its value is
+/// expanded as a single argument, whether or not the reference is put in quotes
+fn variable(name: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("!{}!", name)
+    } else {
+        format!("\"${{{}}}\"", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    /// placeholders should be replaced with references to the variables holding their values
+    fn command_line_replaces_placeholders() {
+        let on_found = OnFound::new("notify.sh {url} {status}", 2);
+
+        assert_eq!(
+            on_found.command_line(),
+            r#"notify.sh "${FEROX_URL}" "${FEROX_STATUS}""#
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(not(target_os = "windows"))]
+    /// nothing in a result's url should be run, even when the user puts quotes around {url}
+    async fn spawn_doesnt_run_what_the_url_holds() {
+        let tmp_dir = tempfile::TempDir::new().unwrap();
+        let log = tmp_dir.path().join("found.log");
+        let injected = tmp_dir.path().join("injected");
+
+        let on_found = OnFound::new(&format!("echo \"{{url}}\" >> {}", log.display()), 1);
+
+        let mut response = FeroxResponse::default();
+        // the query keeps the characters a shell cares about as they are
+        let url = format!("http://localhost/?$(touch${{IFS}}{})", injected.display());
+        response.set_url(&url);
+        response.set_status(StatusCode::OK);
+
+        on_found.spawn(&response);
+        on_found.wait().await;
+
+        assert!(!injected.exists());
+        assert_eq!(
+            std::fs::read_to_string(&log).unwrap().trim(),
+            response.url().as_str()
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(not(target_os = "windows"))]
+    /// every spawned command should have run once wait returns
+    async fn wait_waits_for_spawned_commands() {
+        let tmp_dir = tempfile::TempDir::new().unwrap();
+        let log = tmp_dir.path().join("found.log");
+
+        let on_found = OnFound::new(&format!("echo {{status}} >> {}", log.display()), 1);
+
+        let mut response = FeroxResponse::default();
+
+        for status in &[StatusCode::OK, StatusCode::UNAUTHORIZED] {
+            response.set_status(*status);
+            on_found.spawn(&response);
+        }
+
+        on_found.wait().await;
+
+        let mut lines: Vec<String> = std::fs::read_to_string(&log)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        lines.sort();
+
+        assert_eq!(lines, vec!["200", "401"]);
+    }
+}
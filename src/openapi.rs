@@ -0,0 +1,418 @@
+//! OpenAPI/Swagger spec discovery (--api)
+//!
+//! well-known spec locations are requested at each initial target; every path documented by a
+//! spec that's found is requested by the target's scan before brute forcing starts (as a file,
+//! once for each method the spec documents for it), and resources found by brute force that a
+//! spec covers, but doesn't document, are flagged as undocumented
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::{Method, Url};
+use serde_json::Value;
+
+use crate::{
+    event_handlers::{Command::AddToUsizeField, Handles},
+    response::FeroxResponse,
+    scan_manager::ScanOrder,
+    scanner::RateGate,
+    send_command,
+    statistics::StatField::TotalExpected,
+    utils::logged_request,
+};
+
+/// well-known spec locations, requested below each initial target and at the root of its host
+pub const SPEC_PATHS: [&str; 6] = [
+    "swagger.json",
+    "openapi.json",
+    "openapi.yaml",
+    "swagger.yaml",
+    "v2/api-docs",
+    "v3/api-docs",
+];
+
+/// methods an operation can be documented under
+const METHODS: [&str; 7] = ["get", "head", "options", "post", "put", "patch", "delete"];
+
+/// value substituted for path parameters, i.e. /users/{id} => /users/1
+const PARAMETER_VALUE: &str = "1";
+
+lazy_static! {
+    /// path parameters, i.e. {id}
+    static ref PARAMETER: Regex = Regex::new(r"\{[^}/]*\}").unwrap();
+
+    /// spec locations that were already requested
+    static ref PROBED: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+
+    /// every spec found so far
+    static ref SPECS: RwLock<Vec<Spec>> = RwLock::new(Vec::new());
+}
+
+/// A path documented by a spec, along with one of the methods documented for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    /// path, base path included, i.e. /api/users/{id}
+    pub path: String,
+
+    /// documented method
+    pub method: Method,
+}
+
+/// implementation of Route
+impl Route {
+    /// path with each of its parameters filled in, i.e. /api/users/1
+    pub fn concrete_path(&self) -> String {
+        PARAMETER
+            .replace_all(&self.path, PARAMETER_VALUE)
+            .to_string()
+    }
+
+    /// Whether the given path is this route, or a directory leading up to it; segments holding a
+    /// parameter match anything
+    fn covers(&self, path: &str) -> bool {
+        let documented: Vec<&str> = self.path.split('/').filter(|s| !s.is_empty()).collect();
+        let found: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        found.len() <= documented.len()
+            && found
+                .iter()
+                .zip(documented.iter())
+                .all(|(found, documented)| found == documented || PARAMETER.is_match(documented))
+    }
+}
+
+/// An OpenAPI (3.x) or Swagger (2.0) spec
+#[derive(Debug, Clone)]
+pub struct Spec {
+    /// where the spec was found
+    pub location: Url,
+
+    /// url that every documented path is relative to, from basePath (2.0) or servers (3.x)
+    pub base: Url,
+
+    /// every documented path
+    pub routes: Vec<Route>,
+}
+
+/// implementation of Spec
+impl Spec {
+    /// Parse the given json or yaml, found at `location`; None when it isn't a spec
+    pub fn parse(location: &Url, body: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(body)
+            .ok()
+            .or_else(|| serde_yaml::from_str(body).ok())?;
+
+        if value.get("openapi").is_none() && value.get("swagger").is_none() {
+            return None;
+        }
+
+        let paths = value.get("paths")?.as_object()?;
+
+        // only the path of a server's url is kept, the spec is taken to describe the host it
+        // was found on
+        let base_path = value
+            .get("basePath")
+            .and_then(Value::as_str)
+            .or_else(|| value.get("servers")?.get(0)?.get("url")?.as_str())
+            .map(|url| match Url::parse(url) {
+                Ok(absolute) => absolute.path().to_string(),
+                Err(_) if url.starts_with('/') => url.to_string(),
+                Err(_) => String::new(),
+            })
+            .unwrap_or_default();
+
+        let base_path = base_path.trim_end_matches('/');
+
+        let mut base = location.clone();
+        base.set_path(&format!("{}/", base_path));
+        base.set_query(None);
+        base.set_fragment(None);
+
+        let mut routes = Vec::new();
+
+        for (path, item) in paths {
+            let path = format!("{}/{}", base_path, path.trim_start_matches('/'));
+
+            let mut methods: Vec<Method> = METHODS
+                .iter()
+                .filter(|method| item.get(**method).is_some())
+                .filter_map(|method| Method::from_bytes(method.to_uppercase().as_bytes()).ok())
+                .collect();
+
+            if methods.is_empty() {
+                // a path without any operations documented is still requested
+                methods.push(Method::GET);
+            }
+
+            for method in methods {
+                routes.push(Route {
+                    path: path.clone(),
+                    method,
+                });
+            }
+        }
+
+        Some(Self {
+            location: location.clone(),
+            base,
+            routes,
+        })
+    }
+
+    /// Whether the given url falls within the part of the host this spec describes
+    pub fn covers(&self, url: &Url) -> bool {
+        url.origin() == self.base.origin() && url.path().starts_with(self.base.path())
+    }
+
+    /// Whether the given url is documented by this spec, or is the spec itself
+    pub fn documents(&self, url: &Url) -> bool {
+        if url.origin() != self.location.origin() {
+            return false;
+        }
+
+        url.path() == self.location.path()
+            || self.routes.iter().any(|route| route.covers(url.path()))
+    }
+}
+
+/// Whether the given url falls within the part of a host described by a spec that was found,
+/// without being documented by it
+pub fn is_undocumented(url: &Url) -> bool {
+    let specs = match SPECS.read() {
+        Ok(specs) => specs,
+        Err(_) => return false,
+    };
+
+    let covering: Vec<&Spec> = specs.iter().filter(|spec| spec.covers(url)).collect();
+
+    !covering.is_empty() && !covering.iter().any(|spec| spec.documents(url))
+}
+
+/// Well-known spec locations below the given target and at the root of its host, less those that
+/// were already requested
+fn spec_locations(target: &Url) -> Vec<Url> {
+    let mut below = target.clone();
+    below.set_query(None);
+    below.set_fragment(None);
+
+    if !below.path().ends_with('/') {
+        below.set_path(&format!("{}/", below.path()));
+    }
+
+    let mut root = below.clone();
+    root.set_path("/");
+
+    let mut locations = Vec::new();
+
+    for base in &[below, root] {
+        for path in SPEC_PATHS.iter() {
+            if let Ok(location) = base.join(path) {
+                if !locations.contains(&location) {
+                    locations.push(location);
+                }
+            }
+        }
+    }
+
+    match PROBED.lock() {
+        Ok(mut probed) => locations
+            .into_iter()
+            .filter(|location| probed.insert(location.to_string()))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Url of each of the given spec's routes, paired with the route's method; routes already known to
+/// the scan are left out
+fn route_urls(spec: &Spec, handles: &Handles) -> Vec<(Method, Url)> {
+    let scanned_urls = match handles.ferox_scans() {
+        Ok(scans) => scans,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut routes = Vec::new();
+
+    for route in &spec.routes {
+        let mut url = spec.location.clone();
+        url.set_path(&route.concrete_path());
+        url.set_query(None);
+        url.set_fragment(None);
+
+        if scanned_urls.get_scan_by_url(url.as_str()).is_none() {
+            routes.push((route.method.clone(), url));
+        }
+    }
+
+    routes
+}
+
+/// Request the well-known spec locations of the given target, once the gate allows each of them;
+/// returns every route documented by the specs that are found, for the target's scan to request
+pub async fn discover(target: &str, gate: RateGate, handles: Arc<Handles>) -> Vec<(Method, Url)> {
+    log::trace!("enter: discover({})", target);
+
+    let target = match Url::parse(target) {
+        Ok(url) => url,
+        Err(e) => {
+            log::warn!("Could not look for a spec at {}: {}", target, e);
+            return Vec::new();
+        }
+    };
+
+    let locations = spec_locations(&target);
+
+    send_command!(
+        handles.stats.tx,
+        AddToUsizeField(TotalExpected, locations.len())
+    );
+
+    let mut routes = Vec::new();
+
+    for location in locations {
+        gate.wait().await;
+
+        let response = match logged_request(&location, handles.clone()).await {
+            Ok(response) => FeroxResponse::from(response, true, handles.config.output_level).await,
+            Err(_) => continue,
+        };
+
+        if !response.status().is_success() {
+            continue;
+        }
+
+        let spec = match Spec::parse(&location, response.text()) {
+            Some(spec) => spec,
+            None => continue,
+        };
+
+        log::info!(
+            "{} documents {} routes below {}",
+            location,
+            spec.routes.len(),
+            spec.base
+        );
+
+        // the spec itself is reported like anything else that was found, filters included
+        let filtered = handles
+            .filters
+            .data
+            .should_filter_response(&response, handles.stats.tx.clone());
+
+        if !filtered {
+            if let Ok(scans) = handles.ferox_scans() {
+                scans.add_file_scan(location.as_str(), ScanOrder::Latest);
+            }
+
+            if let Err(e) = response.send_report(handles.output.tx.clone()) {
+                log::warn!("Could not send FeroxResponse to output handler: {}", e);
+            }
+        }
+
+        for route in route_urls(&spec, &handles) {
+            if !routes.contains(&route) {
+                routes.push(route);
+            }
+        }
+
+        if let Ok(mut specs) = SPECS.write() {
+            specs.push(spec);
+        }
+    }
+
+    log::trace!("exit: discover -> {} routes", routes.len());
+    routes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// swagger 2.0 specs should have their paths placed below basePath, once per documented method
+    fn parse_reads_swagger_json() {
+        let location = Url::parse("http://localhost/api/swagger.json").unwrap();
+        let spec = Spec::parse(
+            &location,
+            r#"{"swagger": "2.0", "basePath": "/api/", "paths": {
+                "/users": {"get": {}, "post": {}},
+                "/users/{id}": {"delete": {}},
+                "/login": {"post": {}}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(spec.base.as_str(), "http://localhost/api/");
+        assert_eq!(spec.routes.len(), 4);
+        assert!(spec.routes.contains(&Route {
+            path: String::from("/api/users"),
+            method: Method::GET
+        }));
+        assert!(spec.routes.contains(&Route {
+            path: String::from("/api/users"),
+            method: Method::POST
+        }));
+        assert!(spec.routes.contains(&Route {
+            path: String::from("/api/users/{id}"),
+            method: Method::DELETE
+        }));
+        assert!(spec.routes.contains(&Route {
+            path: String::from("/api/login"),
+            method: Method::POST
+        }));
+    }
+
+    #[test]
+    /// openapi 3.x specs in yaml should use the path of their first server
+    fn parse_reads_openapi_yaml() {
+        let location = Url::parse("http://localhost/openapi.yaml").unwrap();
+        let spec = Spec::parse(
+            &location,
+            "openapi: 3.0.0\nservers:\n  - url: https://api.example.com/v1\npaths:\n  /pets:\n    get: {}\n",
+        )
+        .unwrap();
+
+        assert_eq!(spec.base.as_str(), "http://localhost/v1/");
+        assert_eq!(
+            spec.routes,
+            vec![Route {
+                path: String::from("/v1/pets"),
+                method: Method::GET
+            }]
+        );
+
+        assert!(Spec::parse(&location, r#"{"paths": {}}"#).is_none());
+        assert!(Spec::parse(&location, "<html>Swagger UI</html>").is_none());
+    }
+
+    #[test]
+    /// documented paths, their parameters, and the directories leading up to them are documented;
+    /// anything else within the spec's base path isn't
+    fn spec_documents_routes_and_parents() {
+        let location = Url::parse("http://localhost/swagger.json").unwrap();
+        let spec = Spec::parse(
+            &location,
+            r#"{"swagger": "2.0", "basePath": "/api", "paths": {"/users/{id}/orders": {"get": {}}}}"#,
+        )
+        .unwrap();
+
+        let url = |path: &str| Url::parse(&format!("http://localhost{}", path)).unwrap();
+
+        assert!(spec.documents(&url("/api/users/42/orders")));
+        assert!(spec.documents(&url("/api/users/")));
+        assert!(spec.documents(&url("/swagger.json")));
+        assert!(!spec.documents(&url("/api/admin")));
+        assert!(!spec.documents(&url("/api/users/42/orders/7")));
+
+        assert!(spec.covers(&url("/api/admin")));
+        assert!(!spec.covers(&url("/static/app.js")));
+        assert!(!spec.covers(&Url::parse("http://example.com/api/admin").unwrap()));
+
+        assert_eq!(
+            spec.routes[0].concrete_path(),
+            String::from("/api/users/1/orders")
+        );
+    }
+}
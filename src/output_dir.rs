@@ -0,0 +1,331 @@
+//! --output-dir; results are written to one subdirectory per target, named after the target's
+//! scheme, host, and port
+//!
+//! each target's subdirectory holds the same files, regardless of how the scan was started
+//! (single url, stdin, --parallel)
+//!
+//! ```text
+//! <output-dir>/http_127_0_0_1_8080/
+//!     results.txt     normal output
+//!     results.json    --json output
+//!     bodies/         body of each reported response, named after its path
+//!     graphql/        schema of each GraphQL endpoint that allows introspection
+//!     scan.state      state file written on ctrl+c (or when saved from the scan menu)
+//! ```
+use std::{
+    collections::HashMap,
+    fs::{self, create_dir_all},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use openssl::sha::sha256;
+use reqwest::Url;
+
+use crate::{
+    response::FeroxResponse,
+    utils::{fmt_err, open_file, write_to},
+};
+
+/// normal output, within a target's subdirectory
+const TEXT_RESULTS: &str = "results.txt";
+
+/// --json output, within a target's subdirectory
+const JSON_RESULTS: &str = "results.json";
+
+/// directory holding response bodies, within a target's subdirectory
+const BODIES: &str = "bodies";
+
+/// directory holding GraphQL schemas, within a target's subdirectory
+const GRAPHQL_SCHEMAS: &str = "graphql";
+
+/// state file, within a target's subdirectory
+const STATE_FILE: &str = "scan.state";
+
+/// longest name given to a saved body; longer paths are truncated
+const MAX_BODY_NAME: usize = 200;
+
+/// Name of the subdirectory for the given target, i.e. http_127_0_0_1_8080; anything that isn't
+/// a url (i.e. stdin) is used mostly as-is
+pub fn target_slug(target: &str) -> String {
+    match Url::parse(target) {
+        Ok(url) => {
+            let slug = match url.port() {
+                Some(port) => format!("{}_{}_{}", url.scheme(), url.host_str().unwrap_or(""), port),
+                None => format!("{}_{}", url.scheme(), url.host_str().unwrap_or("")),
+            };
+
+            sanitize(&slug).replace('.', "_")
+        }
+        Err(_) => sanitize(target),
+    }
+}
+
+/// Path of the state file for the given target (or stdin), creating its subdirectory as needed
+pub fn state_file(output_dir: &str, target: &str) -> Result<String> {
+    let dir = Path::new(output_dir).join(target_slug(target));
+
+    create_dir_all(&dir)
+        .with_context(|| fmt_err(&format!("Could not create {}", dir.to_string_lossy())))?;
+
+    Ok(dir.join(STATE_FILE).to_string_lossy().to_string())
+}
+
+/// Path of the file the introspected schema of the GraphQL endpoint at the given url is saved
+/// to, i.e. <output-dir>/http_localhost/graphql/api_graphql.json; creates its directory as needed
+pub fn graphql_schema_file(output_dir: &str, url: &Url) -> Result<String> {
+    let dir = Path::new(output_dir)
+        .join(target_slug(url.as_str()))
+        .join(GRAPHQL_SCHEMAS);
+
+    create_dir_all(&dir)
+        .with_context(|| fmt_err(&format!("Could not create {}", dir.to_string_lossy())))?;
+
+    let name = format!("{}.json", body_name(url));
+
+    Ok(dir.join(name).to_string_lossy().to_string())
+}
+
+/// Name under which the body of the given url is saved, i.e. /admin => admin, and
+/// /js/app.js?v=2 => js_app.js_v_2_e7661085
+///
+/// names that had to be changed to make a safe file name (/js/app.js, /js_app.js, and /js_app.js/
+/// all sanitize to js_app.js) get a hash of the original path and query appended, so that no two
+/// urls share a file
+fn body_name(url: &Url) -> String {
+    let mut path = url.path().trim_start_matches('/').to_string();
+
+    if let Some(query) = url.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+
+    let name: String = sanitize(&path).chars().take(MAX_BODY_NAME).collect();
+
+    if !name.is_empty() && name == path {
+        return name;
+    }
+
+    let digest: String = sha256(path.as_bytes())
+        .iter()
+        .take(4)
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    if name.is_empty() {
+        // target's root
+        format!("index_{}", digest)
+    } else {
+        format!("{}_{}", name, digest)
+    }
+}
+
+/// Replace anything that doesn't belong in a file name with an underscore
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>()
+        .replace("..", "__")
+}
+
+/// Open files belonging to a single target
+#[derive(Debug)]
+struct TargetFiles {
+    /// the target's subdirectory
+    dir: PathBuf,
+
+    /// results.txt
+    text: BufWriter<fs::File>,
+
+    /// results.json
+    json: BufWriter<fs::File>,
+}
+
+/// Writer of per-target results (--output-dir)
+#[derive(Debug)]
+pub struct OutputDir {
+    /// directory under which each target's subdirectory is created
+    root: PathBuf,
+
+    /// files of each target seen so far, keyed by the name of its subdirectory
+    targets: HashMap<String, TargetFiles>,
+}
+
+/// implementation of OutputDir
+impl OutputDir {
+    /// create a writer rooted at the given directory; nothing is created until a result is written
+    pub fn new(root: &str) -> Self {
+        Self {
+            root: PathBuf::from(root),
+            targets: HashMap::new(),
+        }
+    }
+
+    /// files of the target the given url belongs to, creating its subdirectory as needed
+    fn files(&mut self, url: &str) -> Result<&mut TargetFiles> {
+        let slug = target_slug(url);
+
+        if !self.targets.contains_key(&slug) {
+            let dir = self.root.join(&slug);
+
+            create_dir_all(dir.join(BODIES))
+                .with_context(|| fmt_err(&format!("Could not create {}", dir.to_string_lossy())))?;
+
+            let text = open_file(&dir.join(TEXT_RESULTS).to_string_lossy())?;
+            let json = open_file(&dir.join(JSON_RESULTS).to_string_lossy())?;
+
+            self.targets
+                .insert(slug.clone(), TargetFiles { dir, text, json });
+        }
+
+        // unwrap is safe, the target was just inserted if it wasn't already present
+        Ok(self.targets.get_mut(&slug).unwrap())
+    }
+
+    /// write a reported response to its target's results, and save its body
+    pub fn report(&mut self, response: &FeroxResponse) -> Result<()> {
+        let files = self.files(response.url().as_str())?;
+
+        write_to(response, &mut files.text, false)?;
+        write_to(response, &mut files.json, true)?;
+
+        if !response.wildcard() {
+            let path = files.dir.join(BODIES).join(body_name(response.url()));
+
+            fs::write(&path, response.text())
+                .with_context(|| fmt_err(&format!("Could not write {}", path.to_string_lossy())))?;
+        }
+
+        Ok(())
+    }
+
+    /// write a message about the given url (dry run, skipped, etc...) to its target's results
+    pub fn write_line(&mut self, url: &str, text: &str, json: &str) -> Result<()> {
+        let files = self.files(url)?;
+
+        writeln!(files.text, "{}", text)?;
+        files.text.flush()?;
+
+        writeln!(files.json, "{}", json)?;
+        files.json.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    /// subdirectories should be named after a target's scheme, host, and port
+    fn target_slug_uses_origin() {
+        assert_eq!(
+            target_slug("http://127.0.0.1:8080/a/b"),
+            "http_127_0_0_1_8080"
+        );
+        assert_eq!(target_slug("https://example.com/"), "https_example_com");
+        assert_eq!(target_slug("stdin"), "stdin");
+        assert_eq!(target_slug("../../etc"), "______etc");
+    }
+
+    #[test]
+    /// bodies should be named after their path and query, without escaping the bodies directory
+    fn body_name_is_a_safe_file_name() {
+        let name = |url: &str| body_name(&Url::parse(url).unwrap());
+
+        assert_eq!(name("http://localhost/"), "index_e3b0c442");
+        assert_eq!(name("http://localhost/admin"), "admin");
+        assert_eq!(
+            name("http://localhost/js/app.js?v=2"),
+            "js_app.js_v_2_e7661085"
+        );
+        assert_eq!(
+            name("http://localhost/..%2f..%2fsecret"),
+            "___2f___2fsecret_cc8bc9e3"
+        );
+        assert_eq!(
+            name(&format!("http://localhost/{}", "a".repeat(300))).len(),
+            209
+        );
+    }
+
+    #[test]
+    /// urls that sanitize to the same name should still be saved to different files
+    fn body_name_is_unique() {
+        let name = |url: &str| body_name(&Url::parse(url).unwrap());
+
+        let names = vec![
+            name("http://localhost/js/app.js"),
+            name("http://localhost/js_app.js"),
+            name("http://localhost/js_app.js/"),
+            name("http://localhost/js?app.js"),
+        ];
+
+        let mut unique = names.clone();
+        unique.sort();
+        unique.dedup();
+
+        assert_eq!(unique.len(), names.len());
+    }
+
+    #[test]
+    /// each target should get its own results and bodies
+    fn output_dir_writes_per_target_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_str().unwrap();
+        let mut output = OutputDir::new(root);
+
+        let mut response = FeroxResponse::default();
+        response.set_url("http://localhost/admin");
+        response.set_status(StatusCode::OK);
+        response.set_text("welcome");
+        output.report(&response).unwrap();
+
+        output
+            .write_line(
+                "https://other.com/skipped",
+                "skipped https://other.com/skipped",
+                "{}",
+            )
+            .unwrap();
+
+        let localhost = tmp.path().join("http_localhost");
+        let text = fs::read_to_string(localhost.join(TEXT_RESULTS)).unwrap();
+        let json = fs::read_to_string(localhost.join(JSON_RESULTS)).unwrap();
+
+        assert!(text.contains("http://localhost/admin"));
+        assert!(json.contains(r#""url":"http://localhost/admin""#));
+        assert_eq!(
+            fs::read_to_string(localhost.join(BODIES).join("admin")).unwrap(),
+            "welcome"
+        );
+
+        let other = tmp.path().join("https_other_com");
+        assert!(fs::read_to_string(other.join(TEXT_RESULTS))
+            .unwrap()
+            .contains("skipped"));
+
+        assert_eq!(
+            state_file(root, "stdin").unwrap(),
+            tmp.path().join("stdin").join(STATE_FILE).to_string_lossy()
+        );
+
+        let graphql = Url::parse("http://localhost/api/graphql").unwrap();
+        assert_eq!(
+            graphql_schema_file(root, &graphql).unwrap(),
+            localhost
+                .join(GRAPHQL_SCHEMAS)
+                .join("api_graphql_80da5edf.json")
+                .to_string_lossy()
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use reqwest::Url;
+
+use crate::{
+    events::{ScanEvent, Subscriber},
+    response::FeroxResponse,
+};
+
+#[cfg(feature = "plugins")]
+use super::{engine, Plugin};
+
+/// Container around all plugins passed in via --plugin
+///
+/// When feroxbuster is built without the `plugins` feature, this container is always empty and
+/// each hook is a no-op
+#[derive(Debug, Default)]
+pub struct FeroxPlugins {
+    /// collection of loaded plugins, hooks are called in the order the plugins were given
+    #[cfg(feature = "plugins")]
+    plugins: Vec<Plugin>,
+}
+
+/// implementation of FeroxPlugins
+impl FeroxPlugins {
+    /// load each of the given .wasm files
+    #[cfg(feature = "plugins")]
+    pub fn initialize(paths: &[String]) -> anyhow::Result<Self> {
+        let engine = engine()?;
+
+        let mut plugins = Vec::new();
+
+        for path in paths {
+            let plugin = Plugin::from_file(&engine, path)
+                .map_err(|e| anyhow::anyhow!("Could not load plugin {}: {}", path, e))?;
+            plugins.push(plugin);
+        }
+
+        Ok(Self { plugins })
+    }
+
+    /// plugins are only supported when built with the `plugins` feature
+    #[cfg(not(feature = "plugins"))]
+    pub fn initialize(paths: &[String]) -> anyhow::Result<Self> {
+        if !paths.is_empty() {
+            anyhow::bail!("--plugin requires feroxbuster to be built with --features plugins");
+        }
+
+        Ok(Self::default())
+    }
+
+    /// whether or not any plugins were loaded
+    #[cfg(feature = "plugins")]
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// whether or not any plugins were loaded
+    #[cfg(not(feature = "plugins"))]
+    pub fn is_empty(&self) -> bool {
+        true
+    }
+
+    /// pass the given response to each plugin's `on_response` hook; returns true as soon as any
+    /// plugin says the response should be filtered
+    #[cfg(feature = "plugins")]
+    pub fn on_response(&self, response: &FeroxResponse) -> bool {
+        for plugin in &self.plugins {
+            match plugin.on_response(response) {
+                Ok(true) => {
+                    log::debug!("filtered out {} based on {}", response.url(), plugin.path());
+                    return true;
+                }
+                Ok(false) => {}
+                Err(e) => log::warn!("{} on_response failed: {}", plugin.path(), e),
+            }
+        }
+
+        false
+    }
+
+    /// no plugins without the `plugins` feature; never filters
+    #[cfg(not(feature = "plugins"))]
+    pub fn on_response(&self, _response: &FeroxResponse) -> bool {
+        false
+    }
+
+    /// pass the given (reported) response to each plugin's `on_discovery` hook
+    #[cfg(feature = "plugins")]
+    pub fn on_discovery(&self, response: &FeroxResponse) {
+        for plugin in &self.plugins {
+            if let Err(e) = plugin.on_discovery(response) {
+                log::warn!("{} on_discovery failed: {}", plugin.path(), e);
+            }
+        }
+    }
+
+    /// no plugins without the `plugins` feature; does nothing
+    #[cfg(not(feature = "plugins"))]
+    pub fn on_discovery(&self, _response: &FeroxResponse) {}
+
+    /// pass the given url through each plugin's `mutate_request` hook, each plugin sees the url
+    /// as modified by the plugins before it
+    #[cfg(feature = "plugins")]
+    pub fn mutate_request(&self, url: &Url) -> Url {
+        let mut current = url.to_owned();
+
+        for plugin in &self.plugins {
+            match plugin.mutate_request(&current) {
+                Ok(Some(mutated)) => {
+                    log::debug!("{} changed {} to {}", plugin.path(), current, mutated);
+                    current = mutated;
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("{} mutate_request failed: {}", plugin.path(), e),
+            }
+        }
+
+        current
+    }
+
+    /// no plugins without the `plugins` feature; returns the url unchanged
+    #[cfg(not(feature = "plugins"))]
+    pub fn mutate_request(&self, url: &Url) -> Url {
+        url.to_owned()
+    }
+}
+
+/// implementation of Subscriber for FeroxPlugins
+impl Subscriber for FeroxPlugins {
+    /// let each plugin know about reported responses (`on_discovery`)
+    fn on_event(&self, event: &ScanEvent) {
+        if let ScanEvent::ResponseReported(response) = event {
+            self.on_discovery(response);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! WebAssembly plugin support; `.wasm` modules passed via --plugin can hook into response
+//! filtering (`on_response`), reporting (`on_discovery`), and request creation (`mutate_request`)
+//!
+//! loading plugins requires building with `--features plugins`
+mod container;
+#[cfg(feature = "plugins")]
+mod plugin;
+#[cfg(all(test, feature = "plugins"))]
+mod tests;
+
+pub use self::container::FeroxPlugins;
+#[cfg(feature = "plugins")]
+pub use self::plugin::{engine, Plugin};
@@ -0,0 +1,281 @@
+use std::{fmt, sync::Mutex};
+
+use anyhow::{anyhow, bail, Result};
+use reqwest::Url;
+use wasmtime::{Config, Engine, Instance, Memory, Module, Store, TypedFunc};
+
+use crate::response::FeroxResponse;
+
+/// fuel (roughly, wasm instructions) a plugin gets for each hook call; a hook that uses it all up
+/// is stopped, so that one that never returns can't hang the scan
+pub const PLUGIN_FUEL: u64 = 10_000_000;
+
+/// engine with which plugins are compiled and run; fuel is metered, see `PLUGIN_FUEL`
+pub fn engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+
+    Engine::new(&config)
+}
+
+/// A single WebAssembly module loaded via --plugin
+///
+/// Plugins must export `memory` and `alloc(len: i32) -> i32`, which the host uses to hand data
+/// to the plugin. All hooks are optional:
+///
+/// - `on_response(ptr: i32, len: i32) -> i32`: receives a response as JSON; a non-zero return
+///   value filters the response out
+/// - `on_discovery(ptr: i32, len: i32)`: receives a reported response as JSON
+/// - `mutate_request(ptr: i32, len: i32) -> i64`: receives the url about to be requested;
+///   returns `(ptr << 32) | len` of the replacement url, or 0 to leave the url unchanged
+///
+/// If the plugin exports `dealloc(ptr: i32, len: i32)`, the host calls it once a hook returns
+///
+/// Each hook call is given `PLUGIN_FUEL`; a hook that runs out is treated as having failed, so the
+/// response isn't filtered and the url isn't changed
+pub struct Plugin {
+    /// path to the .wasm file from which the plugin was loaded
+    path: String,
+
+    /// wasmtime store that owns the plugin's instance; calls into the plugin need exclusive access
+    ///
+    /// the store's data is the total fuel given to the plugin so far
+    store: Mutex<Store<u64>>,
+
+    /// plugin's exported linear memory
+    memory: Memory,
+
+    /// plugin's exported allocator
+    alloc: TypedFunc<i32, i32>,
+
+    /// plugin's exported deallocator, if any
+    dealloc: Option<TypedFunc<(i32, i32), ()>>,
+
+    /// `on_response` hook, if exported
+    on_response: Option<TypedFunc<(i32, i32), i32>>,
+
+    /// `on_discovery` hook, if exported
+    on_discovery: Option<TypedFunc<(i32, i32), ()>>,
+
+    /// `mutate_request` hook, if exported
+    mutate_request: Option<TypedFunc<(i32, i32), i64>>,
+}
+
+/// Debug implementation for Plugin; wasmtime types aren't useful to print
+impl fmt::Debug for Plugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Plugin")
+            .field("path", &self.path)
+            .field("on_response", &self.on_response.is_some())
+            .field("on_discovery", &self.on_discovery.is_some())
+            .field("mutate_request", &self.mutate_request.is_some())
+            .finish()
+    }
+}
+
+/// implementation of Plugin
+impl Plugin {
+    /// load and instantiate the plugin found at `path`
+    pub fn from_file(engine: &Engine, path: &str) -> Result<Self> {
+        let module = Module::from_file(engine, path)?;
+        Self::from_module(engine, &module, path)
+    }
+
+    /// instantiate an already compiled module, `path` is only used for display purposes
+    pub fn from_module(engine: &Engine, module: &Module, path: &str) -> Result<Self> {
+        let mut store = Store::new(engine, 0);
+
+        // plugins are sandboxed; no host functions are imported
+        let instance = Instance::new(&mut store, module, &[])?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| anyhow!("{} does not export memory", path))?;
+
+        let alloc = instance.get_typed_func::<i32, i32, _>(&mut store, "alloc")?;
+        let dealloc = instance
+            .get_typed_func::<(i32, i32), (), _>(&mut store, "dealloc")
+            .ok();
+
+        let on_response = instance
+            .get_typed_func::<(i32, i32), i32, _>(&mut store, "on_response")
+            .ok();
+        let on_discovery = instance
+            .get_typed_func::<(i32, i32), (), _>(&mut store, "on_discovery")
+            .ok();
+        let mutate_request = instance
+            .get_typed_func::<(i32, i32), i64, _>(&mut store, "mutate_request")
+            .ok();
+
+        if on_response.is_none() && on_discovery.is_none() && mutate_request.is_none() {
+            bail!("{} does not export any known hooks", path);
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            store: Mutex::new(store),
+            memory,
+            alloc,
+            dealloc,
+            on_response,
+            on_discovery,
+            mutate_request,
+        })
+    }
+
+    /// path to the .wasm file from which the plugin was loaded
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// top the plugin's fuel back up to `PLUGIN_FUEL`, ahead of a hook call
+    fn refuel(&self, store: &mut Store<u64>) -> Result<()> {
+        // fuel given to a store only ever adds up, so what's left is what was given minus what
+        // was burned
+        let remaining = store
+            .data()
+            .saturating_sub(store.fuel_consumed().unwrap_or_default());
+        let fuel = PLUGIN_FUEL.saturating_sub(remaining);
+
+        store.add_fuel(fuel)?;
+        *store.data_mut() += fuel;
+
+        Ok(())
+    }
+
+    /// whether the plugin used up all of its fuel, i.e. the hook that just failed was stopped
+    fn out_of_fuel(&self, store: &Store<u64>) -> bool {
+        store.fuel_consumed().unwrap_or_default() >= *store.data()
+    }
+
+    /// the error from a hook call, noting when the hook was stopped for running out of fuel
+    fn hook_error(&self, store: &Store<u64>, hook: &str, error: anyhow::Error) -> anyhow::Error {
+        if self.out_of_fuel(store) {
+            return anyhow!(
+                "{} ran out of fuel after {} instructions and was stopped",
+                hook,
+                PLUGIN_FUEL
+            );
+        }
+
+        error
+    }
+
+    /// copy `data` into the plugin's memory, returning the location it was written to
+    fn write(&self, store: &mut Store<u64>, data: &[u8]) -> Result<(i32, i32)> {
+        let len = data.len() as i32;
+        let ptr = self.alloc.call(&mut *store, len)?;
+
+        self.memory.write(&mut *store, ptr as usize, data)?;
+
+        Ok((ptr, len))
+    }
+
+    /// give memory handed to the plugin back to its allocator, if it exported one
+    fn release(&self, store: &mut Store<u64>, ptr: i32, len: i32) {
+        if let Some(dealloc) = &self.dealloc {
+            dealloc.call(&mut *store, (ptr, len)).unwrap_or_default();
+        }
+    }
+
+    /// serialize a response for consumption by the plugin, including the response body
+    fn response_to_json(response: &FeroxResponse) -> Result<String> {
+        let mut blob = serde_json::to_value(response)?;
+
+        if let Some(map) = blob.as_object_mut() {
+            map.insert("body".to_string(), response.text().to_owned().into());
+        }
+
+        Ok(serde_json::to_string(&blob)?)
+    }
+
+    /// call the plugin's `on_response` hook; returns true if the response should be filtered
+    pub fn on_response(&self, response: &FeroxResponse) -> Result<bool> {
+        let hook = match &self.on_response {
+            Some(hook) => hook,
+            None => return Ok(false),
+        };
+
+        let json = Self::response_to_json(response)?;
+
+        let mut store = self
+            .store
+            .lock()
+            .map_err(|e| anyhow!("Could not lock {}: {}", self.path, e))?;
+
+        self.refuel(&mut store)?;
+
+        let (ptr, len) = self.write(&mut store, json.as_bytes())?;
+        let result = hook
+            .call(&mut *store, (ptr, len))
+            .map_err(|e| self.hook_error(&store, "on_response", e.into()));
+        self.release(&mut store, ptr, len);
+
+        Ok(result? != 0)
+    }
+
+    /// call the plugin's `on_discovery` hook
+    pub fn on_discovery(&self, response: &FeroxResponse) -> Result<()> {
+        let hook = match &self.on_discovery {
+            Some(hook) => hook,
+            None => return Ok(()),
+        };
+
+        let json = Self::response_to_json(response)?;
+
+        let mut store = self
+            .store
+            .lock()
+            .map_err(|e| anyhow!("Could not lock {}: {}", self.path, e))?;
+
+        self.refuel(&mut store)?;
+
+        let (ptr, len) = self.write(&mut store, json.as_bytes())?;
+        let result = hook
+            .call(&mut *store, (ptr, len))
+            .map_err(|e| self.hook_error(&store, "on_discovery", e.into()));
+        self.release(&mut store, ptr, len);
+
+        result?;
+        Ok(())
+    }
+
+    /// call the plugin's `mutate_request` hook; returns the replacement url, if any
+    pub fn mutate_request(&self, url: &Url) -> Result<Option<Url>> {
+        let hook = match &self.mutate_request {
+            Some(hook) => hook,
+            None => return Ok(None),
+        };
+
+        let mut store = self
+            .store
+            .lock()
+            .map_err(|e| anyhow!("Could not lock {}: {}", self.path, e))?;
+
+        self.refuel(&mut store)?;
+
+        let (ptr, len) = self.write(&mut store, url.as_str().as_bytes())?;
+        let result = hook
+            .call(&mut *store, (ptr, len))
+            .map_err(|e| self.hook_error(&store, "mutate_request", e.into()));
+        self.release(&mut store, ptr, len);
+
+        let packed = result?;
+
+        if packed == 0 {
+            // plugin elected to leave the url as-is
+            return Ok(None);
+        }
+
+        let new_ptr = (packed >> 32) as u32 as usize;
+        let new_len = (packed & 0xffff_ffff) as u32 as usize;
+
+        let mut buffer = vec![0; new_len];
+        self.memory.read(&mut *store, new_ptr, &mut buffer)?;
+        self.release(&mut store, new_ptr as i32, new_len as i32);
+
+        let mutated = Url::parse(&String::from_utf8(buffer)?)?;
+
+        Ok(Some(mutated))
+    }
+}
@@ -0,0 +1,120 @@
+use super::*;
+use crate::response::FeroxResponse;
+use reqwest::Url;
+use wasmtime::Module;
+
+/// minimal plugin that filters every response and rewrites every url
+const FILTER_ALL: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 1024))
+  (data (i32.const 0) "http://localhost/mutated")
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    global.get $next
+    local.set $ptr
+    global.get $next
+    local.get $len
+    i32.add
+    global.set $next
+    local.get $ptr)
+  (func (export "on_response") (param i32 i32) (result i32)
+    i32.const 1)
+  (func (export "mutate_request") (param i32 i32) (result i64)
+    i64.const 24))
+"#;
+
+/// minimal plugin that exports memory and an allocator, but no hooks
+const NO_HOOKS: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32)
+    i32.const 0))
+"#;
+
+/// plugin whose hooks never return
+const LOOP_FOREVER: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32)
+    i32.const 0)
+  (func (export "on_response") (param i32 i32) (result i32)
+    (loop $forever
+      br $forever)
+    i32.const 1)
+  (func (export "mutate_request") (param i32 i32) (result i64)
+    (loop $forever
+      br $forever)
+    i64.const 24))
+"#;
+
+/// compile the given WAT into a Plugin
+fn plugin_from_wat(wat: &str) -> anyhow::Result<Plugin> {
+    let engine = engine()?;
+    let module = Module::new(&engine, wat)?;
+    Plugin::from_module(&engine, &module, "test.wasm")
+}
+
+#[test]
+/// a plugin's on_response hook returning non-zero should filter the response
+fn plugin_on_response_filters_response() {
+    let plugin = plugin_from_wat(FILTER_ALL).unwrap();
+
+    let mut resp = FeroxResponse::default();
+    resp.set_url("http://localhost/stuff");
+    resp.set_text("some body");
+
+    assert!(plugin.on_response(&resp).unwrap());
+    assert!(plugin.on_discovery(&resp).is_ok()); // not exported, no-op
+}
+
+#[test]
+/// a plugin's mutate_request hook should be able to replace the requested url
+fn plugin_mutate_request_replaces_url() {
+    let plugin = plugin_from_wat(FILTER_ALL).unwrap();
+    let url = Url::parse("http://localhost/stuff").unwrap();
+
+    let mutated = plugin.mutate_request(&url).unwrap().unwrap();
+
+    assert_eq!(mutated.as_str(), "http://localhost/mutated");
+}
+
+#[test]
+/// hooks that never return should be stopped once they run out of fuel, and the plugin should
+/// get a full tank again for the next call
+fn plugin_hooks_that_never_return_run_out_of_fuel() {
+    let plugin = plugin_from_wat(LOOP_FOREVER).unwrap();
+    let url = Url::parse("http://localhost/stuff").unwrap();
+
+    for _ in 0..2 {
+        let error = plugin.on_response(&FeroxResponse::default()).unwrap_err();
+        assert!(error.to_string().contains("on_response ran out of fuel"));
+
+        let error = plugin.mutate_request(&url).unwrap_err();
+        assert!(error.to_string().contains("mutate_request ran out of fuel"));
+    }
+}
+
+#[test]
+/// a plugin that doesn't export any hooks should fail to load
+fn plugin_without_hooks_is_rejected() {
+    assert!(plugin_from_wat(NO_HOOKS).is_err());
+}
+
+#[test]
+/// FeroxPlugins::initialize should error on a file that doesn't exist
+fn ferox_plugins_initialize_errors_on_bad_path() {
+    let paths = vec![String::from("/this/doesnt/exist.wasm")];
+    assert!(FeroxPlugins::initialize(&paths).is_err());
+}
+
+#[test]
+/// an empty FeroxPlugins never filters and never alters urls
+fn ferox_plugins_default_is_noop() {
+    let plugins = FeroxPlugins::default();
+    let url = Url::parse("http://localhost/stuff").unwrap();
+
+    assert!(plugins.is_empty());
+    assert!(!plugins.on_response(&FeroxResponse::default()));
+    assert_eq!(plugins.mutate_request(&url), url);
+}
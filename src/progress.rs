@@ -1,6 +1,8 @@
 use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use lazy_static::lazy_static;
 
+use crate::events::{ScanEvent, Subscriber};
+
 lazy_static! {
     /// Global progress bar that houses other progress bars
     pub static ref PROGRESS_BAR: MultiProgress = MultiProgress::with_draw_target(ProgressDrawTarget::stdout());
@@ -56,6 +58,20 @@ pub fn add_bar(prefix: &str, length: u64, bar_type: BarType) -> ProgressBar {
     progress_bar
 }
 
+/// stops each scan's progress bar once the scan is over
+#[derive(Debug, Default)]
+pub struct ScanBars;
+
+/// implementation of Subscriber for ScanBars
+impl Subscriber for ScanBars {
+    /// stop the bar of any scan that completed, ran out of time, or was stopped
+    fn on_event(&self, event: &ScanEvent) {
+        if let ScanEvent::ScanComplete(scan) | ScanEvent::ScanAborted(scan) = event {
+            scan.stop_progress_bar();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -78,4 +94,24 @@ mod tests {
         assert!(p3.is_finished());
         assert!(p4.is_finished());
     }
+
+    #[test]
+    /// a scan's bar should be stopped once the scan is over, and not before
+    fn scan_bars_stop_finished_scans() {
+        let pb = add_bar("", 100, BarType::Default);
+        let scan = crate::scan_manager::FeroxScan::new(
+            "http://localhost/",
+            crate::scan_manager::ScanType::Directory,
+            crate::scan_manager::ScanOrder::Latest,
+            100,
+            crate::config::OutputLevel::Default,
+            Some(pb.clone()),
+        );
+
+        ScanBars.on_event(&ScanEvent::ScanStarted(&scan));
+        assert!(!pb.is_finished());
+
+        ScanBars.on_event(&ScanEvent::ScanComplete(&scan));
+        assert!(pb.is_finished());
+    }
 }
@@ -0,0 +1,452 @@
+//! --raw; requests are written to the socket byte-for-byte, instead of going through the http
+//! client, so that header constructs the client refuses (whitespace before the colon, obsolete
+//! line folding, duplicate or conflicting headers, etc...) reach the server as given
+//!
+//! each request gets a connection of its own, which is closed once the response is read; raw
+//! requests don't go through --proxy and don't follow redirects
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use openssl::ssl::{SslConnector, SslStream};
+use reqwest::{
+    header::{HeaderName, HeaderValue},
+    Response, ResponseBuilderExt, StatusCode, Url,
+};
+use tokio::{sync::mpsc::UnboundedSender, task};
+
+use crate::{
+    certificate,
+    config::Configuration,
+    event_handlers::Command::{self, AddError, AddStatus},
+    send_command,
+    statistics::StatError::{Connection, Other, Timeout},
+};
+
+/// A stream that raw requests can be written to; plain tcp or tls
+trait Transport: Read + Write {}
+
+impl<T: Read + Write> Transport for T {}
+
+/// Status, headers, and body of a raw response
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RawResponse {
+    /// status code from the status line
+    pub status: u16,
+
+    /// headers in the order they were received; folded lines are joined to the header they
+    /// continue
+    pub headers: Vec<(String, String)>,
+
+    /// body, with any chunked transfer encoding undone
+    pub body: Vec<u8>,
+}
+
+/// Bytes of the GET request for the given url: the request line, Host, User-Agent, --headers, and
+/// each --raw-header exactly as given
+pub fn build_request(url: &Url, config: &Configuration) -> Vec<u8> {
+    let mut target = url.path().to_string();
+
+    if let Some(query) = url.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or(""), port),
+        None => url.host_str().unwrap_or("").to_string(),
+    };
+
+    let mut request = format!("GET {} HTTP/1.1\r\nHost: {}\r\n", target, host);
+
+    if !config.user_agent.is_empty() {
+        request.push_str(&format!("User-Agent: {}\r\n", config.user_agent));
+    }
+
+    for (name, value) in &config.headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+
+    for line in &config.raw_headers {
+        request.push_str(line);
+        request.push_str("\r\n");
+    }
+
+    // the response is read until the server hangs up
+    request.push_str("Connection: close\r\n\r\n");
+
+    request.into_bytes()
+}
+
+/// index of the first occurrence of `needle` in `haystack`
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Parse a raw response; servers under test are known to misbehave, so anything that isn't the
+/// status line is parsed leniently
+pub fn parse_response(raw: &[u8]) -> Result<RawResponse> {
+    let head_end = find(raw, b"\r\n\r\n").ok_or_else(|| anyhow!("incomplete response head"))?;
+
+    let head = String::from_utf8_lossy(&raw[..head_end]);
+    let mut lines = head.split("\r\n");
+
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| anyhow!("malformed status line"))?;
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+
+    for line in lines {
+        if line.starts_with(' ') || line.starts_with('\t') {
+            // obsolete line folding; continues the previous header's value
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+
+        if let Some(index) = line.find(':') {
+            let name = line[..index].trim().to_string();
+            let value = line[index + 1..].trim().to_string();
+            headers.push((name, value));
+        }
+    }
+
+    let header = |wanted: &str| {
+        headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(wanted))
+            .map(|(_, value)| value.to_lowercase())
+    };
+
+    let mut body = raw[head_end + 4..].to_vec();
+
+    if header("transfer-encoding").map_or(false, |value| value.contains("chunked")) {
+        body = dechunk(&body);
+    } else if let Some(length) = header("content-length").and_then(|v| v.parse::<usize>().ok()) {
+        body.truncate(length);
+    }
+
+    Ok(RawResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+/// Undo chunked transfer encoding; a truncated body is returned as far as it goes
+fn dechunk(mut data: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+
+    while let Some(line_end) = find(data, b"\r\n") {
+        let size_line = String::from_utf8_lossy(&data[..line_end]);
+
+        let size = match usize::from_str_radix(size_line.split(';').next().unwrap_or("").trim(), 16)
+        {
+            Ok(size) => size,
+            Err(_) => break,
+        };
+
+        data = &data[line_end + 2..];
+
+        if size == 0 || data.len() < size {
+            body.extend_from_slice(&data[..size.min(data.len())]);
+            break;
+        }
+
+        body.extend_from_slice(&data[..size]);
+        data = &data[size..];
+
+        if data.starts_with(b"\r\n") {
+            data = &data[2..];
+        }
+    }
+
+    body
+}
+
+/// whether the given bytes already hold the entire response, so that a server that keeps the
+/// connection open doesn't need to time out
+fn is_complete(raw: &[u8]) -> bool {
+    let head_end = match find(raw, b"\r\n\r\n") {
+        Some(index) => index,
+        None => return false,
+    };
+
+    let head = String::from_utf8_lossy(&raw[..head_end]).to_lowercase();
+    let body = &raw[head_end + 4..];
+
+    if head.contains("transfer-encoding: chunked") {
+        return body.ends_with(b"0\r\n\r\n");
+    }
+
+    head.split("\r\n")
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .and_then(|length| length.trim().parse::<usize>().ok())
+        .map_or(false, |length| body.len() >= length)
+}
+
+/// settings a tls connector is built from: -k, --ca-cert and --pin-sha256
+type TlsSettings = (bool, String, Vec<String>);
+
+lazy_static! {
+    /// tls connector shared by all raw requests, along with the settings it was built from
+    static ref CONNECTOR: Mutex<Option<(TlsSettings, SslConnector)>> = Mutex::new(None);
+}
+
+/// tls connector for the given configuration; built by the first raw request, reused by the rest
+fn connector(config: &Configuration) -> Result<SslConnector> {
+    let settings = (
+        config.insecure,
+        config.ca_cert.clone(),
+        config.pin_sha256.clone(),
+    );
+
+    let mut cached = CONNECTOR
+        .lock()
+        .map_err(|_| anyhow!("tls connector lock was poisoned"))?;
+
+    if let Some((built_from, connector)) = cached.as_ref() {
+        if *built_from == settings {
+            return Ok(connector.clone());
+        }
+    }
+
+    let connector = certificate::tls_connector(settings.0, &settings.1, &settings.2)?;
+    *cached = Some((settings, connector.clone()));
+
+    Ok(connector)
+}
+
+/// tls connection to the given host, verified the same way the http client verifies its own, and
+/// against --pin-sha256 during the handshake
+fn connect_tls(host: &str, tcp: TcpStream, config: &Configuration) -> Result<SslStream<TcpStream>> {
+    connector(config)?
+        .configure()?
+        .verify_hostname(!config.insecure)
+        .connect(host, tcp)
+        .map_err(|e| anyhow!("tls handshake with {} failed: {}", host, e))
+}
+
+/// read from the stream until the server hangs up, or until the response is complete
+fn read_response(stream: &mut dyn Transport) -> Result<Vec<u8>> {
+    let mut raw = Vec::new();
+    let mut chunk = [0; 8192];
+
+    loop {
+        match stream.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => {
+                raw.extend_from_slice(&chunk[..read]);
+
+                if is_complete(&raw) {
+                    break;
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(_) if !raw.is_empty() => {
+                // timed out waiting for more, or the server hung up without closing tls properly;
+                // what was read so far is all there is
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(raw)
+}
+
+/// write the request to a new connection to the url's server, returning the raw response
+fn exchange(url: &Url, request: &[u8], config: &Configuration) -> Result<Vec<u8>> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("{} has no host", url))?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+
+    let port = url.port_or_known_default().unwrap_or(80);
+    let timeout = Duration::from_secs(config.timeout);
+
+    let address = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| anyhow!("could not resolve {}", host))?;
+
+    let tcp = TcpStream::connect_timeout(&address, timeout)?;
+    tcp.set_read_timeout(Some(timeout))?;
+    tcp.set_write_timeout(Some(timeout))?;
+
+    let mut stream: Box<dyn Transport> = if url.scheme() == "https" {
+        Box::new(connect_tls(host, tcp, config)?)
+    } else {
+        Box::new(tcp)
+    };
+
+    stream.write_all(request)?;
+    stream.flush()?;
+
+    read_response(&mut stream)
+}
+
+/// rebuild a reqwest::Response from a raw one, so the rest of the scan can't tell the difference
+fn to_response(url: &Url, raw: RawResponse) -> Result<Response> {
+    let status = StatusCode::from_u16(raw.status)?;
+    let mut builder = hyper::Response::builder().status(status).url(url.clone());
+
+    for (name, value) in raw.headers {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            (Ok(name), Ok(value)) => builder = builder.header(name, value),
+            _ => log::debug!("dropped unrepresentable header from {}: {}", url, name),
+        }
+    }
+
+    Ok(Response::from(builder.body(raw.body)?))
+}
+
+/// Raw counterpart of utils::make_request; sends a GET for the given url over a connection of its
+/// own and updates the same statistics
+pub async fn make_request(
+    config: Arc<Configuration>,
+    url: &Url,
+    tx_stats: UnboundedSender<Command>,
+) -> Result<Response> {
+    log::trace!("enter: raw::make_request({}, {:?})", url, tx_stats);
+
+    let request = build_request(url, &config);
+    let target = url.clone();
+
+    let result = task::spawn_blocking(move || exchange(&target, &request, &config))
+        .await
+        .map_err(|e| anyhow!(e))
+        .and_then(|result| result)
+        .and_then(|raw| parse_response(&raw))
+        .and_then(|raw| to_response(url, raw));
+
+    match result {
+        Ok(response) => {
+            log::trace!("exit: raw::make_request -> {:?}", response);
+            send_command!(tx_stats, AddStatus(response.status()));
+            Ok(response)
+        }
+        Err(e) => {
+            let timed_out = e.downcast_ref::<std::io::Error>().map_or(false, |e| {
+                matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock)
+            });
+
+            if timed_out {
+                send_command!(tx_stats, AddError(Timeout));
+            } else if e.downcast_ref::<std::io::Error>().is_some() {
+                send_command!(tx_stats, AddError(Connection));
+            } else {
+                send_command!(tx_stats, AddError(Other));
+            }
+
+            log::warn!("Error while making raw request to {}: {}", url, e);
+            log::trace!("exit: raw::make_request -> {}", e);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::BufRead, io::BufReader, net::TcpListener, thread};
+
+    #[test]
+    /// --raw-header lines should be written exactly as given, after the generated headers
+    fn build_request_keeps_raw_headers_verbatim() {
+        let mut config = Configuration::new().unwrap();
+        config.user_agent = String::from("ferox");
+        config.raw_headers = vec![
+            String::from("Transfer-Encoding : chunked"),
+            String::from("X-Folded: first"),
+            String::from("\tsecond"),
+        ];
+
+        let url = Url::parse("http://localhost:8080/admin?id=1").unwrap();
+        let request = String::from_utf8(build_request(&url, &config)).unwrap();
+
+        assert_eq!(
+            request,
+            "GET /admin?id=1 HTTP/1.1\r\nHost: localhost:8080\r\nUser-Agent: ferox\r\nTransfer-Encoding : chunked\r\nX-Folded: first\r\n\tsecond\r\nConnection: close\r\n\r\n"
+        );
+    }
+
+    #[test]
+    /// folded headers should be joined, and chunked bodies decoded
+    fn parse_response_is_lenient() {
+        let raw = b"HTTP/1.1 403 Forbidden\r\nServer: test\r\nX-Folded: first\r\n second\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+
+        let response = parse_response(raw).unwrap();
+
+        assert_eq!(response.status, 403);
+        assert_eq!(
+            response.headers[1],
+            (String::from("X-Folded"), String::from("first second"))
+        );
+        assert_eq!(response.body, b"hello world");
+        assert!(is_complete(raw));
+
+        let raw = b"HTTP/1.0 200 OK\r\nContent-Length: 2\r\n\r\nokextra";
+        assert_eq!(parse_response(raw).unwrap().body, b"ok");
+        assert!(parse_response(b"garbage").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// requests should be written to the socket, and the response turned into a reqwest::Response
+    async fn make_request_round_trips_over_a_socket() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                request.push_str(&line);
+
+                if line == "\r\n" {
+                    break;
+                }
+            }
+
+            let mut stream = stream;
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nfound")
+                .unwrap();
+
+            request
+        });
+
+        let mut config = Configuration::new().unwrap();
+        config.raw_headers = vec![String::from("X-Weird :value")];
+
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let url = Url::parse(&format!("http://127.0.0.1:{}/admin", port)).unwrap();
+
+        let response = make_request(Arc::new(config), &url, tx).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.url(), &url);
+        assert_eq!(response.text().await.unwrap(), "foun");
+        assert!(server.join().unwrap().contains("\r\nX-Weird :value\r\n"));
+    }
+}
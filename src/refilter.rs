@@ -0,0 +1,251 @@
+//! re-apply filters to the results of a previous scan (refilter subcommand)
+//!
+//! results are read from --json output or a state file and run through the same filters used
+//! during a scan, without sending any requests. Neither format stores response bodies, so the
+//! filters that need one (--filter-regex, --filter-similar-to) are only applied to responses found
+//! in the original scan's --cache-dir
+use std::{fs::read_to_string, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+use fuzzyhash::FuzzyHash;
+use reqwest::Url;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::{
+    cache,
+    config::{Configuration, OutputLevel},
+    filters::{self, FeroxFilters, SimilarityFilter},
+    progress::PROGRESS_PRINTER,
+    response::FeroxResponse,
+    traits::FeroxSerialize,
+    utils::{ferox_print, open_file, write_to},
+    SIMILARITY_THRESHOLD,
+};
+
+/// names of the filters that look at the response body
+const BODY_FILTERS: [&str; 2] = ["Regex", "Similarity"];
+
+/// Read the responses reported by a previous scan; accepts either --json output or a state file
+pub fn read_responses(path: &str) -> Result<Vec<FeroxResponse>> {
+    log::trace!("enter: read_responses({})", path);
+
+    let contents = read_to_string(path).with_context(|| format!("Could not open {}", path))?;
+
+    let entries: Vec<Value> = match serde_json::from_str::<Value>(&contents) {
+        // state file, a single object with all responses under the "responses" key
+        Ok(state) if state["responses"].is_array() => {
+            state["responses"].as_array().cloned().unwrap_or_default()
+        }
+        // --json output, one object per line
+        _ => contents
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .collect(),
+    };
+
+    let responses: Vec<FeroxResponse> = entries
+        .into_iter()
+        .filter(|entry| entry["type"] == "response")
+        .filter_map(|entry| serde_json::from_value(entry).ok())
+        .collect();
+
+    if responses.is_empty() {
+        bail!(
+            "Could not find any responses in {}; expected --json output or a state file",
+            path
+        );
+    }
+
+    log::trace!("exit: read_responses -> {} responses", responses.len());
+    Ok(responses)
+}
+
+/// Rebuild the given response from --cache-dir, body and all; None when it isn't cached
+async fn from_cache(url: &Url, config: &Configuration) -> Option<FeroxResponse> {
+    if config.cache_dir.is_empty() {
+        return None;
+    }
+
+    let key = cache::key("GET", url, config);
+    let cached = cache::load(&config.cache_dir, &key).await?;
+
+    Some(FeroxResponse::from(cached, true, config.output_level).await)
+}
+
+/// Build the filters given on the command line, returning (every filter, filters that don't
+/// need a body)
+async fn build_filters(config: &Configuration) -> (FeroxFilters, FeroxFilters) {
+    let all = FeroxFilters::default();
+    let bodiless = FeroxFilters::default();
+
+    for filter in filters::from_config(config) {
+        all.push(filter).unwrap_or_default();
+    }
+
+    for filter in filters::from_config(config) {
+        if !BODY_FILTERS.contains(&filter.name()) {
+            bodiless.push(filter).unwrap_or_default();
+        }
+    }
+
+    for similar_to in &config.filter_similar {
+        // no requests are sent, the page has to have been cached during the original scan
+        let page = match Url::parse(similar_to) {
+            Ok(url) => from_cache(&url, config).await,
+            Err(_) => None,
+        };
+
+        match page {
+            Some(page) => {
+                let filter = SimilarityFilter {
+                    text: FuzzyHash::new(page.text()).to_string(),
+                    threshold: SIMILARITY_THRESHOLD,
+                };
+                all.push(Box::new(filter)).unwrap_or_default();
+            }
+            None => log::warn!(
+                "Could not find {} in --cache-dir; skipping it as a --filter-similar-to page",
+                similar_to
+            ),
+        }
+    }
+
+    (all, bodiless)
+}
+
+/// Re-apply the filters given on the command line to the given responses, returning those that
+/// would still be reported
+pub async fn refilter(config: &Configuration, responses: Vec<FeroxResponse>) -> Vec<FeroxResponse> {
+    let (all, bodiless) = build_filters(config).await;
+
+    // stats aren't tracked outside of a scan; the receiver only has to outlive the filtering
+    let (tx_stats, _rx_stats) = mpsc::unbounded_channel();
+
+    let mut kept = Vec::new();
+
+    for response in responses {
+        let (response, filters) = match from_cache(response.url(), config).await {
+            Some(mut cached) => {
+                cached.set_wildcard(response.wildcard());
+                (cached, &all)
+            }
+            None => (response, &bodiless),
+        };
+
+        if !filters.should_filter_response(&response, tx_stats.clone()) {
+            kept.push(response);
+        }
+    }
+
+    kept
+}
+
+/// Entry point of the refilter subcommand; reads the previous results, filters them, and writes
+/// what's left to -o|--output (or stdout)
+pub async fn run(config: Arc<Configuration>) -> Result<()> {
+    log::trace!("enter: run({})", config.refilter);
+
+    let responses = read_responses(&config.refilter)?;
+    let total = responses.len();
+
+    let needs_body = !config.filter_regex.is_empty() || !config.filter_similar.is_empty();
+
+    if needs_body && config.cache_dir.is_empty() {
+        log::warn!(
+            "{} doesn't include response bodies; --filter-regex and --filter-similar-to need the original scan's --cache-dir",
+            config.refilter
+        );
+    }
+
+    let kept = refilter(&config, responses).await;
+
+    if config.output.is_empty() {
+        for response in &kept {
+            let line = if config.json {
+                response.as_json()?
+            } else {
+                response.as_str()
+            };
+
+            ferox_print(line.trim_end(), &PROGRESS_PRINTER);
+        }
+    } else {
+        let mut file = open_file(&config.output)?;
+
+        for response in &kept {
+            write_to(response, &mut file, config.json)?;
+        }
+    }
+
+    if matches!(config.output_level, OutputLevel::Default) {
+        let summary = format!(
+            "Kept {} of {} responses from {}",
+            kept.len(),
+            total,
+            config.refilter
+        );
+        ferox_print(&summary, &PROGRESS_PRINTER);
+    }
+
+    log::trace!("exit: run");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// two responses, as written by --json
+    const RESULTS: &str = concat!(
+        r#"{"type":"response","url":"http://localhost/admin","path":"/admin","wildcard":false,"status":403,"content_length":12,"line_count":1,"word_count":2,"headers":{}}"#,
+        "\n",
+        r#"{"type":"statistics","requests":2}"#,
+        "\n",
+        r#"{"type":"response","url":"http://localhost/login","path":"/login","wildcard":false,"status":200,"content_length":512,"line_count":20,"word_count":80,"headers":{}}"#,
+        "\n",
+    );
+
+    #[test]
+    /// responses should be read from both --json output and state files
+    fn read_responses_reads_json_and_state_files() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+
+        std::fs::write(tmp.path(), RESULTS).unwrap();
+        let responses = read_responses(tmp.path().to_str().unwrap()).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0].url().path(), "/admin");
+
+        std::fs::write(
+            tmp.path(),
+            r#"{"scans":[],"config":{},"responses":[{"type":"response","url":"http://localhost/css","path":"/css","wildcard":true,"status":301,"content_length":173,"line_count":10,"word_count":16,"headers":{}}]}"#,
+        )
+        .unwrap();
+        let responses = read_responses(tmp.path().to_str().unwrap()).unwrap();
+        assert_eq!(responses.len(), 1);
+        assert!(responses[0].wildcard());
+
+        std::fs::write(tmp.path(), "not json").unwrap();
+        assert!(read_responses(tmp.path().to_str().unwrap()).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// filters given on the command line should apply to previous results; body filters are
+    /// skipped when there's no body to look at
+    async fn refilter_applies_filters() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), RESULTS).unwrap();
+        let responses = read_responses(tmp.path().to_str().unwrap()).unwrap();
+
+        let config = Configuration {
+            filter_status: vec![403],
+            filter_regex: vec![String::from(".*")],
+            ..Default::default()
+        };
+
+        let kept = refilter(&config, responses).await;
+
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].url().path(), "/login");
+    }
+}
@@ -2,13 +2,20 @@ use std::{
     collections::HashMap,
     convert::{TryFrom, TryInto},
     fmt,
+    io::Cursor,
     str::FromStr,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
 use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use openssl::hash::{hash, MessageDigest};
+use regex::Regex;
 use reqwest::{
-    header::{HeaderMap, HeaderName, HeaderValue},
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_ENCODING, LOCATION},
     Response, StatusCode, Url,
 };
 use serde::ser::SerializeStruct;
@@ -17,13 +24,79 @@ use serde_json::Value;
 
 use crate::{
     config::OutputLevel,
+    cors::Cors,
+    decompress,
     event_handlers::{Command, Handles},
+    findings::{self, Finding, Rule, Severity},
+    fingerprint,
+    header_audit::HeaderAudit,
+    known_files::{KnownFile, KnownFiles},
+    listing,
+    scan_manager::ScanContext,
+    throttle,
     traits::FeroxSerialize,
     url::FeroxUrl,
     utils::{self, fmt_err, status_colorizer},
+    verbs::MethodMatrix,
     CommandSender,
 };
 
+lazy_static! {
+    /// Regular expression used to pull the page title out of html responses
+    static ref TITLE_REGEX: Regex = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+}
+
+/// whether or not response bodies are hashed (md5, sha256) as they're read
+static HASH_BODIES: AtomicBool = AtomicBool::new(true);
+
+/// Turn body hashing on or off for every response read from here on; hashing is only worth the
+/// time when the hashes are shown, looked up, or written out as json
+pub fn set_body_hashing(enabled: bool) {
+    HASH_BODIES.store(enabled, Ordering::Relaxed);
+}
+
+/// Given the body of a response, return the contents of its `<title>` tag, with whitespace
+/// collapsed; returns an empty string when there is no title
+pub fn extract_title(text: &str) -> String {
+    TITLE_REGEX
+        .captures(text)
+        .and_then(|captures| captures.get(1))
+        .map(|title| {
+            title
+                .as_str()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+/// Hash the given favicon the same way Shodan does (http.favicon.hash), i.e. a signed mmh3 hash
+/// of the favicon's base64 encoding, with a newline inserted after every 76 characters
+pub fn favicon_hash(favicon: &[u8]) -> i32 {
+    let encoded = base64::encode(favicon);
+
+    let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / 76 + 1);
+
+    for chunk in encoded.as_bytes().chunks(76) {
+        wrapped.push_str(&String::from_utf8_lossy(chunk));
+        wrapped.push('\n');
+    }
+
+    murmur3::murmur3_32(&mut Cursor::new(wrapped), 0).unwrap_or_default() as i32
+}
+
+/// Hex encoded digest of the given bytes; empty if the digest couldn't be calculated
+fn hex_digest(digest: MessageDigest, bytes: &[u8]) -> String {
+    match hash(digest, bytes) {
+        Ok(digest) => digest.iter().map(|byte| format!("{:02x}", byte)).collect(),
+        Err(e) => {
+            log::warn!("Could not hash response body: {}", e);
+            String::new()
+        }
+    }
+}
+
 /// A `FeroxResponse`, derived from a `Response` to a submitted `Request`
 #[derive(Debug, Clone)]
 pub struct FeroxResponse {
@@ -36,9 +109,14 @@ pub struct FeroxResponse {
     /// The full response text
     text: String,
 
-    /// The content-length of this response, if known
+    /// The content-length of this response, if known; the size of the decoded body
     content_length: u64,
 
+    /// The size of this response's body as it was sent over the wire, before any content
+    /// encoding (gzip, brotli, deflate) was undone; the same as `content_length` when the
+    /// response wasn't compressed
+    transfer_length: u64,
+
     /// The number of lines contained in the body of this response, if known
     line_count: usize,
 
@@ -51,6 +129,55 @@ pub struct FeroxResponse {
     /// Wildcard response status
     wildcard: bool,
 
+    /// Technologies detected in this response (--fingerprint)
+    technologies: Vec<String>,
+
+    /// Contents of the `<title>` tag, if the response is html and has one
+    title: String,
+
+    /// Shodan-compatible (mmh3) hash of the response body, only calculated for .ico files
+    favicon_hash: Option<i32>,
+
+    /// hex encoded md5 of the (decoded) response body, empty if the body wasn't read
+    md5: String,
+
+    /// hex encoded sha256 of the (decoded) response body, empty if the body wasn't read
+    sha256: String,
+
+    /// hashes shown alongside the url in text output (--hashes)
+    shown_hashes: Vec<String>,
+
+    /// well-known file whose hash matches this response's body, if any (--fingerprint-db)
+    known_file: Option<KnownFile>,
+
+    /// first printable characters of the body, whitespace collapsed, shown alongside the url
+    /// (--show-preview)
+    preview: String,
+
+    /// how the resource's responses to HEAD and OPTIONS differ from this one (--verb-compare)
+    method_matrix: Option<MethodMatrix>,
+
+    /// Name of the charset the body was decoded with, empty if the body wasn't read
+    charset: String,
+
+    /// whether the body looks like a directory listing
+    listing: bool,
+
+    /// whether the resource is missing from an OpenAPI/Swagger spec that covers it (--api)
+    undocumented: bool,
+
+    /// CORS policy of the resource, as seen by a request from another origin (--cors)
+    cors: Option<Cors>,
+
+    /// ratings of the response's security headers (--header-audit)
+    header_audit: Option<HeaderAudit>,
+
+    /// most severe interesting file rule matched by this response, if any
+    finding: Option<Finding>,
+
+    /// scan that requested this response, if it was requested on behalf of one
+    scan: Option<ScanContext>,
+
     /// whether the user passed --quiet|--silent on the command line
     pub(crate) output_level: OutputLevel,
 }
@@ -64,10 +191,27 @@ impl Default for FeroxResponse {
             status: Default::default(),
             text: "".to_string(),
             content_length: 0,
+            transfer_length: 0,
             line_count: 0,
             word_count: 0,
             headers: Default::default(),
             wildcard: false,
+            technologies: Vec::new(),
+            title: String::new(),
+            favicon_hash: None,
+            md5: String::new(),
+            sha256: String::new(),
+            shown_hashes: Vec::new(),
+            known_file: None,
+            preview: String::new(),
+            method_matrix: None,
+            charset: String::new(),
+            listing: false,
+            undocumented: false,
+            cors: None,
+            header_audit: None,
+            finding: None,
+            scan: None,
             output_level: Default::default(),
         }
     }
@@ -119,6 +263,166 @@ impl FeroxResponse {
         self.content_length
     }
 
+    /// Get the size of this response's body as it was sent over the wire (i.e. compressed)
+    pub fn transfer_length(&self) -> u64 {
+        self.transfer_length
+    }
+
+    /// Get the technologies detected in this response
+    pub fn technologies(&self) -> &[String] {
+        &self.technologies
+    }
+
+    /// Get the contents of the response's `<title>` tag, empty if there was none
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Get the Shodan-compatible hash of the response body, only calculated for .ico files
+    pub fn favicon_hash(&self) -> Option<i32> {
+        self.favicon_hash
+    }
+
+    /// Get the hex encoded md5 of the response body, empty if the body wasn't read
+    pub fn md5(&self) -> &str {
+        &self.md5
+    }
+
+    /// Get the hex encoded sha256 of the response body, empty if the body wasn't read
+    pub fn sha256(&self) -> &str {
+        &self.sha256
+    }
+
+    /// Get the well-known file whose hash matches this response's body, if any
+    pub fn known_file(&self) -> Option<&KnownFile> {
+        self.known_file.as_ref()
+    }
+
+    /// Get the preview of the response body, empty unless --show-preview was used
+    pub fn preview(&self) -> &str {
+        &self.preview
+    }
+
+    /// Get the resource's method matrix, if --verb-compare was used
+    pub fn method_matrix(&self) -> Option<&MethodMatrix> {
+        self.method_matrix.as_ref()
+    }
+
+    /// Get the name of the charset the body was decoded with, empty if the body wasn't read
+    pub fn charset(&self) -> &str {
+        &self.charset
+    }
+
+    /// Get whether the body looks like a directory listing
+    pub fn listing(&self) -> bool {
+        self.listing
+    }
+
+    /// Get whether the resource is missing from an OpenAPI/Swagger spec that covers it
+    pub fn undocumented(&self) -> bool {
+        self.undocumented
+    }
+
+    /// Get the resource's CORS policy, if --cors was used
+    pub fn cors(&self) -> Option<&Cors> {
+        self.cors.as_ref()
+    }
+
+    /// Get the ratings of the response's security headers, if --header-audit was used
+    pub fn header_audit(&self) -> Option<&HeaderAudit> {
+        self.header_audit.as_ref()
+    }
+
+    /// Get the most severe interesting file rule matched by this response, if any
+    pub fn finding(&self) -> Option<&Finding> {
+        self.finding.as_ref()
+    }
+
+    /// Get the scan that requested this response, if any
+    pub fn scan(&self) -> Option<&ScanContext> {
+        self.scan.as_ref()
+    }
+
+    /// Check a successful response against the given interesting file rules; the most severe
+    /// match, if any, is stored in `finding`
+    pub fn flag_findings(&mut self, rules: &[Rule]) {
+        if self.status.is_success() {
+            self.finding = findings::check(rules, self.url.path(), &self.text);
+        }
+    }
+
+    /// Flag the resource as missing from the OpenAPI/Swagger spec that covers it (--api)
+    pub fn set_undocumented(&mut self, undocumented: bool) {
+        self.undocumented = undocumented;
+    }
+
+    /// Set the resource's CORS policy (--cors)
+    pub fn set_cors(&mut self, cors: Cors) {
+        self.cors = Some(cors);
+    }
+
+    /// Rate the response's security headers (--header-audit)
+    pub fn audit_headers(&mut self) {
+        self.header_audit = Some(HeaderAudit::new(&self.url, &self.headers));
+    }
+
+    /// Set how the resource's responses to other methods differ from this one (--verb-compare)
+    pub fn set_method_matrix(&mut self, matrix: MethodMatrix) {
+        self.method_matrix = Some(matrix);
+    }
+
+    /// Show the given body hashes (md5, sha256) alongside the url in text output (--hashes)
+    pub fn show_hashes(&mut self, algorithms: &[String]) {
+        self.shown_hashes = algorithms.to_vec();
+    }
+
+    /// Keep the first `length` printable characters of the body, with runs of whitespace
+    /// collapsed into a single space, as a preview shown alongside the url (--show-preview)
+    pub fn set_preview(&mut self, length: usize) {
+        if length == 0 {
+            return;
+        }
+
+        let mut preview = String::new();
+        let mut chars = 0;
+        let mut pending_space = false;
+
+        for c in self.text.chars() {
+            if chars >= length {
+                break;
+            }
+
+            if c.is_whitespace() {
+                pending_space = !preview.is_empty();
+            } else if !c.is_control() {
+                if pending_space && chars + 1 < length {
+                    preview.push(' ');
+                    chars += 1;
+                }
+
+                pending_space = false;
+                preview.push(c);
+                chars += 1;
+            }
+        }
+
+        self.preview = preview;
+    }
+
+    /// Look up a successful response's body hashes in the given database of known files; the
+    /// match, if any, is stored in `known_file`
+    pub fn identify(&mut self, known_files: &KnownFiles) {
+        if self.status.is_success() {
+            self.known_file = known_files.lookup(&self.md5, &self.sha256).cloned();
+        }
+    }
+
+    /// Examine the response's headers and body for known technologies; the results are stored
+    /// in `technologies`
+    pub fn fingerprint(&mut self) {
+        self.technologies = fingerprint::fingerprint(self);
+    }
+
     /// Set `FeroxResponse`'s `url` attribute, has no affect if an error occurs
     pub fn set_url(&mut self, url: &str) {
         match Url::parse(&url) {
@@ -137,10 +441,10 @@ impl FeroxResponse {
     }
 
     /// set `text` attribute; update words/lines/content_length
-    #[cfg(test)]
     pub fn set_text(&mut self, text: &str) {
         self.text = String::from(text);
         self.content_length = self.text.len() as u64;
+        self.transfer_length = self.content_length;
         self.line_count = self.text.lines().count();
         self.word_count = self
             .text
@@ -149,6 +453,17 @@ impl FeroxResponse {
             .sum();
     }
 
+    /// set `status` attribute
+    pub fn set_status(&mut self, status: StatusCode) {
+        self.status = status;
+    }
+
+    /// set `headers` attribute
+    #[cfg(test)]
+    pub fn set_headers(&mut self, headers: HeaderMap) {
+        self.headers = headers;
+    }
+
     /// free the `text` data, reducing memory usage
     pub fn drop_text(&mut self) {
         self.text = String::new();
@@ -175,6 +490,22 @@ impl FeroxResponse {
         self.url.query_pairs().count() > 0 || has_extension
     }
 
+    /// Extension of the last segment of the response's path, if it has one
+    ///
+    /// i.e. `js` for http://localhost/some/path/stuff.js
+    pub fn extension(&self) -> Option<&str> {
+        let last = self.url.path_segments()?.last()?;
+
+        let mut parts = last.rsplitn(2, '.');
+        let extension = parts.next()?;
+
+        match parts.next() {
+            // no dot at all, or a dotfile (.htaccess) that only has a name
+            Some(name) if !name.is_empty() && !extension.is_empty() => Some(extension),
+            _ => None,
+        }
+    }
+
     /// Returns line count of the response text.
     pub fn line_count(&self) -> usize {
         self.line_count
@@ -190,37 +521,91 @@ impl FeroxResponse {
         let url = response.url().clone();
         let status = response.status();
         let headers = response.headers().clone();
-        let content_length = response.content_length().unwrap_or(0);
-
-        let text = if read_body {
-            // .text() consumes the response, must be called last
-            // additionally, --extract-links is currently the only place we use the body of the
-            // response, so we forego the processing if not performing extraction
-            match response.text().await {
-                // await the response's body
-                Ok(text) => text,
+        let header_length = response.content_length().unwrap_or(0);
+
+        let mut favicon_hash = None;
+        let mut md5 = String::new();
+        let mut sha256 = String::new();
+        let mut charset = String::new();
+
+        let is_icon = url.path().ends_with(".ico");
+
+        let (text, content_length, transfer_length) = if read_body {
+            // bodies are always read as raw bytes, so that both their size on the wire and their
+            // decoded size are known; --throttle paces the reads
+            match throttle::read_body(response).await {
+                Ok(raw) => {
+                    let transfer_length = raw.len() as u64;
+
+                    let encoding = headers
+                        .get(CONTENT_ENCODING)
+                        .and_then(|value| value.to_str().ok());
+
+                    let bytes = decompress::decode(encoding, raw);
+
+                    if is_icon && !bytes.is_empty() {
+                        // icons are binary, so the raw bytes are used to calculate the favicon
+                        // hash before being (lossily) converted to text like any other response
+                        favicon_hash = Some(self::favicon_hash(&bytes));
+                    }
+
+                    if HASH_BODIES.load(Ordering::Relaxed) {
+                        // hashed before being decoded into text, so that hashes match those of
+                        // the file on disk
+                        md5 = hex_digest(MessageDigest::md5(), &bytes);
+                        sha256 = hex_digest(MessageDigest::sha256(), &bytes);
+                    }
+
+                    let content_length = bytes.len() as u64;
+
+                    // decoded using the detected charset, so that word and line counts are
+                    // correct for non-utf8 pages
+                    let (text, detected) = crate::charset::decode(&headers, &bytes);
+                    charset = detected.to_string();
+
+                    (text, content_length, transfer_length)
+                }
                 Err(e) => {
                     log::warn!("Could not parse body from response: {}", e);
-                    String::new()
+                    (String::new(), header_length, header_length)
                 }
             }
         } else {
-            String::new()
+            (String::new(), header_length, header_length)
         };
 
         let line_count = text.lines().count();
         let word_count = text.lines().map(|s| s.split_whitespace().count()).sum();
+        let title = extract_title(&text);
+        let listing = status.is_success() && listing::is_listing(&text);
 
         FeroxResponse {
             url,
             status,
             content_length,
+            transfer_length,
             text,
             headers,
             line_count,
             word_count,
             output_level,
             wildcard: false,
+            technologies: Vec::new(),
+            title,
+            favicon_hash,
+            md5,
+            sha256,
+            shown_hashes: Vec::new(),
+            known_file: None,
+            preview: String::new(),
+            method_matrix: None,
+            charset,
+            listing,
+            undocumented: false,
+            cors: None,
+            header_audit: None,
+            finding: None,
+            scan: ScanContext::current(),
         }
     }
 
@@ -307,6 +692,40 @@ impl FeroxResponse {
         false
     }
 
+    /// Absolute url of the Location header of a 3xx response, resolved against the response's url
+    pub fn redirect_location(&self) -> Option<Url> {
+        if !self.status().is_redirection() {
+            return None;
+        }
+
+        let location = self.headers().get(LOCATION)?.to_str().ok()?;
+        self.url().join(location).ok()
+    }
+
+    /// Location of a redirect that only adds or removes a trailing slash, i.e. /admin -> /admin/
+    ///
+    /// used by --smart-slash to find the canonical url of a response
+    pub fn slash_redirect(&self) -> Option<Url> {
+        let target = self.redirect_location()?;
+
+        let path = self.url().path();
+
+        let toggled_path = match path.strip_suffix('/') {
+            Some("") => return None, // nothing to remove from the root
+            Some(stripped) => stripped.to_string(),
+            None => format!("{}/", path),
+        };
+
+        let mut toggled = self.url().clone();
+        toggled.set_path(&toggled_path);
+
+        if target == toggled {
+            Some(target)
+        } else {
+            None
+        }
+    }
+
     /// Simple helper to send a `FeroxResponse` over the tx side of an `mpsc::unbounded_channel`
     pub fn send_report(self, report_sender: CommandSender) -> Result<()> {
         log::trace!("enter: send_report({:?}", report_sender);
@@ -367,12 +786,71 @@ impl FeroxSerialize for FeroxResponse {
             message
         } else {
             // not a wildcard, just create a normal entry
+            let mut url = self.url().to_string();
+
+            if !matches!(self.output_level, OutputLevel::Silent) {
+                // --silent output is meant to be piped elsewhere, so only the url is shown
+                if !self.technologies.is_empty() {
+                    url.push_str(&format!(" [{}]", self.technologies.join(", ")));
+                }
+
+                if !self.title.is_empty() {
+                    url.push_str(&format!(" \"{}\"", self.title));
+                }
+
+                if let Some(hash) = self.favicon_hash {
+                    url.push_str(&format!(" (favicon hash: {})", hash));
+                }
+
+                if let Some(known_file) = &self.known_file {
+                    url.push_str(&format!(" (known file: {})", known_file));
+                }
+
+                for algorithm in &self.shown_hashes {
+                    let hash = match algorithm.as_str() {
+                        "md5" => &self.md5,
+                        "sha256" => &self.sha256,
+                        _ => continue,
+                    };
+
+                    if !hash.is_empty() {
+                        url.push_str(&format!(" ({}: {})", algorithm, hash));
+                    }
+                }
+
+                if self.listing {
+                    url.push_str(" (listing=true)");
+                }
+
+                if self.undocumented {
+                    url.push_str(" (undocumented)");
+                }
+
+                if let Some(summary) = self.cors.as_ref().and_then(Cors::summary) {
+                    url.push_str(&format!(" (cors: {})", summary));
+                }
+
+                if !self.preview.is_empty() {
+                    url.push_str(&format!(" (preview: {})", self.preview));
+                }
+
+                if let Some(matrix) = &self.method_matrix {
+                    if !matrix.differences.is_empty() {
+                        url.push_str(&format!(" (methods: {})", matrix.summary()));
+                    }
+                }
+
+                if let Some(finding) = &self.finding {
+                    url.push_str(&format!(" {}", finding.tag()));
+                }
+            }
+
             utils::create_report_string(
                 self.status.as_str(),
                 &lines,
                 &words,
                 &chars,
-                self.url().as_str(),
+                &url,
                 self.output_level,
             )
         }
@@ -438,10 +916,86 @@ impl Serialize for FeroxResponse {
         state.serialize_field("wildcard", &self.wildcard)?;
         state.serialize_field("status", &self.status.as_u16())?;
         state.serialize_field("content_length", &self.content_length)?;
+
+        if self.transfer_length != self.content_length {
+            // only included when the response was compressed
+            state.serialize_field("transfer_length", &self.transfer_length)?;
+        }
+
         state.serialize_field("line_count", &self.line_count)?;
         state.serialize_field("word_count", &self.word_count)?;
         state.serialize_field("headers", &headers)?;
 
+        if !self.technologies.is_empty() {
+            // only included when --fingerprint found something
+            state.serialize_field("technologies", &self.technologies)?;
+        }
+
+        if !self.title.is_empty() {
+            state.serialize_field("title", &self.title)?;
+        }
+
+        if let Some(hash) = self.favicon_hash {
+            state.serialize_field("favicon_hash", &hash)?;
+        }
+
+        if !self.sha256.is_empty() {
+            // only included when the body was read
+            state.serialize_field("md5", &self.md5)?;
+            state.serialize_field("sha256", &self.sha256)?;
+        }
+
+        if let Some(known_file) = &self.known_file {
+            // only included when --fingerprint-db had a matching hash
+            state.serialize_field("known_file", known_file)?;
+        }
+
+        if !self.preview.is_empty() {
+            // only included when --show-preview was used
+            state.serialize_field("preview", &self.preview)?;
+        }
+
+        if let Some(matrix) = &self.method_matrix {
+            // only included when --verb-compare was used
+            state.serialize_field("method_matrix", matrix)?;
+        }
+
+        if !self.charset.is_empty() {
+            // only included when the body was read
+            state.serialize_field("charset", &self.charset)?;
+        }
+
+        if self.listing {
+            state.serialize_field("listing", &self.listing)?;
+        }
+
+        if self.undocumented {
+            // only included when a spec covering the resource was found (--api)
+            state.serialize_field("undocumented", &self.undocumented)?;
+        }
+
+        if let Some(cors) = &self.cors {
+            // only included when --cors checked the resource
+            state.serialize_field("cors", cors)?;
+        }
+
+        if let Some(audit) = &self.header_audit {
+            // only included when --header-audit was used
+            state.serialize_field("security_headers", audit)?;
+        }
+
+        if let Some(finding) = &self.finding {
+            // only included when an interesting file rule matched
+            state.serialize_field("severity", finding.severity.as_str())?;
+            state.serialize_field("finding", &finding.name)?;
+        }
+
+        if let Some(scan) = &self.scan {
+            // only included when the response was requested on behalf of a scan
+            state.serialize_field("scan_id", &scan.id)?;
+            state.serialize_field("depth", &scan.depth)?;
+        }
+
         state.end()
     }
 }
@@ -458,8 +1012,25 @@ impl<'de> Deserialize<'de> for FeroxResponse {
             status: StatusCode::OK,
             text: String::new(),
             content_length: 0,
+            transfer_length: 0,
             headers: HeaderMap::new(),
             wildcard: false,
+            technologies: Vec::new(),
+            title: String::new(),
+            favicon_hash: None,
+            md5: String::new(),
+            sha256: String::new(),
+            shown_hashes: Vec::new(),
+            known_file: None,
+            preview: String::new(),
+            method_matrix: None,
+            charset: String::new(),
+            listing: false,
+            undocumented: false,
+            cors: None,
+            header_audit: None,
+            finding: None,
+            scan: None,
             output_level: Default::default(),
             line_count: 0,
             word_count: 0,
@@ -467,6 +1038,17 @@ impl<'de> Deserialize<'de> for FeroxResponse {
 
         let map: HashMap<String, Value> = HashMap::deserialize(deserializer)?;
 
+        // only serialized when it differs from content_length
+        let mut transfer_length = None;
+
+        // only serialized when an interesting file rule matched
+        let mut severity = None;
+        let mut finding = None;
+
+        // only serialized when the response was requested on behalf of a scan
+        let mut scan_id = None;
+        let mut depth = 0;
+
         for (key, value) in &map {
             match key.as_str() {
                 "url" => {
@@ -490,6 +1072,9 @@ impl<'de> Deserialize<'de> for FeroxResponse {
                         response.content_length = num;
                     }
                 }
+                "transfer_length" => {
+                    transfer_length = value.as_u64();
+                }
                 "line_count" => {
                     if let Some(num) = value.as_u64() {
                         response.line_count = num.try_into().unwrap_or_default();
@@ -521,10 +1106,94 @@ impl<'de> Deserialize<'de> for FeroxResponse {
                         response.wildcard = result;
                     }
                 }
+                "title" => {
+                    if let Some(title) = value.as_str() {
+                        response.title = title.to_string();
+                    }
+                }
+                "favicon_hash" => {
+                    if let Some(num) = value.as_i64() {
+                        response.favicon_hash = i32::try_from(num).ok();
+                    }
+                }
+                "md5" => {
+                    if let Some(md5) = value.as_str() {
+                        response.md5 = md5.to_string();
+                    }
+                }
+                "sha256" => {
+                    if let Some(sha256) = value.as_str() {
+                        response.sha256 = sha256.to_string();
+                    }
+                }
+                "known_file" => {
+                    response.known_file = serde_json::from_value(value.clone()).ok();
+                }
+                "preview" => {
+                    if let Some(preview) = value.as_str() {
+                        response.preview = preview.to_string();
+                    }
+                }
+                "method_matrix" => {
+                    response.method_matrix = serde_json::from_value(value.clone()).ok();
+                }
+                "charset" => {
+                    if let Some(charset) = value.as_str() {
+                        response.charset = charset.to_string();
+                    }
+                }
+                "listing" => {
+                    if let Some(result) = value.as_bool() {
+                        response.listing = result;
+                    }
+                }
+                "undocumented" => {
+                    if let Some(result) = value.as_bool() {
+                        response.undocumented = result;
+                    }
+                }
+                "cors" => {
+                    response.cors = serde_json::from_value(value.clone()).ok();
+                }
+                "security_headers" => {
+                    response.header_audit = serde_json::from_value(value.clone()).ok();
+                }
+                "severity" => {
+                    severity = value.as_str().and_then(Severity::from_name);
+                }
+                "finding" => {
+                    finding = value.as_str().map(String::from);
+                }
+                "scan_id" => {
+                    scan_id = value.as_str().map(String::from);
+                }
+                "depth" => {
+                    if let Some(num) = value.as_u64() {
+                        depth = num.try_into().unwrap_or_default();
+                    }
+                }
+                "technologies" => {
+                    if let Some(techs) = value.as_array() {
+                        response.technologies = techs
+                            .iter()
+                            .filter_map(|tech| tech.as_str().map(String::from))
+                            .collect();
+                    }
+                }
                 _ => {}
             }
         }
 
+        response.transfer_length = transfer_length.unwrap_or(response.content_length);
+
+        if let (Some(severity), Some(name)) = (severity, finding) {
+            response.finding = Some(Finding { name, severity });
+        }
+
+        if let Some(id) = scan_id {
+            response.scan = Some(ScanContext::new(&id, depth));
+        }
+
         Ok(response)
     }
 }
@@ -533,6 +1202,339 @@ impl<'de> Deserialize<'de> for FeroxResponse {
 mod tests {
     use super::*;
 
+    #[test]
+    /// extract_title should pull the title out of html, collapsing any whitespace
+    fn extract_title_finds_title() {
+        let html =
+            "<html><head><TITLE lang=\"en\">\n  Apache Tomcat/9.0.41\n  </TITLE></head></html>";
+        assert_eq!(extract_title(html), "Apache Tomcat/9.0.41");
+        assert_eq!(extract_title("no title here"), "");
+    }
+
+    #[test]
+    /// favicon_hash should match the values produced by python's mmh3.hash(base64.encodebytes())
+    fn favicon_hash_matches_shodan() {
+        assert_eq!(favicon_hash(b"hello"), 1155597304);
+
+        // longer than 76 base64 characters, so the encoding is wrapped across lines
+        let favicon: Vec<u8> = (0..100).collect();
+        assert_eq!(favicon_hash(&favicon), -1165240594);
+    }
+
+    #[test]
+    /// transfer_length should only be serialized for compressed responses, and default to
+    /// content_length when missing
+    fn transfer_length_serialization() {
+        let compressed = r#"{"type":"response","url":"http://localhost/","status":200,"content_length":1000,"transfer_length":120}"#;
+        let response: FeroxResponse = serde_json::from_str(compressed).unwrap();
+        assert_eq!(response.content_length(), 1000);
+        assert_eq!(response.transfer_length(), 120);
+        assert!(serde_json::to_string(&response)
+            .unwrap()
+            .contains(r#""transfer_length":120"#));
+
+        let plain =
+            r#"{"type":"response","url":"http://localhost/","status":200,"content_length":1000}"#;
+        let response: FeroxResponse = serde_json::from_str(plain).unwrap();
+        assert_eq!(response.transfer_length(), 1000);
+        assert!(!serde_json::to_string(&response)
+            .unwrap()
+            .contains("transfer_length"));
+    }
+
+    #[test]
+    /// the originating scan should round trip through json, and only be included when known
+    fn scan_context_serialization() {
+        let mut response = FeroxResponse::default();
+        assert!(!serde_json::to_string(&response)
+            .unwrap()
+            .contains("scan_id"));
+
+        response.scan = Some(ScanContext::new("d8b6eb4f", 1));
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains(r#""scan_id":"d8b6eb4f","depth":1"#));
+
+        let parsed: FeroxResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.scan(), Some(&ScanContext::new("d8b6eb4f", 1)));
+    }
+
+    #[test]
+    /// extension should only be found in the last segment of the path
+    fn extension_from_last_path_segment() {
+        let extension = |url: &str| {
+            let mut response = FeroxResponse::default();
+            response.set_url(url);
+            response.extension().map(String::from)
+        };
+
+        assert_eq!(
+            extension("http://localhost/app.JS"),
+            Some(String::from("JS"))
+        );
+        assert_eq!(
+            extension("http://localhost/v1.2/index.php?id=1"),
+            Some(String::from("php"))
+        );
+        assert_eq!(extension("http://localhost/v1.2/"), None);
+        assert_eq!(extension("http://localhost/.htaccess"), None);
+        assert_eq!(extension("http://localhost/admin"), None);
+    }
+
+    #[test]
+    /// only redirects that add or remove a trailing slash should be considered slash redirects
+    fn slash_redirect_requires_toggled_slash() {
+        let redirect = |url: &str, location: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(LOCATION, HeaderValue::from_str(location).unwrap());
+
+            let mut response = FeroxResponse::default();
+            response.set_url(url);
+            response.set_status(StatusCode::MOVED_PERMANENTLY);
+            response.set_headers(headers);
+            response
+        };
+
+        let added = redirect("http://localhost/admin?x=1", "/admin/?x=1");
+        assert_eq!(
+            added.slash_redirect().unwrap().as_str(),
+            "http://localhost/admin/?x=1"
+        );
+
+        let removed = redirect("http://localhost/admin/", "http://localhost/admin");
+        assert_eq!(
+            removed.slash_redirect().unwrap().as_str(),
+            "http://localhost/admin"
+        );
+
+        assert!(redirect("http://localhost/admin", "/login")
+            .slash_redirect()
+            .is_none());
+        assert!(redirect("http://localhost/", "http://localhost")
+            .slash_redirect()
+            .is_none());
+
+        let mut not_redirect = redirect("http://localhost/admin", "/admin/");
+        not_redirect.set_status(StatusCode::OK);
+        assert!(not_redirect.slash_redirect().is_none());
+    }
+
+    #[test]
+    /// charset should round trip through json, and be left out when the body wasn't read
+    fn charset_serialization() {
+        let json =
+            r#"{"type":"response","url":"http://localhost/","status":200,"charset":"Shift_JIS"}"#;
+        let response: FeroxResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.charset(), "Shift_JIS");
+        assert!(serde_json::to_string(&response)
+            .unwrap()
+            .contains(r#""charset":"Shift_JIS""#));
+
+        let response = FeroxResponse::default();
+        assert!(!serde_json::to_string(&response)
+            .unwrap()
+            .contains("charset"));
+    }
+
+    #[test]
+    /// body hashes should be hex encoded
+    fn hex_digest_matches_known_values() {
+        assert_eq!(
+            hex_digest(MessageDigest::md5(), b"hello"),
+            "5d41402abc4b2a76b9719d911017c592"
+        );
+        assert_eq!(
+            hex_digest(MessageDigest::sha256(), b"hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[test]
+    /// hashes should always be in json, but only shown in text output when asked for
+    fn hashes_serialization() {
+        let json = r#"{"type":"response","url":"http://localhost/","status":200,"md5":"abc","sha256":"def"}"#;
+        let mut response: FeroxResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.md5(), "abc");
+        assert_eq!(response.sha256(), "def");
+        assert!(response
+            .as_json()
+            .unwrap()
+            .contains(r#""md5":"abc","sha256":"def""#));
+        assert!(!response.as_str().contains("def"));
+
+        response.show_hashes(&[String::from("sha256")]);
+        assert!(response.as_str().contains("(sha256: def)"));
+        assert!(!response.as_str().contains("md5"));
+
+        let response = FeroxResponse::default();
+        assert!(!response.as_json().unwrap().contains("sha256"));
+    }
+
+    #[test]
+    /// previews should be limited to printable characters, with whitespace collapsed
+    fn set_preview_collapses_whitespace() {
+        let mut response = FeroxResponse::default();
+        response.set_text("\n\n  <html>\r\n\t<head>\u{7}<title>Index of /</title>");
+
+        response.set_preview(0);
+        assert_eq!(response.preview(), "");
+        assert!(!response.as_json().unwrap().contains("preview"));
+
+        response.set_preview(20);
+        assert_eq!(response.preview(), "<html> <head><title>");
+        assert!(response
+            .as_str()
+            .contains("(preview: <html> <head><title>)"));
+        assert!(response
+            .as_json()
+            .unwrap()
+            .contains(r#""preview":"<html> <head><title>""#));
+
+        response.set_preview(1000);
+        assert_eq!(response.preview(), "<html> <head><title>Index of /</title>");
+
+        let json =
+            r#"{"type":"response","url":"http://localhost/","status":200,"preview":"hello"}"#;
+        let response: FeroxResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.preview(), "hello");
+    }
+
+    #[test]
+    /// method matrices should show their differences in text output, and round trip through json
+    fn method_matrix_serialization() {
+        let json = r#"{"type":"response","url":"http://localhost/api","status":200,"method_matrix":{"methods":{"GET":{"status":200},"OPTIONS":{"status":204,"allow":["GET","PUT"]}},"differences":["OPTIONS 204","OPTIONS allows GET, PUT"]}}"#;
+        let response: FeroxResponse = serde_json::from_str(json).unwrap();
+        let matrix = response.method_matrix().unwrap();
+        assert_eq!(matrix.methods["OPTIONS"].allow, vec!["GET", "PUT"]);
+        assert!(response
+            .as_str()
+            .contains("(methods: OPTIONS 204; OPTIONS allows GET, PUT)"));
+        assert!(response.as_json().unwrap().contains(
+            r#""method_matrix":{"methods":{"GET":{"status":200},"OPTIONS":{"status":204,"allow":["GET","PUT"]}},"differences":["OPTIONS 204","OPTIONS allows GET, PUT"]}"#
+        ));
+
+        let response = FeroxResponse::default();
+        assert!(!response.as_json().unwrap().contains("method_matrix"));
+    }
+
+    #[test]
+    /// known files should show up in both output formats, and round trip through json
+    fn known_file_serialization() {
+        let json = r#"{"type":"response","url":"http://localhost/jquery.js","status":200,"known_file":{"name":"jquery","version":"3.5.1"}}"#;
+        let response: FeroxResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.known_file().unwrap().to_string(), "jquery 3.5.1");
+        assert!(response
+            .as_json()
+            .unwrap()
+            .contains(r#""known_file":{"name":"jquery","version":"3.5.1"}"#));
+        assert!(response.as_str().contains("(known file: jquery 3.5.1)"));
+
+        let mut response = FeroxResponse::default();
+        response.identify(&KnownFiles::default());
+        assert!(response.known_file().is_none());
+        assert!(!response.as_json().unwrap().contains("known_file"));
+    }
+
+    #[test]
+    /// listing should show up as listing=true in both output formats, and only when true
+    fn listing_serialization() {
+        let json =
+            r#"{"type":"response","url":"http://localhost/files/","status":200,"listing":true}"#;
+        let mut response: FeroxResponse = serde_json::from_str(json).unwrap();
+        assert!(response.listing());
+        assert!(response.as_json().unwrap().contains(r#""listing":true"#));
+        assert!(response.as_str().contains("(listing=true)"));
+
+        response.listing = false;
+        assert!(!response.as_json().unwrap().contains("listing"));
+        assert!(!response.as_str().contains("listing"));
+    }
+
+    #[test]
+    /// undocumented resources should be marked in both output formats, and only when flagged
+    fn undocumented_serialization() {
+        let json = r#"{"type":"response","url":"http://localhost/api/debug","status":200,"undocumented":true}"#;
+        let mut response: FeroxResponse = serde_json::from_str(json).unwrap();
+        assert!(response.undocumented());
+        assert!(response
+            .as_json()
+            .unwrap()
+            .contains(r#""undocumented":true"#));
+        assert!(response.as_str().contains("(undocumented)"));
+
+        response.set_undocumented(false);
+        assert!(!response.as_json().unwrap().contains("undocumented"));
+        assert!(!response.as_str().contains("undocumented"));
+    }
+
+    #[test]
+    /// a checked cors policy should always be in json output, but only shown when reflected
+    fn cors_serialization() {
+        let json = r#"{"type":"response","url":"http://localhost/api/me","status":200,"cors":{"allow_origin":"https://evil.example","reflected":true,"credentials":true}}"#;
+        let mut response: FeroxResponse = serde_json::from_str(json).unwrap();
+        assert!(response.cors().unwrap().misconfigured());
+        assert!(response.as_json().unwrap().contains(
+            r#""cors":{"allow_origin":"https://evil.example","reflected":true,"credentials":true}"#
+        ));
+        assert!(response
+            .as_str()
+            .contains("(cors: origin reflected with credentials)"));
+
+        response.set_cors(Cors::default());
+        assert!(response.as_json().unwrap().contains(r#""reflected":false"#));
+        assert!(!response.as_str().contains("cors"));
+    }
+
+    #[test]
+    /// security header ratings should only be in json output, and only when audited
+    fn header_audit_serialization() {
+        let mut response = FeroxResponse::default();
+        response.set_url("https://localhost/login");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-frame-options", HeaderValue::from_static("DENY"));
+        response.set_headers(headers);
+
+        assert!(!response.as_json().unwrap().contains("security_headers"));
+
+        response.audit_headers();
+        let json = response.as_json().unwrap();
+        assert!(json.contains(
+            r#""security_headers":{"csp":"missing","hsts":"missing","x_frame_options":"present"}"#
+        ));
+        assert!(!response.as_str().contains("csp"));
+
+        let response: FeroxResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            response.header_audit().unwrap().hsts,
+            crate::header_audit::Rating::Missing
+        );
+    }
+
+    #[test]
+    /// interesting files should be flagged with a severity in both output formats
+    fn flag_findings_adds_severity() {
+        let mut response = FeroxResponse::default();
+        response.set_url("http://localhost/.git/HEAD");
+        response.set_status(StatusCode::OK);
+        response.set_text("ref: refs/heads/main");
+
+        response.flag_findings(&findings::builtin_rules());
+
+        let json = response.as_json().unwrap();
+        assert!(json.contains(r#""severity":"high""#));
+        assert!(json.contains(r#""finding":"Git repository""#));
+        assert!(response.as_str().contains("Git repository"));
+
+        let deserialized: FeroxResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.finding(), response.finding());
+
+        // the same path behind a 404 isn't interesting
+        response.set_status(StatusCode::NOT_FOUND);
+        response.finding = None;
+        response.flag_findings(&findings::builtin_rules());
+        assert!(response.finding().is_none());
+    }
+
     #[test]
     /// call reached_max_depth with max depth of zero, which is infinite recursion, expect false
     fn reached_max_depth_returns_early_on_zero() {
@@ -543,10 +1545,27 @@ mod tests {
             status: Default::default(),
             text: "".to_string(),
             content_length: 0,
+            transfer_length: 0,
             line_count: 0,
             word_count: 0,
             headers: Default::default(),
             wildcard: false,
+            technologies: Vec::new(),
+            title: String::new(),
+            favicon_hash: None,
+            md5: String::new(),
+            sha256: String::new(),
+            shown_hashes: Vec::new(),
+            known_file: None,
+            preview: String::new(),
+            method_matrix: None,
+            charset: String::new(),
+            listing: false,
+            undocumented: false,
+            cors: None,
+            header_audit: None,
+            finding: None,
+            scan: None,
             output_level: Default::default(),
         };
         let result = response.reached_max_depth(0, 0, handles);
@@ -564,10 +1583,27 @@ mod tests {
             status: Default::default(),
             text: "".to_string(),
             content_length: 0,
+            transfer_length: 0,
             line_count: 0,
             word_count: 0,
             headers: Default::default(),
             wildcard: false,
+            technologies: Vec::new(),
+            title: String::new(),
+            favicon_hash: None,
+            md5: String::new(),
+            sha256: String::new(),
+            shown_hashes: Vec::new(),
+            known_file: None,
+            preview: String::new(),
+            method_matrix: None,
+            charset: String::new(),
+            listing: false,
+            undocumented: false,
+            cors: None,
+            header_audit: None,
+            finding: None,
+            scan: None,
             output_level: Default::default(),
         };
 
@@ -585,10 +1621,27 @@ mod tests {
             status: Default::default(),
             text: "".to_string(),
             content_length: 0,
+            transfer_length: 0,
             line_count: 0,
             word_count: 0,
             headers: Default::default(),
             wildcard: false,
+            technologies: Vec::new(),
+            title: String::new(),
+            favicon_hash: None,
+            md5: String::new(),
+            sha256: String::new(),
+            shown_hashes: Vec::new(),
+            known_file: None,
+            preview: String::new(),
+            method_matrix: None,
+            charset: String::new(),
+            listing: false,
+            undocumented: false,
+            cors: None,
+            header_audit: None,
+            finding: None,
+            scan: None,
             output_level: Default::default(),
         };
 
@@ -606,10 +1659,27 @@ mod tests {
             status: Default::default(),
             text: "".to_string(),
             content_length: 0,
+            transfer_length: 0,
             line_count: 0,
             word_count: 0,
             headers: Default::default(),
             wildcard: false,
+            technologies: Vec::new(),
+            title: String::new(),
+            favicon_hash: None,
+            md5: String::new(),
+            sha256: String::new(),
+            shown_hashes: Vec::new(),
+            known_file: None,
+            preview: String::new(),
+            method_matrix: None,
+            charset: String::new(),
+            listing: false,
+            undocumented: false,
+            cors: None,
+            header_audit: None,
+            finding: None,
+            scan: None,
             output_level: Default::default(),
         };
 
@@ -627,10 +1697,27 @@ mod tests {
             status: Default::default(),
             text: "".to_string(),
             content_length: 0,
+            transfer_length: 0,
             line_count: 0,
             word_count: 0,
             headers: Default::default(),
             wildcard: false,
+            technologies: Vec::new(),
+            title: String::new(),
+            favicon_hash: None,
+            md5: String::new(),
+            sha256: String::new(),
+            shown_hashes: Vec::new(),
+            known_file: None,
+            preview: String::new(),
+            method_matrix: None,
+            charset: String::new(),
+            listing: false,
+            undocumented: false,
+            cors: None,
+            header_audit: None,
+            finding: None,
+            scan: None,
             output_level: Default::default(),
         };
 
@@ -0,0 +1,46 @@
+use std::future::Future;
+
+tokio::task_local! {
+    /// context of the scan on whose behalf the current task is running
+    static SCAN_CONTEXT: ScanContext;
+}
+
+/// Identifies the scan that produced a log entry or response
+///
+/// every task spawned for a scan runs within its context, which is how log entries and responses
+/// are tied back to the directory that was being scanned
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ScanContext {
+    /// FeroxScan.id of the originating scan
+    pub id: String,
+
+    /// recursion depth of the originating scan, 0 for the targets given by the user
+    pub depth: usize,
+}
+
+/// implementation of ScanContext
+impl ScanContext {
+    /// create a new ScanContext
+    pub fn new(id: &str, depth: usize) -> Self {
+        Self {
+            id: id.to_string(),
+            depth,
+        }
+    }
+
+    /// Context of the current task, if it's running on behalf of a scan
+    pub fn current() -> Option<Self> {
+        SCAN_CONTEXT
+            .try_with(|context| context.clone())
+            .ok()
+            .filter(|context| !context.id.is_empty())
+    }
+
+    /// Run the given future within this context
+    pub async fn scope<F>(self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        SCAN_CONTEXT.scope(self, future).await
+    }
+}
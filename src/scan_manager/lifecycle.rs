@@ -0,0 +1,73 @@
+use std::{
+    sync::atomic::{AtomicU8, Ordering},
+    thread,
+    time::Duration,
+};
+
+use crate::SLEEP_DURATION;
+
+/// phase the run is currently in, stored as a Lifecycle discriminant
+static LIFECYCLE: AtomicU8 = AtomicU8::new(Lifecycle::Starting as u8);
+
+/// Phases of the run as a whole
+///
+/// anything that writes the run's state to disk checks the current phase first; while a previous
+/// state file is being loaded, only part of it is in memory, and saving then would replace a
+/// meaningful state file with a near-empty one
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Lifecycle {
+    /// configuration is being read, no scans have started
+    Starting = 0,
+
+    /// the scans and statistics of a previous run are being loaded (--resume-from)
+    Loading = 1,
+
+    /// scans are running
+    Scanning = 2,
+
+    /// all scans are complete, the program is shutting down
+    Finished = 3,
+}
+
+/// implementation of Lifecycle
+impl Lifecycle {
+    /// The phase the run is currently in
+    pub fn current() -> Self {
+        Self::from_u8(LIFECYCLE.load(Ordering::Acquire))
+    }
+
+    /// Move the run into this phase
+    pub fn enter(self) {
+        log::debug!("lifecycle: {:?} -> {:?}", Self::current(), self);
+        LIFECYCLE.store(self as u8, Ordering::Release);
+    }
+
+    /// Whether state saved during this phase would be complete
+    pub fn can_save(self) -> bool {
+        self != Lifecycle::Loading
+    }
+
+    /// Block the calling thread until the run is in a phase in which state can be saved,
+    /// returning that phase
+    pub fn wait_until_saveable() -> Self {
+        loop {
+            let current = Self::current();
+
+            if current.can_save() {
+                return current;
+            }
+
+            thread::sleep(Duration::from_millis(SLEEP_DURATION / 5));
+        }
+    }
+
+    /// convert a stored discriminant back into a Lifecycle
+    pub(super) fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Lifecycle::Starting,
+            1 => Lifecycle::Loading,
+            2 => Lifecycle::Scanning,
+            _ => Lifecycle::Finished,
+        }
+    }
+}
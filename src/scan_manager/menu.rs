@@ -2,6 +2,19 @@ use crate::progress::PROGRESS_BAR;
 use console::{measure_text_width, pad_str, style, Alignment, Term};
 use indicatif::ProgressDrawTarget;
 
+/// What to do with the scans selected in the menu
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(super) enum MenuAction {
+    /// stop the scans entirely
+    Cancel,
+
+    /// pause only the selected scans
+    Pause,
+
+    /// resume selected scans that were previously paused
+    Resume,
+}
+
 /// Interactive scan cancellation menu
 #[derive(Debug)]
 pub(super) struct Menu {
@@ -51,15 +64,28 @@ impl Menu {
             style("ex").cyan(),
         );
 
+        let pause_msg = format!(
+            "Prefix with {} to {} or {} to {} instead ({}: p 3-5)",
+            style("p").yellow(),
+            style("pause").magenta(),
+            style("r").yellow(),
+            style("resume").green(),
+            style("ex").cyan(),
+        );
+
         let longest = measure_text_width(&instructions).max(measure_text_width(&name));
 
         let border = separator.repeat(longest);
 
         let padded_name = pad_str(&name, longest, Alignment::Center, None);
         let padded_force = pad_str(&force_msg, longest, Alignment::Center, None);
+        let padded_pause = pad_str(&pause_msg, longest, Alignment::Center, None);
 
         let header = format!("{}\n{}\n{}", border, padded_name, border);
-        let footer = format!("{}\n{}\n{}\n{}", border, instructions, padded_force, border);
+        let footer = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            border, instructions, padded_force, padded_pause, border
+        );
 
         Self {
             separator,
@@ -161,12 +187,27 @@ impl Menu {
         nums
     }
 
-    /// get comma-separated list of scan indexes from the user
-    pub(super) fn get_scans_from_user(&self) -> Option<(Vec<usize>, bool)> {
+    /// split the action to take (a leading p or r) from the rest of the line; cancel is the
+    /// default when no action is given
+    pub(super) fn split_action<'a>(&self, line: &'a str) -> (MenuAction, &'a str) {
+        let line = line.trim_start();
+
+        if let Some(rest) = line.strip_prefix('p') {
+            (MenuAction::Pause, rest)
+        } else if let Some(rest) = line.strip_prefix('r') {
+            (MenuAction::Resume, rest)
+        } else {
+            (MenuAction::Cancel, line)
+        }
+    }
+
+    /// get the action to take and a comma-separated list of scan indexes from the user
+    pub(super) fn get_scans_from_user(&self) -> Option<(MenuAction, Vec<usize>, bool)> {
         if let Ok(line) = self.term.read_line() {
             let force = line.contains("-f");
             let line = line.replace("-f", "");
-            Some((self.split_to_nums(&line), force))
+            let (action, line) = self.split_action(&line);
+            Some((action, self.split_to_nums(line), force))
         } else {
             None
         }
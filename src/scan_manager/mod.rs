@@ -4,14 +4,23 @@ mod scan;
 mod menu;
 mod utils;
 mod order;
+mod context;
+mod lifecycle;
+mod portable;
 mod state;
 #[cfg(test)]
 mod tests;
 
-pub(self) use menu::Menu;
+pub use context::ScanContext;
+pub use lifecycle::Lifecycle;
+pub(self) use menu::{Menu, MenuAction};
 pub use order::ScanOrder;
-pub use response_container::FeroxResponses;
+pub use portable::{MappingRecord, WordlistRecord};
+pub use response_container::{FeroxResponses, ResponseFingerprint};
 pub use scan::{FeroxScan, ScanStatus, ScanType};
 pub use scan_container::{FeroxScans, PAUSE_SCAN};
-pub use state::FeroxState;
-pub use utils::{resume_scan, start_max_time_thread};
+pub use state::{read_state, redact_url, redacted_settings, FeroxState};
+pub use utils::{
+    in_scan_window, parse_scan_window, resume_scan, start_max_time_thread,
+    start_request_limit_thread, start_scan_window_thread, time_spec_to_secs,
+};
@@ -0,0 +1,385 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use openssl::sha::sha256;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Configuration,
+    utils::{fmt_err, fmt_warn, get_unique_words_from_wordlist},
+    wordlist::Wordlist,
+    wordlists,
+};
+
+/// The wordlist a saved scan was using
+///
+/// a resumed scan picks up each directory where it left off by counting requests, so it has to
+/// request the same words in the same order as the scan it was resumed from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WordlistRecord {
+    /// hex encoded sha256 of the wordlist's words, see `Wordlist::sha256`
+    pub sha256: String,
+
+    /// number of words in the wordlist
+    pub words: usize,
+
+    /// the words themselves, one per line, when saved with --state-embed-wordlist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contents: Option<String>,
+}
+
+/// implementation of WordlistRecord
+impl WordlistRecord {
+    /// Record the given wordlist, along with its words if `embed` is set
+    pub fn new(wordlist: &Wordlist, embed: bool) -> Self {
+        Self {
+            sha256: wordlist.sha256(),
+            words: wordlist.fixed_len(),
+            contents: if embed {
+                Some(wordlist.contents())
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// A --wordlist-rules or --auto-wordlist file a saved scan was using, along with each wordlist it
+/// names; the file itself is small enough that it's always saved
+///
+/// a resumed scan that picked a different wordlist for a directory, or the same wordlist with
+/// different words, would skip or repeat words just like a changed -w
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MappingRecord {
+    /// the file's contents
+    pub contents: String,
+
+    /// each wordlist the file names, by the name it's given in the file
+    pub wordlists: BTreeMap<String, WordlistRecord>,
+}
+
+/// implementation of MappingRecord
+impl MappingRecord {
+    /// Record the file at `path`, along with the words of each wordlist it names if `embed` is
+    /// set; none when there's no file, or it can't be read
+    ///
+    /// wordlists that can't be read aren't recorded, the scan couldn't use them either
+    pub fn new(path: &str, embed: bool) -> Option<Self> {
+        if path.is_empty() {
+            return None;
+        }
+
+        let contents = fs::read_to_string(path).ok()?;
+        let mut file: toml::Value = toml::from_str(&contents).ok()?;
+
+        let wordlists = named_wordlists(&mut file)
+            .into_iter()
+            .filter_map(|name| {
+                let wordlist = get_unique_words_from_wordlist(name, true).ok()?;
+                Some((name.to_string(), WordlistRecord::new(&wordlist, embed)))
+            })
+            .collect();
+
+        Some(Self {
+            contents,
+            wordlists,
+        })
+    }
+}
+
+/// wordlists named by a parsed --wordlist-rules file (the `wordlist` of each of its `rules`) or
+/// --auto-wordlist file (each of its values)
+fn named_wordlists(file: &mut toml::Value) -> Vec<&mut String> {
+    let table = match file.as_table_mut() {
+        Some(table) => table,
+        None => return Vec::new(),
+    };
+
+    let mut names = Vec::new();
+
+    for (key, value) in table.iter_mut() {
+        match value {
+            toml::Value::String(name) => names.push(name),
+            toml::Value::Array(rules) if key == "rules" => {
+                for rule in rules.iter_mut() {
+                    if let Some(toml::Value::String(name)) = rule.get_mut("wordlist") {
+                        names.push(name);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    names
+}
+
+/// configured files (and directories) that a scan reads from
+fn input_paths(config: &mut Configuration) -> Vec<&mut String> {
+    let mut paths = vec![
+        &mut config.wordlist,
+        &mut config.targets,
+        &mut config.compare,
+        &mut config.ca_cert,
+        &mut config.query_file,
+        &mut config.fingerprint_db,
+        &mut config.auto_wordlist,
+        &mut config.wordlist_rules,
+        &mut config.interesting_rules,
+        &mut config.priority_keywords,
+    ];
+
+    paths.extend(config.plugins.iter_mut());
+    paths
+}
+
+/// configured files (and directories) that a scan writes to
+fn output_paths(config: &mut Configuration) -> Vec<&mut String> {
+    vec![
+        &mut config.output,
+        &mut config.urls_file,
+        &mut config.har,
+        &mut config.output_db,
+        &mut config.split_output,
+        &mut config.output_dir,
+        &mut config.debug_log,
+        &mut config.cache_dir,
+    ]
+}
+
+/// Rewrite each configured path beneath `cwd` as relative to it, so that the scan can be resumed
+/// from a copy of the same directory on another machine (or inside a container)
+pub(super) fn relativize_paths(config: &mut Configuration, cwd: &Path) {
+    for path in input_paths(config) {
+        relativize(path, cwd);
+    }
+
+    for path in output_paths(config) {
+        relativize(path, cwd);
+    }
+}
+
+/// rewrite the given path as relative to `cwd`, if it's beneath it
+fn relativize(path: &mut String, cwd: &Path) {
+    if let Ok(relative) = Path::new(path.as_str()).strip_prefix(cwd) {
+        if let Some(relative) = relative.to_str().filter(|relative| !relative.is_empty()) {
+            *path = relative.to_string();
+        }
+    }
+}
+
+/// Point each configured path that doesn't exist on this machine at wherever it can be found
+///
+/// files a scan reads from are looked for relative to the directory the scan was saved from,
+/// relative to the state file, and finally by name next to the state file; files a scan writes to
+/// are written to the current directory when their own directory doesn't exist
+pub(super) fn localize_paths(
+    config: &mut Configuration,
+    saved_cwd: Option<&Path>,
+    state_dir: &Path,
+) {
+    for path in input_paths(config) {
+        if let Some(found) = locate(path, saved_cwd, state_dir) {
+            log::debug!("{} found at {}", path, found);
+            *path = found;
+        }
+    }
+
+    for path in output_paths(config) {
+        if let Some(moved) = relocate(path) {
+            // logging isn't set up yet while the state file is read
+            eprintln!(
+                "{}",
+                fmt_warn(&format!(
+                    "the directory of {} doesn't exist here, writing to {} instead",
+                    path, moved
+                ))
+            );
+            *path = moved;
+        }
+    }
+}
+
+/// where an input file that isn't at its saved path can be found, if anywhere
+fn locate(path: &str, saved_cwd: Option<&Path>, state_dir: &Path) -> Option<String> {
+    let saved = Path::new(path);
+
+    if path.is_empty() || saved.exists() {
+        return None;
+    }
+
+    let mut candidates = Vec::new();
+
+    if saved.is_relative() {
+        if let Some(cwd) = saved_cwd {
+            candidates.push(cwd.join(saved));
+        }
+
+        candidates.push(state_dir.join(saved));
+    }
+
+    if let Some(name) = saved.file_name() {
+        candidates.push(state_dir.join(name));
+    }
+
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.exists())
+        .and_then(|found| found.to_str().map(String::from))
+}
+
+/// file name to write an output file to in the current directory, when the directory it was
+/// saved with doesn't exist
+fn relocate(path: &str) -> Option<String> {
+    let saved = Path::new(path);
+    let parent = saved.parent()?;
+
+    if parent.as_os_str().is_empty() || parent.exists() {
+        return None;
+    }
+
+    saved.file_name()?.to_str().map(String::from)
+}
+
+/// Make sure a resumed scan requests the same words as the scan it was resumed from
+///
+/// the configured wordlist is used when its words match the record; otherwise, words embedded in
+/// the state file (--state-embed-wordlist) are written to disk and used in its place
+pub(super) fn restore_wordlist(config: &mut Configuration, record: &WordlistRecord) -> Result<()> {
+    let found = wordlists::resolve(&config.wordlist, config.wordlist_given)
+        .and_then(|resolved| resolved.load());
+
+    if let Ok(wordlist) = &found {
+        if wordlist.sha256() == record.sha256 {
+            return Ok(());
+        }
+    }
+
+    let contents = match (&record.contents, found) {
+        (Some(contents), _) => contents,
+        (None, Ok(_)) => bail!(
+            "wordlist {} has changed since the scan was saved, restore it or give the wordlist \
+             to use with -w",
+            config.wordlist
+        ),
+        (None, Err(e)) => bail!(
+            "{:#}; give its new location with -w, or save the scan with --state-embed-wordlist",
+            e
+        ),
+    };
+
+    let restored = write_restored(&record.sha256, "txt", contents)?;
+
+    log::debug!(
+        "wordlist {} restored from state file to {}",
+        config.wordlist,
+        restored
+    );
+
+    config.wordlist = restored;
+
+    Ok(())
+}
+
+/// Make sure a resumed scan picks the same wordlists, with the same words, as the scan it was
+/// resumed from; `path` is the --wordlist-rules or --auto-wordlist file
+///
+/// `path` is kept when neither it nor the wordlists it names have changed. otherwise, the saved
+/// file is written to disk and used in its place, naming each wordlist that changed by where its
+/// embedded words (--state-embed-wordlist) were written, or where a copy was found (see
+/// `localize_paths`)
+pub(super) fn restore_mapping(
+    path: &mut String,
+    record: &MappingRecord,
+    saved_cwd: Option<&Path>,
+    state_dir: &Path,
+) -> Result<()> {
+    let mut file: toml::Value = toml::from_str(&record.contents)?;
+
+    let mut changed =
+        fs::read_to_string(path.as_str()).map_or(true, |current| current != record.contents);
+
+    for name in named_wordlists(&mut file) {
+        let saved = match record.wordlists.get(name.as_str()) {
+            Some(saved) => saved,
+            None => continue, // couldn't be read when the scan was saved
+        };
+
+        let located = locate(name, saved_cwd, state_dir).unwrap_or_else(|| name.to_string());
+        let found = get_unique_words_from_wordlist(&located, true);
+
+        if let Ok(wordlist) = &found {
+            if wordlist.sha256() == saved.sha256 {
+                if located != *name {
+                    *name = located;
+                    changed = true;
+                }
+
+                continue;
+            }
+        }
+
+        let contents = match (&saved.contents, found) {
+            (Some(contents), _) => contents,
+            (None, Ok(_)) => bail!(
+                "wordlist {} has changed since the scan was saved, restore it or save the scan \
+                 with --state-embed-wordlist",
+                name
+            ),
+            (None, Err(e)) => bail!(
+                "{:#}; restore it, or save the scan with --state-embed-wordlist",
+                e
+            ),
+        };
+
+        *name = write_restored(&saved.sha256, "txt", contents)?;
+        changed = true;
+    }
+
+    if !changed {
+        return Ok(());
+    }
+
+    let contents = toml::to_string(&file)?;
+
+    let sha256: String = sha256(contents.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    let restored = write_restored(&sha256, "toml", &contents)?;
+
+    log::debug!("{} restored from state file to {}", path, restored);
+
+    *path = restored;
+
+    Ok(())
+}
+
+/// write the given contents, restored from a state file, to disk; returns where they were written
+fn write_restored(sha256: &str, extension: &str, contents: &str) -> Result<String> {
+    let restored = restored_path(sha256, extension);
+
+    if let Some(parent) = restored.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| fmt_err(&format!("Could not create {}", parent.display())))?;
+    }
+
+    fs::write(&restored, contents)
+        .with_context(|| fmt_err(&format!("Could not write {}", restored.display())))?;
+
+    Ok(restored.to_string_lossy().to_string())
+}
+
+/// where restored contents with the given hash are written; alongside downloaded wordlists, or
+/// in the temp directory if there's no data directory
+fn restored_path(sha256: &str, extension: &str) -> PathBuf {
+    let dir = wordlists::data_dir().unwrap_or_else(|_| env::temp_dir());
+    let prefix: String = sha256.chars().take(16).collect();
+
+    dir.join(format!("resumed-{}.{}", prefix, extension))
+}
@@ -1,12 +1,119 @@
 use crate::response::FeroxResponse;
-use serde::{ser::SerializeSeq, Serialize, Serializer};
-use std::sync::{Arc, RwLock};
+use serde::{ser::SerializeSeq, Deserialize, Serialize, Serializer};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashSet},
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        RwLock,
+    },
+};
 
-/// Container around a locked vector of `FeroxResponse`s, adds wrappers for insertion and search
+/// number of independently locked shards across which responses are spread
+const NUM_SHARDS: usize = 16;
+
+/// Compact record of a response, retained in place of the full response with --low-memory
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResponseFingerprint {
+    /// hash of the response's url
+    pub url_hash: u64,
+
+    /// status code of the response
+    pub status: u16,
+
+    /// content length of the response
+    pub content_length: u64,
+}
+
+/// Implementation of ResponseFingerprint
+impl ResponseFingerprint {
+    /// create the fingerprint of the given response
+    pub fn new(response: &FeroxResponse) -> Self {
+        Self {
+            url_hash: url_hash(response.url().as_str()),
+            status: response.status().as_u16(),
+            content_length: response.content_length(),
+        }
+    }
+}
+
+/// Serialized form of a `ResponseFingerprint` in a state file, tagged so that it can be told apart
+/// from a full response when resuming
+#[derive(Serialize)]
+struct TaggedFingerprint<'a> {
+    /// always "fingerprint"
+    #[serde(rename = "type")]
+    kind: &'static str,
+
+    /// the fingerprint itself
+    #[serde(flatten)]
+    fingerprint: &'a ResponseFingerprint,
+}
+
+/// hash used both to pick a url's shard and as the key of its fingerprint
+///
+/// `DefaultHasher::new` isn't randomly keyed, so hashes saved to a state file still match when
+/// the scan is resumed
+fn url_hash(url: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A single shard of `FeroxResponses`; responses and their urls are kept under the same lock
+///
+/// each response (and fingerprint) is stored along with its position in the container as a
+/// whole, so that the original insertion order survives the sharding
 #[derive(Debug, Default)]
+struct Shard {
+    /// responses whose url hashes to this shard, in insertion order
+    responses: Vec<(u64, FeroxResponse)>,
+
+    /// urls of all responses in `responses`, used for constant time lookups in `contains`
+    urls: HashSet<String>,
+
+    /// fingerprints of responses whose url hashes to this shard, retained with --low-memory
+    fingerprints: Vec<(u64, ResponseFingerprint)>,
+
+    /// url hashes of all fingerprints in `fingerprints`, used by `contains`
+    hashes: HashSet<u64>,
+}
+
+/// Container around locked vectors of `FeroxResponse`s, adds wrappers for insertion and search
+///
+/// responses are sharded by url, so that inserts and lookups of different urls rarely contend
+/// for the same lock at high thread counts
+#[derive(Debug)]
 pub struct FeroxResponses {
-    /// Internal structure: locked hashset of `FeroxScan`s
-    pub responses: Arc<RwLock<Vec<FeroxResponse>>>,
+    /// Internal structure: independently locked shards of `FeroxResponse`s
+    shards: Vec<RwLock<Shard>>,
+
+    /// whether only a fingerprint of each newly inserted response is retained (--low-memory)
+    compact: AtomicBool,
+
+    /// position given to the next response (or fingerprint) inserted
+    next: AtomicU64,
+}
+
+/// A single entry of `FeroxResponses`, borrowed from its shard
+enum Entry<'a> {
+    /// a full response
+    Response(&'a FeroxResponse),
+
+    /// the fingerprint of a response (--low-memory)
+    Fingerprint(&'a ResponseFingerprint),
+}
+
+/// Default implementation for FeroxResponses
+impl Default for FeroxResponses {
+    /// create an empty container with all of its shards allocated
+    fn default() -> Self {
+        Self {
+            shards: (0..NUM_SHARDS).map(|_| RwLock::default()).collect(),
+            compact: AtomicBool::new(false),
+            next: AtomicU64::new(0),
+        }
+    }
 }
 
 /// Serialize implementation for FeroxResponses
@@ -16,40 +123,189 @@ impl Serialize for FeroxResponses {
     where
         S: Serializer,
     {
-        if let Ok(responses) = self.responses.read() {
-            let mut seq = serializer.serialize_seq(Some(responses.len()))?;
+        // if for some reason we can't unlock a shard, its responses are left out
+        let shards: Vec<_> = self.shards.iter().filter_map(|s| s.read().ok()).collect();
 
-            for response in responses.iter() {
-                seq.serialize_element(response)?;
-            }
+        let mut entries: Vec<(u64, Entry)> = shards
+            .iter()
+            .flat_map(|shard| {
+                let responses = shard
+                    .responses
+                    .iter()
+                    .map(|(position, response)| (*position, Entry::Response(response)));
+
+                let fingerprints = shard
+                    .fingerprints
+                    .iter()
+                    .map(|(position, fingerprint)| (*position, Entry::Fingerprint(fingerprint)));
+
+                responses.chain(fingerprints)
+            })
+            .collect();
+
+        // state files list responses in the order they were found, regardless of sharding
+        entries.sort_unstable_by_key(|(position, _)| *position);
 
-            seq.end()
-        } else {
-            // if for some reason we can't unlock the mutex, just write an empty list
-            let seq = serializer.serialize_seq(Some(0))?;
-            seq.end()
+        let mut seq = serializer.serialize_seq(Some(entries.len()))?;
+
+        for (_, entry) in entries {
+            match entry {
+                Entry::Response(response) => seq.serialize_element(response)?,
+                Entry::Fingerprint(fingerprint) => seq.serialize_element(&TaggedFingerprint {
+                    kind: "fingerprint",
+                    fingerprint,
+                })?,
+            }
         }
+
+        seq.end()
     }
 }
 
 /// Implementation of `FeroxResponses`
 impl FeroxResponses {
+    /// shard to which the given url hash belongs
+    fn shard(&self, hash: u64) -> &RwLock<Shard> {
+        &self.shards[hash as usize % self.shards.len()]
+    }
+
+    /// Only retain a fingerprint of responses inserted from here on out (--low-memory)
+    pub fn set_compact(&self, compact: bool) {
+        self.compact.store(compact, Ordering::Relaxed);
+    }
+
     /// Add a `FeroxResponse` to the internal container
+    ///
+    /// with --low-memory, only the response's fingerprint is kept
     pub fn insert(&self, response: FeroxResponse) {
-        if let Ok(mut responses) = self.responses.write() {
-            responses.push(response);
+        if self.compact.load(Ordering::Relaxed) {
+            self.insert_fingerprint(ResponseFingerprint::new(&response));
+            return;
+        }
+
+        let url = response.url().to_string();
+
+        if let Ok(mut shard) = self.shard(url_hash(&url)).write() {
+            let position = self.next.fetch_add(1, Ordering::Relaxed);
+            shard.urls.insert(url);
+            shard.responses.push((position, response));
+        }
+    }
+
+    /// Add a `ResponseFingerprint` to the internal container
+    pub fn insert_fingerprint(&self, fingerprint: ResponseFingerprint) {
+        if let Ok(mut shard) = self.shard(fingerprint.url_hash).write() {
+            let position = self.next.fetch_add(1, Ordering::Relaxed);
+            shard.hashes.insert(fingerprint.url_hash);
+            shard.fingerprints.push((position, fingerprint));
         }
     }
 
     /// Simple check for whether or not a FeroxResponse is contained within the inner container
+    ///
+    /// urls are parsed (and therefore normalized) when the response is created, so the string
+    /// form of the url is used as the key
     pub fn contains(&self, other: &FeroxResponse) -> bool {
-        if let Ok(responses) = self.responses.read() {
-            for response in responses.iter() {
-                if response.url() == other.url() {
-                    return true;
-                }
+        let url = other.url().as_str();
+        let hash = url_hash(url);
+
+        match self.shard(hash).read() {
+            Ok(shard) => shard.urls.contains(url) || shard.hashes.contains(&hash),
+            Err(_) => false,
+        }
+    }
+
+    /// Fingerprint kept (--low-memory) for the response to the given url, if any
+    pub fn fingerprint(&self, url: &str) -> Option<ResponseFingerprint> {
+        let hash = url_hash(url);
+        let shard = self.shard(hash).read().ok()?;
+
+        if !shard.hashes.contains(&hash) {
+            return None;
+        }
+
+        shard
+            .fingerprints
+            .iter()
+            .find(|(_, fingerprint)| fingerprint.url_hash == hash)
+            .map(|(_, fingerprint)| *fingerprint)
+    }
+
+    /// Number of responses in the container, fingerprinted or otherwise
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .filter_map(|shard| shard.read().ok())
+            .map(|shard| shard.responses.len() + shard.fingerprints.len())
+            .sum()
+    }
+
+    /// Number of responses in the container per status code, fingerprinted or otherwise
+    pub fn status_counts(&self) -> BTreeMap<u16, usize> {
+        let mut counts = BTreeMap::new();
+
+        for shard in self.shards.iter().filter_map(|shard| shard.read().ok()) {
+            let statuses = shard
+                .responses
+                .iter()
+                .map(|(_, response)| response.status().as_u16())
+                .chain(
+                    shard
+                        .fingerprints
+                        .iter()
+                        .map(|(_, fingerprint)| fingerprint.status),
+                );
+
+            for status in statuses {
+                *counts.entry(status).or_insert(0_usize) += 1;
             }
         }
-        false
+
+        counts
+    }
+
+    /// Whether or not the container is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Call `f` with each (full) response in the container, in the order they were inserted
+    pub fn for_each<F: FnMut(&FeroxResponse)>(&self, f: F) {
+        let shards: Vec<_> = self.shards.iter().filter_map(|s| s.read().ok()).collect();
+
+        let mut responses: Vec<_> = shards
+            .iter()
+            .flat_map(|shard| shard.responses.iter())
+            .collect();
+
+        responses.sort_unstable_by_key(|(position, _)| *position);
+        responses
+            .into_iter()
+            .map(|(_, response)| response)
+            .for_each(f);
+    }
+
+    /// Call `f` with a mutable reference to each response in the container, in the order they
+    /// were inserted
+    pub fn for_each_mut<F: FnMut(&mut FeroxResponse)>(&self, f: F) {
+        let mut shards: Vec<_> = self.shards.iter().filter_map(|s| s.write().ok()).collect();
+
+        let mut responses: Vec<_> = shards
+            .iter_mut()
+            .flat_map(|shard| shard.responses.iter_mut())
+            .collect();
+
+        responses.sort_unstable_by_key(|(position, _)| *position);
+        responses
+            .into_iter()
+            .map(|(_, response)| response)
+            .for_each(f);
+    }
+
+    /// Copy of every (full) response in the container
+    pub fn to_vec(&self) -> Vec<FeroxResponse> {
+        let mut responses = Vec::with_capacity(self.len());
+        self.for_each(|response| responses.push(response.clone()));
+        responses
     }
 }
@@ -1,8 +1,13 @@
 use super::*;
 use crate::{
     config::OutputLevel,
+    events::{ScanEvent, EVENTS},
+    filters::WildcardFilter,
     progress::{add_bar, BarType},
+    response::FeroxResponse,
     scanner::PolicyTrigger,
+    traits::FeroxFilter,
+    SLEEP_DURATION,
 };
 use anyhow::Result;
 use console::style;
@@ -16,8 +21,8 @@ use std::{
     time::Instant,
 };
 
-use std::sync::atomic::{AtomicUsize, Ordering};
-use tokio::{sync, task::JoinHandle};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use tokio::{sync, task::JoinHandle, time};
 use uuid::Uuid;
 
 /// Struct to hold scan-related state
@@ -62,8 +67,17 @@ pub struct FeroxScan {
     /// tracker for total number of errors encountered by the FeroxScan instance
     pub(super) errors: AtomicUsize,
 
+    /// tracker for number of responses that looked like a WAF/CDN block page
+    pub(super) blocked: AtomicUsize,
+
     /// tracker for the time at which this scan was started
     pub(super) start_time: Instant,
+
+    /// whether or not this scan, and only this scan, was paused by the user
+    pub(super) paused: AtomicBool,
+
+    /// this directory's 404 calibration, set once the wildcard heuristic has run against it
+    pub(super) wildcard: Mutex<Option<WildcardFilter>>,
 }
 
 /// Default implementation for FeroxScan
@@ -83,9 +97,12 @@ impl Default for FeroxScan {
             scan_type: ScanType::File,
             output_level: Default::default(),
             errors: Default::default(),
+            blocked: Default::default(),
             status_429s: Default::default(),
             status_403s: Default::default(),
             start_time: Instant::now(),
+            paused: AtomicBool::new(false),
+            wildcard: Mutex::new(None),
         }
     }
 }
@@ -94,6 +111,16 @@ impl Default for FeroxScan {
 impl FeroxScan {
     /// Stop a currently running scan
     pub async fn abort(&self) -> Result<()> {
+        self.stop(ScanStatus::Cancelled).await
+    }
+
+    /// Stop a currently running scan because it looked like a tarpit (--tarpit-latency)
+    pub async fn abandon(&self) -> Result<()> {
+        self.stop(ScanStatus::Abandoned).await
+    }
+
+    /// Stop a currently running scan, leaving it with the given status
+    async fn stop(&self, status: ScanStatus) -> Result<()> {
         log::trace!("enter: abort");
 
         match self.task.try_lock() {
@@ -101,8 +128,8 @@ impl FeroxScan {
                 if let Some(task) = std::mem::replace(&mut *guard, None) {
                     log::trace!("aborting {:?}", self);
                     task.abort();
-                    self.set_status(ScanStatus::Cancelled)?;
-                    self.stop_progress_bar();
+                    self.set_status(status)?;
+                    EVENTS.publish(ScanEvent::ScanAborted(self));
                 }
             }
             Err(e) => {
@@ -118,6 +145,11 @@ impl FeroxScan {
         &self.url
     }
 
+    /// getter for id
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
     /// small wrapper to set the JoinHandle
     pub async fn set_task(&self, task: JoinHandle<()>) -> Result<()> {
         let mut guard = self.task.lock().await;
@@ -134,7 +166,7 @@ impl FeroxScan {
     }
 
     /// Simple helper to call .finish on the scan's progress bar
-    pub(super) fn stop_progress_bar(&self) {
+    pub fn stop_progress_bar(&self) {
         if let Ok(guard) = self.progress_bar.lock() {
             if guard.is_some() {
                 (*guard).as_ref().unwrap().finish_at_current_pos()
@@ -142,6 +174,62 @@ impl FeroxScan {
         }
     }
 
+    /// prefix shown on the scan's progress bar, marks the scan as paused when needed
+    fn bar_prefix(&self) -> String {
+        if self.is_paused() {
+            format!("{} {}", self.url, style("(paused)").yellow())
+        } else {
+            self.url.clone()
+        }
+    }
+
+    /// whether or not this scan was paused by the user
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// pause (or resume) only this scan; its requests wait in `wait_while_paused` until resumed
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Release);
+
+        if let Ok(guard) = self.progress_bar.lock() {
+            if let Some(pb) = guard.as_ref() {
+                pb.set_prefix(&self.bar_prefix());
+            }
+        }
+    }
+
+    /// store the result of running the wildcard heuristic against this directory
+    pub fn set_wildcard_filter(&self, filter: WildcardFilter) {
+        if let Ok(mut guard) = self.wildcard.lock() {
+            *guard = Some(filter);
+        }
+    }
+
+    /// this directory's 404 calibration, None until the wildcard heuristic has run
+    pub fn wildcard_filter(&self) -> Option<WildcardFilter> {
+        self.wildcard.lock().ok()?.clone()
+    }
+
+    /// whether or not the given response looks like this directory's custom 404
+    pub fn is_wildcard(&self, response: &FeroxResponse) -> bool {
+        match self.wildcard.lock() {
+            Ok(guard) => guard
+                .as_ref()
+                .map_or(false, |filter| filter.should_filter_response(response)),
+            Err(_) => false,
+        }
+    }
+
+    /// wait until this scan is no longer paused, returns immediately if it isn't
+    pub async fn wait_while_paused(&self) {
+        let mut interval = time::interval(time::Duration::from_millis(SLEEP_DURATION));
+
+        while self.is_paused() {
+            interval.tick().await;
+        }
+    }
+
     /// Simple helper get a progress bar
     pub fn progress_bar(&self) -> ProgressBar {
         match self.progress_bar.lock() {
@@ -155,7 +243,7 @@ impl FeroxScan {
                         OutputLevel::Silent => BarType::Hidden,
                     };
 
-                    let pb = add_bar(&self.url, self.num_requests, bar_type);
+                    let pb = add_bar(&self.bar_prefix(), self.num_requests, bar_type);
                     pb.reset_elapsed();
 
                     let _ = std::mem::replace(&mut *guard, Some(pb.clone()));
@@ -200,10 +288,11 @@ impl FeroxScan {
         })
     }
 
-    /// Mark the scan as complete and stop the scan's progress bar
+    /// Mark the scan as complete and let the run know (its progress bar is stopped by
+    /// `ScanBars`)
     pub fn finish(&self) -> Result<()> {
         self.set_status(ScanStatus::Complete)?;
-        self.stop_progress_bar();
+        EVENTS.publish(ScanEvent::ScanComplete(self));
         Ok(())
     }
 
@@ -220,6 +309,42 @@ impl FeroxScan {
         false
     }
 
+    /// small wrapper to inspect ScanType and ScanStatus to see if a Directory scan was discovered,
+    /// but hasn't started yet (i.e. it's waiting on --scan-limit)
+    pub fn is_pending(&self) -> bool {
+        if let Ok(guard) = self.status.lock() {
+            return matches!(
+                (self.scan_type, *guard),
+                (ScanType::Directory, ScanStatus::NotStarted)
+            );
+        }
+        false
+    }
+
+    /// small wrapper to inspect ScanStatus and see if the scan was skipped due to --max-scans
+    pub fn is_skipped(&self) -> bool {
+        if let Ok(guard) = self.status.lock() {
+            return matches!(*guard, ScanStatus::Skipped);
+        }
+        false
+    }
+
+    /// small wrapper to inspect ScanStatus and see if the scan ran over --scan-time-limit
+    pub fn is_timed_out(&self) -> bool {
+        if let Ok(guard) = self.status.lock() {
+            return matches!(*guard, ScanStatus::TimedOut);
+        }
+        false
+    }
+
+    /// Mark the scan as having run over --scan-time-limit and let the run know
+    pub fn time_out(&self) -> Result<()> {
+        self.set_status(ScanStatus::TimedOut)?;
+        // the scan ran its course; only its status records that it's incomplete
+        EVENTS.publish(ScanEvent::ScanComplete(self));
+        Ok(())
+    }
+
     /// small wrapper to inspect ScanStatus and see if it's Complete
     pub fn is_complete(&self) -> bool {
         if let Ok(guard) = self.status.lock() {
@@ -236,8 +361,12 @@ impl FeroxScan {
         if guard.is_some() {
             if let Some(task) = std::mem::replace(&mut *guard, None) {
                 task.await.unwrap();
-                self.set_status(ScanStatus::Complete)
-                    .unwrap_or_else(|e| log::warn!("Could not mark scan complete: {}", e))
+
+                if !self.is_timed_out() {
+                    // a timed out scan's task ends early, it's still incomplete
+                    self.set_status(ScanStatus::Complete)
+                        .unwrap_or_else(|e| log::warn!("Could not mark scan complete: {}", e))
+                }
             }
         }
 
@@ -258,6 +387,11 @@ impl FeroxScan {
         self.errors.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// increment the number of blocked responses by 1, returning the new total
+    pub(crate) fn add_blocked(&self) -> usize {
+        self.blocked.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
     /// simple wrapper to call the appropriate getter based on the given PolicyTrigger
     pub fn num_errors(&self, trigger: PolicyTrigger) -> usize {
         match trigger {
@@ -308,6 +442,10 @@ impl fmt::Display for FeroxScan {
                 ScanStatus::NotStarted => style("not started").bright().blue(),
                 ScanStatus::Complete => style("complete").green(),
                 ScanStatus::Cancelled => style("cancelled").red(),
+                ScanStatus::Skipped => style("not scanned").dim(),
+                ScanStatus::TimedOut => style("timed out").red(),
+                ScanStatus::Abandoned => style("abandoned").red(),
+                ScanStatus::Running if self.is_paused() => style("paused").magenta(),
                 ScanStatus::Running => style("running").bright().yellow(),
             }
         } else {
@@ -332,13 +470,14 @@ impl Serialize for FeroxScan {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("FeroxScan", 4)?;
+        let mut state = serializer.serialize_struct("FeroxScan", 6)?;
 
         state.serialize_field("id", &self.id)?;
         state.serialize_field("url", &self.url)?;
         state.serialize_field("scan_type", &self.scan_type)?;
         state.serialize_field("status", &self.status)?;
         state.serialize_field("num_requests", &self.num_requests)?;
+        state.serialize_field("paused", &self.is_paused())?;
 
         state.end()
     }
@@ -378,6 +517,9 @@ impl<'de> Deserialize<'de> for FeroxScan {
                             "Running" => ScanStatus::Running,
                             "Complete" => ScanStatus::Complete,
                             "Cancelled" => ScanStatus::Cancelled,
+                            "Skipped" => ScanStatus::Skipped,
+                            "TimedOut" => ScanStatus::TimedOut,
+                            "Abandoned" => ScanStatus::Abandoned,
                             _ => ScanStatus::default(),
                         })
                     }
@@ -392,6 +534,11 @@ impl<'de> Deserialize<'de> for FeroxScan {
                         scan.num_requests = num_requests;
                     }
                 }
+                "paused" => {
+                    if let Some(paused) = value.as_bool() {
+                        scan.paused = AtomicBool::new(paused);
+                    }
+                }
                 _ => {}
             }
         }
@@ -432,6 +579,15 @@ pub enum ScanStatus {
 
     /// Scan has started, but hasn't finished, nor been cancelled
     Running,
+
+    /// Directory was discovered, but not scanned because --max-scans was reached
+    Skipped,
+
+    /// Scan was stopped because it ran longer than --scan-time-limit
+    TimedOut,
+
+    /// Scan was stopped because the directory looked like a tarpit (--tarpit-latency)
+    Abandoned,
 }
 
 /// Default implementation for ScanStatus
@@ -451,7 +607,7 @@ mod tests {
     #[test]
     /// ensure that num_errors returns the correct values for the given PolicyTrigger
     ///
-    /// covers tests for add_[403,429,error] and the related getters in addition to num_errors
+    /// covers tests for add_[403,429,error,blocked] and the related getters in addition to num_errors
     fn num_errors_returns_correct_values() {
         let scan = FeroxScan::new(
             "http://localhost",
@@ -472,6 +628,9 @@ mod tests {
         assert_eq!(scan.num_errors(PolicyTrigger::Errors), 1);
         assert_eq!(scan.num_errors(PolicyTrigger::Status403), 2);
         assert_eq!(scan.num_errors(PolicyTrigger::Status429), 3);
+
+        assert_eq!(scan.add_blocked(), 1);
+        assert_eq!(scan.add_blocked(), 2);
     }
 
     #[test]
@@ -490,7 +649,10 @@ mod tests {
             status_403s: Default::default(),
             status_429s: Default::default(),
             errors: Default::default(),
+            blocked: Default::default(),
             start_time: Instant::now(),
+            paused: Default::default(),
+            wildcard: Default::default(),
         };
 
         let pb = scan.progress_bar();
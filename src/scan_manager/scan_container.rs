@@ -1,20 +1,25 @@
 use super::scan::ScanType;
 use super::*;
 use crate::{
-    config::OutputLevel,
+    config::{Configuration, OutputLevel},
+    event_handlers::Command::{AddFilterHit, AddToUsizeField},
     progress::PROGRESS_PRINTER,
     progress::{add_bar, BarType},
+    response::FeroxResponse,
     scanner::RESPONSES,
+    statistics::StatField::WildcardsFiltered,
     traits::FeroxSerialize,
-    SLEEP_DURATION,
+    url::normalize_url,
+    wordlist::Wordlist,
+    CommandSender, SLEEP_DURATION,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use reqwest::StatusCode;
 use serde::{ser::SerializeSeq, Serialize, Serializer};
+use serde_json::Value;
 use std::{
+    collections::{HashMap, HashSet},
     convert::TryInto,
-    fs::File,
-    io::BufReader,
     ops::Index,
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
@@ -31,11 +36,26 @@ static INTERACTIVE_BARRIER: AtomicUsize = AtomicUsize::new(0);
 /// Atomic boolean flag, used to determine whether or not a scan should pause or resume
 pub static PAUSE_SCAN: AtomicBool = AtomicBool::new(false);
 
+/// Atomic boolean flag, set when scans were paused via --api-addr, in which case the interactive
+/// menu isn't shown
+static PAUSED_REMOTELY: AtomicBool = AtomicBool::new(false);
+
 /// Container around a locked hashset of `FeroxScan`s, adds wrappers for insertion and searching
 #[derive(Debug, Default)]
 pub struct FeroxScans {
-    /// Internal structure: locked hashset of `FeroxScan`s
-    pub scans: RwLock<Vec<Arc<FeroxScan>>>,
+    /// Internal structure: locked hashset of `FeroxScan`s; kept private so that every scan is
+    /// added through `push`, and is therefore also in `index`
+    scans: RwLock<Vec<Arc<FeroxScan>>>,
+
+    /// url -> `FeroxScan` for every scan added via `insert` or `add_scan`, used for constant
+    /// time lookups by url; urls are keyed by `index_key`
+    index: RwLock<HashMap<String, Arc<FeroxScan>>>,
+
+    /// url -> Location of every redirect seen during recursion, both normalized
+    redirects: RwLock<HashMap<String, String>>,
+
+    /// urls (keyed by `index_key`) that are part of a redirect loop, and won't be scanned
+    redirect_loops: RwLock<HashSet<String>>,
 
     /// menu used for providing a way for users to cancel a scan
     menu: Menu,
@@ -46,6 +66,52 @@ pub struct FeroxScans {
 
     /// whether or not the user passed --silent|--quiet on the command line
     output_level: OutputLevel,
+
+    /// Configuration of each --targets entry; scans of a target, and of anything found beneath
+    /// it, use its Configuration instead of the global one
+    target_configs: RwLock<Vec<Arc<Configuration>>>,
+
+    /// why scans were last paused automatically (--auto-pause), shown when the menu opens
+    pause_reason: Mutex<Option<String>>,
+
+    /// wordlist being scanned with, recorded in state files so that a resumed scan can check it's
+    /// using the same one
+    wordlist: RwLock<Option<Arc<Wordlist>>>,
+
+    /// number of directory scans started so far, checked against --max-scans; recorded in state
+    /// files so that a resumed scan doesn't start counting from zero again
+    num_scans: AtomicUsize,
+}
+
+/// key under which a scan of the given url is indexed; urls are normalized (see `normalize_url`)
+/// so that http://localhost/js/ and HTTP://LOCALHOST:80/js/ aren't scanned twice
+///
+/// the trailing slash is kept as given, since the file http://localhost/js and the directory
+/// http://localhost/js/ are different resources
+fn index_key(url: &str) -> String {
+    let mut key = normalize_url(url);
+    let given = url.split('#').next().unwrap_or(url);
+
+    if !given.ends_with('/') && key.ends_with('/') {
+        // parsing adds a path of / to bare hosts, i.e. http://localhost
+        key.pop();
+    }
+
+    key
+}
+
+/// directory scan of the given url, which ends just before a `/`; directories found while
+/// scanning are indexed with their trailing slash, while user-supplied targets may be indexed
+/// without it
+fn directory_scan(index: &HashMap<String, Arc<FeroxScan>>, url: &str) -> Option<Arc<FeroxScan>> {
+    index
+        .get(&index_key(&format!("{}/", url)))
+        .or_else(|| {
+            index
+                .get(&index_key(url))
+                .filter(|scan| matches!(scan.scan_type, ScanType::Directory))
+        })
+        .cloned()
 }
 
 /// Serialize implementation for FeroxScans
@@ -82,6 +148,18 @@ impl FeroxScans {
         }
     }
 
+    /// Remember the wordlist being scanned with
+    pub fn set_wordlist(&self, wordlist: Arc<Wordlist>) {
+        if let Ok(mut guard) = self.wordlist.write() {
+            *guard = Some(wordlist);
+        }
+    }
+
+    /// The wordlist being scanned with, if it's been set
+    pub fn wordlist(&self) -> Option<Arc<Wordlist>> {
+        self.wordlist.read().ok().and_then(|guard| guard.clone())
+    }
+
     /// Add a `FeroxScan` to the internal container
     ///
     /// If the internal container did NOT contain the scan, true is returned; else false
@@ -94,27 +172,42 @@ impl FeroxScans {
             // can't update the internal container while the scan itself is locked, so first
             // lock the scan and check the container for the scan's presence, then add if
             // not found
-            match self.scans.write() {
-                Ok(mut scans) => {
-                    scans.push(scan);
-                }
-                Err(e) => {
-                    log::warn!("FeroxScans' container's mutex is poisoned: {}", e);
-                    return false;
-                }
+            if let Err(e) = self.push(scan) {
+                log::warn!("{}", e);
+                return false;
             }
         }
 
         sentry
     }
 
-    /// load serialized FeroxScan(s) into this FeroxScans  
-    pub fn add_serialized_scans(&self, filename: &str) -> Result<()> {
-        log::trace!("enter: add_serialized_scans({})", filename);
-        let file = File::open(filename)?;
+    /// add the scan to both the internal container and the url index
+    fn push(&self, scan: Arc<FeroxScan>) -> Result<()> {
+        match self.index.write() {
+            Ok(mut index) => {
+                // the first scan of a given url wins, later scans of the same url (i.e.
+                // --auto-wordlist) are only reachable through the container
+                index
+                    .entry(index_key(&scan.url))
+                    .or_insert_with(|| scan.clone());
+            }
+            Err(e) => bail!("FeroxScans' index's lock is poisoned: {}", e),
+        }
 
-        let reader = BufReader::new(file);
-        let state: serde_json::Value = serde_json::from_reader(reader)?;
+        match self.scans.write() {
+            Ok(mut scans) => {
+                scans.push(scan);
+                Ok(())
+            }
+            Err(e) => bail!("FeroxScans' container's lock is poisoned: {}", e),
+        }
+    }
+
+    /// load serialized FeroxScan(s) into this FeroxScans; `password` is used to decrypt state
+    /// files saved with --state-password
+    pub fn add_serialized_scans(&self, filename: &str, password: &str) -> Result<()> {
+        log::trace!("enter: add_serialized_scans({})", filename);
+        let state = read_state(filename, password)?;
 
         if let Some(scans) = state.get("scans") {
             if let Some(arr_scans) = scans.as_array() {
@@ -133,6 +226,28 @@ impl FeroxScans {
             }
         }
 
+        if let Some(num_scans) = state.get("num_scans").and_then(Value::as_u64) {
+            self.num_scans.store(num_scans as usize, Ordering::SeqCst);
+        }
+
+        if let Some(Value::Array(pending)) = state.get("pending") {
+            // directories that were waiting on --scan-limit when the state was saved; they're
+            // normally among the scans above as well, but are added here if they're not
+            for url in pending.iter().filter_map(Value::as_str) {
+                if !self.contains(url) {
+                    log::debug!("added pending: {}", url);
+                    self.insert(FeroxScan::new(
+                        url,
+                        ScanType::Directory,
+                        ScanOrder::Latest,
+                        0,
+                        self.output_level,
+                        None,
+                    ));
+                }
+            }
+        }
+
         log::trace!("exit: add_serialized_scans");
         Ok(())
     }
@@ -140,26 +255,15 @@ impl FeroxScans {
     /// Simple check for whether or not a FeroxScan is contained within the inner container based
     /// on the given URL
     pub fn contains(&self, url: &str) -> bool {
-        if let Ok(scans) = self.scans.read() {
-            for scan in scans.iter() {
-                if scan.url == url {
-                    return true;
-                }
-            }
+        match self.index.read() {
+            Ok(index) => index.contains_key(&index_key(url)),
+            Err(_) => false,
         }
-        false
     }
 
     /// Find and return a `FeroxScan` based on the given URL
     pub fn get_scan_by_url(&self, url: &str) -> Option<Arc<FeroxScan>> {
-        if let Ok(guard) = self.scans.read() {
-            for scan in guard.iter() {
-                if scan.url == url {
-                    return Some(scan.clone());
-                }
-            }
-        }
-        None
+        self.index.read().ok()?.get(&index_key(url)).cloned()
     }
 
     pub(super) fn get_base_scan_by_url(&self, url: &str) -> Option<Arc<FeroxScan>> {
@@ -182,14 +286,13 @@ impl FeroxScans {
         //      http://shmocalhost/src/release/examples
         //
         //  returns: http://shmocalhost/src/release/examples
-        if let Ok(guard) = self.scans.read() {
+        if let Ok(index) = self.index.read() {
             for (idx, _) in &matches {
-                for scan in guard.iter() {
-                    let slice = url.index(0..*idx);
-                    if slice == scan.url || format!("{}/", slice).as_str() == scan.url {
-                        log::trace!("enter: get_sub_paths_from_path -> {}", scan);
-                        return Some(scan.clone());
-                    }
+                let slice = url.index(0..*idx);
+
+                if let Some(scan) = directory_scan(&index, slice) {
+                    log::trace!("enter: get_sub_paths_from_path -> {}", scan);
+                    return Some(scan);
                 }
             }
         }
@@ -197,6 +300,94 @@ impl FeroxScans {
         log::trace!("enter: get_sub_paths_from_path -> None");
         None
     }
+
+    /// Whether or not the given response looks like the custom 404 of the closest directory scan
+    /// above it that's been calibrated (see `HeuristicTests::wildcard`)
+    ///
+    /// the response's own directory is skipped, i.e. http://localhost/js/ is compared to the
+    /// calibration of http://localhost/, not that of http://localhost/js/
+    pub fn is_wildcard(&self, response: &FeroxResponse, tx_stats: CommandSender) -> bool {
+        let url = response.url().as_str().trim_end_matches('/');
+
+        if let Ok(index) = self.index.read() {
+            for (idx, _) in url.rmatch_indices('/') {
+                let slice = url.index(0..idx);
+
+                let calibrated =
+                    directory_scan(&index, slice).filter(|scan| scan.wildcard_filter().is_some());
+
+                if let Some(scan) = calibrated {
+                    if !scan.is_wildcard(response) {
+                        return false;
+                    }
+
+                    // counted the same as a hit on a global filter
+                    tx_stats
+                        .send(AddToUsizeField(WildcardsFiltered, 1))
+                        .unwrap_or_default();
+                    tx_stats.send(AddFilterHit("Wildcard")).unwrap_or_default();
+
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Record that `url` redirects to `location`, returning the redirect loop this completes, if
+    /// any
+    ///
+    /// the loop is returned as normalized urls that start and end with `url`, i.e.
+    /// [http://localhost/admin, http://localhost/admin/, http://localhost/admin]; every url in it
+    /// is remembered, see `in_redirect_loop`
+    pub fn add_redirect(&self, url: &str, location: &str) -> Option<Vec<String>> {
+        let from = normalize_url(url);
+        let to = normalize_url(location);
+
+        let cycle = {
+            let mut redirects = self.redirects.write().ok()?;
+
+            if redirects.get(&from) == Some(&to) {
+                // already known, along with any loop it's part of
+                return None;
+            }
+
+            redirects.insert(from.clone(), to.clone());
+
+            // a url only redirects to one location, so following the chain from `location`
+            // either comes back around to `url`, or ends (or loops) somewhere else
+            let mut cycle = vec![from.clone()];
+            let mut current = to;
+
+            while current != from {
+                if cycle.contains(&current) {
+                    return None;
+                }
+
+                let next = redirects.get(&current)?.clone();
+                cycle.push(current);
+                current = next;
+            }
+
+            cycle.push(from);
+            cycle
+        };
+
+        if let Ok(mut loops) = self.redirect_loops.write() {
+            loops.extend(cycle.iter().map(|url| index_key(url)));
+        }
+
+        Some(cycle)
+    }
+
+    /// Whether or not the given url was found to be part of a redirect loop
+    pub fn in_redirect_loop(&self, url: &str) -> bool {
+        self.redirect_loops
+            .read()
+            .map_or(false, |loops| loops.contains(&index_key(url)))
+    }
+
     /// add one to either 403 or 429 tracker in the scan related to the given url
     pub fn increment_status_code(&self, url: &str, code: StatusCode) {
         if let Some(scan) = self.get_base_scan_by_url(url) {
@@ -245,7 +436,7 @@ impl FeroxScans {
             if matches!(scan.scan_type, ScanType::Directory) {
                 // we're only interested in displaying directory scans, as those are
                 // the only ones that make sense to be stopped
-                let scan_msg = format!("{:3}: {}", i, scan);
+                let scan_msg = format!("{:3}: {} {}", i, scan.id(), scan);
                 self.menu.println(&scan_msg);
             }
         }
@@ -299,19 +490,65 @@ impl FeroxScans {
         num_cancelled
     }
 
-    /// CLI menu that allows for interactive cancellation of recursed-into directories
+    /// Given a list of indexes, pause (or resume) their associated FeroxScans
+    fn pause_scans(&self, indexes: Vec<usize>, paused: bool) {
+        let menu_pause_duration = Duration::from_millis(SLEEP_DURATION);
+
+        for num in indexes {
+            let selected = match self.scans.read() {
+                Ok(u_scans) => {
+                    if num >= u_scans.len() {
+                        self.menu
+                            .println(&format!("The number {} is not a valid choice.", num));
+                        sleep(menu_pause_duration);
+                        continue;
+                    }
+                    u_scans.index(num).clone()
+                }
+                Err(..) => continue,
+            };
+
+            let verb = if paused { "Pausing" } else { "Resuming" };
+            self.menu.println(&format!("{} {}...", verb, selected.url));
+
+            selected.set_paused(paused);
+        }
+
+        sleep(menu_pause_duration);
+    }
+
+    /// CLI menu that allows for interactive cancellation, pausing, and resuming of recursed-into
+    /// directories
     async fn interactive_menu(&self) -> usize {
         self.menu.hide_progress_bars();
         self.menu.clear_screen();
         self.menu.print_header();
+
+        if let Some(reason) = self
+            .pause_reason
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.take())
+        {
+            self.menu.println(&format!(
+                "⏸️  scans were paused automatically: {}\n",
+                reason
+            ));
+        }
+
         self.display_scans().await;
         self.menu.print_footer();
 
         let mut num_cancelled = 0_usize;
 
-        if let Some((input, force)) = self.menu.get_scans_from_user() {
-            num_cancelled += self.cancel_scans(input, force).await;
-        };
+        match self.menu.get_scans_from_user() {
+            Some((MenuAction::Cancel, input, force)) => {
+                num_cancelled += self.cancel_scans(input, force).await;
+            }
+            Some((MenuAction::Pause, input, _)) => self.pause_scans(input, true),
+            Some((MenuAction::Resume, input, _)) => self.pause_scans(input, false),
+            None => {}
+        }
 
         self.menu.clear_screen();
         self.menu.show_progress_bars();
@@ -321,17 +558,15 @@ impl FeroxScans {
 
     /// prints all known responses that the scanner has already seen
     pub fn print_known_responses(&self) {
-        if let Ok(mut responses) = RESPONSES.responses.write() {
-            for response in responses.iter_mut() {
-                if self.output_level != response.output_level {
-                    // set the output_level prior to printing the response to ensure that the
-                    // response's setting aligns with the overall configuration (since we're
-                    // calling this from a resumed state)
-                    response.output_level = self.output_level;
-                }
-                PROGRESS_PRINTER.println(response.as_str());
+        RESPONSES.for_each_mut(|response| {
+            if self.output_level != response.output_level {
+                // set the output_level prior to printing the response to ensure that the
+                // response's setting aligns with the overall configuration (since we're
+                // calling this from a resumed state)
+                response.output_level = self.output_level;
             }
-        }
+            PROGRESS_PRINTER.println(response.as_str());
+        });
     }
 
     /// if a resumed scan is already complete, display a completed progress bar to the user
@@ -375,7 +610,7 @@ impl FeroxScans {
         if INTERACTIVE_BARRIER.load(Ordering::Relaxed) == 0 {
             INTERACTIVE_BARRIER.fetch_add(1, Ordering::Relaxed);
 
-            if get_user_input {
+            if get_user_input && !PAUSED_REMOTELY.load(Ordering::Acquire) {
                 num_cancelled += self.interactive_menu().await;
                 PAUSE_SCAN.store(false, Ordering::Relaxed);
                 self.print_known_responses();
@@ -399,6 +634,87 @@ impl FeroxScans {
         }
     }
 
+    /// pause all scans on behalf of --auto-pause; unlike `pause_remotely`, the interactive menu is
+    /// shown, along with the given reason, so the user can decide what to do next
+    pub fn pause_automatically(&self, reason: &str) {
+        if PAUSE_SCAN.load(Ordering::Acquire) {
+            // already paused, by the user or otherwise
+            return;
+        }
+
+        if let Ok(mut guard) = self.pause_reason.lock() {
+            *guard = Some(reason.to_string());
+        }
+
+        PAUSE_SCAN.store(true, Ordering::Release);
+    }
+
+    /// pause all scans without displaying the interactive menu (--api-addr)
+    pub fn pause_remotely(&self) {
+        PAUSED_REMOTELY.store(true, Ordering::Release);
+        PAUSE_SCAN.store(true, Ordering::Release);
+    }
+
+    /// resume scans paused via `pause_remotely`
+    pub fn resume_remotely(&self) {
+        PAUSE_SCAN.store(false, Ordering::Release);
+        PAUSED_REMOTELY.store(false, Ordering::Release);
+    }
+
+    /// whether or not scans were paused via `pause_remotely`, as opposed to from the menu
+    pub fn paused_remotely(&self) -> bool {
+        PAUSED_REMOTELY.load(Ordering::Acquire)
+    }
+
+    /// most recently discovered directory scan that's still active; the scans started from the
+    /// initial targets aren't considered, same as in the menu
+    pub fn newest_active_scan(&self) -> Option<Arc<FeroxScan>> {
+        self.scans
+            .read()
+            .ok()?
+            .iter()
+            .rev()
+            .find(|scan| !matches!(scan.scan_order, ScanOrder::Initial) && scan.is_active())
+            .cloned()
+    }
+
+    /// cancel the scan with the given id (--api-addr)
+    ///
+    /// returns the number of requests that won't be made as a result, or None if the id is
+    /// unknown
+    pub async fn cancel_scan_by_id(&self, id: &str) -> Option<usize> {
+        let selected = match self.scans.read() {
+            Ok(scans) => scans.iter().find(|scan| scan.id == id).cloned(),
+            Err(..) => None,
+        }?;
+
+        selected
+            .abort()
+            .await
+            .unwrap_or_else(|e| log::warn!("Could not cancel task: {}", e));
+
+        let pb = selected.progress_bar();
+        Some(pb.length().saturating_sub(pb.position()) as usize)
+    }
+
+    /// pause (or resume) only the scan with the given id (--api-addr)
+    ///
+    /// returns false if the id is unknown
+    pub fn pause_scan_by_id(&self, id: &str, paused: bool) -> bool {
+        let selected = match self.scans.read() {
+            Ok(scans) => scans.iter().find(|scan| scan.id == id).cloned(),
+            Err(..) => None,
+        };
+
+        match selected {
+            Some(scan) => {
+                scan.set_paused(paused);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// set the bar length of FeroxScans
     pub fn set_bar_length(&self, bar_length: u64) {
         if let Ok(mut guard) = self.bar_length.lock() {
@@ -465,6 +781,62 @@ impl FeroxScans {
         self.add_scan(&url, ScanType::Directory, scan_order)
     }
 
+    /// Given a url, create a new `FeroxScan` and add it to `FeroxScans` as a Directory Scan,
+    /// regardless of whether or not the url is already known
+    ///
+    /// used for scanning an already known directory with a different wordlist (--auto-wordlist),
+    /// `num_requests` is the number of requests the scan is expected to make
+    pub fn add_wordlist_scan(&self, url: &str, num_requests: u64) -> Arc<FeroxScan> {
+        let bar_type = match self.output_level {
+            OutputLevel::Default => BarType::Default,
+            OutputLevel::Quiet => BarType::Quiet,
+            OutputLevel::Silent => BarType::Hidden,
+        };
+
+        let progress_bar = add_bar(&url, num_requests, bar_type);
+        progress_bar.reset_elapsed();
+
+        let ferox_scan = FeroxScan::new(
+            &url,
+            ScanType::Directory,
+            ScanOrder::Latest,
+            num_requests,
+            self.output_level,
+            Some(progress_bar),
+        );
+
+        if let Err(e) = self.push(ferox_scan.clone()) {
+            log::warn!("{}", e);
+        }
+
+        ferox_scan
+    }
+
+    /// Given a url, record it as a directory that won't be scanned because --max-scans was
+    /// reached; no progress bar is created for it
+    pub fn add_skipped_scan(&self, url: &str) -> Arc<FeroxScan> {
+        let ferox_scan = match self.get_scan_by_url(url) {
+            Some(scan) => scan,
+            None => {
+                let scan = FeroxScan::new(
+                    url,
+                    ScanType::Directory,
+                    ScanOrder::Latest,
+                    0,
+                    self.output_level,
+                    None,
+                );
+                self.insert(scan.clone());
+                scan
+            }
+        };
+
+        ferox_scan
+            .set_status(ScanStatus::Skipped)
+            .unwrap_or_default();
+        ferox_scan
+    }
+
     /// Given a url, create a new `FeroxScan` and add it to `FeroxScans` as a File Scan
     ///
     /// If `FeroxScans` did not already contain the scan, return true; otherwise return false
@@ -486,6 +858,37 @@ impl FeroxScans {
         false
     }
 
+    /// Number of directory scans started so far, across runs of a resumed scan
+    pub fn num_scans(&self) -> usize {
+        self.num_scans.load(Ordering::SeqCst)
+    }
+
+    /// Record that another directory scan was started
+    pub fn increment_num_scans(&self) {
+        self.num_scans.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Urls of the directory scans that were discovered, but haven't started yet, in the order
+    /// they were discovered
+    pub fn pending_scans(&self) -> Vec<String> {
+        match self.scans.read() {
+            Ok(guard) => guard
+                .iter()
+                .filter(|scan| scan.is_pending())
+                .map(|scan| scan.url().to_string())
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Retrieve all known scans, in the order they were added
+    pub fn get_scans(&self) -> Vec<Arc<FeroxScan>> {
+        self.scans
+            .read()
+            .map(|guard| guard.clone())
+            .unwrap_or_default()
+    }
+
     /// Retrieve all active scans
     pub fn get_active_scans(&self) -> Vec<Arc<FeroxScan>> {
         let mut scans = vec![];
@@ -500,4 +903,32 @@ impl FeroxScans {
         }
         scans
     }
+
+    /// Remember the Configuration of a --targets entry, keyed by its `target_url`
+    pub fn add_target_config(&self, config: Arc<Configuration>) {
+        if let Ok(mut guard) = self.target_configs.write() {
+            guard.push(config);
+        }
+    }
+
+    /// Find the Configuration of the --targets entry under which the given url falls; when
+    /// targets are nested, the most specific (longest) one wins
+    pub fn get_target_config(&self, url: &str) -> Option<Arc<Configuration>> {
+        self.target_configs
+            .read()
+            .ok()?
+            .iter()
+            .filter(|config| {
+                let target = config.target_url.trim_end_matches('/');
+
+                // http://a.example shouldn't claim http://a.example.com/
+                url.starts_with(target)
+                    && matches!(
+                        url[target.len()..].chars().next(),
+                        None | Some('/') | Some('?')
+                    )
+            })
+            .max_by_key(|config| config.target_url.len())
+            .cloned()
+    }
 }
@@ -2,6 +2,7 @@ use super::*;
 use crate::{
     config::{Configuration, OutputLevel},
     event_handlers::Handles,
+    filters::WildcardFilter,
     response::FeroxResponse,
     scanner::RESPONSES,
     statistics::Stats,
@@ -24,6 +25,21 @@ fn default_scantype_is_file() {
     }
 }
 
+#[test]
+/// every phase should survive being stored as its discriminant, and only loading should keep
+/// state from being saved
+fn lifecycle_round_trips_and_guards_loading() {
+    for phase in &[
+        Lifecycle::Starting,
+        Lifecycle::Loading,
+        Lifecycle::Scanning,
+        Lifecycle::Finished,
+    ] {
+        assert_eq!(Lifecycle::from_u8(*phase as u8), *phase);
+        assert_eq!(phase.can_save(), *phase != Lifecycle::Loading);
+    }
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 /// tests that pause_scan pauses execution and releases execution when PAUSE_SCAN is toggled
 /// the spinner used during the test has had .finish_and_clear called on it, meaning that
@@ -55,6 +71,181 @@ fn add_url_to_list_of_scanned_urls_with_unknown_url() {
     assert_eq!(result, true);
 }
 
+#[test]
+/// add_wordlist_scan should add a second scan for an already known url
+fn add_wordlist_scan_with_known_url() {
+    let urls = FeroxScans::default();
+    let url = "http://unknown_url/";
+
+    let (_, first) = urls.add_directory_scan(url, ScanOrder::Latest);
+    let second = urls.add_wordlist_scan(url, 42);
+
+    assert_ne!(first.id, second.id);
+    assert_eq!(second.num_requests, 42);
+    assert!(matches!(second.scan_type, ScanType::Directory));
+    assert_eq!(urls.get_scans().len(), 2);
+}
+
+#[test]
+/// lookups by url should return the first scan added for that url, even after a wordlist scan of
+/// the same url is added
+fn get_scan_by_url_returns_first_scan_for_url() {
+    let urls = FeroxScans::default();
+    let url = "http://unknown_url/";
+
+    let (_, first) = urls.add_directory_scan(url, ScanOrder::Latest);
+    urls.add_wordlist_scan(url, 42);
+
+    assert!(urls.contains(url));
+    assert!(!urls.contains("http://unknown_url"));
+    assert_eq!(urls.get_scan_by_url(url).unwrap().id, first.id);
+    assert_eq!(
+        urls.get_base_scan_by_url("http://unknown_url/stuff.php")
+            .unwrap()
+            .id,
+        first.id
+    );
+}
+
+#[test]
+/// a file scan at /x shouldn't keep the directory /x/ from being scanned, or be mistaken for the
+/// directory's scan
+fn file_scan_does_not_block_directory_scan_of_same_path() {
+    let urls = FeroxScans::default();
+
+    let (file_added, file) = urls.add_file_scan("http://localhost/x", ScanOrder::Latest);
+    assert!(file_added);
+    assert!(!urls.contains("http://localhost/x/"));
+
+    let (dir_added, dir) = urls.add_directory_scan("http://localhost/x/", ScanOrder::Latest);
+    assert!(dir_added);
+    assert_ne!(file.id, dir.id);
+
+    assert_eq!(
+        urls.get_scan_by_url("http://localhost/x").unwrap().id,
+        file.id
+    );
+    assert_eq!(
+        urls.get_scan_by_url("HTTP://LOCALHOST:80/x/").unwrap().id,
+        dir.id
+    );
+    assert_eq!(
+        urls.get_base_scan_by_url("http://localhost/x/stuff.php")
+            .unwrap()
+            .id,
+        dir.id
+    );
+}
+
+#[test]
+/// FeroxResponses::contains should find responses by url
+fn ferox_responses_contains_by_url() {
+    let responses = FeroxResponses::default();
+
+    let mut known = FeroxResponse::default();
+    known.set_url("http://localhost/known");
+    let mut unknown = FeroxResponse::default();
+    unknown.set_url("http://localhost/unknown");
+
+    responses.insert(known.clone());
+
+    assert!(responses.contains(&known));
+    assert!(!responses.contains(&unknown));
+}
+
+#[test]
+/// with compact set, only fingerprints should be kept, and they should still be found, counted,
+/// and serialized
+fn ferox_responses_compact_keeps_fingerprints() {
+    let responses = FeroxResponses::default();
+    responses.set_compact(true);
+
+    let mut response = FeroxResponse::default();
+    response.set_url("http://localhost/compact");
+    response.set_text("stuff");
+
+    responses.insert(response.clone());
+
+    assert!(responses.contains(&response));
+    assert_eq!(responses.len(), 1);
+    assert!(responses.to_vec().is_empty());
+    assert_eq!(responses.status_counts()[&200], 1);
+
+    let json = serde_json::to_value(&responses).unwrap();
+    assert_eq!(json[0]["type"], "fingerprint");
+    assert_eq!(json[0]["status"], 200);
+
+    let fingerprint: ResponseFingerprint = serde_json::from_value(json[0].clone()).unwrap();
+    assert_eq!(fingerprint, ResponseFingerprint::new(&response));
+}
+
+#[test]
+/// responses spread across shards should all be reachable through FeroxResponses
+fn ferox_responses_visits_every_shard() {
+    let responses = FeroxResponses::default();
+    assert!(responses.is_empty());
+
+    for i in 0..100 {
+        let mut response = FeroxResponse::default();
+        response.set_url(&format!("http://localhost/{}", i));
+        responses.insert(response);
+    }
+
+    assert_eq!(responses.len(), 100);
+    assert_eq!(responses.to_vec().len(), 100);
+
+    responses.for_each_mut(|response| response.output_level = OutputLevel::Quiet);
+
+    let mut quiet = 0;
+    responses.for_each(|response| {
+        if response.output_level == OutputLevel::Quiet {
+            quiet += 1;
+        }
+    });
+    assert_eq!(quiet, 100);
+}
+
+#[test]
+/// sharding shouldn't change the order in which responses are visited or serialized
+fn ferox_responses_keeps_insertion_order() {
+    let responses = FeroxResponses::default();
+
+    let urls: Vec<String> = (0..100)
+        .map(|i| format!("http://localhost/{}", i))
+        .collect();
+
+    for (i, url) in urls.iter().enumerate() {
+        let mut response = FeroxResponse::default();
+        response.set_url(url);
+
+        if i % 10 == 0 {
+            responses.insert_fingerprint(ResponseFingerprint::new(&response));
+        } else {
+            responses.insert(response);
+        }
+    }
+
+    let mut visited = Vec::new();
+    responses.for_each(|response| visited.push(response.url().to_string()));
+
+    let expected: Vec<&String> = urls
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i % 10 != 0)
+        .map(|(_, url)| url)
+        .collect();
+    assert_eq!(visited.iter().collect::<Vec<_>>(), expected);
+
+    let json = serde_json::to_value(&responses).unwrap();
+    let json = json.as_array().unwrap();
+
+    assert_eq!(json.len(), 100);
+    assert_eq!(json[0]["type"], "fingerprint");
+    assert_eq!(json[1]["url"], "http://localhost/1");
+    assert_eq!(json[10]["type"], "fingerprint");
+    assert_eq!(json[99]["url"], "http://localhost/99");
+}
+
 #[test]
 /// add a known url to the hashset, with a trailing slash, expect false
 fn add_url_to_list_of_scanned_urls_with_known_url() {
@@ -224,7 +415,7 @@ fn ferox_scan_get_progress_bar_when_none_is_set() {
 fn ferox_scan_deserialize() {
     let fs_json = r#"{"id":"057016a14769414aac9a7a62707598cb","url":"https://spiritanimal.com","scan_type":"Directory","status":"Complete"}"#;
     let fs_json_two = r#"{"id":"057016a14769414aac9a7a62707598cb","url":"https://spiritanimal.com","scan_type":"Not Correct","status":"Cancelled"}"#;
-    let fs_json_three = r#"{"id":"057016a14769414aac9a7a62707598cb","url":"https://spiritanimal.com","scan_type":"Not Correct","status":"","num_requests":42}"#;
+    let fs_json_three = r#"{"id":"057016a14769414aac9a7a62707598cb","url":"https://spiritanimal.com","scan_type":"Not Correct","status":"","num_requests":42,"paused":true}"#;
 
     let fs: FeroxScan = serde_json::from_str(fs_json).unwrap();
     let fs_two: FeroxScan = serde_json::from_str(fs_json_two).unwrap();
@@ -260,6 +451,8 @@ fn ferox_scan_deserialize() {
         ScanStatus::NotStarted
     ));
     assert_eq!(fs_three.num_requests, 42);
+    assert!(fs_three.is_paused());
+    assert!(!fs.is_paused());
     assert_eq!(fs.id, "057016a14769414aac9a7a62707598cb");
 }
 
@@ -275,7 +468,7 @@ fn ferox_scan_serialize() {
         None,
     );
     let fs_json = format!(
-        r#"{{"id":"{}","url":"https://spiritanimal.com","scan_type":"Directory","status":"NotStarted","num_requests":0}}"#,
+        r#"{{"id":"{}","url":"https://spiritanimal.com","scan_type":"Directory","status":"NotStarted","num_requests":0,"paused":false}}"#,
         fs.id
     );
     assert_eq!(fs_json, serde_json::to_string(&*fs).unwrap());
@@ -294,10 +487,10 @@ fn ferox_scans_serialize() {
     );
     let ferox_scans = FeroxScans::default();
     let ferox_scans_json = format!(
-        r#"[{{"id":"{}","url":"https://spiritanimal.com","scan_type":"Directory","status":"NotStarted","num_requests":0}}]"#,
+        r#"[{{"id":"{}","url":"https://spiritanimal.com","scan_type":"Directory","status":"NotStarted","num_requests":0,"paused":false}}]"#,
         ferox_scan.id
     );
-    ferox_scans.scans.write().unwrap().push(ferox_scan);
+    ferox_scans.insert(ferox_scan);
     assert_eq!(
         ferox_scans_json,
         serde_json::to_string(&ferox_scans).unwrap()
@@ -383,13 +576,30 @@ fn feroxstates_feroxserialize_implementation() {
 
     let json_state = ferox_state.as_json().unwrap();
     let expected = format!(
-        r#"{{"scans":[{{"id":"{}","url":"https://spiritanimal.com","scan_type":"Directory","status":"NotStarted","num_requests":0}}],"config":{{"type":"configuration","wordlist":"/usr/share/seclists/Discovery/Web-Content/raft-medium-directories.txt","config":"","proxy":"","replay_proxy":"","target_url":"","status_codes":[200,204,301,302,307,308,401,403,405],"replay_codes":[200,204,301,302,307,308,401,403,405],"filter_status":[],"threads":50,"timeout":7,"verbosity":0,"silent":false,"quiet":false,"auto_bail":false,"auto_tune":false,"json":false,"output":"","debug_log":"","user_agent":"feroxbuster/{}","redirects":false,"insecure":false,"extensions":[],"headers":{{}},"queries":[],"no_recursion":false,"extract_links":false,"add_slash":false,"stdin":false,"depth":4,"scan_limit":0,"parallel":0,"rate_limit":0,"filter_size":[],"filter_line_count":[],"filter_word_count":[],"filter_regex":[],"dont_filter":false,"resumed":false,"resume_from":"","save_state":false,"time_limit":"","filter_similar":[]}},"responses":[{{"type":"response","url":"https://nerdcore.com/css","path":"/css","wildcard":true,"status":301,"content_length":173,"line_count":10,"word_count":16,"headers":{{"server":"nginx/1.16.1"}}}}]"#,
+        r#"{{"scans":[{{"id":"{}","url":"https://spiritanimal.com","scan_type":"Directory","status":"NotStarted","num_requests":0,"paused":false}}],"config":{{"type":"configuration","wordlist":"/usr/share/seclists/Discovery/Web-Content/raft-medium-directories.txt","config":"","profile":"","proxy":"","replay_proxy":"","target_url":"","targets":"","target_ranges":[],"status_codes":[200,204,301,302,307,308,401,403,405],"extension_status":{{}},"replay_codes":[200,204,301,302,307,308,401,403,405],"filter_status":[],"threads":50,"timeout":7,"dns_cache_ttl":60,"verbosity":0,"silent":false,"quiet":false,"no_color":false,"colors":{{}},"auto_bail":false,"auto_tune":false,"auto_pause":0,"auto_pause_window":100,"json":false,"output":"","urls_file":"","har":"","output_db":"","output_dsn":"","split_output":"","output_dir":"","compare":"","debug_log":"","debug_log_json":false,"debug_log_max_size":"","debug_log_backups":5,"cache_dir":"","user_agent":"feroxbuster/{}","redirects":false,"insecure":false,"ca_cert":"","pin_sha256":[],"extensions":[],"headers":{{}},"raw":false,"raw_headers":[],"queries":[],"query_file":"","no_recursion":false,"dont_recurse_regex":[],"extract_links":false,"parse_listings":false,"smart_wordlist":false,"api":false,"fingerprint":false,"hashes":[],"fingerprint_db":"","show_preview":0,"group_similar":0,"auto_extensions":false,"auto_wordlist":"","wordlist_rules":"","interesting_rules":"","bypass_403":false,"bypass_techniques":[],"bypass_budget":250,"verb_compare":false,"follow_disclosures":false,"cors":false,"header_audit":false,"favicon":false,"wsdl":false,"cloud_storage":false,"add_slash":false,"smart_slash":false,"url_encode_level":"none","shuffle":false,"seed":0,"stdin":false,"depth":4,"scan_limit":0,"max_scans":0,"strategy":"bfs","priority_keywords":"","parallel":0,"rate_limit":0,"delay":"","jitter":"","decoy_ratio":0,"filter_size":[],"filter_line_count":[],"filter_word_count":[],"filter_regex":[],"dont_filter":false,"resumed":false,"resume_from":"","save_state":false,"state_redact":false,"state_embed_wordlist":false,"time_limit":"","scan_time_limit":"","tarpit_latency":0,"throttle":"","request_limit":0,"scan_window":"","filter_similar":[],"filter_header":[],"match_header":[],"filter_command":"","plugins":[],"notify_webhook":"","notify_on":[],"on_found":"","on_found_limit":4,"metrics_addr":"","api_addr":"","grpc":"","dry_run":false,"low_memory":false,"check_update":true}},"responses":[{{"type":"response","url":"https://nerdcore.com/css","path":"/css","wildcard":true,"status":301,"content_length":173,"line_count":10,"word_count":16,"headers":{{"server":"nginx/1.16.1"}}}}]"#,
         saved_id, VERSION
     );
     println!("{}\n{}", expected, json_state);
     assert!(predicates::str::contains(expected).eval(&json_state));
 }
 
+#[test]
+/// scan windows should parse into minutes since midnight and handle wrapping around midnight
+fn scan_window_parses_and_wraps() {
+    assert_eq!(parse_scan_window("22:00-06:30"), Some((1320, 390)));
+    assert_eq!(parse_scan_window("nope"), None);
+
+    // same day window
+    assert!(in_scan_window(540, 1020, 600));
+    assert!(!in_scan_window(540, 1020, 1020));
+    assert!(!in_scan_window(540, 1020, 60));
+
+    // overnight window
+    assert!(in_scan_window(1320, 360, 1400));
+    assert!(in_scan_window(1320, 360, 0));
+    assert!(!in_scan_window(1320, 360, 720));
+}
+
 #[should_panic]
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 /// call start_max_time_thread with a valid timespec, expect a panic, but only after a certain
@@ -405,11 +615,47 @@ async fn start_max_time_thread_panics_after_delay() {
 
     let handles = Arc::new(Handles::for_testing(None, Some(Arc::new(config))).0);
 
-    start_max_time_thread(handles).await;
+    let _ = start_max_time_thread(handles).await;
 
     assert!(now.elapsed() > delay);
 }
 
+#[should_panic]
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+/// call start_request_limit_thread after the limit has been reached, expect a panic, as the
+/// sigint handler is called
+async fn start_request_limit_thread_panics_once_limit_reached() {
+    let config = Configuration {
+        request_limit: 2,
+        ..Default::default()
+    };
+
+    let handles = Arc::new(Handles::for_testing(None, Some(Arc::new(config))).0);
+
+    handles.stats.data.add_request();
+    handles.stats.data.add_request();
+
+    let _ = start_request_limit_thread(handles).await;
+}
+
+#[should_panic]
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+/// call start_request_limit_thread once every allowed request has been claimed, but before they've
+/// all been sent, expect a panic, as the sigint handler is called
+async fn start_request_limit_thread_panics_once_requests_claimed() {
+    let config = Configuration {
+        request_limit: 2,
+        ..Default::default()
+    };
+
+    let handles = Arc::new(Handles::for_testing(None, Some(Arc::new(config))).0);
+
+    assert!(handles.stats.data.claim_request(2));
+    assert!(handles.stats.data.claim_request(2));
+
+    let _ = start_request_limit_thread(handles).await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 /// call start_max_time_thread with a timespec that's too large to be parsed correctly, expect
 /// immediate return and no panic, as the sigint handler is never called
@@ -424,7 +670,7 @@ async fn start_max_time_thread_returns_immediately_with_too_large_input() {
     let handles = Arc::new(Handles::for_testing(None, Some(Arc::new(config))).0);
 
     // pub const MAX: usize = usize::MAX; // 18_446_744_073_709_551_615usize
-    start_max_time_thread(handles).await; // can't fit in dest u64
+    assert!(start_max_time_thread(handles).await.is_ok()); // can't fit in dest u64
 
     assert!(now.elapsed() < delay); // assuming function call will take less than 1second
 }
@@ -446,6 +692,9 @@ fn feroxscan_display() {
         task: tokio::sync::Mutex::new(None),
         progress_bar: std::sync::Mutex::new(None),
         errors: Default::default(),
+        blocked: Default::default(),
+        paused: Default::default(),
+        wildcard: Default::default(),
     };
 
     let not_started = format!("{}", scan);
@@ -492,6 +741,9 @@ async fn ferox_scan_abort() {
         }))),
         progress_bar: std::sync::Mutex::new(None),
         errors: Default::default(),
+        blocked: Default::default(),
+        paused: Default::default(),
+        wildcard: Default::default(),
     };
 
     scan.abort().await.unwrap();
@@ -530,6 +782,37 @@ fn split_to_nums_is_correct() {
     assert!(menu.split_to_nums("\n").is_empty());
 }
 
+#[test]
+/// a leading p or r should select pause or resume, anything else is a cancellation
+fn split_action_is_correct() {
+    let menu = Menu::new();
+
+    assert_eq!(menu.split_action("p 3-5"), (MenuAction::Pause, " 3-5"));
+    assert_eq!(menu.split_action("  r4"), (MenuAction::Resume, "4"));
+    assert_eq!(menu.split_action("1,2"), (MenuAction::Cancel, "1,2"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+/// pausing a single scan should leave others alone, and be undone by resuming it
+async fn ferox_scans_pause_scan_by_id() {
+    let scans = FeroxScans::default();
+    let (_, paused) = scans.add_scan("http://localhost/a", ScanType::Directory, ScanOrder::Latest);
+    let (_, running) = scans.add_scan("http://localhost/b", ScanType::Directory, ScanOrder::Latest);
+
+    assert!(scans.pause_scan_by_id(&paused.id, true));
+    assert!(!scans.pause_scan_by_id("nope", true));
+
+    assert!(paused.is_paused());
+    assert!(!running.is_paused());
+
+    // not paused, should return right away
+    running.wait_while_paused().await;
+
+    assert!(scans.pause_scan_by_id(&paused.id, false));
+    assert!(!paused.is_paused());
+    paused.wait_while_paused().await;
+}
+
 #[test]
 /// given a deep url, find the correct scan
 fn get_base_scan_by_url_finds_correct_scan() {
@@ -596,3 +879,477 @@ fn get_base_scan_by_url_finds_correct_scan_with_trailing_slash() {
         scan.id
     );
 }
+
+#[test]
+/// responses should be compared to the calibration of the closest calibrated directory above
+/// them, never to that of their own directory
+fn ferox_scans_is_wildcard_uses_closest_calibration() {
+    let scans = FeroxScans::default();
+    let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let (_, root) = scans.add_directory_scan("http://localhost", ScanOrder::Initial);
+    let (_, js) = scans.add_directory_scan("http://localhost/js/", ScanOrder::Latest);
+    scans.add_directory_scan("http://localhost/css/", ScanOrder::Latest);
+
+    let mut custom_404 = WildcardFilter::new(false);
+    custom_404.size = 4;
+    root.set_wildcard_filter(custom_404);
+
+    // js has no custom 404; css hasn't been calibrated yet
+    js.set_wildcard_filter(WildcardFilter::new(false));
+
+    let response = |url: &str| {
+        let mut response = FeroxResponse::default();
+        response.set_url(url);
+        response.set_text("nope");
+        response
+    };
+
+    assert!(scans.is_wildcard(&response("http://localhost/stuff"), tx.clone()));
+    assert!(scans.is_wildcard(&response("http://localhost/js/"), tx.clone()));
+    assert!(!scans.is_wildcard(&response("http://localhost/js/stuff"), tx.clone()));
+    assert!(scans.is_wildcard(&response("http://localhost/css/stuff"), tx.clone()));
+    assert!(!scans.is_wildcard(&response("http://localhost/js/stuff/"), tx));
+}
+
+#[test]
+/// urls that differ only in case, default port, or dot-segments should be treated as the same
+/// scan, while a trailing slash still tells a directory apart from a file
+fn ferox_scans_dedup_normalizes_urls() {
+    let scans = FeroxScans::default();
+
+    let (added, js) = scans.add_directory_scan("http://localhost/js/", ScanOrder::Latest);
+    assert!(added);
+
+    for url in &[
+        "HTTP://LOCALHOST:80/js/",
+        "http://localhost/css/../js/",
+        "http://localhost/./js/#top",
+    ] {
+        assert!(scans.contains(url));
+        assert_eq!(scans.get_scan_by_url(url).unwrap().id, js.id);
+        assert!(!scans.add_directory_scan(url, ScanOrder::Latest).0);
+    }
+
+    // the path itself is case-sensitive, and so are non-default ports
+    assert!(!scans.contains("http://localhost/JS/"));
+    assert!(!scans.contains("http://localhost:8080/js/"));
+    assert!(!scans.contains("http://localhost/js"));
+    assert_eq!(scans.get_scans().len(), 1);
+
+    assert_eq!(
+        scans
+            .get_base_scan_by_url("http://LocalHost/js/app.js")
+            .unwrap()
+            .id,
+        js.id
+    );
+}
+
+#[test]
+/// a redirect should be reported as a loop only once it leads back to where it started, and every
+/// url in the loop should be remembered
+fn ferox_scans_add_redirect_finds_loops() {
+    let scans = FeroxScans::default();
+
+    // slash/no-slash canonicalization cycle
+    assert!(scans
+        .add_redirect("http://localhost/admin", "http://localhost/admin/")
+        .is_none());
+    assert!(!scans.in_redirect_loop("http://localhost/admin"));
+
+    let cycle = scans
+        .add_redirect("HTTP://localhost:80/admin/", "http://localhost/admin")
+        .unwrap();
+    assert_eq!(
+        cycle,
+        vec![
+            "http://localhost/admin/",
+            "http://localhost/admin",
+            "http://localhost/admin/"
+        ]
+    );
+    assert!(scans.in_redirect_loop("http://localhost/admin"));
+    assert!(scans.in_redirect_loop("http://localhost/admin/"));
+
+    // already known, not reported twice
+    assert!(scans
+        .add_redirect("http://localhost/admin/", "http://localhost/admin")
+        .is_none());
+
+    // longer loop, and a chain that only leads into a loop without being part of it
+    assert!(scans
+        .add_redirect("http://localhost/a", "http://localhost/b")
+        .is_none());
+    assert!(scans
+        .add_redirect("http://localhost/b", "http://localhost/c")
+        .is_none());
+    assert_eq!(
+        scans
+            .add_redirect("http://localhost/c", "http://localhost/a")
+            .unwrap()
+            .len(),
+        4
+    );
+    assert!(scans
+        .add_redirect("http://localhost/login", "http://localhost/a")
+        .is_none());
+    assert!(!scans.in_redirect_loop("http://localhost/login"));
+    assert!(scans.in_redirect_loop("HTTP://localhost/b"));
+    assert!(!scans.in_redirect_loop("http://localhost/b/"));
+}
+
+#[test]
+/// directories waiting on --scan-limit should be saved as pending, and added back on resume
+fn ferox_state_persists_pending_scans() {
+    let scans = Arc::new(FeroxScans::default());
+
+    let (_, running) = scans.add_directory_scan("http://localhost/", ScanOrder::Initial);
+    running.set_status(ScanStatus::Running).unwrap();
+    scans.add_directory_scan("http://localhost/admin/", ScanOrder::Latest);
+    scans.add_file_scan("http://localhost/index.php", ScanOrder::Latest);
+
+    assert_eq!(scans.pending_scans(), vec!["http://localhost/admin/"]);
+
+    let config = Arc::new(Configuration::new().unwrap());
+    let stats = Arc::new(Stats::new(config.extensions.len(), config.json));
+    let state = FeroxState::new(scans, config, &RESPONSES, stats);
+
+    let json_state = state.as_json().unwrap();
+    assert!(json_state.contains(r#""pending":["http://localhost/admin/"]"#));
+
+    // a pending directory that never made it into the scans list
+    let tmp = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        tmp.path(),
+        r#"{"scans":[],"pending":["http://localhost/backup/"]}"#,
+    )
+    .unwrap();
+
+    let resumed = FeroxScans::default();
+    resumed
+        .add_serialized_scans(tmp.path().to_str().unwrap(), "")
+        .unwrap();
+
+    let scan = resumed.get_scan_by_url("http://localhost/backup/").unwrap();
+    assert!(scan.is_pending());
+}
+
+#[test]
+/// a state file saved for one target (--output-dir) should only hold that target's scans
+fn ferox_state_for_target_keeps_only_its_scans() {
+    let scans = Arc::new(FeroxScans::default());
+
+    let (_, running) = scans.add_directory_scan("http://localhost/", ScanOrder::Initial);
+    running.set_status(ScanStatus::Running).unwrap();
+    let (_, other) = scans.add_directory_scan("https://other.com/", ScanOrder::Initial);
+    other.set_status(ScanStatus::Running).unwrap();
+    scans.add_directory_scan("https://other.com/admin/", ScanOrder::Latest);
+
+    let config = Arc::new(Configuration::new().unwrap());
+    let stats = Arc::new(Stats::new(config.extensions.len(), config.json));
+    let state = FeroxState::new(scans, config, &RESPONSES, stats).for_target("https_other_com");
+
+    let json_state = state.as_json().unwrap();
+    assert!(!json_state.contains("http://localhost/"));
+    assert!(json_state.contains(r#""url":"https://other.com/""#));
+    assert!(json_state.contains(r#""pending":["https://other.com/admin/"]"#));
+}
+
+#[test]
+/// directories skipped because of --max-scans aren't active, and are read back from a state file
+fn ferox_scans_tracks_skipped_scans() {
+    let scans = FeroxScans::default();
+
+    let skipped = scans.add_skipped_scan("http://localhost/admin/");
+    assert!(skipped.is_skipped());
+    assert!(!skipped.is_active());
+    assert!(!skipped.is_pending());
+
+    // an already known scan is reused rather than duplicated
+    scans.add_skipped_scan("http://localhost/admin/");
+    assert_eq!(scans.get_scans().len(), 1);
+
+    let tmp = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        tmp.path(),
+        format!(
+            r#"{{"scans":[{}]}}"#,
+            serde_json::to_string(&*skipped).unwrap()
+        ),
+    )
+    .unwrap();
+
+    let resumed = FeroxScans::default();
+    resumed
+        .add_serialized_scans(tmp.path().to_str().unwrap(), "")
+        .unwrap();
+
+    let scan = resumed.get_scan_by_url("http://localhost/admin/").unwrap();
+    assert!(scan.is_skipped());
+}
+
+#[test]
+/// the number of directory scans started (--max-scans) is saved to and read back from a state file
+fn ferox_scans_saves_num_scans() {
+    let scans = Arc::new(FeroxScans::default());
+    scans.increment_num_scans();
+    scans.increment_num_scans();
+
+    let config = Arc::new(Configuration::new().unwrap());
+    let stats = Arc::new(Stats::new(config.extensions.len(), config.json));
+    let state = FeroxState::new(scans, config, &RESPONSES, stats);
+
+    let tmp = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(tmp.path(), state.as_json().unwrap()).unwrap();
+
+    let resumed = FeroxScans::default();
+    resumed
+        .add_serialized_scans(tmp.path().to_str().unwrap(), "")
+        .unwrap();
+
+    assert_eq!(resumed.num_scans(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+/// the scan context should only be visible from within its scope
+async fn scan_context_is_scoped_to_its_task() {
+    assert!(ScanContext::current().is_none());
+
+    let context = ScanContext::new("d8b6eb4f", 2);
+
+    let current = context
+        .clone()
+        .scope(async { ScanContext::current() })
+        .await;
+    assert_eq!(current, Some(context));
+
+    // tasks without an id aren't running on behalf of a scan
+    let current = ScanContext::default()
+        .scope(async { ScanContext::current() })
+        .await;
+    assert!(current.is_none());
+
+    assert!(ScanContext::current().is_none());
+}
+
+#[test]
+/// the newest active directory scan that isn't one of the initial targets should be returned
+fn ferox_scans_newest_active_scan_skips_initial_and_inactive() {
+    let scans = FeroxScans::default();
+    assert!(scans.newest_active_scan().is_none());
+
+    scans.add_directory_scan("http://localhost/", ScanOrder::Initial);
+    assert!(scans.newest_active_scan().is_none());
+
+    scans.add_directory_scan("http://localhost/js/", ScanOrder::Latest);
+    let (_, newest) = scans.add_directory_scan("http://localhost/css/", ScanOrder::Latest);
+    scans.add_file_scan("http://localhost/css/style.css", ScanOrder::Latest);
+
+    assert_eq!(
+        scans.newest_active_scan().unwrap().url(),
+        "http://localhost/css/"
+    );
+
+    newest.set_status(ScanStatus::Cancelled).unwrap();
+    assert_eq!(
+        scans.newest_active_scan().unwrap().url(),
+        "http://localhost/js/"
+    );
+}
+
+#[test]
+/// urls should get the configuration of the most specific --targets entry they fall under
+fn ferox_scans_get_target_config_prefers_longest_target() {
+    let scans = FeroxScans::default();
+
+    for (target, depth) in &[("http://localhost", 1), ("http://localhost/api/", 2)] {
+        let config = Configuration {
+            target_url: target.to_string(),
+            depth: *depth,
+            ..Default::default()
+        };
+        scans.add_target_config(Arc::new(config));
+    }
+
+    let depth = |url| scans.get_target_config(url).map(|config| config.depth);
+
+    assert_eq!(depth("http://localhost"), Some(1));
+    assert_eq!(depth("http://localhost/js/"), Some(1));
+    assert_eq!(depth("http://localhost/api/v1/"), Some(2));
+    assert_eq!(depth("http://localhost.com/"), None);
+    assert_eq!(depth("http://elsewhere/"), None);
+}
+
+#[test]
+/// time specs should be converted to seconds, bad or overly large ones rejected
+fn time_spec_to_secs_converts_units() {
+    assert_eq!(time_spec_to_secs("30s"), Some(30));
+    assert_eq!(time_spec_to_secs("10M"), Some(600));
+    assert_eq!(time_spec_to_secs("2h"), Some(7200));
+    assert_eq!(time_spec_to_secs("1d"), Some(86400));
+    assert_eq!(time_spec_to_secs(""), None);
+    assert_eq!(time_spec_to_secs("soon"), None);
+    assert_eq!(time_spec_to_secs("18446744073709551615d"), None);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+/// a timed out scan should stay timed out (i.e. incomplete) after its task is joined
+async fn ferox_scan_time_out_survives_join() {
+    let scan = FeroxScan::new(
+        "http://localhost/",
+        ScanType::Directory,
+        ScanOrder::Latest,
+        1000,
+        OutputLevel::Default,
+        None,
+    );
+
+    scan.set_task(tokio::spawn(async {})).await.unwrap();
+    scan.time_out().unwrap();
+    scan.join().await;
+
+    assert!(scan.is_timed_out());
+    assert!(!scan.is_complete());
+    assert!(!scan.is_active());
+    assert!(format!("{}", scan).contains("timed out"));
+}
+
+#[test]
+/// paths beneath the directory a scan was saved from should be saved as relative, and found next
+/// to the state file when resumed somewhere else
+fn state_paths_are_relative_and_found_next_to_state_file() {
+    let saved_dir = tempfile::TempDir::new().unwrap();
+    let state_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(state_dir.path().join("words.txt"), "admin\n").unwrap();
+
+    let mut config = Configuration::new().unwrap();
+    config.wordlist = saved_dir
+        .path()
+        .join("words.txt")
+        .to_string_lossy()
+        .to_string();
+    config.output = saved_dir
+        .path()
+        .join("results")
+        .join("ferox.txt")
+        .to_string_lossy()
+        .to_string();
+    config.ca_cert = "/etc/ssl/elsewhere.pem".to_string();
+
+    portable::relativize_paths(&mut config, saved_dir.path());
+
+    assert_eq!(config.wordlist, "words.txt");
+    assert!(config.output.ends_with("ferox.txt") && !config.output.starts_with('/'));
+    assert_eq!(config.ca_cert, "/etc/ssl/elsewhere.pem");
+
+    let gone = std::path::Path::new("/nonexistent/feroxbuster");
+    portable::localize_paths(&mut config, Some(gone), state_dir.path());
+
+    assert_eq!(
+        config.wordlist,
+        state_dir.path().join("words.txt").to_string_lossy()
+    );
+    assert_eq!(config.output, "ferox.txt");
+    assert_eq!(config.ca_cert, "/etc/ssl/elsewhere.pem");
+}
+
+#[test]
+/// a wordlist whose words match the record should be kept, one that doesn't should be replaced
+/// by the embedded words, or refused when there aren't any
+fn restore_wordlist_checks_hash_and_uses_embedded_words() {
+    let tmp_dir = tempfile::TempDir::new().unwrap();
+    let path = tmp_dir.path().join("words.txt");
+    std::fs::write(&path, "admin\nbackup\n").unwrap();
+
+    let saved = crate::wordlist::Wordlist::from_words(&["admin", "backup"]);
+    let mut record = WordlistRecord::new(&saved, false);
+    assert_eq!(record.words, 2);
+    assert!(record.contents.is_none());
+
+    let mut config = Configuration::new().unwrap();
+    config.wordlist = path.to_string_lossy().to_string();
+
+    portable::restore_wordlist(&mut config, &record).unwrap();
+    assert_eq!(config.wordlist, path.to_string_lossy());
+
+    // changed since the scan was saved, and nothing to fall back on
+    std::fs::write(&path, "admin\n").unwrap();
+    assert!(portable::restore_wordlist(&mut config, &record).is_err());
+
+    record = WordlistRecord::new(&saved, true);
+    portable::restore_wordlist(&mut config, &record).unwrap();
+
+    assert_ne!(config.wordlist, path.to_string_lossy());
+    assert_eq!(
+        std::fs::read_to_string(&config.wordlist).unwrap(),
+        "admin\nbackup\n"
+    );
+    std::fs::remove_file(&config.wordlist).unwrap_or_default();
+}
+
+#[test]
+/// a rules file whose wordlists match the record should be kept; once one of them changes, the
+/// saved file is restored, naming the embedded words in place of the changed wordlist
+fn restore_mapping_checks_wordlists_and_rewrites_file() {
+    let tmp_dir = tempfile::TempDir::new().unwrap();
+    let words = tmp_dir.path().join("api.txt");
+    let rules = tmp_dir.path().join("rules.toml");
+    std::fs::write(&words, "users\norders\n").unwrap();
+    std::fs::write(
+        &rules,
+        format!(
+            "[[rules]]\npattern = \"/api/*\"\nwordlist = {:?}\n",
+            words.to_string_lossy()
+        ),
+    )
+    .unwrap();
+
+    let rules_path = rules.to_string_lossy().to_string();
+    let record = portable::MappingRecord::new(&rules_path, true).unwrap();
+    assert_eq!(record.wordlists.len(), 1);
+
+    let mut path = rules_path.clone();
+    portable::restore_mapping(&mut path, &record, None, tmp_dir.path()).unwrap();
+    assert_eq!(path, rules_path);
+
+    std::fs::write(&words, "users\n").unwrap();
+    portable::restore_mapping(&mut path, &record, None, tmp_dir.path()).unwrap();
+    assert_ne!(path, rules_path);
+
+    let restored = crate::wordlist_rules::read_wordlist_rules(&path).unwrap();
+    assert_eq!(restored[0].pattern, "/api/*");
+    assert_eq!(
+        std::fs::read_to_string(&restored[0].wordlist).unwrap(),
+        "users\norders\n"
+    );
+
+    std::fs::remove_file(&restored[0].wordlist).unwrap_or_default();
+    std::fs::remove_file(&path).unwrap_or_default();
+
+    // changed since the scan was saved, and nothing to fall back on
+    let record = portable::MappingRecord::new(&rules_path, false).unwrap();
+    std::fs::write(&words, "users\norders\n").unwrap();
+    let mut path = rules_path;
+    assert!(portable::restore_mapping(&mut path, &record, None, tmp_dir.path()).is_err());
+}
+
+#[test]
+/// the wordlist being scanned with should be recorded in the state, its words only when asked
+fn ferox_state_records_wordlist() {
+    let scans = Arc::new(FeroxScans::default());
+    scans.set_wordlist(Arc::new(crate::wordlist::Wordlist::from_words(&["admin"])));
+
+    let mut config = Configuration::new().unwrap();
+    config.state_embed_wordlist = true;
+    let config = Arc::new(config);
+    let stats = Arc::new(Stats::new(config.extensions.len(), config.json));
+
+    let state = FeroxState::new(scans, config, &RESPONSES, stats);
+    let json_state: serde_json::Value = serde_json::from_str(&state.as_json().unwrap()).unwrap();
+
+    assert_eq!(json_state["wordlist"]["words"], 1);
+    assert_eq!(json_state["wordlist"]["contents"], "admin\n");
+    assert_eq!(json_state["wordlist"]["sha256"].as_str().unwrap().len(), 64);
+    assert!(!json_state["cwd"].as_str().unwrap().is_empty());
+}
@@ -1,35 +1,58 @@
 #[cfg(not(test))]
 use crate::event_handlers::TermInputHandler;
 use crate::{
-    config::Configuration, event_handlers::Handles, parser::TIMESPEC_REGEX, scanner::RESPONSES,
+    config::Configuration,
+    error::{FeroxError, FeroxResult},
+    event_handlers::Handles,
+    parser::{SCAN_WINDOW_REGEX, TIMESPEC_REGEX},
+    scan_manager::{
+        portable::{localize_paths, restore_mapping, restore_wordlist, MappingRecord},
+        read_state, WordlistRecord, PAUSE_SCAN,
+    },
+    scanner::RESPONSES,
 };
 
-use std::{fs::File, io::BufReader, sync::Arc};
+use chrono::{Local, Timelike};
+use std::{
+    path::Path,
+    sync::{atomic::Ordering, Arc},
+};
 use tokio::time;
 
+/// number of seconds between checks of whether or not the current time is within --scan-window
+const SCAN_WINDOW_INTERVAL: u64 = 30;
+
+/// number of milliseconds between checks of the total number of requests sent (--request-limit)
+const REQUEST_LIMIT_INTERVAL: u64 = 100;
+
+/// Convert a time spec (ex: 30s, 10m, 1h, 1d) to a number of seconds; None if the spec can't be
+/// parsed or doesn't fit in a u64
+pub fn time_spec_to_secs(time_spec: &str) -> Option<u64> {
+    let captures = TIMESPEC_REGEX.captures(time_spec)?;
+    let length = captures.get(1)?.as_str().parse::<u64>().ok()?;
+
+    match captures.get(2)?.as_str().to_ascii_lowercase().as_str() {
+        "m" => length.checked_mul(60),           // minutes
+        "h" => length.checked_mul(60 * 60),      // hours
+        "d" => length.checked_mul(60 * 60 * 24), // days
+        _ => Some(length),                       // seconds
+    }
+}
+
 /// Given a string representing some number of seconds, minutes, hours, or days, convert
 /// that representation to seconds and then wait for those seconds to elapse.  Once that period
 /// of time has elapsed, kill all currently running scans and dump a state file to disk that can
 /// be used to resume any unfinished scan.
-pub async fn start_max_time_thread(handles: Arc<Handles>) {
+///
+/// returns the `FeroxError::Interrupted` that should end the program once the limit is reached;
+/// returns Ok when the limit can't be enforced
+pub async fn start_max_time_thread(handles: Arc<Handles>) -> FeroxResult<()> {
     log::trace!("enter: start_max_time_thread({:?})", handles);
 
     // as this function has already made it through the parser, which calls is_match on
-    // the value passed to --time-limit using TIMESPEC_REGEX; we can safely assume that
-    // the capture groups are populated; can expect something like 10m, 30s, 1h, etc...
-    let captures = TIMESPEC_REGEX.captures(&handles.config.time_limit).unwrap();
-    let length_match = captures.get(1).unwrap();
-    let measurement_match = captures.get(2).unwrap();
-
-    if let Ok(length) = length_match.as_str().parse::<u64>() {
-        let length_in_secs = match measurement_match.as_str().to_ascii_lowercase().as_str() {
-            "s" => length,
-            "m" => length * 60,           // minutes
-            "h" => length * 60 * 60,      // hours
-            "d" => length * 60 * 60 * 24, // days
-            _ => length,
-        };
-
+    // the value passed to --time-limit using TIMESPEC_REGEX; can expect something like 10m,
+    // 30s, 1h, etc...
+    if let Some(length_in_secs) = time_spec_to_secs(&handles.config.time_limit) {
         log::debug!(
             "max time limit as string: {} and as seconds: {}",
             handles.config.time_limit,
@@ -43,44 +66,202 @@ pub async fn start_max_time_thread(handles: Arc<Handles>) {
         #[cfg(test)]
         panic!("{:?}", handles);
         #[cfg(not(test))]
-        let _ = TermInputHandler::sigint_handler(handles.clone());
+        return Err(TermInputHandler::sigint_handler(handles));
     }
 
     log::warn!(
         "Could not parse the value provided ({}), can't enforce time limit",
         handles.config.time_limit
     );
+
+    Ok(())
+}
+
+/// Wait for the total number of requests sent across all scans to reach --request-limit.  Once
+/// the limit is reached, kill all currently running scans and dump a state file to disk that can
+/// be used to resume any unfinished scan.
+///
+/// the limit itself is enforced by each scan's `Requester`, which claims a request before sending
+/// it and sends nothing once the limit has been claimed; this only stops the scans afterwards
+///
+/// returns the `FeroxError::Interrupted` that should end the program once the limit is reached
+pub async fn start_request_limit_thread(handles: Arc<Handles>) -> FeroxResult<()> {
+    log::trace!("enter: start_request_limit_thread({:?})", handles);
+
+    let limit = handles.config.request_limit;
+
+    while handles.stats.data.requests() < limit && !handles.stats.data.requests_exhausted(limit) {
+        time::sleep(time::Duration::from_millis(REQUEST_LIMIT_INTERVAL)).await;
+    }
+
+    log::warn!(
+        "Request limit of {} reached; stopping all scans",
+        handles.config.request_limit
+    );
+
+    log::trace!("exit: start_request_limit_thread");
+
+    #[cfg(test)]
+    panic!("{:?}", handles);
+    #[cfg(not(test))]
+    return Err(TermInputHandler::sigint_handler(handles));
+}
+
+/// Parse a --scan-window value into its start and end, as minutes since midnight
+pub fn parse_scan_window(window: &str) -> Option<(u32, u32)> {
+    let captures = SCAN_WINDOW_REGEX.captures(window)?;
+
+    let minutes = |hours: &str, minutes: &str| -> Option<u32> {
+        Some(
+            captures.name(hours)?.as_str().parse::<u32>().ok()? * 60
+                + captures.name(minutes)?.as_str().parse::<u32>().ok()?,
+        )
+    };
+
+    Some((minutes("sh", "sm")?, minutes("eh", "em")?))
+}
+
+/// Determine whether or not the given time (minutes since midnight) falls within the window;
+/// windows that end before they start wrap around midnight, i.e. 22:00-06:00
+pub fn in_scan_window(start: u32, end: u32, now: u32) -> bool {
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Periodically compare the current (local) time to --scan-window, pausing all scans outside of
+/// the window and resuming them once it opens again
+///
+/// only scans paused by this function are resumed by it, a pause from the user is left alone
+pub async fn start_scan_window_thread(handles: Arc<Handles>) {
+    log::trace!("enter: start_scan_window_thread({:?})", handles);
+
+    let (start, end) = match parse_scan_window(&handles.config.scan_window) {
+        Some(window) => window,
+        None => {
+            log::warn!(
+                "Could not parse the value provided ({}), can't enforce scan window",
+                handles.config.scan_window
+            );
+            return;
+        }
+    };
+
+    let mut paused_by_window = false;
+
+    loop {
+        let now = Local::now();
+        let inside = in_scan_window(start, end, now.hour() * 60 + now.minute());
+
+        if let Ok(scans) = handles.ferox_scans() {
+            if !inside && !paused_by_window {
+                paused_by_window = true;
+
+                if PAUSE_SCAN.load(Ordering::Acquire) {
+                    // already paused by the user, who's left to resume them
+                    paused_by_window = false;
+                } else {
+                    log::warn!(
+                        "Outside of scan window {}; pausing all scans",
+                        handles.config.scan_window
+                    );
+                    scans.pause_remotely();
+                }
+            } else if inside && paused_by_window {
+                paused_by_window = false;
+
+                // scans resumed (and possibly paused again) by the user in the meantime stay as
+                // the user left them
+                if scans.paused_remotely() {
+                    log::warn!(
+                        "Inside of scan window {}; resuming all scans",
+                        handles.config.scan_window
+                    );
+                    scans.resume_remotely();
+                }
+            }
+        }
+
+        time::sleep(time::Duration::from_secs(SCAN_WINDOW_INTERVAL)).await;
+    }
 }
 
 /// Primary logic used to load a Configuration from disk and populate the appropriate data
 /// structures
-pub fn resume_scan(filename: &str) -> Configuration {
+///
+/// paths that don't exist on this machine are looked for relative to the state file; the saved
+/// wordlist, --wordlist-rules, and --auto-wordlist are checked against the ones the scan was
+/// started with, and restored from the state file if needed, unless they're in `given` (a
+/// different one was given on the command line, i.e. "wordlist" for -w)
+pub fn resume_scan(filename: &str, password: &str, given: &[&str]) -> FeroxResult<Configuration> {
     log::trace!("enter: resume_scan({})", filename);
 
-    let file = File::open(filename).unwrap_or_else(|e| {
-        log::error!("{}", e);
-        log::error!("Could not open state file, exiting");
-        std::process::exit(1);
-    });
+    let state = read_state(filename, password).map_err(|e| FeroxError::StateFileUnreadable {
+        filename: filename.to_string(),
+        reason: format!("{:#}", e),
+    })?;
+
+    let conf = state
+        .get("config")
+        .ok_or_else(|| FeroxError::StateFileConfig {
+            filename: filename.to_string(),
+            reason: String::from("no configuration found"),
+        })?;
+
+    let mut config: Configuration =
+        serde_json::from_value(conf.clone()).map_err(|e| FeroxError::StateFileConfig {
+            filename: filename.to_string(),
+            reason: e.to_string(),
+        })?;
 
-    let reader = BufReader::new(file);
-    let state: serde_json::Value = serde_json::from_reader(reader).unwrap();
+    let saved_cwd = state["cwd"].as_str().map(Path::new);
+    let state_dir = Path::new(filename)
+        .parent()
+        .unwrap_or_else(|| Path::new(""));
 
-    let conf = state.get("config").unwrap_or_else(|| {
-        log::error!("Could not load configuration from state file, exiting");
-        std::process::exit(1);
-    });
+    localize_paths(&mut config, saved_cwd, state_dir);
+
+    // state files saved before the wordlist was recorded can't be checked
+    if let Ok(record) = serde_json::from_value::<WordlistRecord>(state["wordlist"].clone()) {
+        if !given.contains(&"wordlist") {
+            restore_wordlist(&mut config, &record).map_err(|e| FeroxError::StateFileConfig {
+                filename: filename.to_string(),
+                reason: format!("{:#}", e),
+            })?;
+        }
+    }
 
-    let config = serde_json::from_value(conf.clone()).unwrap_or_else(|e| {
-        log::error!("{}", e);
-        log::error!("Could not deserialize configuration found in state file, exiting");
-        std::process::exit(1);
-    });
+    let mappings = [
+        ("wordlist_rules", &mut config.wordlist_rules),
+        ("auto_wordlist", &mut config.auto_wordlist),
+    ];
+
+    for (name, path) in mappings {
+        if given.contains(&name) {
+            continue;
+        }
+
+        if let Ok(record) = serde_json::from_value::<MappingRecord>(state[name].clone()) {
+            restore_mapping(path, &record, saved_cwd, state_dir).map_err(|e| {
+                FeroxError::StateFileConfig {
+                    filename: filename.to_string(),
+                    reason: format!("{}: {:#}", name, e),
+                }
+            })?;
+        }
+    }
 
     if let Some(responses) = state.get("responses") {
         if let Some(arr_responses) = responses.as_array() {
             for response in arr_responses {
-                if let Ok(deser_resp) = serde_json::from_value(response.clone()) {
+                if response["type"] == "fingerprint" {
+                    // saved with --low-memory, only the fingerprint was retained
+                    if let Ok(fingerprint) = serde_json::from_value(response.clone()) {
+                        RESPONSES.insert_fingerprint(fingerprint);
+                    }
+                } else if let Ok(deser_resp) = serde_json::from_value(response.clone()) {
                     RESPONSES.insert(deser_resp);
                 }
             }
@@ -88,5 +269,5 @@ pub fn resume_scan(filename: &str) -> Configuration {
     }
 
     log::trace!("exit: resume_scan -> {:?}", config);
-    config
+    Ok(config)
 }
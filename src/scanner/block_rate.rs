@@ -0,0 +1,102 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+/// Why scans were paused automatically (--auto-pause)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(super) struct BlockRate {
+    /// number of the window's responses that were blocked, throttled, or errored
+    pub troubled: usize,
+
+    /// number of responses in the window
+    pub window: usize,
+}
+
+/// human readable reason, including the numbers behind the decision
+impl std::fmt::Display for BlockRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of the last {} responses were blocked, throttled, or errored",
+            self.troubled, self.window
+        )
+    }
+}
+
+/// Detection of a high rate of blocked (or errored) responses across all scans (--auto-pause)
+///
+/// unlike the per-scan policies, outcomes from every scan share the same sliding window, since a
+/// WAF that's blocking one directory is likely blocking all of them
+#[derive(Debug, Default)]
+pub(super) struct BlockRateMonitor {
+    /// outcome of each of the most recent responses; true when blocked, throttled, or errored
+    outcomes: Mutex<VecDeque<bool>>,
+}
+
+/// implementation of BlockRateMonitor
+impl BlockRateMonitor {
+    /// record the outcome of a single request; once the last `window` outcomes include more than
+    /// `percent` percent troubled ones, returns the rate and starts over with an empty window
+    pub fn record(&self, troubled: bool, window: usize, percent: usize) -> Option<BlockRate> {
+        let mut outcomes = self.outcomes.lock().ok()?;
+
+        outcomes.push_back(troubled);
+
+        while outcomes.len() > window {
+            outcomes.pop_front();
+        }
+
+        if outcomes.len() < window {
+            return None;
+        }
+
+        let count = outcomes.iter().filter(|troubled| **troubled).count();
+
+        if count * 100 > window * percent {
+            // a resumed scan shouldn't be paused again based on what was seen before the pause
+            outcomes.clear();
+
+            Some(BlockRate {
+                troubled: count,
+                window,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// nothing should be decided before a full window has been observed, and the window should
+    /// slide once it's full
+    fn block_rate_monitor_uses_a_sliding_window() {
+        let monitor = BlockRateMonitor::default();
+
+        for _ in 0..9 {
+            assert!(monitor.record(true, 10, 50).is_none());
+        }
+
+        assert_eq!(
+            monitor.record(true, 10, 50),
+            Some(BlockRate {
+                troubled: 10,
+                window: 10
+            })
+        );
+
+        // window was cleared; 5 of 10 isn't more than 50%, the 6th troubled response is
+        for i in 0..10 {
+            assert!(monitor.record(i % 2 == 1, 10, 50).is_none());
+        }
+
+        assert_eq!(
+            monitor.record(true, 10, 50),
+            Some(BlockRate {
+                troubled: 6,
+                window: 10
+            })
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::{atomic_load, atomic_store};
+
+/// number of requests observed between each adjustment of a scan's concurrency
+const WINDOW_SIZE: usize = 50;
+
+/// concurrency is cut in half when more than this percentage of a window's requests were errors
+const ERROR_RATIO: f64 = 0.05;
+
+/// concurrency is cut in half when a window's 90th percentile latency is this many times that
+/// of the first window
+const SLOWDOWN_FACTOR: f64 = 2.0;
+
+/// concurrency is only increased while a window's 90th percentile latency stays within this many
+/// times that of the first window
+const HEADROOM_FACTOR: f64 = 1.25;
+
+/// observations made since the last adjustment
+#[derive(Debug, Default)]
+struct Window {
+    /// latency of each request, in milliseconds
+    latencies: Vec<f64>,
+
+    /// number of requests that resulted in an error
+    errors: usize,
+
+    /// 90th percentile latency of the very first window, what later windows are compared to
+    baseline: Option<f64>,
+}
+
+/// Per-scan concurrency that starts conservatively and is scaled up or down based on observed
+/// latency and errors (--auto-tune)
+///
+/// additive increase / multiplicative decrease; the scan's requests each hold a permit from the
+/// inner semaphore, and the number of permits is the scan's current concurrency
+#[derive(Debug)]
+pub(super) struct ConcurrencyTuner {
+    /// permits for in-flight requests
+    semaphore: Semaphore,
+
+    /// upper bound on concurrency, i.e. --threads
+    max: usize,
+
+    /// current concurrency
+    current: AtomicUsize,
+
+    /// permits that should be forgotten, instead of released, to reduce concurrency
+    debt: AtomicUsize,
+
+    /// observations made since the last adjustment
+    window: Mutex<Window>,
+}
+
+/// A permit to make a single request; returned to the tuner when dropped, unless concurrency was
+/// lowered in the meantime
+pub(super) struct TunerPermit<'a> {
+    /// tuner from which the permit was acquired
+    tuner: &'a ConcurrencyTuner,
+
+    /// the underlying semaphore permit
+    permit: Option<SemaphorePermit<'a>>,
+}
+
+/// Drop implementation for TunerPermit
+impl Drop for TunerPermit<'_> {
+    /// release the permit, or forget it if concurrency needs to come down
+    fn drop(&mut self) {
+        if let Some(permit) = self.permit.take() {
+            if self.tuner.take_debt() {
+                permit.forget();
+            }
+        }
+    }
+}
+
+/// implementation of ConcurrencyTuner
+impl ConcurrencyTuner {
+    /// create a tuner that never exceeds `threads` concurrent requests, starting at a quarter of
+    /// that
+    pub fn new(threads: usize) -> Self {
+        let max = threads.max(1);
+        let initial = (max / 4).max(1);
+
+        Self {
+            semaphore: Semaphore::new(initial),
+            max,
+            current: AtomicUsize::new(initial),
+            debt: AtomicUsize::new(0),
+            window: Mutex::new(Window::default()),
+        }
+    }
+
+    /// current concurrency
+    pub fn current(&self) -> usize {
+        atomic_load!(self.current)
+    }
+
+    /// wait until another request can be made
+    pub async fn acquire(&self) -> TunerPermit<'_> {
+        TunerPermit {
+            tuner: self,
+            permit: self.semaphore.acquire().await.ok(),
+        }
+    }
+
+    /// pay down one permit's worth of debt, if there is any
+    fn take_debt(&self) -> bool {
+        self.debt
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |debt| {
+                debt.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    /// record the outcome of a single request, adjusting concurrency once a full window of
+    /// requests has been observed
+    pub fn record(&self, latency_ms: f64, is_error: bool) {
+        let (slowdown, errors) = match self.window.lock() {
+            Ok(mut window) => {
+                window.latencies.push(latency_ms);
+
+                if is_error {
+                    window.errors += 1;
+                }
+
+                if window.latencies.len() < WINDOW_SIZE {
+                    return;
+                }
+
+                let p90 = percentile(&mut window.latencies, 0.9);
+                let errors = window.errors;
+
+                window.latencies.clear();
+                window.errors = 0;
+
+                // latency relative to the first window
+                match window.baseline {
+                    Some(baseline) => (p90 / baseline.max(f64::EPSILON), errors),
+                    None => {
+                        window.baseline = Some(p90);
+                        (1.0, errors)
+                    }
+                }
+            }
+            Err(_) => return,
+        };
+
+        let error_ratio = errors as f64 / WINDOW_SIZE as f64;
+
+        if error_ratio > ERROR_RATIO || slowdown > SLOWDOWN_FACTOR {
+            self.scale_down();
+        } else if errors == 0 && slowdown <= HEADROOM_FACTOR {
+            self.scale_up();
+        }
+    }
+
+    /// additive increase
+    fn scale_up(&self) {
+        let current = self.current();
+        let new = (current + (self.max / 10).max(1)).min(self.max);
+
+        if new > current {
+            log::info!("auto-tune: raising concurrency from {} to {}", current, new);
+            atomic_store!(self.current, new);
+            self.semaphore.add_permits(new - current);
+        }
+    }
+
+    /// multiplicative decrease
+    fn scale_down(&self) {
+        let current = self.current();
+        let new = (current / 2).max(1);
+
+        if new < current {
+            log::info!(
+                "auto-tune: lowering concurrency from {} to {}",
+                current,
+                new
+            );
+            atomic_store!(self.current, new);
+            self.debt.fetch_add(current - new, Ordering::SeqCst);
+        }
+    }
+}
+
+/// value at the given percentile (0.0 - 1.0) of the given values
+pub(super) fn percentile(values: &mut [f64], pct: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let index = ((values.len() - 1) as f64 * pct).round() as usize;
+    values[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// helper to record an entire window of identical observations
+    fn record_window(tuner: &ConcurrencyTuner, latency_ms: f64, errors: usize) {
+        for i in 0..WINDOW_SIZE {
+            tuner.record(latency_ms, i < errors);
+        }
+    }
+
+    #[test]
+    /// concurrency should start at a quarter of --threads, and never below 1
+    fn tuner_starts_conservatively() {
+        assert_eq!(ConcurrencyTuner::new(40).current(), 10);
+        assert_eq!(ConcurrencyTuner::new(2).current(), 1);
+    }
+
+    #[test]
+    /// healthy windows should raise concurrency up to --threads, errors and slowdowns should
+    /// lower it
+    fn tuner_scales_up_and_down() {
+        let tuner = ConcurrencyTuner::new(40);
+
+        record_window(&tuner, 100.0, 0); // baseline, healthy
+        assert_eq!(tuner.current(), 14);
+
+        record_window(&tuner, 110.0, 0); // within headroom
+        assert_eq!(tuner.current(), 18);
+
+        record_window(&tuner, 110.0, 10); // too many errors
+        assert_eq!(tuner.current(), 9);
+
+        record_window(&tuner, 300.0, 0); // too slow
+        assert_eq!(tuner.current(), 4);
+
+        record_window(&tuner, 150.0, 0); // neither healthy nor unhealthy
+        assert_eq!(tuner.current(), 4);
+
+        for _ in 0..20 {
+            record_window(&tuner, 100.0, 0);
+        }
+        assert_eq!(tuner.current(), 40);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// permits released while concurrency is being lowered should be forgotten
+    async fn tuner_forgets_permits_when_scaling_down() {
+        let tuner = ConcurrencyTuner::new(16); // 4 permits
+
+        let permits = vec![
+            tuner.acquire().await,
+            tuner.acquire().await,
+            tuner.acquire().await,
+            tuner.acquire().await,
+        ];
+        assert_eq!(tuner.semaphore.available_permits(), 0);
+
+        tuner.scale_down(); // 4 -> 2
+        drop(permits);
+
+        assert_eq!(tuner.semaphore.available_permits(), 2);
+    }
+
+    #[test]
+    /// percentile should pick the value at the nearest rank
+    fn percentile_picks_nearest_rank() {
+        let mut values: Vec<f64> = (1..=10).map(f64::from).rev().collect();
+        assert_eq!(percentile(&mut values, 0.9), 9.0);
+        assert_eq!(percentile(&mut [], 0.9), 0.0);
+    }
+}
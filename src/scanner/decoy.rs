@@ -0,0 +1,154 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    RwLock,
+};
+
+use lazy_static::lazy_static;
+use rand::seq::SliceRandom;
+use regex::Regex;
+
+use crate::response::FeroxResponse;
+
+/// most pages and assets remembered as decoys; once full, newly found ones are ignored
+const MAX_POOL_SIZE: usize = 1000;
+
+lazy_static! {
+    /// static assets (css, scripts, images, fonts) referenced by a page, the sort of thing a
+    /// browser fetches after loading it
+    static ref ASSET_REGEX: Regex = Regex::new(
+        r#"(?i)(?:src|href)\s*=\s*["']([^"'#?\s]+\.(?:css|js|png|jpe?g|gif|svg|ico|webp|woff2?))["']"#
+    )
+    .expect("Could not compile regex");
+
+    /// pages and assets found so far, shared by every scan; decoy requests are drawn from here
+    pub(super) static ref DECOY_POOL: DecoyPool = DecoyPool::default();
+}
+
+/// Urls a browser would plausibly request while looking around the target
+#[derive(Debug, Default)]
+pub(super) struct DecoyPool {
+    /// pages and static assets, in the order they were found
+    urls: RwLock<Vec<String>>,
+}
+
+/// implementation of DecoyPool
+impl DecoyPool {
+    /// remember a reported response, along with the same-host assets it references
+    pub fn remember(&self, response: &FeroxResponse) {
+        if !response.status().is_success() {
+            return;
+        }
+
+        let page = response.url();
+
+        let mut found = vec![page.to_string()];
+
+        for capture in ASSET_REGEX.captures_iter(response.text()) {
+            if let Ok(asset) = page.join(&capture[1]) {
+                if asset.host() == page.host() {
+                    found.push(asset.to_string());
+                }
+            }
+        }
+
+        if let Ok(mut urls) = self.urls.write() {
+            for url in found {
+                if urls.len() >= MAX_POOL_SIZE {
+                    break;
+                }
+
+                if !urls.contains(&url) {
+                    urls.push(url);
+                }
+            }
+        }
+    }
+
+    /// a random remembered url, or `fallback` when nothing has been found yet
+    pub fn pick(&self, fallback: &str) -> String {
+        self.urls
+            .read()
+            .ok()
+            .and_then(|urls| urls.choose(&mut rand::thread_rng()).cloned())
+            .unwrap_or_else(|| fallback.to_string())
+    }
+}
+
+/// Per-scan bookkeeping of when decoy requests are due (--decoy-ratio)
+#[derive(Debug)]
+pub(super) struct Decoys {
+    /// one decoy request is sent for every `ratio` brute force requests
+    ratio: usize,
+
+    /// number of brute force requests seen so far
+    requests: AtomicUsize,
+}
+
+/// implementation of Decoys
+impl Decoys {
+    /// send one decoy request for every `ratio` brute force requests
+    pub fn new(ratio: usize) -> Self {
+        Self {
+            ratio,
+            requests: AtomicUsize::new(0),
+        }
+    }
+
+    /// record a brute force request; returns whether or not a decoy request is due
+    pub fn is_due(&self) -> bool {
+        let requests = self.requests.fetch_add(1, Ordering::Relaxed) + 1;
+        self.ratio > 0 && requests % self.ratio == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    /// a decoy should be due once every `ratio` requests
+    fn decoys_due_every_ratio_requests() {
+        let decoys = Decoys::new(3);
+
+        let due: Vec<bool> = (0..6).map(|_| decoys.is_due()).collect();
+
+        assert_eq!(due, vec![false, false, true, false, false, true]);
+        assert!(!Decoys::new(0).is_due());
+    }
+
+    #[test]
+    /// successful pages and their same-host assets should be remembered, anything else ignored
+    fn decoy_pool_remembers_pages_and_assets() {
+        let pool = DecoyPool::default();
+
+        assert_eq!(pool.pick("http://localhost/"), "http://localhost/");
+
+        let mut missing = FeroxResponse::default();
+        missing.set_url("http://localhost/missing");
+        missing.set_status(StatusCode::NOT_FOUND);
+        pool.remember(&missing);
+
+        assert_eq!(pool.pick("http://localhost/"), "http://localhost/");
+
+        let mut page = FeroxResponse::default();
+        page.set_url("http://localhost/blog/");
+        page.set_status(StatusCode::OK);
+        page.set_text(
+            r#"<link href="css/site.css" rel="stylesheet"><img src='/logo.png'><a href="/about">
+            <script src="http://elsewhere.com/lib.js"></script>"#,
+        );
+        pool.remember(&page);
+        pool.remember(&page);
+
+        assert_eq!(
+            *pool.urls.read().unwrap(),
+            vec![
+                "http://localhost/blog/",
+                "http://localhost/blog/css/site.css",
+                "http://localhost/logo.png"
+            ]
+        );
+        assert!(pool.pick("").starts_with("http://localhost/"));
+    }
+}
@@ -1,26 +1,36 @@
-use std::{ops::Deref, sync::atomic::Ordering, sync::Arc, time::Instant};
+use std::{sync::atomic::Ordering, sync::Arc};
 
 use anyhow::{bail, Result};
 use futures::{stream, StreamExt};
+use indicatif::ProgressBar;
 use lazy_static::lazy_static;
-use tokio::sync::Semaphore;
+use reqwest::{Method, Url};
+use tokio::time;
 
 use crate::{
     event_handlers::{
-        Command::{AddError, AddToF64Field, SubtractFromUsizeField},
+        Command::{AddError, AddToUsizeField, SubtractFromUsizeField},
         Handles,
     },
-    extractor::{ExtractionTarget::RobotsTxt, ExtractorBuilder},
-    heuristics,
-    scan_manager::{FeroxResponses, ScanOrder, ScanStatus, PAUSE_SCAN},
-    statistics::{
-        StatError::Other,
-        StatField::{DirScanTimes, TotalExpected},
+    events::{ScanEvent, EVENTS},
+    extractor::{
+        ExtractionTarget::{DirectoryListing, RobotsTxt},
+        ExtractorBuilder,
     },
-    utils::fmt_err,
+    heuristics, openapi,
+    response::FeroxResponse,
+    scan_manager::{
+        time_spec_to_secs, FeroxResponses, FeroxScan, ScanContext, ScanOrder, ScanStatus,
+        PAUSE_SCAN,
+    },
+    statistics::{StatError::Other, StatField::TotalExpected},
+    url::FeroxUrl,
+    utils::{fmt_err, logged_request},
+    wordlist::{directory_seed, Wordlist},
+    SLEEP_DURATION,
 };
 
-use super::requester::Requester;
+use super::{gate::RateGate, limiter::ScanLimiter, requester::Requester};
 
 lazy_static! {
     /// Vector of FeroxResponse objects
@@ -39,11 +49,14 @@ pub struct FeroxScanner {
     /// found via recursion
     order: ScanOrder,
 
-    /// wordlist that's already been read from disk
-    wordlist: Arc<Vec<String>>,
+    /// wordlist that's already been mapped into memory
+    wordlist: Arc<Wordlist>,
 
     /// limiter that restricts the number of active FeroxScanners  
-    scan_limiter: Arc<Semaphore>,
+    scan_limiter: Arc<ScanLimiter>,
+
+    /// scan to report progress against; when not given, the scan is looked up by `target_url`
+    ferox_scan: Option<Arc<FeroxScan>>,
 }
 
 /// FeroxScanner implementation
@@ -52,8 +65,8 @@ impl FeroxScanner {
     pub fn new(
         target_url: &str,
         order: ScanOrder,
-        wordlist: Arc<Vec<String>>,
-        scan_limiter: Arc<Semaphore>,
+        wordlist: Arc<Wordlist>,
+        scan_limiter: Arc<ScanLimiter>,
         handles: Arc<Handles>,
     ) -> Self {
         Self {
@@ -62,7 +75,157 @@ impl FeroxScanner {
             wordlist,
             scan_limiter,
             target_url: target_url.to_string(),
+            ferox_scan: None,
+        }
+    }
+
+    /// use the given `FeroxScan` instead of the one found by url; needed when the same url is
+    /// scanned more than once (--auto-wordlist)
+    pub fn with_scan(mut self, ferox_scan: Arc<FeroxScan>) -> Self {
+        self.ferox_scan = Some(ferox_scan);
+        self
+    }
+
+    /// Request /favicon.ico from the target's web root and report it along with its hash, for
+    /// correlation with Shodan's http.favicon.hash (--favicon)
+    ///
+    /// the request waits on the scan's rate limit, and the icon is only reported if it makes it
+    /// past the filters, the same as any other response
+    async fn fetch_favicon(&self, gate: &RateGate) {
+        log::trace!("enter: fetch_favicon");
+
+        let url = match Url::parse(&self.target_url).and_then(|url| url.join("/favicon.ico")) {
+            Ok(url) => url,
+            Err(e) => {
+                log::warn!(
+                    "Could not build favicon url from {}: {}",
+                    self.target_url,
+                    e
+                );
+                return;
+            }
+        };
+
+        gate.wait().await;
+
+        if let Ok(response) = logged_request(&url, self.handles.clone()).await {
+            let ferox_response =
+                FeroxResponse::from(response, true, self.handles.config.output_level).await;
+
+            let filtered = self
+                .handles
+                .filters
+                .data
+                .should_filter_response(&ferox_response, self.handles.stats.tx.clone());
+
+            // the output handler still checks the status code against --status-codes
+            if !filtered && ferox_response.favicon_hash().is_some() {
+                if let Err(e) = ferox_response.send_report(self.handles.output.tx.clone()) {
+                    log::warn!("Could not send FeroxResponse to output handler: {}", e);
+                }
+            }
         }
+
+        log::trace!("exit: fetch_favicon");
+    }
+
+    /// Request the target directory itself and, when it's a directory listing, request the listed
+    /// entries instead (--parse-listings)
+    ///
+    /// the directory itself isn't reported, it already was when it was found (and recursed into)
+    ///
+    /// returns whether or not the directory was a listing
+    async fn parse_listing(&self) -> bool {
+        log::trace!("enter: parse_listing");
+
+        // directories found through a redirect are scanned without their trailing slash, which
+        // would only redirect again
+        let directory = if self.target_url.ends_with('/') {
+            self.target_url.clone()
+        } else {
+            format!("{}/", self.target_url)
+        };
+
+        let url = match FeroxUrl::from_string(&directory, self.handles.clone()).format("", None) {
+            Ok(url) => url,
+            Err(e) => {
+                log::warn!("Could not build url from {}: {}", self.target_url, e);
+                return false;
+            }
+        };
+
+        let response = match logged_request(&url, self.handles.clone()).await {
+            Ok(response) => response,
+            Err(e) => {
+                log::warn!("Could not request {}: {}", url, e);
+                return false;
+            }
+        };
+
+        let ferox_response =
+            FeroxResponse::from(response, true, self.handles.config.output_level).await;
+
+        if !ferox_response.listing() {
+            log::trace!("exit: parse_listing -> false");
+            return false;
+        }
+
+        match ExtractorBuilder::default()
+            .response(&ferox_response)
+            .handles(self.handles.clone())
+            .target(DirectoryListing)
+            .build()
+        {
+            Ok(extractor) => {
+                if let Err(e) = extractor.extract().await {
+                    log::warn!("Could not parse directory listing {}: {}", url, e);
+                }
+            }
+            Err(e) => log::warn!("Could not build extractor for {}: {}", url, e),
+        }
+
+        log::trace!("exit: parse_listing -> true");
+        true
+    }
+
+    /// Order in which the wordlist's words are requested when using --shuffle
+    ///
+    /// each directory gets its own order, derived from --seed and the directory's url, so a
+    /// resumed scan gets the same order
+    fn word_order(&self) -> Option<Vec<usize>> {
+        if !self.handles.config.shuffle {
+            return None;
+        }
+
+        let seed = directory_seed(self.handles.config.seed, &self.target_url);
+
+        Some(self.wordlist.shuffled(seed))
+    }
+
+    /// Request the given routes (--api), found in an OpenAPI/Swagger spec, the same way the
+    /// wordlist's words are requested
+    async fn request_routes(
+        &self,
+        routes: Vec<(Method, Url)>,
+        requester: &Arc<Requester>,
+        progress_bar: &ProgressBar,
+    ) -> Result<()> {
+        progress_bar.set_length(progress_bar.length() + routes.len() as u64);
+        self.handles
+            .stats
+            .send(AddToUsizeField(TotalExpected, routes.len()))?;
+
+        stream::iter(routes)
+            .for_each_concurrent(self.handles.config.threads, |(method, url)| async move {
+                requester
+                    .request_url(url, method)
+                    .await
+                    .unwrap_or_else(|e| log::warn!("Requester encountered an error: {}", e));
+                progress_bar.inc(1);
+            })
+            .await;
+
+        Ok(())
     }
 
     /// Scan a given url using a given wordlist
@@ -72,9 +235,11 @@ impl FeroxScanner {
         log::trace!("enter: scan_url");
         log::info!("Starting scan against: {}", self.target_url);
 
-        let scan_timer = Instant::now();
+        // --dry-run; none of the supporting requests below are sent either
+        let dry_run = self.handles.config.dry_run;
 
-        if matches!(self.order, ScanOrder::Initial) && self.handles.config.extract_links {
+        if matches!(self.order, ScanOrder::Initial) && self.handles.config.extract_links && !dry_run
+        {
             // only grab robots.txt on the initial scan_url calls. all fresh dirs will be passed
             // to try_recursion
             let extractor = ExtractorBuilder::default()
@@ -88,11 +253,13 @@ impl FeroxScanner {
 
         let scanned_urls = self.handles.ferox_scans()?;
 
-        let ferox_scan = match scanned_urls.get_scan_by_url(&self.target_url) {
-            Some(scan) => {
-                scan.set_status(ScanStatus::Running)?;
-                scan
-            }
+        let known_scan = match &self.ferox_scan {
+            Some(scan) => Some(scan.clone()),
+            None => scanned_urls.get_scan_by_url(&self.target_url),
+        };
+
+        let ferox_scan = match known_scan {
+            Some(scan) => scan,
             None => {
                 let msg = format!(
                     "Could not find FeroxScan associated with {}; this shouldn't happen... exiting",
@@ -104,34 +271,118 @@ impl FeroxScanner {
 
         let progress_bar = ferox_scan.progress_bar();
 
-        // When acquire is called and the semaphore has remaining permits, the function immediately
+        // When acquire is called and the limiter has remaining permits, the function immediately
         // returns a permit. However, if no remaining permits are available, acquire (asynchronously)
         // waits until an outstanding permit is dropped, at which point, the freed permit is assigned
-        // to the caller.
-        let _permit = self.scan_limiter.acquire().await;
+        // to the highest ranked waiting scan (--strategy).
+        let _permit = self.scan_limiter.acquire(&self.target_url).await;
+
+        // the scan stays NotStarted while waiting, which is how queued scans are told apart from
+        // those in progress when saving state
+        ferox_scan.set_status(ScanStatus::Running)?;
+        EVENTS.publish(ScanEvent::ScanStarted(&ferox_scan));
 
         // Arc clones to be passed around to the various scans
         let looping_words = self.wordlist.clone();
 
-        {
+        if self.handles.config.parse_listings && !dry_run && self.parse_listing().await {
+            // the directory listed its own contents, there's nothing left to brute force
+            let num_skipped = progress_bar
+                .length()
+                .saturating_sub(progress_bar.position()) as usize;
+
+            self.handles
+                .stats
+                .send(SubtractFromUsizeField(TotalExpected, num_skipped))?;
+
+            ferox_scan.finish()?;
+
+            log::trace!("exit: scan_url");
+            return Ok(());
+        }
+
+        if !dry_run {
             let test = heuristics::HeuristicTests::new(self.handles.clone());
-            if let Ok(num_reqs) = test.wildcard(&self.target_url).await {
+            if let Ok(num_reqs) = test.wildcard(&ferox_scan).await {
                 progress_bar.inc(num_reqs);
             }
         }
 
         let requester = Arc::new(Requester::from(self, ferox_scan.clone())?);
-        let increment_len = (self.handles.config.extensions.len() + 1) as u64;
+
+        if matches!(self.order, ScanOrder::Initial) && self.handles.config.favicon && !dry_run {
+            // only grab the favicon on the initial scan_url calls, it's the same for every dir
+            self.fetch_favicon(&requester.gate()).await;
+        }
+
+        if matches!(self.order, ScanOrder::Initial) && self.handles.config.api && !dry_run {
+            // --api; paths documented by an OpenAPI/Swagger spec are requested before brute
+            // forcing, so that whatever the wordlist finds can be compared against the spec
+            let routes =
+                openapi::discover(&self.target_url, requester.gate(), self.handles.clone()).await;
+            self.request_routes(routes, &requester, &progress_bar)
+                .await?;
+        }
+        let num_auto_extensions = requester.auto_extensions.len();
+        let increment_len = (self.handles.config.extensions.len() + num_auto_extensions + 1) as u64;
+
+        // -w -, --smart-wordlist; streamed words weren't known when the progress bars were created,
+        // so each one's requests are added to the totals as it's taken off the wordlist
+        let fixed_len = looping_words.fixed_len();
+
+        if num_auto_extensions > 0 {
+            // requests for extensions added via --auto-extensions weren't accounted for when the
+            // progress bars were created
+            let additional = fixed_len * num_auto_extensions;
+            progress_bar.set_length(progress_bar.length() + additional as u64);
+            self.handles
+                .stats
+                .send(AddToUsizeField(TotalExpected, additional))?;
+        }
+
+        // spawned tasks don't inherit the scan's context, so each producer is given a copy; this
+        // ties their log entries and responses back to this scan
+        let context = ScanContext::current().unwrap_or_default();
+
+        // --shuffle; positions in the scan are mapped to the word requested at that position
+        let word_order = self.word_order();
 
         // producer tasks (mp of mpsc); responsible for making requests
-        let producers = stream::iter(looping_words.deref().to_owned())
-            .map(|word| {
+        // words are passed by index, each task borrows its word from the shared wordlist
+        let positions = stream::unfold(0, |position| {
+            let words = looping_words.clone();
+            async move {
+                if words.wait_for(position).await {
+                    Some((position, position + 1))
+                } else {
+                    None
+                }
+            }
+        });
+
+        let producers = positions
+            .map(|position| {
+                if position >= fixed_len {
+                    progress_bar.set_length(progress_bar.length() + increment_len);
+                    self.handles
+                        .stats
+                        .send(AddToUsizeField(TotalExpected, increment_len as usize))
+                        .unwrap_or_else(|e| log::warn!("Could not update overall scan bar: {}", e));
+                }
+
+                let index = word_order
+                    .as_ref()
+                    .and_then(|order| order.get(position).copied())
+                    .unwrap_or(position);
+                let context_clone = context.clone();
                 let pb = progress_bar.clone(); // progress bar is an Arc around internal state
+                let words_clone = looping_words.clone();
                 let scanned_urls_clone = scanned_urls.clone();
+                let scan_clone = ferox_scan.clone();
                 let requester_clone = requester.clone();
                 let handles_clone = self.handles.clone();
                 (
-                    tokio::spawn(async move {
+                    tokio::spawn(context_clone.scope(async move {
                         if PAUSE_SCAN.load(Ordering::Acquire) {
                             // for every word in the wordlist, check to see if PAUSE_SCAN is set to true
                             // when true; enter a busy loop that only exits by setting PAUSE_SCAN back
@@ -146,11 +397,16 @@ impl FeroxScanner {
                                     });
                             }
                         }
-                        requester_clone
-                            .request(&word)
-                            .await
-                            .unwrap_or_else(|e| log::warn!("Requester encountered an error: {}", e))
-                    }),
+
+                        // only this scan may have been paused, while others continue
+                        scan_clone.wait_while_paused().await;
+
+                        if let Some(word) = words_clone.get(index) {
+                            requester_clone.request(&word).await.unwrap_or_else(|e| {
+                                log::warn!("Requester encountered an error: {}", e)
+                            })
+                        }
+                    })),
                     pb,
                 )
             })
@@ -168,18 +424,112 @@ impl FeroxScanner {
 
         // await tx tasks
         log::trace!("awaiting scan producers");
-        producers.await;
+        // --scan-time-limit; the clock starts once the scan is running, not while it's queued
+        let timed_out = match time_spec_to_secs(&self.handles.config.scan_time_limit) {
+            Some(secs) => run_for(producers, time::Duration::from_secs(secs), &ferox_scan).await,
+            None => {
+                producers.await;
+                false
+            }
+        };
         log::trace!("done awaiting scan producers");
 
-        self.handles.stats.send(AddToF64Field(
-            DirScanTimes,
-            scan_timer.elapsed().as_secs_f64(),
-        ))?;
+        if timed_out {
+            // no more requests are made for this directory; requests already in flight finish
+            // on their own
+            log::warn!(
+                "{} ran longer than --scan-time-limit ({}), stopping it",
+                self.target_url,
+                self.handles.config.scan_time_limit
+            );
 
-        ferox_scan.finish()?;
+            let num_skipped = progress_bar
+                .length()
+                .saturating_sub(progress_bar.position()) as usize;
+
+            self.handles
+                .stats
+                .send(SubtractFromUsizeField(TotalExpected, num_skipped))?;
+        }
+
+        // requests made on behalf of reported responses are part of the scan
+        requester.finish_follow_ups().await;
+
+        if timed_out {
+            // left incomplete in the state file, so a resumed scan picks it back up
+            ferox_scan.time_out()?;
+        } else {
+            ferox_scan.finish()?;
+        }
 
         log::trace!("exit: scan_url");
 
         Ok(())
     }
 }
+
+/// Drive the given scan producers until they're done, or until the scan has run for longer than
+/// `limit`; returns whether or not the scan ran out of time
+///
+/// time spent paused (ENTER, p, or pausing only this scan) doesn't count against the limit
+async fn run_for<F>(producers: F, limit: time::Duration, scan: &FeroxScan) -> bool
+where
+    F: std::future::Future<Output = ()>,
+{
+    tokio::pin!(producers);
+
+    let mut ticks = time::interval(time::Duration::from_millis(SLEEP_DURATION));
+    let mut last_tick = time::Instant::now();
+    let mut elapsed = time::Duration::default();
+
+    loop {
+        tokio::select! {
+            _ = &mut producers => return false,
+            _ = ticks.tick() => {
+                let now = time::Instant::now();
+
+                if !PAUSE_SCAN.load(Ordering::Acquire) && !scan.is_paused() {
+                    elapsed += now - last_tick;
+                }
+
+                last_tick = now;
+
+                if elapsed >= limit {
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// a paused scan's clock should stand still, and only run out once the scan is resumed
+    async fn run_for_ignores_time_spent_paused() {
+        let scan = Arc::new(FeroxScan::default());
+        scan.set_paused(true);
+
+        let limit = time::Duration::from_millis(SLEEP_DURATION);
+        let forever = futures::future::pending::<()>();
+
+        let unpause = {
+            let scan = scan.clone();
+            tokio::spawn(async move {
+                time::sleep(limit * 4).await;
+                scan.set_paused(false);
+            })
+        };
+
+        let start = Instant::now();
+        assert!(run_for(forever, limit, &scan).await);
+        assert!(start.elapsed() >= limit * 5);
+        unpause.await.unwrap();
+
+        // finishing before the limit isn't a time out
+        assert!(!run_for(async {}, limit, &scan).await);
+    }
+}
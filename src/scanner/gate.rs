@@ -0,0 +1,114 @@
+use std::{fmt, sync::Arc};
+
+use anyhow::Result;
+use leaky_bucket::LeakyBucket;
+use tokio::sync::RwLock;
+
+use super::requester::Requester;
+
+/// A scan's rate limit, as seen by requests made on the scan's behalf that aren't built from its
+/// wordlist (favicon, bypass attempts, disclosure follow-ups, etc...)
+///
+/// shares its bucket with the scan's `Requester`, so changes made by --auto-tune (or a limit set
+/// while scanning) apply to both
+#[derive(Clone, Default)]
+pub struct RateGate {
+    /// the scan's rate limiter; None while the scan isn't rate limited
+    bucket: Arc<RwLock<Option<LeakyBucket>>>,
+}
+
+/// implementation of RateGate
+impl RateGate {
+    /// wrap the given rate limiter
+    pub(super) fn new(bucket: Arc<RwLock<Option<LeakyBucket>>>) -> Self {
+        Self { bucket }
+    }
+
+    /// gate with a bucket of its own, allowing `limit` requests per second (--rate-limit); for
+    /// requests that aren't made on behalf of any one scan (i.e. --smart-wordlist's spider). a
+    /// limit of 0 doesn't limit anything
+    pub fn with_limit(limit: usize) -> Result<Self> {
+        let bucket = match limit {
+            0 => None,
+            limit => Some(Requester::build_a_bucket(limit)?),
+        };
+
+        Ok(Self::new(Arc::new(RwLock::new(bucket))))
+    }
+
+    /// wait until the scan's rate limit allows another request; returns immediately when the
+    /// scan isn't rate limited
+    pub async fn wait(&self) {
+        if let Some(bucket) = self.bucket.read().await.as_ref() {
+            if let Err(e) = bucket.acquire_one().await {
+                log::warn!("Could not rate limit request: {}", e);
+            }
+        }
+    }
+}
+
+/// the bucket itself isn't Debug
+impl fmt::Debug for RateGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RateGate")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{Duration, Instant};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// a gate without a bucket shouldn't hold anything up, one with a bucket should wait on it
+    async fn rate_gate_waits_on_its_bucket() {
+        let open = RateGate::default();
+        let start = Instant::now();
+
+        for _ in 0..10 {
+            open.wait().await;
+        }
+
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        let bucket = LeakyBucket::builder()
+            .refill_interval(Duration::from_millis(100))
+            .refill_amount(1)
+            .tokens(1)
+            .max(1)
+            .build()
+            .unwrap();
+
+        let limited = RateGate::new(Arc::new(RwLock::new(Some(bucket))));
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limited.wait().await;
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// a gate built from a limit of 0 shouldn't limit anything, one built from a limit should
+    async fn rate_gate_with_limit() {
+        let open = RateGate::with_limit(0).unwrap();
+        let start = Instant::now();
+
+        for _ in 0..10 {
+            open.wait().await;
+        }
+
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        // 1 token up front, then 1 per second
+        let limited = RateGate::with_limit(1).unwrap();
+        let start = Instant::now();
+
+        for _ in 0..2 {
+            limited.wait().await;
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(900));
+    }
+}
@@ -0,0 +1,418 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap},
+    fs::read_to_string,
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use reqwest::Url;
+use tokio::sync::oneshot;
+
+/// keyword weights used by the priority strategy when no --priority-keywords file is given
+pub const DEFAULT_KEYWORD_WEIGHTS: [(&str, i64); 10] = [
+    ("admin", 10),
+    ("api", 8),
+    ("backup", 7),
+    ("config", 6),
+    ("internal", 5),
+    ("private", 5),
+    ("dev", 4),
+    ("upload", 3),
+    ("test", 3),
+    ("old", 2),
+];
+
+/// Order in which scans waiting on --scan-limit are started (--strategy)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ScanStrategy {
+    /// shallowest directories first
+    BreadthFirst,
+
+    /// deepest directories first
+    DepthFirst,
+
+    /// highest scoring directories first, based on keyword weights
+    Priority,
+}
+
+/// implementation of ScanStrategy
+impl ScanStrategy {
+    /// convert the value of --strategy into a ScanStrategy; anything unknown is breadth-first
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "dfs" => ScanStrategy::DepthFirst,
+            "priority" => ScanStrategy::Priority,
+            _ => ScanStrategy::BreadthFirst,
+        }
+    }
+}
+
+/// Read the keyword -> weight mapping passed in via --priority-keywords
+///
+/// the file is expected to be toml, where each key is a keyword and each value is its weight,
+/// i.e. `admin = 10`
+pub fn read_priority_keywords(path: &str) -> Result<HashMap<String, i64>> {
+    log::trace!("enter: read_priority_keywords({})", path);
+
+    let contents = read_to_string(path).with_context(|| format!("Could not open {}", path))?;
+    let weights: HashMap<String, i64> =
+        toml::from_str(&contents).with_context(|| format!("Could not parse {}", path))?;
+
+    log::trace!("exit: read_priority_keywords -> {:?}", weights);
+    Ok(weights)
+}
+
+/// A scan waiting for a permit
+#[derive(Debug)]
+struct Waiter {
+    /// higher ranks are started first; ties go to the scan that started waiting first
+    rank: (i64, Reverse<u64>),
+
+    /// notified once the scan is given a permit
+    tx: oneshot::Sender<()>,
+}
+
+/// Ord implementation for Waiter, only the rank is compared
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank.cmp(&other.rank)
+    }
+}
+
+/// PartialOrd implementation for Waiter
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// PartialEq implementation for Waiter
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.rank == other.rank
+    }
+}
+
+/// Eq implementation for Waiter
+impl Eq for Waiter {}
+
+/// mutable state of a ScanLimiter
+#[derive(Debug, Default)]
+struct LimiterState {
+    /// number of scans that can be started without waiting
+    available: usize,
+
+    /// whether or not the limiter was closed, no permits are handed out once closed
+    closed: bool,
+
+    /// number of scans that have waited so far, used to break ties
+    next_seq: u64,
+
+    /// scans waiting for a permit
+    waiting: BinaryHeap<Waiter>,
+}
+
+/// Limits the number of concurrent scans (--scan-limit), starting waiting scans in the order
+/// determined by --strategy
+#[derive(Debug)]
+pub struct ScanLimiter {
+    /// order in which waiting scans are started
+    strategy: ScanStrategy,
+
+    /// keyword -> weight, used by the priority strategy
+    weights: HashMap<String, i64>,
+
+    /// available permits and waiting scans
+    state: Mutex<LimiterState>,
+}
+
+/// Permission for a single scan to run, returned to the limiter when dropped
+pub struct ScanPermit<'a> {
+    /// limiter from which the permit was acquired
+    limiter: &'a ScanLimiter,
+}
+
+/// Drop implementation for ScanPermit
+impl Drop for ScanPermit<'_> {
+    /// hand the permit to the next waiting scan
+    fn drop(&mut self) {
+        self.limiter.release();
+    }
+}
+
+/// A scan's place in line; if the scan is cancelled after being given a permit, but before
+/// noticing, the permit is passed along instead of being lost
+struct PendingPermit<'a> {
+    /// limiter from which the permit was requested
+    limiter: &'a ScanLimiter,
+
+    /// notified once the scan is given a permit
+    rx: Option<oneshot::Receiver<()>>,
+}
+
+/// implementation of PendingPermit
+impl<'a> PendingPermit<'a> {
+    /// wait for the permit; None if the limiter was closed
+    async fn wait(&mut self) -> Option<ScanPermit<'a>> {
+        let result = self.rx.as_mut()?.await;
+        self.rx = None;
+
+        result.ok().map(|_| ScanPermit {
+            limiter: self.limiter,
+        })
+    }
+}
+
+/// Drop implementation for PendingPermit
+impl Drop for PendingPermit<'_> {
+    fn drop(&mut self) {
+        if let Some(mut rx) = self.rx.take() {
+            if rx.try_recv().is_ok() {
+                self.limiter.release();
+            }
+        }
+    }
+}
+
+/// implementation of ScanLimiter
+impl ScanLimiter {
+    /// create a limiter that allows `limit` concurrent scans; 0 means no limit
+    pub fn new(limit: usize, strategy: ScanStrategy, weights: HashMap<String, i64>) -> Self {
+        let available = if limit == 0 { usize::MAX } else { limit };
+
+        Self {
+            strategy,
+            weights,
+            state: Mutex::new(LimiterState {
+                available,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// number of non-empty path segments in the url
+    fn depth(url: &str) -> i64 {
+        Url::parse(url)
+            .ok()
+            .and_then(|parsed| {
+                parsed
+                    .path_segments()
+                    .map(|segments| segments.filter(|s| !s.is_empty()).count() as i64)
+            })
+            .unwrap_or(0)
+    }
+
+    /// sum of the weights of every keyword found in the url's path
+    pub fn score(&self, url: &str) -> i64 {
+        let path = Url::parse(url)
+            .map(|parsed| parsed.path().to_lowercase())
+            .unwrap_or_default();
+
+        self.weights
+            .iter()
+            .filter(|(keyword, _)| path.contains(&keyword.to_lowercase()))
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+
+    /// rank of the given url under the limiter's strategy, higher ranks are started first
+    fn rank(&self, url: &str) -> i64 {
+        match self.strategy {
+            ScanStrategy::BreadthFirst => -Self::depth(url),
+            ScanStrategy::DepthFirst => Self::depth(url),
+            ScanStrategy::Priority => self.score(url),
+        }
+    }
+
+    /// wait until the scan of the given url is allowed to start; None if the limiter was closed
+    pub async fn acquire(&self, url: &str) -> Option<ScanPermit<'_>> {
+        let rx = {
+            let mut state = self.state.lock().ok()?;
+
+            if state.closed {
+                return None;
+            }
+
+            if state.available > 0 && state.waiting.is_empty() {
+                state.available -= 1;
+                return Some(ScanPermit { limiter: self });
+            }
+
+            let (tx, rx) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+
+            state.waiting.push(Waiter {
+                rank: (self.rank(url), Reverse(seq)),
+                tx,
+            });
+
+            rx
+        };
+
+        PendingPermit {
+            limiter: self,
+            rx: Some(rx),
+        }
+        .wait()
+        .await
+    }
+
+    /// hand a permit to the highest ranked waiting scan, or make it available if none are waiting
+    fn release(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            while let Some(waiter) = state.waiting.pop() {
+                if waiter.tx.send(()).is_ok() {
+                    return;
+                }
+                // the waiting scan was cancelled, try the next one
+            }
+
+            state.available = state.available.saturating_add(1);
+        }
+    }
+
+    /// stop handing out permits, any waiting scans are given None
+    pub fn close(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.closed = true;
+            state.waiting.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// helper to fill a limiter (with a limit of 1) with waiting scans, then record the order in
+    /// which they're started
+    async fn start_order(strategy: ScanStrategy, urls: &[&'static str]) -> Vec<&'static str> {
+        let weights = DEFAULT_KEYWORD_WEIGHTS
+            .iter()
+            .map(|(keyword, weight)| (keyword.to_string(), *weight))
+            .collect();
+
+        let limiter = Arc::new(ScanLimiter::new(1, strategy, weights));
+        let started = Arc::new(Mutex::new(Vec::new()));
+
+        let first = limiter.acquire("http://localhost/").await;
+
+        let mut tasks = Vec::new();
+
+        for &url in urls {
+            let limiter = limiter.clone();
+            let started = started.clone();
+
+            tasks.push(tokio::spawn(async move {
+                let _permit = limiter.acquire(url).await;
+                started.lock().unwrap().push(url);
+            }));
+
+            // let the task start waiting before spawning the next
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        }
+
+        drop(first);
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let order = started.lock().unwrap().clone();
+        order
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// breadth-first should start the shallowest directories first
+    async fn limiter_breadth_first() {
+        let order = start_order(
+            ScanStrategy::BreadthFirst,
+            &[
+                "http://localhost/a/b/",
+                "http://localhost/c/",
+                "http://localhost/d/",
+            ],
+        )
+        .await;
+
+        assert_eq!(
+            order,
+            vec![
+                "http://localhost/c/",
+                "http://localhost/d/",
+                "http://localhost/a/b/"
+            ]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// depth-first should start the deepest directories first
+    async fn limiter_depth_first() {
+        let order = start_order(
+            ScanStrategy::DepthFirst,
+            &[
+                "http://localhost/c/",
+                "http://localhost/a/b/",
+                "http://localhost/d/",
+            ],
+        )
+        .await;
+
+        assert_eq!(
+            order,
+            vec![
+                "http://localhost/a/b/",
+                "http://localhost/c/",
+                "http://localhost/d/"
+            ]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// priority should start the highest scoring directories first
+    async fn limiter_priority() {
+        let order = start_order(
+            ScanStrategy::Priority,
+            &[
+                "http://localhost/css/",
+                "http://localhost/api/",
+                "http://localhost/Admin/",
+            ],
+        )
+        .await;
+
+        assert_eq!(
+            order,
+            vec![
+                "http://localhost/Admin/",
+                "http://localhost/api/",
+                "http://localhost/css/"
+            ]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// once closed, no more permits should be handed out
+    async fn limiter_close_stops_permits() {
+        let limiter = ScanLimiter::new(0, ScanStrategy::BreadthFirst, HashMap::new());
+        assert!(limiter.acquire("http://localhost/").await.is_some());
+
+        limiter.close();
+        assert!(limiter.acquire("http://localhost/").await.is_none());
+    }
+
+    #[test]
+    /// read_priority_keywords should parse a keyword -> weight mapping from toml
+    fn read_priority_keywords_parses_mapping() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), "admin = 10\n\"wp-admin\" = 20\n").unwrap();
+
+        let weights = read_priority_keywords(tmp.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(weights["wp-admin"], 20);
+        assert_eq!(weights.len(), 2);
+    }
+}
@@ -1,12 +1,23 @@
+mod block_rate;
+mod concurrency;
+mod decoy;
 mod ferox_scanner;
+mod gate;
 mod utils;
 mod init;
+mod limiter;
 #[cfg(test)]
 mod tests;
 mod limit_heap;
 mod policy_data;
 mod requester;
+mod tarpit;
 
 pub use self::ferox_scanner::{FeroxScanner, RESPONSES};
+pub use self::gate::RateGate;
 pub use self::init::initialize;
+pub use self::limiter::{
+    read_priority_keywords, ScanLimiter, ScanStrategy, DEFAULT_KEYWORD_WEIGHTS,
+};
+pub use self::requester::{current_rate_limit, set_rate_limit};
 pub use self::utils::PolicyTrigger;
@@ -1,32 +1,125 @@
 use std::{
     cmp::max,
-    sync::{atomic::Ordering, Arc, Mutex},
+    future::Future,
+    mem,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use anyhow::Result;
+use chrono::Utc;
+use lazy_static::lazy_static;
 use leaky_bucket::LeakyBucket;
+use rand::Rng;
+use reqwest::{Method, StatusCode, Url};
 use tokio::{
     sync::{oneshot, RwLock},
-    time::{sleep, Duration},
+    task::JoinHandle,
+    time::{sleep, Duration, Instant},
 };
 
 use crate::{
-    atomic_load, atomic_store,
+    atomic_load, atomic_store, bypass, cloud_storage,
     config::RequesterPolicy,
+    cors,
+    disclosure::{self, Disclosure},
     event_handlers::{
-        Command::{self, AddError, SubtractFromUsizeField},
+        Command::{self, AddError, AddToUsizeField, SubtractFromUsizeField},
         Handles,
     },
     extractor::{ExtractionTarget::ResponseBody, ExtractorBuilder},
+    fingerprint::{auto_extensions, fingerprint},
+    graphql, har,
+    parser::DELAY_REGEX,
     response::FeroxResponse,
-    scan_manager::{FeroxScan, ScanStatus},
-    statistics::{StatError::Other, StatField::TotalExpected},
+    scan_manager::{FeroxScan, ScanContext, ScanStatus},
+    statistics::{
+        StatError::Other,
+        StatField::{Blocked, TotalExpected},
+    },
     url::FeroxUrl,
-    utils::logged_request,
-    HIGH_ERROR_RATIO,
+    utils::{logged_request, method_request},
+    verbs, waf,
+    wordlist::UrlEncodeLevel,
+    wsdl, HIGH_ERROR_RATIO,
+};
+
+use super::{
+    block_rate::BlockRateMonitor,
+    concurrency::ConcurrencyTuner,
+    decoy::{Decoys, DECOY_POOL},
+    gate::RateGate,
+    policy_data::PolicyData,
+    tarpit::{Tarpit, TarpitDetector},
+    FeroxScanner, PolicyTrigger,
 };
 
-use super::{policy_data::PolicyData, FeroxScanner, PolicyTrigger};
+lazy_static! {
+    /// outcomes of the most recent responses across all scans (--auto-pause)
+    static ref BLOCK_RATE: BlockRateMonitor = BlockRateMonitor::default();
+}
+
+/// value of `RATE_LIMIT_OVERRIDE` when the limit hasn't been changed while scanning
+const NO_OVERRIDE: usize = usize::MAX;
+
+/// requests per second limit set while the scan is running (--api-addr), applies to every scan;
+/// 0 removes any limit
+static RATE_LIMIT_OVERRIDE: AtomicUsize = AtomicUsize::new(NO_OVERRIDE);
+
+/// change the requests per second limit of all running scans, 0 removes the limit
+pub fn set_rate_limit(limit: usize) {
+    RATE_LIMIT_OVERRIDE.store(limit, Ordering::SeqCst);
+}
+
+/// requests per second limit currently in place; the one set while scanning if there is one,
+/// otherwise the given --rate-limit (0 means no limit)
+pub fn current_rate_limit(configured: usize) -> usize {
+    match RATE_LIMIT_OVERRIDE.load(Ordering::SeqCst) {
+        NO_OVERRIDE => configured,
+        limit => limit,
+    }
+}
+
+/// Convert a delay (ex: 100ms, 2s, 1m) to a number of milliseconds; None if the delay can't be
+/// parsed or doesn't fit in a u64
+fn delay_to_millis(delay: &str) -> Option<u64> {
+    let captures = DELAY_REGEX.captures(delay)?;
+    let length = captures.name("n")?.as_str().parse::<u64>().ok()?;
+
+    match captures.name("u")?.as_str().to_ascii_lowercase().as_str() {
+        "s" => length.checked_mul(1000),      // seconds
+        "m" => length.checked_mul(60 * 1000), // minutes
+        _ => Some(length),                    // milliseconds
+    }
+}
+
+/// `delay` moved up or down by a random amount of at most `jitter`; never negative
+fn jittered(delay: u64, jitter: u64) -> u64 {
+    rand::thread_rng().gen_range(delay.saturating_sub(jitter)..=delay.saturating_add(jitter))
+}
+
+/// Run the follow-ups that add to the given response (--verb-compare, --cors), then report it
+///
+/// meant to be spawned as one of the scan's follow-ups; every request waits on the given gate
+async fn report_with_follow_ups(
+    mut response: FeroxResponse,
+    gate: RateGate,
+    handles: Arc<Handles>,
+) {
+    if handles.config.verb_compare {
+        verbs::compare(&mut response, &gate, &handles).await;
+    }
+
+    if handles.config.cors {
+        cors::check_response(&mut response, &gate, &handles).await;
+    }
+
+    if let Err(e) = response.send_report(handles.output.tx.clone()) {
+        log::warn!("Could not send FeroxResponse to output handler: {}", e);
+    }
+}
 
 /// Makes multiple requests based on the presence of extensions
 pub(super) struct Requester {
@@ -36,8 +129,8 @@ pub(super) struct Requester {
     /// url that will be scanned
     target_url: String,
 
-    /// limits requests per second if present
-    rate_limiter: RwLock<Option<LeakyBucket>>,
+    /// limits requests per second if present; shared with the scan's `RateGate`
+    rate_limiter: Arc<RwLock<Option<LeakyBucket>>>,
 
     /// data regarding policy and metadata about last enforced trigger etc...
     policy_data: PolicyData,
@@ -51,6 +144,30 @@ pub(super) struct Requester {
     /// seen; this will satisfy the non-mut self constraint (due to us being behind an Arc, and
     /// the need for a counter
     tuning_lock: Mutex<usize>,
+
+    /// extensions added due to detected technologies before this Requester was created
+    /// (--auto-extensions)
+    pub(super) auto_extensions: Vec<String>,
+
+    /// last value of `RATE_LIMIT_OVERRIDE` applied to `rate_limiter`
+    applied_override: AtomicUsize,
+
+    /// scales the number of concurrent requests made by this scan (--auto-tune)
+    concurrency: Option<ConcurrencyTuner>,
+
+    /// decides whether or not this scan's directory is a tarpit (--tarpit-latency)
+    tarpit: Option<TarpitDetector>,
+
+    /// milliseconds to wait before each request (--delay), and the most by which each wait may
+    /// randomly vary (--jitter)
+    delay: Option<(u64, u64)>,
+
+    /// decides when decoy requests are sent amid this scan's requests (--decoy-ratio)
+    decoys: Option<Decoys>,
+
+    /// requests made on behalf of reported responses (--follow-disclosures, --bypass-403,
+    /// etc...); they run alongside the scan and are awaited before it completes
+    follow_ups: Mutex<Vec<JoinHandle<()>>>,
 }
 
 /// Requester implementation
@@ -70,18 +187,120 @@ impl Requester {
             scanner.handles.config.timeout,
         );
 
+        let concurrency = if scanner.handles.config.auto_tune {
+            Some(ConcurrencyTuner::new(scanner.handles.config.threads))
+        } else {
+            None
+        };
+
+        let tarpit = match scanner.handles.config.tarpit_latency {
+            0 => None,
+            threshold => Some(TarpitDetector::new(threshold)),
+        };
+
+        let delay = match (
+            delay_to_millis(&scanner.handles.config.delay),
+            delay_to_millis(&scanner.handles.config.jitter),
+        ) {
+            (None, None) => None,
+            (delay, jitter) => Some((delay.unwrap_or_default(), jitter.unwrap_or_default())),
+        };
+
+        let decoys = match scanner.handles.config.decoy_ratio {
+            0 => None,
+            ratio => Some(Decoys::new(ratio)),
+        };
+
         Ok(Self {
             ferox_scan,
             policy_data,
-            rate_limiter: RwLock::new(rate_limiter),
+            rate_limiter: Arc::new(RwLock::new(rate_limiter)),
             handles: scanner.handles.clone(),
             target_url: scanner.target_url.to_owned(),
             tuning_lock: Mutex::new(0),
+            auto_extensions: auto_extensions(&scanner.handles.config.extensions),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency,
+            tarpit,
+            delay,
+            decoys,
+            follow_ups: Mutex::new(Vec::new()),
         })
     }
 
+    /// run the given follow-up alongside the scan, instead of holding up the word that led to it
+    fn spawn_follow_up<F>(&self, follow_up: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        // the follow-up's log entries and responses are tied back to this scan
+        let context = ScanContext::current().unwrap_or_default();
+        let task = tokio::spawn(context.scope(follow_up));
+
+        if let Ok(mut tasks) = self.follow_ups.lock() {
+            // finished follow-ups don't need to be waited on
+            tasks.retain(|task| !task.is_finished());
+            tasks.push(task);
+        }
+    }
+
+    /// Wait for every follow-up spawned so far to finish
+    pub async fn finish_follow_ups(&self) {
+        let tasks = match self.follow_ups.lock() {
+            Ok(mut tasks) => mem::take(&mut *tasks),
+            Err(_) => return,
+        };
+
+        for task in tasks {
+            if let Err(e) = task.await {
+                log::warn!("follow-up request task failed: {}", e);
+            }
+        }
+    }
+
+    /// --decoy-ratio; when due, request a page or asset that's already been found, the way a
+    /// browser would, so brute forcing blends in with browsing. decoy responses aren't reported
+    async fn send_decoy(&self) {
+        let due = self.decoys.as_ref().map_or(false, |decoys| decoys.is_due());
+
+        if !due {
+            return;
+        }
+
+        if !self.claim_request() {
+            return;
+        }
+
+        let decoy = DECOY_POOL.pick(&self.target_url);
+
+        // decoys count against the scan's rate limit, same as the requests they're mixed in with
+        self.gate().wait().await;
+
+        match Url::parse(&decoy) {
+            Ok(url) => match logged_request(&url, self.handles.clone()).await {
+                Ok(_) => log::debug!("sent decoy request to {}", url),
+                Err(e) => log::debug!("decoy request to {} failed: {}", url, e),
+            },
+            Err(e) => log::warn!("Could not parse decoy url {}: {}", decoy, e),
+        }
+    }
+
+    /// claim one of the requests allowed by --request-limit, before sending it; returns false
+    /// once the limit has been reached, true when there's no limit
+    fn claim_request(&self) -> bool {
+        let limit = self.handles.config.request_limit;
+        limit == 0 || self.handles.stats.data.claim_request(limit)
+    }
+
+    /// wait out --delay, varied by a random amount within --jitter, before sending a request
+    async fn delay(&self) {
+        if let Some((delay, jitter)) = self.delay {
+            sleep(Duration::from_millis(jittered(delay, jitter))).await;
+        }
+    }
+
     /// build a LeakyBucket, given a rate limit (as requests per second)
-    fn build_a_bucket(limit: usize) -> Result<LeakyBucket> {
+    pub(super) fn build_a_bucket(limit: usize) -> Result<LeakyBucket> {
         let refill = max((limit as f64 / 10.0).round() as usize, 1); // minimum of 1 per second
         let tokens = max((limit as f64 / 2.0).round() as usize, 1);
         let interval = if refill == 1 { 1000 } else { 100 }; // 1 second if refill is 1
@@ -108,6 +327,11 @@ impl Requester {
         atomic_store!(self.policy_data.cooling_down, false, Ordering::SeqCst);
     }
 
+    /// the scan's rate limit, for requests made on its behalf outside of the wordlist
+    pub fn gate(&self) -> RateGate {
+        RateGate::new(self.rate_limiter.clone())
+    }
+
     /// limit the number of requests per second
     pub async fn limit(&self) -> Result<()> {
         self.rate_limiter
@@ -120,6 +344,38 @@ impl Requester {
         Ok(())
     }
 
+    /// apply any rate limit set while the scan is running; returns whether or not such a limit is
+    /// currently in place
+    async fn apply_rate_limit_override(&self) -> Result<bool> {
+        let limit = RATE_LIMIT_OVERRIDE.load(Ordering::SeqCst);
+
+        if limit == NO_OVERRIDE {
+            return Ok(false);
+        }
+
+        if self.applied_override.swap(limit, Ordering::SeqCst) != limit {
+            let new_limit = if limit == 0 { None } else { Some(limit) };
+            self.set_rate_limiter(new_limit).await?;
+        }
+
+        Ok(limit > 0)
+    }
+
+    /// enforce --auto-pause; all scans are paused once too many of the most recent responses
+    /// were blocked, throttled, or errored
+    fn check_block_rate(&self, troubled: bool) {
+        let config = &self.handles.config;
+
+        if let Some(rate) = BLOCK_RATE.record(troubled, config.auto_pause_window, config.auto_pause)
+        {
+            log::warn!("pausing all scans: {}", rate);
+
+            if let Ok(scans) = self.handles.ferox_scans() {
+                scans.pause_automatically(&rate.to_string());
+            }
+        }
+    }
+
     /// small function to break out different error checking mechanisms
     fn too_many_errors(&self) -> bool {
         let total = self.ferox_scan.num_errors(PolicyTrigger::Errors);
@@ -289,94 +545,454 @@ impl Requester {
         Ok(())
     }
 
+    /// enforce --tarpit-latency; the rest of a directory that's too slow to be worth scanning
+    /// is dropped
+    async fn abandon(&self, tarpit: Tarpit) -> Result<()> {
+        if !self.ferox_scan.is_active() {
+            // already stopped, possibly by another request that saw the same window
+            return Ok(());
+        }
+
+        log::warn!("abandoning {}: {}", self.ferox_scan, tarpit);
+
+        self.ferox_scan
+            .abandon()
+            .await
+            .unwrap_or_else(|e| log::warn!("Could not abandon scan: {}", e));
+
+        // figure out how many requests are skipped as a result
+        let pb = self.ferox_scan.progress_bar();
+        let num_skipped = pb.length().saturating_sub(pb.position()) as usize;
+
+        self.handles
+            .stats
+            .send(SubtractFromUsizeField(TotalExpected, num_skipped))
+            .unwrap_or_else(|e| log::warn!("Could not update overall scan bar: {}", e));
+
+        let reason = format!("{}; {} requests sent", tarpit, pb.position());
+
+        self.handles.output.send(Command::ReportAbandoned(
+            self.ferox_scan.url().to_string(),
+            reason,
+        ))?;
+
+        Ok(())
+    }
+
+    /// record a response that looked like a WAF/CDN block page, warning the user as the scan's
+    /// number of blocked responses grows
+    fn report_blocked(&self, response: &FeroxResponse, waf: &str) -> Result<()> {
+        log::debug!("{} looks blocked by {}", response.url(), waf);
+
+        self.handles
+            .stats
+            .send(AddToUsizeField(Blocked, 1))
+            .unwrap_or_else(|e| log::warn!("Could not update blocked statistic: {}", e));
+
+        let blocked = self.ferox_scan.add_blocked();
+
+        if waf::should_warn(blocked) {
+            self.handles.output.send(Command::ReportBlocked(
+                response.url().to_string(),
+                waf.to_string(),
+                blocked,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Request the canonical url of a response that redirected to the same url with its trailing
+    /// slash added or removed (--smart-slash); the original response is returned if it wasn't
+    /// such a redirect, or if the canonical url couldn't be requested
+    async fn follow_slash_redirect(&self, response: FeroxResponse) -> FeroxResponse {
+        let canonical = match response.slash_redirect() {
+            Some(url) => url,
+            None => return response,
+        };
+
+        log::debug!(
+            "following slash redirect {} -> {}",
+            response.url(),
+            canonical
+        );
+
+        // the canonical url counts against the scan's rate limit like any other request
+        self.gate().wait().await;
+
+        let started = Utc::now();
+        let timer = Instant::now();
+
+        let followed = match logged_request(&canonical, self.handles.clone()).await {
+            Ok(followed) => followed,
+            Err(e) => {
+                log::warn!("Could not follow slash redirect to {}: {}", canonical, e);
+                return response;
+            }
+        };
+
+        let elapsed_ms = timer.elapsed().as_secs_f64() * 1000.0;
+        let version = followed.version();
+
+        let followed = FeroxResponse::from(followed, true, self.handles.config.output_level).await;
+
+        if !self.handles.config.har.is_empty() {
+            let entry = har::entry(
+                &Method::GET,
+                version,
+                &canonical,
+                &self.handles.config,
+                &followed,
+                started,
+                elapsed_ms,
+            );
+            har::record(&entry);
+        }
+
+        if followed.slash_redirect().as_ref() == Some(response.url()) {
+            // /admin -> /admin/ -> /admin; neither is canonical, so the original response is
+            // kept, and the loop is recorded for recursion to find when it's tried
+            log::debug!(
+                "{} and {} redirect to each other",
+                response.url(),
+                canonical
+            );
+
+            if let Ok(scans) = self.handles.ferox_scans() {
+                if let Some(cycle) =
+                    scans.add_redirect(followed.url().as_str(), response.url().as_str())
+                {
+                    self.handles
+                        .output
+                        .send(Command::ReportRedirectLoop(cycle))
+                        .unwrap_or_default();
+                }
+            }
+
+            return response;
+        }
+
+        followed
+    }
+
     /// Wrapper for make_request
     ///
     /// Attempts recursion when appropriate and sends Responses to the output handler for processing
     pub async fn request(&self, word: &str) -> Result<()> {
         log::trace!("enter: request({})", word);
 
-        let urls =
-            FeroxUrl::from_string(&self.target_url, self.handles.clone()).formatted_urls(word)?;
+        // --url-encode-level; words are encoded before extensions or slashes are added
+        let level = UrlEncodeLevel::from_name(&self.handles.config.url_encode_level);
+        let encoded = level.encode(word);
+        let word = encoded.as_ref();
+
+        let ferox_url = FeroxUrl::from_string(&self.target_url, self.handles.clone());
+        let mut urls = ferox_url.formatted_urls(word)?;
+
+        for ext in self.auto_extensions.iter() {
+            // extensions added via --auto-extensions
+            if let Ok(url) = ferox_url.format(word, Some(ext)) {
+                urls.push(url);
+            }
+        }
 
         for url in urls {
-            // auto_tune is true, or rate_limit was set (mutually exclusive to user)
-            // and a rate_limiter has been created
-            // short-circuiting the lock access behind the first boolean check
-            let should_tune = self.handles.config.auto_tune || self.handles.config.rate_limit > 0;
-            let should_limit = should_tune && self.rate_limiter.read().await.is_some();
-
-            if should_limit {
-                // found a rate limiter, limit that junk!
-                if let Err(e) = self.limit().await {
-                    log::warn!("Could not rate limit scan: {}", e);
-                    self.handles.stats.send(AddError(Other)).unwrap_or_default();
+            self.request_url(url, Method::GET).await?;
+        }
+
+        log::trace!("exit: request");
+        Ok(())
+    }
+
+    /// Request the given url with the given method, then put the response through everything a
+    /// word's response goes through: recursion, filtering, follow-ups, and reporting
+    pub async fn request_url(&self, url: Url, method: Method) -> Result<()> {
+        log::trace!("enter: request_url({}, {})", method, url);
+
+        if self.handles.config.dry_run {
+            // --dry-run; report the url, as any loaded plugins would alter it, instead of
+            // requesting it
+            let url = self.handles.config.loaded_plugins.mutate_request(&url);
+            self.handles
+                .output
+                .send(Command::ReportUrl(url.to_string()))?;
+            return Ok(());
+        }
+
+        if !self.claim_request() {
+            // --request-limit reached; start_request_limit_thread stops the scans
+            log::debug!("request limit reached, not requesting {}", url);
+            return Ok(());
+        }
+
+        // --delay/--jitter; each concurrent request waits on its own, so the wait is per thread
+        self.delay().await;
+
+        self.send_decoy().await;
+
+        // a limit set while scanning (--api-addr) is enforced the same way as --rate-limit
+        let overridden = self.apply_rate_limit_override().await?;
+
+        // auto_tune is true, or rate_limit was set (mutually exclusive to user)
+        // and a rate_limiter has been created
+        // short-circuiting the lock access behind the first boolean check
+        let should_tune =
+            overridden || self.handles.config.auto_tune || self.handles.config.rate_limit > 0;
+        let should_limit = should_tune && self.rate_limiter.read().await.is_some();
+
+        if should_limit {
+            // found a rate limiter, limit that junk!
+            if let Err(e) = self.limit().await {
+                log::warn!("Could not rate limit scan: {}", e);
+                self.handles.stats.send(AddError(Other)).unwrap_or_default();
+            }
+        }
+
+        // give any loaded plugins a chance to alter the request (--plugin)
+        let url = self.handles.config.loaded_plugins.mutate_request(&url);
+
+        // --auto-tune; wait until the scan's current concurrency allows another request, the
+        // permit is held for the remainder of this url's processing
+        let _permit = match &self.concurrency {
+            Some(tuner) => Some(tuner.acquire().await),
+            None => None,
+        };
+
+        let started = Utc::now();
+        let timer = Instant::now();
+
+        let result = if method == Method::GET {
+            logged_request(&url, self.handles.clone()).await
+        } else {
+            // bodiless requests with a method that expects a body still say how long it is
+            let headers = if matches!(method, Method::POST | Method::PUT | Method::PATCH) {
+                vec![("Content-Length", String::from("0"))]
+            } else {
+                Vec::new()
+            };
+
+            method_request(method.clone(), &url, &headers, &self.handles).await
+        };
+
+        let elapsed_ms = timer.elapsed().as_secs_f64() * 1000.0;
+
+        if let Some(tuner) = &self.concurrency {
+            let is_error = match &result {
+                Ok(response) => {
+                    response.status().is_server_error()
+                        || response.status() == StatusCode::TOO_MANY_REQUESTS
                 }
+                Err(_) => true,
+            };
+
+            tuner.record(elapsed_ms, is_error);
+        }
+
+        if let Some(detector) = &self.tarpit {
+            let timed_out = result.as_ref().err().map_or(false, |e| {
+                e.downcast_ref::<reqwest::Error>()
+                    .map_or(false, |e| e.is_timeout())
+            });
+
+            if let Some(tarpit) = detector.record(elapsed_ms, timed_out) {
+                self.abandon(tarpit).await?;
             }
+        }
 
-            let response = logged_request(&url, self.handles.clone()).await?;
-
-            if (should_tune || self.handles.config.auto_bail)
-                && !atomic_load!(self.policy_data.cooling_down, Ordering::SeqCst)
-            {
-                // only check for policy enforcement when the trigger isn't on cooldown and tuning
-                // or bailing is in place (should_tune used here because when auto-tune is on, we'll
-                // reach this without a rate_limiter in place)
-                match self.policy_data.policy {
-                    RequesterPolicy::AutoTune => {
-                        if let Some(trigger) = self.should_enforce_policy() {
-                            self.tune(trigger).await?;
-                        }
+        if self.handles.config.auto_pause > 0 && result.is_err() {
+            self.check_block_rate(true);
+        }
+
+        let response = result?;
+
+        if (should_tune || self.handles.config.auto_bail)
+            && !atomic_load!(self.policy_data.cooling_down, Ordering::SeqCst)
+        {
+            // only check for policy enforcement when the trigger isn't on cooldown and tuning
+            // or bailing is in place (should_tune used here because when auto-tune is on, we'll
+            // reach this without a rate_limiter in place)
+            match self.policy_data.policy {
+                RequesterPolicy::AutoTune => {
+                    if let Some(trigger) = self.should_enforce_policy() {
+                        self.tune(trigger).await?;
                     }
-                    RequesterPolicy::AutoBail => {
-                        if let Some(trigger) = self.should_enforce_policy() {
-                            self.bail(trigger).await?;
-                        }
+                }
+                RequesterPolicy::AutoBail => {
+                    if let Some(trigger) = self.should_enforce_policy() {
+                        self.bail(trigger).await?;
                     }
-                    RequesterPolicy::Default => {}
                 }
+                RequesterPolicy::Default => {}
             }
+        }
+
+        // response came back without error, convert it to FeroxResponse
+        let version = response.version();
+        let ferox_response =
+            FeroxResponse::from(response, true, self.handles.config.output_level).await;
+
+        let blocked_by = waf::detect(&ferox_response);
+
+        if let Some(waf) = blocked_by {
+            // counted whether or not the response is filtered later on
+            self.report_blocked(&ferox_response, waf)?;
+        }
+
+        if self.handles.config.auto_pause > 0 {
+            // rate limited or overloaded responses are as much a sign of trouble as blocks
+            let throttled = matches!(
+                ferox_response.status(),
+                &StatusCode::TOO_MANY_REQUESTS | &StatusCode::SERVICE_UNAVAILABLE
+            );
+
+            self.check_block_rate(blocked_by.is_some() || throttled);
+        }
+
+        if !self.handles.config.har.is_empty() {
+            // --har; every exchange is archived, regardless of whether or not it's filtered
+            let entry = har::entry(
+                &method,
+                version,
+                &url,
+                &self.handles.config,
+                &ferox_response,
+                started,
+                elapsed_ms,
+            );
+            har::record(&entry);
+        }
+
+        // --smart-slash; a redirect that only adds (or removes) a trailing slash is followed,
+        // so that only the canonical url is reported and recursed into
+        let ferox_response = if self.handles.config.smart_slash {
+            self.follow_slash_redirect(ferox_response).await
+        } else {
+            ferox_response
+        };
+
+        // do recursion if appropriate
+        if !self.handles.config.no_recursion {
+            self.handles
+                .send_scan_command(Command::TryRecursion(Box::new(ferox_response.clone())))?;
+            let (tx, rx) = oneshot::channel::<bool>();
+            self.handles.send_scan_command(Command::Sync(tx))?;
+            rx.await?;
+        }
+
+        // purposefully doing recursion before filtering. the thought process is that
+        // even though this particular url is filtered, subsequent urls may not
+        if self
+            .handles
+            .filters
+            .data
+            .should_filter_response(&ferox_response, self.handles.stats.tx.clone())
+        {
+            return Ok(());
+        }
+
+        // this directory's custom 404, per its own calibration
+        if self
+            .handles
+            .ferox_scans()?
+            .is_wildcard(&ferox_response, self.handles.stats.tx.clone())
+        {
+            return Ok(());
+        }
+
+        if self.handles.config.extract_links && !ferox_response.status().is_redirection() {
+            let extractor = ExtractorBuilder::default()
+                .target(ResponseBody)
+                .response(&ferox_response)
+                .handles(self.handles.clone())
+                .build()?;
+
+            extractor.extract().await?;
+        }
 
-            // response came back without error, convert it to FeroxResponse
-            let ferox_response =
-                FeroxResponse::from(response, true, self.handles.config.output_level).await;
-
-            // do recursion if appropriate
-            if !self.handles.config.no_recursion {
-                self.handles
-                    .send_scan_command(Command::TryRecursion(Box::new(ferox_response.clone())))?;
-                let (tx, rx) = oneshot::channel::<bool>();
-                self.handles.send_scan_command(Command::Sync(tx))?;
-                rx.await?;
+        if self.handles.config.cloud_storage {
+            // buckets live on other hosts, so they're checked rather than scanned
+            let buckets = cloud_storage::unchecked_buckets(ferox_response.text());
+
+            if !buckets.is_empty() {
+                self.spawn_follow_up(cloud_storage::check(
+                    ferox_response.url().clone(),
+                    buckets,
+                    self.handles.clone(),
+                ));
             }
+        }
 
-            // purposefully doing recursion before filtering. the thought process is that
-            // even though this particular url is filtered, subsequent urls may not
-            if self
-                .handles
-                .filters
-                .data
-                .should_filter_response(&ferox_response, self.handles.stats.tx.clone())
-            {
-                continue;
+        if !self.handles.config.auto_wordlist.is_empty() {
+            let technologies = fingerprint(&ferox_response);
+
+            if !technologies.is_empty() {
+                // scan the current directory with any technology-specific wordlists
+                self.handles.send_scan_command(Command::TryAutoWordlist(
+                    self.target_url.clone(),
+                    technologies,
+                ))?;
             }
+        }
 
-            if self.handles.config.extract_links && !ferox_response.status().is_redirection() {
-                let extractor = ExtractorBuilder::default()
-                    .target(ResponseBody)
-                    .response(&ferox_response)
-                    .handles(self.handles.clone())
-                    .build()?;
+        // forbidden; the resource is requested again using tricks that get past misconfigured
+        // access controls
+        if bypass::should_attempt(&ferox_response, &self.handles.config) {
+            self.spawn_follow_up(bypass::attempt(
+                ferox_response.url().clone(),
+                *ferox_response.status(),
+                self.gate(),
+                self.handles.clone(),
+            ));
+        }
 
-                extractor.extract().await?;
+        // exposed .git/.svn/.DS_Store; well-known paths beneath it are requested to confirm
+        // the disclosure
+        if self.handles.config.follow_disclosures {
+            if let Some(disclosure) = Disclosure::from_url(ferox_response.url()) {
+                self.spawn_follow_up(disclosure::follow_up(
+                    disclosure,
+                    self.gate(),
+                    self.handles.clone(),
+                ));
             }
+        }
 
+        // GraphQL endpoint; an introspection query is sent, in case it hands out the schema
+        if graphql::should_probe(&ferox_response, &self.handles.config) {
+            self.spawn_follow_up(graphql::probe(
+                ferox_response.url().clone(),
+                self.gate(),
+                self.handles.clone(),
+            ));
+        }
+
+        // SOAP endpoint (or a WSDL document); the operations it describes are reported
+        if self.handles.config.wsdl && wsdl::should_probe(&ferox_response) {
+            self.spawn_follow_up(wsdl::probe(
+                ferox_response.clone(),
+                self.gate(),
+                self.handles.clone(),
+            ));
+        }
+
+        if self.decoys.is_some() {
+            DECOY_POOL.remember(&ferox_response);
+        }
+
+        if self.handles.config.verb_compare || self.handles.config.cors {
+            // allowed methods (HEAD and OPTIONS responses are compared against this one) and the
+            // CORS policy are shown with the response, which is reported once they're in
+            self.spawn_follow_up(report_with_follow_ups(
+                ferox_response,
+                self.gate(),
+                self.handles.clone(),
+            ));
+        } else if let Err(e) = ferox_response.send_report(self.handles.output.tx.clone()) {
             // everything else should be reported
-            if let Err(e) = ferox_response.send_report(self.handles.output.tx.clone()) {
-                log::warn!("Could not send FeroxResponse to output handler: {}", e);
-            }
+            log::warn!("Could not send FeroxResponse to output handler: {}", e);
         }
 
-        log::trace!("exit: request");
+        log::trace!("exit: request_url");
         Ok(())
     }
 }
@@ -537,9 +1153,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: Arc::new(FeroxScan::default()),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: Default::default(),
         };
 
@@ -564,9 +1187,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: ferox_scan.clone(),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: Default::default(),
         };
 
@@ -588,9 +1218,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: ferox_scan.clone(),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: Default::default(),
         };
 
@@ -627,9 +1264,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: ferox_scan.clone(),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: Default::default(),
         };
 
@@ -681,9 +1325,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: req_clone,
             target_url: "http://one/one/stuff.php".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: Default::default(),
         };
 
@@ -714,9 +1365,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: Arc::new(FeroxScan::default()),
             target_url: "http://one/one/stuff.php".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: Default::default(),
         };
 
@@ -735,9 +1393,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: Arc::new(FeroxScan::default()),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: Default::default(),
         };
 
@@ -757,9 +1422,16 @@ mod tests {
         let requester = Arc::new(Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: Arc::new(FeroxScan::default()),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: PolicyData::new(RequesterPolicy::AutoBail, 7),
         });
 
@@ -786,9 +1458,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: Arc::new(FeroxScan::default()),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: PolicyData::new(RequesterPolicy::AutoBail, 7),
         };
 
@@ -823,9 +1502,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: Arc::new(scan),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(Some(limiter)),
+            rate_limiter: Arc::new(RwLock::new(Some(limiter))),
             policy_data: PolicyData::new(RequesterPolicy::AutoBail, 7),
         };
 
@@ -858,9 +1544,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: Arc::new(scan),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: PolicyData::new(RequesterPolicy::AutoBail, 7),
         };
 
@@ -885,9 +1578,16 @@ mod tests {
         let mut requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: Arc::new(FeroxScan::default()),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(None),
+            rate_limiter: Arc::new(RwLock::new(None)),
             policy_data: PolicyData::new(RequesterPolicy::AutoBail, 7),
         };
 
@@ -942,9 +1642,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: Arc::new(FeroxScan::default()),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(Some(limiter)),
+            rate_limiter: Arc::new(RwLock::new(Some(limiter))),
             policy_data: PolicyData::new(RequesterPolicy::AutoBail, 7),
         };
 
@@ -984,9 +1691,16 @@ mod tests {
         let requester = Requester {
             handles,
             tuning_lock: Mutex::new(0),
+            auto_extensions: Vec::new(),
+            applied_override: AtomicUsize::new(NO_OVERRIDE),
+            concurrency: None,
+            tarpit: None,
+            delay: None,
+            decoys: None,
+            follow_ups: Mutex::new(Vec::new()),
             ferox_scan: scan.clone(),
             target_url: "http://localhost".to_string(),
-            rate_limiter: RwLock::new(Some(limiter)),
+            rate_limiter: Arc::new(RwLock::new(Some(limiter))),
             policy_data: PolicyData::new(RequesterPolicy::AutoTune, 4),
         };
 
@@ -1011,4 +1725,25 @@ mod tests {
         scan.finish().unwrap();
         assert!(start.elapsed().as_millis() >= 2000);
     }
+
+    #[test]
+    /// delays should be converted to milliseconds, anything unparseable should be None
+    fn delay_to_millis_converts_units() {
+        assert_eq!(delay_to_millis("100ms"), Some(100));
+        assert_eq!(delay_to_millis("2S"), Some(2000));
+        assert_eq!(delay_to_millis("1m"), Some(60_000));
+        assert_eq!(delay_to_millis(""), None);
+        assert_eq!(delay_to_millis("1h"), None);
+    }
+
+    #[test]
+    /// jittered delays should stay within jitter of the delay, and never go below 0
+    fn jittered_stays_within_bounds() {
+        assert_eq!(jittered(100, 0), 100);
+
+        for _ in 0..500 {
+            assert!((50..=150).contains(&jittered(100, 50)));
+            assert!((0..=60).contains(&jittered(10, 50)));
+        }
+    }
 }
@@ -0,0 +1,158 @@
+use std::sync::Mutex;
+
+use super::concurrency::percentile;
+
+/// number of requests observed before deciding whether or not a directory is a tarpit
+const WINDOW_SIZE: usize = 30;
+
+/// a directory is abandoned when at least this percentage of a window's requests timed out
+const TIMEOUT_RATIO: f64 = 0.5;
+
+/// Why a directory was considered a tarpit
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(super) enum Tarpit {
+    /// median latency of a window's requests, in milliseconds, was over --tarpit-latency
+    Latency(f64),
+
+    /// number of a window's requests that timed out
+    Timeouts(usize),
+}
+
+/// human readable reason, including the numbers behind the decision
+impl std::fmt::Display for Tarpit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tarpit::Latency(median) => write!(
+                f,
+                "median latency of {:.0}ms over the last {} requests",
+                median, WINDOW_SIZE
+            ),
+            Tarpit::Timeouts(timeouts) => write!(
+                f,
+                "{} of the last {} requests timed out",
+                timeouts, WINDOW_SIZE
+            ),
+        }
+    }
+}
+
+/// observations made since the last window was evaluated
+#[derive(Debug, Default)]
+struct Window {
+    /// latency of each request, in milliseconds
+    latencies: Vec<f64>,
+
+    /// number of requests that timed out
+    timeouts: usize,
+}
+
+/// Per-scan detection of directories too slow to be worth scanning (--tarpit-latency)
+///
+/// requests are looked at in windows; a window whose median latency is over the threshold, or
+/// where most requests timed out, marks the directory as a tarpit
+#[derive(Debug)]
+pub(super) struct TarpitDetector {
+    /// median latency, in milliseconds, above which a directory is a tarpit
+    threshold: f64,
+
+    /// observations made since the last window was evaluated
+    window: Mutex<Window>,
+}
+
+/// implementation of TarpitDetector
+impl TarpitDetector {
+    /// create a detector that considers a median latency above `threshold_ms` too slow
+    pub fn new(threshold_ms: u64) -> Self {
+        Self {
+            threshold: threshold_ms as f64,
+            window: Mutex::new(Window::default()),
+        }
+    }
+
+    /// record the outcome of a single request; once a full window of requests has been
+    /// observed, returns why the directory is a tarpit, if it is one
+    pub fn record(&self, latency_ms: f64, timed_out: bool) -> Option<Tarpit> {
+        let mut window = self.window.lock().ok()?;
+
+        window.latencies.push(latency_ms);
+
+        if timed_out {
+            window.timeouts += 1;
+        }
+
+        if window.latencies.len() < WINDOW_SIZE {
+            return None;
+        }
+
+        let median = percentile(&mut window.latencies, 0.5);
+        let timeouts = window.timeouts;
+
+        window.latencies.clear();
+        window.timeouts = 0;
+
+        if timeouts as f64 / WINDOW_SIZE as f64 >= TIMEOUT_RATIO {
+            Some(Tarpit::Timeouts(timeouts))
+        } else if median > self.threshold {
+            Some(Tarpit::Latency(median))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// helper to record an entire window of identical observations, returning the last verdict
+    fn record_window(
+        detector: &TarpitDetector,
+        latency_ms: f64,
+        timeouts: usize,
+    ) -> Option<Tarpit> {
+        (0..WINDOW_SIZE)
+            .map(|i| detector.record(latency_ms, i < timeouts))
+            .last()
+            .flatten()
+    }
+
+    #[test]
+    /// nothing should be decided before a full window has been observed
+    fn detector_waits_for_full_window() {
+        let detector = TarpitDetector::new(1000);
+
+        for _ in 0..WINDOW_SIZE - 1 {
+            assert_eq!(detector.record(5000.0, true), None);
+        }
+    }
+
+    #[test]
+    /// slow and timed out windows should be tarpits, healthy ones shouldn't
+    fn detector_flags_slow_and_timed_out_windows() {
+        let detector = TarpitDetector::new(1000);
+
+        assert_eq!(record_window(&detector, 200.0, 0), None);
+        assert_eq!(record_window(&detector, 200.0, 10), None);
+        assert_eq!(
+            record_window(&detector, 1500.0, 0),
+            Some(Tarpit::Latency(1500.0))
+        );
+        assert_eq!(
+            record_window(&detector, 200.0, 15),
+            Some(Tarpit::Timeouts(15))
+        );
+    }
+
+    #[test]
+    /// reasons should include the numbers behind them
+    fn tarpit_display_includes_stats() {
+        assert_eq!(
+            Tarpit::Latency(1499.6).to_string(),
+            "median latency of 1500ms over the last 30 requests"
+        );
+        assert_eq!(
+            Tarpit::Timeouts(20).to_string(),
+            "20 of the last 30 requests timed out"
+        );
+    }
+}
@@ -1,7 +1,5 @@
 use std::sync::Arc;
 
-use tokio::sync::Semaphore;
-
 use crate::{
     config::OutputLevel,
     event_handlers::Handles,
@@ -14,7 +12,7 @@ use super::*;
 #[should_panic]
 /// try to hit struct field coverage of FileOutHandler
 async fn get_scan_by_url_bails_on_unfound_url() {
-    let sem = Semaphore::new(10);
+    let sem = ScanLimiter::new(10, ScanStrategy::BreadthFirst, Default::default());
     let urls = FeroxScans::new(OutputLevel::Default);
 
     let scanner = FeroxScanner::new(
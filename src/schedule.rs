@@ -0,0 +1,269 @@
+//! recurring scans on a cron schedule (--schedule)
+//!
+//! the scheduling process stays resident and runs each scan as a child process, using the same
+//! command line minus --schedule. Each run writes its results (as json) to a timestamped file and
+//! is compared against the results of the run before it (--compare)
+use std::{env::current_exe, path::Path, process::Command, str::FromStr, thread::sleep};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use cron::Schedule;
+
+use crate::config::Configuration;
+
+/// base name of each run's output file when -o|--output isn't used
+const DEFAULT_OUTPUT_BASE: &str = "ferox-scheduled";
+
+/// short flags that don't take a value, and so may be clustered ahead of -o (i.e. -vvo out.txt)
+const CLUSTERED_FLAGS: &str = "vqDrknfe";
+
+/// Translate a standard cron weekday field (0-7, where both 0 and 7 are sunday) to the one used
+/// by the cron crate (1-7, where 1 is sunday); names (MON, TUE, ...) are left as-is
+fn translate_weekday(field: &str) -> String {
+    let shift = |day: &str| match day.parse::<u8>() {
+        Ok(day) => (day % 7 + 1).to_string(),
+        Err(_) => day.to_string(),
+    };
+
+    field
+        .split(',')
+        .map(|item| {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, format!("/{}", step)),
+                None => (item, String::new()),
+            };
+
+            match range.split_once('-') {
+                // a range ending on sunday (i.e. 5-7) would wrap around; split it in two
+                Some((start, "7")) if start != "0" => format!("{}-7{},1", shift(start), step),
+                Some((start, end)) => format!("{}-{}{}", shift(start), shift(end), step),
+                None => format!("{}{}", shift(range), step),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parse a cron expression; standard five field expressions (minute hour day month weekday) are
+/// accepted, as are those with a leading seconds field
+pub fn parse_schedule(expression: &str) -> Result<Schedule> {
+    let expression = expression.trim();
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+
+    let normalized = if fields.len() == 5 {
+        // standard cron numbers weekdays from 0, the cron crate from 1
+        format!(
+            "0 {} {}",
+            fields[..4].join(" "),
+            translate_weekday(fields[4])
+        )
+    } else {
+        expression.to_string()
+    };
+
+    Schedule::from_str(&normalized)
+        .map_err(|e| anyhow!("Could not parse schedule {}: {}", expression, e))
+}
+
+/// Build the command line for a single scheduled run from the original command line
+///
+/// --schedule, -o|--output, and --compare are replaced by values specific to the run
+pub fn child_args(args: &[String], output: &str, previous: Option<&str>) -> Vec<String> {
+    let replaced = ["--schedule", "-o", "--output", "--compare"];
+
+    let mut child = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if replaced.contains(&arg.as_str()) {
+            iter.next(); // skip the flag's value as well
+            continue;
+        }
+
+        if replaced
+            .iter()
+            .any(|flag| arg.starts_with(&format!("{}=", flag)))
+        {
+            continue;
+        }
+
+        if !arg.starts_with("--") && arg.starts_with('-') {
+            // clustered short flags, i.e. -vvo results.txt or -vvoresults.txt
+            let cluster = &arg[1..];
+            let flags = cluster
+                .find(|c| !CLUSTERED_FLAGS.contains(c))
+                .unwrap_or(cluster.len());
+
+            if cluster[flags..].starts_with('o') {
+                if flags > 0 {
+                    child.push(format!("-{}", &cluster[..flags]));
+                }
+
+                if cluster.len() == flags + 1 {
+                    iter.next(); // the value is the next argument
+                }
+
+                continue;
+            }
+        }
+
+        child.push(arg.clone());
+    }
+
+    child.push(String::from("--output"));
+    child.push(output.to_string());
+
+    if !child.iter().any(|arg| arg == "--json") {
+        // the next run's comparison needs json
+        child.push(String::from("--json"));
+    }
+
+    if let Some(previous) = previous {
+        child.push(String::from("--compare"));
+        child.push(previous.to_string());
+    }
+
+    child
+}
+
+/// Run the configured scan every time the schedule fires; only returns on error
+pub fn run(config: &Configuration) -> Result<()> {
+    let schedule = parse_schedule(&config.schedule)?;
+
+    let exe = current_exe()?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let base = if config.output.is_empty() {
+        DEFAULT_OUTPUT_BASE
+    } else {
+        &config.output
+    };
+
+    // a --compare given on the command line seeds the first run's comparison
+    let mut previous = if config.compare.is_empty() {
+        None
+    } else {
+        Some(config.compare.clone())
+    };
+
+    loop {
+        let next = schedule
+            .upcoming(Local)
+            .next()
+            .ok_or_else(|| anyhow!("Schedule {} never fires", config.schedule))?;
+
+        eprintln!("Next scheduled scan at {}", next.to_rfc3339());
+
+        if let Ok(wait) = (next - Local::now()).to_std() {
+            sleep(wait);
+        }
+
+        let output = format!("{}-{}.json", base, next.format("%Y%m%d-%H%M%S"));
+
+        let status = Command::new(&exe)
+            .args(child_args(&args, &output, previous.as_deref()))
+            .status()
+            .with_context(|| format!("Could not start scheduled scan from {:?}", exe))?;
+
+        if !status.success() {
+            eprintln!("Scheduled scan exited with {}", status);
+        }
+
+        if Path::new(&output).exists() {
+            // this run becomes the baseline for the next
+            previous = Some(output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// helper to turn a command line into owned strings
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    /// five field expressions should be accepted, as should six
+    fn parse_schedule_accepts_standard_cron() {
+        assert!(parse_schedule("0 3 * * *").is_ok());
+        assert!(parse_schedule("30 0 3 * * *").is_ok());
+        assert!(parse_schedule("not a schedule").is_err());
+    }
+
+    #[test]
+    /// --schedule, --output, and --compare should be replaced with run specific values
+    fn child_args_replaces_run_specific_flags() {
+        let original =
+            args("-u http://localhost --schedule=0 -o results.txt --compare old.json -t 10");
+
+        let child = child_args(&original, "results.txt-1.json", Some("results.txt-0.json"));
+
+        assert_eq!(
+            child,
+            args("-u http://localhost -t 10 --output results.txt-1.json --json --compare results.txt-0.json")
+        );
+    }
+
+    #[test]
+    /// -o should be replaced even when clustered with other short flags
+    fn child_args_replaces_clustered_output() {
+        let child = child_args(
+            &args("-u http://localhost -vvo old.json -k"),
+            "out.json",
+            None,
+        );
+        assert_eq!(
+            child,
+            args("-u http://localhost -vv -k --output out.json --json")
+        );
+
+        let child = child_args(&args("-u http://localhost -qoold.json"), "out.json", None);
+        assert_eq!(
+            child,
+            args("-u http://localhost -q --output out.json --json")
+        );
+
+        // o is part of -x's value here, not -o
+        let child = child_args(&args("-u http://localhost -xphp,json"), "out.json", None);
+        assert_eq!(
+            child,
+            args("-u http://localhost -xphp,json --output out.json --json")
+        );
+    }
+
+    #[test]
+    /// standard cron weekdays (0 and 7 are sunday) should fire on the expected days
+    fn parse_schedule_uses_standard_weekdays() {
+        use chrono::{Datelike, Weekday};
+
+        let weekdays = |expression| {
+            parse_schedule(expression)
+                .unwrap()
+                .upcoming(Local)
+                .take(7)
+                .map(|next| next.weekday())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(weekdays("0 3 * * 1").iter().all(|d| *d == Weekday::Mon));
+        assert!(weekdays("0 3 * * 0").iter().all(|d| *d == Weekday::Sun));
+        assert!(weekdays("0 3 * * 7").iter().all(|d| *d == Weekday::Sun));
+        assert!(weekdays("0 3 * * 1-5")
+            .iter()
+            .all(|d| *d != Weekday::Sat && *d != Weekday::Sun));
+        assert!(weekdays("0 3 * * 6-7")
+            .iter()
+            .all(|d| *d == Weekday::Sat || *d == Weekday::Sun));
+        assert!(weekdays("0 3 * * MON").iter().all(|d| *d == Weekday::Mon));
+    }
+
+    #[test]
+    /// --json shouldn't be added twice, and there's nothing to compare against on the first run
+    fn child_args_first_run_with_json() {
+        let child = child_args(&args("-u http://localhost --json"), "out.json", None);
+        assert_eq!(child, args("-u http://localhost --json --output out.json"));
+    }
+}
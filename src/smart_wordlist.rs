@@ -0,0 +1,269 @@
+//! cewl-style wordlist generation from the target itself (--smart-wordlist)
+//!
+//! a handful of pages are spidered from each initial target, following the same links that
+//! --extract-links would; words found in their html are added to the end of the scanning wordlist
+//! while the scans are already running, so every directory scan (recursive ones included) ends up
+//! requesting them too
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::{header::CONTENT_TYPE, Url};
+
+use crate::{
+    event_handlers::{Command::AddToUsizeField, Handles},
+    extractor::LINKFINDER_REGEX,
+    response::FeroxResponse,
+    scanner::RateGate,
+    statistics::StatField::TotalExpected,
+    utils::logged_request,
+    wordlist::Wordlist,
+};
+
+/// number of pages spidered from each initial target
+pub const MAX_PAGES: usize = 25;
+
+/// shortest word taken from a page; anything shorter is mostly noise (a, of, to, ...)
+const MIN_WORD_LEN: usize = 3;
+
+/// longest word taken from a page
+const MAX_WORD_LEN: usize = 32;
+
+/// links to files with these extensions aren't followed, they won't contain any html
+const SKIPPED_EXTENSIONS: [&str; 14] = [
+    "js", "css", "png", "jpg", "jpeg", "gif", "svg", "ico", "webp", "woff", "woff2", "ttf", "pdf",
+    "zip",
+];
+
+lazy_static! {
+    /// script and style elements, whose contents aren't text shown on the page
+    static ref SCRIPTS: Regex = Regex::new(r"(?is)<script\b.*?</script>|<style\b.*?</style>").unwrap();
+
+    /// html tags and comments
+    static ref TAGS: Regex = Regex::new(r"(?s)<!--.*?-->|<[^>]*>").unwrap();
+
+    /// html entities, i.e. &amp; or &#8217;
+    static ref ENTITIES: Regex = Regex::new(r"&#?[a-zA-Z0-9]+;").unwrap();
+
+    /// characters that can make up a word; limited to those that are safe in a url path
+    static ref WORDS: Regex = Regex::new(r"[a-zA-Z0-9_-]+").unwrap();
+
+    /// links within a page, see `LINKFINDER_REGEX`
+    static ref LINKS: Regex = Regex::new(LINKFINDER_REGEX).unwrap();
+}
+
+/// Words in the text of the given html, in the order they appear, without repeats
+pub fn words_from_html(html: &str) -> Vec<String> {
+    let text = SCRIPTS.replace_all(html, " ");
+    let text = TAGS.replace_all(&text, " ");
+    let text = ENTITIES.replace_all(&text, " ");
+
+    let mut seen = HashSet::new();
+
+    WORDS
+        .find_iter(&text)
+        .map(|found| found.as_str().trim_matches(|c| c == '-' || c == '_'))
+        .filter(|word| (MIN_WORD_LEN..=MAX_WORD_LEN).contains(&word.len()))
+        .filter(|word| !word.chars().all(|c| c.is_ascii_digit()))
+        .filter(|word| seen.insert(word.to_string()))
+        .map(String::from)
+        .collect()
+}
+
+/// Pages linked to from the given html that are on the same host as `page`; links to assets
+/// (scripts, images, ...) are left out
+pub fn links_from_html(page: &Url, html: &str) -> Vec<Url> {
+    let mut links = Vec::new();
+
+    for capture in LINKS.captures_iter(html) {
+        let link = capture[0].trim_matches(|c| c == '\'' || c == '"');
+
+        let mut url = match page.join(link) {
+            Ok(url) => url,
+            Err(_) => continue,
+        };
+
+        if url.host() != page.host() || url.port_or_known_default() != page.port_or_known_default()
+        {
+            // don't spider things that aren't part of the original target
+            continue;
+        }
+
+        url.set_query(None);
+        url.set_fragment(None);
+
+        let extension = url
+            .path_segments()
+            .and_then(|segments| segments.last())
+            .and_then(|last| last.rsplit_once('.'))
+            .map(|(_, extension)| extension.to_lowercase());
+
+        if let Some(extension) = extension {
+            if SKIPPED_EXTENSIONS.contains(&extension.as_str()) {
+                continue;
+            }
+        }
+
+        if !links.contains(&url) {
+            links.push(url);
+        }
+    }
+
+    links
+}
+
+/// Request the given page, once the gate allows it, returning its body only when it's html that was
+/// retrieved successfully
+async fn retrieve(url: &Url, gate: &RateGate, handles: Arc<Handles>) -> Option<FeroxResponse> {
+    gate.wait().await;
+
+    let response = logged_request(url, handles.clone()).await.ok()?;
+
+    let ferox_response = FeroxResponse::from(response, true, handles.config.output_level).await;
+
+    let is_html = ferox_response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| value.contains("html"));
+
+    if !ferox_response.status().is_success() || !is_html {
+        return None;
+    }
+
+    Some(ferox_response)
+}
+
+/// Spider the given targets (see `spider`) in a task of its own; when that task fails, the
+/// wordlist is ended all the same, so that the scans waiting on more words don't wait forever
+pub async fn run(targets: Vec<String>, handles: Arc<Handles>, wordlist: Arc<Wordlist>) {
+    log::trace!("enter: run({:?})", targets);
+
+    let spidering = tokio::spawn(spider(targets, handles, wordlist.clone()));
+
+    if let Err(e) = spidering.await {
+        log::warn!("--smart-wordlist stopped spidering early: {}", e);
+        wordlist.end();
+    }
+
+    log::trace!("exit: run");
+}
+
+/// Spider up to `MAX_PAGES` pages from each of the given targets, adding every word found that
+/// isn't already in `wordlist` to it; the wordlist is ended afterwards, which lets the scans that
+/// are waiting on more words finish
+///
+/// pages are requested no faster than --rate-limit allows
+pub async fn spider(targets: Vec<String>, handles: Arc<Handles>, wordlist: Arc<Wordlist>) {
+    log::trace!("enter: spider({:?})", targets);
+
+    let gate = RateGate::with_limit(handles.config.rate_limit).unwrap_or_else(|e| {
+        log::warn!("Could not rate limit --smart-wordlist: {}", e);
+        RateGate::default()
+    });
+
+    let mut known: HashSet<String> = wordlist.iter().map(String::from).collect();
+    let mut num_added = 0;
+    let mut num_pages = 0;
+
+    for target in &targets {
+        let start = match Url::parse(target) {
+            Ok(url) => url,
+            Err(e) => {
+                log::warn!("Could not spider {}: {}", target, e);
+                continue;
+            }
+        };
+
+        let mut queue = VecDeque::from(vec![start.clone()]);
+        let mut visited = HashSet::new();
+        visited.insert(start);
+
+        let mut pages = 0;
+
+        while let Some(url) = queue.pop_front() {
+            if pages >= MAX_PAGES {
+                break;
+            }
+
+            pages += 1;
+
+            handles
+                .stats
+                .send(AddToUsizeField(TotalExpected, 1))
+                .unwrap_or_else(|e| log::warn!("Could not update overall scan bar: {}", e));
+
+            let response = match retrieve(&url, &gate, handles.clone()).await {
+                Some(response) => response,
+                None => continue,
+            };
+
+            for word in words_from_html(response.text()) {
+                if known.insert(word.clone()) {
+                    wordlist.push(&word);
+                    num_added += 1;
+                }
+            }
+
+            for link in links_from_html(response.url(), response.text()) {
+                if visited.insert(link.clone()) {
+                    queue.push_back(link);
+                }
+            }
+        }
+
+        num_pages += pages;
+    }
+
+    wordlist.end();
+
+    log::info!(
+        "--smart-wordlist added {} words from {} pages",
+        num_added,
+        num_pages
+    );
+    log::trace!("exit: spider");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// words should come from the page's text, not its markup, comments, scripts, or entities
+    fn words_from_html_skips_markup() {
+        let html = r#"<html><head><title>Acme Portal</title>
+            <style>.hidden { display: none }</style>
+            <script>var secretToken = "abc";</script></head>
+            <body><!-- staging --><a href="/login">Customer login</a> &amp; 2021
+            <p class="banner">Acme -invoices- of</p></body></html>"#;
+
+        assert_eq!(
+            words_from_html(html),
+            vec!["Acme", "Portal", "Customer", "login", "invoices"]
+        );
+    }
+
+    #[test]
+    /// only pages on the same host should be followed, without their queries or fragments
+    fn links_from_html_stays_on_target() {
+        let page = Url::parse("http://localhost/app/").unwrap();
+        let html = r#"<a href="/about?x=1#team">about</a>
+            <a href="http://example.com/elsewhere">elsewhere</a>
+            <a href="./docs/index.html">docs</a>
+            <script src="/static/app.js"></script>
+            <img src="/img/logo.png">
+            <a href="/about">about again</a>"#;
+
+        assert_eq!(
+            links_from_html(&page, html),
+            vec![
+                Url::parse("http://localhost/about").unwrap(),
+                Url::parse("http://localhost/app/docs/index.html").unwrap(),
+            ]
+        );
+    }
+}
@@ -1,22 +1,22 @@
 use std::{
     collections::HashMap,
     convert::TryFrom,
-    fs::File,
-    io::BufReader,
     sync::{
         atomic::{AtomicUsize, Ordering},
         Mutex,
     },
 };
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use reqwest::StatusCode;
 use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 
 use crate::{
+    filters::filter_rank,
+    scan_manager::read_state,
     traits::FeroxSerialize,
-    utils::{fmt_err, open_file, write_to},
+    utils::{open_file, write_to},
 };
 
 use super::{error::StatError, field::StatField};
@@ -33,6 +33,10 @@ pub struct Stats {
     /// tracker for total number of requests sent by the client
     pub(crate) requests: AtomicUsize,
 
+    /// tracker for number of requests cleared to be sent under --request-limit; unlike
+    /// `requests`, it's counted before a request is sent
+    requests_claimed: AtomicUsize,
+
     /// tracker for total number of requests expected to send if the scan runs to completion
     ///
     /// Note: this is a per-scan expectation; `expected_requests * current # of scans` would be
@@ -105,9 +109,15 @@ pub struct Stats {
     /// tracker for overall number of all filtered responses
     responses_filtered: AtomicUsize,
 
+    /// tracker for the number of responses suppressed by each filter, keyed by the filter's name
+    filter_hits: Mutex<HashMap<String, usize>>,
+
     /// tracker for number of files found
     resources_discovered: AtomicUsize,
 
+    /// tracker for number of responses that looked like a WAF/CDN block page
+    blocked: AtomicUsize,
+
     /// tracker for number of errors triggered during URL formatting
     url_format_errors: AtomicUsize,
 
@@ -154,7 +164,7 @@ impl Serialize for Stats {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Stats", 32)?;
+        let mut state = serializer.serialize_struct("Stats", 34)?;
 
         state.serialize_field("type", &self.kind)?;
         state.serialize_field("timeouts", &atomic_load!(self.timeouts))?;
@@ -181,10 +191,12 @@ impl Serialize for Stats {
         state.serialize_field("status_508s", &atomic_load!(self.status_508s))?;
         state.serialize_field("wildcards_filtered", &atomic_load!(self.wildcards_filtered))?;
         state.serialize_field("responses_filtered", &atomic_load!(self.responses_filtered))?;
+        state.serialize_field("filter_hits", &self.filter_hits)?;
         state.serialize_field(
             "resources_discovered",
             &atomic_load!(self.resources_discovered),
         )?;
+        state.serialize_field("blocked", &atomic_load!(self.blocked))?;
         state.serialize_field("url_format_errors", &atomic_load!(self.url_format_errors))?;
         state.serialize_field("redirection_errors", &atomic_load!(self.redirection_errors))?;
         state.serialize_field("connection_errors", &atomic_load!(self.connection_errors))?;
@@ -377,6 +389,17 @@ impl<'a> Deserialize<'a> for Stats {
                         }
                     }
                 }
+                "filter_hits" => {
+                    if let Some(map) = value.as_object() {
+                        for (name, hits) in map {
+                            if let Some(num) = hits.as_u64() {
+                                if let Ok(parsed) = usize::try_from(num) {
+                                    stats.add_filter_hits(name, parsed);
+                                }
+                            }
+                        }
+                    }
+                }
                 "resources_discovered" => {
                     if let Some(num) = value.as_u64() {
                         if let Ok(parsed) = usize::try_from(num) {
@@ -384,6 +407,13 @@ impl<'a> Deserialize<'a> for Stats {
                         }
                     }
                 }
+                "blocked" => {
+                    if let Some(num) = value.as_u64() {
+                        if let Ok(parsed) = usize::try_from(num) {
+                            atomic_increment!(stats.blocked, parsed);
+                        }
+                    }
+                }
                 "url_format_errors" => {
                     if let Some(num) = value.as_u64() {
                         if let Ok(parsed) = usize::try_from(num) {
@@ -466,6 +496,16 @@ impl Stats {
         atomic_load!(self.resources_discovered)
     }
 
+    /// public getter for blocked
+    pub fn blocked(&self) -> usize {
+        atomic_load!(self.blocked)
+    }
+
+    /// public getter for requests
+    pub fn requests(&self) -> usize {
+        atomic_load!(self.requests)
+    }
+
     /// public getter for errors
     pub fn errors(&self) -> usize {
         atomic_load!(self.errors)
@@ -486,6 +526,34 @@ impl Stats {
         atomic_load!(self.total_expected)
     }
 
+    /// public getter for the number of responses suppressed by each filter, in the order in which
+    /// the filters are evaluated
+    pub fn filter_hits(&self) -> Vec<(String, usize)> {
+        let mut hits = match self.filter_hits.lock() {
+            Ok(guard) => guard
+                .iter()
+                .map(|(name, count)| (name.to_owned(), *count))
+                .collect::<Vec<_>>(),
+            Err(_) => return vec![],
+        };
+
+        hits.sort_by_key(|(name, _)| filter_rank(name));
+
+        hits
+    }
+
+    /// increment the number of responses suppressed by the filter with the given name
+    pub fn add_filter_hit(&self, name: &str) {
+        self.add_filter_hits(name, 1);
+    }
+
+    /// add `value` to the number of responses suppressed by the filter with the given name
+    fn add_filter_hits(&self, name: &str, value: usize) {
+        if let Ok(mut guard) = self.filter_hits.lock() {
+            *guard.entry(name.to_string()).or_insert(0) += value;
+        }
+    }
+
     /// public getter for initial_targets
     pub fn initial_targets(&self) -> usize {
         atomic_load!(self.initial_targets)
@@ -496,6 +564,18 @@ impl Stats {
         atomic_increment!(self.requests);
     }
 
+    /// Claim one of the `limit` requests that may be sent (--request-limit); returns false once
+    /// all of them have been claimed
+    pub fn claim_request(&self, limit: usize) -> bool {
+        self.requests_claimed.fetch_add(1, Ordering::SeqCst) < limit
+    }
+
+    /// whether or not all of the `limit` requests that may be sent (--request-limit) have been
+    /// claimed
+    pub fn requests_exhausted(&self, limit: usize) -> bool {
+        atomic_load!(self.requests_claimed) >= limit
+    }
+
     /// given an `Instant` update total runtime
     fn update_runtime(&self, seconds: f64) {
         if let Ok(mut runtime) = self.total_runtime.lock() {
@@ -647,24 +727,26 @@ impl Stats {
             StatField::InitialTargets => {
                 atomic_increment!(self.initial_targets, value);
             }
+            StatField::Blocked => {
+                atomic_increment!(self.blocked, value);
+            }
             _ => {} // f64 fields
         }
     }
 
     /// Merge a given `Stats` object from a json entry written to disk when handling a Ctrl+c
     ///
-    /// This is only ever called when resuming a scan from disk
-    pub fn merge_from(&self, filename: &str) -> Result<()> {
-        let file = File::open(filename)
-            .with_context(|| fmt_err(&format!("Could not open {}", filename)))?;
-        let reader = BufReader::new(file);
-        let state: serde_json::Value = serde_json::from_reader(reader)?;
+    /// This is only ever called when resuming a scan from disk; `password` is used to decrypt
+    /// state files saved with --state-password
+    pub fn merge_from(&self, filename: &str, password: &str) -> Result<()> {
+        let state = read_state(filename, password)?;
 
         if let Some(state_stats) = state.get("statistics") {
             let d_stats = serde_json::from_value::<Stats>(state_stats.clone())?;
             atomic_increment!(self.successes, atomic_load!(d_stats.successes));
             atomic_increment!(self.timeouts, atomic_load!(d_stats.timeouts));
             atomic_increment!(self.requests, atomic_load!(d_stats.requests));
+            atomic_increment!(self.requests_claimed, atomic_load!(d_stats.requests));
             atomic_increment!(self.errors, atomic_load!(d_stats.errors));
             atomic_increment!(self.redirects, atomic_load!(d_stats.redirects));
             atomic_increment!(self.client_errors, atomic_load!(d_stats.client_errors));
@@ -692,6 +774,7 @@ impl Stats {
                 self.resources_discovered,
                 atomic_load!(d_stats.resources_discovered)
             );
+            atomic_increment!(self.blocked, atomic_load!(d_stats.blocked));
             atomic_increment!(
                 self.url_format_errors,
                 atomic_load!(d_stats.url_format_errors)
@@ -706,6 +789,10 @@ impl Stats {
             );
             atomic_increment!(self.request_errors, atomic_load!(d_stats.request_errors));
 
+            for (name, hits) in d_stats.filter_hits() {
+                self.add_filter_hits(&name, hits);
+            }
+
             if let Ok(scan_times) = d_stats.directory_scan_times.lock() {
                 for scan_time in scan_times.iter() {
                     self.update_f64_field(StatField::DirScanTimes, *scan_time);
@@ -719,7 +806,7 @@ impl Stats {
 #[cfg(test)]
 mod tests {
     use crate::{config::Configuration, Command};
-    use std::fs::write;
+    use std::{fs::write, sync::Arc};
     use tempfile::NamedTempFile;
 
     use super::super::*;
@@ -741,6 +828,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    /// exactly --request-limit requests should be claimable, however many threads are claiming
+    fn claim_request_allows_exactly_the_limit() {
+        let stats = Arc::new(Stats::new(0, false));
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let stats = stats.clone();
+                std::thread::spawn(move || (0..100).filter(|_| stats.claim_request(250)).count())
+            })
+            .collect();
+
+        let claimed: usize = threads.into_iter().map(|t| t.join().unwrap()).sum();
+
+        assert_eq!(claimed, 250);
+        assert!(stats.requests_exhausted(250));
+        assert!(!stats.claim_request(250));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     /// when sent StatCommand::AddRequest, stats object should reflect the change
     ///
@@ -837,17 +943,37 @@ mod tests {
         assert_eq!(stats.responses_filtered.load(Ordering::Relaxed), 3);
     }
 
+    #[test]
+    /// Stats::add_filter_hit should track each filter separately and Stats::filter_hits should
+    /// report them in evaluation order
+    fn stats_add_filter_hit_tracks_each_filter() {
+        let stats = Stats::new(0, false);
+
+        assert!(stats.filter_hits().is_empty());
+
+        stats.add_filter_hit("Regex");
+        stats.add_filter_hit("Status Code");
+        stats.add_filter_hit("Regex");
+
+        assert_eq!(
+            stats.filter_hits(),
+            vec![(String::from("Status Code"), 1), (String::from("Regex"), 2)]
+        );
+    }
+
     #[test]
     /// Stats::merge_from should properly increment expected fields and ignore others
     fn stats_merge_from_alters_correct_fields() {
-        let contents = r#"{"statistics":{"type":"statistics","timeouts":1,"requests":9207,"expected_per_scan":707,"total_expected":9191,"errors":3,"successes":720,"redirects":13,"client_errors":8474,"server_errors":2,"total_scans":13,"initial_targets":1,"links_extracted":51,"status_403s":3,"status_200s":720,"status_301s":12,"status_302s":1,"status_401s":4,"status_429s":2,"status_500s":5,"status_503s":9,"status_504s":6,"status_508s":7,"wildcards_filtered":707,"responses_filtered":707,"resources_discovered":27,"directory_scan_times":[2.211973078,1.989015505,1.898675839,3.9714468910000003,4.938152838,5.256073528,6.021986595,6.065740734,6.42633762,7.095142125,7.336982137,5.319785619,4.843649778],"total_runtime":[11.556575456000001],"url_format_errors":17,"redirection_errors":12,"connection_errors":21,"request_errors":4}}"#;
+        let contents = r#"{"statistics":{"type":"statistics","timeouts":1,"requests":9207,"expected_per_scan":707,"total_expected":9191,"errors":3,"successes":720,"redirects":13,"client_errors":8474,"server_errors":2,"total_scans":13,"initial_targets":1,"links_extracted":51,"status_403s":3,"status_200s":720,"status_301s":12,"status_302s":1,"status_401s":4,"status_429s":2,"status_500s":5,"status_503s":9,"status_504s":6,"status_508s":7,"wildcards_filtered":707,"responses_filtered":707,"filter_hits":{"Wildcard":707,"Size":3},"resources_discovered":27,"blocked":5,"directory_scan_times":[2.211973078,1.989015505,1.898675839,3.9714468910000003,4.938152838,5.256073528,6.021986595,6.065740734,6.42633762,7.095142125,7.336982137,5.319785619,4.843649778],"total_runtime":[11.556575456000001],"url_format_errors":17,"redirection_errors":12,"connection_errors":21,"request_errors":4}}"#;
         let config = Configuration::new().unwrap();
         let stats = Stats::new(config.extensions.len(), config.json);
 
         let tfile = NamedTempFile::new().unwrap();
         write(&tfile, contents).unwrap();
 
-        stats.merge_from(tfile.path().to_str().unwrap()).unwrap();
+        stats
+            .merge_from(tfile.path().to_str().unwrap(), "")
+            .unwrap();
 
         // as of 2.1.0; all Stats fields are accounted for whether they're updated in merge_from
         // or not
@@ -875,7 +1001,12 @@ mod tests {
         assert_eq!(atomic_load!(stats.status_508s), 7);
         assert_eq!(atomic_load!(stats.wildcards_filtered), 707);
         assert_eq!(atomic_load!(stats.responses_filtered), 707);
+        assert_eq!(
+            stats.filter_hits(),
+            vec![(String::from("Size"), 3), (String::from("Wildcard"), 707)]
+        );
         assert_eq!(atomic_load!(stats.resources_discovered), 27);
+        assert_eq!(atomic_load!(stats.blocked), 5);
         assert_eq!(atomic_load!(stats.url_format_errors), 17);
         assert_eq!(atomic_load!(stats.redirection_errors), 12);
         assert_eq!(atomic_load!(stats.connection_errors), 21);
@@ -28,6 +28,9 @@ pub enum StatField {
     /// Translates to `initial_targets`
     InitialTargets,
 
+    /// Translates to `blocked`
+    Blocked,
+
     /// Translates to `directory_scan_times`; assumes a single append to the vector
     DirScanTimes,
 }
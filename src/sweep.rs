@@ -0,0 +1,193 @@
+//! --target; addresses and ports are probed for web servers, each of which becomes a target
+//!
+//! a port is only considered once a tcp connection to it succeeds, after which https is tried
+//! before http; whichever one gets a response is the scheme used for the target
+use std::{
+    error::Error,
+    net::{Ipv4Addr, SocketAddr},
+};
+
+use anyhow::{anyhow, Result};
+use futures::{stream, StreamExt};
+use reqwest::{Client, Url};
+use tokio::{
+    net::TcpStream,
+    time::{timeout, Duration},
+};
+
+use crate::{
+    certificate::{PinVerifier, Untrusted},
+    config::Configuration,
+    parser::expand_target_range,
+};
+
+/// how long to wait for a tcp connection before considering a port closed
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Probe every address and port covered by the given --target ranges, returning the url of each
+/// web server found, in the order they appear in the ranges
+pub async fn discover(ranges: &[String], config: &Configuration) -> Result<Vec<String>> {
+    log::trace!("enter: discover({:?})", ranges);
+
+    let mut candidates = Vec::new();
+
+    for range in ranges {
+        candidates.extend(expand_target_range(range).map_err(|e| anyhow!(e))?);
+    }
+
+    log::info!(
+        "Probing {} addresses and ports for web servers",
+        candidates.len()
+    );
+
+    let found: Vec<Option<String>> = stream::iter(candidates)
+        .map(|(address, port)| {
+            probe(
+                address,
+                port,
+                &config.client,
+                config.pin_verifier.as_deref(),
+            )
+        })
+        .buffered(config.threads.max(1))
+        .collect()
+        .await;
+
+    let targets: Vec<String> = found.into_iter().flatten().collect();
+
+    log::trace!("exit: discover -> {:?}", targets);
+    Ok(targets)
+}
+
+/// Url of the web server listening on the given address and port, if there is one
+async fn probe(
+    address: Ipv4Addr,
+    port: u16,
+    client: &Client,
+    verifier: Option<&PinVerifier>,
+) -> Option<String> {
+    let socket = SocketAddr::from((address, port));
+
+    match timeout(CONNECT_TIMEOUT, TcpStream::connect(socket)).await {
+        Ok(Ok(_)) => {}
+        _ => return None, // closed, filtered, or nothing there
+    }
+
+    for scheme in &["https", "http"] {
+        let url = format!("{}://{}/", scheme, socket);
+
+        if let (Some(verifier), Ok(parsed)) = (verifier, Url::parse(&url)) {
+            match verifier.verify(&parsed).await {
+                Err(e) if e.is::<Untrusted>() => {
+                    // speaks https, with a certificate that fails --pin-sha256; not even probed
+                    log::warn!("{}, skipping it", e);
+                    return None;
+                }
+                Err(_) => continue, // no tls handshake, try http
+                Ok(_) => {}
+            }
+        }
+
+        // any response at all means the scheme is right; the target's status doesn't matter yet
+        match client.get(&url).send().await {
+            Ok(_) => {
+                log::debug!("found web server at {}", url);
+                return Some(url);
+            }
+            Err(e) if is_certificate_error(&e) => {
+                // the port speaks https; asking it for http would only get a "plain http request
+                // sent to https port" page, which isn't the target. it's only scanned with -k
+                log::warn!(
+                    "{} has a certificate that isn't trusted, skipping it (use --insecure to scan it)",
+                    url
+                );
+                return None;
+            }
+            Err(_) => {}
+        }
+    }
+
+    None
+}
+
+/// Whether or not the given error was caused by the server's certificate failing verification
+fn is_certificate_error(error: &reqwest::Error) -> bool {
+    let mut source = error.source();
+
+    while let Some(cause) = source {
+        if cause.to_string().contains("certificate verify failed") {
+            return true;
+        }
+
+        source = cause.source();
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TlsServer;
+    use tokio::net::TcpListener;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// ports that refuse connections, or that don't speak http(s), shouldn't become targets
+    async fn probe_skips_closed_and_non_http_ports() {
+        // bound, then dropped; nothing is listening on the port afterwards
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed_port = closed.local_addr().unwrap().port();
+        drop(closed);
+
+        // accepts connections, then immediately hangs up
+        let silent = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let silent_port = silent.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = silent.accept().await {
+                drop(stream);
+            }
+        });
+
+        let client = Client::new();
+        let localhost = Ipv4Addr::LOCALHOST;
+
+        assert!(probe(localhost, closed_port, &client, None).await.is_none());
+        assert!(probe(localhost, silent_port, &client, None).await.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// an https server with an untrusted certificate should only be found with -k, and never be
+    /// mistaken for an http server
+    async fn probe_honors_insecure() {
+        let srv = TlsServer::start();
+        let localhost = Ipv4Addr::LOCALHOST;
+
+        assert!(probe(localhost, srv.port(), &Client::new(), None)
+            .await
+            .is_none());
+
+        let insecure = Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            probe(localhost, srv.port(), &insecure, None).await,
+            Some(format!("https://127.0.0.1:{}/", srv.port()))
+        );
+        assert_eq!(srv.hits(), 1);
+
+        // a certificate that fails --pin-sha256 isn't probed at all
+        let config = Configuration {
+            insecure: true,
+            pin_sha256: vec![String::from("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=")],
+            ..Default::default()
+        };
+        let verifier = PinVerifier::new(&config).unwrap();
+
+        assert!(probe(localhost, srv.port(), &insecure, Some(&verifier))
+            .await
+            .is_none());
+        assert_eq!(srv.hits(), 1);
+    }
+}
@@ -0,0 +1,293 @@
+//! scaffolding for integration tests that run scans against an httpmock server; temporary
+//! wordlists and canned state files (for --resume-from), along with fixtures shared by the unit
+//! tests
+//!
+//! only compiled with the `test-utils` feature, so that forks and plugin authors can test against
+//! the scanning pipeline the same way feroxbuster's own integration tests do
+//!
+//! ```ignore
+//! use feroxbuster::test_utils::{setup_tmp_directory, teardown_tmp_directory, Method, MockServer};
+//!
+//! let srv = MockServer::start();
+//! let (tmp_dir, wordlist) = setup_tmp_directory(&["admin".to_string()], "wordlist")?;
+//!
+//! let admin = srv.mock(|when, then| {
+//!     when.method(Method::GET).path("/admin");
+//!     then.status(200).body("welcome");
+//! });
+//!
+//! // run feroxbuster against srv.url("/") using the wordlist...
+//!
+//! assert_eq!(admin.hits(), 1);
+//! teardown_tmp_directory(tmp_dir);
+//! ```
+//!
+//! the rest of the crate is only ever referred to as `feroxbuster::`, never `crate::`;
+//! feroxbuster's own integration tests include this file directly, so that they don't need the
+//! feature, and that path works both there and in the library itself
+use std::{
+    error::Error,
+    fs::{remove_dir_all, write},
+    io::{Read, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use openssl::{
+    asn1::Asn1Time,
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::PKey,
+    rsa::Rsa,
+    sha::sha256,
+    ssl::{SslAcceptor, SslMethod},
+    x509::{extension::SubjectAlternativeName, X509NameBuilder, X509},
+};
+use reqwest::{
+    header::{HeaderMap, HeaderValue},
+    StatusCode, Url,
+};
+use serde_json::json;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use feroxbuster::response::FeroxResponse;
+
+pub use httpmock::{Method, MockServer};
+
+/// integration test helper: creates a temp directory, and writes `words` to
+/// a file named `filename` in the temp directory
+pub fn setup_tmp_directory(
+    words: &[String],
+    filename: &str,
+) -> Result<(TempDir, PathBuf), Box<dyn Error>> {
+    let tmp_dir = TempDir::new()?;
+    let file = tmp_dir.path().join(&filename);
+    write(&file, words.join("\n"))?;
+    Ok((tmp_dir, file))
+}
+
+/// integration test helper: removes a temporary directory, presumably created with
+/// [setup_tmp_directory](fn.setup_tmp_directory.html)
+pub fn teardown_tmp_directory(directory: TempDir) {
+    remove_dir_all(directory).unwrap();
+}
+
+/// response with the given url, status, and body
+pub fn response(url: &str, status: u16, body: &str) -> FeroxResponse {
+    let mut response = FeroxResponse::default();
+    response.set_url(url);
+    response.set_status(StatusCode::from_u16(status).unwrap());
+    response.set_text(body);
+    response
+}
+
+/// header map built from the given (name, value) pairs
+pub fn header_map(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+    let mut map = HeaderMap::new();
+
+    for (name, value) in pairs {
+        map.append(*name, HeaderValue::from_static(value));
+    }
+
+    map
+}
+
+/// state file helper: a serialized directory scan of `url`, as found in a state file's `scans`;
+/// `status` is one of NotStarted, Running, Complete, or Cancelled
+pub fn scan_json(url: &str, status: &str) -> String {
+    json!({
+        "id": Uuid::new_v4().to_simple().to_string(),
+        "url": url,
+        "scan_type": "Directory",
+        "status": status,
+    })
+    .to_string()
+}
+
+/// state file helper: a serialized response, with an empty body, as found in a state file's
+/// `responses`; urls in `responses` are considered already seen when the scan is resumed
+pub fn response_json(url: &str, status: u16) -> String {
+    let path = Url::parse(url)
+        .map(|parsed| parsed.path().to_string())
+        .unwrap_or_default();
+
+    json!({
+        "type": "response",
+        "url": url,
+        "path": path,
+        "wildcard": false,
+        "status": status,
+        "content_length": 0,
+        "line_count": 0,
+        "word_count": 0,
+        "headers": {},
+    })
+    .to_string()
+}
+
+/// state file helper: a serialized configuration that scans `target` with `wordlist`, as found in
+/// a state file's `config`; every other option keeps its default
+pub fn config_json(wordlist: &Path, target: &str) -> String {
+    json!({
+        "type": "configuration",
+        "wordlist": wordlist.to_string_lossy(),
+        "target_url": target,
+    })
+    .to_string()
+}
+
+/// integration test helper: creates a temp directory holding a state file made of the given
+/// scans, configuration, and responses, suitable for --resume-from
+pub fn setup_state_file(
+    scans: &[String],
+    config: &str,
+    responses: &[String],
+) -> Result<(TempDir, PathBuf), Box<dyn Error>> {
+    let contents = format!(
+        r#"{{"scans":[{}],"config":{},"responses":[{}]}}"#,
+        scans.join(","),
+        config,
+        responses.join(",")
+    );
+
+    setup_tmp_directory(&[contents], "state-file")
+}
+
+/// integration test helper: an https server for localhost, using a freshly generated
+/// self-signed certificate; every request gets an empty 200 response
+///
+/// only requests that make it past the tls handshake are counted as hits
+pub struct TlsServer {
+    /// port the server is listening on
+    port: u16,
+
+    /// base64 encoded sha256 of the certificate's public key, as given to --pin-sha256
+    pin: String,
+
+    /// the certificate, PEM encoded, as given to --ca-cert
+    certificate: Vec<u8>,
+
+    /// number of requests received
+    hits: Arc<AtomicUsize>,
+}
+
+/// implementation of TlsServer
+impl TlsServer {
+    /// generate a certificate and start serving on a random port
+    pub fn start() -> Self {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, "localhost")
+            .unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(30).unwrap())
+            .unwrap();
+
+        let alt_names = SubjectAlternativeName::new()
+            .dns("localhost")
+            .ip("127.0.0.1")
+            .build(&builder.x509v3_context(None, None))
+            .unwrap();
+        builder.append_extension(alt_names).unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        let cert = builder.build();
+
+        let pin = base64::encode(sha256(&key.public_key_to_der().unwrap()));
+        let certificate = cert.to_pem().unwrap();
+
+        let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+        acceptor.set_private_key(&key).unwrap();
+        acceptor.set_certificate(&cert).unwrap();
+        let acceptor = Arc::new(acceptor.build());
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let hits = Arc::new(AtomicUsize::new(0));
+
+        let counter = hits.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let acceptor = acceptor.clone();
+                let counter = counter.clone();
+
+                thread::spawn(move || {
+                    // handshakes rejected by the client end here, before any request is read
+                    let mut stream = match acceptor.accept(stream) {
+                        Ok(stream) => stream,
+                        Err(_) => return,
+                    };
+
+                    let mut request = Vec::new();
+                    let mut buf = [0_u8; 1024];
+
+                    loop {
+                        match stream.read(&mut buf) {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+
+                        if request.windows(4).any(|window| window == b"\r\n\r\n") {
+                            break;
+                        }
+                    }
+
+                    counter.fetch_add(1, Ordering::SeqCst);
+
+                    let _ = stream.write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    );
+                    let _ = stream.shutdown();
+                });
+            }
+        });
+
+        Self {
+            port,
+            pin,
+            certificate,
+            hits,
+        }
+    }
+
+    /// https url of the given path on the server, i.e. https://localhost:41234/admin
+    pub fn url(&self, path: &str) -> String {
+        format!("https://localhost:{}{}", self.port, path)
+    }
+
+    /// port the server is listening on
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// base64 encoded sha256 of the server's public key
+    pub fn pin(&self) -> &str {
+        &self.pin
+    }
+
+    /// the server's certificate, PEM encoded
+    pub fn certificate(&self) -> &[u8] {
+        &self.certificate
+    }
+
+    /// number of requests the server has received
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+}
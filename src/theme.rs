@@ -0,0 +1,154 @@
+//! user-configurable colors (`colors` in the config file, --no-color, NO_COLOR)
+//!
+//! each class of status code can be given its own console style, written the same way as
+//! console's dotted style strings (i.e. `blue`, `red.bold`, `black.on_yellow`); classes that
+//! aren't configured keep their default color
+use std::{collections::HashMap, env, sync::RwLock};
+
+use console::Style;
+use lazy_static::lazy_static;
+
+use crate::config::Configuration;
+
+/// classes of status that can be given a color: 1xx - 5xx, wildcard responses, and errors
+pub const COLOR_CLASSES: [&str; 7] = ["1xx", "2xx", "3xx", "4xx", "5xx", "wildcard", "error"];
+
+/// parts of a dotted style string that console understands; anything else is silently dropped by
+/// Style::from_dotted_str, so it's reported by Configuration::validate instead
+const STYLE_PARTS: [&str; 24] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright",
+    "on_black",
+    "on_red",
+    "on_green",
+    "on_yellow",
+    "on_blue",
+    "on_magenta",
+    "on_cyan",
+    "on_white",
+    "on_bright",
+    "bold",
+    "dim",
+    "underlined",
+    "blink",
+    "reverse",
+    "hidden",
+];
+
+lazy_static! {
+    /// class -> style configured by the user
+    static ref THEME: RwLock<HashMap<&'static str, Style>> = RwLock::new(HashMap::new());
+}
+
+/// Class of the given status, based on its first character (same logic as status_colorizer)
+fn class(status: &str) -> Option<&'static str> {
+    match status.chars().next() {
+        Some('1') => Some("1xx"),
+        Some('2') => Some("2xx"),
+        Some('3') => Some("3xx"),
+        Some('4') => Some("4xx"),
+        Some('5') => Some("5xx"),
+        Some('W') => Some("wildcard"),
+        Some('E') => Some("error"),
+        _ => None,
+    }
+}
+
+/// Whether or not the NO_COLOR environment variable is set (https://no-color.org)
+fn no_color_requested() -> bool {
+    env::var_os("NO_COLOR").map_or(false, |value| !value.is_empty())
+}
+
+/// Style configured for the given status, if the user configured one for its class
+pub fn style_for(status: &str) -> Option<Style> {
+    let class = class(status)?;
+
+    THEME.read().ok()?.get(class).cloned()
+}
+
+/// Parts of the given dotted style string that aren't a known color or attribute
+pub fn unknown_style_parts(style: &str) -> Vec<&str> {
+    style
+        .split('.')
+        .filter(|part| !STYLE_PARTS.contains(part))
+        .collect()
+}
+
+/// Parse the colors read from the config file into class -> style
+///
+/// unknown classes are ignored here, they're reported by Configuration::validate
+fn read_theme(colors: &HashMap<String, String>) -> HashMap<&'static str, Style> {
+    colors
+        .iter()
+        .filter_map(|(name, style)| {
+            COLOR_CLASSES
+                .iter()
+                .find(|class| class.eq_ignore_ascii_case(name))
+                .map(|class| (*class, Style::from_dotted_str(style)))
+        })
+        .collect()
+}
+
+/// Apply --no-color / NO_COLOR, and store the colors read from the config file
+pub fn initialize(config: &Configuration) {
+    log::trace!("enter: initialize({:?})", config.colors);
+
+    if config.no_color || no_color_requested() {
+        // every console::style, including those used by progress bars, is printed as plain text
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+
+    let theme = read_theme(&config.colors);
+
+    if let Ok(mut guard) = THEME.write() {
+        *guard = theme;
+    }
+
+    log::trace!("exit: initialize");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// statuses should map to the class named after their first character
+    fn class_uses_first_character() {
+        assert_eq!(class("200"), Some("2xx"));
+        assert_eq!(class("404"), Some("4xx"));
+        assert_eq!(class("WLD"), Some("wildcard"));
+        assert_eq!(class("ERROR"), Some("error"));
+        assert_eq!(class("farfignewton"), None);
+    }
+
+    #[test]
+    /// only parts that console doesn't understand should be reported
+    fn unknown_style_parts_reports_typos() {
+        assert!(unknown_style_parts("black.on_yellow.bold").is_empty());
+        assert_eq!(unknown_style_parts("bleu.bold"), vec!["bleu"]);
+        assert_eq!(unknown_style_parts(""), vec![""]);
+    }
+
+    #[test]
+    /// configured classes should be read case-insensitively, unknown classes should be ignored
+    fn read_theme_reads_colors() {
+        let mut colors = HashMap::new();
+        colors.insert(String::from("4XX"), String::from("blue"));
+        colors.insert(String::from("6xx"), String::from("red"));
+
+        let theme = read_theme(&colors);
+        assert_eq!(theme.len(), 1);
+        assert_eq!(
+            theme["4xx"].apply_to("403").to_string(),
+            Style::from_dotted_str("blue").apply_to("403").to_string()
+        );
+    }
+}
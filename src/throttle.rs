@@ -0,0 +1,117 @@
+//! aggregate download bandwidth limiting (--throttle)
+//!
+//! all response bodies share a single budget; bodies are read chunk by chunk, and the next read
+//! is delayed until the bytes already read fit within the configured rate
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+use lazy_static::lazy_static;
+use reqwest::Response;
+use tokio::time::sleep;
+
+use crate::parser::THROTTLE_REGEX;
+
+/// maximum number of bytes per second read across all responses, 0 means no limit is imposed
+static RATE: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    /// point in time at which the bytes read so far will have been 'paid for'
+    static ref NEXT_READ: Mutex<Instant> = Mutex::new(Instant::now());
+}
+
+/// Parse a --throttle value into bytes per second; units are powers of 1024
+///
+/// Examples of expected values: 500kb/s, 1mb/s, 2048b
+pub fn parse_throttle(throttle: &str) -> Option<u64> {
+    let captures = THROTTLE_REGEX.captures(throttle)?;
+
+    let amount = captures.name("n")?.as_str().parse::<u64>().ok()?;
+
+    let multiplier = match captures.name("u")?.as_str().to_ascii_lowercase().as_str() {
+        "kb" => 1024,
+        "mb" => 1024 * 1024,
+        "gb" => 1024 * 1024 * 1024,
+        _ => 1,
+    };
+
+    amount.checked_mul(multiplier).filter(|rate| *rate > 0)
+}
+
+/// Enable bandwidth limiting for the remainder of the scan
+pub fn initialize(throttle: &str) -> Result<()> {
+    log::trace!("enter: initialize({})", throttle);
+
+    match parse_throttle(throttle) {
+        Some(rate) => RATE.store(rate, Ordering::Relaxed),
+        None => bail!("Could not parse throttle {} (ex: 500kb/s)", throttle),
+    }
+
+    log::trace!("exit: initialize");
+    Ok(())
+}
+
+/// whether or not bandwidth limiting is enabled
+pub fn enabled() -> bool {
+    RATE.load(Ordering::Relaxed) > 0
+}
+
+/// Account for `num_bytes` having been read, waiting until doing so is within the configured rate
+async fn consume(num_bytes: usize) {
+    let rate = RATE.load(Ordering::Relaxed);
+
+    if rate == 0 {
+        return;
+    }
+
+    let cost = Duration::from_secs_f64(num_bytes as f64 / rate as f64);
+
+    let wait = match NEXT_READ.lock() {
+        Ok(mut next) => {
+            let now = Instant::now();
+            let start = (*next).max(now);
+            *next = start + cost;
+            *next - now
+        }
+        Err(_) => return,
+    };
+
+    sleep(wait).await;
+}
+
+/// Read the entire body of the given response, staying within the configured rate
+pub async fn read_body(mut response: Response) -> reqwest::Result<Vec<u8>> {
+    if !enabled() {
+        return Ok(response.bytes().await?.to_vec());
+    }
+
+    let mut body = Vec::new();
+
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        consume(chunk.len()).await;
+    }
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// parse_throttle should understand each unit, with or without a trailing /s
+    fn parse_throttle_understands_units() {
+        assert_eq!(parse_throttle("2048b"), Some(2048));
+        assert_eq!(parse_throttle("500kb/s"), Some(500 * 1024));
+        assert_eq!(parse_throttle("1MB/s"), Some(1024 * 1024));
+        assert_eq!(parse_throttle("2gb"), Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_throttle("0kb/s"), None);
+        assert_eq!(parse_throttle("fast"), None);
+    }
+}
@@ -15,6 +15,10 @@ pub trait FeroxFilter: Debug + Send + Sync {
     /// Determine whether or not this particular filter should be applied or not
     fn should_filter_response(&self, response: &FeroxResponse) -> bool;
 
+    /// Human readable name of the filter; must match an entry in `filters::FILTER_REGISTRY`,
+    /// which determines the filter's position in the evaluation pipeline
+    fn name(&self) -> &'static str;
+
     /// delegates to the FeroxFilter-implementing type which gives us the actual type of self
     fn box_eq(&self, other: &dyn Any) -> bool;
 
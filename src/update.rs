@@ -0,0 +1,521 @@
+//! update subcommand; replace the running binary with the latest release
+//!
+//! the release archive for this platform is only installed once its sha256 matches the checksum
+//! published alongside it, either as `<archive>.sha256` or as a line of a `SHA256SUMS` file
+//!
+//! the checksum is downloaded from the same release as the archive, so it only catches a corrupt or
+//! truncated download; it doesn't authenticate the release, anyone able to replace the archive can
+//! replace its checksum too
+use std::{
+    cmp::Ordering,
+    env, fs,
+    io::{Cursor, Read},
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+use openssl::sha::sha256;
+use reqwest::{Client, Proxy};
+use serde::Deserialize;
+
+use crate::{banner::UPDATE_URL, config::Configuration, progress::PROGRESS_PRINTER, VERSION};
+
+/// name of the release asset listing the checksums of every other asset
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+/// Version number of a release, i.e. 2.3.1 or 2.4.0-rc1
+///
+/// ordered by major, minor and patch; a pre-release comes before the release it leads up to, but
+/// pre-releases of the same version aren't ordered among themselves
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+    /// major, minor and patch numbers
+    numbers: (u64, u64, u64),
+
+    /// false for pre-releases (anything after a `-`)
+    release: bool,
+}
+
+/// implementation of Version
+impl Version {
+    /// parse a version number, with or without a leading `v`; build metadata (`+...`) is ignored
+    fn parse(version: &str) -> Option<Self> {
+        let version = version.trim().trim_start_matches('v');
+        let version = version.split('+').next().unwrap_or(version);
+
+        let (numbers, release) = match version.split_once('-') {
+            Some((numbers, _)) => (numbers, false),
+            None => (version, true),
+        };
+
+        let mut parts = numbers.split('.').map(|part| part.parse::<u64>().ok());
+
+        let numbers = (parts.next()??, parts.next()??, parts.next()??);
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self { numbers, release })
+    }
+}
+
+/// Subset of github's release json that's needed to find and download the archive
+#[derive(Debug, Deserialize)]
+struct Release {
+    /// tag of the release, i.e. v2.3.1
+    tag_name: String,
+
+    /// files attached to the release
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+/// Single file attached to a release
+#[derive(Debug, Deserialize)]
+struct Asset {
+    /// file name, i.e. x86_64-linux-feroxbuster.zip
+    name: String,
+
+    /// where the file is downloaded from
+    browser_download_url: String,
+}
+
+/// implementation of Release
+impl Release {
+    /// download url of the asset with the given name
+    fn url_of(&self, name: &str) -> Option<&str> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .map(|asset| asset.browser_download_url.as_str())
+    }
+}
+
+/// Name of the release archive built for the given os and architecture (as found in
+/// `std::env::consts`); None when no zip archive is published for it
+pub fn asset_name(os: &str, arch: &str) -> Option<&'static str> {
+    match (os, arch) {
+        ("linux", "x86_64") => Some("x86_64-linux-feroxbuster.zip"),
+        ("linux", "x86") => Some("x86-linux-feroxbuster.zip"),
+        ("linux", "aarch64") => Some("aarch64-feroxbuster.zip"),
+        ("macos", "x86_64") => Some("x86_64-macos-feroxbuster.zip"),
+        ("windows", "x86_64") => Some("x86_64-windows-feroxbuster.exe.zip"),
+        ("windows", "x86") => Some("x86-windows-feroxbuster.exe.zip"),
+        _ => None,
+    }
+}
+
+/// Checksum of `asset` found in the given checksum file; either a bare hash (`<asset>.sha256`)
+/// or `<hash>  <name>` lines (`SHA256SUMS`)
+fn expected_checksum(listing: &str, asset: &str) -> Option<String> {
+    for line in listing.lines() {
+        let mut fields = line.split_whitespace();
+
+        let hash = match fields.next() {
+            Some(hash) => hash,
+            None => continue,
+        };
+
+        match fields.next() {
+            // sha256sum marks files read in binary mode with a leading *
+            Some(name) if name.trim_start_matches('*') != asset => continue,
+            _ => return Some(hash.to_lowercase()),
+        }
+    }
+
+    None
+}
+
+/// hex encoded sha256 of the given bytes
+fn checksum(bytes: &[u8]) -> String {
+    sha256(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// The feroxbuster executable contained in a release archive
+fn extract_binary(archive: &[u8]) -> Result<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(archive))?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+
+        let is_binary = Path::new(file.name()).file_name().map_or(false, |name| {
+            name == "feroxbuster" || name == "feroxbuster.exe"
+        });
+
+        if is_binary {
+            let mut binary = Vec::new();
+            file.read_to_end(&mut binary)?;
+            return Ok(binary);
+        }
+    }
+
+    bail!("The release archive doesn't contain a feroxbuster executable")
+}
+
+/// Swap the executable at `current` for `binary`; the new binary is written next to the old one
+/// and renamed over it, so that a failure part way through leaves the old one in place
+fn replace_binary(current: &Path, binary: &[u8]) -> Result<()> {
+    let staged = current.with_extension("new");
+
+    fs::write(&staged, binary).with_context(|| format!("Could not write {}", staged.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+
+    #[cfg(windows)]
+    {
+        // a running executable can't be overwritten, but it can be moved out of the way
+        let old = current.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(current, &old)?;
+    }
+
+    fs::rename(&staged, current)
+        .with_context(|| format!("Could not replace {}", current.display()))?;
+
+    Ok(())
+}
+
+/// body of a successful GET of the given url
+async fn download(client: &Client, url: &str) -> Result<Vec<u8>> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Could not download {}", url))?;
+
+    if !response.status().is_success() {
+        bail!("Could not download {}: {}", url, response.status());
+    }
+
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Replace the executable at `current` with the latest release described at `url`, unless the
+/// running version is the same or newer; returns the version installed, if any
+pub async fn update(client: &Client, url: &str, current: &Path) -> Result<Option<String>> {
+    log::trace!("enter: update({}, {})", url, current.display());
+
+    let release: Release = serde_json::from_slice(&download(client, url).await?)
+        .with_context(|| format!("Could not parse the release found at {}", url))?;
+
+    let latest = release.tag_name.trim_start_matches('v').to_string();
+
+    let newer = match (Version::parse(&latest), Version::parse(VERSION)) {
+        (Some(latest), Some(running)) => latest.cmp(&running) == Ordering::Greater,
+        _ => bail!(
+            "Could not compare release {} to the running version ({})",
+            release.tag_name,
+            VERSION
+        ),
+    };
+
+    if !newer {
+        // already the latest, or a build that's newer than any release
+        log::trace!("exit: update -> None");
+        return Ok(None);
+    }
+
+    let name = match asset_name(env::consts::OS, env::consts::ARCH) {
+        Some(name) => name,
+        None => bail!(
+            "No release is published for {}-{}; download one from https://github.com/epi052/feroxbuster/releases/latest",
+            env::consts::OS,
+            env::consts::ARCH
+        ),
+    };
+
+    let archive_url = match release.url_of(name) {
+        Some(archive_url) => archive_url,
+        None => bail!("Release {} has no {}", release.tag_name, name),
+    };
+
+    let checksum_file = format!("{}.sha256", name);
+
+    let checksum_url = match release
+        .url_of(&checksum_file)
+        .or_else(|| release.url_of(CHECKSUMS_ASSET))
+    {
+        Some(checksum_url) => checksum_url,
+        None => bail!(
+            "Release {} has no checksum for {}; refusing to install it unverified",
+            release.tag_name,
+            name
+        ),
+    };
+
+    let listing = String::from_utf8_lossy(&download(client, checksum_url).await?).to_string();
+
+    let expected = match expected_checksum(&listing, name) {
+        Some(expected) => expected,
+        None => bail!("{} doesn't list a checksum for {}", checksum_url, name),
+    };
+
+    let archive = download(client, archive_url).await?;
+    let actual = checksum(&archive);
+
+    if actual != expected {
+        bail!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            name,
+            expected,
+            actual
+        );
+    }
+
+    replace_binary(current, &extract_binary(&archive)?)?;
+
+    log::trace!("exit: update -> {}", latest);
+    Ok(Some(latest))
+}
+
+/// Update the running binary to the latest release
+pub async fn run(config: Arc<Configuration>) -> Result<()> {
+    log::trace!("enter: run({:?})", config.proxy);
+
+    let mut builder = Client::builder().user_agent(format!("feroxbuster/{}", VERSION));
+
+    if !config.proxy.is_empty() {
+        builder = builder.proxy(Proxy::all(&config.proxy)?);
+    }
+
+    let current = env::current_exe().context("Could not locate the running executable")?;
+
+    match update(&builder.build()?, UPDATE_URL, &current).await? {
+        Some(latest) => PROGRESS_PRINTER.println(format!(
+            "Updated {} from v{} to v{}",
+            current.display(),
+            VERSION,
+            latest
+        )),
+        None => PROGRESS_PRINTER.println(format!("v{} is the latest release", VERSION)),
+    }
+
+    log::trace!("exit: run");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::{Method::GET, MockServer};
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::{write::FileOptions, ZipWriter};
+
+    /// zip archive holding a single file with the given name and contents
+    fn archive_of(name: &str, contents: &[u8]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer.start_file(name, FileOptions::default()).unwrap();
+        writer.write_all(contents).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// release json served by `srv`, with the given assets available under /download/
+    fn release_json(srv: &MockServer, tag: &str, assets: &[&str]) -> String {
+        let assets: Vec<String> = assets
+            .iter()
+            .map(|name| {
+                format!(
+                    r#"{{"name":"{}","browser_download_url":"{}"}}"#,
+                    name,
+                    srv.url(format!("/download/{}", name))
+                )
+            })
+            .collect();
+
+        format!(
+            r#"{{"tag_name":"{}","assets":[{}]}}"#,
+            tag,
+            assets.join(",")
+        )
+    }
+
+    #[test]
+    /// supported platforms should map to their archive, others to nothing
+    fn asset_name_maps_platforms() {
+        assert_eq!(
+            asset_name("linux", "x86_64"),
+            Some("x86_64-linux-feroxbuster.zip")
+        );
+        assert_eq!(
+            asset_name("windows", "x86_64"),
+            Some("x86_64-windows-feroxbuster.exe.zip")
+        );
+        assert_eq!(asset_name("freebsd", "x86_64"), None);
+    }
+
+    #[test]
+    /// both bare hashes and sha256sum style listings should be understood
+    fn expected_checksum_reads_both_formats() {
+        assert_eq!(
+            expected_checksum("ABC123\n", "x86_64-linux-feroxbuster.zip"),
+            Some(String::from("abc123"))
+        );
+
+        let listing = "111  aarch64-feroxbuster.zip\n222 *x86_64-linux-feroxbuster.zip\n";
+        assert_eq!(
+            expected_checksum(listing, "x86_64-linux-feroxbuster.zip"),
+            Some(String::from("222"))
+        );
+        assert_eq!(
+            expected_checksum(listing, "x86-linux-feroxbuster.zip"),
+            None
+        );
+    }
+
+    #[test]
+    /// the executable should be found regardless of the directory it's stored under
+    fn extract_binary_finds_executable() {
+        let archive = archive_of("release/feroxbuster", b"new binary");
+        assert_eq!(extract_binary(&archive).unwrap(), b"new binary");
+
+        let archive = archive_of("README.md", b"nothing to see");
+        assert!(extract_binary(&archive).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// an archive whose checksum matches should replace the binary
+    async fn update_replaces_binary_when_checksum_matches() {
+        let name = match asset_name(env::consts::OS, env::consts::ARCH) {
+            Some(name) => name,
+            None => return,
+        };
+
+        let srv = MockServer::start();
+        let archive = archive_of("feroxbuster", b"new binary");
+
+        srv.mock(|when, then| {
+            when.method(GET).path("/latest");
+            then.status(200)
+                .body(release_json(&srv, "v999.0.0", &[name, CHECKSUMS_ASSET]));
+        });
+        srv.mock(|when, then| {
+            when.method(GET).path(format!("/download/{}", name));
+            then.status(200).body(archive.clone());
+        });
+        srv.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/download/{}", CHECKSUMS_ASSET));
+            then.status(200)
+                .body(format!("{}  {}\n", checksum(&archive), name));
+        });
+
+        let tmp_dir = TempDir::new().unwrap();
+        let current = tmp_dir.path().join("feroxbuster");
+        fs::write(&current, b"old binary").unwrap();
+
+        let installed = update(&Client::new(), &srv.url("/latest"), &current)
+            .await
+            .unwrap();
+
+        assert_eq!(installed, Some(String::from("999.0.0")));
+        assert_eq!(fs::read(&current).unwrap(), b"new binary");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// an archive whose checksum doesn't match should leave the binary alone
+    async fn update_refuses_checksum_mismatch() {
+        let name = match asset_name(env::consts::OS, env::consts::ARCH) {
+            Some(name) => name,
+            None => return,
+        };
+
+        let srv = MockServer::start();
+        let checksum_file = format!("{}.sha256", name);
+
+        srv.mock(|when, then| {
+            when.method(GET).path("/latest");
+            then.status(200)
+                .body(release_json(&srv, "v999.0.0", &[name, &checksum_file]));
+        });
+        srv.mock(|when, then| {
+            when.method(GET).path(format!("/download/{}", name));
+            then.status(200)
+                .body(archive_of("feroxbuster", b"tampered binary"));
+        });
+        srv.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/download/{}", checksum_file));
+            then.status(200).body(checksum(b"something else"));
+        });
+
+        let tmp_dir = TempDir::new().unwrap();
+        let current = tmp_dir.path().join("feroxbuster");
+        fs::write(&current, b"old binary").unwrap();
+
+        let result = update(&Client::new(), &srv.url("/latest"), &current).await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Checksum mismatch"));
+        assert_eq!(fs::read(&current).unwrap(), b"old binary");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// nothing should be downloaded when already on the latest release
+    async fn update_does_nothing_when_up_to_date() {
+        let srv = MockServer::start();
+
+        srv.mock(|when, then| {
+            when.method(GET).path("/latest");
+            then.status(200)
+                .body(format!(r#"{{"tag_name":"v{}","assets":[]}}"#, VERSION));
+        });
+
+        let tmp_dir = TempDir::new().unwrap();
+        let current = tmp_dir.path().join("feroxbuster");
+
+        let installed = update(&Client::new(), &srv.url("/latest"), &current)
+            .await
+            .unwrap();
+
+        assert_eq!(installed, None);
+        assert!(!current.exists());
+    }
+
+    #[test]
+    /// versions should be ordered by their numbers rather than compared as text, with
+    /// pre-releases before their release
+    fn versions_are_ordered_numerically() {
+        let version = |version: &str| Version::parse(version).unwrap();
+
+        assert!(version("v2.10.0") > version("2.9.1"));
+        assert!(version("2.3.1") < version("2.3.2-dev"));
+        assert!(version("2.4.0-rc1") < version("2.4.0"));
+        assert_eq!(version("v2.4.0+build.7"), version("2.4.0"));
+        assert!(Version::parse("2.4").is_none());
+        assert!(Version::parse("latest").is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// a build that's newer than the latest release shouldn't be replaced by it
+    async fn update_never_downgrades() {
+        let srv = MockServer::start();
+
+        srv.mock(|when, then| {
+            when.method(GET).path("/latest");
+            then.status(200)
+                .body(r#"{"tag_name":"v0.0.1","assets":[]}"#);
+        });
+
+        let tmp_dir = TempDir::new().unwrap();
+        let current = tmp_dir.path().join("feroxbuster");
+
+        let installed = update(&Client::new(), &srv.url("/latest"), &current)
+            .await
+            .unwrap();
+
+        assert_eq!(installed, None);
+        assert!(!current.exists());
+    }
+}
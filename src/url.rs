@@ -1,7 +1,21 @@
 use crate::{event_handlers::Handles, statistics::StatError::UrlFormat, Command::AddError};
 use anyhow::{anyhow, bail, Result};
 use reqwest::Url;
-use std::{convert::TryInto, fmt, sync::Arc};
+use std::{
+    convert::TryInto,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// placeholder in --query names/values that's replaced by a value from --query-file
+pub const QUERY_PLACEHOLDER: &str = "{QUERY}";
+
+/// index of the next --query-file value to use, shared by every scan so that values rotate
+/// across all requests
+static NEXT_QUERY_VALUE: AtomicUsize = AtomicUsize::new(0);
 
 /// abstraction around target urls; collects all Url related shenanigans in one place
 #[derive(Debug)]
@@ -124,13 +138,36 @@ impl FeroxUrl {
             log::trace!("exit: format -> {}", joined);
             Ok(joined)
         } else {
-            let with_params =
-                Url::parse_with_params(joined.as_str(), &self.handles.config.queries)?;
+            let with_params = Url::parse_with_params(joined.as_str(), &self.queries())?;
             log::trace!("exit: format_url -> {}", with_params);
             Ok(with_params) // request with params attached
         }
     }
 
+    /// Query parameters for the next request, with the next value from --query-file substituted
+    /// for any {QUERY} placeholders
+    fn queries(&self) -> Vec<(String, String)> {
+        let config = &self.handles.config;
+
+        if config.query_values.is_empty() {
+            return config.queries.clone();
+        }
+
+        let index = NEXT_QUERY_VALUE.fetch_add(1, Ordering::Relaxed) % config.query_values.len();
+        let value = &config.query_values[index];
+
+        config
+            .queries
+            .iter()
+            .map(|(name, query)| {
+                (
+                    name.replace(QUERY_PLACEHOLDER, value),
+                    query.replace(QUERY_PLACEHOLDER, value),
+                )
+            })
+            .collect()
+    }
+
     /// Gets the length of a url's path
     pub fn path_length(&self) -> Result<u64> {
         let parsed = Url::parse(&self.target)?;
@@ -229,6 +266,22 @@ impl fmt::Display for FeroxUrl {
     }
 }
 
+/// Canonical form of the given url, used to tell whether two urls point at the same resource
+///
+/// the scheme and host are lowercased, default ports and fragments are dropped, and dot-segments
+/// (`/./`, `/../`) are resolved; the path is otherwise left as-is, since servers are free to treat
+/// it case-sensitively. Urls that can't be parsed are returned unchanged
+pub fn normalize_url(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) => {
+            // parsing already takes care of case, default ports, and dot-segments
+            parsed.set_fragment(None);
+            parsed.to_string()
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,6 +438,35 @@ mod tests {
         );
     }
 
+    #[test]
+    /// {QUERY} placeholders should be replaced by rotating through the --query-file values
+    fn format_url_substitutes_query_values() {
+        let config = Configuration {
+            queries: vec![
+                (String::from("cb"), String::from("{QUERY}")),
+                (String::from("stuff"), String::from("things")),
+            ],
+            query_values: vec![String::from("one"), String::from("two")],
+            ..Default::default()
+        };
+
+        let handles = Arc::new(Handles::for_testing(None, Some(Arc::new(config))).0);
+        let url = FeroxUrl::from_string("http://localhost", handles);
+
+        let mut formatted: Vec<String> = (0..2)
+            .map(|_| url.format("lazer", None).unwrap().to_string())
+            .collect();
+        formatted.sort();
+
+        assert_eq!(
+            formatted,
+            vec![
+                "http://localhost/lazer?cb=one&stuff=things",
+                "http://localhost/lazer?cb=two&stuff=things"
+            ]
+        );
+    }
+
     #[test]
     /// base url + no word + no slash + no extension + queries
     fn format_url_without_word_joins_queries() {
@@ -460,4 +542,22 @@ mod tests {
 
         assert!(formatted.is_err());
     }
+
+    #[test]
+    /// case, default ports, fragments, and dot-segments shouldn't matter; the path's case should
+    fn normalize_url_canonicalizes_equivalent_urls() {
+        assert_eq!(
+            normalize_url("HTTP://LocalHost:80/a/./b/../Admin#top"),
+            "http://localhost/a/Admin"
+        );
+        assert_eq!(
+            normalize_url("https://localhost:443/%2e%2e/js/"),
+            "https://localhost/js/"
+        );
+        assert_eq!(
+            normalize_url("http://localhost:8080/js"),
+            "http://localhost:8080/js"
+        );
+        assert_eq!(normalize_url("not a url"), "not a url");
+    }
 }
@@ -1,28 +1,48 @@
 use anyhow::{bail, Context, Result};
 use console::{strip_ansi_codes, style, user_attended};
 use indicatif::ProgressBar;
-use reqwest::{Client, Response, StatusCode, Url};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode, Url};
 #[cfg(not(target_os = "windows"))]
 use rlimit::{getrlimit, setrlimit, Resource, Rlim};
 use std::{
     fs,
-    io::{self, BufWriter, Write},
+    io::{BufWriter, Write},
     sync::Arc,
 };
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{
+    cache,
+    certificate::PinVerifier,
     config::OutputLevel,
     event_handlers::{
         Command::{self, AddError, AddStatus},
         Handles,
     },
     progress::PROGRESS_PRINTER,
-    send_command,
+    raw, send_command,
     statistics::StatError::{Connection, Other, Redirection, Request, Timeout},
+    theme,
     traits::FeroxSerialize,
+    wordlist::Wordlist,
+    wordlists,
 };
 
+/// Map the given wordlist into memory then store it inside an Arc; `given` is whether the user
+/// chose the wordlist, see wordlists::resolve
+pub fn get_unique_words_from_wordlist(path: &str, given: bool) -> Result<Arc<Wordlist>> {
+    log::trace!("enter: get_unique_words_from_wordlist({}, {})", path, given);
+
+    let words = wordlists::resolve(path, given)?.load()?;
+
+    log::trace!(
+        "exit: get_unique_words_from_wordlist -> Arc<wordlist[{} words...]>",
+        words.len()
+    );
+
+    Ok(Arc::new(words))
+}
+
 /// Given the path to a file, open the file in append mode (create it if it doesn't exist) and
 /// return a reference to the buffered file
 pub fn open_file(filename: &str) -> Result<BufWriter<fs::File>> {
@@ -41,7 +61,13 @@ pub fn open_file(filename: &str) -> Result<BufWriter<fs::File>> {
 }
 
 /// Takes in a string and examines the first character to return a color version of the same string
+///
+/// colors configured in the config file take precedence over the defaults below
 pub fn status_colorizer(status: &str) -> String {
+    if let Some(custom) = theme::style_for(status) {
+        return custom.apply_to(status).to_string();
+    }
+
     match status.chars().next() {
         Some('1') => style(status).blue().to_string(), // informational
         Some('2') => style(status).green().to_string(), // success
@@ -59,6 +85,11 @@ pub fn fmt_err(msg: &str) -> String {
     format!("{}: {}", status_colorizer("ERROR"), msg)
 }
 
+/// same as `fmt_err`, for problems that don't stop the scan; for use before logging is set up
+pub fn fmt_warn(msg: &str) -> String {
+    format!("{}: {}", style("WARNING").yellow(), msg)
+}
+
 /// Takes in a string and colors it using console::style
 ///
 /// mainly putting this here in case i want to change the color later, making any changes easy
@@ -92,7 +123,40 @@ pub async fn logged_request(url: &Url, handles: Arc<Handles>) -> Result<Response
     let level = handles.config.output_level;
     let tx_stats = handles.stats.tx.clone();
 
-    let response = make_request(client, url, level, tx_stats).await;
+    let cache_dir = &handles.config.cache_dir;
+    let cache_key = cache::key("GET", url, &handles.config);
+
+    let cached = if cache_dir.is_empty() {
+        None
+    } else {
+        cache::load(cache_dir, &cache_key).await
+    };
+
+    let response = match cached {
+        Some(cached) => {
+            // counted like any other response, so --auto-tune/--auto-bail still see 403s/429s
+            log::debug!("using cached response for {}", url);
+            send_command!(tx_stats, AddStatus(cached.status()));
+            Ok(cached)
+        }
+        None => {
+            let verifier = handles.config.pin_verifier.as_deref();
+
+            let response = if handles.config.raw {
+                // --raw; written to the socket byte-for-byte
+                raw::make_request(handles.config.clone(), url, tx_stats.clone()).await
+            } else {
+                make_request(client, verifier, Method::GET, url, level, tx_stats.clone()).await
+            };
+
+            match response {
+                Ok(resp) if !cache_dir.is_empty() => {
+                    cache::store(cache_dir, &cache_key, resp).await
+                }
+                response => response,
+            }
+        }
+    };
 
     let scans = handles.ferox_scans()?;
 
@@ -114,24 +178,120 @@ pub async fn logged_request(url: &Url, handles: Arc<Handles>) -> Result<Response
     }
 }
 
-/// Initiate request to the given `Url` using `Client`
+/// Initiate a request to the given `Url` using `Client` and the given method; when a PinVerifier is
+/// given, nothing is sent unless the server's certificate matches one of its pins
 pub async fn make_request(
     client: &Client,
+    verifier: Option<&PinVerifier>,
+    method: Method,
     url: &Url,
     output_level: OutputLevel,
     tx_stats: UnboundedSender<Command>,
 ) -> Result<Response> {
     log::trace!(
-        "enter: make_request(Configuration::Client, {}, {:?}, {:?})",
+        "enter: make_request(Configuration::Client, {}, {}, {:?}, {:?})",
+        method,
         url,
         output_level,
         tx_stats
     );
 
-    match client.get(url.to_owned()).send().await {
+    let request = client.request(method, url.to_owned());
+    let result = send_request(request, verifier, output_level, tx_stats).await;
+
+    log::trace!("exit: make_request -> {:?}", result);
+    result
+}
+
+/// Send a request using the given method and additional headers; see make_request (i.e.
+/// --bypass-403's rewrite headers)
+pub async fn method_request(
+    method: Method,
+    url: &Url,
+    headers: &[(&str, String)],
+    handles: &Handles,
+) -> Result<Response> {
+    log::trace!("enter: method_request({}, {}, {:?})", method, url, headers);
+
+    let mut request = handles.config.client.request(method, url.to_owned());
+
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+
+    let result = send_request(
+        request,
+        handles.config.pin_verifier.as_deref(),
+        handles.config.output_level,
+        handles.stats.tx.clone(),
+    )
+    .await;
+
+    log::trace!("exit: method_request -> {:?}", result);
+    result
+}
+
+/// Send a request with the given body, using the given method and additional headers; see
+/// make_request (i.e. a GraphQL introspection query)
+pub async fn body_request(
+    method: Method,
+    url: &Url,
+    headers: &[(&str, String)],
+    body: String,
+    handles: &Handles,
+) -> Result<Response> {
+    log::trace!("enter: body_request({}, {}, {:?})", method, url, headers);
+
+    let mut request = handles.config.client.request(method, url.to_owned());
+
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+
+    let result = send_request(
+        request.body(body),
+        handles.config.pin_verifier.as_deref(),
+        handles.config.output_level,
+        handles.stats.tx.clone(),
+    )
+    .await;
+
+    log::trace!("exit: body_request -> {:?}", result);
+    result
+}
+
+/// Send the given request, updating statistics with its outcome; when a PinVerifier is given,
+/// nothing is sent unless the server's certificate matches one of its pins
+pub async fn send_request(
+    request: RequestBuilder,
+    verifier: Option<&PinVerifier>,
+    output_level: OutputLevel,
+    tx_stats: UnboundedSender<Command>,
+) -> Result<Response> {
+    let (client, request) = request.build_split();
+
+    let request = match request {
+        Ok(request) => request,
         Err(e) => {
-            log::trace!("exit: make_request -> {}", e);
+            send_command!(tx_stats, AddError(Other));
+            log::warn!("Error while making request: {}", e);
+            return Err(e.into());
+        }
+    };
+
+    let url = request.url().clone();
 
+    if let Some(verifier) = verifier {
+        if let Err(e) = verifier.verify(&url).await {
+            // the server's certificate isn't one we trust, it's sent nothing
+            send_command!(tx_stats, AddError(Connection));
+            log::warn!("Error while making request: {}", e);
+            return Err(e);
+        }
+    }
+
+    match client.execute(request).await {
+        Err(e) => {
             if e.is_timeout() {
                 send_command!(tx_stats, AddError(Timeout));
             } else if e.is_redirect() {
@@ -166,10 +326,19 @@ pub async fn make_request(
             }
 
             log::warn!("Error while making request: {}", e);
-            bail!("{}", e)
+
+            // the reqwest::Error is kept intact, so callers can tell timeouts apart
+            Err(e.into())
         }
         Ok(resp) => {
-            log::trace!("exit: make_request -> {:?}", resp);
+            if let Err(e) = verifier.map_or(Ok(()), |verifier| verifier.check(&resp)) {
+                // i.e. redirected to a server whose certificate isn't one we trust, its response
+                // is discarded
+                send_command!(tx_stats, AddError(Connection));
+                log::warn!("Error while making request: {}", e);
+                return Err(e);
+            }
+
             send_command!(tx_stats, AddStatus(resp.status()));
             Ok(resp)
         }
@@ -252,15 +421,12 @@ pub fn set_open_file_limit(limit: usize) -> bool {
     false
 }
 
-/// Given a string and a reference to a locked buffered file, write the contents and flush
-/// the buffer to disk.
-pub fn write_to<T>(
-    value: &T,
-    file: &mut io::BufWriter<fs::File>,
-    convert_to_json: bool,
-) -> Result<()>
+/// Given a string and a reference to a locked buffered file (or any other writer), write the
+/// contents and flush the buffer to disk.
+pub fn write_to<T, W>(value: &T, file: &mut W, convert_to_json: bool) -> Result<()>
 where
     T: FeroxSerialize,
+    W: Write,
 {
     // note to future self: adding logging of anything other than error to this function
     // is a bad idea. we call this function while processing records generated by the logger.
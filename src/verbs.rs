@@ -0,0 +1,288 @@
+//! --verb-compare; each reported resource is also requested with HEAD and OPTIONS, and the ways
+//! their responses differ from GET's are recorded as the resource's method matrix; the resource is
+//! reported once its matrix is complete
+//!
+//! an `Allow` (or `Access-Control-Allow-Methods`) header discloses which methods a resource
+//! accepts, which are often more than the GET a scan would otherwise try
+use std::collections::BTreeMap;
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue},
+    Method, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    event_handlers::{Command::AddToUsizeField, Handles},
+    response::FeroxResponse,
+    scanner::RateGate,
+    send_command,
+    statistics::StatField::TotalExpected,
+    utils::make_request,
+};
+
+/// methods requested alongside the scan's GET
+const COMPARED_METHODS: [Method; 2] = [Method::HEAD, Method::OPTIONS];
+
+/// headers that list the methods a resource accepts
+const ALLOW_HEADERS: [&str; 2] = ["allow", "access-control-allow-methods"];
+
+/// headers that are expected to differ between any two responses, even for the same method
+const VOLATILE_HEADERS: [&str; 8] = [
+    "date",
+    "content-length",
+    "transfer-encoding",
+    "connection",
+    "keep-alive",
+    "age",
+    "expires",
+    "set-cookie",
+];
+
+/// Response of a resource to a single method
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MethodResult {
+    /// status code of the response
+    pub status: u16,
+
+    /// methods listed in the response's allow headers, if any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+
+    /// headers that weren't part of GET's response, or whose values differ from it; always
+    /// empty for GET itself
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Responses of a resource to GET, HEAD, and OPTIONS, and how they differ from one another
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MethodMatrix {
+    /// response to each method, keyed by the method's name
+    pub methods: BTreeMap<String, MethodResult>,
+
+    /// human readable differences, i.e. `HEAD 405` or `OPTIONS allows GET, PUT`; empty when
+    /// every method got the same response
+    #[serde(default)]
+    pub differences: Vec<String>,
+}
+
+/// implementation of MethodMatrix
+impl MethodMatrix {
+    /// Compare the GET response of a resource with its responses to other methods
+    pub fn new(get: &FeroxResponse, others: &[(Method, StatusCode, HeaderMap)]) -> Self {
+        let mut matrix = Self::default();
+
+        let get_allow = allowed(get.headers());
+
+        if !get_allow.is_empty() {
+            matrix
+                .differences
+                .push(format!("GET allows {}", get_allow.join(", ")));
+        }
+
+        matrix.methods.insert(
+            Method::GET.to_string(),
+            MethodResult {
+                status: get.status().as_u16(),
+                allow: get_allow,
+                headers: BTreeMap::new(),
+            },
+        );
+
+        for (method, status, headers) in others {
+            let result = MethodResult {
+                status: status.as_u16(),
+                allow: allowed(headers),
+                headers: changed_headers(get.headers(), headers),
+            };
+
+            if status != get.status() {
+                matrix
+                    .differences
+                    .push(format!("{} {}", method, status.as_u16()));
+            }
+
+            if !result.allow.is_empty() {
+                matrix
+                    .differences
+                    .push(format!("{} allows {}", method, result.allow.join(", ")));
+            }
+
+            if !result.headers.is_empty() {
+                let names: Vec<&str> = result.headers.keys().map(String::as_str).collect();
+
+                matrix
+                    .differences
+                    .push(format!("{} headers {}", method, names.join(", ")));
+            }
+
+            matrix.methods.insert(method.to_string(), result);
+        }
+
+        matrix
+    }
+
+    /// all differences on a single line, as shown alongside the url in text output
+    pub fn summary(&self) -> String {
+        self.differences.join("; ")
+    }
+}
+
+/// header value as a string, with any invalid characters replaced
+fn header_str(value: &HeaderValue) -> String {
+    String::from_utf8_lossy(value.as_bytes()).trim().to_string()
+}
+
+/// methods listed in the given allow headers, upper-cased and without duplicates
+fn allowed(headers: &HeaderMap) -> Vec<String> {
+    let mut methods = Vec::new();
+
+    for name in ALLOW_HEADERS.iter() {
+        for value in headers.get_all(*name) {
+            for method in header_str(value).split(',') {
+                let method = method.trim().to_uppercase();
+
+                if !method.is_empty() && !methods.contains(&method) {
+                    methods.push(method);
+                }
+            }
+        }
+    }
+
+    methods
+}
+
+/// headers of `other` that are missing from, or have a different value in, `get`; volatile and
+/// allow headers are left out
+fn changed_headers(get: &HeaderMap, other: &HeaderMap) -> BTreeMap<String, String> {
+    let mut changed = BTreeMap::new();
+
+    for (name, value) in other {
+        let name = name.as_str();
+
+        if VOLATILE_HEADERS.contains(&name) || ALLOW_HEADERS.contains(&name) {
+            continue;
+        }
+
+        if !get.get_all(name).iter().any(|expected| expected == value) {
+            changed.insert(name.to_string(), header_str(value));
+        }
+    }
+
+    changed
+}
+
+/// Request the given response's url with HEAD and OPTIONS, storing how their responses differ
+/// from it as the response's method matrix; methods whose requests fail are left out
+///
+/// every request waits on the given gate
+pub async fn compare(response: &mut FeroxResponse, gate: &RateGate, handles: &Handles) {
+    log::trace!("enter: compare({})", response.url());
+
+    send_command!(
+        handles.stats.tx,
+        AddToUsizeField(TotalExpected, COMPARED_METHODS.len())
+    );
+
+    let mut others = Vec::new();
+
+    for method in COMPARED_METHODS.iter() {
+        gate.wait().await;
+
+        let other = make_request(
+            &handles.config.client,
+            handles.config.pin_verifier.as_deref(),
+            method.clone(),
+            response.url(),
+            handles.config.output_level,
+            handles.stats.tx.clone(),
+        )
+        .await;
+
+        if let Ok(other) = other {
+            others.push((method.clone(), other.status(), other.headers().clone()));
+        }
+    }
+
+    let matrix = MethodMatrix::new(&response, &others);
+
+    log::trace!("exit: compare -> {:?}", matrix);
+    response.set_method_matrix(matrix);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::header_map;
+
+    #[test]
+    /// status changes, allow headers, and new headers should all be reported, while volatile
+    /// headers and identical responses aren't
+    fn method_matrix_reports_differences() {
+        let mut get = FeroxResponse::default();
+        get.set_status(StatusCode::OK);
+        get.set_headers(header_map(&[
+            ("server", "nginx"),
+            ("date", "Mon, 01 Jan 2024 00:00:00 GMT"),
+        ]));
+
+        let others = vec![
+            (
+                Method::HEAD,
+                StatusCode::OK,
+                header_map(&[
+                    ("server", "nginx"),
+                    ("date", "Mon, 01 Jan 2024 00:00:01 GMT"),
+                ]),
+            ),
+            (
+                Method::OPTIONS,
+                StatusCode::NO_CONTENT,
+                header_map(&[
+                    ("server", "nginx"),
+                    ("allow", "GET, head,PUT"),
+                    ("allow", "GET"),
+                    ("x-debug", "1"),
+                ]),
+            ),
+        ];
+
+        let matrix = MethodMatrix::new(&get, &others);
+
+        assert_eq!(
+            matrix.differences,
+            vec![
+                "OPTIONS 204",
+                "OPTIONS allows GET, HEAD, PUT",
+                "OPTIONS headers x-debug"
+            ]
+        );
+        assert_eq!(matrix.methods["GET"].status, 200);
+        assert_eq!(
+            matrix.methods["HEAD"],
+            MethodResult {
+                status: 200,
+                ..Default::default()
+            }
+        );
+        assert_eq!(matrix.methods["OPTIONS"].headers["x-debug"], "1");
+        assert_eq!(
+            matrix.summary(),
+            "OPTIONS 204; OPTIONS allows GET, HEAD, PUT; OPTIONS headers x-debug"
+        );
+    }
+
+    #[test]
+    /// an allow header on the GET response is disclosure in and of itself
+    fn method_matrix_reports_get_allow() {
+        let mut get = FeroxResponse::default();
+        get.set_status(StatusCode::METHOD_NOT_ALLOWED);
+        get.set_headers(header_map(&[("access-control-allow-methods", "POST")]));
+
+        let matrix = MethodMatrix::new(&get, &[]);
+
+        assert_eq!(matrix.differences, vec!["GET allows POST"]);
+        assert_eq!(matrix.methods["GET"].allow, vec!["POST"]);
+    }
+}
@@ -0,0 +1,194 @@
+//! heuristic detection of responses generated by a WAF or CDN that's blocking the scan, rather
+//! than by the target itself
+//!
+//! a blocked response looks like any other 403 to the filters; knowing that it came from a WAF
+//! tells the user that results are being censored
+use crate::response::FeroxResponse;
+
+/// A single WAF/CDN block page signature; a response matches if its status is one of the listed
+/// codes and any one of its indicators is present
+pub struct BlockSignature {
+    /// name of the WAF or CDN, shown in warnings
+    pub name: &'static str,
+
+    /// status codes used by the block page
+    pub statuses: &'static [u16],
+
+    /// (header name, substring of header value) pairs, case-insensitive; a name ending with `*`
+    /// matches every header whose name starts with what comes before it
+    pub headers: &'static [(&'static str, &'static str)],
+
+    /// substrings of the response body, case-sensitive
+    pub body: &'static [&'static str],
+}
+
+/// All known block page signatures
+pub const BLOCK_SIGNATURES: [BlockSignature; 6] = [
+    BlockSignature {
+        name: "Cloudflare",
+        statuses: &[403, 429, 503],
+        headers: &[("cf-mitigated", "challenge")],
+        body: &[
+            "error code: 1020",
+            "Attention Required! | Cloudflare",
+            "cf-error-details",
+        ],
+    },
+    BlockSignature {
+        name: "Akamai",
+        statuses: &[403],
+        headers: &[("server", "akamaighost")],
+        body: &["Reference&#32;&#35;"],
+    },
+    BlockSignature {
+        name: "ModSecurity",
+        statuses: &[403, 406, 501],
+        headers: &[("server", "mod_security")],
+        body: &["Mod_Security", "ModSecurity"],
+    },
+    BlockSignature {
+        name: "Imperva",
+        statuses: &[403],
+        headers: &[],
+        body: &["Incapsula incident ID", "_Incapsula_Resource"],
+    },
+    BlockSignature {
+        name: "Sucuri",
+        statuses: &[403],
+        headers: &[("x-sucuri-block", "")],
+        body: &["Sucuri WebSite Firewall - Access Denied"],
+    },
+    BlockSignature {
+        // awselb and cloudfront serve their own 403s too, so neither says anything on its own
+        name: "AWS WAF",
+        statuses: &[202, 403, 405],
+        headers: &[("x-amzn-waf-*", "")],
+        body: &[
+            "Request blocked. We can't connect to the server for this app or website at this time.",
+        ],
+    },
+];
+
+/// implementation of BlockSignature
+impl BlockSignature {
+    /// determine whether or not the given response is this WAF's block page
+    pub fn matches(&self, response: &FeroxResponse) -> bool {
+        if !self.statuses.contains(&response.status().as_u16()) {
+            return false;
+        }
+
+        let header_match = self.headers.iter().any(|(wanted, expected)| {
+            response.headers().iter().any(|(name, value)| {
+                let name_match = match wanted.strip_suffix('*') {
+                    Some(prefix) => name.as_str().starts_with(prefix),
+                    None => name == wanted,
+                };
+
+                name_match
+                    && String::from_utf8_lossy(value.as_bytes())
+                        .to_lowercase()
+                        .contains(expected)
+            })
+        });
+
+        header_match
+            || self
+                .body
+                .iter()
+                .any(|needle| response.text().contains(needle))
+    }
+}
+
+/// Name of the WAF or CDN that generated the given response, if it looks like a block page
+pub fn detect(response: &FeroxResponse) -> Option<&'static str> {
+    BLOCK_SIGNATURES
+        .iter()
+        .find(|signature| signature.matches(response))
+        .map(|signature| signature.name)
+}
+
+/// Whether a scan that has seen the given number of blocked responses should warn the user;
+/// warnings are shown at 1, 10, 100, ... so that a blocked scan doesn't flood the terminal
+pub fn should_warn(blocked: usize) -> bool {
+    let mut threshold = 1;
+
+    while threshold < blocked {
+        threshold = threshold.saturating_mul(10);
+    }
+
+    threshold == blocked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::{
+        header::{HeaderMap, HeaderValue},
+        StatusCode,
+    };
+
+    #[test]
+    /// block pages should be recognized by body or header, but only with a matching status
+    fn detect_recognizes_block_pages() {
+        let mut resp = FeroxResponse::default();
+        resp.set_status(StatusCode::FORBIDDEN);
+        resp.set_text("<html>error code: 1020</html>");
+        assert_eq!(detect(&resp), Some("Cloudflare"));
+
+        resp.set_status(StatusCode::OK);
+        assert_eq!(detect(&resp), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Server", HeaderValue::from_static("AkamaiGHost"));
+
+        let mut resp = FeroxResponse::default();
+        resp.set_status(StatusCode::FORBIDDEN);
+        resp.set_headers(headers);
+        assert_eq!(detect(&resp), Some("Akamai"));
+
+        let mut resp = FeroxResponse::default();
+        resp.set_status(StatusCode::NOT_ACCEPTABLE);
+        resp.set_text("This error was generated by Mod_Security.");
+        assert_eq!(detect(&resp), Some("ModSecurity"));
+    }
+
+    #[test]
+    /// AWS WAF should only be recognized by its own headers or block page, not by the load
+    /// balancer or CDN in front of it
+    fn detect_requires_aws_waf_indicators() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Server", HeaderValue::from_static("awselb/2.0"));
+
+        let mut resp = FeroxResponse::default();
+        resp.set_status(StatusCode::FORBIDDEN);
+        resp.set_headers(headers.clone());
+        resp.set_text("Generated by cloudfront (CloudFront)");
+        assert_eq!(detect(&resp), None);
+
+        headers.insert("x-amzn-waf-action", HeaderValue::from_static("captcha"));
+        resp.set_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.set_headers(headers);
+        assert_eq!(detect(&resp), Some("AWS WAF"));
+
+        let mut resp = FeroxResponse::default();
+        resp.set_status(StatusCode::FORBIDDEN);
+        resp.set_text("<h2>Request blocked. We can't connect to the server for this app or website at this time.</h2>");
+        assert_eq!(detect(&resp), Some("AWS WAF"));
+    }
+
+    #[test]
+    /// a plain 403 from the target itself isn't a block
+    fn detect_ignores_ordinary_forbidden() {
+        let mut resp = FeroxResponse::default();
+        resp.set_status(StatusCode::FORBIDDEN);
+        resp.set_text("You don't have permission to access this resource.");
+        assert_eq!(detect(&resp), None);
+    }
+
+    #[test]
+    /// warnings should only be shown at powers of ten
+    fn should_warn_at_powers_of_ten() {
+        let warned: Vec<usize> = (0..=1000).filter(|n| should_warn(*n)).collect();
+        assert_eq!(warned, vec![1, 10, 100, 1000]);
+    }
+}
@@ -0,0 +1,689 @@
+//! memory-mapped wordlists
+//!
+//! a wordlist is mapped into memory once and shared by every directory scan; words are handed out
+//! as slices of the mapping, rather than each scan holding its own copy of every word. words are
+//! transformed (i.e. --url-encode-level) just before they're turned into urls
+//!
+//! wordlists read from stdin (-w -) are streamed instead; scans request words as they arrive, and
+//! wait for more until stdin is closed. --smart-wordlist streams words gathered from the target
+//! after the words of the mapped wordlist
+use std::{
+    borrow::Cow,
+    fmt::Write,
+    fs::File,
+    io::Read,
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use memmap2::Mmap;
+use openssl::sha::Sha256;
+use tokio::{io::AsyncRead, sync::Notify};
+use tokio_util::codec::{FramedRead, LinesCodec};
+
+/// -w value that streams the wordlist from stdin
+pub const STDIN_WORDLIST: &str = "-";
+
+/// Compact, general-purpose wordlist built into the binary; used when the default wordlist isn't
+/// installed, so that a scan can run without any wordlist on disk
+pub const EMBEDDED_WORDLIST: &str = include_str!("default-wordlist.txt");
+
+/// Bytes backing a `Wordlist`
+#[derive(Debug)]
+enum Source {
+    /// wordlist file mapped into memory
+    Mapped(Mmap),
+
+    /// wordlist held in memory, used for files that can't be mapped (empty files, FIFOs, etc...)
+    /// and tests
+    Owned(Vec<u8>),
+
+    /// wordlist built into the binary
+    Static(&'static [u8]),
+}
+
+/// implementation of Source
+impl Source {
+    /// the underlying bytes
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Source::Mapped(mmap) => &mmap[..],
+            Source::Owned(bytes) => bytes,
+            Source::Static(bytes) => bytes,
+        }
+    }
+}
+
+/// Default implementation for Source
+impl Default for Source {
+    /// an empty wordlist
+    fn default() -> Self {
+        Source::Owned(Vec::new())
+    }
+}
+
+/// Words of a streamed wordlist that have been read so far
+#[derive(Debug, Default)]
+struct Stream {
+    /// words in the order they were read; each one is requested by every directory scan for the
+    /// rest of the run, so they're kept for as long as the wordlist is
+    words: RwLock<Vec<String>>,
+
+    /// whether the end of the stream has been reached
+    ended: AtomicBool,
+
+    /// woken whenever a word is added, or the stream ends
+    changed: Notify,
+}
+
+/// A wordlist, where each word is a range of bytes within the (possibly mapped) source
+///
+/// empty lines, comments (lines starting with #), and lines that aren't valid utf-8 are skipped
+#[derive(Debug, Default)]
+pub struct Wordlist {
+    /// bytes of the entire wordlist
+    source: Source,
+
+    /// location of each word within `source`
+    words: Vec<Range<usize>>,
+
+    /// words added after the wordlist was created, when streaming (-w -, --smart-wordlist); they
+    /// come after the words in `source`
+    stream: Option<Stream>,
+}
+
+/// implementation of Wordlist
+impl Wordlist {
+    /// Map the wordlist at the given path into memory and index its words
+    ///
+    /// anything that can't be mapped (FIFOs, process substitution, /proc files, etc...) is read
+    /// into memory instead
+    pub fn from_file(path: &str) -> Result<Self> {
+        let mut file = File::open(&path).with_context(|| format!("Could not open {}", path))?;
+
+        let metadata = file
+            .metadata()
+            .with_context(|| format!("Could not read metadata of {}", path))?;
+
+        // empty files can't be mapped, and non-regular files may report a length of 0 while still
+        // having something to read
+        let mapped = if metadata.is_file() && metadata.len() > 0 {
+            // safety: the mapping is read-only; modifying or truncating the wordlist while a scan
+            // is running is undefined behavior, same as for any other memory-mapped file
+            match unsafe { Mmap::map(&file) } {
+                Ok(mmap) => Some(Source::Mapped(mmap)),
+                Err(e) => {
+                    log::debug!("Could not map {}, reading it instead: {}", path, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let source = match mapped {
+            Some(source) => source,
+            None => {
+                let mut bytes = Vec::new();
+                file.read_to_end(&mut bytes)
+                    .with_context(|| format!("Could not read {}", path))?;
+                Source::Owned(bytes)
+            }
+        };
+
+        Ok(Self::index(source))
+    }
+
+    /// The wordlist built into the binary, see `EMBEDDED_WORDLIST`
+    pub fn embedded() -> Self {
+        Self::index(Source::Static(EMBEDDED_WORDLIST.as_bytes()))
+    }
+
+    /// Create a wordlist from words already in memory
+    pub fn from_words<S: AsRef<str>>(words: &[S]) -> Self {
+        let mut bytes = Vec::new();
+
+        for word in words {
+            bytes.extend_from_slice(word.as_ref().as_bytes());
+            bytes.push(b'\n');
+        }
+
+        Self::index(Source::Owned(bytes))
+    }
+
+    /// find the location of each word within the given source
+    fn index(source: Source) -> Self {
+        let bytes = source.bytes();
+        let mut words = Vec::new();
+        let mut start = 0;
+
+        while start < bytes.len() {
+            let newline = bytes[start..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(bytes.len(), |offset| start + offset);
+
+            let mut end = newline;
+
+            if end > start && bytes[end - 1] == b'\r' {
+                end -= 1;
+            }
+
+            let line = &bytes[start..end];
+
+            if !line.is_empty() && line[0] != b'#' && std::str::from_utf8(line).is_ok() {
+                words.push(start..end);
+            }
+
+            start = newline + 1;
+        }
+
+        Self {
+            source,
+            words,
+            stream: None,
+        }
+    }
+
+    /// Create an empty wordlist that words are added to over time; see `push` and `end`
+    pub fn streaming() -> Self {
+        Self::default().extendable()
+    }
+
+    /// Allow words to be added after the ones already in the wordlist; see `push` and `end`
+    pub fn extendable(mut self) -> Self {
+        self.stream = Some(Stream::default());
+        self
+    }
+
+    /// Stream the wordlist from the given reader (i.e. stdin), one word per line; words can be
+    /// requested as soon as they're read
+    pub fn from_reader<R>(reader: R) -> Arc<Self>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let wordlist = Arc::new(Self::streaming());
+        let streamed = wordlist.clone();
+
+        tokio::spawn(async move {
+            let mut lines = FramedRead::new(reader, LinesCodec::new());
+
+            while let Some(line) = lines.next().await {
+                match line {
+                    Ok(line) => streamed.push(&line),
+                    Err(e) => {
+                        log::warn!("Could not read from the streamed wordlist: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            streamed.end();
+        });
+
+        wordlist
+    }
+
+    /// Whether or not the wordlist is streamed, i.e. words are added to it over time
+    pub fn is_streaming(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Add a word to a streamed wordlist; same rules as a wordlist file, empty lines and comments
+    /// are skipped
+    pub fn push(&self, line: &str) {
+        let stream = match &self.stream {
+            Some(stream) => stream,
+            None => return,
+        };
+
+        let word = line.trim_end_matches('\r');
+
+        if word.is_empty() || word.starts_with('#') {
+            return;
+        }
+
+        if let Ok(mut words) = stream.words.write() {
+            words.push(word.to_string());
+        }
+
+        stream.changed.notify_waiters();
+    }
+
+    /// Mark the end of a streamed wordlist; scans that are waiting for more words finish
+    pub fn end(&self) {
+        if let Some(stream) = &self.stream {
+            stream.ended.store(true, Ordering::Release);
+            stream.changed.notify_waiters();
+        }
+    }
+
+    /// Wait until there's a word at the given index; false when there never will be
+    pub async fn wait_for(&self, index: usize) -> bool {
+        let stream = match &self.stream {
+            Some(stream) => stream,
+            None => return index < self.len(),
+        };
+
+        loop {
+            // registered before checking, so that a word added in between isn't missed
+            let changed = stream.changed.notified();
+
+            if index < self.len() {
+                return true;
+            }
+
+            if stream.ended.load(Ordering::Acquire) {
+                return false;
+            }
+
+            changed.await;
+        }
+    }
+
+    /// Number of words in the wordlist; for streamed wordlists, the number added so far
+    pub fn len(&self) -> usize {
+        let streamed = self.stream.as_ref().map_or(0, |stream| {
+            stream.words.read().map_or(0, |words| words.len())
+        });
+
+        self.words.len() + streamed
+    }
+
+    /// Number of words the wordlist was created with, not counting any streamed words
+    pub fn fixed_len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Whether or not the wordlist is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Word at the given index, if any; words of the source are borrowed, streamed words are
+    /// copied out from behind their lock
+    pub fn get(&self, index: usize) -> Option<Cow<'_, str>> {
+        let range = match self.words.get(index) {
+            Some(range) => range.clone(),
+            None => {
+                let stream = self.stream.as_ref()?;
+                return stream
+                    .words
+                    .read()
+                    .ok()?
+                    .get(index - self.words.len())
+                    .map(|word| Cow::Owned(word.clone()));
+            }
+        };
+
+        // every indexed word was validated as utf-8 in `index`
+        std::str::from_utf8(&self.source.bytes()[range])
+            .ok()
+            .map(Cow::Borrowed)
+    }
+
+    /// Iterate over all words in the wordlist
+    pub fn iter(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        (0..self.len()).filter_map(move |index| self.get(index))
+    }
+
+    /// Index of every word, in a pseudo-random order determined entirely by `seed` (--shuffle);
+    /// streamed words aren't included, they're requested in the order they were added
+    ///
+    /// the same seed always gives the same order, which is what allows a resumed scan to request
+    /// words in the same order as the scan it was resumed from
+    pub fn shuffled(&self, seed: u64) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.fixed_len()).collect();
+        let mut state = seed;
+
+        // fisher-yates
+        for i in (1..order.len()).rev() {
+            let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+            order.swap(i, j);
+        }
+
+        order
+    }
+
+    /// The words the wordlist was created with, one per line; streamed words aren't included
+    ///
+    /// comments, empty lines, and line endings are normalized away, so two files with the same
+    /// words give the same contents
+    pub fn contents(&self) -> String {
+        let mut contents = String::new();
+
+        for word in (0..self.fixed_len()).filter_map(|index| self.get(index)) {
+            contents.push_str(&word);
+            contents.push('\n');
+        }
+
+        contents
+    }
+
+    /// Hex encoded sha256 of `contents`, used to tell whether a resumed scan is using the same
+    /// wordlist as the scan it was resumed from
+    pub fn sha256(&self) -> String {
+        let mut hasher = Sha256::new();
+
+        for word in (0..self.fixed_len()).filter_map(|index| self.get(index)) {
+            hasher.update(word.as_bytes());
+            hasher.update(b"\n");
+        }
+
+        hasher
+            .finish()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// Seed used to shuffle the words requested from the given url (--shuffle); each directory gets
+/// its own order, derived from --seed
+///
+/// the url is hashed with FNV-1a rather than one of std's hashers, whose output isn't guaranteed
+/// to stay the same between releases
+pub fn directory_seed(seed: u64, url: &str) -> u64 {
+    let hash = url.bytes().fold(0xCBF2_9CE4_8422_2325, |hash: u64, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3)
+    });
+
+    let mut state = seed ^ hash;
+    splitmix64(&mut state)
+}
+
+/// splitmix64 pseudo-random number generator; kept in-house rather than using an rng crate, whose
+/// output may change between versions, since seeds outlive the process in state files
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// How wordlist entries are percent-encoded before being requested (--url-encode-level)
+///
+/// every character other than ascii letters, digits, and `/` is encoded, so that things like
+/// `.git` or `wp-admin` slip past filters that match on the literal word
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum UrlEncodeLevel {
+    /// words are requested as-is
+    None,
+
+    /// `.git` -> `%2Egit`
+    Standard,
+
+    /// `.git` -> `%252Egit`, for servers that decode twice
+    Double,
+
+    /// like standard, but every other escape uses lowercase hex, i.e. `%2Ewp%2dadmin`
+    MixedCase,
+}
+
+/// implementation of UrlEncodeLevel
+impl UrlEncodeLevel {
+    /// convert the value of --url-encode-level into a UrlEncodeLevel; anything unknown is none
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "standard" => UrlEncodeLevel::Standard,
+            "double" => UrlEncodeLevel::Double,
+            "mixed-case" => UrlEncodeLevel::MixedCase,
+            _ => UrlEncodeLevel::None,
+        }
+    }
+
+    /// Encode the given word; words without anything to encode are returned unchanged
+    pub fn encode<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        let needs_encoding = |b: &u8| !b.is_ascii_alphanumeric() && *b != b'/';
+
+        if *self == UrlEncodeLevel::None || !word.bytes().any(|b| needs_encoding(&b)) {
+            return Cow::Borrowed(word);
+        }
+
+        let mut encoded = String::with_capacity(word.len() * 3);
+        let mut escapes = 0;
+
+        for byte in word.bytes() {
+            if !needs_encoding(&byte) {
+                encoded.push(byte as char);
+                continue;
+            }
+
+            // writing to a String can't fail
+            let _ = match self {
+                UrlEncodeLevel::Double => write!(encoded, "%25{:02X}", byte),
+                UrlEncodeLevel::MixedCase if escapes % 2 == 1 => write!(encoded, "%{:02x}", byte),
+                _ => write!(encoded, "%{:02X}", byte),
+            };
+
+            escapes += 1;
+        }
+
+        Cow::Owned(encoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    /// comments, empty lines, and invalid utf-8 should be skipped; line endings stripped
+    fn wordlist_from_file_skips_unwanted_lines() {
+        let mut tmp = tempfile::NamedTempFile::new().unwrap();
+        tmp.write_all(b"# comment\nadmin\r\n\nbackup\n\xff\xfe\nlogin")
+            .unwrap();
+
+        let wordlist = Wordlist::from_file(tmp.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(wordlist.len(), 3);
+        assert_eq!(
+            wordlist.iter().collect::<Vec<_>>(),
+            vec!["admin", "backup", "login"]
+        );
+        assert_eq!(wordlist.get(3).as_deref(), None);
+    }
+
+    #[test]
+    /// empty files can't be mapped, but are still valid (empty) wordlists
+    fn wordlist_from_empty_file() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+
+        let wordlist = Wordlist::from_file(tmp.path().to_str().unwrap()).unwrap();
+
+        assert!(wordlist.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// FIFOs (i.e. -w <(cat words.txt)) can't be mapped, but should still be read
+    fn wordlist_from_fifo() {
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("words");
+
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let writer_path = fifo.clone();
+        let writer = std::thread::spawn(move || {
+            std::fs::write(writer_path, "admin\nbackup\n").unwrap();
+        });
+
+        let wordlist = Wordlist::from_file(fifo.to_str().unwrap()).unwrap();
+        writer.join().unwrap();
+
+        assert_eq!(wordlist.iter().collect::<Vec<_>>(), vec!["admin", "backup"]);
+    }
+
+    #[test]
+    /// words already in memory should be indexed the same way as those read from a file
+    fn wordlist_from_words() {
+        let wordlist = Wordlist::from_words(&["one", "", "two"]);
+
+        assert_eq!(wordlist.iter().collect::<Vec<_>>(), vec!["one", "two"]);
+    }
+
+    #[test]
+    /// the hash should cover the words themselves, not comments or line endings
+    fn wordlist_sha256_ignores_formatting() {
+        let mut tmp = tempfile::NamedTempFile::new().unwrap();
+        tmp.write_all(b"# comment\none\r\n\ntwo").unwrap();
+
+        let from_file = Wordlist::from_file(tmp.path().to_str().unwrap()).unwrap();
+        let from_words = Wordlist::from_words(&["one", "two"]);
+
+        assert_eq!(from_file.contents(), "one\ntwo\n");
+        assert_eq!(from_file.sha256(), from_words.sha256());
+        assert_ne!(from_file.sha256(), Wordlist::from_words(&["two"]).sha256());
+        assert_eq!(from_file.sha256().len(), 64);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// streamed words should be available as they're added, and waiting should stop at the end
+    async fn wordlist_streaming_waits_for_words() {
+        let wordlist = Arc::new(Wordlist::streaming());
+        assert!(wordlist.is_streaming());
+
+        wordlist.push("admin");
+        wordlist.push("# comment");
+        wordlist.push("");
+        assert!(wordlist.wait_for(0).await);
+        assert_eq!(wordlist.get(0).as_deref(), Some("admin"));
+
+        let waiting = {
+            let wordlist = wordlist.clone();
+            tokio::spawn(async move { wordlist.wait_for(1).await })
+        };
+
+        wordlist.push("backup\r");
+        assert!(waiting.await.unwrap());
+        assert_eq!(wordlist.get(1).as_deref(), Some("backup"));
+
+        let waiting = {
+            let wordlist = wordlist.clone();
+            tokio::spawn(async move { wordlist.wait_for(2).await })
+        };
+
+        wordlist.end();
+        assert!(!waiting.await.unwrap());
+        assert_eq!(wordlist.len(), 2);
+    }
+
+    #[test]
+    /// streamed words should come after the words the wordlist was created with, and only those
+    /// should be shuffled
+    fn wordlist_extendable_appends_words() {
+        let wordlist = Wordlist::from_words(&["one", "two", "three"]).extendable();
+        wordlist.push("four");
+        wordlist.push("five");
+
+        assert_eq!(wordlist.fixed_len(), 3);
+        assert_eq!(wordlist.len(), 5);
+        assert_eq!(
+            wordlist.iter().collect::<Vec<_>>(),
+            vec!["one", "two", "three", "four", "five"]
+        );
+
+        let mut order = wordlist.shuffled(42);
+        order.sort_unstable();
+        assert_eq!(order, vec![0, 1, 2]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// every line of the reader should end up in the wordlist
+    async fn wordlist_from_reader_streams_lines() {
+        let wordlist = Wordlist::from_reader(&b"one\ntwo\n# three\nfour"[..]);
+
+        let mut position = 0;
+        while wordlist.wait_for(position).await {
+            position += 1;
+        }
+
+        assert_eq!(
+            wordlist.iter().collect::<Vec<_>>(),
+            vec!["one", "two", "four"]
+        );
+    }
+
+    #[test]
+    /// the built-in wordlist should be usable, and free of duplicates
+    fn wordlist_embedded_has_unique_words() {
+        let wordlist = Wordlist::embedded();
+        let mut words: Vec<Cow<str>> = wordlist.iter().collect();
+
+        assert!(words.len() > 100);
+        assert!(words.contains(&Cow::Borrowed("admin")));
+
+        words.sort_unstable();
+        words.dedup();
+        assert_eq!(words.len(), wordlist.len());
+    }
+
+    #[test]
+    /// shuffling should produce a permutation of every index, determined only by the seed
+    fn wordlist_shuffled_is_deterministic_permutation() {
+        let words: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+        let wordlist = Wordlist::from_words(&words);
+
+        let order = wordlist.shuffled(31337);
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+
+        assert_eq!(sorted, (0..100).collect::<Vec<_>>());
+        assert_ne!(order, sorted);
+        assert_eq!(order, wordlist.shuffled(31337));
+        assert_ne!(order, wordlist.shuffled(31338));
+        assert!(Wordlist::default().shuffled(1).is_empty());
+    }
+
+    #[test]
+    /// each directory should get its own seed, and the same one every time
+    fn directory_seed_is_fixed_per_url() {
+        let seed = directory_seed(31337, "http://localhost/");
+
+        assert_eq!(seed, directory_seed(31337, "http://localhost/"));
+        assert_ne!(seed, directory_seed(31337, "http://localhost/js/"));
+        assert_ne!(seed, directory_seed(31338, "http://localhost/"));
+        // pinned, so a change in the hash (which would break resumed scans) is caught
+        assert_eq!(seed, 7_501_522_935_201_948_047);
+    }
+
+    #[test]
+    /// a missing file should result in an error
+    fn wordlist_from_missing_file_errors() {
+        assert!(Wordlist::from_file("/nonexistent/wordlist.txt").is_err());
+    }
+
+    #[test]
+    /// each --url-encode-level should encode everything but letters, digits, and slashes
+    fn url_encode_levels() {
+        let word = ".git/wp-admin";
+
+        assert_eq!(UrlEncodeLevel::from_name("none").encode(word), word);
+        assert_eq!(
+            UrlEncodeLevel::from_name("standard").encode(word),
+            "%2Egit/wp%2Dadmin"
+        );
+        assert_eq!(
+            UrlEncodeLevel::from_name("double").encode(word),
+            "%252Egit/wp%252Dadmin"
+        );
+        assert_eq!(
+            UrlEncodeLevel::from_name("mixed-case").encode("a.b-c_d"),
+            "a%2Eb%2dc%5Fd"
+        );
+        assert_eq!(UrlEncodeLevel::from_name("double").encode("admin"), "admin");
+        assert_eq!(UrlEncodeLevel::from_name("standard").encode("ü"), "%C3%BC");
+    }
+}
@@ -0,0 +1,173 @@
+//! per-directory wordlists (--wordlist-rules)
+//!
+//! directories found through recursion are scanned with the wordlist of the first rule whose
+//! pattern matches the directory's path, instead of the wordlist passed via -w; i.e. directories
+//! below /api/ can get a list of api routes, while those below /static/ get a list of file names
+use std::fs::read_to_string;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use reqwest::Url;
+use serde::Deserialize;
+
+/// A pattern, and the wordlist used for directories whose path matches it
+#[derive(Debug, Clone)]
+pub struct WordlistRule {
+    /// pattern as written in the rules file, i.e. /api/*
+    pub pattern: String,
+
+    /// path (or name, see `wordlists`) of the wordlist
+    pub wordlist: String,
+
+    /// `pattern`, compiled
+    compiled: Regex,
+}
+
+/// implementation of WordlistRule
+impl WordlistRule {
+    /// Create a rule; `*` in the pattern matches any run of characters, `/` included, and
+    /// everything else is matched literally
+    pub fn new(pattern: &str, wordlist: &str) -> Result<Self> {
+        let expression = pattern
+            .trim_end_matches('/')
+            .split('*')
+            .map(regex::escape)
+            .collect::<Vec<_>>()
+            .join(".*");
+
+        let compiled = Regex::new(&format!("^{}/?$", expression))
+            .with_context(|| format!("Invalid wordlist rule pattern {}", pattern))?;
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            wordlist: wordlist.to_string(),
+            compiled,
+        })
+    }
+
+    /// Whether the rule applies to the directory at the given url; only the path is matched
+    pub fn is_match(&self, url: &str) -> bool {
+        match Url::parse(url) {
+            // directories aren't always found with their trailing slash, /api should match /api/*
+            Ok(parsed) if parsed.path().ends_with('/') => self.compiled.is_match(parsed.path()),
+            Ok(parsed) => self.compiled.is_match(&format!("{}/", parsed.path())),
+            Err(_) => false,
+        }
+    }
+}
+
+/// One rule in a --wordlist-rules file
+#[derive(Debug, Deserialize)]
+struct UserRule {
+    /// pattern matched against a directory's path
+    pattern: String,
+
+    /// wordlist used for matching directories
+    wordlist: String,
+}
+
+/// Layout of a --wordlist-rules file
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    /// every rule in the file
+    #[serde(default)]
+    rules: Vec<UserRule>,
+}
+
+/// Read the rules file passed in via --wordlist-rules
+///
+/// the file is expected to be toml, where each rule is an entry in the `rules` array; rules are
+/// checked in the order they're written, i.e.
+///
+/// ```toml
+/// [[rules]]
+/// pattern = "/api/*"
+/// wordlist = "/wordlists/api-routes.txt"
+///
+/// [[rules]]
+/// pattern = "*/static/*"
+/// wordlist = "seclists/raft-small"
+/// ```
+pub fn read_wordlist_rules(path: &str) -> Result<Vec<WordlistRule>> {
+    log::trace!("enter: read_wordlist_rules({})", path);
+
+    let contents = read_to_string(path).with_context(|| format!("Could not open {}", path))?;
+    let file: RulesFile =
+        toml::from_str(&contents).with_context(|| format!("Could not parse {}", path))?;
+
+    let rules = file
+        .rules
+        .iter()
+        .map(|rule| WordlistRule::new(&rule.pattern, &rule.wordlist))
+        .collect::<Result<Vec<_>>>()?;
+
+    log::trace!("exit: read_wordlist_rules -> {} rules", rules.len());
+    Ok(rules)
+}
+
+/// First of the given rules that applies to the directory at the given url
+pub fn find<'a>(rules: &'a [WordlistRule], url: &str) -> Option<&'a WordlistRule> {
+    rules.iter().find(|rule| rule.is_match(url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// * should match anything, including nested directories, and the rest should be literal
+    fn wordlist_rule_matches_paths() {
+        let rule = WordlistRule::new("/api/*", "api.txt").unwrap();
+
+        assert!(rule.is_match("http://localhost/api"));
+        assert!(rule.is_match("http://localhost/api/users/"));
+        assert!(rule.is_match("http://localhost/api/v1/users"));
+        assert!(!rule.is_match("http://localhost/app/api/users/"));
+        assert!(!rule.is_match("http://localhost/apix/users/"));
+
+        let rule = WordlistRule::new("*/static/*", "files.txt").unwrap();
+        assert!(rule.is_match("http://localhost/app/static/img/"));
+        assert!(rule.is_match("http://localhost/static"));
+        assert!(!rule.is_match("http://localhost/statics/"));
+
+        let rule = WordlistRule::new("/v1.0/", "v1.txt").unwrap();
+        assert!(rule.is_match("http://localhost/v1.0"));
+        assert!(!rule.is_match("http://localhost/v100/"));
+    }
+
+    #[test]
+    /// rules should be read in order, and the first matching rule should win
+    fn read_wordlist_rules_keeps_order() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            r#"
+            [[rules]]
+            pattern = "/api/admin/*"
+            wordlist = "/wordlists/admin.txt"
+
+            [[rules]]
+            pattern = "/api/*"
+            wordlist = "/wordlists/api.txt"
+            "#,
+        )
+        .unwrap();
+
+        let rules = read_wordlist_rules(tmp.path().to_str().unwrap()).unwrap();
+        assert_eq!(rules.len(), 2);
+
+        let found = find(&rules, "http://localhost/api/admin/users/").unwrap();
+        assert_eq!(found.wordlist, "/wordlists/admin.txt");
+
+        let found = find(&rules, "http://localhost/api/users/").unwrap();
+        assert_eq!(found.wordlist, "/wordlists/api.txt");
+
+        assert!(find(&rules, "http://localhost/static/").is_none());
+    }
+
+    #[test]
+    /// read_wordlist_rules should error on a missing file
+    fn read_wordlist_rules_errors_on_missing_file() {
+        assert!(read_wordlist_rules("/nonexistent/wordlist-rules.toml").is_err());
+    }
+}
@@ -0,0 +1,372 @@
+//! wordlists subcommand; download common wordlists into the data directory, where -w can then
+//! find them by name (ex: -w seclists/raft-medium)
+//!
+//! when the default wordlist (a SecLists install) doesn't exist, its downloaded copy is used
+//! instead, and failing that the wordlist built into the binary; so that a SecLists install isn't
+//! needed to scan with the defaults
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::{Client, Proxy};
+
+use crate::{
+    config::Configuration, progress::PROGRESS_PRINTER, wordlist::Wordlist, DEFAULT_WORDLIST,
+    VERSION,
+};
+
+/// where SecLists' files are downloaded from
+const SECLISTS_URL: &str =
+    "https://raw.githubusercontent.com/danielmiessler/SecLists/master/Discovery/Web-Content";
+
+/// name of the downloaded copy of `DEFAULT_WORDLIST`
+const DEFAULT_NAME: &str = "seclists/raft-medium";
+
+/// Wordlist that can be downloaded by name
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Entry {
+    /// name used with fetch and -w, i.e. seclists/raft-medium
+    pub name: &'static str,
+
+    /// file name within SecLists' Discovery/Web-Content directory
+    pub file: &'static str,
+
+    /// short description, shown by list
+    pub description: &'static str,
+}
+
+/// implementation of Entry
+impl Entry {
+    /// where the wordlist is downloaded from
+    pub fn url(&self) -> String {
+        format!("{}/{}", SECLISTS_URL, self.file)
+    }
+
+    /// where the downloaded wordlist is kept, within the given data directory
+    pub fn path_in(&self, data_dir: &Path) -> PathBuf {
+        data_dir.join(format!("{}.txt", self.name))
+    }
+}
+
+/// every wordlist that can be downloaded by name
+pub static CATALOG: [Entry; 6] = [
+    Entry {
+        name: "seclists/raft-small",
+        file: "raft-small-directories.txt",
+        description: "raft directories, ~20k words",
+    },
+    Entry {
+        name: "seclists/raft-medium",
+        file: "raft-medium-directories.txt",
+        description: "raft directories, ~30k words (the default wordlist)",
+    },
+    Entry {
+        name: "seclists/raft-large",
+        file: "raft-large-directories.txt",
+        description: "raft directories, ~62k words",
+    },
+    Entry {
+        name: "seclists/common",
+        file: "common.txt",
+        description: "common files and directories, ~4.7k words",
+    },
+    Entry {
+        name: "seclists/quickhits",
+        file: "quickhits.txt",
+        description: "commonly exposed sensitive files, ~2.5k words",
+    },
+    Entry {
+        name: "seclists/directory-list-2.3-medium",
+        file: "directory-list-2.3-medium.txt",
+        description: "DirBuster's medium directory list, ~220k words",
+    },
+];
+
+/// Catalog entry with the given name
+pub fn find(name: &str) -> Option<&'static Entry> {
+    CATALOG.iter().find(|entry| entry.name == name)
+}
+
+/// Where the words of a -w value come from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolved {
+    /// file at the given path
+    File(String),
+
+    /// wordlist built into the binary, in place of a default wordlist that isn't installed
+    Embedded,
+}
+
+/// implementation of Resolved
+impl Resolved {
+    /// Read the wordlist's words
+    pub fn load(&self) -> Result<Wordlist> {
+        match self {
+            Resolved::File(path) => Wordlist::from_file(path),
+            Resolved::Embedded => Ok(Wordlist::embedded()),
+        }
+    }
+}
+
+/// Directory into which wordlists are downloaded, i.e. ~/.local/share/feroxbuster/wordlists
+pub fn data_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Couldn't find a data directory"))?;
+    Ok(data_dir.join("feroxbuster").join("wordlists"))
+}
+
+/// Source of the given -w value, within the given data directory; see `resolve`
+fn resolve_in(data_dir: &Path, wordlist: &str, given: bool) -> Result<Resolved> {
+    if Path::new(wordlist).exists() {
+        return Ok(Resolved::File(wordlist.to_string()));
+    }
+
+    // a default that was chosen explicitly is a path like any other
+    let default = !given && wordlist == DEFAULT_WORDLIST;
+
+    let name = if default { DEFAULT_NAME } else { wordlist };
+
+    let entry = match find(name) {
+        Some(entry) => entry,
+        None => return Ok(Resolved::File(wordlist.to_string())),
+    };
+
+    let path = entry.path_in(data_dir);
+
+    if path.exists() {
+        Ok(Resolved::File(path.to_string_lossy().to_string()))
+    } else if default {
+        // neither SecLists nor its downloaded copy
+        Ok(Resolved::Embedded)
+    } else {
+        bail!(
+            "{} hasn't been downloaded yet, run: feroxbuster wordlists fetch {}",
+            name,
+            name
+        )
+    }
+}
+
+/// Source of the given -w value; files that exist are used as-is, names from the catalog are
+/// looked up in the data directory, and the default wordlist falls back to its downloaded copy,
+/// then to the built-in wordlist
+///
+/// `given` is whether the wordlist was chosen by the user; only a wordlist that was left at its
+/// default falls back, so that an explicit -w for a missing file is still an error
+pub fn resolve(wordlist: &str, given: bool) -> Result<Resolved> {
+    match data_dir() {
+        Ok(data_dir) => resolve_in(&data_dir, wordlist, given),
+        // nowhere anything could have been downloaded to
+        Err(_) if !given && wordlist == DEFAULT_WORDLIST && !Path::new(wordlist).exists() => {
+            Ok(Resolved::Embedded)
+        }
+        Err(_) => Ok(Resolved::File(wordlist.to_string())),
+    }
+}
+
+/// Download the wordlist at `url` to `path`; it's written next to `path` first, so that an
+/// interrupted download isn't mistaken for a complete one
+async fn download(client: &Client, url: &str, path: &Path) -> Result<usize> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Could not download {}", url))?;
+
+    if !response.status().is_success() {
+        bail!("Could not download {}: {}", url, response.status());
+    }
+
+    let body = response.bytes().await?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Could not create {}", parent.display()))?;
+    }
+
+    let partial = path.with_extension("part");
+    fs::write(&partial, &body).with_context(|| format!("Could not write {}", partial.display()))?;
+    fs::rename(&partial, path).with_context(|| format!("Could not write {}", path.display()))?;
+
+    Ok(body.split(|byte| *byte == b'\n').count())
+}
+
+/// One line per catalog entry: name, whether it's been downloaded, and its description
+fn format_catalog(data_dir: &Path) -> Vec<String> {
+    CATALOG
+        .iter()
+        .map(|entry| {
+            let status = if entry.path_in(data_dir).exists() {
+                "fetched"
+            } else {
+                "-"
+            };
+
+            format!("{:36} {:8} {}", entry.name, status, entry.description)
+        })
+        .collect()
+}
+
+/// List or fetch wordlists, as given on the command line
+pub async fn run(config: Arc<Configuration>) -> Result<()> {
+    log::trace!(
+        "enter: run({}, {:?})",
+        config.wordlists,
+        config.wordlist_names
+    );
+
+    let data_dir = data_dir()?;
+
+    if config.wordlists == "list" {
+        for line in format_catalog(&data_dir) {
+            PROGRESS_PRINTER.println(line);
+        }
+
+        log::trace!("exit: run");
+        return Ok(());
+    }
+
+    // check every name before downloading anything
+    let mut entries = Vec::new();
+
+    for name in &config.wordlist_names {
+        match find(name) {
+            Some(entry) => entries.push(entry),
+            None => bail!("Unknown wordlist {}; see: feroxbuster wordlists list", name),
+        }
+    }
+
+    let mut builder = Client::builder().user_agent(format!("feroxbuster/{}", VERSION));
+
+    if !config.proxy.is_empty() {
+        builder = builder.proxy(Proxy::all(&config.proxy)?);
+    }
+
+    let client = builder.build()?;
+
+    for entry in entries {
+        let path = entry.path_in(&data_dir);
+        let lines = download(&client, &entry.url(), &path).await?;
+
+        PROGRESS_PRINTER.println(format!(
+            "Fetched {} ({} lines) to {}",
+            entry.name,
+            lines,
+            path.display()
+        ));
+    }
+
+    log::trace!("exit: run");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::{Method::GET, MockServer};
+    use tempfile::TempDir;
+
+    #[test]
+    /// names should be unique, and the default wordlist should be in the catalog
+    fn catalog_names_are_unique() {
+        for (i, entry) in CATALOG.iter().enumerate() {
+            assert_eq!(find(entry.name), Some(&CATALOG[i]));
+        }
+
+        assert!(DEFAULT_WORDLIST.ends_with(find(DEFAULT_NAME).unwrap().file));
+        assert_eq!(find("seclists/nope"), None);
+    }
+
+    #[test]
+    /// files are used as-is, names are looked up, and names that weren't fetched are reported
+    fn resolve_in_finds_fetched_wordlists() {
+        let tmp_dir = TempDir::new().unwrap();
+        let existing = tmp_dir.path().join("words.txt");
+        fs::write(&existing, "admin").unwrap();
+        let existing = existing.to_string_lossy().to_string();
+
+        assert_eq!(
+            resolve_in(tmp_dir.path(), &existing, true).unwrap(),
+            Resolved::File(existing.clone())
+        );
+        assert_eq!(
+            resolve_in(tmp_dir.path(), "/no/such/file", true).unwrap(),
+            Resolved::File(String::from("/no/such/file"))
+        );
+        assert!(resolve_in(tmp_dir.path(), "seclists/common", true)
+            .unwrap_err()
+            .to_string()
+            .contains("wordlists fetch seclists/common"));
+
+        let fetched = find("seclists/common").unwrap().path_in(tmp_dir.path());
+        fs::create_dir_all(fetched.parent().unwrap()).unwrap();
+        fs::write(&fetched, "admin").unwrap();
+
+        assert_eq!(
+            resolve_in(tmp_dir.path(), "seclists/common", true).unwrap(),
+            Resolved::File(fetched.to_string_lossy().to_string())
+        );
+    }
+
+    #[test]
+    /// a missing default wordlist should fall back to its downloaded copy, if there is one, and
+    /// to the built-in wordlist otherwise
+    fn resolve_in_falls_back_for_default() {
+        if Path::new(DEFAULT_WORDLIST).exists() {
+            // SecLists is installed, nothing to fall back from
+            return;
+        }
+
+        let tmp_dir = TempDir::new().unwrap();
+        assert_eq!(
+            resolve_in(tmp_dir.path(), DEFAULT_WORDLIST, false).unwrap(),
+            Resolved::Embedded
+        );
+
+        // the same path given with -w is only a path
+        assert_eq!(
+            resolve_in(tmp_dir.path(), DEFAULT_WORDLIST, true).unwrap(),
+            Resolved::File(DEFAULT_WORDLIST.to_string())
+        );
+
+        let fetched = find(DEFAULT_NAME).unwrap().path_in(tmp_dir.path());
+        fs::create_dir_all(fetched.parent().unwrap()).unwrap();
+        fs::write(&fetched, "admin").unwrap();
+
+        assert_eq!(
+            resolve_in(tmp_dir.path(), DEFAULT_WORDLIST, false).unwrap(),
+            Resolved::File(fetched.to_string_lossy().to_string())
+        );
+        assert_eq!(
+            resolve_in(tmp_dir.path(), DEFAULT_WORDLIST, true).unwrap(),
+            Resolved::File(DEFAULT_WORDLIST.to_string())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    /// a downloaded wordlist should end up at its path, with no partial file left behind
+    async fn download_writes_wordlist() {
+        let srv = MockServer::start();
+
+        srv.mock(|when, then| {
+            when.method(GET).path("/common.txt");
+            then.status(200).body("admin\nlogin\nbackup");
+        });
+
+        let tmp_dir = TempDir::new().unwrap();
+        let path = find("seclists/common").unwrap().path_in(tmp_dir.path());
+
+        let lines = download(&Client::new(), &srv.url("/common.txt"), &path)
+            .await
+            .unwrap();
+
+        assert_eq!(lines, 3);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "admin\nlogin\nbackup");
+        assert!(!path.with_extension("part").exists());
+        assert!(format_catalog(tmp_dir.path())
+            .iter()
+            .any(|line| line.starts_with("seclists/common") && line.contains("fetched")));
+    }
+}
@@ -0,0 +1,242 @@
+//! SOAP endpoint detection
+//!
+//! with --wsdl, once something that looks like a SOAP endpoint (Service.asmx, Service.svc, /soap,
+//! etc...) is discovered, its ?wsdl is requested; a WSDL document describes every operation the service
+//! accepts, so their names are reported alongside the endpoint. WSDL documents found directly by
+//! the scan are parsed the same way
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::{StatusCode, Url};
+
+use crate::{
+    event_handlers::{
+        Command::{self, AddToUsizeField},
+        Handles,
+    },
+    response::FeroxResponse,
+    scanner::RateGate,
+    send_command,
+    statistics::StatField::TotalExpected,
+    utils::logged_request,
+};
+
+/// extensions of SOAP endpoints, i.e. Service.asmx
+const ENDPOINT_EXTENSIONS: [&str; 3] = ["asmx", "svc", "jws"];
+
+/// last path segments of SOAP endpoints, i.e. /api/soap
+const ENDPOINT_NAMES: [&str; 5] = ["soap", "ws", "services", "webservice", "webservices"];
+
+/// namespaces that identify a WSDL 1.1 or 2.0 document
+const WSDL_NAMESPACES: [&str; 2] = ["schemas.xmlsoap.org/wsdl", "www.w3.org/ns/wsdl"];
+
+lazy_static! {
+    /// operation elements, whatever their namespace prefix, i.e. <wsdl:operation name="GetUser">
+    static ref OPERATION: Regex =
+        Regex::new(r#"<(?:[\w-]+:)?operation\b[^>]*?\bname\s*=\s*["']([^"']+)["']"#).unwrap();
+
+    /// service element, i.e. <wsdl:service name="UserService">
+    static ref SERVICE: Regex =
+        Regex::new(r#"<(?:[\w-]+:)?service\b[^>]*?\bname\s*=\s*["']([^"']+)["']"#).unwrap();
+
+    /// endpoints whose ?wsdl was already requested (or that were WSDL documents themselves)
+    static ref PROBED: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// A WSDL document, and the operations it describes
+#[derive(Debug, Clone)]
+pub struct Wsdl {
+    /// url of the WSDL document, i.e. http://localhost/Service.asmx?wsdl
+    pub url: String,
+
+    /// name of the service, empty if the document doesn't name one
+    pub service: String,
+
+    /// names of the operations, in the order they're described
+    pub operations: Vec<String>,
+}
+
+/// Display implementation for Wsdl
+impl fmt::Display for Wsdl {
+    /// url, service, and operations, i.e.
+    /// http://localhost/Users.asmx?wsdl UserService (2 operations: GetUser, DeleteUser)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.url)?;
+
+        if !self.service.is_empty() {
+            write!(f, " {}", self.service)?;
+        }
+
+        write!(
+            f,
+            " ({} operations: {})",
+            self.operations.len(),
+            self.operations.join(", ")
+        )
+    }
+}
+
+/// Determine whether the given body is a WSDL document
+pub fn is_wsdl(text: &str) -> bool {
+    (text.contains("definitions") && WSDL_NAMESPACES.iter().any(|ns| text.contains(ns)))
+        || (text.contains("<description") && text.contains(WSDL_NAMESPACES[1]))
+}
+
+/// Determine whether the given url looks like a SOAP endpoint, going by its last path segment
+pub fn is_endpoint(url: &Url) -> bool {
+    let last = match url
+        .path()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .map(str::to_lowercase)
+    {
+        Some(last) => last,
+        None => return false,
+    };
+
+    if ENDPOINT_NAMES.contains(&last.as_str()) {
+        return true;
+    }
+
+    last.rsplit_once('.').map_or(false, |(_, extension)| {
+        ENDPOINT_EXTENSIONS.contains(&extension)
+    })
+}
+
+/// Name of the service and its operations, without repeats (they're listed once per binding)
+pub fn parse(text: &str) -> (String, Vec<String>) {
+    let service = SERVICE
+        .captures(text)
+        .map(|captures| captures[1].to_string())
+        .unwrap_or_default();
+
+    let mut operations = Vec::new();
+
+    for captures in OPERATION.captures_iter(text) {
+        let name = captures[1].to_string();
+
+        if !operations.contains(&name) {
+            operations.push(name);
+        }
+    }
+
+    (service, operations)
+}
+
+/// Determine whether the given response should be looked into for a WSDL document; anything that
+/// answered with a 404 isn't an endpoint
+pub fn should_probe(response: &FeroxResponse) -> bool {
+    response.status() != &StatusCode::NOT_FOUND
+        && (is_endpoint(response.url()) || is_wsdl(response.text()))
+}
+
+/// Find the WSDL document of the endpoint that gave the given response (or the response itself,
+/// if it's a WSDL document), reporting its operations; the document is requested once the gate
+/// allows it, and nothing is probed more than once
+pub async fn probe(response: FeroxResponse, gate: RateGate, handles: Arc<Handles>) {
+    log::trace!("enter: probe({})", response.url());
+
+    let mut endpoint = response.url().clone();
+    endpoint.set_query(None);
+    endpoint.set_fragment(None);
+
+    match PROBED.lock() {
+        Ok(mut guard) => {
+            if !guard.insert(endpoint.to_string()) {
+                return;
+            }
+        }
+        Err(_) => return,
+    }
+
+    let (url, text) = if is_wsdl(response.text()) {
+        (response.url().clone(), response.text().to_string())
+    } else {
+        let mut url = endpoint;
+        url.set_query(Some("wsdl"));
+
+        send_command!(handles.stats.tx, AddToUsizeField(TotalExpected, 1));
+
+        gate.wait().await;
+
+        let document = match logged_request(&url, handles.clone()).await {
+            Ok(document) => FeroxResponse::from(document, true, handles.config.output_level).await,
+            Err(_) => return,
+        };
+
+        if !document.status().is_success() || !is_wsdl(document.text()) {
+            log::trace!("exit: probe");
+            return;
+        }
+
+        (url, document.text().to_string())
+    };
+
+    let (service, operations) = parse(&text);
+
+    let wsdl = Wsdl {
+        url: url.to_string(),
+        service,
+        operations,
+    };
+
+    log::info!("wsdl found at {}", wsdl);
+
+    if let Err(e) = handles.output.send(Command::ReportWsdl(Box::new(wsdl))) {
+        log::warn!("Could not send wsdl to output handler: {}", e);
+    }
+
+    log::trace!("exit: probe");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// asmx/svc/jws files and soap-ish directories should be probed, other paths shouldn't
+    fn is_endpoint_checks_last_segment() {
+        let is = |url: &str| is_endpoint(&Url::parse(url).unwrap());
+
+        assert!(is("http://localhost/Users.asmx"));
+        assert!(is("http://localhost/api/Orders.SVC"));
+        assert!(is("http://localhost/axis/Version.jws"));
+        assert!(is("http://localhost/api/soap/"));
+        assert!(!is("http://localhost/soap/index.php"));
+        assert!(!is("http://localhost/"));
+    }
+
+    #[test]
+    /// operations should be listed once, whatever their prefix, along with the service's name
+    fn parse_finds_service_and_operations() {
+        let text = r#"<?xml version="1.0"?>
+            <wsdl:definitions xmlns:wsdl="http://schemas.xmlsoap.org/wsdl/">
+              <wsdl:portType name="UserPort">
+                <wsdl:operation name="GetUser"/>
+                <wsdl:operation name="DeleteUser"/>
+              </wsdl:portType>
+              <wsdl:binding name="UserBinding" type="tns:UserPort">
+                <wsdl:operation name="GetUser"><soap:operation soapAction="GetUser"/></wsdl:operation>
+                <wsdl:operation name="DeleteUser"/>
+              </wsdl:binding>
+              <wsdl:service name="UserService"/>
+            </wsdl:definitions>"#;
+
+        assert!(is_wsdl(text));
+        assert_eq!(
+            parse(text),
+            (
+                String::from("UserService"),
+                vec![String::from("GetUser"), String::from("DeleteUser")]
+            )
+        );
+
+        assert!(!is_wsdl("<html>Service definitions</html>"));
+    }
+}
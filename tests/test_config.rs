@@ -25,3 +25,27 @@ fn read_in_config_file_for_settings() -> Result<(), Box<dyn std::error::Error>>
 
     Ok(())
 }
+
+#[test]
+/// a seed from the config file without --shuffle should only be warned about, the config file
+/// isn't subject to the command line's requirement that --seed comes with --shuffle
+fn config_file_seed_without_shuffle_warns() -> Result<(), Box<dyn std::error::Error>> {
+    let (tmp_dir, file) = setup_tmp_directory(&["seed = 31337".to_string()], "ferox-config.toml")?;
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .current_dir(&tmp_dir)
+        .arg("--url")
+        .arg("http://localhost")
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "WARNING: seed: --seed only applies when --shuffle is used",
+        ));
+
+    teardown_tmp_directory(tmp_dir);
+
+    Ok(())
+}
@@ -153,3 +153,50 @@ fn main_parallel_spawns_children() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+/// the completions subcommand should print a script for the given shell, without scanning
+fn main_completions_prints_script() {
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("completions")
+        .arg("bash")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("_feroxbuster()").and(predicate::str::contains("--wordlist")),
+        );
+}
+
+#[test]
+/// the manpage subcommand should print a roff document listing the options
+fn main_manpage_prints_roff() {
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("manpage")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::starts_with(".TH FEROXBUSTER 1")
+                .and(predicate::str::contains("\\-\\-wordlist")),
+        );
+}
+
+#[test]
+/// --low-memory can't be scheduled, since each scheduled scan is compared to the one before it;
+/// the problem should be caught before anything is scheduled
+fn main_rejects_low_memory_with_schedule() {
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg("http://localhost")
+        .arg("--low-memory")
+        .arg("--schedule")
+        .arg("0 3 * * *")
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "low_memory: can't be used with schedule",
+        ));
+}
@@ -430,3 +430,71 @@ fn auto_tune_slows_scan_with_general_errors() {
     assert!(error_mock.hits() <= 180); // may or may not see all other error requests
     assert!(start.elapsed().as_millis() >= 7000); // scan should hit time limit due to limiting
 }
+
+#[test]
+/// --tarpit-latency should abandon a directory whose responses are consistently slower than the
+/// given threshold, reporting it as such
+fn tarpit_latency_abandons_slow_directory() {
+    let srv = MockServer::start();
+    let words: Vec<String> = (0..200).map(|i| format!("slow{}", i)).collect();
+    let (tmp_dir, file) = setup_tmp_directory(&words, "wordlist").unwrap();
+
+    let slow_mock = srv.mock(|when, then| {
+        when.method(GET)
+            .path_matches(Regex::new("/slow[0-9]+").unwrap());
+        then.status(404).delay(Duration::from_millis(500));
+    });
+
+    let cmd = Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--tarpit-latency")
+        .arg("250")
+        .arg("--threads")
+        .arg("10")
+        .unwrap();
+
+    teardown_tmp_directory(tmp_dir);
+
+    let stdout = String::from_utf8_lossy(&cmd.stdout);
+    assert!(stdout.contains("abandoned (tarpit)"));
+    assert!(stdout.contains("over the last 30 requests"));
+    assert!(slow_mock.hits() < 200);
+}
+
+#[test]
+/// responses that look like a WAF's block page should be reported, at 1, 10, 100... per scan
+fn waf_block_pages_are_reported() {
+    let srv = MockServer::start();
+    let words: Vec<String> = (0..12).map(|i| format!("blocked{}", i)).collect();
+    let (tmp_dir, file) = setup_tmp_directory(&words, "wordlist").unwrap();
+
+    let blocked_mock = srv.mock(|when, then| {
+        when.method(GET)
+            .path_matches(Regex::new("/blocked[0-9]+").unwrap());
+        then.status(403)
+            .body("<html><title>Access denied</title>error code: 1020</html>");
+    });
+
+    let cmd = Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--threads")
+        .arg("1")
+        .unwrap();
+
+    teardown_tmp_directory(tmp_dir);
+
+    let stdout = String::from_utf8_lossy(&cmd.stdout);
+    assert!(stdout.contains("blocked (waf)"));
+    assert!(stdout.contains("Cloudflare; 1 blocked in this scan"));
+    assert!(stdout.contains("Cloudflare; 10 blocked in this scan"));
+    assert!(!stdout.contains("Cloudflare; 2 blocked in this scan"));
+    assert_eq!(blocked_mock.hits(), 12);
+}
@@ -6,7 +6,10 @@ use predicates::prelude::*;
 use std::fs::{read_to_string, write};
 use std::path::Path;
 use std::time;
-use utils::{setup_tmp_directory, teardown_tmp_directory};
+use utils::{
+    config_json, response_json, scan_json, setup_state_file, setup_tmp_directory,
+    teardown_tmp_directory,
+};
 
 #[test]
 /// pass a known serialized scan with 1 scan complete and 1 not. expect the incomplete scan to
@@ -90,6 +93,46 @@ fn resume_scan_works() {
     assert_eq!(not_scanned_yet.hits(), 1);
 }
 
+#[test]
+/// resume from a state file built with the canned state helpers; expect the unfinished scan to
+/// request every word, and only the response that wasn't already known to be reported
+fn resume_scan_from_canned_state_file() {
+    let srv = MockServer::start();
+    let (tmp_dir, file) =
+        setup_tmp_directory(&["known".to_string(), "unknown".to_string()], "wordlist").unwrap();
+
+    let known = srv.mock(|when, then| {
+        when.method(GET).path("/known");
+        then.status(200).body("already seen");
+    });
+
+    let unknown = srv.mock(|when, then| {
+        when.method(GET).path("/unknown");
+        then.status(200).body("never seen");
+    });
+
+    let (tmp_dir2, state_file) = setup_state_file(
+        &[scan_json(&srv.url("/"), "NotStarted")],
+        &config_json(&file, &srv.url("/")),
+        &[response_json(&srv.url("/known"), 200)],
+    )
+    .unwrap();
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--resume-from")
+        .arg(state_file.as_os_str())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/unknown").and(predicate::str::contains("/known").not()));
+
+    teardown_tmp_directory(tmp_dir);
+    teardown_tmp_directory(tmp_dir2);
+
+    assert_eq!(known.hits(), 1);
+    assert_eq!(unknown.hits(), 1);
+}
+
 #[test]
 /// kick off scan with a time limit;  
 fn time_limit_enforced_when_specified() {
@@ -128,3 +171,40 @@ fn time_limit_enforced_when_specified() {
 
     teardown_tmp_directory(tmp_dir);
 }
+
+#[test]
+/// kick off scan with a per-directory time limit; expect the scan to stop early, without the
+/// whole process being killed like it is with --time-limit
+fn scan_time_limit_enforced_when_specified() {
+    let srv = MockServer::start();
+    let (tmp_dir, file) =
+        setup_tmp_directory(&["css".to_string(), "stuff".to_string()], "wordlist").unwrap();
+
+    // ensure the command would run long enough by adding crap to the wordlist
+    let more_words = read_to_string(Path::new("tests/extra-words")).unwrap();
+    write(&file, more_words).unwrap();
+
+    let now = time::Instant::now();
+    let lower_bound = time::Duration::new(3, 0);
+    let upper_bound = time::Duration::new(10, 0);
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--scan-time-limit")
+        .arg("3s")
+        .arg("--threads")
+        .arg("4")
+        .arg("--dont-filter")
+        .assert()
+        .success();
+
+    // --scan-time-limit is 3 seconds; the rest of the wordlist is abandoned, after which the
+    // process exits normally
+    assert!(now.elapsed() > lower_bound && now.elapsed() < upper_bound);
+
+    teardown_tmp_directory(tmp_dir);
+}
@@ -1,8 +1,9 @@
 mod utils;
 use assert_cmd::prelude::*;
-use httpmock::Method::GET;
+use httpmock::Method::{CONNECT, DELETE, GET, HEAD, OPTIONS, POST};
 use httpmock::MockServer;
 use predicates::prelude::*;
+use regex::Regex;
 use std::{process::Command, time};
 use utils::{setup_tmp_directory, teardown_tmp_directory};
 
@@ -37,6 +38,146 @@ fn scanner_single_request_scan() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+/// stream the wordlist from stdin (-w -), expect each word read to be requested
+fn scanner_streamed_wordlist_scan() {
+    let srv = MockServer::start();
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200).body("this is a test");
+    });
+
+    let other_mock = srv.mock(|when, then| {
+        when.method(GET).path("/README");
+        then.status(404);
+    });
+
+    assert_cmd::Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg("-")
+        .write_stdin("LICENSE\n# comment\nREADME\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/LICENSE").and(predicate::str::contains("200")));
+
+    assert_eq!(mock.hits(), 1);
+    assert_eq!(other_mock.hits(), 1);
+}
+
+#[test]
+/// spider the target with --smart-wordlist, expect words from its pages to be requested
+fn scanner_smart_wordlist_scan() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    srv.mock(|when, then| {
+        when.method(GET).path("/");
+        then.status(200)
+            .header("Content-Type", "text/html")
+            .body(r#"<html><body>Welcome <a href="/about">about us</a></body></html>"#);
+    });
+
+    srv.mock(|when, then| {
+        when.method(GET).path("/about");
+        then.status(200)
+            .header("Content-Type", "text/html")
+            .body("<html><body><p>Invoices live in the billingportal</p></body></html>");
+    });
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/billingportal");
+        then.status(200).body("this is a test");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--smart-wordlist")
+        .arg("--no-recursion")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/billingportal"));
+
+    assert_eq!(mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// recurse into a directory matched by --wordlist-rules, expect it to be scanned with the rule's
+/// wordlist instead of -w's
+fn scanner_wordlist_rules_scan() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["api".to_string()], "wordlist")?;
+
+    let api_words = tmp_dir.path().join("api-routes");
+    std::fs::write(&api_words, "users\n")?;
+
+    let rules = tmp_dir.path().join("wordlist-rules.toml");
+    std::fs::write(
+        &rules,
+        format!(
+            "[[rules]]\npattern = \"/api/*\"\nwordlist = {:?}\n",
+            api_words.to_string_lossy()
+        ),
+    )?;
+
+    srv.mock(|when, then| {
+        when.method(GET).path("/api");
+        then.status(301).header("Location", &srv.url("/api/"));
+    });
+
+    let users_mock = srv.mock(|when, then| {
+        when.method(GET).path("/api/users");
+        then.status(200).body("this is a test");
+    });
+
+    let api_api_mock = srv.mock(|when, then| {
+        when.method(GET).path("/api/api");
+        then.status(200).body("this is a test");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--wordlist-rules")
+        .arg(rules.as_os_str())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/api/users"));
+
+    assert_eq!(users_mock.hits(), 1);
+    assert_eq!(api_api_mock.hits(), 0);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// -w - and --stdin both read from stdin, expect an error
+fn scanner_streamed_wordlist_conflicts_with_stdin() {
+    assert_cmd::Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--stdin")
+        .arg("--wordlist")
+        .arg("-")
+        .write_stdin("http://localhost\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "-w - and --stdin can't both read from stdin",
+        ));
+}
+
 #[test]
 /// send a valid request, follow redirects into new directories, expect 301/200 responses
 fn scanner_recursive_request_scan() -> Result<(), Box<dyn std::error::Error>> {
@@ -163,6 +304,108 @@ fn scanner_recursive_request_scan_using_only_success_responses(
     Ok(())
 }
 
+#[test]
+/// send a single valid request, get a response, and write only its url to disk
+fn scanner_single_request_scan_with_urls_file() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200).body("this is a test");
+    });
+
+    let outfile = tmp_dir.path().join("urls");
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--urls-file")
+        .arg(outfile.as_os_str())
+        .unwrap();
+
+    let contents = std::fs::read_to_string(outfile)?;
+
+    assert_eq!(contents, format!("{}\n", srv.url("/LICENSE")));
+
+    assert_eq!(mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// re-running a scan with the same --output should only append new results
+fn scanner_rerun_with_same_output_file_dedups() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200).body("this is a test");
+    });
+
+    let outfile = tmp_dir.path().join("output");
+
+    for _ in 0..2 {
+        Command::cargo_bin("feroxbuster")
+            .unwrap()
+            .arg("--url")
+            .arg(srv.url("/"))
+            .arg("--wordlist")
+            .arg(file.as_os_str())
+            .arg("-o")
+            .arg(outfile.as_os_str())
+            .unwrap();
+    }
+
+    let contents = std::fs::read_to_string(outfile)?;
+
+    assert_eq!(contents.matches(&srv.url("/LICENSE")).count(), 1);
+
+    assert_eq!(mock.hits(), 2);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --dry-run should write the urls that would be requested without requesting any of them
+fn scanner_dry_run_sends_no_requests() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET);
+        then.status(200).body("this is a test");
+    });
+
+    let outfile = tmp_dir.path().join("output");
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--extensions")
+        .arg("php")
+        .arg("--dry-run")
+        .arg("-o")
+        .arg(outfile.as_os_str())
+        .unwrap();
+
+    let contents = std::fs::read_to_string(outfile)?;
+
+    assert!(contents.contains(&srv.url("/LICENSE\n")));
+    assert!(contents.contains(&srv.url("/LICENSE.php\n")));
+
+    assert_eq!(mock.hits(), 0);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
 #[test]
 /// send a single valid request, get a response, and write it to disk
 fn scanner_single_request_scan_with_file_output() -> Result<(), Box<dyn std::error::Error>> {
@@ -508,6 +751,50 @@ fn scanner_single_request_scan_with_debug_logging_as_json() {
     teardown_tmp_directory(tmp_dir);
 }
 
+#[test]
+/// --debug-log-json should write JSON log entries without --json, and --debug-log-max-size should
+/// rotate the debug log
+fn scanner_single_request_scan_with_rotated_json_debug_logging() {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist").unwrap();
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200).body("this is a test");
+    });
+
+    let outfile = tmp_dir.path().join("debug.log");
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("-vvvv")
+        .arg("--debug-log")
+        .arg(outfile.as_os_str())
+        .arg("--debug-log-json")
+        .arg("--debug-log-max-size")
+        .arg("16kb")
+        .arg("--debug-log-backups")
+        .arg("2")
+        .unwrap();
+
+    let contents = std::fs::read_to_string(&outfile).unwrap();
+    assert!(contents
+        .lines()
+        .all(|line| line.starts_with("{\"type\":\"log\"")));
+    assert!(contents.contains("\"timestamp\":"));
+    assert!(contents.len() <= 16 * 1024);
+
+    assert!(tmp_dir.path().join("debug.log.1").exists());
+    assert!(!tmp_dir.path().join("debug.log.3").exists());
+
+    assert_eq!(mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+}
+
 #[test]
 /// send a single valid request, filter the response by regex, expect one out of 2 urls
 fn scanner_single_request_scan_with_regex_filtered_result() {
@@ -638,3 +925,1164 @@ fn rate_limit_enforced_when_specified() {
 
     teardown_tmp_directory(tmp_dir);
 }
+
+#[test]
+/// with --favicon, the favicon should be requested and reported with its hash, titles should be
+/// shown as well
+fn scanner_reports_favicon_hash_and_title() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200)
+            .body("<html><head><title>Some License</title></head></html>");
+    });
+
+    let favicon_mock = srv.mock(|when, then| {
+        when.method(GET).path("/favicon.ico");
+        then.status(200).body("hello");
+    });
+
+    let cmd = Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--favicon")
+        .unwrap();
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("/LICENSE \"Some License\"").and(
+            predicate::str::contains("/favicon.ico (favicon hash: 1155597304)"),
+        ));
+
+    assert_eq!(mock.hits(), 1);
+    assert_eq!(favicon_mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// the favicon shouldn't be requested without --favicon, and shouldn't be reported when filtered
+fn scanner_favicon_is_opt_in_and_filtered() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let favicon_mock = srv.mock(|when, then| {
+        when.method(GET).path("/favicon.ico");
+        then.status(200).body("hello");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .assert()
+        .success();
+
+    assert_eq!(favicon_mock.hits(), 0);
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--favicon")
+        .arg("--filter-size")
+        .arg("5")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("favicon.ico").not());
+
+    assert_eq!(favicon_mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --shuffle changes the order of requests, not which are sent; expect every word requested once
+fn scanner_shuffle_requests_every_word_once() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let words: Vec<String> = (0..20).map(|i| format!("word{}", i)).collect();
+    let (tmp_dir, file) = setup_tmp_directory(&words, "wordlist")?;
+
+    let found = srv.mock(|when, then| {
+        when.method(GET).path("/word7");
+        then.status(200).body("this is a test");
+    });
+
+    let missing = srv.mock(|when, then| {
+        when.method(GET)
+            .path_matches(Regex::new("/word([0-689]|1[0-9])$").unwrap());
+        then.status(404);
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--dont-filter")
+        .arg("--shuffle")
+        .arg("--seed")
+        .arg("31337")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/word7").and(predicate::str::contains("200")))
+        .stderr(predicate::str::contains("Shuffle Seed").and(predicate::str::contains("31337")));
+
+    assert_eq!(found.hits(), 1);
+    assert_eq!(missing.hits(), 19);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --decoy-ratio 2 with 10 words; expect 5 decoy requests for the target or what was found there,
+/// none of which are reported
+fn scanner_decoy_ratio_sends_unreported_decoys() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let mut words: Vec<String> = (0..9).map(|i| format!("missing{}", i)).collect();
+    words.push("LICENSE".to_string());
+    let (tmp_dir, file) = setup_tmp_directory(&words, "wordlist")?;
+
+    let root = srv.mock(|when, then| {
+        when.method(GET).path("/");
+        then.status(200).body("home");
+    });
+
+    let license = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200)
+            .body(r#"<link href="style.css" rel="stylesheet">"#);
+    });
+
+    let style = srv.mock(|when, then| {
+        when.method(GET).path("/style.css");
+        then.status(200).body("body {}");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--decoy-ratio")
+        .arg("2")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("/LICENSE").and(predicate::str::contains("style.css").not()),
+        );
+
+    // the initial connectivity check of the target, the brute force request for LICENSE, plus one
+    // decoy for every 2 words
+    assert_eq!(root.hits() + license.hits() + style.hits(), 7);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --hashes sha256 shows the sha256 of each body alongside its url; expect the hash of the body
+fn scanner_hashes_shows_body_hash() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200).body("hello");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--hashes")
+        .arg("sha256")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "/LICENSE (sha256: 2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824)",
+        ));
+
+    assert_eq!(mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --fingerprint-db with the sha256 of a response's body; expect the url annotated with the file
+fn scanner_fingerprint_db_identifies_known_file() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["jquery.js".to_string()], "wordlist")?;
+    let (db_dir, db) = setup_tmp_directory(
+        &[
+            "[[files]]".to_string(),
+            "name = \"jquery\"".to_string(),
+            "version = \"3.5.1\"".to_string(),
+            "sha256 = \"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824\""
+                .to_string(),
+        ],
+        "known-files.toml",
+    )?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/jquery.js");
+        then.status(200).body("hello");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--fingerprint-db")
+        .arg(db.as_os_str())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "/jquery.js (known file: jquery 3.5.1)",
+        ));
+
+    assert_eq!(mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    teardown_tmp_directory(db_dir);
+    Ok(())
+}
+
+#[test]
+/// --show-preview should show the start of the body, whitespace collapsed, next to the url
+fn scanner_show_preview_shows_start_of_body() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["admin".to_string()], "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/admin");
+        then.status(200)
+            .body("<html>\n  <head>\n    <title>Sign in</title>\n  </head>\n</html>");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--show-preview")
+        .arg("21")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "/admin \"Sign in\" (preview: <html> <head> <title>)",
+        ));
+
+    assert_eq!(mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --output-dir should give the target a subdirectory with its results and bodies
+fn scanner_output_dir_writes_per_target_results() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200).body("this is a test");
+    });
+
+    let output_dir = tmp_dir.path().join("results");
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--output-dir")
+        .arg(output_dir.as_os_str())
+        .unwrap();
+
+    let target_dir = output_dir.join(format!("http_127_0_0_1_{}", srv.port()));
+
+    let text = std::fs::read_to_string(target_dir.join("results.txt"))?;
+    let json = std::fs::read_to_string(target_dir.join("results.json"))?;
+    let body = std::fs::read_to_string(target_dir.join("bodies").join("LICENSE"))?;
+
+    assert!(text.contains(&srv.url("/LICENSE")));
+    assert!(json.contains(&format!(r#""url":"{}""#, srv.url("/LICENSE"))));
+    assert_eq!(body, "this is a test");
+
+    assert_eq!(mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --targets should scan each target with the options given alongside it, and only those
+fn scanner_targets_file_applies_per_target_options() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+    let (targets_dir, targets) = setup_tmp_directory(
+        &[
+            "# one target per line".to_string(),
+            format!("{} -H 'X-Target: a'", srv.url("/a/")),
+            srv.url("/b/"),
+        ],
+        "targets.txt",
+    )?;
+
+    let mock_a = srv.mock(|when, then| {
+        when.method(GET).path("/a/LICENSE").header("X-Target", "a");
+        then.status(200).body("this is a test");
+    });
+
+    let mock_b = srv.mock(|when, then| {
+        when.method(GET).path("/b/LICENSE");
+        then.status(200).body("this is also a test");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--targets")
+        .arg(targets.as_os_str())
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/a/LICENSE").and(predicate::str::contains("/b/LICENSE")));
+
+    assert_eq!(mock_a.hits(), 1);
+    assert_eq!(mock_b.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    teardown_tmp_directory(targets_dir);
+    Ok(())
+}
+
+#[test]
+/// --target should probe the given address and port, and scan the web server found there
+fn scanner_target_range_finds_and_scans_web_server() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200).body("this is a test");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--target")
+        .arg(format!("127.0.0.1/32:{}", srv.port()))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "http://127.0.0.1:{}/LICENSE",
+            srv.port()
+        )));
+
+    assert_eq!(mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --bypass-403 should report a rewrite header that gets past a 403, but not its baseline
+fn scanner_bypass_403_reports_working_technique() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["admin".to_string()], "wordlist")?;
+
+    let forbidden = srv.mock(|when, then| {
+        when.method(GET).path("/admin");
+        then.status(403).body("go away");
+    });
+
+    let bypassed = srv.mock(|when, then| {
+        when.method(GET)
+            .path("/")
+            .header("X-Original-URL", "/admin");
+        then.status(200).body("welcome, admin");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--bypass-403")
+        .arg("--bypass-techniques")
+        .arg("rewrite-headers")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("bypassed")
+                .and(predicate::str::contains("403 => 200 (rewrite-headers: GET"))
+                .and(predicate::str::contains("X-Original-URL: /admin"))
+                .and(predicate::str::contains("X-Rewrite-URL").not()),
+        );
+
+    assert_eq!(forbidden.hits(), 1);
+    assert_eq!(bypassed.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --verb-compare should request discovered resources with HEAD and OPTIONS, and report the
+/// methods disclosed by the Allow header
+fn scanner_verb_compare_reports_allowed_methods() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["api".to_string()], "wordlist")?;
+
+    let get = srv.mock(|when, then| {
+        when.method(GET).path("/api");
+        then.status(200).body("hello there!");
+    });
+
+    let head = srv.mock(|when, then| {
+        when.method(HEAD).path("/api");
+        then.status(405);
+    });
+
+    let options = srv.mock(|when, then| {
+        when.method(OPTIONS).path("/api");
+        then.status(204).header("Allow", "GET, PUT, DELETE");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--verb-compare")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("/api")
+                .and(predicate::str::contains("(methods: HEAD 405; OPTIONS 204;"))
+                .and(predicate::str::contains("OPTIONS allows GET, PUT, DELETE")),
+        );
+
+    assert_eq!(get.hits(), 1);
+    assert_eq!(head.hits(), 1);
+    assert_eq!(options.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --cors should request reported resources from another origin, and report those whose policy
+/// reflects it with credentials allowed
+fn scanner_cors_reports_reflected_origin() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["me".to_string()], "wordlist")?;
+
+    let check = srv.mock(|when, then| {
+        when.method(GET)
+            .path("/me")
+            .header("Origin", "https://evil.example");
+        then.status(200)
+            .header("Access-Control-Allow-Origin", "https://evil.example")
+            .header("Access-Control-Allow-Credentials", "true")
+            .body("{\"user\": \"admin\"}");
+    });
+
+    let get = srv.mock(|when, then| {
+        when.method(GET).path("/me");
+        then.status(200).body("{\"user\": \"admin\"}");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--cors")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("/me").and(predicate::str::contains(
+                "(cors: origin reflected with credentials)",
+            )),
+        );
+
+    assert_eq!(check.hits(), 1);
+    assert_eq!(get.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+/// --on-found should run the given command for each reported result, with its url and status
+fn scanner_on_found_runs_command() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) =
+        setup_tmp_directory(&["LICENSE".to_string(), "admin".to_string()], "wordlist")?;
+    let found = tmp_dir.path().join("found.log");
+
+    srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200).body("this is a test");
+    });
+
+    srv.mock(|when, then| {
+        when.method(GET).path("/admin");
+        then.status(403);
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--on-found")
+        .arg(format!("echo {{status}} {{url}} >> {}", found.display()))
+        .assert()
+        .success();
+
+    let mut lines: Vec<String> = std::fs::read_to_string(&found)?
+        .lines()
+        .map(String::from)
+        .collect();
+    lines.sort();
+
+    assert_eq!(
+        lines,
+        vec![
+            format!("200 {}", srv.url("/LICENSE")),
+            format!("403 {}", srv.url("/admin")),
+        ]
+    );
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// a GraphQL endpoint that answers an introspection query should be reported, with its schema
+/// saved to --output-dir
+fn scanner_graphql_introspection_reports_schema() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["graphql".to_string()], "wordlist")?;
+
+    let endpoint = srv.mock(|when, then| {
+        when.method(GET).path("/graphql");
+        then.status(405)
+            .body(r#"{"errors": [{"message": "GraphQL only supports POST requests."}]}"#);
+    });
+
+    let introspection = srv.mock(|when, then| {
+        when.method(POST)
+            .path("/graphql")
+            .header("Content-Type", "application/json")
+            .body_contains("__schema");
+        then.status(200).body(
+            r#"{"data": {"__schema": {"queryType": {"name": "Query"}, "types": [{"kind": "OBJECT", "name": "Query"}]}}}"#,
+        );
+    });
+
+    let output_dir = tmp_dir.path().join("results");
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--output-dir")
+        .arg(output_dir.as_os_str())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("introspection (graphql)")
+                .and(predicate::str::contains("(1 types; schema saved to"))
+                .and(predicate::str::contains("GraphQL introspection")),
+        );
+
+    let schema_file = output_dir
+        .join(format!("http_127_0_0_1_{}", srv.port()))
+        .join("graphql")
+        .join("graphql.json");
+    let schema = std::fs::read_to_string(schema_file)?;
+
+    assert!(schema.contains(r#""queryType""#));
+    assert_eq!(endpoint.hits(), 1);
+    assert_eq!(introspection.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --api should request the paths documented by a swagger spec with each of their methods, and
+/// flag what the wordlist finds that the spec doesn't document
+fn scanner_api_requests_documented_paths() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["admin".to_string()], "wordlist")?;
+
+    let spec = srv.mock(|when, then| {
+        when.method(GET).path("/swagger.json");
+        then.status(200).body(
+            r#"{"swagger": "2.0", "paths": {"/users/{id}": {"get": {}, "delete": {}}, "/login": {"post": {}}}}"#,
+        );
+    });
+
+    let delete = srv.mock(|when, then| {
+        when.method(DELETE).path("/users/1");
+        then.status(204);
+    });
+
+    let users = srv.mock(|when, then| {
+        when.method(GET).path("/users/1");
+        then.status(200).body(r#"{"id": 1, "name": "admin"}"#);
+    });
+
+    let login = srv.mock(|when, then| {
+        when.method(POST).path("/login");
+        then.status(200).body(r#"{"token": "abc"}"#);
+    });
+
+    let admin = srv.mock(|when, then| {
+        when.method(GET).path("/admin");
+        then.status(200).body("admin panel");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--api")
+        .arg("--no-recursion")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(srv.url("/users/1"))
+                .and(predicate::str::contains(srv.url("/login")))
+                .and(predicate::str::contains(format!(
+                    "{} (undocumented)",
+                    srv.url("/admin")
+                )))
+                .and(
+                    predicate::str::contains(format!("{} (undocumented)", srv.url("/users/1")))
+                        .not(),
+                ),
+        );
+
+    assert_eq!(spec.hits(), 1);
+    assert_eq!(users.hits(), 1);
+    assert_eq!(delete.hits(), 1);
+    assert_eq!(login.hits(), 1);
+    assert_eq!(admin.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --api should put a spec through the filters before reporting it, and still request its paths
+fn scanner_api_filters_spec() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["admin".to_string()], "wordlist")?;
+
+    let spec = srv.mock(|when, then| {
+        when.method(GET).path("/swagger.json");
+        then.status(200)
+            .body(r#"{"swagger": "2.0", "paths": {"/users": {"get": {}}}}"#);
+    });
+
+    let users = srv.mock(|when, then| {
+        when.method(GET).path("/users");
+        then.status(200).body(r#"[{"id": 1}]"#);
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--api")
+        .arg("--no-recursion")
+        .arg("--filter-regex")
+        .arg("swagger")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(srv.url("/users"))
+                .and(predicate::str::contains(srv.url("/swagger.json")).not()),
+        );
+
+    assert_eq!(spec.hits(), 1);
+    assert_eq!(users.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// with --cloud-storage, a bucket referenced by a reported page should be checked using a
+/// path-style url; the check goes through --proxy, where it shows up as a CONNECT to the provider
+fn scanner_cloud_storage_checks_buckets() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["index.html".to_string()], "wordlist")?;
+
+    let page = srv.mock(|when, then| {
+        when.method(GET).path("/index.html");
+        then.status(200)
+            .body(r#"<img src="https://media.example.com.s3.amazonaws.com/logo.png">"#);
+    });
+
+    let path_style = srv.mock(|when, then| {
+        when.method(CONNECT).header("host", "s3.amazonaws.com:443");
+        then.status(502);
+    });
+
+    let virtual_hosted = srv.mock(|when, then| {
+        when.method(CONNECT)
+            .header("host", "media.example.com.s3.amazonaws.com:443");
+        then.status(502);
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--proxy")
+        .arg(srv.url("/"))
+        .arg("--cloud-storage")
+        .assert()
+        .success();
+
+    assert_eq!(page.hits(), 1);
+    assert_eq!(path_style.hits(), 1);
+    assert_eq!(virtual_hosted.hits(), 0);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// without --cloud-storage, buckets referenced by a reported page shouldn't be checked
+fn scanner_cloud_storage_is_opt_in() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["index.html".to_string()], "wordlist")?;
+
+    let page = srv.mock(|when, then| {
+        when.method(GET).path("/index.html");
+        then.status(200)
+            .body(r#"<img src="https://s3.amazonaws.com/assets/logo.png">"#);
+    });
+
+    let check = srv.mock(|when, then| {
+        when.method(CONNECT).header("host", "s3.amazonaws.com:443");
+        then.status(502);
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--proxy")
+        .arg(srv.url("/"))
+        .arg("--extract-links")
+        .assert()
+        .success();
+
+    assert_eq!(page.hits(), 1);
+    assert_eq!(check.hits(), 0);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// with --wsdl, a discovered .asmx file should have its ?wsdl requested, and the operations it
+/// lists reported
+fn scanner_wsdl_reports_soap_operations() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["Users.asmx".to_string()], "wordlist")?;
+
+    // the more specific mock goes first, so that it's the one matched by /Users.asmx?wsdl
+    let wsdl = srv.mock(|when, then| {
+        when.method(GET)
+            .path("/Users.asmx")
+            .query_param_exists("wsdl");
+        then.status(200).body(
+            r#"<wsdl:definitions xmlns:wsdl="http://schemas.xmlsoap.org/wsdl/">
+                <wsdl:portType name="UserSoap"><wsdl:operation name="GetUser"/></wsdl:portType>
+                <wsdl:service name="UserService"/>
+            </wsdl:definitions>"#,
+        );
+    });
+
+    let endpoint = srv.mock(|when, then| {
+        when.method(GET).path("/Users.asmx");
+        then.status(200)
+            .body("<html>UserService Web Service</html>");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--wsdl")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("wsdl (soap)").and(predicate::str::contains(
+                "UserService (1 operations: GetUser)",
+            )),
+        );
+
+    assert_eq!(endpoint.hits(), 1);
+    assert_eq!(wsdl.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// a directory that redirects to itself with a trailing slash, which redirects right back, should
+/// be reported as a redirect loop and not scanned
+fn scanner_reports_slash_redirect_loop() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["admin".to_string()], "wordlist")?;
+
+    let without_slash = srv.mock(|when, then| {
+        when.method(GET).path("/admin");
+        then.status(301).header("Location", &srv.url("/admin/"));
+    });
+
+    let with_slash = srv.mock(|when, then| {
+        when.method(GET).path("/admin/");
+        then.status(301).header("Location", &srv.url("/admin"));
+    });
+
+    let beneath = srv.mock(|when, then| {
+        when.method(GET)
+            .path_matches(Regex::new("^/admin/.+").unwrap());
+        then.status(404);
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--smart-slash")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("not scanned (redirect loop)").and(
+            predicate::str::contains(format!("{} -> {}", srv.url("/admin"), srv.url("/admin/"))),
+        ));
+
+    assert_eq!(without_slash.hits(), 1);
+    assert_eq!(with_slash.hits(), 1);
+    assert_eq!(beneath.hits(), 0);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// a directory listing that was recursed into should be parsed, but not reported a second time;
+/// directories it lists should be reported once
+fn scanner_parse_listings_reports_directories_once() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["files".to_string()], "wordlist")?;
+
+    let mock_redirect = srv.mock(|when, then| {
+        when.method(GET).path("/files");
+        then.status(301).header("Location", &srv.url("/files/"));
+    });
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/files/");
+        then.status(200).body(
+            r#"<title>Index of /files</title><a href="/">Parent Directory</a>
+<a href="notes.txt">notes.txt</a><a href="old/">old/</a>"#,
+        );
+    });
+
+    let mock_old = srv.mock(|when, then| {
+        when.method(GET).path("/files/old/");
+        then.status(200).body(
+            r#"<title>Index of /files/old</title><a href="/files/">Parent Directory</a>
+<a href="backup.zip">backup.zip</a>"#,
+        );
+    });
+
+    let mock_notes = srv.mock(|when, then| {
+        when.method(GET).path("/files/notes.txt");
+        then.status(200).body("some notes");
+    });
+
+    let mock_backup = srv.mock(|when, then| {
+        when.method(GET).path("/files/old/backup.zip");
+        then.status(200).body("PK");
+    });
+
+    let output = Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--parse-listings")
+        .output()?;
+
+    let contents = String::from_utf8_lossy(&output.stdout);
+    let lines_with = |url: &str| {
+        contents
+            .lines()
+            .filter(|line| line.split_whitespace().any(|field| field == url))
+            .count()
+    };
+
+    assert_eq!(lines_with(&srv.url("/files/")), 0);
+    assert_eq!(lines_with(&srv.url("/files/old/")), 1);
+    assert_eq!(lines_with(&srv.url("/files/notes.txt")), 1);
+    assert_eq!(lines_with(&srv.url("/files/old/backup.zip")), 1);
+    assert_eq!(mock_redirect.hits(), 1);
+    assert_eq!(mock.hits(), 1);
+    assert_eq!(mock_old.hits(), 2);
+    assert_eq!(mock_notes.hits(), 1);
+    assert_eq!(mock_backup.hits(), 1);
+
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// a discovered .git directory is only followed up on with --follow-disclosures
+fn scanner_follow_disclosures_reports_retrievable_paths() -> Result<(), Box<dyn std::error::Error>>
+{
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&[".git/HEAD".to_string()], "wordlist")?;
+
+    srv.mock(|when, then| {
+        when.method(GET).path("/.git/HEAD");
+        then.status(200).body("ref: refs/heads/main");
+    });
+
+    let config_mock = srv.mock(|when, then| {
+        when.method(GET).path("/.git/config");
+        then.status(200)
+            .body("[core]\n\trepositoryformatversion = 0\n");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/.git/config").not());
+
+    assert_eq!(config_mock.hits(), 0);
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--follow-disclosures")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/.git/config"));
+
+    assert_eq!(config_mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --pin-sha256 that doesn't match the server's certificate should keep every request from
+/// reaching the server; a matching one should let the scan through
+fn scanner_pin_mismatch_sends_no_requests() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = utils::TlsServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let ca_cert = tmp_dir.path().join("ca.pem");
+    std::fs::write(&ca_cert, srv.certificate())?;
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--ca-cert")
+        .arg(&ca_cert)
+        .arg("--pin-sha256")
+        .arg("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=")
+        .unwrap();
+
+    assert_eq!(srv.hits(), 0);
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--ca-cert")
+        .arg(&ca_cert)
+        .arg("--pin-sha256")
+        .arg(srv.pin())
+        .unwrap();
+
+    assert!(srv.hits() > 0);
+
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --auto-pause should count 429 responses as trouble, and pause once too many come back
+fn scanner_auto_pause_counts_throttled_responses() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let words: Vec<String> = (0..20).map(|i| format!("word{}", i)).collect();
+    let (tmp_dir, file) = setup_tmp_directory(&words, "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET);
+        then.status(429);
+    });
+
+    let cmd = Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--auto-pause")
+        .arg("50")
+        .arg("--auto-pause-window")
+        .arg("4")
+        .arg("--threads")
+        .arg("1")
+        .unwrap();
+
+    cmd.assert().success().stderr(predicate::str::contains(
+        "scans were paused automatically: 4 of the last 4 responses were blocked, throttled, or errored",
+    ));
+
+    assert!(mock.hits() > 0);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --group-similar should collapse all but the first response of the group into a summary, while
+/// still writing every response to -o
+fn scanner_group_similar_writes_collapsed_responses() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let words = ["a", "bb", "ccc"].map(String::from);
+    let (tmp_dir, file) = setup_tmp_directory(&words, "wordlist")?;
+
+    let mocks: Vec<_> = words
+        .iter()
+        .map(|word| {
+            srv.mock(|when, then| {
+                when.method(GET).path(format!("/{}", word));
+                then.status(200)
+                    .body(format!("<h1>Not Here</h1>\nyou asked for /{}", word));
+            })
+        })
+        .collect();
+
+    let outfile = tmp_dir.path().join("output");
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--group-similar")
+        .arg("1")
+        .arg("--threads")
+        .arg("1")
+        .arg("-o")
+        .arg(outfile.as_os_str())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "(found 3 similar responses, 2 collapsed)",
+        ));
+
+    let contents = std::fs::read_to_string(outfile)?;
+
+    for word in &words {
+        assert!(contents.contains(&srv.url(&format!("/{}\n", word))));
+    }
+
+    for mock in mocks {
+        assert_eq!(mock.hits(), 1);
+    }
+
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// --request-limit 10 with 500 words and 50 threads; expect no more than 10 of the words to be
+/// requested, however many requests are in flight when the limit is reached
+fn scanner_request_limit_is_not_exceeded() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let words: Vec<String> = (0..500).map(|i| format!("word{}", i)).collect();
+    let (tmp_dir, file) = setup_tmp_directory(&words, "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET)
+            .path_matches(Regex::new("^/word[0-9]+$").unwrap());
+        then.status(404);
+    });
+
+    assert_cmd::Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .current_dir(&tmp_dir)
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .arg("--dont-filter")
+        .arg("--threads")
+        .arg("50")
+        .arg("--request-limit")
+        .arg("10")
+        .timeout(time::Duration::from_secs(30))
+        .output()?;
+
+    assert!(
+        (1..=10).contains(&mock.hits()),
+        "sent {} requests",
+        mock.hits()
+    );
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
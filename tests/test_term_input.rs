@@ -0,0 +1,69 @@
+//! the terminal input handler puts windows consoles into raw mode while a scan runs; these make
+//! sure scans still run to completion when there's no console to put into raw mode
+#![cfg(target_os = "windows")]
+mod utils;
+use assert_cmd::{prelude::*, Command};
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use predicates::prelude::*;
+use std::{process::Stdio, time::Duration};
+use utils::{setup_tmp_directory, teardown_tmp_directory};
+
+#[test]
+/// scan with stdin redirected from nothing, expect the scan to complete normally
+fn term_input_scan_completes_without_console() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200).body("this is a test");
+    });
+
+    let mut cmd = std::process::Command::cargo_bin("feroxbuster").unwrap();
+    cmd.arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .stdin(Stdio::null());
+
+    Command::from_std(cmd)
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/LICENSE").and(predicate::str::contains("200")));
+
+    assert_eq!(mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}
+
+#[test]
+/// pipe a newline into a scan; piped input isn't a key press, expect the scan to complete without
+/// pausing
+fn term_input_piped_enter_does_not_pause_scan() -> Result<(), Box<dyn std::error::Error>> {
+    let srv = MockServer::start();
+    let (tmp_dir, file) = setup_tmp_directory(&["LICENSE".to_string()], "wordlist")?;
+
+    let mock = srv.mock(|when, then| {
+        when.method(GET).path("/LICENSE");
+        then.status(200).body("this is a test");
+    });
+
+    Command::cargo_bin("feroxbuster")
+        .unwrap()
+        .arg("--url")
+        .arg(srv.url("/"))
+        .arg("--wordlist")
+        .arg(file.as_os_str())
+        .write_stdin("\r\n")
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/LICENSE").and(predicate::str::contains("200")))
+        .stderr(predicate::str::contains("Scan Cancel Menu").not());
+
+    assert_eq!(mock.hits(), 1);
+    teardown_tmp_directory(tmp_dir);
+    Ok(())
+}